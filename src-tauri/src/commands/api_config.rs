@@ -1,7 +1,14 @@
 use crate::db::pool::DbPool;
-use crate::models::api_config::{ApiConfig, CreateApiConfigInput, UpdateApiConfigInput};
-use crate::models::error::AppResult;
+use crate::models::api_config::{
+    ApiConfig, CreateApiConfigInput, GroupModelOverrides, UpdateApiConfigInput,
+};
+use crate::models::environment_variable::EnvSnippetImportResult;
+use crate::models::error::{AppError, AppResult};
+use crate::services::env_snippet::EnvSnippetService;
 use crate::services::ApiConfigService;
+use crate::services::backend_models::{BackendModelList, BackendModelsService};
+use crate::services::server_port_migration::{DeprecatedServerPortUsage, ServerPortMigrationService};
+use crate::utils::server_url::NormalizedServerUrl;
 use crate::commands::proxy_service::ProxyServiceState;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -22,6 +29,27 @@ pub fn create_api_config(
     pool.with_connection(|conn| ApiConfigService::create_config(conn, &input))
 }
 
+/// 从 `export` / `.env` 环境变量片段创建 API 配置
+///
+/// # 参数
+/// - `pool`: 数据库连接池
+/// - `text`: 供应商提供的环境变量片段
+/// - `group_id`: 可选的目标分组ID
+///
+/// # 说明
+/// 识别 ANTHROPIC_BASE_URL、ANTHROPIC_AUTH_TOKEN/ANTHROPIC_API_KEY 以及模型相关变量，
+/// 返回创建的配置与已解析/缺失的字段
+#[tauri::command]
+pub fn create_config_from_env_snippet(
+    text: String,
+    group_id: Option<i64>,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<EnvSnippetImportResult> {
+    log::info!("从环境变量片段创建 API 配置 (group_id: {:?})", group_id);
+
+    pool.with_connection(|conn| EnvSnippetService::create_config(conn, &text, group_id))
+}
+
 /// 列出所有 API 配置
 ///
 /// # 参数
@@ -70,6 +98,11 @@ pub async fn update_api_config(
     // 执行更新
     let updated_config = pool.with_connection(|conn| ApiConfigService::update_config(conn, &input))?;
 
+    // 地址、密钥或供应商类型变更后，缓存的模型列表不再可靠
+    if input.server_url.is_some() || input.api_key.is_some() || input.provider_type.is_some() {
+        BackendModelsService::invalidate(input.id);
+    }
+
     // 检查是否需要触发代理状态刷新
     // 如果更新的是 is_available 字段，并且这个配置是当前激活的配置
     if input.is_available.is_some() {
@@ -217,6 +250,66 @@ pub async fn quick_test_config_url(url: String) -> AppResult<EndpointTestResult>
     })
 }
 
+/// 查询后端实际支持的模型列表
+///
+/// 按配置的供应商类型调用模型列表接口（Claude/OpenAI: `/v1/models`，Gemini: `/v1beta/models`），
+/// 结果按配置缓存 5 分钟，供 UI 在设置 haiku/sonnet/opus 模型时提供下拉选项。
+///
+/// # 参数
+/// - `config_id`: 配置ID
+/// - `force_refresh`: 是否忽略缓存重新查询
+#[tauri::command]
+pub async fn fetch_backend_models(
+    config_id: i64,
+    force_refresh: Option<bool>,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<BackendModelList> {
+    log::info!("查询后端模型列表: config_id {}", config_id);
+
+    BackendModelsService::fetch_models(pool.inner(), config_id, force_refresh.unwrap_or(false)).await
+}
+
+/// 校验并规范化服务器地址
+///
+/// 在用户输入时调用，返回规范化后的地址及需要提示的可疑写法
+/// （如路径前缀以 /v1 结尾、使用明文 HTTP 等）。
+/// 创建/更新配置时会自动使用相同规则规范化 server_url。
+///
+/// # 参数
+/// - `url`: 用户输入的服务器地址
+#[tauri::command]
+pub fn normalize_server_url(url: String) -> AppResult<NormalizedServerUrl> {
+    log::debug!("规范化服务器地址: {}", url);
+
+    crate::utils::server_url::normalize_server_url(&url).map_err(|e| AppError::ValidationError {
+        field: "server_url".to_string(),
+        message: e,
+    })
+}
+
+/// 检测已弃用的 server_port 会影响行为的配置
+///
+/// server_port 不参与转发，端口只由 server_url 决定。返回 server_port 与实际连接端口不一致的配置：
+/// - `foldable`: server_url 未指定端口，可将 server_port 并入 server_url
+/// - `ignored`: server_url 已指定其它端口，server_port 被忽略
+///
+/// # 参数
+/// - `apply`: 为 true 时将可并入的端口写入 server_url，返回迁移后的剩余结果
+#[tauri::command]
+pub fn detect_deprecated_server_port(
+    apply: Option<bool>,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<Vec<DeprecatedServerPortUsage>> {
+    log::info!("检测已弃用的 server_port (apply: {:?})", apply);
+
+    pool.with_connection(|conn| {
+        if apply.unwrap_or(false) {
+            ServerPortMigrationService::fold_ports(conn)?;
+        }
+        ServerPortMigrationService::detect(conn)
+    })
+}
+
 /// 设置配置的启用状态
 ///
 /// # 参数
@@ -243,37 +336,126 @@ pub async fn set_config_enabled(
 
     // 如果是停用操作，检查是否需要触发自动切换
     if !enabled {
-        let proxy_service = proxy_state.service();
-        let current_status = proxy_service.get_status().await?;
+        switch_away_from_config(&updated_config, &pool, &proxy_state).await?;
+    }
 
-        // 如果停用的是当前激活的配置
-        if current_status.active_config_id == Some(config_id) {
-            log::warn!(
-                "正在停用当前激活的配置: {} (ID: {})，尝试切换到下一个可用配置",
-                updated_config.name,
-                config_id
-            );
+    Ok(updated_config)
+}
 
-            // 触发自动切换
-            if let Some(group_id) = current_status.active_group_id {
-                // 获取下一个可用配置
-                if let Ok(next_config) = pool.with_connection(|conn| {
-                    ApiConfigService::list_enabled_available_configs(conn, group_id)
-                }) {
-                    if let Some(next) = next_config.first() {
-                        log::info!("自动切换到配置: {} (ID: {})", next.name, next.id);
-                        // 更新代理服务使用新配置
-                        let _ = proxy_service.switch_config(next.id).await;
-                    } else {
-                        log::warn!("没有可用的备用配置，代理服务可能无法正常工作");
-                    }
+/// 批量设置分组内所有配置的模型覆盖
+///
+/// # 参数
+/// - `group_id`: 分组ID
+/// - `overrides`: 模型覆盖（None 保持不变，空字符串清除），可选只填充未设置的字段
+#[tauri::command]
+pub fn set_group_model_overrides(
+    group_id: i64,
+    overrides: GroupModelOverrides,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<Vec<ApiConfig>> {
+    log::info!("批量设置分组 {} 的模型覆盖", group_id);
+
+    pool.with_connection(|conn| ApiConfigService::set_group_model_overrides(conn, group_id, &overrides))
+}
+
+/// 定时停用配置直到指定时间
+///
+/// # 参数
+/// - `config_id`: 配置ID
+/// - `until`: 停用截止时间（RFC3339），到期后自动恢复
+///
+/// # 说明
+/// 如果定时停用的是当前激活的配置，会尝试自动切换到下一个可用配置
+#[tauri::command]
+pub async fn set_config_disabled_until(
+    config_id: i64,
+    until: String,
+    pool: State<'_, Arc<DbPool>>,
+    proxy_state: State<'_, ProxyServiceState>,
+) -> AppResult<ApiConfig> {
+    log::info!("定时停用配置: ID {} 直到 {}", config_id, until);
+
+    let updated_config = pool.with_connection(|conn| {
+        ApiConfigService::set_disabled_until(conn, config_id, &until)
+    })?;
+
+    switch_away_from_config(&updated_config, &pool, &proxy_state).await?;
+
+    Ok(updated_config)
+}
+
+/// 清除配置的定时停用，立即恢复可用
+#[tauri::command]
+pub fn clear_config_disabled_until(
+    config_id: i64,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<ApiConfig> {
+    log::info!("清除定时停用: ID {}", config_id);
+
+    pool.with_connection(|conn| ApiConfigService::clear_disabled_until(conn, config_id))
+}
+
+/// 手动重置配置的失败与可用状态
+///
+/// # 说明
+/// 清零数据库中的连续失败次数并恢复可用，同时清除重试管理器中的失败计数，
+/// 避免旧的失败记录在下一次请求失败时立即触发自动切换
+#[tauri::command]
+pub fn reset_config_state(
+    config_id: i64,
+    pool: State<'_, Arc<DbPool>>,
+    proxy_state: State<'_, ProxyServiceState>,
+) -> AppResult<ApiConfig> {
+    log::info!("手动重置配置状态: ID {}", config_id);
+
+    let config = pool.with_connection(|conn| ApiConfigService::reset_config_state(conn, config_id))?;
+
+    proxy_state
+        .service()
+        .server()
+        .auto_switch_service()
+        .reset_failure_counter(config_id);
+
+    Ok(config)
+}
+
+/// 当前激活配置被停用时，切换到分组内下一个可用配置
+async fn switch_away_from_config(
+    config: &ApiConfig,
+    pool: &State<'_, Arc<DbPool>>,
+    proxy_state: &State<'_, ProxyServiceState>,
+) -> AppResult<()> {
+    let config_id = config.id;
+    let proxy_service = proxy_state.service();
+    let current_status = proxy_service.get_status().await?;
+
+    // 如果停用的是当前激活的配置
+    if current_status.active_config_id == Some(config_id) {
+        log::warn!(
+            "正在停用当前激活的配置: {} (ID: {})，尝试切换到下一个可用配置",
+            config.name,
+            config_id
+        );
+
+        // 触发自动切换
+        if let Some(group_id) = current_status.active_group_id {
+            // 获取下一个可用配置
+            if let Ok(next_config) = pool.with_connection(|conn| {
+                ApiConfigService::list_enabled_available_configs(conn, group_id)
+            }) {
+                if let Some(next) = next_config.first() {
+                    log::info!("自动切换到配置: {} (ID: {})", next.name, next.id);
+                    // 更新代理服务使用新配置
+                    let _ = proxy_service.switch_config(next.id).await;
+                } else {
+                    log::warn!("没有可用的备用配置，代理服务可能无法正常工作");
                 }
             }
-
-            // 触发状态刷新以通知UI更新
-            let _ = proxy_service.refresh_status().await;
         }
+
+        // 触发状态刷新以通知UI更新
+        let _ = proxy_service.refresh_status().await;
     }
 
-    Ok(updated_config)
+    Ok(())
 }
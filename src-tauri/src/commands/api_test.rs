@@ -5,12 +5,23 @@
  * Commands:
  * - test_api_config: Test single configuration
  * - test_group_configs: Test all configurations in a group
+ * - test_stale_configs: Test only configurations not tested recently
+ * - test_config_via_proxy: Test a configuration through the proxy forwarding pipeline
+ * - get_config_timing_breakdown: Get last measured DNS/connect/TLS/TTFB split
+ * - compare_test_runs: Compare two time windows and flag regressions
+ * - validate_provider_preset: Probe a provider preset's endpoint with a sample key
+ * - run_conversion_self_test: Run the bundled corpus through every format converter
+ * - diagnose_last_failure: Explain a configuration's most recent failure and suggest next steps
  */
 
 use crate::db::DbPool;
 use crate::models::error::AppResult;
-use crate::models::test_result::TestResult;
+use crate::models::provider_preset::PresetValidationResult;
+use crate::models::test_result::{StaleConfigTestResult, TestResult, TimingBreakdown};
 use crate::services::api_test::ApiTestService;
+use crate::services::conversion_self_test::{ConversionSelfTestReport, ConversionSelfTestService};
+use crate::services::failure_diagnosis::{FailureDiagnosis, FailureDiagnosisService};
+use crate::services::test_comparison::{TestComparisonService, TestRunComparison, TimeWindow};
 use std::sync::Arc;
 use tauri::{AppHandle, State};
 
@@ -34,6 +45,27 @@ pub async fn test_api_config(
     service.test_single_config(config_id).await
 }
 
+/// Test single API configuration through the proxy forwarding pipeline
+///
+/// Unlike `test_api_config`, the request goes through the same path building,
+/// header rewriting, field filtering and format conversion as live proxy traffic
+///
+/// # Arguments
+/// - `config_id`: API configuration ID
+///
+/// # Returns
+/// - TestResult with latency and status (not saved to test history)
+#[tauri::command]
+pub async fn test_config_via_proxy(
+    config_id: i64,
+    db_pool: State<'_, Arc<DbPool>>,
+) -> AppResult<TestResult> {
+    log::info!("Command: test_config_via_proxy (config_id: {})", config_id);
+
+    let service = ApiTestService::new(db_pool.inner().clone());
+    service.test_config_via_proxy(config_id).await
+}
+
 /// Test all configurations in a group
 ///
 /// Tests configurations in parallel for better performance
@@ -56,6 +88,29 @@ pub async fn test_group_configs(
     service.test_group_configs(group_id).await
 }
 
+/// Test only configurations whose last test is older than the threshold
+///
+/// Configurations that were never tested are always included.
+/// Tests run with a concurrency cap so a sweep doesn't hammer every backend at once
+///
+/// # Arguments
+/// - `max_age_minutes`: Minimum age of the last test for a configuration to be retested
+///
+/// # Returns
+/// - Tested and skipped configuration IDs, plus the new test results
+#[tauri::command]
+pub async fn test_stale_configs(
+    max_age_minutes: i64,
+    app_handle: AppHandle,
+    db_pool: State<'_, Arc<DbPool>>,
+) -> AppResult<StaleConfigTestResult> {
+    log::info!("Command: test_stale_configs (max_age_minutes: {})", max_age_minutes);
+
+    let service = ApiTestService::new(db_pool.inner().clone());
+    service.set_app_handle(app_handle).await;
+    service.test_stale_configs(max_age_minutes).await
+}
+
 /// Get recent test results for a configuration
 ///
 /// # Arguments
@@ -76,6 +131,105 @@ pub fn get_test_results(
     service.get_recent_test_results(config_id, limit.unwrap_or(10))
 }
 
+/// Get the last measured connection timing breakdown for a configuration
+///
+/// # Arguments
+/// - `config_id`: API configuration ID
+///
+/// # Returns
+/// - DNS / TCP connect / TLS handshake / TTFB split, or None if never measured
+#[tauri::command]
+pub fn get_config_timing_breakdown(
+    config_id: i64,
+    db_pool: State<'_, Arc<DbPool>>,
+) -> AppResult<Option<TimingBreakdown>> {
+    log::debug!("Command: get_config_timing_breakdown (config_id: {})", config_id);
+
+    let service = ApiTestService::new(db_pool.inner().clone());
+    service.get_latest_timing_breakdown(config_id)
+}
+
+/// Compare a configuration's success rate and latency between two time windows
+///
+/// Uses both TestResult history and live ProxyRequestLog traffic
+///
+/// # Arguments
+/// - `config_id`: API configuration ID
+/// - `baseline_window`: Reference window (RFC3339 start/end)
+/// - `current_window`: Window to check for regressions
+///
+/// # Returns
+/// - Per-window stats, deltas and statistically significant regressions
+#[tauri::command]
+pub fn compare_test_runs(
+    config_id: i64,
+    baseline_window: TimeWindow,
+    current_window: TimeWindow,
+    db_pool: State<'_, Arc<DbPool>>,
+) -> AppResult<TestRunComparison> {
+    log::debug!("Command: compare_test_runs (config_id: {})", config_id);
+
+    TestComparisonService::compare(&db_pool, config_id, baseline_window, current_window)
+}
+
+/// Validate a provider preset by probing its endpoint with a sample key
+///
+/// Normalizes the preset's server URL, checks reachability and runs the same
+/// request as `test_api_config`. Results are not stored in test history.
+///
+/// # Arguments
+/// - `preset_id`: Provider preset ID
+/// - `sample_key`: API key used for the probe
+///
+/// # Returns
+/// - Reachability, authentication outcome and whether the preset is usable
+#[tauri::command]
+pub async fn validate_provider_preset(
+    preset_id: String,
+    sample_key: String,
+    db_pool: State<'_, Arc<DbPool>>,
+) -> AppResult<PresetValidationResult> {
+    log::debug!("Command: validate_provider_preset (preset_id: {})", preset_id);
+
+    let service = ApiTestService::new(db_pool.inner().clone());
+    service.validate_provider_preset(&preset_id, &sample_key).await
+}
+
+/// Run the format conversion self-test
+///
+/// Feeds a bundled corpus of representative requests, responses and stream
+/// chunks through every converter and checks the results with the request
+/// validators and the `ConversionMatrix`. Runs locally without contacting
+/// any backend.
+///
+/// # Returns
+/// - Pass/fail matrix of client format × backend provider with per-sample checks
+#[tauri::command]
+pub async fn run_conversion_self_test() -> AppResult<ConversionSelfTestReport> {
+    log::debug!("Command: run_conversion_self_test");
+    Ok(ConversionSelfTestService::run())
+}
+
+/// Diagnose a configuration's most recent failure
+///
+/// Picks the latest failed proxy request or connection test, classifies the
+/// error and maps it to a plain-language diagnosis with remediation steps.
+///
+/// # Arguments
+/// - `config_id`: API configuration ID
+///
+/// # Returns
+/// - Diagnosis of the latest failure, or None if the configuration has no failures
+#[tauri::command]
+pub fn diagnose_last_failure(
+    config_id: i64,
+    db_pool: State<'_, Arc<DbPool>>,
+) -> AppResult<Option<FailureDiagnosis>> {
+    log::debug!("Command: diagnose_last_failure (config_id: {})", config_id);
+
+    FailureDiagnosisService::diagnose_last_failure(&db_pool, config_id)
+}
+
 #[cfg(all(test, feature = "old_tests"))]
 mod tests {
     use super::*;
@@ -1,4 +1,8 @@
+use crate::services::app_rollback::{self, AppRollbackService, RollbackArtifact};
+use crate::services::app_updater::DownloadProgress;
 use crate::services::{AppUpdater, AppVersionInfo};
+use std::path::Path;
+use tauri::{AppHandle, Emitter, Window};
 
 /// 检查应用更新
 #[tauri::command]
@@ -22,17 +26,91 @@ pub fn get_app_version() -> String {
 }
 
 /// 下载更新包
+///
+/// 流式下载，进度通过 `app-update-progress` 事件推送；下载完成后校验 SHA-256，校验通过才返回成功。
+/// 未传入 `sha256` 时使用最新 Release 中该资源的摘要，无法获取摘要时拒绝下载。
+/// 校验通过的更新包会归档一份，下次更新后即可回退到该版本 (版本取自 `version` 或下载链接)；
+/// 当前运行版本尚无归档时 (例如首次更新)，下载完成后在后台从该版本的 Release 补充归档
 #[tauri::command]
-pub async fn download_app_update(url: String, save_path: String) -> Result<(), String> {
+pub async fn download_app_update(
+    url: String,
+    save_path: String,
+    sha256: Option<String>,
+    version: Option<String>,
+    window: Window,
+) -> Result<(), String> {
     let current_version = env!("CARGO_PKG_VERSION").to_string();
 
     let updater = AppUpdater::new(
         "sunjackson".to_string(),
         "claude-code-proxy".to_string(),
-        current_version,
+        current_version.clone(),
     );
 
-    updater.download_update(&url, &save_path).await
+    let expected_sha256 = match sha256.filter(|s| !s.trim().is_empty()) {
+        Some(sha256) => sha256,
+        None => updater
+            .find_asset_sha256(&url)
+            .await?
+            .ok_or_else(|| "无法获取安装包的 SHA-256 校验值，已取消下载".to_string())?,
+    };
+
+    updater
+        .download_update(&url, &save_path, &expected_sha256, |progress: DownloadProgress| {
+            let _ = window.emit("app-update-progress", &progress);
+        })
+        .await?;
+
+    // 归档失败不影响本次更新
+    let service = match AppRollbackService::with_default_dir() {
+        Ok(service) => service,
+        Err(e) => {
+            log::warn!("无法打开更新包归档目录: {}", e);
+            return Ok(());
+        }
+    };
+    match version.or_else(|| app_rollback::version_from_download_url(&url)) {
+        Some(version) => {
+            if let Err(e) = service.archive(Path::new(&save_path), &version, &expected_sha256) {
+                log::warn!("归档更新包失败: {}", e);
+            }
+        }
+        None => log::warn!("无法确定更新包版本，未归档: {}", url),
+    }
+
+    tokio::spawn(async move {
+        if let Err(e) = service.archive_current_release(&updater, &current_version).await {
+            log::warn!("归档当前版本 {} 失败，将无法回退到该版本: {}", current_version, e);
+        }
+    });
+    Ok(())
+}
+
+/// 列出可回退的旧版本 (版本低于当前版本且归档的更新包校验通过)
+#[tauri::command]
+pub fn list_available_rollbacks() -> Result<Vec<RollbackArtifact>, String> {
+    let service = AppRollbackService::with_default_dir()?;
+    Ok(service.list_available(env!("CARGO_PKG_VERSION")))
+}
+
+/// 回退到之前的应用版本
+///
+/// 再次校验归档的更新包后启动它 (安装包或 AppImage)，随后退出当前应用。
+/// 未指定 `version` 时回退到可用的最新旧版本
+#[tauri::command]
+pub async fn rollback_app_update(version: Option<String>, app: AppHandle) -> Result<RollbackArtifact, String> {
+    let service = AppRollbackService::with_default_dir()?;
+    let artifact = service.find_rollback(env!("CARGO_PKG_VERSION"), version.as_deref())?;
+    log::info!("回退应用版本: {} -> {}", env!("CARGO_PKG_VERSION"), artifact.version);
+
+    app_rollback::launch_artifact(&artifact)?;
+
+    // 稍后退出，先将结果返回前端
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        app.exit(0);
+    });
+    Ok(artifact)
 }
 
 /// 打开发布页面
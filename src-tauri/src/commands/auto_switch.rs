@@ -5,16 +5,47 @@
  * Commands:
  * - toggle_auto_switch: 启用/禁用分组自动切换
  * - get_switch_logs: 获取切换日志列表
+ * - get_recent_switch_events: 获取最近的切换事件 (与 auto-switch-triggered 事件内容一致)
+ * - export_switch_logs: 导出切换日志时间线 (CSV/JSON)
+ * - inject_config_failure: 注入模拟故障（仅用于测试）
+ * - get_retry_state / reset_retry_state: 查看/重置配置的重试与退避状态
  */
 
+use crate::commands::proxy_service::ProxyServiceState;
 use crate::db::DbPool;
-use crate::models::error::AppResult;
-use crate::models::switch_log::SwitchLogDetail;
+use crate::models::error::{AppError, AppResult};
+use crate::models::error_classifier::ErrorRecoverability;
+use crate::models::retry_strategy::ConfigRetryState;
+use crate::models::switch_log::{
+    ErrorType, SwitchLogDetail, SwitchLogExportFormat, SwitchLogExportResult, SwitchLogTimeWindow,
+};
+use crate::services::api_config::ApiConfigService;
 use crate::services::auto_switch::AutoSwitchService;
+use crate::services::error_classifier::ErrorClassifier;
 use crate::utils::time::now_rfc3339;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufWriter;
 use std::sync::Arc;
 use tauri::State;
 
+/// 模拟故障注入结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InjectFailureResult {
+    /// 注入故障的配置 ID
+    pub config_id: i64,
+    /// 配置所属分组 ID
+    pub group_id: i64,
+    /// 送入自动切换流程的合成错误信息
+    pub error_message: String,
+    /// 错误分类结果
+    pub error_type: ErrorType,
+    /// 可恢复性判断结果
+    pub recoverability: ErrorRecoverability,
+    /// 切换到的新配置 ID（None 表示进入重试或没有可用配置）
+    pub switched_to_config_id: Option<i64>,
+}
+
 /// 启用/禁用分组的自动切换功能
 ///
 /// # Arguments
@@ -98,6 +129,72 @@ pub fn get_switch_logs(
     service.get_switch_logs(group_id, limit.unwrap_or(50), offset.unwrap_or(0))
 }
 
+/// 获取最近的切换事件
+///
+/// 返回内容与 `auto-switch-triggered` 事件一致，前端可在订阅事件前先拉取最近历史
+///
+/// # Arguments
+/// - `limit`: 返回数量(可选,默认50,最大500)
+///
+/// # Returns
+/// - Vec<SwitchLogDetail>: 按时间倒序的切换事件
+#[tauri::command]
+pub fn get_recent_switch_events(
+    limit: Option<i32>,
+    db_pool: State<'_, Arc<DbPool>>,
+) -> AppResult<Vec<SwitchLogDetail>> {
+    log::debug!("Command: get_recent_switch_events (limit: {:?})", limit);
+
+    let service = AutoSwitchService::new(db_pool.inner().clone());
+    service.get_recent_switch_events(limit.unwrap_or(50))
+}
+
+/// 导出切换日志时间线
+///
+/// 不分页，按时间升序逐行写入文件，每行包含距上一次切换的间隔，
+/// 便于识别频繁来回切换的时段
+///
+/// # Arguments
+/// - `file_path`: 导出文件路径
+/// - `format`: 导出格式 (csv / json)
+/// - `time_window`: 时间窗口(可选,缺省导出全部)
+/// - `group_id`: 分组 ID(可选,用于筛选)
+///
+/// # Returns
+/// - SwitchLogExportResult: 导出文件路径与记录数
+#[tauri::command]
+pub fn export_switch_logs(
+    file_path: String,
+    format: SwitchLogExportFormat,
+    time_window: Option<SwitchLogTimeWindow>,
+    group_id: Option<i64>,
+    db_pool: State<'_, Arc<DbPool>>,
+) -> AppResult<SwitchLogExportResult> {
+    log::info!(
+        "Command: export_switch_logs (file_path: {}, format: {:?}, group_id: {:?})",
+        file_path,
+        format,
+        group_id
+    );
+
+    let time_window = time_window.unwrap_or_default();
+    let file = File::create(&file_path).map_err(|e| AppError::IoError {
+        message: format!("创建导出文件失败: {}", e),
+    })?;
+    let mut writer = BufWriter::new(file);
+
+    let service = AutoSwitchService::new(db_pool.inner().clone());
+    let row_count = service.export_switch_logs(group_id, &time_window, format, &mut writer)?;
+
+    log::info!("已导出 {} 条切换日志到 {}", row_count, file_path);
+
+    Ok(SwitchLogExportResult {
+        file_path,
+        format,
+        row_count,
+    })
+}
+
 /// 清空切换日志
 ///
 /// # Arguments
@@ -116,6 +213,89 @@ pub fn clear_switch_logs(
     service.clear_switch_logs(group_id)
 }
 
+/// 注入模拟故障（仅用于测试）
+///
+/// 测试专用命令：不会向任何后端发送真实流量，只是把一次合成的失败送入
+/// 代理服务共享的 `AutoSwitchService::handle_failure_with_retry`，
+/// 以便端到端观察切换过程、切换日志以及 UI/托盘的状态更新。
+///
+/// # Arguments
+/// - `config_id`: 模拟失败的配置 ID
+/// - `reason`: 错误类型（network/timeout/authentication/insufficient_balance/
+///   account_banned/rate_limit/server_error）或任意错误信息
+///
+/// # Returns
+/// - InjectFailureResult: 分类结果及是否发生切换
+#[tauri::command]
+pub async fn inject_config_failure(
+    config_id: i64,
+    reason: String,
+    db_pool: State<'_, Arc<DbPool>>,
+    proxy_state: State<'_, ProxyServiceState>,
+) -> AppResult<InjectFailureResult> {
+    log::warn!(
+        "Command: inject_config_failure [TESTING ONLY] (config_id: {}, reason: {})",
+        config_id,
+        reason
+    );
+
+    let config = db_pool.with_connection(|conn| ApiConfigService::get_config_by_id(conn, config_id))?;
+    let group_id = config.group_id.ok_or_else(|| AppError::ValidationError {
+        field: "config_id".to_string(),
+        message: format!("配置 {} 不属于任何分组,无法模拟自动切换", config_id),
+    })?;
+
+    // 使用代理服务共享的自动切换服务,确保事件推送和切换回调与真实故障一致
+    let auto_switch = proxy_state.service().server().auto_switch_service();
+    let (error_message, switched_to_config_id) =
+        auto_switch.inject_failure(config_id, group_id, &reason).await?;
+
+    let (error_type, recoverability) = ErrorClassifier::new().classify(&error_message);
+
+    Ok(InjectFailureResult {
+        config_id,
+        group_id,
+        error_message,
+        error_type,
+        recoverability,
+        switched_to_config_id,
+    })
+}
+
+/// 获取分组内每个配置的重试状态
+///
+/// 返回当前连续重试次数、分组策略的最大重试次数以及下一次允许重试的时间,
+/// 重试次数达到最大值后下一次失败会切换到下一个配置
+///
+/// # Arguments
+/// - `group_id`: 分组 ID
+#[tauri::command]
+pub async fn get_retry_state(
+    group_id: i64,
+    proxy_state: State<'_, ProxyServiceState>,
+) -> AppResult<Vec<ConfigRetryState>> {
+    log::debug!("Command: get_retry_state (group_id: {})", group_id);
+
+    proxy_state.service().server().auto_switch_service().get_retry_state(group_id)
+}
+
+/// 重置配置的重试状态
+///
+/// 清零重试次数并解除退避,用于修复后端后手动恢复卡在退避中的配置
+///
+/// # Arguments
+/// - `config_id`: 配置 ID
+#[tauri::command]
+pub async fn reset_retry_state(
+    config_id: i64,
+    proxy_state: State<'_, ProxyServiceState>,
+) -> AppResult<()> {
+    log::info!("Command: reset_retry_state (config_id: {})", config_id);
+
+    proxy_state.service().server().auto_switch_service().reset_retry_state(config_id);
+    Ok(())
+}
+
 #[cfg(all(test, feature = "old_tests"))]
 mod tests {
     use super::*;
@@ -6,14 +6,16 @@
  * - query_balance: Query balance for a single configuration
  * - query_all_balances: Query balance for all auto-enabled configurations
  * - get_all_balance_info: Get all balance info from database
+ * - start_balance_watch / stop_balance_watch: Poll a single config's balance in real time
  */
 
 use crate::db::DbPool;
 use crate::models::balance::BalanceInfo;
 use crate::models::error::AppResult;
+use crate::services::balance_watch::{BalanceWatchManager, BALANCE_UPDATED_EVENT};
 use crate::services::BalanceService;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 
 /// Query balance for a single configuration
 ///
@@ -65,6 +67,48 @@ pub fn get_all_balance_info(
     service.get_all_balance_info()
 }
 
+/// Start polling a configuration's balance, emitting `balance-updated` events
+///
+/// Independent of the background balance scheduler. Only one watch runs per
+/// configuration; starting again replaces the running watch. Watches stop
+/// automatically after an hour or when the balance can no longer be queried.
+///
+/// # Arguments
+/// - `config_id`: API configuration ID
+/// - `interval_secs`: Polling interval in seconds (2-300)
+#[tauri::command]
+pub async fn start_balance_watch(
+    config_id: i64,
+    interval_secs: u64,
+    app: AppHandle,
+    watch_manager: State<'_, BalanceWatchManager>,
+) -> AppResult<()> {
+    log::info!(
+        "Command: start_balance_watch (config_id: {}, interval: {}s)",
+        config_id,
+        interval_secs
+    );
+
+    watch_manager.start(config_id, interval_secs, move |info| {
+        if let Err(e) = app.emit(BALANCE_UPDATED_EVENT, info) {
+            log::error!("Failed to emit {} event: {}", BALANCE_UPDATED_EVENT, e);
+        }
+    })
+}
+
+/// Stop polling a configuration's balance
+///
+/// # Returns
+/// - true if a watch was running
+#[tauri::command]
+pub fn stop_balance_watch(
+    config_id: i64,
+    watch_manager: State<'_, BalanceWatchManager>,
+) -> AppResult<bool> {
+    log::info!("Command: stop_balance_watch (config_id: {})", config_id);
+    Ok(watch_manager.stop(config_id))
+}
+
 #[cfg(all(test, feature = "old_tests"))]
 mod tests {
     use super::*;
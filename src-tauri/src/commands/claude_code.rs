@@ -1,7 +1,10 @@
 use crate::models::config_backup::ConfigBackup;
+use crate::commands::proxy_service::ProxyServiceState;
 use crate::models::error::{AppError, AppResult};
+use crate::models::proxy_status::ProxyStatus;
+use crate::services::claude_config::{ClaudeIntegrationReport, PortReconcileReport};
 use crate::services::{BackupService, ClaudeConfigService, ProxyConfig};
-use crate::utils::paths;
+use crate::utils::paths::{self, ClaudeSettingsPaths};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -29,7 +32,7 @@ pub struct ClaudeCodePath {
 
 /// 检测 Claude Code 配置路径
 ///
-/// 跨平台检测 ~/.claude/settings.json 路径
+/// 跨平台检测当前生效的 settings.json 路径（默认 ~/.claude/settings.json）
 /// - Windows: %USERPROFILE%\.claude\settings.json
 /// - macOS: ~/.claude/settings.json
 /// - Linux: ~/.claude/settings.json
@@ -78,6 +81,20 @@ pub fn detect_claude_code_path() -> AppResult<ClaudeCodePath> {
     Ok(result)
 }
 
+/// 检测 Claude Code 配置文件的所有候选位置
+///
+/// 返回找到的候选位置、是否存在以及当前生效的位置和选择原因，
+/// 启用/禁用代理时写入的就是生效的配置文件
+#[tauri::command]
+pub fn detect_claude_code_settings_paths() -> AppResult<ClaudeSettingsPaths> {
+    log::info!("开始检测 Claude Code 配置文件候选位置");
+
+    let result = paths::detect_claude_code_settings_paths()?;
+
+    log::info!("Claude Code 生效的配置文件: {} ({})", result.active_path, result.reason);
+    Ok(result)
+}
+
 /// 列出所有 Claude Code 配置备份
 ///
 /// 返回备份列表,按时间倒序排列
@@ -289,6 +306,62 @@ pub fn get_claude_code_settings() -> AppResult<String> {
     Ok(content)
 }
 
+/// 检查 Claude Code 配置是否正确指向正在运行的代理
+///
+/// 比较 settings.json 中的 ANTHROPIC_BASE_URL / http.proxy / 代理相关环境变量
+/// 与代理实际监听的地址 (端口被占用时可能已自动递增)
+#[tauri::command]
+pub async fn verify_claude_code_integration(
+    state: tauri::State<'_, ProxyServiceState>,
+) -> AppResult<ClaudeIntegrationReport> {
+    log::info!("检查 Claude Code 与代理的集成配置");
+
+    let status = state.service().get_status().await?;
+    let proxy = ProxyConfig {
+        host: status.listen_host,
+        port: status.listen_port as u16,
+    };
+    let report =
+        ClaudeConfigService::verify_integration(&proxy, status.status == ProxyStatus::Running)?;
+
+    log::info!(
+        "集成检查完成: passed={}, 不匹配项 {} 个",
+        report.passed,
+        report.mismatches.len()
+    );
+    Ok(report)
+}
+
+/// 比对代理实际绑定端口与 Claude Code 配置中的端口
+///
+/// 代理因端口占用自动换用其它端口 (或原端口再次可用) 后，settings.json 中的端口可能已过期。
+/// 默认只返回检测结果与将要做的改写；`apply` 为 true 时改写为当前端口 (写入前自动备份)
+#[tauri::command]
+pub async fn reconcile_claude_code_port(
+    apply: Option<bool>,
+    state: tauri::State<'_, ProxyServiceState>,
+) -> AppResult<PortReconcileReport> {
+    let apply = apply.unwrap_or(false);
+    log::info!("比对 Claude Code 配置端口 (apply={})", apply);
+
+    let status = state.service().get_status().await?;
+    let proxy = ProxyConfig {
+        host: status.listen_host,
+        port: status.listen_port as u16,
+    };
+    let report = ClaudeConfigService::reconcile_port(&proxy, status.status == ProxyStatus::Running, apply)?;
+
+    if !report.changes.is_empty() {
+        log::info!(
+            "Claude Code 配置端口 {:?} 与代理端口 {} 不一致 (已改写: {})",
+            report.configured_port,
+            report.listen_port,
+            report.applied
+        );
+    }
+    Ok(report)
+}
+
 /// 检查路径是否可读
 fn check_readable(path: &PathBuf) -> bool {
     #[cfg(unix)]
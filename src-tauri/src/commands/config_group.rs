@@ -1,7 +1,9 @@
 use crate::db::pool::DbPool;
-use crate::models::config_group::ConfigGroup;
+use crate::models::config_group::{ConfigGroup, GroupRetryStrategy, UpdateGroupRetryStrategyInput};
 use crate::models::error::AppResult;
+use crate::services::group_consistency::{GroupConsistencyService, GroupProviderConsistency};
 use crate::services::ConfigManager;
+use crate::utils::time::now_rfc3339;
 use std::sync::Arc;
 use tauri::State;
 
@@ -15,6 +17,11 @@ use tauri::State;
 /// - `latency_threshold_ms`: 延迟阈值(毫秒)
 /// - `health_check_enabled`: 是否启用健康检查
 /// - `health_check_interval_sec`: 健康检查间隔(秒)
+/// - `filter_sse_keepalive`: 是否过滤流式响应中的 SSE 保活事件
+/// - `body_transform`: 请求体/响应体转换规则 (JSON),为空表示不转换
+/// - `forward_client_ip`: 是否向后端转发客户端真实 IP (X-Forwarded-For / X-Real-IP)
+/// - `sse_retry_ms`: 流式响应开头发送的 SSE `retry:` 重连间隔(毫秒),为空表示不发送
+/// - `native_claude_only`: 仅包含原生 Claude 后端,请求体按原始字节转发
 #[tauri::command]
 pub fn create_config_group(
     name: String,
@@ -23,6 +30,11 @@ pub fn create_config_group(
     latency_threshold_ms: i32,
     health_check_enabled: Option<bool>,
     health_check_interval_sec: Option<i32>,
+    filter_sse_keepalive: Option<bool>,
+    body_transform: Option<String>,
+    forward_client_ip: Option<bool>,
+    sse_retry_ms: Option<i32>,
+    native_claude_only: Option<bool>,
     pool: State<'_, Arc<DbPool>>,
 ) -> AppResult<ConfigGroup> {
     log::info!("创建配置分组: {}", name);
@@ -37,10 +49,18 @@ pub fn create_config_group(
         retry_base_delay_ms: 2000,
         retry_max_delay_ms: 8000,
         rate_limit_delay_ms: 30000,
+        retry_strategy_customized: false,
         health_check_enabled: health_check_enabled.unwrap_or(false),
         health_check_interval_sec: health_check_interval_sec.unwrap_or(300),
-        created_at: chrono::Local::now().naive_local().to_string(),
-        updated_at: chrono::Local::now().naive_local().to_string(),
+        filter_sse_keepalive: filter_sse_keepalive.unwrap_or(false),
+        body_transform: body_transform.filter(|s| !s.trim().is_empty()),
+        forward_client_ip: forward_client_ip.unwrap_or(false),
+        sse_retry_ms: sse_retry_ms.filter(|ms| *ms > 0),
+        canary_config_id: None,
+        canary_percentage: 0,
+        native_claude_only: native_claude_only.unwrap_or(false),
+        created_at: now_rfc3339(),
+        updated_at: now_rfc3339(),
     };
 
     pool.with_connection(|conn| ConfigManager::create_group(conn, &group))
@@ -65,6 +85,11 @@ pub fn list_config_groups(pool: State<'_, Arc<DbPool>>) -> AppResult<Vec<ConfigG
 /// - `latency_threshold_ms`: 延迟阈值(毫秒)
 /// - `health_check_enabled`: 是否启用健康检查
 /// - `health_check_interval_sec`: 健康检查间隔(秒)
+/// - `filter_sse_keepalive`: 是否过滤流式响应中的 SSE 保活事件
+/// - `body_transform`: 请求体/响应体转换规则 (JSON),传入空字符串清除
+/// - `forward_client_ip`: 是否向后端转发客户端真实 IP (X-Forwarded-For / X-Real-IP)
+/// - `sse_retry_ms`: 流式响应开头发送的 SSE `retry:` 重连间隔(毫秒),传入 0 清除
+/// - `native_claude_only`: 仅包含原生 Claude 后端,请求体按原始字节转发
 #[tauri::command]
 pub fn update_config_group(
     id: i64,
@@ -74,6 +99,11 @@ pub fn update_config_group(
     latency_threshold_ms: i32,
     health_check_enabled: Option<bool>,
     health_check_interval_sec: Option<i32>,
+    filter_sse_keepalive: Option<bool>,
+    body_transform: Option<String>,
+    forward_client_ip: Option<bool>,
+    sse_retry_ms: Option<i32>,
+    native_claude_only: Option<bool>,
     pool: State<'_, Arc<DbPool>>,
 ) -> AppResult<ConfigGroup> {
     log::info!("更新配置分组: ID {}", id);
@@ -91,10 +121,26 @@ pub fn update_config_group(
         retry_base_delay_ms: existing_group.retry_base_delay_ms,
         retry_max_delay_ms: existing_group.retry_max_delay_ms,
         rate_limit_delay_ms: existing_group.rate_limit_delay_ms,
+        retry_strategy_customized: existing_group.retry_strategy_customized,
         health_check_enabled: health_check_enabled.unwrap_or(existing_group.health_check_enabled),
         health_check_interval_sec: health_check_interval_sec.unwrap_or(existing_group.health_check_interval_sec),
+        filter_sse_keepalive: filter_sse_keepalive.unwrap_or(existing_group.filter_sse_keepalive),
+        body_transform: match body_transform {
+            Some(spec) if spec.trim().is_empty() => None,
+            Some(spec) => Some(spec),
+            None => existing_group.body_transform,
+        },
+        forward_client_ip: forward_client_ip.unwrap_or(existing_group.forward_client_ip),
+        sse_retry_ms: match sse_retry_ms {
+            Some(ms) if ms <= 0 => None,
+            Some(ms) => Some(ms),
+            None => existing_group.sse_retry_ms,
+        },
+        canary_config_id: existing_group.canary_config_id,
+        canary_percentage: existing_group.canary_percentage,
+        native_claude_only: native_claude_only.unwrap_or(existing_group.native_claude_only),
         created_at: existing_group.created_at,
-        updated_at: chrono::Local::now().naive_local().to_string(),
+        updated_at: now_rfc3339(),
     };
 
     pool.with_connection(|conn| ConfigManager::update_group(conn, &group))
@@ -132,3 +178,130 @@ pub fn count_configs_in_group(group_id: i64, pool: State<'_, Arc<DbPool>>) -> Ap
 
     pool.with_connection(|conn| ConfigManager::count_configs_in_group(conn, group_id))
 }
+
+/// 获取分组生效的重试策略
+#[tauri::command]
+pub fn get_group_retry_strategy(
+    group_id: i64,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<GroupRetryStrategy> {
+    log::debug!("获取分组重试策略: group_id {}", group_id);
+
+    let group = pool.with_connection(|conn| ConfigManager::get_group_by_id(conn, group_id))?;
+
+    Ok(GroupRetryStrategy {
+        group_id,
+        customized: group.retry_strategy_customized,
+        strategy: group.retry_strategy(),
+    })
+}
+
+/// 更新分组重试策略
+///
+/// # 参数
+/// - `input`: 重试策略参数,未提供的字段沿用当前生效的值
+#[tauri::command]
+pub fn update_group_retry_strategy(
+    input: UpdateGroupRetryStrategyInput,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<ConfigGroup> {
+    log::info!("更新分组重试策略: group_id {}", input.group_id);
+
+    pool.with_connection(|conn| ConfigManager::update_retry_strategy(conn, &input))
+}
+
+/// 设置分组的高延迟切换阈值
+///
+/// 请求耗时超过阈值时触发高延迟自动切换 (需启用自动切换)。
+/// 阈值越低切换越激进，范围 500-100000 毫秒
+///
+/// # 参数
+/// - `group_id`: 分组 ID
+/// - `latency_threshold_ms`: 延迟阈值(毫秒)
+///
+/// # 返回
+/// - 更新后的分组
+#[tauri::command]
+pub fn set_group_latency_threshold(
+    group_id: i64,
+    latency_threshold_ms: i32,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<ConfigGroup> {
+    log::info!("设置分组延迟阈值: group_id {}, {}ms", group_id, latency_threshold_ms);
+
+    pool.with_connection(|conn| ConfigManager::set_latency_threshold(conn, group_id, latency_threshold_ms))
+}
+
+/// 设置分组的金丝雀配置
+///
+/// 按百分比将该分组的部分请求路由到金丝雀配置，代理运行中修改对下一个请求生效。
+/// 金丝雀连续失败 3 次后百分比自动置为 0
+///
+/// # 参数
+/// - `group_id`: 分组 ID
+/// - `canary_config_id`: 金丝雀配置 ID (为空表示清除)
+/// - `canary_percentage`: 路由到金丝雀的请求百分比 (0-100)
+///
+/// # 返回
+/// - 更新后的分组
+#[tauri::command]
+pub fn set_group_canary(
+    group_id: i64,
+    canary_config_id: Option<i64>,
+    canary_percentage: i32,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<ConfigGroup> {
+    log::info!(
+        "设置分组金丝雀配置: group_id {}, config {:?}, {}%",
+        group_id,
+        canary_config_id,
+        canary_percentage
+    );
+
+    let group = pool.with_connection(|conn| {
+        ConfigManager::set_canary(conn, group_id, canary_config_id, canary_percentage)
+    })?;
+    crate::proxy::canary::reset(group_id);
+    Ok(group)
+}
+
+/// 恢复分组使用默认重试策略
+#[tauri::command]
+pub fn reset_group_retry_strategy(
+    group_id: i64,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<ConfigGroup> {
+    log::info!("恢复分组默认重试策略: group_id {}", group_id);
+
+    pool.with_connection(|conn| ConfigManager::reset_retry_strategy(conn, group_id))
+}
+
+/// 检查分组内配置的提供商类型是否一致
+///
+/// 混用多种提供商时返回警告，自动切换可能在会话中途切换到其他提供商；
+/// 结果按提供商列出配置，便于拆分分组
+///
+/// # 参数
+/// - `group_id`: 分组 ID
+#[tauri::command]
+pub fn check_group_provider_consistency(
+    group_id: i64,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<GroupProviderConsistency> {
+    log::debug!("检查分组提供商一致性: group_id {}", group_id);
+
+    pool.with_connection(|conn| GroupConsistencyService::check(conn, group_id))
+}
+
+/// 确认分组当前的混合提供商组合
+///
+/// 确认后不再警告，分组加入新的提供商类型时会重新提示
+#[tauri::command]
+pub fn acknowledge_group_provider_mix(
+    group_id: i64,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<GroupProviderConsistency> {
+    log::info!("确认分组混合提供商组合: group_id {}", group_id);
+
+    pool.with_connection(|conn| GroupConsistencyService::acknowledge(conn, group_id))
+}
@@ -0,0 +1,71 @@
+/**
+ * 数据库维护命令
+ *
+ * 命令列表:
+ * - compact_database: 压缩数据库（WAL checkpoint + VACUUM），回收已删除数据占用的空间
+ * - check_clock_skew: 检查日志时间戳的格式一致性与时钟偏差
+ */
+
+use crate::commands::proxy_service::ProxyServiceState;
+use crate::db::DbPool;
+use crate::models::error::{AppError, AppResult};
+use crate::services::db_maintenance::{CompactProgress, CompactResult, DbMaintenanceService};
+use crate::services::timestamp_audit::{ClockSkewReport, TimestampAuditService};
+use std::sync::Arc;
+use tauri::{Emitter, State, Window};
+
+/// 允许压缩数据库时的最大并发代理请求数
+const COMPACT_MAX_ACTIVE_REQUESTS: usize = 2;
+
+/// 压缩数据库
+///
+/// VACUUM 期间主连接与日志写入连接均被占用，代理请求的日志写入会等待完成，
+/// 因此代理繁忙时默认拒绝执行（可通过 `force` 强制执行）。
+/// 进度通过 `database-compact-progress` 事件推送。
+///
+/// # 参数
+/// - `force`: 是否忽略代理负载检查
+///
+/// # 返回
+/// - CompactResult: 压缩前后文件大小及释放的字节数
+#[tauri::command]
+pub async fn compact_database(
+    force: Option<bool>,
+    window: Window,
+    pool: State<'_, Arc<DbPool>>,
+    proxy_state: State<'_, ProxyServiceState>,
+) -> AppResult<CompactResult> {
+    // 流式请求在流结束前都计入
+    let active = proxy_state.service().active_request_count().await;
+    log::info!("Command: compact_database (active proxy requests: {})", active);
+
+    if !force.unwrap_or(false) && active > COMPACT_MAX_ACTIVE_REQUESTS {
+        return Err(AppError::InUse {
+            message: format!("代理当前有 {} 个请求正在处理，请稍后再压缩数据库", active),
+        });
+    }
+
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        DbMaintenanceService::compact(&pool, |progress: CompactProgress| {
+            let _ = window.emit("database-compact-progress", &progress);
+        })
+    })
+    .await
+    .map_err(|e| AppError::SystemError {
+        message: format!("数据库压缩任务失败: {}", e),
+    })?
+}
+
+/// 检查日志时间戳的格式一致性与时钟偏差
+///
+/// 报告各日志表中非 UTC RFC3339 格式的时间戳数量，
+/// 以及晚于当前时间的时间戳数量 (系统时钟回拨或漂移时出现)。
+///
+/// # 返回
+/// - ClockSkewReport: 各时间戳列的检查结果
+#[tauri::command]
+pub fn check_clock_skew(pool: State<'_, Arc<DbPool>>) -> AppResult<ClockSkewReport> {
+    log::info!("Command: check_clock_skew");
+    pool.with_connection(TimestampAuditService::check)
+}
@@ -3,9 +3,16 @@
  * 提供环境变量的查询、设置和应用功能
  */
 
-use crate::models::error::AppResult;
+use crate::commands::proxy_service::ProxyServiceState;
+use crate::models::environment_variable::{AnthropicEnvCheck, ConfigEnvExports};
+use crate::models::error::{AppError, AppResult};
+use crate::models::proxy_status::ProxyStatus;
+use crate::services::claude_config::{ClaudeConfigService, ProxyConfig};
+use crate::services::env_snippet::EnvSnippetService;
 use crate::services::env_var::EnvironmentVariableService;
 use crate::services::ApiConfigService;
+use crate::services::session_config::env_session_id;
+use crate::services::SESSION_CONFIG_MAP;
 use crate::db::DbPool;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -119,13 +126,58 @@ pub fn apply_config_to_env(
     Ok(())
 }
 
-/// 检查 Anthropic 环境变量是否已设置
+/// 生成使用指定配置启动 Claude Code 的环境变量导出命令
+///
+/// 为配置注册固定的代理会话 (`env-{config_id}`)，返回 bash/zsh 与 PowerShell 两种语法的
+/// 导出命令，复制到自己的终端后启动的 Claude Code 会经代理使用该配置。
+/// 导出内容不包含配置中保存的 API 密钥。应用重启或会话被清理后，代理会按会话 ID 直接解析到该配置，
+/// 导出命令无需重新生成 (配置被删除后失效)。
+///
+/// # 参数
+/// - `config_id`: API 配置 ID
+#[tauri::command]
+pub async fn get_config_env_exports(
+    config_id: i64,
+    db_pool: State<'_, Arc<DbPool>>,
+    proxy_state: State<'_, ProxyServiceState>,
+) -> AppResult<ConfigEnvExports> {
+    let config = db_pool.with_connection(|conn| {
+        ApiConfigService::get_config_by_id(conn, config_id)
+    })?;
+
+    let status = proxy_state.service().get_status().await?;
+    if status.status != ProxyStatus::Running {
+        return Err(AppError::InvalidState {
+            message: "代理未运行，请先启动代理服务".to_string(),
+        });
+    }
+    let proxy_base_url = ClaudeConfigService::expected_base_url(&ProxyConfig {
+        host: status.listen_host,
+        port: status.listen_port as u16,
+    });
+
+    let session_id = env_session_id(config_id);
+    SESSION_CONFIG_MAP.register(session_id.clone(), config_id, Some(config.name.clone()));
+
+    Ok(EnvSnippetService::config_exports(&config, &proxy_base_url, &session_id))
+}
+
+/// 检查 Anthropic 环境变量
+///
+/// 报告 ANTHROPIC_BASE_URL / ANTHROPIC_API_KEY / ANTHROPIC_AUTH_TOKEN 的取值，
+/// 并在代理运行时标出未指向代理的变量及清除方法
 #[tauri::command]
-pub fn check_anthropic_env(
+pub async fn check_anthropic_env(
     state: State<'_, EnvironmentVariableState>,
-) -> AppResult<bool> {
-    let service = state.service();
-    service.check_anthropic_env()
+    proxy_state: State<'_, ProxyServiceState>,
+) -> AppResult<AnthropicEnvCheck> {
+    let status = proxy_state.service().get_status().await?;
+    let proxy = (status.status == ProxyStatus::Running).then_some(ProxyConfig {
+        host: status.listen_host,
+        port: status.listen_port as u16,
+    });
+
+    state.service().check_anthropic_env(proxy.as_ref())
 }
 
 /// 清除 Anthropic 相关环境变量
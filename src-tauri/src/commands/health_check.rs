@@ -5,7 +5,8 @@
 
 use crate::commands::proxy_service::ProxyServiceState;
 use crate::db::DbPool;
-use crate::models::health_check::ConfigHealthSummary;
+use crate::models::health_check::{ConfigHealthSummary, HealthCheckMode};
+use crate::services::api_config::ApiConfigService;
 use crate::services::health_check_scheduler::HealthCheckScheduler;
 use crate::services::health_check_service::HealthCheckService;
 use serde::{Deserialize, Serialize};
@@ -238,6 +239,21 @@ pub async fn get_health_check_summaries(
     }
 }
 
+/// 为分组内所有配置设置健康检查探测方式
+#[tauri::command]
+pub async fn set_group_health_check_mode(
+    pool: State<'_, Arc<DbPool>>,
+    group_id: i64,
+    mode: HealthCheckMode,
+) -> Result<usize, String> {
+    log::info!("设置分组 {} 的健康检查方式: {}", group_id, mode.as_str());
+    pool.with_connection(|conn| ApiConfigService::set_group_health_check_mode(conn, group_id, mode))
+        .map_err(|e| {
+            log::error!("设置健康检查方式失败: {:?}", e);
+            e.to_string()
+        })
+}
+
 /// 切换自动健康检查状态
 #[tauri::command]
 pub async fn toggle_auto_health_check(
@@ -1,6 +1,10 @@
-use crate::models::mcp::{McpServerConfig, McpServerInfo, McpServerTemplate};
+use crate::models::mcp::{
+    McpBatchTestProgress, McpBatchTestResult, McpImportResult, McpImportStrategy, McpServerConfig,
+    McpServerDiagnostics, McpServerInfo, McpServerTemplate,
+};
 use crate::services::McpConfigService;
 use std::collections::HashMap;
+use tauri::{Emitter, Window};
 
 /// 列出所有 MCP 服务器
 #[tauri::command]
@@ -57,12 +61,36 @@ pub async fn test_mcp_server(name: String) -> Result<String, String> {
         .map_err(|e| e.to_string())
 }
 
+/// 诊断 MCP 服务器连通性 (执行真实的 initialize 握手)
+#[tauri::command]
+pub async fn diagnose_mcp_server(name: String) -> Result<McpServerDiagnostics, String> {
+    McpConfigService::diagnose_server(name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 并发测试所有 MCP 服务器
+///
+/// 每完成一个服务器推送一次 "mcp-test-progress" 事件，返回各服务器的握手结果、工具数量与错误
+#[tauri::command]
+pub async fn test_all_mcp_servers(window: Window) -> Result<McpBatchTestResult, String> {
+    McpConfigService::test_all_servers(|progress: McpBatchTestProgress| {
+        let _ = window.emit("mcp-test-progress", &progress);
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
 /// 批量导入 MCP 服务器
+///
+/// strategy 缺省为 skip (跳过同名服务器)
 #[tauri::command]
 pub async fn import_mcp_servers(
     servers: HashMap<String, McpServerConfig>,
-) -> Result<(), String> {
-    McpConfigService::import_servers(servers).map_err(|e| e.to_string())
+    strategy: Option<McpImportStrategy>,
+) -> Result<McpImportResult, String> {
+    McpConfigService::import_servers(servers, strategy.unwrap_or_default())
+        .map_err(|e| e.to_string())
 }
 
 /// 导出 MCP 服务器配置
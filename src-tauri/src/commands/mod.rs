@@ -6,6 +6,7 @@ pub mod auto_switch;
 pub mod balance;
 pub mod claude_code;
 pub mod config_group;
+pub mod database;
 pub mod env_var;
 pub mod health_check;
 pub mod mcp;
@@ -22,41 +23,59 @@ pub mod terminal;
 
 // 重新导出常用命令
 pub use api_config::{
-    create_api_config, delete_api_config, get_api_config, get_api_key, list_api_configs,
-    quick_test_config_url, reorder_api_config, set_config_enabled, test_api_endpoints,
-    update_api_config,
+    create_api_config, create_config_from_env_snippet, delete_api_config, detect_deprecated_server_port, fetch_backend_models, get_api_config, get_api_key,
+    list_api_configs, normalize_server_url, quick_test_config_url, reorder_api_config, set_config_enabled, set_group_model_overrides,
+    set_config_disabled_until, clear_config_disabled_until, reset_config_state, test_api_endpoints, update_api_config,
 };
 
-pub use api_test::{get_test_results, test_api_config, test_group_configs};
+pub use api_test::{
+    compare_test_runs, get_config_timing_breakdown, get_test_results, test_api_config, test_config_via_proxy,
+    test_group_configs, test_stale_configs, validate_provider_preset, run_conversion_self_test,
+    diagnose_last_failure,
+};
 
 pub use app_update::{
-    check_app_updates, download_app_update, get_app_version, open_release_page,
+    check_app_updates, download_app_update, get_app_version, list_available_rollbacks, open_release_page,
+    rollback_app_update,
 };
 
-pub use auto_switch::{clear_switch_logs, get_switch_logs, toggle_auto_switch};
+pub use auto_switch::{
+    clear_switch_logs, export_switch_logs, get_recent_switch_events, get_switch_logs, inject_config_failure, get_retry_state, reset_retry_state, toggle_auto_switch,
+};
 
-pub use balance::{get_all_balance_info, query_all_balances, query_balance};
+pub use balance::{
+    get_all_balance_info, query_all_balances, query_balance, start_balance_watch, stop_balance_watch,
+};
 
 pub use claude_code::{
     clear_all_claude_code_backups, create_claude_code_backup, delete_claude_code_backup,
-    detect_claude_code_path, disable_claude_code_proxy, enable_claude_code_proxy,
+    detect_claude_code_path, detect_claude_code_settings_paths, disable_claude_code_proxy, enable_claude_code_proxy,
     get_claude_code_proxy, get_claude_code_settings, list_claude_code_backups,
     preview_claude_code_backup, restore_claude_code_backup, restore_claude_code_config,
+    verify_claude_code_integration, reconcile_claude_code_port,
 };
 
 pub use config_group::{
     count_configs_in_group, create_config_group, delete_config_group, get_config_group,
-    list_config_groups, update_config_group,
+    get_group_retry_strategy, list_config_groups, reset_group_retry_strategy, set_group_latency_threshold,
+    set_group_canary, update_config_group, update_group_retry_strategy,
+    check_group_provider_consistency, acknowledge_group_provider_mix,
 };
 
+pub use database::{check_clock_skew, compact_database};
+
 pub use proxy_service::{
-    get_proxy_status, start_proxy_service, stop_proxy_service, switch_proxy_config,
-    switch_proxy_group, ProxyServiceState,
+    clear_connection_pool, create_proxy_listener, delete_routing_snapshot, diagnose_port_conflict, get_connection_pool_stats, get_metrics_prometheus, get_metrics_snapshot_interval, get_metrics_snapshots, get_proxy_status, list_active_requests, list_proxy_listeners,
+    list_routing_snapshots, preview_forwarded_request, get_effective_config, remove_proxy_listener,
+    trace_next_request, cancel_request_trace, get_request_trace_status, set_artificial_latency, clear_artificial_latency, get_artificial_latencies, restore_routing_snapshot,
+    set_metrics_snapshot_interval, run_load_test,
+    save_routing_snapshot, set_proxy_stream_limits, set_proxy_timeouts, start_proxy_listener, start_proxy_service, stop_proxy_listener,
+    stop_proxy_service, switch_proxy_config, switch_proxy_group, ProxyServiceState,
 };
 
 pub use provider_preset::{
     get_provider_categories, get_provider_preset, get_provider_presets_by_category,
-    get_recommended_provider_presets, list_provider_presets,
+    get_recommended_provider_presets, list_provider_presets, validate_embedded_providers,
 };
 
 pub use recommendation::{
@@ -64,45 +83,53 @@ pub use recommendation::{
 };
 
 pub use env_var::{
-    apply_config_to_env, check_anthropic_env, clear_anthropic_env, get_environment_variable,
+    apply_config_to_env, get_config_env_exports, check_anthropic_env, clear_anthropic_env, get_environment_variable,
     list_environment_variables, set_environment_variable, set_environment_variables,
     unset_environment_variable, EnvironmentVariableState,
 };
 
 pub use proxy_log::{
-    cleanup_proxy_request_logs, get_all_proxy_request_logs, get_proxy_request_log_count,
-    get_proxy_request_log_detail, get_proxy_request_log_stats, get_proxy_request_logs,
+    cleanup_proxy_request_logs, generate_curl_repro, get_all_proxy_request_logs, get_proxy_request_log_count,
+    get_log_bodies_enabled, get_log_full_bodies_enabled, get_log_retention_policy, get_proxy_request_log_detail,
+    get_proxy_request_log_stats, get_proxy_request_logs, get_stream_integrity_issues,
+    set_log_bodies_enabled, set_log_full_bodies_enabled, set_log_retention_policy, get_log_sampling_policy, set_log_sampling_policy,
 };
 
 pub use health_check::{
     get_health_check_status, get_health_check_summaries, run_health_check_now,
-    start_health_check, stop_health_check, toggle_auto_health_check, HealthCheckState,
+    set_group_health_check_mode, start_health_check, stop_health_check, toggle_auto_health_check, HealthCheckState,
 };
 
 pub use setup::{
-    check_can_install, check_can_install_enhanced, check_for_updates, check_system_configured,
-    detect_environment, detect_environment_enhanced, generate_environment_report, get_claude_version,
-    get_default_node_environment, install_claude_code, run_claude_doctor, set_default_node_environment,
-    uninstall_claude_code, update_claude_code, verify_claude_installation,
+    check_can_install, check_can_install_enhanced, check_for_updates, check_llm_endpoints_reachability,
+    check_system_configured,
+    detect_environment, detect_environment_enhanced, generate_config_report, generate_environment_report,
+    get_claude_version, get_default_node_environment, install_claude_code, run_claude_doctor,
+    set_default_node_environment, uninstall_claude_code, update_claude_code, verify_claude_installation,
 };
 
 pub use mcp::{
-    add_mcp_server, add_mcp_server_from_template, export_mcp_servers, get_mcp_templates,
-    import_mcp_servers, list_mcp_servers, remove_mcp_server, test_mcp_server, update_mcp_server,
+    add_mcp_server, add_mcp_server_from_template, diagnose_mcp_server, export_mcp_servers,
+    get_mcp_templates, import_mcp_servers, list_mcp_servers, remove_mcp_server, test_all_mcp_servers,
+    test_mcp_server, update_mcp_server,
 };
 
-pub use permissions::{clear_permissions_config, get_permissions_config, update_permissions_config};
+pub use permissions::{
+    clear_permissions_config, diff_permissions_config, get_permissions_config, update_permissions_config,
+    validate_permissions_config,
+};
 
 pub use slash_commands::{
     list_slash_commands, get_slash_command, create_slash_command, update_slash_command,
     delete_slash_command, read_slash_command_body, migrate_skills_to_commands,
+    validate_slash_commands, prune_broken_slash_commands,
 };
 
 pub use terminal::{
     build_terminal_env_vars, cleanup_stale_terminal_sessions, clear_all_terminal_sessions,
-    get_terminal_proxy_url, get_terminal_session, get_terminal_session_count,
-    list_terminal_sessions, register_terminal_session, remove_terminal_session,
-    TerminalSessionInfo,
+    create_session_with_config, get_terminal_proxy_url, get_terminal_session, get_terminal_session_count,
+    list_terminal_sessions, register_terminal_session, remove_terminal_session, kill_terminal_session,
+    PinnedSessionInfo, TerminalSessionInfo,
     // PTY commands
     create_pty_session, create_claude_code_session, pty_write_input, close_pty_session,
     list_pty_sessions, get_pty_session_count, pty_resize,
@@ -111,7 +138,8 @@ pub use terminal::{
 pub use model_mapping::{
     list_model_mappings, get_model_mapping, create_model_mapping, update_model_mapping,
     delete_model_mapping, batch_delete_model_mappings, export_model_mappings,
-    import_model_mappings, reset_to_default_mappings, ModelMappingServiceState,
+    import_model_mappings, reset_to_default_mappings, estimate_request_tokens, get_default_request_model, set_default_request_model,
+    ModelMappingServiceState,
 };
 
 pub use project_context::{
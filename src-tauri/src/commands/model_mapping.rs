@@ -4,7 +4,8 @@
  * 提供前端调用的 IPC 接口
  */
 
-use crate::models::error::AppResult;
+use crate::converters::token_estimator::{self, TokenEstimate};
+use crate::models::error::{AppError, AppResult};
 use crate::models::model_mapping::*;
 use crate::services::model_mapping_service::ModelMappingService;
 use std::sync::Arc;
@@ -154,7 +155,7 @@ pub async fn import_model_mappings(
     state: State<'_, ModelMappingServiceState>,
 ) -> AppResult<(usize, usize)> {
     let export: ModelMappingExport = serde_json::from_str(&export_json).map_err(|e| {
-        crate::models::error::AppError::ValidationError {
+        AppError::ValidationError {
             field: "export_json".to_string(),
             message: format!("JSON 解析失败: {}", e),
         }
@@ -172,3 +173,46 @@ pub async fn reset_to_default_mappings(
     let service = state.service.lock().await;
     service.reset_to_default_mappings().await
 }
+
+/// 获取客户端未指定模型时注入的默认模型
+#[tauri::command]
+pub async fn get_default_request_model(
+    state: State<'_, ModelMappingServiceState>,
+) -> AppResult<Option<String>> {
+    let service = state.service.lock().await;
+    service.get_default_request_model().await
+}
+
+/// 设置客户端未指定模型时注入的默认模型
+///
+/// 请求体缺少 `model` 字段或为空时，代理在格式转换与模型映射之前注入该模型；传入空值表示清除
+#[tauri::command]
+pub async fn set_default_request_model(
+    model: Option<String>,
+    state: State<'_, ModelMappingServiceState>,
+) -> AppResult<()> {
+    let service = state.service.lock().await;
+    service.set_default_request_model(model).await
+}
+
+/// 估算请求的输入 token 数 (支持 Claude 与 OpenAI 请求格式)
+///
+/// 结果为启发式估算，并给出相对模型上下文窗口的占用比例
+#[tauri::command]
+pub async fn estimate_request_tokens(body: serde_json::Value) -> AppResult<TokenEstimate> {
+    // 允许前端直接传入原始请求体字符串
+    let body = match body {
+        serde_json::Value::String(raw) => {
+            serde_json::from_str(&raw).map_err(|e| AppError::ValidationError {
+                field: "body".to_string(),
+                message: format!("JSON 解析失败: {}", e),
+            })?
+        }
+        other => other,
+    };
+
+    token_estimator::estimate_request_tokens(&body).map_err(|message| AppError::ValidationError {
+        field: "body".to_string(),
+        message,
+    })
+}
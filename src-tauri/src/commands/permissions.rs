@@ -1,4 +1,4 @@
-use crate::models::claude_advanced::PermissionsConfig;
+use crate::models::claude_advanced::{PermissionsConfig, PermissionsDiff};
 use crate::services::PermissionsConfigService;
 
 /// 读取 Permissions 配置
@@ -13,6 +13,21 @@ pub async fn update_permissions_config(config: PermissionsConfig) -> Result<(),
     PermissionsConfigService::write_permissions(&config).map_err(|e| e.to_string())
 }
 
+/// 校验 Permissions 配置 (不写入)
+///
+/// 与 `update_permissions_config` 写入前的校验相同：格式错误时返回错误，
+/// 否则返回不阻止写入的警告 (未知工具名称、allow 与 deny 重叠)
+#[tauri::command]
+pub async fn validate_permissions_config(config: PermissionsConfig) -> Result<Vec<String>, String> {
+    PermissionsConfigService::validate_permissions_config(&config).map_err(|e| e.to_string())
+}
+
+/// 预览 Permissions 配置修改：与当前配置比较，返回新增/移除/修改的规则
+#[tauri::command]
+pub async fn diff_permissions_config(new_config: PermissionsConfig) -> Result<PermissionsDiff, String> {
+    PermissionsConfigService::diff_permissions_config(&new_config).map_err(|e| e.to_string())
+}
+
 /// 清除 Permissions 配置
 #[tauri::command]
 pub async fn clear_permissions_config() -> Result<(), String> {
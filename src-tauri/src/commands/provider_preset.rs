@@ -1,5 +1,5 @@
 use crate::models::error::AppResult;
-use crate::models::provider_preset::{ProviderCategory, ProviderPreset};
+use crate::models::provider_preset::{EmbeddedProvidersReport, ProviderCategory, ProviderPreset};
 use crate::services::ProviderPresetService;
 
 /// 获取所有供应商预设
@@ -57,3 +57,13 @@ pub fn get_provider_categories() -> Vec<ProviderCategory> {
     log::debug!("获取所有供应商分类");
     ProviderPresetService::get_all_categories()
 }
+
+/// 校验内嵌的 providers.json
+///
+/// # 返回
+/// 返回内嵌配置的解析与校验结果（远程获取失败时推荐服务依赖该配置回退）
+#[tauri::command]
+pub fn validate_embedded_providers() -> EmbeddedProvidersReport {
+    log::debug!("校验内嵌的供应商配置");
+    ProviderPresetService::validate_embedded_providers()
+}
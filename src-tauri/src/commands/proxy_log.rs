@@ -4,7 +4,12 @@
  */
 
 use crate::db::DbPool;
-use crate::services::proxy_log::{LogStats, ProxyRequestLog, ProxyRequestLogDetail, ProxyRequestLogService};
+use crate::services::proxy_log::{
+    ConfigStreamIntegrity, LogStats, ProxyRequestLog, ProxyRequestLogDetail, ProxyRequestLogService,
+};
+use crate::services::curl_repro;
+use crate::services::log_retention::{LogRetentionPolicy, LogRetentionService};
+use crate::services::log_sampling::{LogSamplingPolicy, LogSamplingService};
 use std::sync::Arc;
 use tauri::State;
 
@@ -45,6 +50,16 @@ pub async fn get_proxy_request_log_detail(
         .map_err(|e| e.to_string())
 }
 
+/// 将代理请求日志还原为 curl 复现命令（API 密钥以 `$API_KEY` 占位）
+#[tauri::command]
+pub async fn generate_curl_repro(
+    pool: State<'_, Arc<DbPool>>,
+    log_id: i64,
+) -> Result<String, String> {
+    curl_repro::generate_curl_repro(&pool, log_id)
+        .map_err(|e| e.to_string())
+}
+
 /// 清理旧的代理请求日志
 #[tauri::command]
 pub async fn cleanup_proxy_request_logs(
@@ -57,6 +72,69 @@ pub async fn cleanup_proxy_request_logs(
         .map_err(|e| e.to_string())
 }
 
+/// 获取请求日志保留策略
+#[tauri::command]
+pub async fn get_log_retention_policy(
+    pool: State<'_, Arc<DbPool>>,
+) -> Result<LogRetentionPolicy, String> {
+    pool.with_connection(LogRetentionService::get_policy)
+        .map_err(|e| e.to_string())
+}
+
+/// 设置请求日志保留策略
+///
+/// - `max_count`: 最多保留条数，为空表示不限制
+/// - `max_age_days`: 最长保留天数，为空表示不限制
+/// - `cleanup_interval_minutes`: 后台清理间隔，为空时保持原值
+#[tauri::command]
+pub async fn set_log_retention_policy(
+    pool: State<'_, Arc<DbPool>>,
+    max_count: Option<i64>,
+    max_age_days: Option<i64>,
+    cleanup_interval_minutes: Option<i32>,
+) -> Result<LogRetentionPolicy, String> {
+    pool.with_connection(|conn| {
+        let current = LogRetentionService::get_policy(conn)?;
+        let policy = LogRetentionPolicy {
+            max_count,
+            max_age_days,
+            cleanup_interval_minutes: cleanup_interval_minutes
+                .unwrap_or(current.cleanup_interval_minutes),
+        };
+        LogRetentionService::set_policy(conn, &policy)?;
+        Ok(policy)
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// 获取请求日志采样策略
+#[tauri::command]
+pub async fn get_log_sampling_policy(
+    pool: State<'_, Arc<DbPool>>,
+) -> Result<LogSamplingPolicy, String> {
+    pool.with_connection(LogSamplingService::get_policy)
+        .map_err(|e| e.to_string())
+}
+
+/// 设置请求日志采样策略
+///
+/// - `all`: 记录全部请求（默认）
+/// - `errors_only`: 仅记录失败请求
+/// - `errors_and_slow`: 记录失败请求和耗时超过 `slow_threshold_ms` 的请求
+/// - `sampled`: 记录全部失败请求，成功请求按 `percent`% 采样
+///
+/// 未记录的请求仍计入请求指标
+#[tauri::command]
+pub async fn set_log_sampling_policy(
+    pool: State<'_, Arc<DbPool>>,
+    policy: LogSamplingPolicy,
+) -> Result<LogSamplingPolicy, String> {
+    pool.with_connection(|conn| LogSamplingService::set_policy(conn, &policy))
+        .map_err(|e| e.to_string())?;
+    LogSamplingService::cache_policy(policy);
+    Ok(policy)
+}
+
 /// 获取代理请求日志总数
 #[tauri::command]
 pub async fn get_proxy_request_log_count(
@@ -77,3 +155,59 @@ pub async fn get_proxy_request_log_stats(
     ProxyRequestLogService::get_logs_stats(&pool, hours)
         .map_err(|e| e.to_string())
 }
+
+/// 获取最近存在流完整性问题的请求（按配置分组）
+///
+/// 用于定位间歇性截断流或事件顺序异常的后端
+#[tauri::command]
+pub async fn get_stream_integrity_issues(
+    pool: State<'_, Arc<DbPool>>,
+    limit: Option<i64>,
+) -> Result<Vec<ConfigStreamIntegrity>, String> {
+    let limit = limit.unwrap_or(100);
+
+    ProxyRequestLogService::get_stream_integrity_issues(&pool, limit)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取是否记录请求体/响应体内容
+#[tauri::command]
+pub async fn get_log_bodies_enabled(
+    pool: State<'_, Arc<DbPool>>,
+) -> Result<bool, String> {
+    pool.with_connection(|conn| Ok(ProxyRequestLogService::body_logging_enabled(conn)))
+        .map_err(|e| e.to_string())
+}
+
+/// 设置是否记录请求体/响应体内容
+///
+/// 关闭后代理日志只保存大小、状态、模型、耗时等元数据，不保存请求体与响应体
+#[tauri::command]
+pub async fn set_log_bodies_enabled(
+    pool: State<'_, Arc<DbPool>>,
+    enabled: bool,
+) -> Result<(), String> {
+    ProxyRequestLogService::set_body_logging_enabled(&pool, enabled)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取是否保存完整请求体/响应体
+#[tauri::command]
+pub async fn get_log_full_bodies_enabled(
+    pool: State<'_, Arc<DbPool>>,
+) -> Result<bool, String> {
+    pool.with_connection(|conn| Ok(ProxyRequestLogService::full_body_logging_enabled(conn)))
+        .map_err(|e| e.to_string())
+}
+
+/// 设置是否保存完整请求体/响应体
+///
+/// 开启后超过 8KB 的请求体/响应体完整保存到侧表 (上限 1MB)，日志详情返回完整内容
+#[tauri::command]
+pub async fn set_log_full_bodies_enabled(
+    pool: State<'_, Arc<DbPool>>,
+    enabled: bool,
+) -> Result<(), String> {
+    ProxyRequestLogService::set_full_body_logging_enabled(&pool, enabled)
+        .map_err(|e| e.to_string())
+}
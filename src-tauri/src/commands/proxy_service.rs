@@ -6,15 +6,43 @@
  * - start_proxy_service: Start proxy server
  * - stop_proxy_service: Stop proxy server
  * - get_proxy_status: Get current status
+ * - diagnose_port_conflict: Identify the process holding the proxy port
  * - switch_proxy_group: Switch to different group
  * - switch_proxy_config: Switch to different configuration
+ * - set_proxy_timeouts: Update connect / request timeouts
+ * - set_proxy_stream_limits: Update streaming response duration / chunk limits
+ * - save_routing_snapshot / restore_routing_snapshot: Bookmark routing state
+ * - list_routing_snapshots / delete_routing_snapshot: Manage snapshots
+ * - list_active_requests: List in-flight proxy requests
+ * - get_metrics_prometheus: Export request metrics in Prometheus text format
+ * - get_connection_pool_stats / clear_connection_pool: Per-host backend connection stats and cached host state
+ * - preview_forwarded_request: Show the transformed request without sending it
+ * - get_effective_config: Explain the merged settings a config is forwarded with
+ * - trace_next_request / cancel_request_trace / get_request_trace_status: One-shot request trace to file
+ * - set/clear/get_artificial_latency: Inject latency into forwarding (testing aid for high-latency switching)
+ * - run_load_test: Send synthetic concurrent requests through the proxy and report throughput / latency
+ * - create/start/stop/remove/list_proxy_listener(s): Manage additional named listeners
  */
 
-use crate::models::error::AppResult;
-use crate::models::proxy_status::ProxyService as ProxyServiceModel;
+use crate::models::error::{AppError, AppResult};
+use crate::db::DbPool;
+use crate::models::proxy_status::{ProxyListenerInfo, ProxyService as ProxyServiceModel, ProxyStatus};
+use crate::proxy::active_requests::ActiveRequestInfo;
+use crate::proxy::connection_pool::{self, ConnectionPoolClearResult, ConnectionPoolStats};
+use crate::proxy::latency_injection::{self, ArtificialLatency};
+use crate::proxy::request_trace::{self, RequestTraceStatus};
+use crate::proxy::router::{EffectiveConfig, ForwardedRequestPreview, RequestRouter};
+use crate::models::routing_snapshot::{RestoreRoutingSnapshotResult, RoutingSnapshot};
+use crate::services::api_config::ApiConfigService;
+use crate::services::claude_config::{ClaudeConfigService, ProxyConfig};
+use crate::services::load_test::{self, LoadTestProgress, LoadTestReport};
+use crate::services::port_diagnosis::{PortConflictDiagnosis, PortDiagnosisService};
+use crate::services::metrics_snapshot::{MetricsSnapshot, MetricsSnapshotService};
 use crate::services::proxy_service::ProxyService;
+use crate::services::routing_snapshot::RoutingSnapshotService;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{Emitter, State, Window};
 
 /// Global proxy service state
 #[derive(Clone)]
@@ -75,6 +103,37 @@ pub async fn get_proxy_status(
     state.service().get_status().await
 }
 
+/// Diagnose whether a proxy port is held by another process
+///
+/// - Reports the PID and process name holding the port (lsof / ss / netstat)
+/// - Explains the automatic port fallback when the proxy moved to another port
+///
+/// # Arguments
+/// - `port`: Port to check, defaults to the default proxy port
+///
+/// # Returns
+/// - PortConflictDiagnosis describing the port holder
+#[tauri::command]
+pub async fn diagnose_port_conflict(
+    port: Option<u16>,
+    state: State<'_, ProxyServiceState>,
+) -> AppResult<PortConflictDiagnosis> {
+    let port = port.unwrap_or_else(crate::utils::constants::default_proxy_port);
+    log::info!("Command: diagnose_port_conflict (port: {})", port);
+
+    let status = state.service().get_status().await?;
+    let active_port = (status.status == ProxyStatus::Running)
+        .then(|| u16::try_from(status.listen_port).ok())
+        .flatten();
+    let host = status.listen_host;
+
+    tokio::task::spawn_blocking(move || PortDiagnosisService::diagnose(&host, port, active_port))
+        .await
+        .map_err(|e| AppError::SystemError {
+            message: format!("端口诊断任务失败: {}", e),
+        })
+}
+
 /// Switch to different configuration group
 ///
 /// - Switches to target group
@@ -116,6 +175,455 @@ pub async fn switch_proxy_config(
     state.service().switch_config(config_id).await
 }
 
+/// Update proxy-wide connect / request timeouts
+///
+/// - Connect timeout applies to TCP connect + TLS handshake only
+/// - Request timeout applies to sending the request and waiting for the response
+/// - Per-config overrides take precedence
+///
+/// # Arguments
+/// - `connect_timeout_secs`: New connect timeout (None keeps current)
+/// - `request_timeout_secs`: New request timeout (None keeps current)
+///
+/// # Returns
+/// - Effective [connect_timeout_secs, request_timeout_secs]
+#[tauri::command]
+pub async fn set_proxy_timeouts(
+    connect_timeout_secs: Option<u64>,
+    request_timeout_secs: Option<u64>,
+    state: State<'_, ProxyServiceState>,
+) -> AppResult<(u64, u64)> {
+    log::info!(
+        "Command: set_proxy_timeouts (connect: {:?}, request: {:?})",
+        connect_timeout_secs,
+        request_timeout_secs
+    );
+    state
+        .service()
+        .set_timeouts(connect_timeout_secs, request_timeout_secs)
+        .await
+}
+
+/// Update proxy-wide streaming response limits
+///
+/// - Streams running longer than the duration limit or producing more chunks
+///   than the chunk limit are terminated with a clean `message_stop`
+/// - Terminated streams count as failures for auto-switch
+/// - 0 disables a limit
+///
+/// # Arguments
+/// - `max_duration_secs`: New maximum stream duration (None keeps current)
+/// - `max_chunks`: New maximum chunk count (None keeps current)
+///
+/// # Returns
+/// - Effective [max_stream_duration_secs, max_stream_chunks]
+#[tauri::command]
+pub async fn set_proxy_stream_limits(
+    max_duration_secs: Option<u64>,
+    max_chunks: Option<u32>,
+    state: State<'_, ProxyServiceState>,
+) -> AppResult<(u64, u32)> {
+    log::info!(
+        "Command: set_proxy_stream_limits (max_duration: {:?}, max_chunks: {:?})",
+        max_duration_secs,
+        max_chunks
+    );
+    state
+        .service()
+        .set_stream_limits(max_duration_secs, max_chunks)
+        .await
+}
+
+/// Save current routing state as a named snapshot
+///
+/// Captures active group id, active config id, per-config enabled flags and
+/// per-group auto-switch flags. Saving with an existing name overwrites it.
+///
+/// # Arguments
+/// - `name`: Snapshot name
+#[tauri::command]
+pub async fn save_routing_snapshot(
+    name: String,
+    state: State<'_, ProxyServiceState>,
+) -> AppResult<RoutingSnapshot> {
+    log::info!("Command: save_routing_snapshot (name: {})", name);
+    state.service().save_routing_snapshot(&name).await
+}
+
+/// Restore a named routing snapshot through the proxy service
+///
+/// # Arguments
+/// - `name`: Snapshot name
+#[tauri::command]
+pub async fn restore_routing_snapshot(
+    name: String,
+    state: State<'_, ProxyServiceState>,
+) -> AppResult<RestoreRoutingSnapshotResult> {
+    log::info!("Command: restore_routing_snapshot (name: {})", name);
+    state.service().restore_routing_snapshot(&name).await
+}
+
+/// List saved routing snapshots (most recently updated first)
+#[tauri::command]
+pub fn list_routing_snapshots(pool: State<'_, Arc<DbPool>>) -> AppResult<Vec<RoutingSnapshot>> {
+    log::debug!("Command: list_routing_snapshots");
+    pool.with_connection(RoutingSnapshotService::list)
+}
+
+/// Delete a routing snapshot
+///
+/// # Arguments
+/// - `name`: Snapshot name
+#[tauri::command]
+pub fn delete_routing_snapshot(name: String, pool: State<'_, Arc<DbPool>>) -> AppResult<()> {
+    log::info!("Command: delete_routing_snapshot (name: {})", name);
+    pool.with_connection(|conn| RoutingSnapshotService::delete(conn, &name))
+}
+
+/// List requests the proxy is currently working on
+///
+/// Includes streaming responses until the stream finishes. Useful for spotting
+/// requests stuck waiting on an unresponsive backend.
+#[tauri::command]
+pub fn list_active_requests(state: State<'_, ProxyServiceState>) -> AppResult<Vec<ActiveRequestInfo>> {
+    log::debug!("Command: list_active_requests");
+    Ok(state.service().list_active_requests())
+}
+
+/// Create an additional named proxy listener on another port
+///
+/// The listener is created stopped; call `start_proxy_listener` to bind it.
+/// Failovers on the listener only change its own active config. Listeners are
+/// not persisted and disappear when the application restarts.
+///
+/// # Arguments
+/// - `name`: Unique listener name
+/// - `port`: Listen port
+/// - `group_id`: Active group (first available config is used)
+/// - `config_id`: Active configuration (takes precedence over `group_id`)
+#[tauri::command]
+pub async fn create_proxy_listener(
+    name: String,
+    port: u16,
+    group_id: Option<i64>,
+    config_id: Option<i64>,
+    state: State<'_, ProxyServiceState>,
+) -> AppResult<ProxyListenerInfo> {
+    log::info!(
+        "Command: create_proxy_listener (name: {}, port: {}, group: {:?}, config: {:?})",
+        name,
+        port,
+        group_id,
+        config_id
+    );
+    state
+        .service()
+        .create_listener(&name, port, group_id, config_id)
+        .await
+}
+
+/// Start a named proxy listener
+#[tauri::command]
+pub async fn start_proxy_listener(
+    name: String,
+    state: State<'_, ProxyServiceState>,
+) -> AppResult<ProxyListenerInfo> {
+    log::info!("Command: start_proxy_listener (name: {})", name);
+    state.service().start_listener(&name).await
+}
+
+/// Stop a named proxy listener
+#[tauri::command]
+pub async fn stop_proxy_listener(
+    name: String,
+    state: State<'_, ProxyServiceState>,
+) -> AppResult<ProxyListenerInfo> {
+    log::info!("Command: stop_proxy_listener (name: {})", name);
+    state.service().stop_listener(&name).await
+}
+
+/// Remove a named proxy listener (stops it first if running)
+#[tauri::command]
+pub async fn remove_proxy_listener(name: String, state: State<'_, ProxyServiceState>) -> AppResult<()> {
+    log::info!("Command: remove_proxy_listener (name: {})", name);
+    state.service().remove_listener(&name).await
+}
+
+/// List named proxy listeners
+#[tauri::command]
+pub async fn list_proxy_listeners(state: State<'_, ProxyServiceState>) -> AppResult<Vec<ProxyListenerInfo>> {
+    log::debug!("Command: list_proxy_listeners");
+    Ok(state.service().list_listeners().await)
+}
+
+/// Export proxy request metrics in Prometheus exposition format
+///
+/// Includes per-config request counts by status, latency histograms, token
+/// totals and the in-flight request gauge. Counters reset when the app restarts.
+#[tauri::command]
+pub async fn get_metrics_prometheus(state: State<'_, ProxyServiceState>) -> AppResult<String> {
+    log::debug!("Command: get_metrics_prometheus");
+    let active_requests = state.service().active_request_count().await;
+    Ok(crate::proxy::prometheus::render_metrics(active_requests))
+}
+
+/// Get per-host backend connection statistics
+///
+/// Each forwarded request currently opens its own connection, so `idle` and
+/// `hit_rate` stay at 0 and `pooling_enabled` is false; `active` / `opened` /
+/// `failed` show how connections to each backend are doing.
+#[tauri::command]
+pub fn get_connection_pool_stats() -> AppResult<ConnectionPoolStats> {
+    log::debug!("Command: get_connection_pool_stats");
+    Ok(connection_pool::stats())
+}
+
+/// Reset backend connection statistics and forget cached per-host state
+///
+/// Escape hatch after a backend-side change (new IP, fixed certificate,
+/// changed compression support) without restarting the app. Connections
+/// still serving a request are left to finish.
+#[tauri::command]
+pub fn clear_connection_pool() -> AppResult<ConnectionPoolClearResult> {
+    log::info!("Command: clear_connection_pool");
+    Ok(connection_pool::clear())
+}
+
+/// Query persisted metrics snapshots within a time range (oldest first)
+///
+/// Each snapshot holds the request counts, average latency and token totals
+/// for the period since the previous snapshot, so ranges can be summed directly.
+///
+/// # Arguments
+/// - `start` / `end`: RFC3339 bounds, unbounded when omitted
+/// - `limit`: Maximum number of snapshots (default 1000)
+#[tauri::command]
+pub fn get_metrics_snapshots(
+    start: Option<String>,
+    end: Option<String>,
+    limit: Option<i64>,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<Vec<MetricsSnapshot>> {
+    log::debug!("Command: get_metrics_snapshots (start: {:?}, end: {:?})", start, end);
+    pool.with_connection(|conn| {
+        MetricsSnapshotService::query(conn, start.as_deref(), end.as_deref(), limit.unwrap_or(1000))
+    })
+}
+
+/// Get the metrics snapshot interval in minutes (0 = disabled)
+#[tauri::command]
+pub fn get_metrics_snapshot_interval(pool: State<'_, Arc<DbPool>>) -> AppResult<i32> {
+    log::debug!("Command: get_metrics_snapshot_interval");
+    pool.with_connection(MetricsSnapshotService::get_interval)
+}
+
+/// Set the metrics snapshot interval in minutes (0 disables snapshots)
+///
+/// Takes effect after the current interval elapses; no restart required.
+#[tauri::command]
+pub fn set_metrics_snapshot_interval(interval_minutes: i32, pool: State<'_, Arc<DbPool>>) -> AppResult<()> {
+    log::info!("Command: set_metrics_snapshot_interval ({} min)", interval_minutes);
+    pool.with_connection(|conn| MetricsSnapshotService::set_interval(conn, interval_minutes))
+}
+
+/// Preview the exact request that would be sent to a backend
+///
+/// Runs the same transformation pipeline as forwarding (host/auth rewrite, path
+/// building, field filtering, model remapping, format conversion) without sending.
+///
+/// # Arguments
+/// - `config_id`: Target configuration
+/// - `sample_body`: Request body as sent by the client (Claude format)
+/// - `path`: Client request path (defaults to `/v1/messages`)
+/// - `headers`: Additional client request headers
+#[tauri::command]
+pub fn preview_forwarded_request(
+    config_id: i64,
+    sample_body: String,
+    path: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<ForwardedRequestPreview> {
+    log::debug!("Command: preview_forwarded_request (config_id: {})", config_id);
+
+    let mut header_map = hyper::HeaderMap::new();
+    header_map.insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("application/json"),
+    );
+    for (name, value) in headers.unwrap_or_default() {
+        let name = hyper::header::HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+            AppError::ValidationError {
+                field: "headers".to_string(),
+                message: format!("无效的请求头名称 {}: {}", name, e),
+            }
+        })?;
+        let value = hyper::header::HeaderValue::from_str(&value).map_err(|e| {
+            AppError::ValidationError {
+                field: "headers".to_string(),
+                message: format!("无效的请求头 {}: {}", name, e),
+            }
+        })?;
+        header_map.insert(name, value);
+    }
+
+    let path = path.unwrap_or_else(|| "/v1/messages".to_string());
+    RequestRouter::new(pool.inner().clone()).preview_request(
+        config_id,
+        &path,
+        header_map,
+        sample_body.as_bytes(),
+    )
+}
+
+/// Resolve the settings a configuration is actually forwarded with
+///
+/// Merges config fields, group settings, app settings, the running proxy's
+/// global settings and built-in defaults in the same order the router does,
+/// and marks where each value came from.
+///
+/// # Arguments
+/// - `config_id`: Target configuration
+#[tauri::command]
+pub async fn get_effective_config(
+    config_id: i64,
+    pool: State<'_, Arc<DbPool>>,
+    state: State<'_, ProxyServiceState>,
+) -> AppResult<EffectiveConfig> {
+    log::debug!("Command: get_effective_config (config_id: {})", config_id);
+
+    let proxy_config = state.service().server().config().await;
+    RequestRouter::new(pool.inner().clone()).effective_config(config_id, Some(&proxy_config))
+}
+
+/// Trace the next request forwarded to a configuration
+///
+/// Records DNS / connect / TLS / TTFB timings, per-chunk arrival times and
+/// the full request and response bodies into a standalone trace file, then
+/// disarms. Replaces any trace that has not been hit yet.
+///
+/// # Arguments
+/// - `config_id`: Configuration whose next request should be traced
+#[tauri::command]
+pub async fn trace_next_request(
+    config_id: i64,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<RequestTraceStatus> {
+    log::info!("Command: trace_next_request (config_id: {})", config_id);
+
+    pool.with_connection(|conn| ApiConfigService::get_config_by_id(conn, config_id))?;
+    request_trace::arm(config_id);
+    Ok(request_trace::status())
+}
+
+/// Cancel a request trace that has not been hit yet
+#[tauri::command]
+pub async fn cancel_request_trace() -> AppResult<RequestTraceStatus> {
+    log::info!("Command: cancel_request_trace");
+
+    request_trace::disarm();
+    Ok(request_trace::status())
+}
+
+/// Get the pending trace target and the most recently written trace file
+#[tauri::command]
+pub async fn get_request_trace_status() -> AppResult<RequestTraceStatus> {
+    Ok(request_trace::status())
+}
+
+/// Inject artificial latency into every request forwarded to a configuration
+///
+/// Testing aid for the high-latency auto-switch: pick a value above the
+/// group's latency threshold to trigger `SwitchReason::HighLatency`.
+/// The setting lives in memory only and is lost on restart.
+///
+/// # Arguments
+/// - `config_id`: Configuration to slow down
+/// - `latency_ms`: Delay added before connecting to the backend; 0 clears it
+#[tauri::command]
+pub async fn set_artificial_latency(
+    config_id: i64,
+    latency_ms: u64,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<Vec<ArtificialLatency>> {
+    log::info!("Command: set_artificial_latency (config_id: {}, latency_ms: {})", config_id, latency_ms);
+
+    if latency_ms > latency_injection::MAX_ARTIFICIAL_LATENCY_MS {
+        return Err(AppError::ValidationError {
+            field: "latency_ms".to_string(),
+            message: format!("Latency cannot exceed {}ms", latency_injection::MAX_ARTIFICIAL_LATENCY_MS),
+        });
+    }
+
+    pool.with_connection(|conn| ApiConfigService::get_config_by_id(conn, config_id))?;
+    latency_injection::set(config_id, latency_ms);
+    Ok(latency_injection::list())
+}
+
+/// Remove injected latency for one configuration, or for all when `config_id` is omitted
+#[tauri::command]
+pub async fn clear_artificial_latency(config_id: Option<i64>) -> AppResult<Vec<ArtificialLatency>> {
+    log::info!("Command: clear_artificial_latency (config_id: {:?})", config_id);
+
+    latency_injection::clear(config_id);
+    Ok(latency_injection::list())
+}
+
+/// List configurations that currently have injected latency
+#[tauri::command]
+pub async fn get_artificial_latencies() -> AppResult<Vec<ArtificialLatency>> {
+    Ok(latency_injection::list())
+}
+
+/// Load-test the proxy with synthetic concurrent requests
+///
+/// Requests go through the running proxy listener on a temporary session pinned
+/// to `config_id`, so they exercise the real forwarding path (connection pool,
+/// retries, failover) without touching global routing. The proxy has no request
+/// concurrency limit of its own; `concurrency` is capped at
+/// `MAX_LOAD_TEST_CONCURRENCY`. Each request is a `max_tokens: 1` message and is
+/// billed by the backend. Emits "load-test-progress" events.
+///
+/// # Arguments
+/// - `config_id`: Configuration the requests are routed to
+/// - `concurrency`: Requests in flight at once
+/// - `total_requests`: Number of requests to send
+#[tauri::command]
+pub async fn run_load_test(
+    config_id: i64,
+    concurrency: usize,
+    total_requests: usize,
+    window: Window,
+    state: State<'_, ProxyServiceState>,
+    pool: State<'_, Arc<DbPool>>,
+) -> AppResult<LoadTestReport> {
+    log::info!(
+        "Command: run_load_test (config_id: {}, concurrency: {}, total_requests: {})",
+        config_id,
+        concurrency,
+        total_requests
+    );
+
+    load_test::validate_params(concurrency, total_requests)?;
+    pool.with_connection(|conn| ApiConfigService::get_config_by_id(conn, config_id))?;
+
+    let status = state.service().get_status().await?;
+    if status.status != ProxyStatus::Running {
+        return Err(AppError::InvalidState {
+            message: "Proxy server is not running".to_string(),
+        });
+    }
+    let base_url = ClaudeConfigService::expected_base_url(&ProxyConfig {
+        host: status.listen_host,
+        port: status.listen_port as u16,
+    });
+
+    load_test::run_load_test(&base_url, config_id, concurrency, total_requests, |progress: LoadTestProgress| {
+        let _ = window.emit("load-test-progress", &progress);
+    })
+    .await
+}
+
 #[cfg(all(test, feature = "old_tests"))]
 mod tests {
     use super::*;
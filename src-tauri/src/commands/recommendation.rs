@@ -4,14 +4,15 @@
  *
  * Commands:
  * - load_recommended_services: 加载推荐服务列表
- * - refresh_recommended_services: 强制刷新推荐服务列表
+ * - refresh_recommended_services: 强制刷新推荐服务列表，返回数据源、数量与耗时
  */
 
 use crate::models::error::AppResult;
-use crate::models::recommended_service::RecommendedService;
-use crate::services::recommendation::RecommendationService;
+use crate::models::recommended_service::{RecommendationRefreshResult, RecommendedService};
+use crate::services::recommendation::{RecommendationService, DEFAULT_REMOTE_TIMEOUT};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::State;
 use tokio::sync::Mutex;
 
@@ -66,25 +67,37 @@ pub async fn load_recommended_services(
 
 /// 强制刷新推荐服务列表
 ///
+/// 等待刷新完成后返回；远程获取失败时错误信息写入结果的 `error` 字段，
+/// `source` 标明实际使用的数据源 (remote/local/embedded/cache)
+///
+/// # Arguments
+/// - `timeout_secs`: 远程获取超时时间（秒，默认 10）
+///
 /// # Returns
-/// - Vec<RecommendedService>: 刷新后的推荐服务列表
+/// - RecommendationRefreshResult: 刷新结果
 #[tauri::command]
 pub async fn refresh_recommended_services(
+    timeout_secs: Option<u64>,
     state: State<'_, RecommendationServiceState>,
-) -> AppResult<Vec<RecommendedService>> {
-    log::info!("Command: refresh_recommended_services");
+) -> AppResult<RecommendationRefreshResult> {
+    log::info!("Command: refresh_recommended_services (timeout_secs: {:?})", timeout_secs);
+
+    let timeout = timeout_secs
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_REMOTE_TIMEOUT);
 
     let service = state.service();
     let service_lock = service.lock().await;
+    let result = service_lock.refresh(timeout).await?;
 
-    // 清空缓存
-    service_lock.clear_cache();
-
-    // 重新加载
-    let services = service_lock.load_services(true).await?;
-
-    log::info!("成功刷新 {} 个推荐服务", services.len());
-    Ok(services)
+    log::info!(
+        "成功刷新 {} 个推荐服务 (source: {:?}, latency: {:?} ms)",
+        result.count,
+        result.source,
+        result.latency_ms
+    );
+    Ok(result)
 }
 
 #[cfg(test)]
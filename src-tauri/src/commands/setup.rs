@@ -2,7 +2,11 @@
 
 use crate::db::DbPool;
 use crate::models::node_environment::EnhancedEnvironmentStatus;
-use crate::services::{ClaudeInstaller, EnvironmentStatus, InstallOptions, InstallProgress};
+use crate::services::config_report::ConfigReportService;
+use crate::services::{
+    ClaudeInstaller, EnhancedEnvironmentDetector, EnvironmentStatus, InstallMethod, InstallOptions,
+    InstallPlan, InstallProgress, LlmEndpointReachability,
+};
 use std::sync::Arc;
 use tauri::{Emitter, State, Window};
 use rusqlite::params;
@@ -41,7 +45,7 @@ fn disabled_enhanced_environment_status() -> EnhancedEnvironmentStatus {
         git_bash_installed: false,
         ripgrep_installed: false,
         network_available: true,
-        detected_at: chrono::Utc::now().to_rfc3339(),
+        detected_at: crate::utils::time::now_rfc3339(),
         detection_duration_ms: 0,
     }
 }
@@ -67,11 +71,20 @@ pub async fn detect_environment() -> Result<EnvironmentStatus, String> {
 }
 
 /// 安装 Claude Code
+///
+/// - `dry_run`: 为 true 时只返回将要执行的安装计划，不修改系统
 #[tauri::command]
 pub async fn install_claude_code(
     options: InstallOptions,
+    dry_run: Option<bool>,
+    pool: State<'_, Arc<DbPool>>,
     window: Window,
-) -> Result<(), String> {
+) -> Result<Option<InstallPlan>, String> {
+    if dry_run.unwrap_or(false) {
+        let plan = ClaudeInstaller::plan(&options, default_node_environment_label(pool.inner())).await?;
+        return Ok(Some(plan));
+    }
+
     // 创建进度回调，通过事件发送进度
     let progress_callback = move |progress: InstallProgress| {
         let _ = window.emit("install-progress", &progress);
@@ -79,7 +92,8 @@ pub async fn install_claude_code(
 
     ClaudeInstaller::install(options, progress_callback)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    Ok(None)
 }
 
 /// 运行 claude doctor
@@ -123,6 +137,28 @@ pub async fn generate_environment_report() -> Result<String, String> {
     ))
 }
 
+/// 生成可分享的只读配置报告
+///
+/// - `format`: "markdown" (默认) 或 "json"
+/// - `include_balances`: 是否包含余额，默认不包含
+///
+/// 报告中的密钥仅保留后 4 位
+#[tauri::command]
+pub async fn generate_config_report(
+    format: Option<String>,
+    include_balances: Option<bool>,
+    pool: State<'_, Arc<DbPool>>,
+) -> Result<String, String> {
+    let report = ConfigReportService::generate(pool.inner(), include_balances.unwrap_or(false))
+        .map_err(|e| e.to_string())?;
+
+    match format.as_deref().unwrap_or("markdown") {
+        "markdown" | "md" => Ok(report.to_markdown()),
+        "json" => serde_json::to_string_pretty(&report).map_err(|e| e.to_string()),
+        other => Err(format!("不支持的报告格式: {}", other)),
+    }
+}
+
 /// 检查是否可以安装
 #[tauri::command]
 pub async fn check_can_install() -> Result<(bool, Vec<String>), String> {
@@ -166,6 +202,14 @@ pub async fn detect_environment_enhanced(
     Ok(disabled_enhanced_environment_status())
 }
 
+/// 检测常用大模型服务端点（Anthropic / Gemini / OpenAI）的网络可达性
+#[tauri::command]
+pub async fn check_llm_endpoints_reachability() -> Result<Vec<LlmEndpointReachability>, String> {
+    EnhancedEnvironmentDetector::check_llm_endpoints_reachability()
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// 设置默认 Node 环境
 #[tauri::command]
 pub async fn set_default_node_environment(
@@ -213,6 +257,12 @@ pub async fn set_default_node_environment(
 #[tauri::command]
 pub async fn get_default_node_environment(
     pool: State<'_, Arc<DbPool>>,
+) -> Result<Option<NodeEnvironmentConfig>, String> {
+    load_default_node_environment(pool.inner())
+}
+
+fn load_default_node_environment(
+    pool: &Arc<DbPool>,
 ) -> Result<Option<NodeEnvironmentConfig>, String> {
     pool.with_connection(|conn| {
         Ok(conn
@@ -248,10 +298,35 @@ pub struct NodeEnvironmentConfig {
 }
 
 /// 检查增强版是否可以安装
+///
+/// - `dry_run`: 为 true 时在提示信息中附带安装计划 (将执行的命令、目标版本、Node 环境)
+/// - `method`: 计划使用的安装方式，默认 NPM
 #[tauri::command]
 pub async fn check_can_install_enhanced(
     pool: State<'_, Arc<DbPool>>,
+    dry_run: Option<bool>,
+    method: Option<InstallMethod>,
 ) -> Result<(bool, Vec<String>), String> {
-    let _ = pool; // 环境检测已禁用
-    Ok((true, vec![]))
+    if !dry_run.unwrap_or(false) {
+        return Ok((true, vec![])); // 环境检测已禁用
+    }
+
+    let options = InstallOptions {
+        method: method.unwrap_or(InstallMethod::NPM),
+        auto_configure: false,
+        auto_backup: false,
+        auto_test: false,
+        auto_start_proxy: false,
+    };
+    match ClaudeInstaller::plan(&options, default_node_environment_label(pool.inner())).await {
+        Ok(plan) => Ok((true, plan.summary_lines())),
+        Err(e) => Ok((false, vec![e])),
+    }
+}
+
+/// 默认 Node 环境的描述 (用于安装计划)
+fn default_node_environment_label(pool: &Arc<DbPool>) -> Option<String> {
+    load_default_node_environment(pool).ok().flatten().map(|env| {
+        format!("{} {} ({})", env.manager_type, env.node_version, env.node_path)
+    })
 }
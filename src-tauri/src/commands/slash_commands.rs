@@ -3,7 +3,7 @@
 //! 提供 Claude Code 新版斜杠命令管理的 IPC 接口
 
 use crate::models::claude_advanced::{
-    CommandScope, SlashCommand, SlashCommandInfo, SlashCommandInput,
+    BrokenSlashCommand, CommandScope, SlashCommand, SlashCommandInfo, SlashCommandInput,
 };
 use crate::services::SlashCommandService;
 use std::path::PathBuf;
@@ -70,6 +70,24 @@ pub async fn read_slash_command_body(
     SlashCommandService::read_command_body(&name, scope, root).map_err(|e| e.to_string())
 }
 
+/// 检查无法读取的斜杠命令文件 (如链接的文件已被删除)
+#[tauri::command]
+pub async fn validate_slash_commands(
+    project_root: Option<String>,
+) -> Result<Vec<BrokenSlashCommand>, String> {
+    let root = project_root.map(PathBuf::from);
+    SlashCommandService::validate_commands(root).map_err(|e| e.to_string())
+}
+
+/// 删除无法读取的斜杠命令文件
+#[tauri::command]
+pub async fn prune_broken_slash_commands(
+    project_root: Option<String>,
+) -> Result<Vec<BrokenSlashCommand>, String> {
+    let root = project_root.map(PathBuf::from);
+    SlashCommandService::prune_broken_commands(root).map_err(|e| e.to_string())
+}
+
 /// 从旧版 skills 迁移到新版 commands
 #[tauri::command]
 pub async fn migrate_skills_to_commands() -> Result<Vec<String>, String> {
@@ -5,9 +5,13 @@
  * Each terminal session can be bound to a specific API config for routing.
  */
 
+use crate::db::DbPool;
+use crate::services::api_config::ApiConfigService;
 use crate::services::session_config::{SessionConfigEntry, SESSION_CONFIG_MAP};
 use crate::services::pty_manager::{PtyManagerState, PtySessionInfo, ClaudeCodeOptions};
+use crate::utils::time::to_rfc3339_utc;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tauri::State;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 
@@ -19,6 +23,12 @@ pub struct TerminalSessionInfo {
     pub name: Option<String>,
     pub created_at: String,
     pub last_used_at: String,
+    /// Seconds since the session last routed a request (what stale cleanup compares against)
+    #[serde(default)]
+    pub idle_secs: i64,
+    /// PID of the PTY shell process, if the session has one
+    #[serde(default)]
+    pub pid: Option<u32>,
 }
 
 impl From<(String, SessionConfigEntry)> for TerminalSessionInfo {
@@ -27,12 +37,31 @@ impl From<(String, SessionConfigEntry)> for TerminalSessionInfo {
             session_id,
             config_id: entry.config_id,
             name: entry.name,
-            created_at: entry.created_at.to_rfc3339(),
-            last_used_at: entry.last_used_at.to_rfc3339(),
+            created_at: to_rfc3339_utc(&entry.created_at),
+            last_used_at: to_rfc3339_utc(&entry.last_used_at),
+            idle_secs: chrono::Utc::now()
+                .signed_duration_since(entry.last_used_at)
+                .num_seconds(),
+            pid: None,
         }
     }
 }
 
+/// Ad-hoc session pinned to a config, with the URL to point a client at
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedSessionInfo {
+    pub session_id: String,
+    pub config_id: i64,
+    pub config_name: String,
+    /// Use as ANTHROPIC_BASE_URL: http://127.0.0.1:{port}/session/{session_id}
+    pub proxy_url: String,
+}
+
+/// Proxy URL that routes requests through the given session
+fn session_proxy_url(port: u16, session_id: &str) -> String {
+    format!("http://127.0.0.1:{}/session/{}", port, session_id)
+}
+
 /// Register a new terminal session with its proxy config
 ///
 /// # Arguments
@@ -70,16 +99,43 @@ pub async fn get_terminal_session(session_id: String) -> Result<Option<TerminalS
 }
 
 /// List all active terminal sessions
+///
+/// Includes idle time and the PTY process ID so stale sessions can be identified
 #[tauri::command]
-pub async fn list_terminal_sessions() -> Result<Vec<TerminalSessionInfo>, String> {
-    let sessions = SESSION_CONFIG_MAP
-        .list_sessions()
-        .into_iter()
-        .map(TerminalSessionInfo::from)
-        .collect();
+pub async fn list_terminal_sessions(
+    pty_state: State<'_, PtyManagerState>,
+) -> Result<Vec<TerminalSessionInfo>, String> {
+    let manager = pty_state.manager();
+    let mut sessions = Vec::new();
+    for entry in SESSION_CONFIG_MAP.list_sessions() {
+        let mut info = TerminalSessionInfo::from(entry);
+        info.pid = manager.session_pid(&info.session_id).await;
+        sessions.push(info);
+    }
     Ok(sessions)
 }
 
+/// Kill a terminal session
+///
+/// Terminates the PTY process (if any) and removes the session and its config pin.
+/// Use this for sessions the automatic stale cleanup misses.
+///
+/// # Arguments
+/// - `session_id`: Session to kill
+///
+/// # Returns
+/// - `false` if the session was not found
+#[tauri::command]
+pub async fn kill_terminal_session(
+    session_id: String,
+    pty_state: State<'_, PtyManagerState>,
+) -> Result<bool, String> {
+    log::info!("Killing terminal session: {}", session_id);
+
+    let manager = pty_state.manager();
+    manager.kill_session(&session_id).await
+}
+
 /// Remove a terminal session (called when terminal closes)
 #[tauri::command]
 pub async fn remove_terminal_session(session_id: String) -> Result<bool, String> {
@@ -123,8 +179,44 @@ pub async fn get_terminal_proxy_url(
     proxy_port: Option<u16>,
 ) -> Result<String, String> {
     let port = proxy_port.unwrap_or(25341);
-    let proxy_url = format!("http://127.0.0.1:{}/session/{}", port, session_id);
-    Ok(proxy_url)
+    Ok(session_proxy_url(port, &session_id))
+}
+
+/// Create an ad-hoc session pinned to a specific config
+///
+/// Registers a new session id in the session config map so requests sent to the
+/// returned URL use `config_id`, while global routing stays untouched. No PTY is
+/// started; the session is removed by `remove_terminal_session` or stale cleanup.
+///
+/// # Arguments
+/// - `config_id`: API config to pin the session to
+/// - `name`: Optional display name for the session
+#[tauri::command]
+pub async fn create_session_with_config(
+    config_id: i64,
+    name: Option<String>,
+    pool: State<'_, Arc<DbPool>>,
+    pty_state: State<'_, PtyManagerState>,
+) -> Result<PinnedSessionInfo, String> {
+    let config = pool
+        .with_connection(|conn| ApiConfigService::get_config_by_id(conn, config_id))
+        .map_err(|e| e.to_string())?;
+    let session_id = format!("adhoc-{}", uuid::Uuid::new_v4().simple());
+
+    log::info!(
+        "Creating ad-hoc session: {} -> config_id={} ({})",
+        session_id,
+        config_id,
+        config.name
+    );
+    SESSION_CONFIG_MAP.register(session_id.clone(), config_id, name);
+
+    Ok(PinnedSessionInfo {
+        proxy_url: session_proxy_url(pty_state.manager().proxy_port(), &session_id),
+        session_id,
+        config_id,
+        config_name: config.name,
+    })
 }
 
 /// Build environment variables for a terminal session
@@ -136,7 +228,7 @@ pub async fn build_terminal_env_vars(
     proxy_port: Option<u16>,
 ) -> Result<std::collections::HashMap<String, String>, String> {
     let port = proxy_port.unwrap_or(25341);
-    let proxy_url = format!("http://127.0.0.1:{}/session/{}", port, session_id);
+    let proxy_url = session_proxy_url(port, &session_id);
 
     let mut env_vars = std::collections::HashMap::new();
 
@@ -201,27 +293,18 @@ pub async fn create_pty_session(
     manager.create_session(
         session_id.clone(),
         config_id,
-        name.clone(),
-        work_dir.clone(),
+        name,
+        work_dir,
         rows.unwrap_or(24),
         cols.unwrap_or(80),
         app_handle,
     ).await?;
 
     // Return session info
-    Ok(PtySessionInfo {
-        session_id,
-        config_id,
-        name,
-        work_dir: work_dir.unwrap_or_else(|| {
-            dirs::home_dir()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_else(|| ".".to_string())
-        }),
-        running: true,
-        is_claude_code: false,
-        claude_options: None,
-    })
+    manager
+        .session_info(&session_id)
+        .await
+        .ok_or_else(|| format!("Session not found: {}", session_id))
 }
 
 /// Create a new Claude Code terminal session
@@ -260,24 +343,19 @@ pub async fn create_claude_code_session(
     manager.create_claude_code_session(
         session_id.clone(),
         config_id,
-        name.clone(),
-        Some(work_dir.clone()),
+        name,
+        Some(work_dir),
         rows.unwrap_or(24),
         cols.unwrap_or(80),
         app_handle,
-        claude_options.clone(),
+        claude_options,
     ).await?;
 
     // Return session info
-    Ok(PtySessionInfo {
-        session_id,
-        config_id,
-        name,
-        work_dir,
-        running: true,
-        is_claude_code: true,
-        claude_options: Some(claude_options),
-    })
+    manager
+        .session_info(&session_id)
+        .await
+        .ok_or_else(|| format!("Session not found: {}", session_id))
 }
 
 /// Write input to a PTY session
@@ -2,12 +2,15 @@
  * Claude API 请求转换为 Gemini API 请求
  */
 
-use super::claude_types::{ClaudeContent, ClaudeContentBlock, ClaudeImageSource, ClaudeMessageRole, ClaudeRequest};
+use super::claude_types::{ClaudeContent, ClaudeContentBlock, ClaudeImageSource, ClaudeMessageRole, ClaudeRequest, ClaudeSystem};
 #[cfg(test)]
 use super::claude_types::ClaudeMessage;
 use super::gemini_types::{GeminiContent, GeminiGenerationConfig, GeminiInlineData, GeminiPart, GeminiRequest};
 use crate::models::error::AppResult;
 
+/// Gemini generationConfig.stopSequences 允许的最大停止序列数
+const GEMINI_MAX_STOP_SEQUENCES: usize = 5;
+
 /// 将 Claude API 请求转换为 Gemini API 请求
 ///
 /// # 参数
@@ -60,18 +63,30 @@ pub fn convert_claude_request_to_gemini(
     }
 
     if let Some(ref stop_sequences) = claude_req.stop_sequences {
-        generation_config.stop_sequences = Some(stop_sequences.clone());
+        if stop_sequences.len() > GEMINI_MAX_STOP_SEQUENCES {
+            log::warn!(
+                "Gemini API 最多支持 {} 个停止序列，已截断 (原有 {} 个)",
+                GEMINI_MAX_STOP_SEQUENCES,
+                stop_sequences.len()
+            );
+        }
+        generation_config.stop_sequences = Some(
+            stop_sequences
+                .iter()
+                .take(GEMINI_MAX_STOP_SEQUENCES)
+                .cloned()
+                .collect(),
+        );
     }
 
-    // 处理 system 指令
-    let system_instruction = if let Some(ref system_text) = claude_req.system {
-        Some(GeminiContent {
-            role: None,
-            parts: vec![GeminiPart::text(system_text)],
-        })
-    } else {
-        None
-    };
+    // 处理 system 指令 (文本块逐个转换为 part；Gemini 的缓存需通过 cachedContent 显式创建，cache_control 不转发)
+    let system_instruction = claude_req.system.as_ref().map(|system| GeminiContent {
+        role: None,
+        parts: match system {
+            ClaudeSystem::Text(text) => vec![GeminiPart::text(text)],
+            ClaudeSystem::Blocks(blocks) => blocks.iter().map(|block| GeminiPart::text(&block.text)).collect(),
+        },
+    });
 
     let gemini_req = GeminiRequest {
         contents,
@@ -227,7 +242,7 @@ mod tests {
             top_p: None,
             top_k: None,
             stream: None,
-            system: Some("You are a helpful assistant".to_string()),
+            system: Some("You are a helpful assistant".to_string().into()),
             stop_sequences: None,
         };
 
@@ -244,6 +259,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_convert_with_system_blocks() {
+        let claude_req: ClaudeRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude-sonnet-4-5-20250929",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "system": [
+                {"type": "text", "text": "You are Claude Code."},
+                {"type": "text", "text": "Be concise.", "cache_control": {"type": "ephemeral"}}
+            ]
+        }))
+        .unwrap();
+
+        let (gemini_req, _) = convert_claude_request_to_gemini(&claude_req, "gemini-pro").unwrap();
+        let system = gemini_req.system_instruction.unwrap();
+        assert_eq!(system.parts.len(), 2);
+        assert_eq!(system.parts[0].text.as_deref(), Some("You are Claude Code."));
+        assert_eq!(system.parts[1].text.as_deref(), Some("Be concise."));
+    }
+
     #[test]
     fn test_convert_stream_request() {
         let claude_req = ClaudeRequest {
@@ -267,4 +301,36 @@ mod tests {
         let (_, path) = result.unwrap();
         assert_eq!(path, "/v1beta/models/gemini-pro:streamGenerateContent");
     }
+
+    #[test]
+    fn test_convert_sampling_params() {
+        let claude_req = ClaudeRequest {
+            model: "claude-sonnet-4-5-20250929".to_string(),
+            messages: vec![ClaudeMessage {
+                role: ClaudeMessageRole::User,
+                content: ClaudeContent::Text("Hello".to_string()),
+            }],
+            max_tokens: Some(256),
+            temperature: Some(0.5),
+            top_p: Some(0.9),
+            top_k: Some(32),
+            stream: None,
+            system: None,
+            stop_sequences: Some((1..=7).map(|i| format!("stop{}", i)).collect()),
+        };
+
+        let (gemini_req, _) = convert_claude_request_to_gemini(&claude_req, "gemini-pro").unwrap();
+        let config = gemini_req.generation_config.clone().unwrap();
+
+        assert_eq!(config.top_p, Some(0.9));
+        assert_eq!(config.top_k, Some(32));
+        assert_eq!(config.temperature, Some(0.5));
+        let stops = config.stop_sequences.unwrap();
+        assert_eq!(stops.len(), GEMINI_MAX_STOP_SEQUENCES);
+        assert_eq!(stops[0], "stop1");
+
+        let json = serde_json::to_value(&gemini_req).unwrap();
+        assert_eq!(json["generationConfig"]["topK"], 32);
+        assert_eq!(json["generationConfig"]["stopSequences"][0], "stop1");
+    }
 }
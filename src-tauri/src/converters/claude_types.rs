@@ -124,6 +124,50 @@ pub enum ClaudeContentBlock {
     },
 }
 
+/// Claude system 文本块 (可携带 prompt caching 的 cache_control)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "text")]
+pub struct ClaudeSystemBlock {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<serde_json::Value>,
+}
+
+/// Claude system 提示词：字符串或文本块数组
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ClaudeSystem {
+    /// 文本
+    Text(String),
+    /// 文本块数组
+    Blocks(Vec<ClaudeSystemBlock>),
+}
+
+impl ClaudeSystem {
+    /// 合并后的文本 (多个文本块以空行分隔)
+    pub fn text(&self) -> String {
+        match self {
+            ClaudeSystem::Text(text) => text.clone(),
+            ClaudeSystem::Blocks(blocks) => blocks
+                .iter()
+                .map(|block| block.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        }
+    }
+
+    /// 是否有文本块携带 cache_control
+    pub fn has_cache_control(&self) -> bool {
+        matches!(self, ClaudeSystem::Blocks(blocks) if blocks.iter().any(|b| b.cache_control.is_some()))
+    }
+}
+
+impl From<String> for ClaudeSystem {
+    fn from(text: String) -> Self {
+        ClaudeSystem::Text(text)
+    }
+}
+
 /// Claude 消息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaudeMessage {
@@ -141,7 +185,7 @@ pub struct ClaudeRequest {
     pub top_p: Option<f32>,
     pub top_k: Option<i32>,
     pub stream: Option<bool>,
-    pub system: Option<String>,
+    pub system: Option<ClaudeSystem>,
     pub stop_sequences: Option<Vec<String>>,
 }
 
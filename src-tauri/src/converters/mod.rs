@@ -16,6 +16,8 @@ pub mod gemini_to_claude;
 pub mod openai_claude;
 pub mod validator;
 pub mod model_mapper;
+pub mod token_estimator;
+pub mod tokenizer;
 
 // 注意：这些导出在 router.rs 中通过完整路径使用
 // 保留它们以供将来可能的直接使用
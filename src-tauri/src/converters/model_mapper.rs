@@ -604,6 +604,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_round_trip_mapping() {
+        let mapper = ModelMapper::new();
+
+        // 每个 Claude 模型映射到 OpenAI 后再映射回来应得到原模型
+        for claude in [
+            "claude-sonnet-4-5-20250929",
+            "claude-3-5-haiku-20241022",
+            "claude-3-opus-20240229",
+            "claude-3-sonnet-20240229",
+            "claude-3-haiku-20240307",
+        ] {
+            let openai = mapper.claude_to_openai(claude);
+            assert_eq!(mapper.openai_to_claude(&openai), claude, "via {}", openai);
+        }
+    }
+
     #[test]
     fn test_model_info() {
         let mapper = ModelMapper::new();
@@ -10,7 +10,7 @@
 
 use super::claude_types::{
     ClaudeContent, ClaudeContentBlock, ClaudeImageSource, ClaudeMessage, ClaudeMessageRole,
-    ClaudeRequest, ClaudeResponse, ClaudeStreamEvent, ClaudeToolResultContent, ClaudeUsage,
+    ClaudeRequest, ClaudeResponse, ClaudeStreamEvent, ClaudeSystem, ClaudeToolResultContent, ClaudeUsage,
 };
 use super::openai_types::{
     OpenAIChoice, OpenAIContentPart, OpenAIDelta, OpenAIImageUrl, OpenAIMessage,
@@ -18,6 +18,9 @@ use super::openai_types::{
     OpenAIToolCall, OpenAIUsage,
 };
 
+/// OpenAI API `stop` 参数允许的最大停止序列数
+const OPENAI_MAX_STOP_SEQUENCES: usize = 4;
+
 // ════════════════════════════════════════════════════════════════════════════
 // OpenAI → Claude 转换 (Codex/Cursor 访问 Claude API)
 // ════════════════════════════════════════════════════════════════════════════
@@ -141,9 +144,9 @@ pub fn convert_openai_request_to_claude(openai_req: &OpenAIRequest) -> ClaudeReq
         max_tokens: openai_req.max_tokens,
         temperature: openai_req.temperature,
         top_p: openai_req.top_p,
-        top_k: None,
+        top_k: openai_req.top_k,
         stream: openai_req.stream,
-        system: system_prompt,
+        system: system_prompt.map(ClaudeSystem::Text),
         stop_sequences: openai_req.stop.clone(),
     }
 }
@@ -156,7 +159,7 @@ fn convert_openai_content_to_claude(content: &OpenAIMessageContent) -> ClaudeCon
             let blocks: Vec<ClaudeContentBlock> = parts
                 .iter()
                 .filter_map(|part| match part {
-                    OpenAIContentPart::Text { text } => {
+                    OpenAIContentPart::Text { text, .. } => {
                         Some(ClaudeContentBlock::Text { text: text.clone() })
                     }
                     OpenAIContentPart::ImageUrl { image_url } => {
@@ -329,6 +332,25 @@ pub fn convert_claude_response_to_openai(
 // Claude → OpenAI 转换 (Claude Code 访问 OpenAI API)
 // ════════════════════════════════════════════════════════════════════════════
 
+/// 将 Claude system 提示词转换为 OpenAI system 消息内容
+///
+/// 文本块携带 cache_control 时保留为多部分内容 (每块一个 text part，附带 cache_control)，
+/// 否则合并为纯文本
+fn convert_claude_system_to_openai(system: &ClaudeSystem) -> OpenAIMessageContent {
+    match system {
+        ClaudeSystem::Blocks(blocks) if system.has_cache_control() => OpenAIMessageContent::Parts(
+            blocks
+                .iter()
+                .map(|block| OpenAIContentPart::Text {
+                    text: block.text.clone(),
+                    cache_control: block.cache_control.clone(),
+                })
+                .collect(),
+        ),
+        _ => OpenAIMessageContent::Text(system.text()),
+    }
+}
+
 /// 将 Claude 请求转换为 OpenAI 请求
 ///
 /// # Arguments
@@ -343,7 +365,7 @@ pub fn convert_claude_request_to_openai(claude_req: &ClaudeRequest) -> OpenAIReq
     if let Some(system) = &claude_req.system {
         openai_messages.push(OpenAIMessage {
             role: "system".to_string(),
-            content: Some(OpenAIMessageContent::Text(system.clone())),
+            content: Some(convert_claude_system_to_openai(system)),
             name: None,
             tool_calls: None,
             tool_call_id: None,
@@ -472,6 +494,22 @@ pub fn convert_claude_request_to_openai(claude_req: &ClaudeRequest) -> OpenAIReq
         }
     }
 
+    // OpenAI 官方 API 不支持 top_k，转发会被拒绝
+    if let Some(top_k) = claude_req.top_k {
+        log::warn!("OpenAI API 不支持 top_k 参数，已忽略 (top_k={})", top_k);
+    }
+
+    let stop = claude_req.stop_sequences.as_ref().map(|stops| {
+        if stops.len() > OPENAI_MAX_STOP_SEQUENCES {
+            log::warn!(
+                "OpenAI API 最多支持 {} 个停止序列，已截断 (原有 {} 个)",
+                OPENAI_MAX_STOP_SEQUENCES,
+                stops.len()
+            );
+        }
+        stops.iter().take(OPENAI_MAX_STOP_SEQUENCES).cloned().collect()
+    });
+
     OpenAIRequest {
         model: claude_req.model.clone(),
         messages: openai_messages,
@@ -479,7 +517,8 @@ pub fn convert_claude_request_to_openai(claude_req: &ClaudeRequest) -> OpenAIReq
         max_tokens: claude_req.max_tokens,
         stream: claude_req.stream,
         top_p: claude_req.top_p,
-        stop: claude_req.stop_sequences.clone(),
+        top_k: None,
+        stop,
         frequency_penalty: None,
         presence_penalty: None,
         n: None,
@@ -504,7 +543,10 @@ fn convert_claude_content_to_openai(content: &ClaudeContent) -> OpenAIMessageCon
                     .iter()
                     .filter_map(|block| match block {
                         ClaudeContentBlock::Text { text } => {
-                            Some(OpenAIContentPart::Text { text: text.clone() })
+                            Some(OpenAIContentPart::Text {
+                                text: text.clone(),
+                                cache_control: None,
+                            })
                         }
                         ClaudeContentBlock::Image { source } => {
                             Some(convert_claude_image_to_openai(source))
@@ -881,7 +923,7 @@ mod tests {
         let claude_req = convert_openai_request_to_claude(&openai_req);
 
         assert_eq!(claude_req.model, "gpt-4");
-        assert_eq!(claude_req.system, Some("You are helpful.".to_string()));
+        assert_eq!(claude_req.system, Some(ClaudeSystem::Text("You are helpful.".to_string())));
         assert_eq!(claude_req.messages.len(), 1);
         assert_eq!(claude_req.messages[0].role, ClaudeMessageRole::User);
         assert_eq!(claude_req.temperature, Some(0.7));
@@ -901,7 +943,7 @@ mod tests {
             top_p: None,
             top_k: None,
             stream: Some(false),
-            system: Some("You are helpful.".to_string()),
+            system: Some("You are helpful.".to_string().into()),
             stop_sequences: None,
         };
 
@@ -915,6 +957,32 @@ mod tests {
         assert_eq!(openai_req.max_tokens, Some(1000));
     }
 
+    #[test]
+    fn test_convert_claude_system_blocks_to_openai() {
+        let mut claude_req: ClaudeRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude-3-opus-20240229",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "system": [
+                {"type": "text", "text": "Rule 1"},
+                {"type": "text", "text": "Rule 2", "cache_control": {"type": "ephemeral"}}
+            ]
+        }))
+        .unwrap();
+
+        // 带 cache_control 时保留为多部分内容
+        let openai_req = convert_claude_request_to_openai(&claude_req);
+        let json = serde_json::to_value(&openai_req.messages[0]).unwrap();
+        assert_eq!(json["content"][0], serde_json::json!({"type": "text", "text": "Rule 1"}));
+        assert_eq!(json["content"][1]["cache_control"]["type"], "ephemeral");
+
+        // 没有缓存提示时合并为纯文本
+        if let Some(ClaudeSystem::Blocks(blocks)) = &mut claude_req.system {
+            blocks[1].cache_control = None;
+        }
+        let openai_req = convert_claude_request_to_openai(&claude_req);
+        assert_eq!(openai_req.messages[0].content_text(), "Rule 1\n\nRule 2");
+    }
+
     #[test]
     fn test_convert_claude_response_to_openai() {
         let claude_resp = ClaudeResponse {
@@ -1022,7 +1090,7 @@ mod tests {
 
         let claude_req = convert_openai_request_to_claude(&openai_req);
 
-        assert_eq!(claude_req.system, Some("Rule 1\n\nRule 2".to_string()));
+        assert_eq!(claude_req.system, Some(ClaudeSystem::Text("Rule 1\n\nRule 2".to_string())));
     }
 
     #[test]
@@ -1165,4 +1233,53 @@ mod tests {
         assert_eq!(openai_req.messages[2].role, "tool");
         assert_eq!(openai_req.messages[2].tool_call_id, Some("toolu_123".to_string()));
     }
+
+    #[test]
+    fn test_openai_sampling_params_to_claude() {
+        let openai_req = OpenAIRequest {
+            model: "gpt-4".to_string(),
+            messages: vec![OpenAIMessage::user("Hello")],
+            top_p: Some(0.9),
+            top_k: Some(40),
+            stop: Some(vec!["END".to_string()]),
+            ..Default::default()
+        };
+
+        let claude_req = convert_openai_request_to_claude(&openai_req);
+
+        assert_eq!(claude_req.top_p, Some(0.9));
+        assert_eq!(claude_req.top_k, Some(40));
+        assert_eq!(claude_req.stop_sequences, Some(vec!["END".to_string()]));
+    }
+
+    #[test]
+    fn test_claude_sampling_params_to_openai() {
+        let claude_req = ClaudeRequest {
+            model: "claude-3-opus-20240229".to_string(),
+            messages: vec![ClaudeMessage {
+                role: ClaudeMessageRole::User,
+                content: ClaudeContent::Text("Hello".to_string()),
+            }],
+            max_tokens: Some(100),
+            temperature: None,
+            top_p: Some(0.8),
+            top_k: Some(20),
+            stream: None,
+            system: None,
+            stop_sequences: Some((1..=6).map(|i| format!("stop{}", i)).collect()),
+        };
+
+        let openai_req = convert_claude_request_to_openai(&claude_req);
+
+        assert_eq!(openai_req.top_p, Some(0.8));
+        // OpenAI 不支持 top_k，不应转发
+        assert_eq!(openai_req.top_k, None);
+        // 停止序列按 OpenAI 上限截断
+        let stop = openai_req.stop.unwrap();
+        assert_eq!(stop.len(), OPENAI_MAX_STOP_SEQUENCES);
+        assert_eq!(stop[0], "stop1");
+
+        let json = serde_json::to_value(&convert_claude_request_to_openai(&claude_req)).unwrap();
+        assert!(json.get("top_k").is_none());
+    }
 }
@@ -39,8 +39,16 @@ pub struct OpenAIRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f32>,
 
-    /// 停止序列
+    /// Top-K 采样 (非标准扩展，vLLM 等兼容服务支持；OpenAI 官方 API 不接受)
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<i32>,
+
+    /// 停止序列 (OpenAI 允许单个字符串或字符串数组)
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_stop"
+    )]
     pub stop: Option<Vec<String>>,
 
     /// 频率惩罚 (-2.0 - 2.0)
@@ -64,6 +72,25 @@ pub struct OpenAIRequest {
     pub stream_options: Option<OpenAIStreamOptions>,
 }
 
+/// 解析 `stop` 字段: 兼容单个字符串与字符串数组
+fn deserialize_stop<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stop {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match Option::<Stop>::deserialize(deserializer)? {
+        Some(Stop::One(stop)) => Some(vec![stop]),
+        Some(Stop::Many(stops)) => Some(stops),
+        None => None,
+    })
+}
+
 /// OpenAI 流式选项
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct OpenAIStreamOptions {
@@ -149,7 +176,7 @@ impl OpenAIMessageContent {
                 parts
                     .iter()
                     .filter_map(|part| {
-                        if let OpenAIContentPart::Text { text } = part {
+                        if let OpenAIContentPart::Text { text, .. } = part {
                             Some(text.clone())
                         } else {
                             None
@@ -176,7 +203,12 @@ impl OpenAIMessageContent {
 pub enum OpenAIContentPart {
     /// 文本内容
     #[serde(rename = "text")]
-    Text { text: String },
+    Text {
+        text: String,
+        /// prompt caching 提示 (部分兼容 OpenAI 格式的服务支持，如 OpenRouter)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cache_control: Option<serde_json::Value>,
+    },
     /// 图片 URL
     #[serde(rename = "image_url")]
     ImageUrl { image_url: OpenAIImageUrl },
@@ -556,9 +588,11 @@ mod tests {
         let parts_content = OpenAIMessageContent::Parts(vec![
             OpenAIContentPart::Text {
                 text: "Part 1".to_string(),
+                cache_control: None,
             },
             OpenAIContentPart::Text {
                 text: "Part 2".to_string(),
+                cache_control: None,
             },
         ]);
         assert_eq!(parts_content.as_text(), "Part 1Part 2");
@@ -594,6 +628,31 @@ mod tests {
         assert!(json.contains("user"));
     }
 
+    #[test]
+    fn test_deserialize_openai_request_stop() {
+        let single: OpenAIRequest = serde_json::from_value(serde_json::json!({
+            "model": "gpt-4",
+            "messages": [],
+            "stop": "END"
+        }))
+        .unwrap();
+        assert_eq!(single.stop, Some(vec!["END".to_string()]));
+
+        let many: OpenAIRequest = serde_json::from_value(serde_json::json!({
+            "model": "gpt-4",
+            "messages": [],
+            "stop": ["a", "b"],
+            "top_k": 40
+        }))
+        .unwrap();
+        assert_eq!(many.stop, Some(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(many.top_k, Some(40));
+
+        let absent: OpenAIRequest =
+            serde_json::from_value(serde_json::json!({"model": "gpt-4", "messages": []})).unwrap();
+        assert_eq!(absent.stop, None);
+    }
+
     #[test]
     fn test_deserialize_openai_response() {
         let json = r#"{
@@ -0,0 +1,375 @@
+/**
+ * 请求 Token 估算
+ *
+ * 在发送请求前粗略估算输入 token 数，用于提示是否接近模型上下文上限
+ * 同时支持 Claude (/v1/messages) 与 OpenAI (/v1/chat/completions) 请求格式
+ *
+ * 估算规则 (字符启发式，误差通常在 ±15% 以内):
+ * - CJK 字符约 1 token/字
+ * - 其他文本按 ~4 字符/token 与 ~0.75 词/token 取较大值
+ * - 每张图片按固定 token 计
+ * - 每条消息附加少量格式开销
+ *
+ * 文本部分的计数委托给当前分词器 (见 tokenizer 模块)，默认即上述字符启发式
+ */
+
+use super::model_mapper::MODEL_MAPPER;
+use super::tokenizer::{active_tokenizer, Tokenizer};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 每条消息的格式开销 (role 与分隔符)
+const MESSAGE_OVERHEAD_TOKENS: u32 = 4;
+
+/// 单张图片的估算 token 数 (约 1.15 MP 图片的上限)
+const IMAGE_TOKENS: u32 = 1600;
+
+/// 每个工具定义的固定开销
+const TOOL_OVERHEAD_TOKENS: u32 = 10;
+
+/// Claude 模型未知时使用的上下文窗口
+const DEFAULT_CLAUDE_CONTEXT_TOKENS: u32 = 200_000;
+
+/// 超过上下文窗口该比例时给出接近上限的提示
+const NEAR_LIMIT_RATIO: f64 = 0.9;
+
+/// 请求格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestFormat {
+    Claude,
+    OpenAI,
+}
+
+/// 请求 token 估算结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenEstimate {
+    /// 识别出的请求格式
+    pub format: RequestFormat,
+
+    /// 请求中的模型
+    pub model: Option<String>,
+
+    /// 估算的输入 token 总数
+    pub input_tokens: u32,
+
+    /// 其中系统提示词部分
+    pub system_tokens: u32,
+
+    /// 其中消息部分
+    pub message_tokens: u32,
+
+    /// 其中工具定义部分
+    pub tool_tokens: u32,
+
+    /// 消息数量
+    pub message_count: usize,
+
+    /// 图片数量
+    pub image_count: usize,
+
+    /// 请求的最大输出 token 数
+    pub max_output_tokens: Option<u32>,
+
+    /// 模型上下文窗口 (未知模型为 None)
+    pub context_window: Option<u32>,
+
+    /// 输入占上下文窗口的比例
+    pub usage_ratio: Option<f64>,
+
+    /// 输入是否接近上下文上限
+    pub near_limit: bool,
+
+    /// 输入加最大输出是否超出上下文窗口
+    pub exceeds_limit: bool,
+}
+
+/// 估算请求体的输入 token 数
+pub fn estimate_request_tokens(body: &Value) -> Result<TokenEstimate, String> {
+    estimate_request_tokens_with(body, active_tokenizer().as_ref())
+}
+
+/// 使用指定分词器估算请求体的输入 token 数
+pub fn estimate_request_tokens_with(
+    body: &Value,
+    tokenizer: &dyn Tokenizer,
+) -> Result<TokenEstimate, String> {
+    let obj = body.as_object().ok_or("请求体必须是 JSON 对象")?;
+    let messages = obj
+        .get("messages")
+        .and_then(Value::as_array)
+        .ok_or("请求体缺少 messages 数组")?;
+
+    let format = detect_format(body);
+    let model = obj.get("model").and_then(Value::as_str).map(str::to_string);
+
+    let mut image_count = 0;
+    let mut system_tokens = obj
+        .get("system")
+        .map(|system| count_content(system, tokenizer, &mut image_count))
+        .unwrap_or(0);
+
+    let mut message_tokens = 0;
+    for message in messages {
+        let tokens = MESSAGE_OVERHEAD_TOKENS
+            + message
+                .get("content")
+                .map(|content| count_content(content, tokenizer, &mut image_count))
+                .unwrap_or(0)
+            + message
+                .get("tool_calls")
+                .map(|calls| count_json(calls, tokenizer))
+                .unwrap_or(0);
+
+        // OpenAI 格式的 system 消息计入系统提示词
+        if message.get("role").and_then(Value::as_str) == Some("system") {
+            system_tokens += tokens;
+        } else {
+            message_tokens += tokens;
+        }
+    }
+
+    let tool_tokens = obj
+        .get("tools")
+        .and_then(Value::as_array)
+        .map(|tools| {
+            tools
+                .iter()
+                .map(|tool| TOOL_OVERHEAD_TOKENS + count_json(tool, tokenizer))
+                .sum::<u32>()
+        })
+        .unwrap_or(0);
+
+    let input_tokens = system_tokens + message_tokens + tool_tokens;
+    let max_output_tokens = obj
+        .get("max_tokens")
+        .or_else(|| obj.get("max_completion_tokens"))
+        .and_then(Value::as_u64)
+        .map(|n| n as u32);
+    let context_window = model.as_deref().and_then(context_window_for);
+    let usage_ratio = context_window.map(|window| input_tokens as f64 / window as f64);
+
+    Ok(TokenEstimate {
+        format,
+        model,
+        input_tokens,
+        system_tokens,
+        message_tokens,
+        tool_tokens,
+        message_count: messages.len(),
+        image_count,
+        max_output_tokens,
+        context_window,
+        usage_ratio,
+        near_limit: usage_ratio.map(|r| r >= NEAR_LIMIT_RATIO).unwrap_or(false),
+        exceeds_limit: context_window
+            .map(|window| input_tokens + max_output_tokens.unwrap_or(0) > window)
+            .unwrap_or(false),
+    })
+}
+
+/// 根据字段特征识别请求格式
+fn detect_format(body: &Value) -> RequestFormat {
+    if body.get("system").is_some() {
+        return RequestFormat::Claude;
+    }
+
+    let messages = body.get("messages").and_then(Value::as_array);
+    let openai_like = messages
+        .map(|messages| {
+            messages.iter().any(|m| {
+                matches!(m.get("role").and_then(Value::as_str), Some("system") | Some("tool"))
+                    || m.get("tool_calls").is_some()
+                    || m
+                        .get("content")
+                        .and_then(Value::as_array)
+                        .map(|parts| {
+                            parts.iter().any(|p| {
+                                p.get("type").and_then(Value::as_str) == Some("image_url")
+                            })
+                        })
+                        .unwrap_or(false)
+            })
+        })
+        .unwrap_or(false);
+
+    if openai_like || body.get("max_completion_tokens").is_some() {
+        RequestFormat::OpenAI
+    } else {
+        RequestFormat::Claude
+    }
+}
+
+/// 模型上下文窗口
+fn context_window_for(model: &str) -> Option<u32> {
+    if let Some(tokens) = MODEL_MAPPER.get_max_context_tokens(model) {
+        return Some(tokens as u32);
+    }
+    if MODEL_MAPPER.is_claude_model(model) {
+        return Some(DEFAULT_CLAUDE_CONTEXT_TOKENS);
+    }
+    None
+}
+
+/// 统计 content 字段 (字符串或内容块数组)
+fn count_content(content: &Value, tokenizer: &dyn Tokenizer, image_count: &mut usize) -> u32 {
+    match content {
+        Value::String(text) => tokenizer.count_tokens(text),
+        Value::Array(blocks) => blocks
+            .iter()
+            .map(|block| count_block(block, tokenizer, image_count))
+            .sum(),
+        Value::Null => 0,
+        other => count_json(other, tokenizer),
+    }
+}
+
+/// 统计单个内容块
+fn count_block(block: &Value, tokenizer: &dyn Tokenizer, image_count: &mut usize) -> u32 {
+    match block.get("type").and_then(Value::as_str) {
+        Some("text") => block
+            .get("text")
+            .and_then(Value::as_str)
+            .map(|text| tokenizer.count_tokens(text))
+            .unwrap_or(0),
+        Some("image") | Some("image_url") => {
+            *image_count += 1;
+            IMAGE_TOKENS
+        }
+        Some("thinking") => block
+            .get("thinking")
+            .and_then(Value::as_str)
+            .map(|text| tokenizer.count_tokens(text))
+            .unwrap_or(0),
+        Some("tool_use") => {
+            block
+                .get("name")
+                .and_then(Value::as_str)
+                .map(|text| tokenizer.count_tokens(text))
+                .unwrap_or(0)
+                + block.get("input").map(|input| count_json(input, tokenizer)).unwrap_or(0)
+        }
+        Some("tool_result") => block
+            .get("content")
+            .map(|content| count_content(content, tokenizer, image_count))
+            .unwrap_or(0),
+        _ => match block {
+            Value::String(text) => tokenizer.count_tokens(text),
+            other => count_json(other, tokenizer),
+        },
+    }
+}
+
+/// 按序列化后的 JSON 文本估算 (工具定义、工具参数等结构化内容)
+fn count_json(value: &Value, tokenizer: &dyn Tokenizer) -> u32 {
+    match value {
+        Value::String(text) => tokenizer.count_tokens(text),
+        other => tokenizer.count_tokens(&other.to_string()),
+    }
+}
+
+/// 估算一段文本的 token 数
+pub fn estimate_text_tokens(text: &str) -> u32 {
+    let mut cjk_chars = 0u32;
+    let mut other_chars = 0u32;
+    for ch in text.chars() {
+        if is_cjk(ch) {
+            cjk_chars += 1;
+        } else {
+            other_chars += 1;
+        }
+    }
+
+    let words = text
+        .split(|c: char| c.is_whitespace() || is_cjk(c))
+        .filter(|word| !word.is_empty())
+        .count() as f64;
+    let by_chars = other_chars as f64 / 4.0;
+    let by_words = words / 0.75;
+
+    cjk_chars + by_chars.max(by_words).ceil() as u32
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x3040..=0x30FF // 平假名/片假名
+            | 0x3400..=0x4DBF // CJK 扩展 A
+            | 0x4E00..=0x9FFF // CJK 统一汉字
+            | 0xAC00..=0xD7AF // 韩文音节
+            | 0xF900..=0xFAFF // CJK 兼容汉字
+            | 0xFF00..=0xFFEF // 全角字符
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converters::tokenizer::HeuristicTokenizer;
+    use serde_json::json;
+
+    #[test]
+    fn test_estimate_text_tokens() {
+        assert_eq!(estimate_text_tokens(""), 0);
+        // 英文约 4 字符/token
+        let english = "The quick brown fox jumps over the lazy dog. ".repeat(20);
+        let tokens = estimate_text_tokens(&english);
+        assert!((200..=300).contains(&tokens), "tokens = {}", tokens);
+        // 中文约 1 token/字
+        assert_eq!(estimate_text_tokens("你好世界"), 4);
+    }
+
+    #[test]
+    fn test_estimate_claude_request() {
+        let body = json!({
+            "model": "claude-sonnet-4-5-20250929",
+            "max_tokens": 1024,
+            "system": [{"type": "text", "text": "You are a helpful assistant."}],
+            "messages": [
+                {"role": "user", "content": [
+                    {"type": "text", "text": "Describe this image."},
+                    {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "AAAA"}}
+                ]},
+                {"role": "assistant", "content": "It is a cat."}
+            ],
+            "tools": [{"name": "get_weather", "description": "Get weather", "input_schema": {"type": "object"}}]
+        });
+
+        let estimate = estimate_request_tokens(&body).unwrap();
+        assert_eq!(estimate.format, RequestFormat::Claude);
+        assert_eq!(estimate.message_count, 2);
+        assert_eq!(estimate.image_count, 1);
+        assert!(estimate.system_tokens > 0);
+        assert!(estimate.tool_tokens > TOOL_OVERHEAD_TOKENS);
+        assert!(estimate.message_tokens > IMAGE_TOKENS);
+        assert_eq!(estimate.context_window, Some(200_000));
+        assert_eq!(estimate.max_output_tokens, Some(1024));
+        assert!(!estimate.near_limit);
+        assert!(!estimate.exceeds_limit);
+    }
+
+    #[test]
+    fn test_estimate_openai_request_near_limit() {
+        let body = json!({
+            "model": "claude-3-5-haiku-20241022",
+            "max_completion_tokens": 20000,
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": "word ".repeat(150_000)}
+            ]
+        });
+
+        // 阈值按启发式规则构造，固定使用启发式分词器
+        let estimate = estimate_request_tokens_with(&body, &HeuristicTokenizer).unwrap();
+        assert_eq!(estimate.format, RequestFormat::OpenAI);
+        assert!(estimate.system_tokens > 0);
+        assert!(estimate.near_limit);
+        assert!(estimate.exceeds_limit);
+    }
+
+    #[test]
+    fn test_estimate_rejects_invalid_body() {
+        assert!(estimate_request_tokens(&json!([])).is_err());
+        assert!(estimate_request_tokens(&json!({"model": "x"})).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+/**
+ * 可插拔分词器
+ *
+ * 用于在后端未返回 usage 时本地估算 token 数
+ * - 默认使用字符启发式估算 (见 token_estimator)
+ * - 启用 `tokenizer` feature 后使用 tiktoken 兼容的 BPE 分词器 (cl100k_base)
+ * - 作为库使用时可通过 `set_tokenizer` 替换为自定义实现
+ */
+
+use super::token_estimator::estimate_text_tokens;
+use std::sync::{Arc, RwLock};
+
+/// 分词器接口
+pub trait Tokenizer: Send + Sync {
+    /// 分词器名称 (用于日志)
+    fn name(&self) -> &'static str;
+
+    /// 统计一段文本的 token 数
+    fn count_tokens(&self, text: &str) -> u32;
+}
+
+/// 字符启发式分词器 (无额外依赖)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HeuristicTokenizer;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn name(&self) -> &'static str {
+        "heuristic"
+    }
+
+    fn count_tokens(&self, text: &str) -> u32 {
+        estimate_text_tokens(text)
+    }
+}
+
+/// tiktoken 兼容分词器 (cl100k_base)
+#[cfg(feature = "tokenizer")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TiktokenTokenizer;
+
+#[cfg(feature = "tokenizer")]
+impl Tokenizer for TiktokenTokenizer {
+    fn name(&self) -> &'static str {
+        "cl100k_base"
+    }
+
+    fn count_tokens(&self, text: &str) -> u32 {
+        tiktoken_rs::cl100k_base_singleton().encode_ordinary(text).len() as u32
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref ACTIVE_TOKENIZER: RwLock<Arc<dyn Tokenizer>> = RwLock::new(default_tokenizer());
+}
+
+/// 按编译特性选择默认分词器
+fn default_tokenizer() -> Arc<dyn Tokenizer> {
+    #[cfg(feature = "tokenizer")]
+    {
+        Arc::new(TiktokenTokenizer)
+    }
+    #[cfg(not(feature = "tokenizer"))]
+    {
+        Arc::new(HeuristicTokenizer)
+    }
+}
+
+/// 当前使用的分词器
+pub fn active_tokenizer() -> Arc<dyn Tokenizer> {
+    ACTIVE_TOKENIZER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// 替换全局分词器
+pub fn set_tokenizer(tokenizer: Arc<dyn Tokenizer>) {
+    log::info!("Token 估算分词器已切换为 {}", tokenizer.name());
+    *ACTIVE_TOKENIZER.write().unwrap_or_else(|e| e.into_inner()) = tokenizer;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristic_tokenizer() {
+        let tokenizer = HeuristicTokenizer;
+        assert_eq!(tokenizer.count_tokens(""), 0);
+        assert_eq!(tokenizer.count_tokens("你好世界"), 4);
+    }
+
+    #[cfg(feature = "tokenizer")]
+    #[test]
+    fn test_tiktoken_tokenizer() {
+        let tokenizer = TiktokenTokenizer;
+        assert_eq!(tokenizer.count_tokens("hello world"), 2);
+    }
+}
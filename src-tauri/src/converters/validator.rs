@@ -248,8 +248,12 @@ impl OpenAIRequestValidator {
                     ));
                 }
 
-                // 验证内容不为空（除非是 function/tool 角色）
-                if msg.role != "function" && msg.role != "tool" && msg.content_text().is_empty()
+                // 验证内容不为空（除非是 function/tool 角色，或发起工具调用的 assistant 消息）
+                let has_tool_calls = msg.tool_calls.as_ref().is_some_and(|calls| !calls.is_empty());
+                if msg.role != "function"
+                    && msg.role != "tool"
+                    && !has_tool_calls
+                    && msg.content_text().is_empty()
                 {
                     errors.push(ValidationError::required(&format!(
                         "messages[{}].content",
@@ -280,6 +284,13 @@ impl OpenAIRequestValidator {
             }
         }
 
+        // 验证 top_k (非标准扩展)
+        if let Some(top_k) = request.top_k {
+            if top_k < 0 {
+                errors.push(ValidationError::out_of_range("top_k", Some(0), None));
+            }
+        }
+
         // 验证 n
         if let Some(n) = request.n {
             if n < 1 || n > 128 {
@@ -375,7 +386,7 @@ impl ValidationUtils {
 mod tests {
     use super::*;
     use crate::converters::claude_types::ClaudeContent;
-    use crate::converters::openai_types::OpenAIMessageContent;
+    use crate::converters::openai_types::{OpenAIMessageContent, OpenAIToolCall};
 
     #[test]
     fn test_claude_request_valid() {
@@ -456,6 +467,7 @@ mod tests {
             max_tokens: Some(1000),
             stream: None,
             top_p: None,
+            top_k: None,
             stop: None,
             frequency_penalty: None,
             presence_penalty: None,
@@ -482,6 +494,7 @@ mod tests {
             max_tokens: None,
             stream: None,
             top_p: None,
+            top_k: None,
             stop: None,
             frequency_penalty: None,
             presence_penalty: None,
@@ -494,6 +507,29 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_openai_request_tool_call_without_content() {
+        let assistant = |tool_calls: Option<Vec<OpenAIToolCall>>| OpenAIMessage {
+            role: "assistant".to_string(),
+            content: None,
+            name: None,
+            tool_calls,
+            tool_call_id: None,
+        };
+        let mut request: OpenAIRequest = serde_json::from_value(serde_json::json!({
+            "model": "gpt-4",
+            "messages": [{"role": "user", "content": "Weather?"}],
+        }))
+        .unwrap();
+
+        request.messages.push(assistant(Some(vec![OpenAIToolCall::new("call_1", "get_weather", "{}")])));
+        assert!(OpenAIRequestValidator::validate(&request).is_ok());
+
+        // 没有工具调用的 assistant 消息仍需要内容
+        request.messages.push(assistant(None));
+        assert!(OpenAIRequestValidator::validate(&request).is_err());
+    }
+
     #[test]
     fn test_validation_utils_model_name() {
         assert!(ValidationUtils::validate_model_name("gpt-4"));
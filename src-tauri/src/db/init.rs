@@ -4,7 +4,11 @@ use crate::models::error::{AppError, AppResult};
 use crate::utils::constants::default_proxy_port;
 use crate::utils::paths;
 use rusqlite::Connection;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 其他连接持有写锁时的等待时间，超时后才返回 "database is locked"
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// 获取数据库文件路径
 /// 数据库存储在应用数据目录: {app_data_dir}/database.db
@@ -38,11 +42,7 @@ pub fn initialize_database() -> AppResult<Connection> {
         })?
     };
 
-    // 启用外键约束
-    conn.execute("PRAGMA foreign_keys = ON;", [])
-        .map_err(|e| AppError::DatabaseError {
-            message: format!("启用外键约束失败: {}", e),
-        })?;
+    configure_connection(&conn)?;
 
     // 执行 schema.sql 创建表结构
     let schema_sql = include_str!("schema.sql");
@@ -64,6 +64,43 @@ pub fn initialize_database() -> AppResult<Connection> {
     Ok(conn)
 }
 
+/// 打开请求日志专用写入连接
+///
+/// 与主连接指向同一个数据库文件，WAL 模式下日志写入与主连接上的读取互不阻塞
+pub fn open_log_writer(db_path: &Path) -> AppResult<Connection> {
+    let conn = Connection::open(db_path).map_err(|e| AppError::DatabaseError {
+        message: format!("打开日志写入连接失败: {}", e),
+    })?;
+    configure_connection(&conn)?;
+    Ok(conn)
+}
+
+/// 设置连接参数: 外键约束、WAL 日志模式与忙等待超时
+///
+/// WAL 模式下读操作不会被写操作阻塞；多个连接同时写入时，
+/// 后到的写操作在 BUSY_TIMEOUT 内等待而不是立即失败
+fn configure_connection(conn: &Connection) -> AppResult<()> {
+    // 启用外键约束
+    conn.execute("PRAGMA foreign_keys = ON;", [])
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("启用外键约束失败: {}", e),
+        })?;
+
+    // 内存数据库不支持 WAL，会保持 memory 模式
+    let journal_mode: String = conn
+        .query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("启用 WAL 模式失败: {}", e),
+        })?;
+    log::debug!("数据库日志模式: {}", journal_mode);
+
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(|e| AppError::DatabaseError {
+        message: format!("设置忙等待超时失败: {}", e),
+    })?;
+
+    Ok(())
+}
+
 /// 插入默认数据
 /// 包括: AppSettings, ConfigGroup("未分组"), ProxyService
 fn insert_default_data(conn: &Connection) -> AppResult<()> {
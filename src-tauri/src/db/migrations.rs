@@ -4,7 +4,7 @@ use crate::models::error::{AppError, AppResult};
 use rusqlite::{Connection, OptionalExtension};
 
 /// 数据库版本
-const CURRENT_DB_VERSION: i32 = 17;
+const CURRENT_DB_VERSION: i32 = 52;
 
 /// 获取当前数据库版本
 pub fn get_db_version(conn: &Connection) -> AppResult<i32> {
@@ -129,6 +129,146 @@ pub fn migrate_database(conn: &Connection) -> AppResult<()> {
                 // v16 -> v17: 终端会话管理支持
                 migrate_v16_to_v17(conn)?;
             }
+            18 => {
+                // v17 -> v18: 分组级别的 SSE 保活事件过滤
+                migrate_v17_to_v18(conn)?;
+            }
+            19 => {
+                // v18 -> v19: 代理请求日志记录请求追踪 ID
+                migrate_v18_to_v19(conn)?;
+            }
+            20 => {
+                // v19 -> v20: 分组级别的请求体/响应体转换规则
+                migrate_v19_to_v20(conn)?;
+            }
+            21 => {
+                // v20 -> v21: 分组级别的重试策略覆盖
+                migrate_v20_to_v21(conn)?;
+            }
+            22 => {
+                // v21 -> v22: 流式响应完整性报告
+                migrate_v21_to_v22(conn)?;
+            }
+            23 => {
+                // v22 -> v23: 配置级别的连接/请求超时覆盖
+                migrate_v22_to_v23(conn)?;
+            }
+            24 => {
+                // v23 -> v24: 路由状态快照
+                migrate_v23_to_v24(conn)?;
+            }
+            25 => {
+                // v24 -> v25: 分组级别的客户端 IP 转发
+                migrate_v24_to_v25(conn)?;
+            }
+            26 => {
+                // v25 -> v26: 分组级别的 SSE 重连间隔
+                migrate_v25_to_v26(conn)?;
+            }
+            27 => {
+                // v26 -> v27: 配置级别的定时停用
+                migrate_v26_to_v27(conn)?;
+            }
+            28 => {
+                // v27 -> v28: 测试结果的连接阶段耗时分解
+                migrate_v27_to_v28(conn)?;
+            }
+            29 => {
+                // v28 -> v29: 应用级别的请求体日志开关
+                migrate_v28_to_v29(conn)?;
+            }
+            30 => {
+                // v29 -> v30: 请求日志的 token 用量
+                migrate_v29_to_v30(conn)?;
+            }
+            31 => {
+                // v30 -> v31: 请求日志定期清理策略
+                migrate_v30_to_v31(conn)?;
+            }
+            32 => {
+                // v31 -> v32: 配置级别的固定查询参数
+                migrate_v31_to_v32(conn)?;
+            }
+            33 => {
+                // v32 -> v33: 配置级别的请求字段过滤
+                migrate_v32_to_v33(conn)?;
+            }
+            34 => {
+                // v33 -> v34: 默认请求模型
+                migrate_v33_to_v34(conn)?;
+            }
+            35 => {
+                // v34 -> v35: 配置级别的健康检查探测方式
+                migrate_v34_to_v35(conn)?;
+            }
+            36 => {
+                // v35 -> v36: 配置级别的 metadata.user_id 处理方式
+                migrate_v35_to_v36(conn)?;
+            }
+            37 => {
+                // v36 -> v37: 完整请求体/响应体侧表
+                migrate_v36_to_v37(conn)?;
+            }
+            38 => {
+                // v37 -> v38: 配置级别的 anthropic-beta 请求头过滤
+                migrate_v37_to_v38(conn)?;
+            }
+            39 => {
+                // v38 -> v39: 代理指标定期快照
+                migrate_v38_to_v39(conn)?;
+            }
+            40 => {
+                // v39 -> v40: 分组金丝雀配置
+                migrate_v39_to_v40(conn)?;
+            }
+            41 => {
+                // v40 -> v41: 时间戳统一为 UTC RFC3339
+                migrate_v40_to_v41(conn)?;
+            }
+            42 => {
+                // v41 -> v42: 配置级别的 API 密钥来源
+                migrate_v41_to_v42(conn)?;
+            }
+            43 => {
+                // v42 -> v43: 配置级别的请求体压缩
+                migrate_v42_to_v43(conn)?;
+            }
+            44 => {
+                // v43 -> v44: 分组仅原生 Claude 透传
+                migrate_v43_to_v44(conn)?;
+            }
+            45 => {
+                // v44 -> v45: 请求日志采样策略
+                migrate_v44_to_v45(conn)?;
+            }
+            46 => {
+                // v45 -> v46: 分组混合提供商确认
+                migrate_v45_to_v46(conn)?;
+            }
+            47 => {
+                // v46 -> v47: 配置级别的 User-Agent 覆盖
+                migrate_v46_to_v47(conn)?;
+            }
+            48 => {
+                // v47 -> v48: 已弃用的 server_port 并入 server_url
+                migrate_v47_to_v48(conn)?;
+            }
+            49 => {
+                // v48 -> v49: 放宽切换日志原因约束
+                migrate_v48_to_v49(conn)?;
+            }
+            50 => {
+                // v49 -> v50: 持久化代理全局超时
+                migrate_v49_to_v50(conn)?;
+            }
+            51 => {
+                // v50 -> v51: 持久化流式响应上限
+                migrate_v50_to_v51(conn)?;
+            }
+            52 => {
+                // v51 -> v52: 记录配置最后健康时间
+                migrate_v51_to_v52(conn)?;
+            }
             _ => {
                 return Err(AppError::DatabaseError {
                     message: format!("未知的迁移版本: v{}", version),
@@ -734,6 +874,1254 @@ fn migrate_v16_to_v17(conn: &Connection) -> AppResult<()> {
     Ok(())
 }
 
+/// 迁移: v17 -> v18 - 分组级别的 SSE 保活事件过滤
+/// 为 ConfigGroup 添加 filter_sse_keepalive 字段
+fn migrate_v17_to_v18(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v17 -> v18 迁移: 添加分组级别的 SSE 保活事件过滤");
+
+    // 检查 filter_sse_keepalive 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ConfigGroup)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"filter_sse_keepalive".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v17 -> v18 迁移: filter_sse_keepalive 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute(
+        "ALTER TABLE ConfigGroup ADD COLUMN filter_sse_keepalive BOOLEAN NOT NULL DEFAULT 0",
+        [],
+    )
+    .map_err(|e| AppError::DatabaseError {
+        message: format!("添加 filter_sse_keepalive 字段失败: {}", e),
+    })?;
+
+    log::info!("v17 -> v18 迁移完成: 已添加 filter_sse_keepalive 字段");
+    Ok(())
+}
+
+/// 迁移: v18 -> v19 - 代理请求日志记录请求追踪 ID
+/// 为 ProxyRequestLog 添加 request_id 字段
+fn migrate_v18_to_v19(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v18 -> v19 迁移: 代理请求日志记录请求追踪 ID");
+
+    // 检查 request_id 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ProxyRequestLog)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"request_id".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v18 -> v19 迁移: request_id 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    // 加载迁移 SQL 文件
+    let migration_sql = include_str!("migrations/migration_v19_request_id.sql");
+
+    // 执行迁移 SQL
+    conn.execute_batch(migration_sql)
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("v18->v19 迁移失败: {}", e),
+        })?;
+
+    log::info!("v18 -> v19 迁移完成: 已添加 request_id 字段");
+    Ok(())
+}
+
+/// 迁移: v19 -> v20 - 分组级别的请求体/响应体转换规则
+/// 为 ConfigGroup 添加 body_transform 字段
+fn migrate_v19_to_v20(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v19 -> v20 迁移: 添加分组级别的请求体/响应体转换规则");
+
+    // 检查 body_transform 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ConfigGroup)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"body_transform".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v19 -> v20 迁移: body_transform 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute("ALTER TABLE ConfigGroup ADD COLUMN body_transform TEXT", [])
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 body_transform 字段失败: {}", e),
+        })?;
+
+    log::info!("v19 -> v20 迁移完成: 已添加 body_transform 字段");
+    Ok(())
+}
+
+/// 迁移: v20 -> v21 - 分组级别的重试策略覆盖
+/// 为 ConfigGroup 添加 retry_strategy_customized 字段
+///
+/// 重试参数列在 v5 已存在但从未生效,已被修改为非默认值的分组视为已自定义
+fn migrate_v20_to_v21(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v20 -> v21 迁移: 添加分组级别的重试策略覆盖");
+
+    // 检查 retry_strategy_customized 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ConfigGroup)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"retry_strategy_customized".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v20 -> v21 迁移: retry_strategy_customized 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    // 加载迁移 SQL 文件
+    let migration_sql = include_str!("migrations/migration_v21_group_retry_strategy.sql");
+
+    // 执行迁移 SQL
+    conn.execute_batch(migration_sql)
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("v20->v21 迁移失败: {}", e),
+        })?;
+
+    log::info!("v20 -> v21 迁移完成: 已添加 retry_strategy_customized 字段");
+    Ok(())
+}
+
+/// 迁移: v21 -> v22 - 流式响应完整性报告
+/// 为 ProxyRequestLog 添加 stream_integrity 与 stream_integrity_ok 字段
+fn migrate_v21_to_v22(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v21 -> v22 迁移: 流式响应完整性报告");
+
+    // 检查 stream_integrity 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ProxyRequestLog)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"stream_integrity".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v21 -> v22 迁移: stream_integrity 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    // 加载迁移 SQL 文件
+    let migration_sql = include_str!("migrations/migration_v22_stream_integrity.sql");
+
+    // 执行迁移 SQL
+    conn.execute_batch(migration_sql)
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("v21->v22 迁移失败: {}", e),
+        })?;
+
+    log::info!("v21 -> v22 迁移完成: 已添加流完整性字段");
+    Ok(())
+}
+
+/// 迁移: v22 -> v23 - 配置级别的连接超时 / 请求超时覆盖
+/// 为 ApiConfig 添加 connect_timeout_secs 与 request_timeout_secs 字段
+fn migrate_v22_to_v23(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v22 -> v23 迁移: 配置级别的连接/请求超时");
+
+    // 检查 connect_timeout_secs 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ApiConfig)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"connect_timeout_secs".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v22 -> v23 迁移: connect_timeout_secs 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    // 加载迁移 SQL 文件
+    let migration_sql = include_str!("migrations/migration_v23_config_timeouts.sql");
+
+    // 执行迁移 SQL
+    conn.execute_batch(migration_sql)
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("v22->v23 迁移失败: {}", e),
+        })?;
+
+    log::info!("v22 -> v23 迁移完成: 已添加连接/请求超时字段");
+    Ok(())
+}
+
+/// 迁移: v23 -> v24 - 路由状态快照
+/// 创建 RoutingSnapshot 表
+fn migrate_v23_to_v24(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v23 -> v24 迁移: 路由状态快照");
+
+    // 检查 RoutingSnapshot 表是否已存在
+    let table_exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type='table' AND name='RoutingSnapshot')",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查 RoutingSnapshot 表是否存在失败: {}", e),
+        })?;
+
+    if table_exists {
+        log::info!("v23 -> v24 迁移: RoutingSnapshot 表已存在，跳过迁移");
+        return Ok(());
+    }
+
+    // 加载迁移 SQL 文件
+    let migration_sql = include_str!("migrations/migration_v24_routing_snapshot.sql");
+
+    // 执行迁移 SQL
+    conn.execute_batch(migration_sql)
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("v23->v24 迁移失败: {}", e),
+        })?;
+
+    log::info!("v23 -> v24 迁移完成: 已创建 RoutingSnapshot 表");
+    Ok(())
+}
+
+/// 迁移: v24 -> v25 - 分组级别的客户端 IP 转发
+/// 为 ConfigGroup 添加 forward_client_ip 字段
+fn migrate_v24_to_v25(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v24 -> v25 迁移: 添加分组级别的客户端 IP 转发");
+
+    // 检查 forward_client_ip 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ConfigGroup)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"forward_client_ip".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v24 -> v25 迁移: forward_client_ip 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute(
+        "ALTER TABLE ConfigGroup ADD COLUMN forward_client_ip BOOLEAN NOT NULL DEFAULT 0",
+        [],
+    )
+    .map_err(|e| AppError::DatabaseError {
+        message: format!("添加 forward_client_ip 字段失败: {}", e),
+    })?;
+
+    log::info!("v24 -> v25 迁移完成: 已添加 forward_client_ip 字段");
+    Ok(())
+}
+
+/// 迁移: v25 -> v26 - 分组级别的 SSE 重连间隔
+/// 为 ConfigGroup 添加 sse_retry_ms 字段
+fn migrate_v25_to_v26(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v25 -> v26 迁移: 添加分组级别的 SSE 重连间隔");
+
+    // 检查 sse_retry_ms 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ConfigGroup)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"sse_retry_ms".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v25 -> v26 迁移: sse_retry_ms 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute("ALTER TABLE ConfigGroup ADD COLUMN sse_retry_ms INTEGER", [])
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 sse_retry_ms 字段失败: {}", e),
+        })?;
+
+    log::info!("v25 -> v26 迁移完成: 已添加 sse_retry_ms 字段");
+    Ok(())
+}
+
+/// 迁移: v26 -> v27 - 配置级别的定时停用
+/// 为 ApiConfig 添加 disabled_until 字段 (RFC3339，NULL 表示未定时停用)
+fn migrate_v26_to_v27(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v26 -> v27 迁移: 添加配置定时停用字段");
+
+    // 检查 disabled_until 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ApiConfig)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"disabled_until".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v26 -> v27 迁移: disabled_until 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute("ALTER TABLE ApiConfig ADD COLUMN disabled_until TEXT", [])
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 disabled_until 字段失败: {}", e),
+        })?;
+
+    log::info!("v26 -> v27 迁移完成: 已添加 disabled_until 字段");
+    Ok(())
+}
+
+/// 迁移: v27 -> v28 - 测试结果的连接阶段耗时分解
+/// 为 TestResult 添加 dns_ms / connect_ms / tls_ms / ttfb_ms 字段
+fn migrate_v27_to_v28(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v27 -> v28 迁移: 测试结果的连接阶段耗时分解");
+
+    // 检查 dns_ms 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(TestResult)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"dns_ms".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v27 -> v28 迁移: dns_ms 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    // 加载迁移 SQL 文件
+    let migration_sql = include_str!("migrations/migration_v28_test_timing_breakdown.sql");
+
+    // 执行迁移 SQL
+    conn.execute_batch(migration_sql)
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("v27->v28 迁移失败: {}", e),
+        })?;
+
+    log::info!("v27 -> v28 迁移完成: 已添加连接阶段耗时字段");
+    Ok(())
+}
+
+/// 迁移: v28 -> v29 - 请求体日志开关
+///
+/// 为 AppSettings 添加 log_bodies 字段（默认开启）
+fn migrate_v28_to_v29(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v28 -> v29 迁移: 添加请求体日志开关");
+
+    // 检查 log_bodies 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(AppSettings)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"log_bodies".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v28 -> v29 迁移: log_bodies 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute(
+        "ALTER TABLE AppSettings ADD COLUMN log_bodies BOOLEAN NOT NULL DEFAULT 1",
+        [],
+    )
+    .map_err(|e| AppError::DatabaseError {
+        message: format!("添加 log_bodies 字段失败: {}", e),
+    })?;
+
+    log::info!("v28 -> v29 迁移完成: 已添加 log_bodies 字段");
+    Ok(())
+}
+
+/// 迁移: v29 -> v30 - 请求日志的 token 用量
+/// 为 ProxyRequestLog 添加 input_tokens / output_tokens 及是否为本地估算的标记
+fn migrate_v29_to_v30(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v29 -> v30 迁移: 请求日志的 token 用量");
+
+    // 检查 input_tokens 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ProxyRequestLog)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"input_tokens".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v29 -> v30 迁移: input_tokens 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    // 加载迁移 SQL 文件
+    let migration_sql = include_str!("migrations/migration_v30_token_usage.sql");
+
+    // 执行迁移 SQL
+    conn.execute_batch(migration_sql)
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("v29->v30 迁移失败: {}", e),
+        })?;
+
+    log::info!("v29 -> v30 迁移完成: 已添加 token 用量字段");
+    Ok(())
+}
+
+/// 迁移: v30 -> v31 - 请求日志定期清理策略
+/// 为 AppSettings 添加日志保留条数、保留天数和清理间隔
+fn migrate_v30_to_v31(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v30 -> v31 迁移: 请求日志定期清理策略");
+
+    // 检查 log_retention_max_count 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(AppSettings)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"log_retention_max_count".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v30 -> v31 迁移: log_retention_max_count 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    // 加载迁移 SQL 文件
+    let migration_sql = include_str!("migrations/migration_v31_log_retention.sql");
+
+    // 执行迁移 SQL
+    conn.execute_batch(migration_sql)
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("v30->v31 迁移失败: {}", e),
+        })?;
+
+    log::info!("v30 -> v31 迁移完成: 已添加日志清理策略字段");
+    Ok(())
+}
+
+/// 迁移: v31 -> v32 - 配置级别的固定查询参数
+/// 为 ApiConfig 添加 extra_query 字段（如 `key=xxx`，NULL 表示不追加）
+fn migrate_v31_to_v32(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v31 -> v32 迁移: 添加固定查询参数");
+
+    // 检查 extra_query 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ApiConfig)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"extra_query".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v31 -> v32 迁移: extra_query 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute("ALTER TABLE ApiConfig ADD COLUMN extra_query TEXT", [])
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 extra_query 字段失败: {}", e),
+        })?;
+
+    log::info!("v31 -> v32 迁移完成: 已添加 extra_query 字段");
+    Ok(())
+}
+
+/// 迁移: v32 -> v33 - 配置级别的请求字段过滤
+/// 为 ApiConfig 添加 strip_request_fields 字段（JSON 数组，NULL 表示使用默认的 context_management）
+fn migrate_v32_to_v33(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v32 -> v33 迁移: 添加请求字段过滤列表");
+
+    // 检查 strip_request_fields 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ApiConfig)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"strip_request_fields".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v32 -> v33 迁移: strip_request_fields 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute("ALTER TABLE ApiConfig ADD COLUMN strip_request_fields TEXT", [])
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 strip_request_fields 字段失败: {}", e),
+        })?;
+
+    log::info!("v32 -> v33 迁移完成: 已添加 strip_request_fields 字段");
+    Ok(())
+}
+
+/// 迁移: v33 -> v34 - 默认请求模型
+/// 为 AppSettings 添加 default_request_model 字段（客户端未指定模型时注入，NULL 表示不注入）
+fn migrate_v33_to_v34(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v33 -> v34 迁移: 添加默认请求模型");
+
+    // 检查 default_request_model 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(AppSettings)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"default_request_model".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v33 -> v34 迁移: default_request_model 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute("ALTER TABLE AppSettings ADD COLUMN default_request_model TEXT", [])
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 default_request_model 字段失败: {}", e),
+        })?;
+
+    log::info!("v33 -> v34 迁移完成: 已添加 default_request_model 字段");
+    Ok(())
+}
+
+/// 迁移: v34 -> v35 - 健康检查探测方式
+/// 为 ApiConfig 添加 health_check_mode 字段（health / full / reachability / models，默认 health）
+fn migrate_v34_to_v35(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v34 -> v35 迁移: 添加健康检查探测方式");
+
+    // 检查 health_check_mode 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ApiConfig)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"health_check_mode".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v34 -> v35 迁移: health_check_mode 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute(
+        "ALTER TABLE ApiConfig ADD COLUMN health_check_mode TEXT NOT NULL DEFAULT 'health'",
+        [],
+    )
+    .map_err(|e| AppError::DatabaseError {
+        message: format!("添加 health_check_mode 字段失败: {}", e),
+    })?;
+
+    log::info!("v34 -> v35 迁移完成: 已添加 health_check_mode 字段");
+    Ok(())
+}
+
+/// 迁移: v35 -> v36 - 配置级别的 metadata.user_id 处理方式
+/// 为 ApiConfig 添加 metadata_user_id_policy 字段（passthrough / strip / anonymize，默认 passthrough）
+fn migrate_v35_to_v36(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v35 -> v36 迁移: 添加 metadata.user_id 处理方式");
+
+    // 检查 metadata_user_id_policy 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ApiConfig)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"metadata_user_id_policy".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v35 -> v36 迁移: metadata_user_id_policy 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute(
+        "ALTER TABLE ApiConfig ADD COLUMN metadata_user_id_policy TEXT NOT NULL DEFAULT 'passthrough'",
+        [],
+    )
+    .map_err(|e| AppError::DatabaseError {
+        message: format!("添加 metadata_user_id_policy 字段失败: {}", e),
+    })?;
+
+    log::info!("v35 -> v36 迁移完成: 已添加 metadata_user_id_policy 字段");
+    Ok(())
+}
+
+/// 迁移: v36 -> v37 - 完整请求体/响应体日志
+/// 创建 ProxyRequestBody 表，为 AppSettings 添加 log_full_bodies 字段（默认关闭）
+fn migrate_v36_to_v37(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v36 -> v37 迁移: 完整请求体/响应体日志");
+
+    // 加载迁移 SQL 文件 (CREATE TABLE IF NOT EXISTS，可重复执行)
+    let migration_sql = include_str!("migrations/migration_v37_proxy_request_body.sql");
+    conn.execute_batch(migration_sql)
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("v36->v37 迁移失败: {}", e),
+        })?;
+
+    // 检查 log_full_bodies 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(AppSettings)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"log_full_bodies".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if !column_exists {
+        conn.execute(
+            "ALTER TABLE AppSettings ADD COLUMN log_full_bodies BOOLEAN NOT NULL DEFAULT 0",
+            [],
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 log_full_bodies 字段失败: {}", e),
+        })?;
+    }
+
+    log::info!("v36 -> v37 迁移完成: 已创建 ProxyRequestBody 表并添加 log_full_bodies 字段");
+    Ok(())
+}
+
+/// 迁移: v37 -> v38 - 配置级别的 anthropic-beta 请求头过滤
+/// 为 ApiConfig 添加 anthropic_beta_filter 字段（JSON，NULL 表示原样转发）
+fn migrate_v37_to_v38(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v37 -> v38 迁移: 添加 anthropic-beta 请求头过滤");
+
+    // 检查 anthropic_beta_filter 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ApiConfig)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"anthropic_beta_filter".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v37 -> v38 迁移: anthropic_beta_filter 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute("ALTER TABLE ApiConfig ADD COLUMN anthropic_beta_filter TEXT", [])
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 anthropic_beta_filter 字段失败: {}", e),
+        })?;
+
+    log::info!("v37 -> v38 迁移完成: 已添加 anthropic_beta_filter 字段");
+    Ok(())
+}
+
+/// 迁移: v38 -> v39 - 代理指标定期快照
+/// 创建 MetricsSnapshot 表，为 AppSettings 添加 metrics_snapshot_interval_minutes 字段（默认 15 分钟，0 表示关闭）
+fn migrate_v38_to_v39(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v38 -> v39 迁移: 代理指标定期快照");
+
+    // 加载迁移 SQL 文件 (CREATE TABLE IF NOT EXISTS，可重复执行)
+    let migration_sql = include_str!("migrations/migration_v39_metrics_snapshot.sql");
+    conn.execute_batch(migration_sql)
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("v38->v39 迁移失败: {}", e),
+        })?;
+
+    // 检查 metrics_snapshot_interval_minutes 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(AppSettings)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"metrics_snapshot_interval_minutes".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if !column_exists {
+        conn.execute(
+            "ALTER TABLE AppSettings ADD COLUMN metrics_snapshot_interval_minutes INTEGER NOT NULL DEFAULT 15",
+            [],
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 metrics_snapshot_interval_minutes 字段失败: {}", e),
+        })?;
+    }
+
+    log::info!("v38 -> v39 迁移完成: 已创建 MetricsSnapshot 表并添加 metrics_snapshot_interval_minutes 字段");
+    Ok(())
+}
+
+/// 迁移: v39 -> v40 - 分组金丝雀配置
+/// 为 ConfigGroup 添加 canary_config_id 与 canary_percentage 字段（默认 0，即关闭）
+fn migrate_v39_to_v40(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v39 -> v40 迁移: 添加分组金丝雀配置");
+
+    // 检查 canary_config_id 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ConfigGroup)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"canary_config_id".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v39 -> v40 迁移: canary_config_id 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute_batch(
+        "ALTER TABLE ConfigGroup ADD COLUMN canary_config_id INTEGER;
+         ALTER TABLE ConfigGroup ADD COLUMN canary_percentage INTEGER NOT NULL DEFAULT 0;",
+    )
+    .map_err(|e| AppError::DatabaseError {
+        message: format!("添加金丝雀配置字段失败: {}", e),
+    })?;
+
+    log::info!("v39 -> v40 迁移完成: 已添加 canary_config_id / canary_percentage 字段");
+    Ok(())
+}
+
+/// 迁移: v40 -> v41 - 时间戳统一为 UTC RFC3339
+/// 将日志类表中带本地时区偏移或 SQLite `CURRENT_TIMESTAMP` 格式的旧时间戳改写为 UTC RFC3339，
+/// 使按字符串排序与比较的结果与时间顺序一致
+fn migrate_v40_to_v41(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v40 -> v41 迁移: 规范化时间戳");
+
+    let updated = crate::services::timestamp_audit::TimestampAuditService::normalize_stored(conn)?;
+
+    log::info!("v40 -> v41 迁移完成: 已规范化 {} 个时间戳", updated);
+    Ok(())
+}
+
+/// 迁移: v41 -> v42 - 配置级别的 API 密钥来源
+/// 为 ApiConfig 添加 api_key_mode 字段（stored / client，默认 stored 即使用配置中保存的密钥）
+fn migrate_v41_to_v42(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v41 -> v42 迁移: 添加 API 密钥来源");
+
+    // 检查 api_key_mode 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ApiConfig)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"api_key_mode".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v41 -> v42 迁移: api_key_mode 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute(
+        "ALTER TABLE ApiConfig ADD COLUMN api_key_mode TEXT NOT NULL DEFAULT 'stored'",
+        [],
+    )
+    .map_err(|e| AppError::DatabaseError {
+        message: format!("添加 api_key_mode 字段失败: {}", e),
+    })?;
+
+    log::info!("v41 -> v42 迁移完成: 已添加 api_key_mode 字段");
+    Ok(())
+}
+
+/// 迁移: v42 -> v43 - 配置级别的请求体压缩
+/// 为 ApiConfig 添加 request_compression 字段（off / auto / force，默认 off 即不压缩）
+fn migrate_v42_to_v43(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v42 -> v43 迁移: 添加请求体压缩方式");
+
+    // 检查 request_compression 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ApiConfig)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"request_compression".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v42 -> v43 迁移: request_compression 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute(
+        "ALTER TABLE ApiConfig ADD COLUMN request_compression TEXT NOT NULL DEFAULT 'off'",
+        [],
+    )
+    .map_err(|e| AppError::DatabaseError {
+        message: format!("添加 request_compression 字段失败: {}", e),
+    })?;
+
+    log::info!("v42 -> v43 迁移完成: 已添加 request_compression 字段");
+    Ok(())
+}
+
+/// 迁移: v43 -> v44 - 分组仅原生 Claude 透传
+/// 为 ConfigGroup 添加 native_claude_only 字段（默认 0，即允许格式转换）
+fn migrate_v43_to_v44(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v43 -> v44 迁移: 添加分组原生 Claude 透传开关");
+
+    // 检查 native_claude_only 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ConfigGroup)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"native_claude_only".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v43 -> v44 迁移: native_claude_only 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute(
+        "ALTER TABLE ConfigGroup ADD COLUMN native_claude_only BOOLEAN NOT NULL DEFAULT 0",
+        [],
+    )
+    .map_err(|e| AppError::DatabaseError {
+        message: format!("添加 native_claude_only 字段失败: {}", e),
+    })?;
+
+    log::info!("v43 -> v44 迁移完成: 已添加 native_claude_only 字段");
+    Ok(())
+}
+
+/// 迁移: v44 -> v45 - 请求日志采样策略
+/// 为 AppSettings 添加 log_sampling_mode / log_slow_threshold_ms / log_sample_percent 字段（默认记录全部请求）
+fn migrate_v44_to_v45(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v44 -> v45 迁移: 添加请求日志采样策略");
+
+    // 检查 log_sampling_mode 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(AppSettings)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"log_sampling_mode".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v44 -> v45 迁移: log_sampling_mode 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute_batch(
+        "ALTER TABLE AppSettings ADD COLUMN log_sampling_mode TEXT NOT NULL DEFAULT 'all';
+         ALTER TABLE AppSettings ADD COLUMN log_slow_threshold_ms INTEGER NOT NULL DEFAULT 10000;
+         ALTER TABLE AppSettings ADD COLUMN log_sample_percent INTEGER NOT NULL DEFAULT 100;",
+    )
+    .map_err(|e| AppError::DatabaseError {
+        message: format!("添加日志采样策略字段失败: {}", e),
+    })?;
+
+    log::info!("v44 -> v45 迁移完成: 已添加日志采样策略字段");
+    Ok(())
+}
+
+/// 迁移: v45 -> v46 - 分组混合提供商确认
+/// 为 ConfigGroup 添加 acknowledged_provider_mix 字段（用户确认过的提供商组合，默认 NULL）
+fn migrate_v45_to_v46(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v45 -> v46 迁移: 添加分组混合提供商确认");
+
+    // 检查 acknowledged_provider_mix 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ConfigGroup)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"acknowledged_provider_mix".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v45 -> v46 迁移: acknowledged_provider_mix 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute("ALTER TABLE ConfigGroup ADD COLUMN acknowledged_provider_mix TEXT", [])
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 acknowledged_provider_mix 字段失败: {}", e),
+        })?;
+
+    log::info!("v45 -> v46 迁移完成: 已添加 acknowledged_provider_mix 字段");
+    Ok(())
+}
+
+/// 迁移: v46 -> v47 - 配置级别的 User-Agent 覆盖
+/// 为 ApiConfig 添加 user_agent 字段（默认 NULL，即透传客户端的 User-Agent）
+fn migrate_v46_to_v47(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v46 -> v47 迁移: 添加 User-Agent 覆盖");
+
+    // 检查 user_agent 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ApiConfig)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"user_agent".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v46 -> v47 迁移: user_agent 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute("ALTER TABLE ApiConfig ADD COLUMN user_agent TEXT", [])
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 user_agent 字段失败: {}", e),
+        })?;
+
+    log::info!("v46 -> v47 迁移完成: 已添加 user_agent 字段");
+    Ok(())
+}
+
+/// 迁移: v47 -> v48 - 已弃用的 server_port 并入 server_url
+/// server_url 未指定端口且 server_port 为有意义的值时，将端口写入 server_url
+fn migrate_v47_to_v48(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v47 -> v48 迁移: 将已弃用的 server_port 并入 server_url");
+
+    let folded = crate::services::server_port_migration::ServerPortMigrationService::fold_ports(conn)?;
+
+    log::info!("v47 -> v48 迁移完成: 已迁移 {} 个配置的端口", folded);
+    Ok(())
+}
+
+/// 迁移: v48 -> v49 - 放宽切换日志原因约束
+/// 重建 SwitchLog 表，reason 的 CHECK 约束加入 retry_failed / unrecoverable_error /
+/// rate_limit_exceeded (原约束只允许最初的 5 种原因，重试失败等切换无法写入)
+fn migrate_v48_to_v49(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v48 -> v49 迁移: 放宽切换日志原因约束");
+
+    // 检查约束是否已包含新的切换原因
+    let table_sql: String = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'SwitchLog'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("读取 SwitchLog 表结构失败: {}", e),
+        })?;
+
+    if table_sql.contains("retry_failed") {
+        log::info!("v48 -> v49 迁移: 原因约束已包含新的切换原因，跳过迁移");
+        return Ok(());
+    }
+
+    // SQLite 无法修改 CHECK 约束，需要重建表；使用保存点保证失败时不留下半成品
+    let rebuild = conn.execute_batch(
+        "SAVEPOINT migrate_v49;
+         CREATE TABLE SwitchLog_v49 (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             switch_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+             reason TEXT NOT NULL CHECK(reason IN ('connection_failed', 'timeout', 'quota_exceeded', 'high_latency', 'manual',
+                                                   'retry_failed', 'unrecoverable_error', 'rate_limit_exceeded')),
+             source_config_id INTEGER,
+             target_config_id INTEGER NOT NULL,
+             group_id INTEGER NOT NULL,
+             is_cross_group BOOLEAN NOT NULL DEFAULT 0 CHECK(is_cross_group = 0),
+             latency_before_ms INTEGER CHECK(latency_before_ms >= 0),
+             latency_after_ms INTEGER CHECK(latency_after_ms >= 0),
+             error_message TEXT,
+             retry_count INTEGER NOT NULL DEFAULT 0,
+             error_type TEXT,
+             error_details TEXT,
+
+             FOREIGN KEY (group_id) REFERENCES ConfigGroup(id)
+                 ON DELETE RESTRICT
+                 ON UPDATE CASCADE,
+             FOREIGN KEY (source_config_id) REFERENCES ApiConfig(id)
+                 ON DELETE SET NULL
+                 ON UPDATE CASCADE,
+             FOREIGN KEY (target_config_id) REFERENCES ApiConfig(id)
+                 ON DELETE RESTRICT
+                 ON UPDATE CASCADE
+         );
+         INSERT INTO SwitchLog_v49 (
+             id, switch_at, reason, source_config_id, target_config_id, group_id,
+             is_cross_group, latency_before_ms, latency_after_ms, error_message,
+             retry_count, error_type, error_details
+         )
+         SELECT id, switch_at, reason, source_config_id, target_config_id, group_id,
+                is_cross_group, latency_before_ms, latency_after_ms, error_message,
+                retry_count, error_type, error_details
+         FROM SwitchLog;
+         DROP TABLE SwitchLog;
+         ALTER TABLE SwitchLog_v49 RENAME TO SwitchLog;
+         CREATE INDEX IF NOT EXISTS idx_switch_time ON SwitchLog(switch_at);
+         CREATE INDEX IF NOT EXISTS idx_switch_group ON SwitchLog(group_id);
+         CREATE INDEX IF NOT EXISTS idx_switch_source ON SwitchLog(source_config_id);
+         CREATE INDEX IF NOT EXISTS idx_switch_target ON SwitchLog(target_config_id);
+         CREATE INDEX IF NOT EXISTS idx_switch_log_error_type ON SwitchLog(error_type);
+         CREATE INDEX IF NOT EXISTS idx_switch_log_switch_at ON SwitchLog(switch_at DESC);
+         RELEASE migrate_v49;",
+    );
+
+    if let Err(e) = rebuild {
+        let _ = conn.execute_batch("ROLLBACK TO migrate_v49; RELEASE migrate_v49;");
+        return Err(AppError::DatabaseError {
+            message: format!("重建 SwitchLog 表失败: {}", e),
+        });
+    }
+
+    log::info!("v48 -> v49 迁移完成: 已重建 SwitchLog 表");
+    Ok(())
+}
+
+/// 迁移: v49 -> v50 - 持久化代理全局超时
+/// 为 AppSettings 添加 proxy_connect_timeout_secs 与 proxy_request_timeout_secs 字段（NULL 表示使用默认值）
+fn migrate_v49_to_v50(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v49 -> v50 迁移: 添加代理全局超时设置");
+
+    let columns: Vec<String> = conn
+        .prepare("PRAGMA table_info(AppSettings)")
+        .and_then(|mut stmt| {
+            let columns = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns)
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    for column in ["proxy_connect_timeout_secs", "proxy_request_timeout_secs"] {
+        if columns.iter().any(|c| c == column) {
+            continue;
+        }
+        conn.execute(
+            &format!("ALTER TABLE AppSettings ADD COLUMN {} INTEGER CHECK({} > 0)", column, column),
+            [],
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 {} 字段失败: {}", column, e),
+        })?;
+    }
+
+    log::info!("v49 -> v50 迁移完成: 已添加代理全局超时字段");
+    Ok(())
+}
+
+/// 迁移: v50 -> v51 - 持久化流式响应上限
+/// 为 AppSettings 添加 proxy_max_stream_duration_secs 与 proxy_max_stream_chunks 字段（NULL 表示使用默认值，0 表示不限制）
+fn migrate_v50_to_v51(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v50 -> v51 迁移: 添加流式响应上限设置");
+
+    let columns: Vec<String> = conn
+        .prepare("PRAGMA table_info(AppSettings)")
+        .and_then(|mut stmt| {
+            let columns = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns)
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    for column in ["proxy_max_stream_duration_secs", "proxy_max_stream_chunks"] {
+        if columns.iter().any(|c| c == column) {
+            continue;
+        }
+        conn.execute(
+            &format!("ALTER TABLE AppSettings ADD COLUMN {} INTEGER CHECK({} >= 0)", column, column),
+            [],
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 {} 字段失败: {}", column, e),
+        })?;
+    }
+
+    log::info!("v50 -> v51 迁移完成: 已添加流式响应上限字段");
+    Ok(())
+}
+
+/// 迁移: v51 -> v52 - 配置最后健康时间
+/// 为 ApiConfig 添加 last_healthy_at 字段，请求成功或健康检查成功时刷新，用于权重时间衰减
+fn migrate_v51_to_v52(conn: &Connection) -> AppResult<()> {
+    log::info!("执行 v51 -> v52 迁移: 添加配置最后健康时间");
+
+    // 检查 last_healthy_at 列是否已存在
+    let column_exists: bool = conn
+        .prepare("PRAGMA table_info(ApiConfig)")
+        .and_then(|mut stmt| {
+            let columns: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .collect();
+            Ok(columns.contains(&"last_healthy_at".to_string()))
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查列是否存在失败: {}", e),
+        })?;
+
+    if column_exists {
+        log::info!("v51 -> v52 迁移: last_healthy_at 列已存在，跳过迁移");
+        return Ok(());
+    }
+
+    conn.execute("ALTER TABLE ApiConfig ADD COLUMN last_healthy_at TEXT", [])
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("添加 last_healthy_at 字段失败: {}", e),
+        })?;
+
+    // 已有的最后成功时间作为初始值
+    conn.execute("UPDATE ApiConfig SET last_healthy_at = last_success_time", [])
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("初始化 last_healthy_at 失败: {}", e),
+        })?;
+
+    log::info!("v51 -> v52 迁移完成: 已添加 last_healthy_at 字段");
+    Ok(())
+}
+
 /// 回滚迁移 (仅用于开发/测试)
 /// 警告: 回滚可能导致数据丢失
 #[allow(dead_code)]
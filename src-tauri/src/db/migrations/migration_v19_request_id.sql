@@ -0,0 +1,6 @@
+-- 迁移 v18 -> v19: 代理请求日志记录请求追踪 ID
+-- 用于关联客户端日志、代理日志与后端支持工单
+
+ALTER TABLE ProxyRequestLog ADD COLUMN request_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_proxy_log_request_id ON ProxyRequestLog(request_id);
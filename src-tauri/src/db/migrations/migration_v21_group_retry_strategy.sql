@@ -0,0 +1,11 @@
+-- 迁移 v20 -> v21: 分组级别的重试策略覆盖
+-- 未自定义的分组使用全局默认重试策略
+
+ALTER TABLE ConfigGroup ADD COLUMN retry_strategy_customized BOOLEAN NOT NULL DEFAULT 0;
+
+-- 已修改过重试参数的分组视为已自定义
+UPDATE ConfigGroup SET retry_strategy_customized = 1
+WHERE retry_count != 3
+   OR retry_base_delay_ms != 2000
+   OR retry_max_delay_ms != 8000
+   OR rate_limit_delay_ms != 30000;
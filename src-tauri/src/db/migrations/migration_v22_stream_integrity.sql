@@ -0,0 +1,8 @@
+-- 迁移 v21 -> v22: 流式响应完整性报告
+-- 记录每个流式请求是否以 message_start 开始、message_stop 结束以及事件顺序异常
+-- stream_integrity_ok 为 NULL 表示非流式请求或非 Claude SSE 格式
+
+ALTER TABLE ProxyRequestLog ADD COLUMN stream_integrity TEXT;
+ALTER TABLE ProxyRequestLog ADD COLUMN stream_integrity_ok BOOLEAN;
+
+CREATE INDEX IF NOT EXISTS idx_proxy_log_stream_integrity ON ProxyRequestLog(stream_integrity_ok, config_id);
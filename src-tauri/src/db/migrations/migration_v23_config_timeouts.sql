@@ -0,0 +1,7 @@
+-- 迁移 v22 -> v23: 配置级别的连接超时 / 请求超时覆盖
+-- connect_timeout_secs: TCP 连接 + TLS 握手阶段的超时
+-- request_timeout_secs: 发送请求并等待响应头阶段的超时
+-- NULL 表示使用代理服务的全局设置
+
+ALTER TABLE ApiConfig ADD COLUMN connect_timeout_secs INTEGER CHECK(connect_timeout_secs IS NULL OR (connect_timeout_secs > 0 AND connect_timeout_secs <= 300));
+ALTER TABLE ApiConfig ADD COLUMN request_timeout_secs INTEGER CHECK(request_timeout_secs IS NULL OR (request_timeout_secs > 0 AND request_timeout_secs <= 3600));
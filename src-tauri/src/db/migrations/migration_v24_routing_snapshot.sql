@@ -0,0 +1,13 @@
+-- 迁移 v23 -> v24: 路由状态快照
+-- 保存当前激活的分组/配置以及各配置启用状态、各分组自动切换状态，便于一键恢复
+-- enabled_flags 为 JSON: {"configs": [...], "groups": [...]}
+
+CREATE TABLE IF NOT EXISTS RoutingSnapshot (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE CHECK(length(name) > 0 AND length(name) <= 100),
+    active_group_id INTEGER,
+    active_config_id INTEGER,
+    enabled_flags TEXT NOT NULL DEFAULT '{}',
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,10 @@
+-- 迁移 v27 -> v28: 测试结果的连接阶段耗时分解
+-- dns_ms: DNS 解析耗时
+-- connect_ms: TCP 连接耗时
+-- tls_ms: TLS 握手耗时 (HTTP 为 NULL)
+-- ttfb_ms: 发送请求到收到首字节的耗时
+
+ALTER TABLE TestResult ADD COLUMN dns_ms INTEGER CHECK(dns_ms IS NULL OR dns_ms >= 0);
+ALTER TABLE TestResult ADD COLUMN connect_ms INTEGER CHECK(connect_ms IS NULL OR connect_ms >= 0);
+ALTER TABLE TestResult ADD COLUMN tls_ms INTEGER CHECK(tls_ms IS NULL OR tls_ms >= 0);
+ALTER TABLE TestResult ADD COLUMN ttfb_ms INTEGER CHECK(ttfb_ms IS NULL OR ttfb_ms >= 0);
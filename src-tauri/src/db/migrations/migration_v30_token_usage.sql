@@ -0,0 +1,8 @@
+-- 迁移 v29 -> v30: 请求 token 用量
+-- 优先记录后端返回的 usage；后端未返回时记录本地分词器估算值，并以 *_estimated 标记
+-- input_tokens / output_tokens 为 NULL 表示无法统计（如非对话请求）
+
+ALTER TABLE ProxyRequestLog ADD COLUMN input_tokens INTEGER;
+ALTER TABLE ProxyRequestLog ADD COLUMN output_tokens INTEGER;
+ALTER TABLE ProxyRequestLog ADD COLUMN input_tokens_estimated BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE ProxyRequestLog ADD COLUMN output_tokens_estimated BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,8 @@
+-- 迁移 v30 -> v31: 请求日志定期清理策略
+-- log_retention_max_count: 最多保留的日志条数，NULL 表示不限制
+-- log_retention_max_age_days: 日志最长保留天数，NULL 表示不限制
+-- log_cleanup_interval_minutes: 后台清理任务的执行间隔
+
+ALTER TABLE AppSettings ADD COLUMN log_retention_max_count INTEGER DEFAULT 10000;
+ALTER TABLE AppSettings ADD COLUMN log_retention_max_age_days INTEGER;
+ALTER TABLE AppSettings ADD COLUMN log_cleanup_interval_minutes INTEGER NOT NULL DEFAULT 60;
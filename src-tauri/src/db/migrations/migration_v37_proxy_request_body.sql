@@ -0,0 +1,14 @@
+-- 迁移 v36 -> v37: 完整请求体/响应体侧表
+-- ProxyRequestLog 只保留截断后的内容 (8KB)，开启完整请求体日志后超出部分的完整内容 (有上限) 存于此表
+-- kind: request / response
+
+CREATE TABLE IF NOT EXISTS ProxyRequestBody (
+    log_id INTEGER NOT NULL,
+    kind TEXT NOT NULL CHECK(kind IN ('request', 'response')),
+    content TEXT NOT NULL,
+    -- 原始大小 (字节)，content 超过上限被截断时大于 content 长度
+    size INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (log_id, kind),
+    FOREIGN KEY (log_id) REFERENCES ProxyRequestLog(id) ON DELETE CASCADE
+);
@@ -0,0 +1,20 @@
+-- 迁移 v38 -> v39: 代理指标定期快照
+-- 内存中的请求指标在重启后丢失，后台任务按间隔将上一周期的增量写入此表
+-- config_metrics: 按配置拆分的指标 (JSON 数组)
+
+CREATE TABLE IF NOT EXISTS MetricsSnapshot (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    captured_at DATETIME NOT NULL,
+    -- 快照覆盖的时间长度 (秒)
+    period_secs INTEGER NOT NULL DEFAULT 0,
+    total_requests INTEGER NOT NULL DEFAULT 0,
+    successful_requests INTEGER NOT NULL DEFAULT 0,
+    failed_requests INTEGER NOT NULL DEFAULT 0,
+    streaming_requests INTEGER NOT NULL DEFAULT 0,
+    avg_response_time_ms REAL NOT NULL DEFAULT 0,
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    config_metrics TEXT NOT NULL DEFAULT '[]'
+);
+
+CREATE INDEX IF NOT EXISTS idx_metrics_snapshot_captured_at ON MetricsSnapshot(captured_at);
@@ -6,3 +6,13 @@ pub mod seed;
 // 重新导出常用类型和函数
 pub use init::initialize_database;
 pub use pool::DbPool;
+
+/// 创建已建表并执行全部迁移的内存数据库（用于测试）
+#[cfg(test)]
+pub fn test_db() -> rusqlite::Connection {
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+    conn.execute_batch(include_str!("schema.sql")).unwrap();
+    migrations::migrate_database(&conn).unwrap();
+    conn
+}
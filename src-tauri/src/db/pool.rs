@@ -7,9 +7,14 @@ use std::sync::{Arc, Mutex};
 /// 数据库连接池
 /// 使用简单的单连接模式,因为 SQLite 对并发写入的支持有限
 /// 对于更高并发需求,可以考虑使用 r2d2 或其他连接池库
+///
+/// 请求日志可以使用独立的写入连接 (见 `with_log_writer`)，配合 WAL 模式
+/// 日志写入不会占用主连接，转发时读取配置不必等待日志落盘
 #[derive(Clone)]
 pub struct DbPool {
     connection: Arc<Mutex<Connection>>,
+    /// 请求日志专用写入连接，未设置时与主连接共用
+    log_writer: Option<Arc<Mutex<Connection>>>,
 }
 
 impl DbPool {
@@ -17,9 +22,23 @@ impl DbPool {
     pub fn new(conn: Connection) -> Self {
         DbPool {
             connection: Arc::new(Mutex::new(conn)),
+            log_writer: None,
         }
     }
 
+    /// 设置请求日志专用写入连接
+    ///
+    /// 连接需指向同一个数据库文件并启用 WAL 模式 (见 `init::open_log_writer`)
+    pub fn with_log_writer(mut self, conn: Connection) -> Self {
+        self.log_writer = Some(Arc::new(Mutex::new(conn)));
+        self
+    }
+
+    /// 是否使用独立的日志写入连接
+    pub fn has_log_writer(&self) -> bool {
+        self.log_writer.is_some()
+    }
+
     /// 获取数据库连接
     /// 返回 Arc<Mutex<Connection>> 用于多线程访问
     pub fn get_connection(&self) -> Arc<Mutex<Connection>> {
@@ -63,6 +82,39 @@ impl DbPool {
         f(&conn)
     }
 
+    /// 执行请求日志写入操作
+    /// 使用日志专用连接，未设置时回退到主连接
+    pub fn with_log_connection<F, T>(&self, f: F) -> AppResult<T>
+    where
+        F: FnOnce(&Connection) -> AppResult<T>,
+    {
+        let Some(log_writer) = &self.log_writer else {
+            return self.with_connection(f);
+        };
+        let conn = log_writer.lock().map_err(|e| AppError::DatabaseError {
+            message: format!("获取日志写入连接锁失败: {}", e),
+        })?;
+        f(&conn)
+    }
+
+    /// 执行需要独占数据库的维护操作 (如 VACUUM)
+    ///
+    /// 同时持有日志写入连接的锁：否则日志连接的写入会在 VACUUM 期间
+    /// 等待 BUSY_TIMEOUT 后失败并被丢弃，持锁后日志写入改为等待维护完成
+    pub fn with_exclusive_connection<F, T>(&self, f: F) -> AppResult<T>
+    where
+        F: FnOnce(&Connection) -> AppResult<T>,
+    {
+        // 先锁日志连接再锁主连接，其他代码不会同时持有两把锁
+        let _log_writer = match &self.log_writer {
+            Some(log_writer) => Some(log_writer.lock().map_err(|e| AppError::DatabaseError {
+                message: format!("获取日志写入连接锁失败: {}", e),
+            })?),
+            None => None,
+        };
+        self.with_connection(f)
+    }
+
     /// 执行事务操作
     /// 自动处理 BEGIN/COMMIT/ROLLBACK
     pub fn transaction<F, T>(&self, f: F) -> AppResult<T>
@@ -124,7 +176,7 @@ impl DbPool {
     pub fn optimize(&self) -> AppResult<()> {
         log::info!("正在优化数据库...");
 
-        self.with_connection(|conn| {
+        self.with_exclusive_connection(|conn| {
             conn.execute("VACUUM", []).map_err(|e| AppError::DatabaseError {
                 message: format!("数据库优化失败: {}", e),
             })?;
@@ -298,6 +350,108 @@ mod tests {
         assert_eq!(count, 0);
     }
 
+    #[test]
+    fn test_log_writes_do_not_block_reads() {
+        use std::sync::mpsc;
+        use std::thread;
+
+        let db_error = |e: rusqlite::Error| AppError::DatabaseError { message: e.to_string() };
+        let path = std::env::temp_dir().join(format!("claude_code_proxy_log_writer_{}.db", std::process::id()));
+        let remove_files = || {
+            for suffix in ["", "-wal", "-shm"] {
+                let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+            }
+        };
+        remove_files();
+
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE config (id INTEGER PRIMARY KEY, name TEXT);
+             INSERT INTO config (name) VALUES ('c');
+             CREATE TABLE log (id INTEGER PRIMARY KEY, body TEXT);",
+        )
+        .unwrap();
+        let pool = DbPool::new(conn).with_log_writer(crate::db::init::open_log_writer(&path).unwrap());
+        assert!(pool.has_log_writer());
+
+        // 日志连接持有写事务期间，主连接仍可读取配置
+        let (locked_tx, locked_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let writer_pool = pool.clone();
+        let writer = thread::spawn(move || {
+            writer_pool
+                .with_log_connection(|conn| {
+                    conn.execute_batch("BEGIN IMMEDIATE; INSERT INTO log (body) VALUES ('held');")
+                        .map_err(db_error)?;
+                    locked_tx.send(()).unwrap();
+                    release_rx.recv().unwrap();
+                    conn.execute_batch("COMMIT").map_err(db_error)
+                })
+                .unwrap();
+        });
+        locked_rx.recv().unwrap();
+        let name: String = pool
+            .with_connection(|conn| conn.query_row("SELECT name FROM config", [], |row| row.get(0)).map_err(db_error))
+            .unwrap();
+        assert_eq!(name, "c");
+        release_tx.send(()).unwrap();
+        writer.join().unwrap();
+
+        // 并发写日志与读配置
+        let writers: Vec<_> = (0..4)
+            .map(|i| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    for _ in 0..50 {
+                        pool.with_log_connection(|conn| {
+                            conn.execute("INSERT INTO log (body) VALUES (?1)", [i.to_string()])
+                                .map_err(db_error)
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for _ in 0..200 {
+            let count: i64 = pool
+                .with_connection(|conn| {
+                    conn.query_row("SELECT COUNT(*) FROM config", [], |row| row.get(0)).map_err(db_error)
+                })
+                .unwrap();
+            assert_eq!(count, 1);
+        }
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let logged: i64 = pool
+            .with_connection(|conn| conn.query_row("SELECT COUNT(*) FROM log", [], |row| row.get(0)).map_err(db_error))
+            .unwrap();
+        assert_eq!(logged, 201);
+
+        drop(pool);
+        remove_files();
+    }
+
+    #[test]
+    fn test_exclusive_connection_holds_log_writer() {
+        let pool = DbPool::new(Connection::open_in_memory().unwrap())
+            .with_log_writer(Connection::open_in_memory().unwrap());
+        let log_writer = pool.log_writer.clone().unwrap();
+
+        pool.with_exclusive_connection(|_| {
+            assert!(log_writer.try_lock().is_err());
+            Ok(())
+        })
+        .unwrap();
+        assert!(log_writer.try_lock().is_ok());
+
+        // 没有日志写入连接时与 with_connection 相同
+        let pool = DbPool::new(Connection::open_in_memory().unwrap());
+        assert!(pool.with_exclusive_connection(|_| Ok(())).is_ok());
+    }
+
     #[test]
     fn test_get_stats() {
         let conn = Connection::open_in_memory().unwrap();
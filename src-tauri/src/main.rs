@@ -11,60 +11,78 @@ mod tray;
 mod utils;
 
 use commands::{
-    add_mcp_server, add_mcp_server_from_template, apply_config_to_env,
+    add_mcp_server, add_mcp_server_from_template, apply_config_to_env, get_config_env_exports,
     check_anthropic_env, check_app_updates, check_can_install, check_can_install_enhanced,
-    check_for_updates, clear_all_claude_code_backups, clear_anthropic_env,
-    clear_permissions_config, clear_switch_logs, cleanup_proxy_request_logs,
-    count_configs_in_group, create_api_config, create_claude_code_backup, create_config_group,
-    delete_api_config, delete_claude_code_backup, delete_config_group, detect_claude_code_path,
+    check_for_updates, check_llm_endpoints_reachability, clear_all_claude_code_backups,
+    clear_anthropic_env,
+    clear_permissions_config, clear_switch_logs, cleanup_proxy_request_logs, get_log_bodies_enabled,
+    get_log_retention_policy, set_log_retention_policy, get_log_sampling_policy, set_log_sampling_policy,
+    set_log_bodies_enabled, get_log_full_bodies_enabled, set_log_full_bodies_enabled,
+    count_configs_in_group, create_api_config, create_config_from_env_snippet, create_claude_code_backup, create_config_group,
+    delete_api_config, delete_claude_code_backup, delete_config_group, detect_claude_code_path, detect_claude_code_settings_paths,
     detect_environment, detect_environment_enhanced, disable_claude_code_proxy,
-    download_app_update, enable_claude_code_proxy, export_mcp_servers,
-    generate_environment_report, get_all_balance_info, get_all_proxy_request_logs,
+    download_app_update, list_available_rollbacks, rollback_app_update, enable_claude_code_proxy, export_mcp_servers,
+    generate_config_report, generate_environment_report, get_all_balance_info, get_all_proxy_request_logs,
     get_api_config, get_api_key, get_app_version, get_claude_code_proxy, get_claude_code_settings,
-    get_claude_version, get_config_group, get_default_node_environment, get_environment_variable,
+    verify_claude_code_integration, reconcile_claude_code_port,
+    get_claude_version, get_config_group, get_group_retry_strategy, get_default_node_environment,
+    get_environment_variable, reset_group_retry_strategy, set_group_latency_threshold, set_group_canary, update_group_retry_strategy,
+    check_group_provider_consistency, acknowledge_group_provider_mix,
     get_mcp_templates, get_permissions_config, get_provider_categories, get_provider_preset,
-    get_provider_presets_by_category, get_proxy_request_log_count, get_proxy_request_log_detail,
-    get_proxy_request_log_stats, get_proxy_request_logs, get_proxy_status,
-    get_recommended_provider_presets, get_switch_logs, get_test_results, get_health_check_status,
-    get_health_check_summaries, toggle_auto_health_check, import_mcp_servers,
-    install_claude_code, list_api_configs, list_claude_code_backups, list_config_groups,
-    list_environment_variables, list_mcp_servers, list_provider_presets,
-    load_recommended_services, open_release_page, preview_claude_code_backup, query_all_balances,
-    query_balance, quick_test_config_url, refresh_recommended_services,
+    get_provider_presets_by_category, get_proxy_request_log_count, get_proxy_request_log_detail, generate_curl_repro,
+    get_proxy_request_log_stats, get_proxy_request_logs, get_proxy_status, diagnose_port_conflict, get_stream_integrity_issues,
+    get_recommended_provider_presets, get_recent_switch_events, get_switch_logs, get_test_results, get_config_timing_breakdown, compare_test_runs, test_config_via_proxy, get_health_check_status,
+    export_switch_logs,
+    get_health_check_summaries, set_group_health_check_mode, toggle_auto_health_check, import_mcp_servers, inject_config_failure, get_retry_state, reset_retry_state,
+    install_claude_code, compact_database, check_clock_skew, list_api_configs, list_claude_code_backups, list_config_groups,
+    list_environment_variables, list_mcp_servers, list_provider_presets, validate_embedded_providers,
+    load_recommended_services, open_release_page, preview_claude_code_backup, query_all_balances, start_balance_watch, stop_balance_watch,
+    fetch_backend_models, normalize_server_url, detect_deprecated_server_port, query_balance, quick_test_config_url, refresh_recommended_services,
     remove_mcp_server, reorder_api_config, restore_claude_code_backup,
-    restore_claude_code_config, run_claude_doctor, run_health_check_now, set_config_enabled,
+    restore_claude_code_config, run_claude_doctor, run_health_check_now, set_config_enabled, set_group_model_overrides,
+    set_config_disabled_until, clear_config_disabled_until, reset_config_state,
     set_default_node_environment, set_environment_variable, set_environment_variables,
     start_health_check, start_proxy_service, stop_health_check, stop_proxy_service,
-    switch_proxy_config, switch_proxy_group, test_api_config, test_api_endpoints,
-    test_group_configs, test_mcp_server, toggle_auto_switch, uninstall_claude_code,
+    set_proxy_timeouts, set_proxy_stream_limits, save_routing_snapshot, restore_routing_snapshot, list_routing_snapshots,
+    delete_routing_snapshot, list_active_requests, get_metrics_prometheus, get_connection_pool_stats, clear_connection_pool, get_metrics_snapshots, get_metrics_snapshot_interval, set_metrics_snapshot_interval, preview_forwarded_request, get_effective_config, trace_next_request, cancel_request_trace, get_request_trace_status, set_artificial_latency, clear_artificial_latency, get_artificial_latencies, run_load_test, create_proxy_listener,
+    start_proxy_listener, stop_proxy_listener, remove_proxy_listener, list_proxy_listeners, switch_proxy_config, switch_proxy_group, test_api_config, test_api_endpoints,
+    test_group_configs, test_stale_configs, test_mcp_server, test_all_mcp_servers, diagnose_mcp_server, toggle_auto_switch, uninstall_claude_code,
     unset_environment_variable, update_api_config, update_claude_code, update_config_group,
-    update_mcp_server, update_permissions_config, verify_claude_installation,
+    update_mcp_server, update_permissions_config, validate_permissions_config, diff_permissions_config, validate_provider_preset, run_conversion_self_test, diagnose_last_failure, verify_claude_installation,
     check_system_configured, EnvironmentVariableState, HealthCheckState, ProxyServiceState,
     RecommendationServiceState,
     // 终端会话管理
     register_terminal_session, get_terminal_session,
-    list_terminal_sessions, remove_terminal_session, get_terminal_session_count,
+    list_terminal_sessions, remove_terminal_session, kill_terminal_session, get_terminal_session_count,
     cleanup_stale_terminal_sessions, clear_all_terminal_sessions, get_terminal_proxy_url,
-    build_terminal_env_vars,
+    create_session_with_config, build_terminal_env_vars,
     // PTY 管理
     create_pty_session, create_claude_code_session, pty_write_input, close_pty_session,
     list_pty_sessions, get_pty_session_count, pty_resize,
     // 模型映射配置
     list_model_mappings, get_model_mapping, create_model_mapping, update_model_mapping,
     delete_model_mapping, batch_delete_model_mappings, export_model_mappings,
-    import_model_mappings, reset_to_default_mappings, ModelMappingServiceState,
+    import_model_mappings, reset_to_default_mappings, estimate_request_tokens, get_default_request_model, set_default_request_model,
+    ModelMappingServiceState,
     // 斜杠命令管理 (新版 Claude Code 规范)
     list_slash_commands, get_slash_command, create_slash_command, update_slash_command,
     delete_slash_command, read_slash_command_body, migrate_skills_to_commands,
+    validate_slash_commands, prune_broken_slash_commands,
     // 项目上下文信息
     get_project_context, list_project_memories, read_memory_content,
     read_project_claude_md, save_project_claude_md, save_memory_content, delete_memory,
 };
 use db::{initialize_database, DbPool};
+use db::init::{get_db_path, open_log_writer};
 use services::balance_scheduler::BalanceScheduler;
+use services::config_reenable_scheduler::ConfigReenableScheduler;
+use services::balance_watch::BalanceWatchManager;
+use services::log_cleanup_scheduler::LogCleanupScheduler;
+use services::metrics_snapshot_scheduler::MetricsSnapshotScheduler;
 use services::model_mapping_service::ModelMappingService;
 use services::proxy_service::ProxyService;
 use services::PtyManagerState;
+use services::ProviderPresetService;
 use std::sync::Arc;
 use utils::logger;
 
@@ -79,7 +97,14 @@ fn main() {
 
     // 初始化数据库
     let conn = initialize_database().expect("无法初始化数据库");
-    let db_pool = Arc::new(DbPool::new(conn));
+    let db_pool = match get_db_path().and_then(|path| open_log_writer(&path)) {
+        Ok(log_writer) => DbPool::new(conn).with_log_writer(log_writer),
+        Err(e) => {
+            log::warn!("无法打开日志写入连接，请求日志将使用主连接: {}", e);
+            DbPool::new(conn)
+        }
+    };
+    let db_pool = Arc::new(db_pool);
 
     log::info!("数据库连接池已创建");
 
@@ -97,6 +122,17 @@ fn main() {
         3600, // 默认 TTL 1小时
     );
 
+    // 检查内嵌的 providers.json，避免网络故障时才发现回退不可用
+    let embedded_providers = ProviderPresetService::validate_embedded_providers();
+    if !embedded_providers.usable {
+        log::warn!(
+            "内嵌的 providers.json 不可用，远程获取失败时推荐服务无法回退: {}",
+            embedded_providers.errors.join("; ")
+        );
+    } else if !embedded_providers.warnings.is_empty() {
+        log::warn!("内嵌的 providers.json 存在问题: {}", embedded_providers.warnings.join("; "));
+    }
+
     log::info!("推荐服务已初始化");
 
     // 初始化环境变量服务
@@ -114,6 +150,18 @@ fn main() {
 
     log::info!("余额查询调度器已初始化");
 
+    // 初始化余额实时监视管理器
+    let balance_watch_manager = BalanceWatchManager::new(db_pool.clone());
+
+    // 初始化定时停用恢复调度器
+    let reenable_scheduler = Arc::new(ConfigReenableScheduler::new(db_pool.clone()));
+
+    // 初始化请求日志清理调度器
+    let log_cleanup_scheduler = Arc::new(LogCleanupScheduler::new(db_pool.clone()));
+
+    // 初始化代理指标快照调度器
+    let metrics_snapshot_scheduler = Arc::new(MetricsSnapshotScheduler::new(db_pool.clone()));
+
     // 初始化 PTY 管理器
     let pty_state = PtyManagerState::new(25341); // 默认代理端口
 
@@ -137,6 +185,7 @@ fn main() {
         .manage(health_check_state)
         .manage(pty_state)
         .manage(model_mapping_state)
+        .manage(balance_watch_manager)
         .setup(move |app| {
             let handle = app.handle().clone();
             // Set app handle for proxy service (for event emission)
@@ -160,6 +209,30 @@ fn main() {
                 }
             });
 
+            // 启动定时停用恢复调度器
+            let reenable_clone = reenable_scheduler.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = reenable_clone.start().await {
+                    log::error!("Failed to start config re-enable scheduler: {}", e);
+                }
+            });
+
+            // 启动请求日志清理调度器
+            let log_cleanup_clone = log_cleanup_scheduler.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = log_cleanup_clone.start().await {
+                    log::error!("Failed to start log cleanup scheduler: {}", e);
+                }
+            });
+
+            // 启动代理指标快照调度器
+            let metrics_snapshot_clone = metrics_snapshot_scheduler.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = metrics_snapshot_clone.start().await {
+                    log::error!("Failed to start metrics snapshot scheduler: {}", e);
+                }
+            });
+
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -171,6 +244,7 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             detect_claude_code_path,
+            detect_claude_code_settings_paths,
             list_claude_code_backups,
             create_claude_code_backup,
             restore_claude_code_backup,
@@ -181,43 +255,105 @@ fn main() {
             disable_claude_code_proxy,
             get_claude_code_proxy,
             get_claude_code_settings,
+            verify_claude_code_integration,
+            reconcile_claude_code_port,
             restore_claude_code_config,
             create_config_group,
             list_config_groups,
             update_config_group,
             delete_config_group,
             get_config_group,
+            get_group_retry_strategy,
+            update_group_retry_strategy,
+            reset_group_retry_strategy,
+            check_group_provider_consistency,
+            acknowledge_group_provider_mix,
+            set_group_latency_threshold,
+            set_group_canary,
             count_configs_in_group,
             create_api_config,
+            create_config_from_env_snippet,
             list_api_configs,
             get_api_config,
             update_api_config,
             delete_api_config,
             reorder_api_config,
             set_config_enabled,
+            set_group_model_overrides,
+            set_config_disabled_until,
+            clear_config_disabled_until,
+            reset_config_state,
             get_api_key,
             test_api_config,
             test_api_endpoints,
             quick_test_config_url,
+            normalize_server_url,
+            detect_deprecated_server_port,
+            fetch_backend_models,
             test_group_configs,
+            test_stale_configs,
             get_test_results,
+            get_config_timing_breakdown,
+            compare_test_runs,
+            validate_provider_preset,
+            run_conversion_self_test,
+            diagnose_last_failure,
+            test_config_via_proxy,
             query_balance,
             query_all_balances,
+            start_balance_watch,
+            stop_balance_watch,
             get_all_balance_info,
             start_proxy_service,
             stop_proxy_service,
             get_proxy_status,
+            diagnose_port_conflict,
             switch_proxy_group,
             switch_proxy_config,
+            set_proxy_timeouts,
+            set_proxy_stream_limits,
+            save_routing_snapshot,
+            restore_routing_snapshot,
+            list_routing_snapshots,
+            delete_routing_snapshot,
+            list_active_requests,
+            get_metrics_prometheus,
+            get_metrics_snapshots,
+            get_metrics_snapshot_interval,
+            set_metrics_snapshot_interval,
+            get_connection_pool_stats,
+            clear_connection_pool,
+            preview_forwarded_request,
+            get_effective_config,
+            trace_next_request,
+            cancel_request_trace,
+            get_request_trace_status,
+            set_artificial_latency,
+            clear_artificial_latency,
+            get_artificial_latencies,
+            run_load_test,
+            create_proxy_listener,
+            start_proxy_listener,
+            stop_proxy_listener,
+            remove_proxy_listener,
+            list_proxy_listeners,
             toggle_auto_switch,
             get_switch_logs,
+            get_recent_switch_events,
+            export_switch_logs,
             clear_switch_logs,
+            inject_config_failure,
+            get_retry_state,
+            reset_retry_state,
+            compact_database,
+            check_clock_skew,
             load_recommended_services,
             refresh_recommended_services,
             list_provider_presets,
             get_provider_preset,
             get_provider_presets_by_category,
             get_recommended_provider_presets,
+            validate_embedded_providers,
             get_provider_categories,
             list_environment_variables,
             get_environment_variable,
@@ -225,15 +361,26 @@ fn main() {
             unset_environment_variable,
             set_environment_variables,
             apply_config_to_env,
+            get_config_env_exports,
             check_anthropic_env,
             clear_anthropic_env,
             // 代理请求日志
             get_proxy_request_logs,
             get_all_proxy_request_logs,
             cleanup_proxy_request_logs,
+            get_log_retention_policy,
+            set_log_retention_policy,
+            get_log_sampling_policy,
+            set_log_sampling_policy,
             get_proxy_request_log_count,
             get_proxy_request_log_detail,
+            generate_curl_repro,
             get_proxy_request_log_stats,
+            get_stream_integrity_issues,
+            get_log_bodies_enabled,
+            set_log_bodies_enabled,
+            get_log_full_bodies_enabled,
+            set_log_full_bodies_enabled,
             // 健康检查
             start_health_check,
             stop_health_check,
@@ -241,17 +388,20 @@ fn main() {
             get_health_check_status,
             get_health_check_summaries,
             toggle_auto_health_check,
+            set_group_health_check_mode,
             // 环境设置和 Claude Code 安装
             detect_environment,
             detect_environment_enhanced,
             set_default_node_environment,
             get_default_node_environment,
             check_can_install_enhanced,
+            check_llm_endpoints_reachability,
             install_claude_code,
             run_claude_doctor,
             get_claude_version,
             verify_claude_installation,
             uninstall_claude_code,
+            generate_config_report,
             generate_environment_report,
             check_can_install,
             check_for_updates,
@@ -265,11 +415,15 @@ fn main() {
             get_mcp_templates,
             add_mcp_server_from_template,
             test_mcp_server,
+            test_all_mcp_servers,
+            diagnose_mcp_server,
             import_mcp_servers,
             export_mcp_servers,
             // Permissions 配置管理
             get_permissions_config,
             update_permissions_config,
+            validate_permissions_config,
+            diff_permissions_config,
             clear_permissions_config,
             // 斜杠命令管理 (新版 Claude Code 规范)
             list_slash_commands,
@@ -278,21 +432,27 @@ fn main() {
             update_slash_command,
             delete_slash_command,
             read_slash_command_body,
+            validate_slash_commands,
+            prune_broken_slash_commands,
             migrate_skills_to_commands,
             // 应用更新
             check_app_updates,
             get_app_version,
             download_app_update,
+            list_available_rollbacks,
+            rollback_app_update,
             open_release_page,
             // 终端会话管理
             register_terminal_session,
             get_terminal_session,
             list_terminal_sessions,
             remove_terminal_session,
+            kill_terminal_session,
             get_terminal_session_count,
             cleanup_stale_terminal_sessions,
             clear_all_terminal_sessions,
             get_terminal_proxy_url,
+            create_session_with_config,
             build_terminal_env_vars,
             // PTY 管理
             create_pty_session,
@@ -312,6 +472,9 @@ fn main() {
             export_model_mappings,
             import_model_mappings,
             reset_to_default_mappings,
+            estimate_request_tokens,
+            get_default_request_model,
+            set_default_request_model,
             // 项目上下文信息
             get_project_context,
             list_project_memories,
@@ -1,8 +1,29 @@
 #![allow(dead_code)]
 
+use crate::models::health_check::HealthCheckMode;
+use crate::utils::server_url::normalize_extra_query;
+use chrono::{DateTime, Local, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// 配置级别连接超时覆盖的上限（秒）
+pub const MAX_CONNECT_TIMEOUT_SECS: i32 = 300;
+
+/// 配置级别请求超时覆盖的上限（秒）
+pub const MAX_REQUEST_TIMEOUT_SECS: i32 = 3600;
+
+/// 自定义 User-Agent 的最大长度
+pub const MAX_USER_AGENT_LEN: usize = 512;
+
+/// 未配置时转发前从 Claude 请求体中移除的字段（官方 API 不支持）
+pub const DEFAULT_STRIP_REQUEST_FIELDS: &[&str] = &["context_management"];
+
+/// 转发时不允许移除的必需字段
+const REQUIRED_REQUEST_FIELDS: &[&str] = &["model", "messages"];
+
+/// 匿名化 metadata.user_id 时使用的固定值
+pub const ANONYMIZED_METADATA_USER_ID: &str = "anonymous";
+
 /// 默认值函数：返回 true
 fn default_true() -> bool {
     true
@@ -41,6 +62,199 @@ impl std::fmt::Display for ProviderType {
     }
 }
 
+/// 转发 Claude 请求时对 `metadata.user_id` 的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataUserIdPolicy {
+    /// 原样转发 (默认)
+    #[default]
+    Passthrough,
+    /// 移除该字段
+    Strip,
+    /// 替换为固定的匿名值
+    Anonymize,
+}
+
+impl std::str::FromStr for MetadataUserIdPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "passthrough" => Ok(MetadataUserIdPolicy::Passthrough),
+            "strip" => Ok(MetadataUserIdPolicy::Strip),
+            "anonymize" => Ok(MetadataUserIdPolicy::Anonymize),
+            _ => Err(format!("未知的 metadata.user_id 处理方式: {}", s)),
+        }
+    }
+}
+
+impl MetadataUserIdPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetadataUserIdPolicy::Passthrough => "passthrough",
+            MetadataUserIdPolicy::Strip => "strip",
+            MetadataUserIdPolicy::Anonymize => "anonymize",
+        }
+    }
+
+    /// 按策略改写请求体中的 `metadata.user_id`，返回是否有改动
+    pub fn apply(&self, body: &mut serde_json::Value) -> bool {
+        let Some(metadata) = body.get_mut("metadata").and_then(|m| m.as_object_mut()) else {
+            return false;
+        };
+        if !metadata.contains_key("user_id") {
+            return false;
+        }
+
+        match self {
+            MetadataUserIdPolicy::Passthrough => false,
+            MetadataUserIdPolicy::Strip => {
+                metadata.remove("user_id");
+                true
+            }
+            MetadataUserIdPolicy::Anonymize => {
+                metadata.insert(
+                    "user_id".to_string(),
+                    serde_json::Value::String(ANONYMIZED_METADATA_USER_ID.to_string()),
+                );
+                true
+            }
+        }
+    }
+}
+
+/// 转发时使用的 API 密钥来源
+///
+/// 两种方式互斥：要么用配置中保存的密钥覆盖客户端的认证头，要么原样透传客户端的认证头
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyMode {
+    /// 使用配置中保存的密钥替换 Authorization 头 (默认)
+    #[default]
+    Stored,
+    /// 透传客户端发送的 x-api-key / Authorization 头 (如客户端使用自己的官方密钥)
+    Client,
+}
+
+impl std::str::FromStr for ApiKeyMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stored" => Ok(ApiKeyMode::Stored),
+            "client" => Ok(ApiKeyMode::Client),
+            _ => Err(format!("未知的 API 密钥来源: {}", s)),
+        }
+    }
+}
+
+impl ApiKeyMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyMode::Stored => "stored",
+            ApiKeyMode::Client => "client",
+        }
+    }
+}
+
+/// 发往后端的请求体压缩方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestCompression {
+    /// 不压缩 (默认)
+    #[default]
+    Off,
+    /// 后端声明支持 gzip 请求体时压缩
+    Auto,
+    /// 总是压缩 (后端拒绝过压缩请求体时除外)
+    Force,
+}
+
+impl std::str::FromStr for RequestCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(RequestCompression::Off),
+            "auto" => Ok(RequestCompression::Auto),
+            "force" => Ok(RequestCompression::Force),
+            _ => Err(format!("未知的请求体压缩方式: {}", s)),
+        }
+    }
+}
+
+impl RequestCompression {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestCompression::Off => "off",
+            RequestCompression::Auto => "auto",
+            RequestCompression::Force => "force",
+        }
+    }
+}
+
+/// 转发时对 `anthropic-beta` 请求头的过滤方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnthropicBetaMode {
+    /// 原样转发 (默认)
+    #[default]
+    Passthrough,
+    /// 只转发列表中的 beta 标志 (列表为空时移除全部)
+    Allowlist,
+    /// 移除列表中的 beta 标志 (`*` 表示全部)
+    Denylist,
+}
+
+/// `anthropic-beta` 请求头过滤规则
+///
+/// 部分后端遇到不认识的 beta 标志会返回 400，可按配置移除这些标志
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnthropicBetaFilter {
+    #[serde(default)]
+    pub mode: AnthropicBetaMode,
+    /// beta 标志列表 (不区分大小写)
+    #[serde(default)]
+    pub flags: Vec<String>,
+}
+
+impl AnthropicBetaFilter {
+    /// 是否原样转发
+    pub fn is_passthrough(&self) -> bool {
+        self.mode == AnthropicBetaMode::Passthrough
+    }
+
+    /// 验证 beta 标志列表
+    pub fn validate(&self) -> Result<(), String> {
+        for flag in &self.flags {
+            let flag = flag.trim();
+            if flag.is_empty() {
+                return Err("beta 标志不能为空".to_string());
+            }
+            if flag.contains(',') || flag.contains(char::is_whitespace) {
+                return Err(format!("beta 标志不能包含逗号或空白: {}", flag));
+            }
+        }
+        Ok(())
+    }
+
+    fn listed(&self, flag: &str) -> bool {
+        self.flags
+            .iter()
+            .map(|f| f.trim())
+            .any(|f| f == "*" || f.eq_ignore_ascii_case(flag))
+    }
+
+    /// 按规则过滤 beta 标志，返回 (转发的标志, 移除的标志)
+    pub fn split<'a>(&self, flags: impl IntoIterator<Item = &'a str>) -> (Vec<String>, Vec<String>) {
+        flags.into_iter().map(str::to_string).partition(|flag| match self.mode {
+            AnthropicBetaMode::Passthrough => true,
+            AnthropicBetaMode::Allowlist => self.listed(flag),
+            AnthropicBetaMode::Denylist => !self.listed(flag),
+        })
+    }
+}
+
 /// 供应商分类
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -218,6 +432,53 @@ pub struct ApiConfig {
     /// 余额查询间隔（秒）
     pub balance_check_interval_sec: Option<i32>,
 
+    /// 连接超时（秒，覆盖代理全局设置，仅作用于 TCP 连接 + TLS 握手）
+    #[serde(default)]
+    pub connect_timeout_secs: Option<i32>,
+    /// 请求超时（秒，覆盖代理全局设置，作用于发送请求并等待响应）
+    #[serde(default)]
+    pub request_timeout_secs: Option<i32>,
+
+    /// 定时停用截止时间（RFC3339），到期前视为不可用，到期后由调度器自动恢复
+    #[serde(default)]
+    pub disabled_until: Option<String>,
+
+    /// 转发时追加的固定查询参数（如 `key=xxx`），与客户端同名参数以此为准
+    #[serde(default)]
+    pub extra_query: Option<String>,
+
+    /// 透传转发前从请求体中移除的顶层字段（JSON 数组），为空时使用 DEFAULT_STRIP_REQUEST_FIELDS
+    #[serde(default)]
+    pub strip_request_fields: Option<String>,
+
+    /// 定时健康检查的探测方式
+    #[serde(default)]
+    pub health_check_mode: HealthCheckMode,
+
+    /// 转发 Claude 请求时对 metadata.user_id 的处理方式
+    #[serde(default)]
+    pub metadata_user_id_policy: MetadataUserIdPolicy,
+
+    /// `anthropic-beta` 请求头过滤规则（AnthropicBetaFilter 的 JSON），为空表示原样转发
+    #[serde(default)]
+    pub anthropic_beta_filter: Option<String>,
+
+    /// 转发时使用的 API 密钥来源
+    #[serde(default)]
+    pub api_key_mode: ApiKeyMode,
+
+    /// 发往后端的请求体压缩方式
+    #[serde(default)]
+    pub request_compression: RequestCompression,
+
+    /// 覆盖发往后端的 User-Agent，为空表示透传客户端的 User-Agent
+    #[serde(default)]
+    pub user_agent: Option<String>,
+
+    /// 最后一次确认可用的时间（请求成功或健康检查成功）
+    #[serde(default)]
+    pub last_healthy_at: Option<String>,
+
     /// 创建时间
     pub created_at: String,
 
@@ -273,6 +534,40 @@ pub struct CreateApiConfigInput {
     pub auto_balance_check: Option<bool>,
     pub balance_check_interval_sec: Option<i32>,
     pub balance_currency: Option<String>,
+
+    // 超时设置（秒）
+    pub connect_timeout_secs: Option<i32>,
+    pub request_timeout_secs: Option<i32>,
+
+    // 转发时追加的固定查询参数（更新时传空字符串表示清除）
+    pub extra_query: Option<String>,
+
+    // 透传转发前从请求体中移除的顶层字段（空列表表示不移除任何字段）
+    pub strip_request_fields: Option<Vec<String>>,
+
+    // 定时健康检查的探测方式
+    #[serde(default)]
+    pub health_check_mode: Option<HealthCheckMode>,
+
+    // 转发 Claude 请求时对 metadata.user_id 的处理方式
+    #[serde(default)]
+    pub metadata_user_id_policy: Option<MetadataUserIdPolicy>,
+
+    // anthropic-beta 请求头过滤规则（更新时传 passthrough 表示清除）
+    #[serde(default)]
+    pub anthropic_beta_filter: Option<AnthropicBetaFilter>,
+
+    // 转发时使用的 API 密钥来源 (stored / client)
+    #[serde(default)]
+    pub api_key_mode: Option<ApiKeyMode>,
+
+    // 发往后端的请求体压缩方式 (off / auto / force)
+    #[serde(default)]
+    pub request_compression: Option<RequestCompression>,
+
+    // 覆盖发往后端的 User-Agent（更新时传空字符串表示清除，恢复透传客户端 UA）
+    #[serde(default)]
+    pub user_agent: Option<String>,
 }
 
 /// 更新 API 配置的输入参数
@@ -326,6 +621,40 @@ pub struct UpdateApiConfigInput {
     pub auto_balance_check: Option<bool>,
     pub balance_check_interval_sec: Option<i32>,
     pub balance_currency: Option<String>,
+
+    // 超时设置（秒）
+    pub connect_timeout_secs: Option<i32>,
+    pub request_timeout_secs: Option<i32>,
+
+    // 转发时追加的固定查询参数（更新时传空字符串表示清除）
+    pub extra_query: Option<String>,
+
+    // 透传转发前从请求体中移除的顶层字段（空列表表示不移除任何字段）
+    pub strip_request_fields: Option<Vec<String>>,
+
+    // 定时健康检查的探测方式
+    #[serde(default)]
+    pub health_check_mode: Option<HealthCheckMode>,
+
+    // 转发 Claude 请求时对 metadata.user_id 的处理方式
+    #[serde(default)]
+    pub metadata_user_id_policy: Option<MetadataUserIdPolicy>,
+
+    // anthropic-beta 请求头过滤规则（更新时传 passthrough 表示清除）
+    #[serde(default)]
+    pub anthropic_beta_filter: Option<AnthropicBetaFilter>,
+
+    // 转发时使用的 API 密钥来源 (stored / client)
+    #[serde(default)]
+    pub api_key_mode: Option<ApiKeyMode>,
+
+    // 发往后端的请求体压缩方式 (off / auto / force)
+    #[serde(default)]
+    pub request_compression: Option<RequestCompression>,
+
+    // 覆盖发往后端的 User-Agent（更新时传空字符串表示清除，恢复透传客户端 UA）
+    #[serde(default)]
+    pub user_agent: Option<String>,
 }
 
 /// 重新排序配置的输入参数
@@ -335,6 +664,42 @@ pub struct ReorderConfigInput {
     pub new_sort_order: i32,
 }
 
+/// 批量设置分组内配置模型覆盖的输入参数
+///
+/// 字段为 None 时保持不变，为空字符串时清除该覆盖
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GroupModelOverrides {
+    pub default_model: Option<String>,
+    pub haiku_model: Option<String>,
+    pub sonnet_model: Option<String>,
+    pub opus_model: Option<String>,
+    pub small_fast_model: Option<String>,
+
+    /// 只填充尚未设置的字段，不覆盖已有值
+    #[serde(default)]
+    pub only_fill_blanks: bool,
+}
+
+impl GroupModelOverrides {
+    /// 需要更新的 (列名, 新值) 列表，空字符串转换为 None
+    pub fn columns(&self) -> Vec<(&'static str, Option<&str>)> {
+        [
+            ("default_model", &self.default_model),
+            ("haiku_model", &self.haiku_model),
+            ("sonnet_model", &self.sonnet_model),
+            ("opus_model", &self.opus_model),
+            ("small_fast_model", &self.small_fast_model),
+        ]
+        .into_iter()
+        .filter_map(|(column, value)| {
+            value
+                .as_deref()
+                .map(|v| (column, Some(v.trim()).filter(|v| !v.is_empty())))
+        })
+        .collect()
+    }
+}
+
 impl ApiConfig {
     /// 验证配置名称
     pub fn validate_name(name: &str) -> Result<(), String> {
@@ -396,6 +761,105 @@ impl ApiConfig {
         Ok(())
     }
 
+    /// 验证超时覆盖值（秒），0 表示清除覆盖、使用代理全局设置
+    pub fn validate_timeout_secs(field: &str, secs: i32, max: i32) -> Result<(), String> {
+        if secs < 0 || secs > max {
+            return Err(format!("{} 必须在 0-{} 秒之间", field, max));
+        }
+
+        Ok(())
+    }
+
+    /// 验证透传转发前需要移除的请求字段
+    pub fn validate_strip_request_fields(fields: &[String]) -> Result<(), String> {
+        for field in fields {
+            let field = field.trim();
+            if field.is_empty() {
+                return Err("移除的请求字段名不能为空".to_string());
+            }
+            if REQUIRED_REQUEST_FIELDS.contains(&field) {
+                return Err(format!("不能移除必需的请求字段: {}", field));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 转发时的 `anthropic-beta` 请求头过滤规则（未配置或无效时原样转发）
+    pub fn beta_filter(&self) -> AnthropicBetaFilter {
+        self.anthropic_beta_filter
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+
+    /// 透传转发前需要从请求体中移除的顶层字段
+    pub fn request_fields_to_strip(&self) -> Vec<String> {
+        self.strip_request_fields
+            .as_deref()
+            .and_then(|json| serde_json::from_str::<Vec<String>>(json).ok())
+            .unwrap_or_else(|| DEFAULT_STRIP_REQUEST_FIELDS.iter().map(|f| f.to_string()).collect())
+    }
+
+    /// 验证定时停用截止时间，必须是晚于当前时间的 RFC3339 时间
+    pub fn validate_disabled_until(until: &str, now: DateTime<Utc>) -> Result<DateTime<Local>, String> {
+        let until = DateTime::parse_from_rfc3339(until)
+            .map_err(|e| format!("停用截止时间格式无效 (需要 RFC3339): {}", e))?;
+
+        if until.with_timezone(&Utc) <= now {
+            return Err("停用截止时间必须晚于当前时间".to_string());
+        }
+
+        Ok(until.with_timezone(&Local))
+    }
+
+    /// 检查配置在指定时间是否处于定时停用期内
+    pub fn is_disabled_at(&self, now: DateTime<Utc>) -> bool {
+        self.disabled_until
+            .as_deref()
+            .and_then(|until| DateTime::parse_from_rfc3339(until).ok())
+            .map(|until| until.with_timezone(&Utc) > now)
+            .unwrap_or(false)
+    }
+
+    /// 实际使用的健康检查方式
+    ///
+    /// 透传客户端密钥的配置没有可用的密钥，只做不携带密钥的连通性探测
+    pub fn effective_health_check_mode(&self) -> HealthCheckMode {
+        match self.api_key_mode {
+            ApiKeyMode::Client => HealthCheckMode::Reachability,
+            ApiKeyMode::Stored => self.health_check_mode,
+        }
+    }
+
+    /// 验证 API 密钥来源与提供商类型的组合
+    ///
+    /// 透传客户端密钥时请求不做格式转换，只支持 Claude 提供商
+    pub fn validate_api_key_mode(mode: ApiKeyMode, provider_type: ProviderType) -> Result<(), String> {
+        if mode == ApiKeyMode::Client && provider_type != ProviderType::Claude {
+            return Err("透传客户端密钥只支持 Claude 提供商，其它提供商需使用配置中保存的密钥".to_string());
+        }
+        Ok(())
+    }
+
+    /// 校验并规范化自定义 User-Agent
+    ///
+    /// 去除首尾空白，空字符串返回 None（透传客户端 UA）；
+    /// 只允许可见 ASCII 字符、空格与制表符，保证可以作为请求头的值发送
+    pub fn normalize_user_agent(user_agent: &str) -> Result<Option<String>, String> {
+        let user_agent = user_agent.trim();
+        if user_agent.is_empty() {
+            return Ok(None);
+        }
+        if user_agent.len() > MAX_USER_AGENT_LEN {
+            return Err(format!("User-Agent 长度不能超过 {} 个字符", MAX_USER_AGENT_LEN));
+        }
+        if !user_agent.chars().all(|c| c == ' ' || c == '\t' || c.is_ascii_graphic()) {
+            return Err("User-Agent 只能包含可见 ASCII 字符".to_string());
+        }
+        Ok(Some(user_agent.to_string()))
+    }
+
     /// 检查 API 密钥是否已加密
     pub fn is_encrypted(&self) -> bool {
         self.api_key == "[ENCRYPTED]"
@@ -428,6 +892,34 @@ impl CreateApiConfigInput {
             ApiConfig::validate_sort_order(order)?;
         }
 
+        if let Some(secs) = self.connect_timeout_secs {
+            ApiConfig::validate_timeout_secs("connect_timeout_secs", secs, MAX_CONNECT_TIMEOUT_SECS)?;
+        }
+
+        if let Some(secs) = self.request_timeout_secs {
+            ApiConfig::validate_timeout_secs("request_timeout_secs", secs, MAX_REQUEST_TIMEOUT_SECS)?;
+        }
+
+        if let Some(ref query) = self.extra_query {
+            normalize_extra_query(query)?;
+        }
+
+        if let Some(ref fields) = self.strip_request_fields {
+            ApiConfig::validate_strip_request_fields(fields)?;
+        }
+
+        if let Some(ref filter) = self.anthropic_beta_filter {
+            filter.validate()?;
+        }
+
+        if let Some(mode) = self.api_key_mode {
+            ApiConfig::validate_api_key_mode(mode, self.provider_type.unwrap_or_default())?;
+        }
+
+        if let Some(ref user_agent) = self.user_agent {
+            ApiConfig::normalize_user_agent(user_agent)?;
+        }
+
         Ok(())
     }
 }
@@ -456,6 +948,30 @@ impl UpdateApiConfigInput {
             ApiConfig::validate_sort_order(order)?;
         }
 
+        if let Some(secs) = self.connect_timeout_secs {
+            ApiConfig::validate_timeout_secs("connect_timeout_secs", secs, MAX_CONNECT_TIMEOUT_SECS)?;
+        }
+
+        if let Some(secs) = self.request_timeout_secs {
+            ApiConfig::validate_timeout_secs("request_timeout_secs", secs, MAX_REQUEST_TIMEOUT_SECS)?;
+        }
+
+        if let Some(ref query) = self.extra_query {
+            normalize_extra_query(query)?;
+        }
+
+        if let Some(ref fields) = self.strip_request_fields {
+            ApiConfig::validate_strip_request_fields(fields)?;
+        }
+
+        if let Some(ref filter) = self.anthropic_beta_filter {
+            filter.validate()?;
+        }
+
+        if let Some(ref user_agent) = self.user_agent {
+            ApiConfig::normalize_user_agent(user_agent)?;
+        }
+
         Ok(())
     }
 }
@@ -503,6 +1019,133 @@ mod tests {
         assert!(ApiConfig::validate_server_port(65536).is_err());
     }
 
+    #[test]
+    fn test_validate_timeout_overrides() {
+        assert!(ApiConfig::validate_timeout_secs("connect_timeout_secs", 0, MAX_CONNECT_TIMEOUT_SECS).is_ok());
+        assert!(ApiConfig::validate_timeout_secs("connect_timeout_secs", 10, MAX_CONNECT_TIMEOUT_SECS).is_ok());
+        assert!(ApiConfig::validate_timeout_secs("connect_timeout_secs", -1, MAX_CONNECT_TIMEOUT_SECS).is_err());
+        assert!(ApiConfig::validate_timeout_secs("connect_timeout_secs", 301, MAX_CONNECT_TIMEOUT_SECS).is_err());
+
+        let input = UpdateApiConfigInput {
+            id: 1,
+            request_timeout_secs: Some(MAX_REQUEST_TIMEOUT_SECS + 1),
+            ..Default::default()
+        };
+        assert!(input.validate().is_err());
+    }
+
+    #[test]
+    fn test_strip_request_fields() {
+        let mut config: ApiConfig = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": "c",
+            "api_key": "k",
+            "server_url": "https://api.example.com",
+            "server_port": 443,
+            "sort_order": 0,
+            "is_available": true,
+            "auto_balance_check": false,
+            "created_at": "",
+            "updated_at": ""
+        }))
+        .unwrap();
+        assert_eq!(config.request_fields_to_strip(), vec!["context_management".to_string()]);
+
+        config.strip_request_fields = Some("[]".to_string());
+        assert!(config.request_fields_to_strip().is_empty());
+
+        assert!(ApiConfig::validate_strip_request_fields(&["metadata".to_string()]).is_ok());
+        assert!(ApiConfig::validate_strip_request_fields(&[" ".to_string()]).is_err());
+        assert!(ApiConfig::validate_strip_request_fields(&["messages".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_validate_api_key_mode() {
+        assert!(ApiConfig::validate_api_key_mode(ApiKeyMode::Stored, ProviderType::Gemini).is_ok());
+        assert!(ApiConfig::validate_api_key_mode(ApiKeyMode::Client, ProviderType::Claude).is_ok());
+        assert!(ApiConfig::validate_api_key_mode(ApiKeyMode::Client, ProviderType::OpenAI).is_err());
+        assert_eq!("client".parse::<ApiKeyMode>().unwrap(), ApiKeyMode::Client);
+        assert!("both".parse::<ApiKeyMode>().is_err());
+    }
+
+    #[test]
+    fn test_normalize_user_agent() {
+        assert_eq!(ApiConfig::normalize_user_agent("  ").unwrap(), None);
+        assert_eq!(
+            ApiConfig::normalize_user_agent(" Mozilla/5.0 (X11; Linux x86_64) ").unwrap(),
+            Some("Mozilla/5.0 (X11; Linux x86_64)".to_string())
+        );
+        assert!(ApiConfig::normalize_user_agent("agent\r\nx-injected: 1").is_err());
+        assert!(ApiConfig::normalize_user_agent("客户端/1.0").is_err());
+        assert!(ApiConfig::normalize_user_agent(&"a".repeat(MAX_USER_AGENT_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_metadata_user_id_policy() {
+        let body = serde_json::json!({"model": "m", "metadata": {"user_id": "user_123", "other": 1}});
+
+        let mut passthrough = body.clone();
+        assert!(!MetadataUserIdPolicy::Passthrough.apply(&mut passthrough));
+        assert_eq!(passthrough, body);
+
+        let mut stripped = body.clone();
+        assert!(MetadataUserIdPolicy::Strip.apply(&mut stripped));
+        assert_eq!(stripped["metadata"], serde_json::json!({"other": 1}));
+
+        let mut anonymized = body.clone();
+        assert!(MetadataUserIdPolicy::Anonymize.apply(&mut anonymized));
+        assert_eq!(anonymized["metadata"]["user_id"], ANONYMIZED_METADATA_USER_ID);
+
+        let mut no_metadata = serde_json::json!({"model": "m"});
+        assert!(!MetadataUserIdPolicy::Strip.apply(&mut no_metadata));
+        assert_eq!("anonymize".parse::<MetadataUserIdPolicy>().unwrap(), MetadataUserIdPolicy::Anonymize);
+    }
+
+    #[test]
+    fn test_anthropic_beta_filter() {
+        let allowlist: AnthropicBetaFilter =
+            serde_json::from_str(r#"{"mode":"allowlist","flags":["Interleaved-Thinking-2025-05-14"]}"#).unwrap();
+        let (kept, stripped) = allowlist.split(["interleaved-thinking-2025-05-14", "context-1m-2025-08-07"]);
+        assert_eq!(kept, vec!["interleaved-thinking-2025-05-14".to_string()]);
+        assert_eq!(stripped, vec!["context-1m-2025-08-07".to_string()]);
+
+        let deny_all = AnthropicBetaFilter {
+            mode: AnthropicBetaMode::Denylist,
+            flags: vec!["*".to_string()],
+        };
+        assert!(deny_all.split(["a", "b"]).0.is_empty());
+        assert!(AnthropicBetaFilter::default().split(["a"]).1.is_empty());
+
+        assert!(allowlist.validate().is_ok());
+        let invalid = AnthropicBetaFilter {
+            mode: AnthropicBetaMode::Allowlist,
+            flags: vec!["a,b".to_string()],
+        };
+        assert!(invalid.validate().is_err());
+
+        // 未配置或无法解析时原样转发
+        let mut config: ApiConfig = serde_json::from_value(serde_json::json!({
+            "id": 1, "name": "c", "api_key": "k", "server_url": "https://api.example.com",
+            "server_port": 443, "sort_order": 0, "is_available": true,
+            "auto_balance_check": false, "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        assert!(config.beta_filter().is_passthrough());
+        config.anthropic_beta_filter = Some("not json".to_string());
+        assert!(config.beta_filter().is_passthrough());
+    }
+
+    #[test]
+    fn test_validate_disabled_until() {
+        let now = Utc::now();
+        let future = (now + chrono::Duration::hours(2)).to_rfc3339();
+        let past = (now - chrono::Duration::minutes(1)).to_rfc3339();
+
+        assert!(ApiConfig::validate_disabled_until(&future, now).is_ok());
+        assert!(ApiConfig::validate_disabled_until(&past, now).is_err());
+        assert!(ApiConfig::validate_disabled_until("tomorrow", now).is_err());
+    }
+
     #[test]
     fn test_is_encrypted() {
         let config = ApiConfig {
@@ -542,6 +1185,18 @@ mod tests {
             balance_query_error: None,
             auto_balance_check: false,
             balance_check_interval_sec: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
+            disabled_until: None,
+            extra_query: None,
+            strip_request_fields: None,
+            health_check_mode: HealthCheckMode::Health,
+            metadata_user_id_policy: MetadataUserIdPolicy::Passthrough,
+            anthropic_beta_filter: None,
+            api_key_mode: ApiKeyMode::Stored,
+            request_compression: RequestCompression::Off,
+            user_agent: None,
+            last_healthy_at: None,
             organization_id: None,
             created_at: "2025-11-09".to_string(),
             updated_at: "2025-11-09".to_string(),
@@ -589,6 +1244,18 @@ mod tests {
             balance_query_error: None,
             auto_balance_check: false,
             balance_check_interval_sec: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
+            disabled_until: None,
+            extra_query: None,
+            strip_request_fields: None,
+            health_check_mode: HealthCheckMode::Health,
+            metadata_user_id_policy: MetadataUserIdPolicy::Passthrough,
+            anthropic_beta_filter: None,
+            api_key_mode: ApiKeyMode::Stored,
+            request_compression: RequestCompression::Off,
+            user_agent: None,
+            last_healthy_at: None,
             organization_id: None,
             created_at: "2025-11-09".to_string(),
             updated_at: "2025-11-09".to_string(),
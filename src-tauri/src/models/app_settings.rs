@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 
+use crate::models::config_group::ConfigGroup;
 use crate::utils::constants::default_proxy_port_i32;
 use serde::{Deserialize, Serialize};
 
@@ -34,6 +35,22 @@ pub struct AppSettings {
     /// 健康检查间隔(秒)，默认300秒(5分钟)
     pub health_check_interval_secs: i32,
 
+    /// 是否在代理日志中记录请求体/响应体内容，默认开启
+    pub log_bodies: bool,
+
+    /// 请求日志最多保留条数，None 表示不限制
+    pub log_retention_max_count: Option<i64>,
+
+    /// 请求日志最长保留天数，None 表示不限制
+    pub log_retention_max_age_days: Option<i64>,
+
+    /// 后台日志清理间隔(分钟)，默认60分钟
+    pub log_cleanup_interval_minutes: i32,
+
+    /// 客户端请求未指定模型时注入的默认模型，None 表示不注入
+    #[serde(default)]
+    pub default_request_model: Option<String>,
+
     /// 最后更新时间
     pub updated_at: String,
 }
@@ -87,23 +104,16 @@ pub struct UpdateAppSettingsInput {
     pub recommendation_cache_ttl_sec: Option<i32>,
     pub auto_health_check_enabled: Option<bool>,
     pub health_check_interval_secs: Option<i32>,
+    pub log_bodies: Option<bool>,
 }
 
 impl AppSettings {
     /// 获取单例 ID
     pub const SINGLETON_ID: i64 = 1;
 
-    /// 验证延迟阈值
+    /// 验证延迟阈值（新建分组的默认值，与分组使用相同的范围）
     pub fn validate_latency_threshold(threshold_ms: i32) -> Result<(), String> {
-        if threshold_ms <= 0 {
-            return Err("延迟阈值必须大于 0".to_string());
-        }
-
-        if threshold_ms > 100000 {
-            return Err("延迟阈值不能超过 100000 毫秒".to_string());
-        }
-
-        Ok(())
+        ConfigGroup::validate_latency_threshold(threshold_ms)
     }
 
     /// 验证代理端口
@@ -173,6 +183,11 @@ impl Default for AppSettings {
             recommendation_cache_ttl_sec: 3600,
             auto_health_check_enabled: false,
             health_check_interval_secs: 300,
+            log_bodies: true,
+            log_retention_max_count: Some(10000),
+            log_retention_max_age_days: None,
+            log_cleanup_interval_minutes: 60,
+            default_request_model: None,
             updated_at: String::new(),
         }
     }
@@ -0,0 +1,356 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 单个方向允许的最大规则数
+const MAX_RULES_PER_DIRECTION: usize = 50;
+
+/// 请求体/响应体转换规则集 (分组级别)
+///
+/// 声明式的 JSON Pointer 规则,不支持执行任意代码。示例:
+///
+/// ```json
+/// {
+///   "request": [
+///     { "op": "set", "path": "/max_tokens", "value": 4096 },
+///     { "op": "default", "path": "/system", "value": "Always answer in English." },
+///     { "op": "remove", "path": "/metadata" }
+///   ],
+///   "response": [
+///     { "op": "remove", "path": "/usage/cache_creation" }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BodyTransformSpec {
+    /// 转发前应用于请求体的规则
+    #[serde(default)]
+    pub request: Vec<TransformRule>,
+
+    /// 返回客户端前应用于响应体的规则 (仅非流式、无需格式转换的响应)
+    #[serde(default)]
+    pub response: Vec<TransformRule>,
+}
+
+/// 转换操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransformOp {
+    /// 设置字段值 (覆盖已有值,缺失的中间对象会自动创建)
+    Set,
+    /// 删除字段 (字段不存在时忽略)
+    Remove,
+    /// 仅在字段不存在时设置默认值
+    Default,
+}
+
+/// 单条转换规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransformRule {
+    /// 操作类型
+    pub op: TransformOp,
+
+    /// 目标字段的 JSON Pointer (RFC 6901),如 "/max_tokens"
+    pub path: String,
+
+    /// 写入的值 (set/default 必填,remove 不允许)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+}
+
+impl BodyTransformSpec {
+    /// 解析并验证规则集 JSON
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let spec: Self =
+            serde_json::from_str(json).map_err(|e| format!("转换规则格式错误: {}", e))?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// 验证规则集
+    pub fn validate(&self) -> Result<(), String> {
+        for (direction, rules) in [("request", &self.request), ("response", &self.response)] {
+            if rules.len() > MAX_RULES_PER_DIRECTION {
+                return Err(format!(
+                    "{} 转换规则不能超过 {} 条",
+                    direction, MAX_RULES_PER_DIRECTION
+                ));
+            }
+
+            for (index, rule) in rules.iter().enumerate() {
+                rule.validate()
+                    .map_err(|e| format!("{} 第 {} 条转换规则无效: {}", direction, index + 1, e))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 是否没有任何规则
+    pub fn is_empty(&self) -> bool {
+        self.request.is_empty() && self.response.is_empty()
+    }
+
+    /// 对请求体应用规则,返回实际生效的规则数
+    pub fn apply_request(&self, body: &mut Value) -> usize {
+        apply_rules(&self.request, body)
+    }
+
+    /// 对响应体应用规则,返回实际生效的规则数
+    pub fn apply_response(&self, body: &mut Value) -> usize {
+        apply_rules(&self.response, body)
+    }
+}
+
+impl TransformRule {
+    /// 验证单条规则
+    pub fn validate(&self) -> Result<(), String> {
+        let tokens = parse_pointer(&self.path)?;
+
+        match self.op {
+            TransformOp::Set | TransformOp::Default => {
+                if self.value.is_none() {
+                    return Err(format!("{} 操作必须提供 value", self.op_name()));
+                }
+            }
+            TransformOp::Remove => {
+                if self.value.is_some() {
+                    return Err("remove 操作不能提供 value".to_string());
+                }
+                if tokens.last().map(|t| t == "-").unwrap_or(false) {
+                    return Err("remove 操作的路径不能以 \"-\" 结尾".to_string());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn op_name(&self) -> &'static str {
+        match self.op {
+            TransformOp::Set => "set",
+            TransformOp::Remove => "remove",
+            TransformOp::Default => "default",
+        }
+    }
+}
+
+/// 解析 JSON Pointer 为路径片段
+///
+/// 不允许指向根节点 (空字符串),避免整体替换请求体
+fn parse_pointer(path: &str) -> Result<Vec<String>, String> {
+    if path.is_empty() {
+        return Err("路径不能为空".to_string());
+    }
+
+    let rest = path
+        .strip_prefix('/')
+        .ok_or_else(|| format!("路径 {} 必须以 / 开头", path))?;
+
+    rest.split('/')
+        .map(|token| {
+            let mut unescaped = String::with_capacity(token.len());
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                if c == '~' {
+                    match chars.next() {
+                        Some('0') => unescaped.push('~'),
+                        Some('1') => unescaped.push('/'),
+                        _ => return Err(format!("路径 {} 包含无效的转义序列", path)),
+                    }
+                } else {
+                    unescaped.push(c);
+                }
+            }
+            Ok(unescaped)
+        })
+        .collect()
+}
+
+/// 依次应用规则,返回实际生效的规则数
+///
+/// 无法应用的规则 (如路径经过非容器类型) 会被跳过
+fn apply_rules(rules: &[TransformRule], body: &mut Value) -> usize {
+    let mut applied = 0;
+
+    for rule in rules {
+        let Ok(tokens) = parse_pointer(&rule.path) else {
+            continue;
+        };
+        let Some((last, parents)) = tokens.split_last() else {
+            continue;
+        };
+
+        let create_missing = rule.op != TransformOp::Remove;
+        let Some(parent) = navigate(body, parents, create_missing) else {
+            log::debug!("Body transform skipped, path not reachable: {}", rule.path);
+            continue;
+        };
+
+        let changed = match rule.op {
+            TransformOp::Set => set_child(parent, last, rule.value.clone(), true),
+            TransformOp::Default => set_child(parent, last, rule.value.clone(), false),
+            TransformOp::Remove => remove_child(parent, last),
+        };
+
+        if changed {
+            applied += 1;
+        }
+    }
+
+    applied
+}
+
+/// 定位父节点,`create_missing` 为 true 时自动创建缺失的中间对象
+fn navigate<'a>(mut current: &'a mut Value, tokens: &[String], create_missing: bool) -> Option<&'a mut Value> {
+    for token in tokens {
+        current = match current {
+            Value::Object(map) => {
+                if !map.contains_key(token) {
+                    if !create_missing {
+                        return None;
+                    }
+                    map.insert(token.clone(), Value::Object(Default::default()));
+                }
+                map.get_mut(token)?
+            }
+            Value::Array(items) => {
+                let index: usize = token.parse().ok()?;
+                items.get_mut(index)?
+            }
+            _ => return None,
+        };
+    }
+
+    Some(current)
+}
+
+/// 写入子节点,`overwrite` 为 false 时仅在不存在时写入
+fn set_child(parent: &mut Value, key: &str, value: Option<Value>, overwrite: bool) -> bool {
+    let Some(value) = value else {
+        return false;
+    };
+
+    match parent {
+        Value::Object(map) => {
+            if !overwrite && map.contains_key(key) {
+                return false;
+            }
+            map.insert(key.to_string(), value);
+            true
+        }
+        Value::Array(items) => {
+            if key == "-" {
+                items.push(value);
+                return true;
+            }
+            match key.parse::<usize>() {
+                Ok(index) if index < items.len() => {
+                    if overwrite {
+                        items[index] = value;
+                    }
+                    overwrite
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// 删除子节点
+fn remove_child(parent: &mut Value, key: &str) -> bool {
+    match parent {
+        Value::Object(map) => map.remove(key).is_some(),
+        Value::Array(items) => match key.parse::<usize>() {
+            Ok(index) if index < items.len() => {
+                items.remove(index);
+                true
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_and_validate() {
+        let spec = BodyTransformSpec::parse(
+            r#"{"request": [{"op": "set", "path": "/max_tokens", "value": 1024}]}"#,
+        )
+        .unwrap();
+        assert_eq!(spec.request.len(), 1);
+        assert!(spec.response.is_empty());
+
+        assert!(BodyTransformSpec::parse("not json").is_err());
+        assert!(BodyTransformSpec::parse(r#"{"request": [{"op": "eval", "path": "/a"}]}"#).is_err());
+        assert!(BodyTransformSpec::parse(r#"{"request": [{"op": "set", "path": "/a"}]}"#).is_err());
+        assert!(BodyTransformSpec::parse(r#"{"request": [{"op": "set", "path": "a", "value": 1}]}"#).is_err());
+        assert!(BodyTransformSpec::parse(r#"{"request": [{"op": "set", "path": "", "value": 1}]}"#).is_err());
+        assert!(BodyTransformSpec::parse(r#"{"request": [{"op": "remove", "path": "/a", "value": 1}]}"#).is_err());
+        assert!(BodyTransformSpec::parse(r#"{"request": [{"op": "remove", "path": "/a~2"}]}"#).is_err());
+        assert!(BodyTransformSpec::parse(r#"{"requests": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_apply_request_rules() {
+        let spec = BodyTransformSpec::parse(
+            r#"{"request": [
+                {"op": "set", "path": "/max_tokens", "value": 4096},
+                {"op": "default", "path": "/system", "value": "injected"},
+                {"op": "default", "path": "/temperature", "value": 0.2},
+                {"op": "remove", "path": "/metadata"},
+                {"op": "set", "path": "/extra/nested~1key", "value": true},
+                {"op": "set", "path": "/messages/-", "value": {"role": "user", "content": "hi"}}
+            ]}"#,
+        )
+        .unwrap();
+
+        let mut body = json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 64000,
+            "temperature": 1.0,
+            "metadata": {"user_id": "abc"},
+            "messages": []
+        });
+
+        let applied = spec.apply_request(&mut body);
+
+        assert_eq!(applied, 5);
+        assert_eq!(
+            body,
+            json!({
+                "model": "claude-sonnet-4-5",
+                "max_tokens": 4096,
+                "temperature": 1.0,
+                "system": "injected",
+                "extra": {"nested/key": true},
+                "messages": [{"role": "user", "content": "hi"}]
+            })
+        );
+    }
+
+    #[test]
+    fn test_unreachable_paths_are_skipped() {
+        let spec = BodyTransformSpec::parse(
+            r#"{"response": [
+                {"op": "remove", "path": "/usage/cache_creation"},
+                {"op": "set", "path": "/model/name", "value": "x"},
+                {"op": "remove", "path": "/content/5"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let mut body = json!({"model": "claude", "content": []});
+        assert_eq!(spec.apply_response(&mut body), 0);
+        assert_eq!(body, json!({"model": "claude", "content": []}));
+    }
+}
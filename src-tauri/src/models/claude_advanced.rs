@@ -24,6 +24,45 @@ impl Default for PermissionsConfig {
     }
 }
 
+/// 权限规则的修改 (同一工具的匹配条件变化)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionRuleChange {
+    pub from: String,
+    pub to: String,
+}
+
+/// 单个规则列表 (allow / deny) 的变化
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionRuleListDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<PermissionRuleChange>,
+}
+
+impl PermissionRuleListDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// 新旧 Permissions 配置的差异
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionsDiff {
+    pub allow: PermissionRuleListDiff,
+    pub deny: PermissionRuleListDiff,
+    /// 是否可能放宽工具权限 (新增/修改 allow 规则，或移除/修改 deny 规则)
+    pub may_broaden: bool,
+}
+
+impl PermissionsDiff {
+    pub fn has_changes(&self) -> bool {
+        !self.allow.is_empty() || !self.deny.is_empty()
+    }
+}
+
 /// Claude Code Skills 配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -203,6 +242,26 @@ pub struct SlashCommandInfo {
     pub file_path: String,
 }
 
+/// 无法读取的斜杠命令文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenSlashCommand {
+    /// 命令名称 (不含 / 前缀)
+    pub name: String,
+
+    /// 命令作用域
+    pub scope: CommandScope,
+
+    /// 命令文件路径
+    pub file_path: String,
+
+    /// 缺失的目标文件 (符号链接指向的文件已被删除时)
+    pub missing_path: Option<String>,
+
+    /// 无法读取的原因
+    pub reason: String,
+}
+
 // ============================================================
 // Claude Code 项目记忆 (Memories) 数据模型
 // 路径: ~/.claude/memories/ (用户级) 或 .claude/memories/ (项目级)
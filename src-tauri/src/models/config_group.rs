@@ -1,5 +1,7 @@
 #![allow(dead_code)]
 
+use crate::models::body_transform::BodyTransformSpec;
+use crate::models::retry_strategy::RetryStrategy;
 use serde::{Deserialize, Serialize};
 
 /// ConfigGroup (配置分组) 数据模型
@@ -19,6 +21,9 @@ pub struct ConfigGroup {
     pub auto_switch_enabled: bool,
 
     /// 延迟阈值(毫秒),超过此值触发自动切换
+    ///
+    /// 仅在启用自动切换时生效。阈值越低切换越激进：正常的长响应也可能被判定为高延迟，
+    /// 导致配置频繁轮换；默认值 100000 相当于关闭高延迟切换
     pub latency_threshold_ms: i32,
 
     /// 重试次数 (1-10)
@@ -33,12 +38,44 @@ pub struct ConfigGroup {
     /// 限流错误延迟 (毫秒)
     pub rate_limit_delay_ms: i32,
 
+    /// 是否使用自定义重试策略 (false 时使用全局默认策略)
+    pub retry_strategy_customized: bool,
+
     /// 是否启用健康检查
     pub health_check_enabled: bool,
 
     /// 健康检查间隔 (秒, 60-3600)
     pub health_check_interval_sec: i32,
 
+    /// 是否过滤流式响应中的 SSE 保活事件（`event: ping` 与注释行）
+    pub filter_sse_keepalive: bool,
+
+    /// 请求体/响应体转换规则 (BodyTransformSpec 的 JSON),为空表示不转换
+    pub body_transform: Option<String>,
+
+    /// 是否通过 `X-Forwarded-For` / `X-Real-IP` 向后端转发客户端真实 IP (默认关闭)
+    #[serde(default)]
+    pub forward_client_ip: bool,
+
+    /// 流式响应开头发送的 SSE `retry:` 重连间隔 (毫秒, 100-600000),为空表示不发送
+    #[serde(default)]
+    pub sse_retry_ms: Option<i32>,
+
+    /// 金丝雀配置 ID：按 canary_percentage 比例将请求路由到该配置 (客户端收到其响应)
+    #[serde(default)]
+    pub canary_config_id: Option<i64>,
+
+    /// 路由到金丝雀配置的请求百分比 (0-100, 0 表示关闭)
+    #[serde(default)]
+    pub canary_percentage: i32,
+
+    /// 仅包含原生 Claude 后端：请求体不做任何 JSON 解析与改写，按原始字节转发
+    ///
+    /// 开启后分组内的配置必须都是 Claude 提供商；字段过滤、模型覆盖、metadata.user_id 处理、
+    /// 分组请求体转换规则与请求体压缩等需要改写请求体的设置不再生效
+    #[serde(default)]
+    pub native_claude_only: bool,
+
     /// 创建时间
     pub created_at: String,
 
@@ -55,6 +92,12 @@ pub struct CreateConfigGroupInput {
     pub latency_threshold_ms: Option<i32>,
     pub health_check_enabled: Option<bool>,
     pub health_check_interval_sec: Option<i32>,
+    pub filter_sse_keepalive: Option<bool>,
+    pub body_transform: Option<String>,
+    pub forward_client_ip: Option<bool>,
+    pub sse_retry_ms: Option<i32>,
+    #[serde(default)]
+    pub native_claude_only: Option<bool>,
 }
 
 /// 更新配置分组的输入参数
@@ -67,6 +110,12 @@ pub struct UpdateConfigGroupInput {
     pub latency_threshold_ms: Option<i32>,
     pub health_check_enabled: Option<bool>,
     pub health_check_interval_sec: Option<i32>,
+    pub filter_sse_keepalive: Option<bool>,
+    pub body_transform: Option<String>,
+    pub forward_client_ip: Option<bool>,
+    pub sse_retry_ms: Option<i32>,
+    #[serde(default)]
+    pub native_claude_only: Option<bool>,
 }
 
 /// 更新分组重试策略的输入参数
@@ -88,7 +137,26 @@ pub struct UpdateGroupRetryStrategyInput {
     pub rate_limit_delay_ms: Option<i32>,
 }
 
+/// 分组当前生效的重试策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupRetryStrategy {
+    /// 分组 ID
+    pub group_id: i64,
+
+    /// 是否为分组自定义策略
+    pub customized: bool,
+
+    /// 生效的重试策略
+    pub strategy: RetryStrategy,
+}
+
 impl ConfigGroup {
+    /// 延迟阈值下限 (毫秒)，低于此值几乎每个请求都会触发切换
+    pub const MIN_LATENCY_THRESHOLD_MS: i32 = 500;
+
+    /// 延迟阈值上限 (毫秒)
+    pub const MAX_LATENCY_THRESHOLD_MS: i32 = 100000;
+
     /// 验证分组名称
     pub fn validate_name(name: &str) -> Result<(), String> {
         if name.is_empty() {
@@ -104,12 +172,12 @@ impl ConfigGroup {
 
     /// 验证延迟阈值
     pub fn validate_latency_threshold(threshold_ms: i32) -> Result<(), String> {
-        if threshold_ms <= 0 {
-            return Err("延迟阈值必须大于 0".to_string());
-        }
-
-        if threshold_ms > 100000 {
-            return Err("延迟阈值不能超过 100000 毫秒".to_string());
+        if !(Self::MIN_LATENCY_THRESHOLD_MS..=Self::MAX_LATENCY_THRESHOLD_MS).contains(&threshold_ms) {
+            return Err(format!(
+                "延迟阈值必须在 {}-{} 毫秒之间",
+                Self::MIN_LATENCY_THRESHOLD_MS,
+                Self::MAX_LATENCY_THRESHOLD_MS
+            ));
         }
 
         Ok(())
@@ -147,6 +215,78 @@ impl ConfigGroup {
         Ok(())
     }
 
+    /// 验证金丝雀流量百分比
+    pub fn validate_canary_percentage(percentage: i32) -> Result<(), String> {
+        if !(0..=100).contains(&percentage) {
+            return Err("金丝雀流量百分比必须在 0-100 之间".to_string());
+        }
+        Ok(())
+    }
+
+    /// 生效的金丝雀配置 (已设置且百分比大于 0)
+    pub fn active_canary(&self) -> Option<(i64, i32)> {
+        self.canary_config_id
+            .filter(|_| self.canary_percentage > 0)
+            .map(|id| (id, self.canary_percentage.min(100)))
+    }
+
+    /// 验证 SSE 重连间隔
+    pub fn validate_sse_retry(retry_ms: i32) -> Result<(), String> {
+        if retry_ms < 100 || retry_ms > 600000 {
+            return Err("SSE 重连间隔必须在 100-600000 毫秒之间".to_string());
+        }
+        Ok(())
+    }
+
+    /// 流式响应中发送的 SSE 重连提示 (毫秒)，未配置或无效时返回 None
+    pub fn sse_retry_hint(&self) -> Option<u32> {
+        self.sse_retry_ms
+            .filter(|ms| Self::validate_sse_retry(*ms).is_ok())
+            .map(|ms| ms as u32)
+    }
+
+    /// 验证请求体/响应体转换规则
+    pub fn validate_body_transform(spec: &str) -> Result<(), String> {
+        BodyTransformSpec::parse(spec).map(|_| ())
+    }
+
+    /// 解析转换规则 (未配置或没有任何规则时返回 None)
+    pub fn body_transform_spec(&self) -> Option<BodyTransformSpec> {
+        let spec = self.body_transform.as_deref()?;
+        match BodyTransformSpec::parse(spec) {
+            Ok(spec) if !spec.is_empty() => Some(spec),
+            Ok(_) => None,
+            Err(e) => {
+                log::warn!("分组 {} 的转换规则无效,已忽略: {}", self.id, e);
+                None
+            }
+        }
+    }
+
+    /// 获取分组生效的重试策略
+    ///
+    /// 未自定义或存储的参数无效时回退到默认策略
+    pub fn retry_strategy(&self) -> RetryStrategy {
+        if !self.retry_strategy_customized {
+            return RetryStrategy::default();
+        }
+
+        let strategy = RetryStrategy::new(
+            self.retry_count.max(0) as u32,
+            self.retry_base_delay_ms.max(0) as u32,
+            self.retry_max_delay_ms.max(0) as u32,
+            self.rate_limit_delay_ms.max(0) as u32,
+        );
+
+        match strategy.validate() {
+            Ok(()) => strategy,
+            Err(e) => {
+                log::warn!("分组 {} 的重试策略无效,使用默认策略: {}", self.id, e);
+                RetryStrategy::default()
+            }
+        }
+    }
+
     /// 检查是否为特殊分组 "未分组"
     pub fn is_ungrouped(&self) -> bool {
         self.name == "未分组"
@@ -162,6 +302,12 @@ impl ConfigGroup {
     pub fn validate(&self) -> Result<(), String> {
         Self::validate_name(&self.name)?;
         Self::validate_latency_threshold(self.latency_threshold_ms)?;
+        if let Some(ref spec) = self.body_transform {
+            Self::validate_body_transform(spec)?;
+        }
+        if let Some(retry_ms) = self.sse_retry_ms {
+            Self::validate_sse_retry(retry_ms)?;
+        }
         Ok(())
     }
 }
@@ -175,6 +321,14 @@ impl CreateConfigGroupInput {
             ConfigGroup::validate_latency_threshold(threshold)?;
         }
 
+        if let Some(ref spec) = self.body_transform {
+            ConfigGroup::validate_body_transform(spec)?;
+        }
+
+        if let Some(retry_ms) = self.sse_retry_ms {
+            ConfigGroup::validate_sse_retry(retry_ms)?;
+        }
+
         Ok(())
     }
 }
@@ -190,6 +344,14 @@ impl UpdateConfigGroupInput {
             ConfigGroup::validate_latency_threshold(threshold)?;
         }
 
+        if let Some(ref spec) = self.body_transform {
+            ConfigGroup::validate_body_transform(spec)?;
+        }
+
+        if let Some(retry_ms) = self.sse_retry_ms {
+            ConfigGroup::validate_sse_retry(retry_ms)?;
+        }
+
         Ok(())
     }
 }
@@ -239,13 +401,23 @@ mod tests {
     #[test]
     fn test_validate_latency_threshold() {
         assert!(ConfigGroup::validate_latency_threshold(3000).is_ok());
-        assert!(ConfigGroup::validate_latency_threshold(1).is_ok());
+        assert!(ConfigGroup::validate_latency_threshold(500).is_ok());
         assert!(ConfigGroup::validate_latency_threshold(100000).is_ok());
+        assert!(ConfigGroup::validate_latency_threshold(499).is_err());
+        assert!(ConfigGroup::validate_latency_threshold(1).is_err());
         assert!(ConfigGroup::validate_latency_threshold(0).is_err());
         assert!(ConfigGroup::validate_latency_threshold(-100).is_err());
         assert!(ConfigGroup::validate_latency_threshold(100001).is_err());
     }
 
+    #[test]
+    fn test_validate_canary_percentage() {
+        assert!(ConfigGroup::validate_canary_percentage(0).is_ok());
+        assert!(ConfigGroup::validate_canary_percentage(100).is_ok());
+        assert!(ConfigGroup::validate_canary_percentage(-1).is_err());
+        assert!(ConfigGroup::validate_canary_percentage(101).is_err());
+    }
+
     #[test]
     fn test_is_ungrouped() {
         let group = ConfigGroup {
@@ -258,8 +430,16 @@ mod tests {
             retry_base_delay_ms: 2000,
             retry_max_delay_ms: 8000,
             rate_limit_delay_ms: 30000,
+            retry_strategy_customized: false,
             health_check_enabled: true,
             health_check_interval_sec: 60,
+            filter_sse_keepalive: false,
+            body_transform: None,
+            forward_client_ip: false,
+            sse_retry_ms: None,
+            canary_config_id: None,
+            canary_percentage: 0,
+            native_claude_only: false,
             created_at: "2025-11-09".to_string(),
             updated_at: "2025-11-09".to_string(),
         };
@@ -280,8 +460,16 @@ mod tests {
             retry_base_delay_ms: 2000,
             retry_max_delay_ms: 8000,
             rate_limit_delay_ms: 30000,
+            retry_strategy_customized: false,
             health_check_enabled: true,
             health_check_interval_sec: 60,
+            filter_sse_keepalive: false,
+            body_transform: None,
+            forward_client_ip: false,
+            sse_retry_ms: None,
+            canary_config_id: None,
+            canary_percentage: 0,
+            native_claude_only: false,
             created_at: "2025-11-09".to_string(),
             updated_at: "2025-11-09".to_string(),
         };
@@ -290,6 +478,47 @@ mod tests {
         assert!(group.can_delete());
     }
 
+    #[test]
+    fn test_retry_strategy_fallback() {
+        let mut group = ConfigGroup {
+            id: 1,
+            name: "本地模型".to_string(),
+            description: None,
+            auto_switch_enabled: true,
+            latency_threshold_ms: 100000,
+            retry_count: 1,
+            retry_base_delay_ms: 200,
+            retry_max_delay_ms: 1000,
+            rate_limit_delay_ms: 1000,
+            retry_strategy_customized: false,
+            health_check_enabled: false,
+            health_check_interval_sec: 300,
+            filter_sse_keepalive: false,
+            body_transform: None,
+            forward_client_ip: false,
+            sse_retry_ms: None,
+            canary_config_id: None,
+            canary_percentage: 0,
+            native_claude_only: false,
+            created_at: "2025-11-09".to_string(),
+            updated_at: "2025-11-09".to_string(),
+        };
+
+        // 未自定义时使用默认策略
+        assert_eq!(group.retry_strategy().rate_limit_delay_ms, 30000);
+
+        group.retry_strategy_customized = true;
+        let strategy = group.retry_strategy();
+        assert_eq!(strategy.max_retries, 1);
+        assert_eq!(strategy.base_delay_ms, 200);
+        assert_eq!(strategy.max_delay_ms, 1000);
+        assert_eq!(strategy.rate_limit_delay_ms, 1000);
+
+        // 参数无效时回退到默认策略
+        group.retry_base_delay_ms = 5000;
+        assert_eq!(group.retry_strategy().max_retries, 3);
+    }
+
     #[test]
     fn test_create_input_validation() {
         let valid_input = CreateConfigGroupInput {
@@ -299,6 +528,11 @@ mod tests {
             latency_threshold_ms: Some(5000),
             health_check_enabled: Some(true),
             health_check_interval_sec: Some(60),
+            filter_sse_keepalive: Some(true),
+            body_transform: Some(r#"{"request": [{"op": "set", "path": "/max_tokens", "value": 4096}]}"#.to_string()),
+            forward_client_ip: Some(true),
+            sse_retry_ms: Some(3000),
+            native_claude_only: None,
         };
         assert!(valid_input.validate().is_ok());
 
@@ -309,7 +543,27 @@ mod tests {
             latency_threshold_ms: None,
             health_check_enabled: None,
             health_check_interval_sec: None,
+            filter_sse_keepalive: None,
+            body_transform: None,
+            forward_client_ip: None,
+            sse_retry_ms: None,
+            native_claude_only: None,
         };
         assert!(invalid_input.validate().is_err());
+
+        let invalid_transform = CreateConfigGroupInput {
+            name: "测试分组".to_string(),
+            description: None,
+            auto_switch_enabled: None,
+            latency_threshold_ms: None,
+            health_check_enabled: None,
+            health_check_interval_sec: None,
+            filter_sse_keepalive: None,
+            body_transform: Some(r#"{"request": [{"op": "set", "path": "/max_tokens"}]}"#.to_string()),
+            forward_client_ip: None,
+            sse_retry_ms: None,
+            native_claude_only: None,
+        };
+        assert!(invalid_transform.validate().is_err());
     }
 }
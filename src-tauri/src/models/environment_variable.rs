@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 
+use crate::models::api_config::ApiConfig;
 use serde::{Deserialize, Serialize};
 
 /// EnvironmentVariable (环境变量) 数据模型
@@ -54,6 +55,104 @@ pub struct EnvironmentVariableDetail {
     pub is_masked: bool,
 }
 
+/// Anthropic 环境变量的当前取值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicEnvValue {
+    /// 变量名
+    pub key: String,
+
+    /// 是否已设置 (空字符串视为未设置)
+    pub is_set: bool,
+
+    /// 变量值 (密钥类变量已脱敏)
+    pub value: Option<String>,
+}
+
+/// 与代理配置冲突的 Anthropic 环境变量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicEnvConflict {
+    /// 变量名
+    pub key: String,
+
+    /// 期望值
+    pub expected: Option<String>,
+
+    /// 实际值 (密钥类变量已脱敏)
+    pub actual: Option<String>,
+
+    /// 说明
+    pub message: String,
+
+    /// 清除该变量的建议操作
+    pub clear_hint: String,
+}
+
+/// Anthropic 环境变量检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicEnvCheck {
+    /// 是否已设置 Base URL 及任一凭据
+    pub is_configured: bool,
+
+    /// 环境中的 ANTHROPIC_BASE_URL
+    pub base_url: Option<String>,
+
+    /// 凭据来源变量名 (ANTHROPIC_AUTH_TOKEN 优先于 ANTHROPIC_API_KEY)
+    pub auth_token_source: Option<String>,
+
+    /// 代理是否正在运行
+    pub proxy_running: bool,
+
+    /// 代理运行时期望的 ANTHROPIC_BASE_URL
+    pub expected_base_url: Option<String>,
+
+    /// 各变量的当前取值
+    pub variables: Vec<AnthropicEnvValue>,
+
+    /// 冲突项
+    pub conflicts: Vec<AnthropicEnvConflict>,
+}
+
+/// 从环境变量片段创建配置的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvSnippetImportResult {
+    /// 创建的配置
+    pub config: ApiConfig,
+
+    /// 已解析并写入配置的变量名
+    pub parsed_fields: Vec<String>,
+
+    /// 片段中未提供的可识别变量名
+    pub missing_fields: Vec<String>,
+
+    /// 无法识别而被忽略的变量名
+    pub ignored_keys: Vec<String>,
+}
+
+/// 使用指定配置启动 Claude Code 的环境变量导出命令
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigEnvExports {
+    pub config_id: i64,
+    pub config_name: String,
+
+    /// 绑定到该配置的代理会话 ID
+    pub session_id: String,
+
+    /// 指向代理会话的 ANTHROPIC_BASE_URL
+    pub base_url: String,
+
+    /// 导出的变量 (键, 值)，不包含配置中保存的 API 密钥
+    pub variables: Vec<(String, String)>,
+
+    /// bash / zsh 语法
+    pub posix: String,
+
+    /// PowerShell 语法
+    pub powershell: String,
+
+    /// 配置使用客户端密钥透传，需要把占位符替换为自己的 API 密钥
+    pub requires_client_key: bool,
+}
+
 impl EnvironmentVariable {
     /// 验证变量名
     pub fn validate_key(key: &str) -> Result<(), String> {
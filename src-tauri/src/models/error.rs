@@ -119,6 +119,10 @@ pub enum AppError {
     /// 没有可用配置
     #[error("没有可用配置")]
     NoConfigAvailable,
+
+    /// 请求体超过代理缓冲上限
+    #[error("请求体过大: 超过 {limit_bytes} 字节的缓冲上限")]
+    PayloadTooLarge { limit_bytes: u64 },
 }
 
 /// 错误响应格式
@@ -172,6 +176,7 @@ impl AppError {
             AppError::PathNotFound { .. } => "PathNotFound".to_string(),
             AppError::ConversionError { .. } => "ConversionError".to_string(),
             AppError::NoConfigAvailable => "NoConfigAvailable".to_string(),
+            AppError::PayloadTooLarge { .. } => "PayloadTooLarge".to_string(),
         }
     }
 }
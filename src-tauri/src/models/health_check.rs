@@ -33,6 +33,51 @@ impl HealthCheckStatus {
     }
 }
 
+/// 健康检查探测方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthCheckMode {
+    /// 携带密钥请求 /v1/health (默认)
+    #[default]
+    Health,
+    /// 发送真实的 /v1/messages 测试请求 (会消耗 token)
+    Full,
+    /// 仅 TCP + TLS + HEAD 探测，不携带密钥
+    Reachability,
+    /// 携带密钥请求 GET /v1/models
+    Models,
+}
+
+impl std::str::FromStr for HealthCheckMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "health" => Ok(HealthCheckMode::Health),
+            "full" => Ok(HealthCheckMode::Full),
+            "reachability" => Ok(HealthCheckMode::Reachability),
+            "models" => Ok(HealthCheckMode::Models),
+            _ => Err(format!("未知的健康检查方式: {}", s)),
+        }
+    }
+}
+
+impl HealthCheckMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthCheckMode::Health => "health",
+            HealthCheckMode::Full => "full",
+            HealthCheckMode::Reachability => "reachability",
+            HealthCheckMode::Models => "models",
+        }
+    }
+
+    /// 该探测方式是否会产生计费请求
+    pub fn is_billable(&self) -> bool {
+        matches!(self, HealthCheckMode::Full)
+    }
+}
+
 /// 健康检查记录
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthCheckRecord {
@@ -100,6 +145,12 @@ pub struct ConfigHealthSummary {
     pub availability_24h: f64,
     /// 当天平均延迟，字段名保留 24h 以保持 API 兼容性
     pub avg_latency_24h: Option<f64>,
+    /// 当前使用的探测方式
+    #[serde(default)]
+    pub probe_mode: HealthCheckMode,
+    /// 探测是否会产生计费请求
+    #[serde(default)]
+    pub billable: bool,
 }
 
 #[cfg(test)]
@@ -129,4 +180,19 @@ mod tests {
         assert_eq!(HealthCheckStatus::Failed.as_str(), "failed");
         assert_eq!(HealthCheckStatus::Timeout.as_str(), "timeout");
     }
+
+    #[test]
+    fn test_health_check_mode() {
+        for mode in [
+            HealthCheckMode::Health,
+            HealthCheckMode::Full,
+            HealthCheckMode::Reachability,
+            HealthCheckMode::Models,
+        ] {
+            assert_eq!(mode.as_str().parse::<HealthCheckMode>().unwrap(), mode);
+            assert_eq!(mode.is_billable(), mode == HealthCheckMode::Full);
+        }
+        assert!("unknown".parse::<HealthCheckMode>().is_err());
+        assert_eq!(HealthCheckMode::default(), HealthCheckMode::Health);
+    }
 }
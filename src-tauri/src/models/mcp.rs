@@ -50,6 +50,259 @@ pub struct McpServerInfo {
     pub enabled: bool,
 }
 
+/// MCP 服务器传输方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpTransport {
+    /// 本地进程 (stdin/stdout)
+    Stdio,
+    /// 旧版 HTTP + SSE 传输
+    Sse,
+    /// Streamable HTTP 传输
+    Http,
+}
+
+/// MCP 服务器声明的工具
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpToolInfo {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// MCP 服务器声明的资源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpResourceInfo {
+    pub uri: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// MCP 服务器连通性诊断结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerDiagnostics {
+    /// 服务器名称
+    pub name: String,
+
+    /// 尝试使用的传输方式
+    pub transport: McpTransport,
+
+    /// 连接目标 (stdio 为完整命令行，远程为 URL)
+    pub target: String,
+
+    /// 进程是否成功启动 (仅 stdio)
+    pub process_spawned: Option<bool>,
+
+    /// initialize 握手是否成功
+    pub handshake_ok: bool,
+
+    /// 服务器返回的协议版本
+    pub protocol_version: Option<String>,
+
+    /// 服务器名称与版本 (serverInfo)
+    pub server_name: Option<String>,
+    pub server_version: Option<String>,
+
+    /// 服务器声明的能力 (capabilities 原始 JSON)
+    pub capabilities: Option<serde_json::Value>,
+
+    /// 工具列表
+    pub tools: Vec<McpToolInfo>,
+
+    /// 资源列表
+    pub resources: Vec<McpResourceInfo>,
+
+    /// 启动进程 / 建立连接耗时 (毫秒)
+    pub connect_ms: Option<u64>,
+
+    /// initialize 握手耗时 (毫秒)
+    pub handshake_ms: Option<u64>,
+
+    /// 诊断总耗时 (毫秒)
+    pub total_ms: u64,
+
+    /// 失败阶段 (spawn / connect / initialize / tools/list / resources/list)
+    pub failed_stage: Option<String>,
+
+    /// 具体错误信息
+    pub error: Option<String>,
+
+    /// 进程 stderr 的最后几行 (仅 stdio，便于定位启动失败原因)
+    pub stderr_tail: Option<String>,
+}
+
+impl McpServerDiagnostics {
+    /// 创建空的诊断结果
+    pub fn new(name: impl Into<String>, transport: McpTransport, target: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            transport,
+            target: target.into(),
+            process_spawned: None,
+            handshake_ok: false,
+            protocol_version: None,
+            server_name: None,
+            server_version: None,
+            capabilities: None,
+            tools: Vec::new(),
+            resources: Vec::new(),
+            connect_ms: None,
+            handshake_ms: None,
+            total_ms: 0,
+            failed_stage: None,
+            error: None,
+            stderr_tail: None,
+        }
+    }
+
+    /// 记录失败阶段与错误 (只保留第一个错误)
+    pub fn fail(&mut self, stage: &str, error: impl Into<String>) {
+        if self.error.is_none() {
+            self.failed_stage = Some(stage.to_string());
+            self.error = Some(error.into());
+        }
+    }
+}
+
+/// 批量测试中单个 MCP 服务器的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerTestSummary {
+    /// 服务器名称
+    pub name: String,
+
+    /// 传输方式 (配置无法解析时为 None)
+    pub transport: Option<McpTransport>,
+
+    /// 连接目标
+    pub target: Option<String>,
+
+    /// initialize 握手是否成功
+    pub handshake_ok: bool,
+
+    /// 服务器声明的工具数量
+    pub tool_count: usize,
+
+    /// 测试总耗时 (毫秒)
+    pub total_ms: u64,
+
+    /// 失败阶段 (config / spawn / connect / initialize / tools/list / resources/list)
+    pub failed_stage: Option<String>,
+
+    /// 具体错误信息
+    pub error: Option<String>,
+}
+
+impl From<McpServerDiagnostics> for McpServerTestSummary {
+    fn from(diag: McpServerDiagnostics) -> Self {
+        Self {
+            name: diag.name,
+            transport: Some(diag.transport),
+            target: Some(diag.target),
+            handshake_ok: diag.handshake_ok,
+            tool_count: diag.tools.len(),
+            total_ms: diag.total_ms,
+            failed_stage: diag.failed_stage,
+            error: diag.error,
+        }
+    }
+}
+
+/// 批量测试进度 (每完成一个服务器推送一次)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpBatchTestProgress {
+    /// 已完成数量
+    pub completed: usize,
+
+    /// 服务器总数
+    pub total: usize,
+
+    /// 刚完成的服务器结果
+    pub server: McpServerTestSummary,
+}
+
+/// 批量测试所有 MCP 服务器的结果汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct McpBatchTestResult {
+    /// 握手成功数量
+    pub passed: usize,
+
+    /// 失败数量
+    pub failed: usize,
+
+    /// 各服务器的结果 (按名称排序)
+    pub servers: Vec<McpServerTestSummary>,
+}
+
+/// 导入 MCP 服务器时的同名冲突处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpImportStrategy {
+    /// 跳过已存在的同名服务器
+    #[default]
+    Skip,
+    /// 以 `name-2`、`name-3` ... 的形式重命名后导入
+    Rename,
+    /// 覆盖已存在的同名服务器
+    Overwrite,
+}
+
+/// 单个导入条目的处理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpImportStatus {
+    Added,
+    Renamed,
+    Overwritten,
+    Skipped,
+    Error,
+}
+
+/// 单个导入条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpImportEntry {
+    /// 导入文件中的服务器名称
+    pub name: String,
+
+    /// 实际写入的名称 (重命名时与 name 不同，跳过/出错时为 None)
+    pub imported_as: Option<String>,
+
+    pub status: McpImportStatus,
+
+    /// 跳过或出错的原因
+    pub message: Option<String>,
+}
+
+/// 批量导入 MCP 服务器的结果汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct McpImportResult {
+    /// 新增数量 (含重命名)
+    pub added: usize,
+
+    /// 覆盖数量
+    pub overwritten: usize,
+
+    /// 跳过数量
+    pub skipped: usize,
+
+    /// 出错数量
+    pub errors: usize,
+
+    /// 各条目的处理结果 (按名称排序)
+    pub entries: Vec<McpImportEntry>,
+}
+
+impl McpImportResult {
+    /// 记录一个条目并更新计数
+    pub fn push(&mut self, entry: McpImportEntry) {
+        match entry.status {
+            McpImportStatus::Added | McpImportStatus::Renamed => self.added += 1,
+            McpImportStatus::Overwritten => self.overwritten += 1,
+            McpImportStatus::Skipped => self.skipped += 1,
+            McpImportStatus::Error => self.errors += 1,
+        }
+        self.entries.push(entry);
+    }
+}
+
 /// MCP 服务器预设模板
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpServerTemplate {
@@ -1,9 +1,11 @@
 pub mod api_config;
 pub mod app_settings;
 pub mod balance;
+pub mod body_transform;
 pub mod claude_advanced;
 pub mod config_backup;
 pub mod config_group;
+pub mod config_report;
 pub mod environment_variable;
 pub mod error;
 pub mod error_classifier;
@@ -16,6 +18,7 @@ pub mod provider_preset;
 pub mod proxy_status;
 pub mod recommended_service;
 pub mod retry_strategy;
+pub mod routing_snapshot;
 pub mod switch_log;
 pub mod terminal_session;
 pub mod test_result;
@@ -128,6 +128,46 @@ pub struct ProviderConfig {
     pub providers: Vec<ProviderPreset>,
 }
 
+/// 供应商预设实测验证结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetValidationResult {
+    /// 供应商 ID
+    pub preset_id: String,
+    /// 规范化后的服务器地址（地址无效时为 None）
+    pub server_url: Option<String>,
+    /// 服务器是否可连接
+    pub reachable: bool,
+    /// 示例密钥是否通过认证（无法判断时为 None）
+    pub authenticated: Option<bool>,
+    /// 预设是否可用（连接与测试请求均成功）
+    pub usable: bool,
+    /// 测试请求耗时 (毫秒)
+    pub latency_ms: Option<i64>,
+    /// 规范化地址时的提示
+    pub warnings: Vec<String>,
+    /// 错误信息
+    pub error_message: Option<String>,
+}
+
+/// 内嵌 providers.json 校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedProvidersReport {
+    /// 内嵌配置能否作为推荐服务的回退数据（没有错误）
+    pub usable: bool,
+    /// 配置版本（解析失败时为 None）
+    pub version: Option<String>,
+    /// 供应商数量
+    pub provider_count: usize,
+    /// 在推荐服务页面显示的供应商数量（回退时实际返回的数量）
+    pub recommended_count: usize,
+    /// 导致回退不可用的问题
+    pub errors: Vec<String>,
+    /// 不影响回退的问题
+    pub warnings: Vec<String>,
+}
+
 impl ProviderPreset {
     /// 获取热度等级
     pub fn hotness_grade(&self) -> &'static str {
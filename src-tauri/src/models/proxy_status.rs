@@ -93,6 +93,29 @@ impl ProxyStatus {
     }
 }
 
+/// 命名代理监听实例
+/// 与主代理服务并行运行在不同端口上，各自拥有独立的活动分组/配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyListenerInfo {
+    /// 监听实例名称
+    pub name: String,
+
+    /// 运行状态
+    pub status: ProxyStatus,
+
+    /// 监听地址
+    pub listen_host: String,
+
+    /// 监听端口
+    pub listen_port: i32,
+
+    /// 当前使用的分组 ID
+    pub active_group_id: Option<i64>,
+
+    /// 当前使用的 API 配置 ID
+    pub active_config_id: Option<i64>,
+}
+
 /// 代理状态详情 (用于前端展示)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyStatusDetail {
@@ -71,6 +71,45 @@ impl ServiceSource {
     }
 }
 
+/// 刷新推荐服务时实际使用的数据源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RefreshSource {
+    /// 远程 OSS
+    Remote,
+
+    /// 本地 JSON 文件
+    Local,
+
+    /// 内嵌的 providers.json
+    Embedded,
+
+    /// 远程失败后沿用的上次缓存
+    Cache,
+}
+
+/// 刷新推荐服务的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendationRefreshResult {
+    /// 实际使用的数据源
+    pub source: RefreshSource,
+
+    /// 加载的推荐服务数量
+    pub count: usize,
+
+    /// 远程获取耗时 (毫秒)，未配置远程 URL 时为 None
+    pub latency_ms: Option<u64>,
+
+    /// 远程获取失败的错误信息 (成功时为 None)
+    pub error: Option<String>,
+
+    /// 刷新后的推荐服务列表
+    pub services: Vec<RecommendedService>,
+
+    /// 刷新完成时间
+    pub refreshed_at: String,
+}
+
 /// 创建推荐服务的输入参数
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateRecommendedServiceInput {
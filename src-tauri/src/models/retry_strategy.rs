@@ -106,6 +106,28 @@ impl Default for RetryStrategy {
     }
 }
 
+/// 配置当前的重试状态 (用于 UI 展示重试/退避进度)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigRetryState {
+    /// 配置 ID
+    pub config_id: i64,
+
+    /// 配置名称
+    pub config_name: String,
+
+    /// 当前连续重试次数
+    pub retry_count: u32,
+
+    /// 分组策略的最大重试次数，达到后切换到下一个配置
+    pub max_retries: u32,
+
+    /// 下一次允许重试的时间 (RFC3339)，未处于退避中时为 None
+    pub next_retry_at: Option<String>,
+
+    /// 是否仍处于退避等待中
+    pub in_backoff: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,104 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+/// 快照名称最大长度
+const MAX_SNAPSHOT_NAME_LEN: usize = 100;
+
+/// RoutingSnapshot (路由状态快照) 数据模型
+/// 记录某一时刻的激活分组/配置以及启用状态，用于快速恢复到已知可用的路由状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingSnapshot {
+    /// 快照唯一标识符
+    pub id: i64,
+
+    /// 快照名称 (唯一)
+    pub name: String,
+
+    /// 保存时激活的分组 ID
+    pub active_group_id: Option<i64>,
+
+    /// 保存时激活的配置 ID
+    pub active_config_id: Option<i64>,
+
+    /// 保存时的启用状态
+    pub flags: RoutingSnapshotFlags,
+
+    /// 创建时间
+    pub created_at: String,
+
+    /// 最后更新时间 (同名快照再次保存时更新)
+    pub updated_at: String,
+}
+
+/// 快照中的启用状态 (以 JSON 存储在 enabled_flags 列)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoutingSnapshotFlags {
+    /// 各配置的启用状态
+    #[serde(default)]
+    pub configs: Vec<ConfigEnabledFlag>,
+
+    /// 各分组的自动切换状态
+    #[serde(default)]
+    pub groups: Vec<GroupEnabledFlag>,
+}
+
+/// 单个配置的启用状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigEnabledFlag {
+    pub config_id: i64,
+    pub group_id: Option<i64>,
+    pub is_enabled: bool,
+}
+
+/// 单个分组的自动切换状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupEnabledFlag {
+    pub group_id: i64,
+    pub auto_switch_enabled: bool,
+}
+
+/// 恢复快照的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreRoutingSnapshotResult {
+    /// 被恢复的快照
+    pub snapshot: RoutingSnapshot,
+
+    /// 实际恢复的配置启用状态数量
+    pub restored_configs: usize,
+
+    /// 实际恢复的分组自动切换状态数量
+    pub restored_groups: usize,
+
+    /// 快照中已不存在的配置/分组 (已跳过)
+    pub skipped: Vec<String>,
+}
+
+impl RoutingSnapshot {
+    /// 验证快照名称
+    pub fn validate_name(name: &str) -> Result<(), String> {
+        let trimmed = name.trim();
+        if trimmed.is_empty() {
+            return Err("快照名称不能为空".to_string());
+        }
+
+        if trimmed.chars().count() > MAX_SNAPSHOT_NAME_LEN {
+            return Err(format!("快照名称不能超过 {} 个字符", MAX_SNAPSHOT_NAME_LEN));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(RoutingSnapshot::validate_name("known-good").is_ok());
+        assert!(RoutingSnapshot::validate_name("").is_err());
+        assert!(RoutingSnapshot::validate_name("   ").is_err());
+        assert!(RoutingSnapshot::validate_name(&"a".repeat(101)).is_err());
+    }
+}
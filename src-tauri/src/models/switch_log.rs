@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// SwitchLog (切换日志) 数据模型
@@ -220,6 +221,15 @@ pub struct SwitchLogDetail {
     /// 日志 ID
     pub id: i64,
 
+    /// 分组 ID
+    pub group_id: i64,
+
+    /// 源配置 ID (配置已删除或首次选择时为 None)
+    pub source_config_id: Option<i64>,
+
+    /// 目标配置 ID
+    pub target_config_id: i64,
+
     /// 切换时间
     pub switch_at: String,
 
@@ -257,6 +267,111 @@ pub struct SwitchLogDetail {
     pub error_details: Option<String>,
 }
 
+/// 切换日志导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SwitchLogExportFormat {
+    Csv,
+    Json,
+}
+
+/// 切换日志导出时间窗口 (RFC3339，缺省表示不限)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SwitchLogTimeWindow {
+    /// 起始时间 (含)
+    pub start: Option<String>,
+
+    /// 结束时间 (含)
+    pub end: Option<String>,
+}
+
+/// 切换日志导出行 (按时间升序，用于时间线分析)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchLogExportRow {
+    pub id: i64,
+    pub switch_at: String,
+    pub reason: String,
+    pub group_id: i64,
+    pub group_name: Option<String>,
+    pub source_config_id: Option<i64>,
+    pub source_config_name: Option<String>,
+    pub target_config_id: i64,
+    pub target_config_name: Option<String>,
+    pub latency_before_ms: Option<i32>,
+    pub latency_after_ms: Option<i32>,
+    pub retry_count: i32,
+    pub error_type: Option<String>,
+    pub error_message: Option<String>,
+
+    /// 距上一次切换的时间 (毫秒)，第一条或时间无法解析时为空
+    pub ms_since_previous: Option<i64>,
+}
+
+/// 切换日志导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchLogExportResult {
+    /// 导出文件路径
+    pub file_path: String,
+
+    /// 导出格式
+    pub format: SwitchLogExportFormat,
+
+    /// 导出的记录数
+    pub row_count: usize,
+}
+
+impl SwitchLogExportRow {
+    /// CSV 表头
+    pub const CSV_HEADER: &'static str = "id,switch_at,reason,group_id,group_name,\
+source_config_id,source_config_name,target_config_id,target_config_name,\
+latency_before_ms,latency_after_ms,retry_count,error_type,error_message,ms_since_previous";
+
+    /// 转换为一行 CSV (不含换行符)
+    pub fn to_csv_record(&self) -> String {
+        fn opt<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(ToString::to_string).unwrap_or_default()
+        }
+
+        [
+            self.id.to_string(),
+            csv_escape(&self.switch_at),
+            csv_escape(&self.reason),
+            self.group_id.to_string(),
+            csv_escape(&opt(&self.group_name)),
+            opt(&self.source_config_id),
+            csv_escape(&opt(&self.source_config_name)),
+            self.target_config_id.to_string(),
+            csv_escape(&opt(&self.target_config_name)),
+            opt(&self.latency_before_ms),
+            opt(&self.latency_after_ms),
+            self.retry_count.to_string(),
+            csv_escape(&opt(&self.error_type)),
+            csv_escape(&opt(&self.error_message)),
+            opt(&self.ms_since_previous),
+        ]
+        .join(",")
+    }
+}
+
+/// CSV 字段转义 (含逗号、引号或换行时加引号)
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// 解析切换时间 (RFC3339 或 SQLite CURRENT_TIMESTAMP 格式，后者按 UTC 处理)
+pub fn parse_switch_at(switch_at: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(switch_at)
+        .map(|dt| dt.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(switch_at, "%Y-%m-%d %H:%M:%S").map(|dt| dt.and_utc())
+        })
+        .ok()
+}
+
 impl SwitchLog {
     /// 验证切换日志
     pub fn validate(&self) -> Result<(), String> {
@@ -405,4 +520,39 @@ mod tests {
         };
         assert!(invalid_log.validate().is_err());
     }
+
+    #[test]
+    fn test_export_row_csv_record() {
+        let row = SwitchLogExportRow {
+            id: 7,
+            switch_at: "2025-11-09T10:00:00+08:00".to_string(),
+            reason: "retry_failed".to_string(),
+            group_id: 1,
+            group_name: Some("default".to_string()),
+            source_config_id: Some(1),
+            source_config_name: Some("a, b".to_string()),
+            target_config_id: 2,
+            target_config_name: Some("c".to_string()),
+            latency_before_ms: Some(1200),
+            latency_after_ms: None,
+            retry_count: 3,
+            error_type: Some("timeout".to_string()),
+            error_message: Some("say \"hi\"".to_string()),
+            ms_since_previous: Some(1500),
+        };
+
+        assert_eq!(
+            row.to_csv_record(),
+            "7,2025-11-09T10:00:00+08:00,retry_failed,1,default,1,\"a, b\",2,c,1200,,3,timeout,\"say \"\"hi\"\"\",1500"
+        );
+        assert_eq!(SwitchLogExportRow::CSV_HEADER.split(',').count(), 15);
+    }
+
+    #[test]
+    fn test_parse_switch_at() {
+        let rfc = parse_switch_at("2025-11-09T10:00:00+08:00").unwrap();
+        let sqlite = parse_switch_at("2025-11-09 02:00:01").unwrap();
+        assert_eq!((sqlite - rfc).num_milliseconds(), 1000);
+        assert!(parse_switch_at("2025-11-09").is_none());
+    }
 }
@@ -38,6 +38,42 @@ pub struct TestResult {
 
     /// 尝试次数（1=首次，2=重试）
     pub attempt: Option<i32>,
+
+    /// 连接阶段耗时分解 (DNS / TCP / TLS / TTFB)
+    #[serde(default)]
+    pub timing: Option<TimingBreakdown>,
+}
+
+/// 连接阶段耗时分解 (毫秒)
+///
+/// 用于区分延迟来自 DNS 解析、网络连接还是后端处理
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimingBreakdown {
+    /// DNS 解析耗时
+    pub dns_ms: Option<i32>,
+
+    /// TCP 连接耗时
+    pub connect_ms: Option<i32>,
+
+    /// TLS 握手耗时 (HTTP 为 None)
+    pub tls_ms: Option<i32>,
+
+    /// 发送请求到收到首字节的耗时
+    pub ttfb_ms: Option<i32>,
+
+    /// 测量时间 (对应测试结果的 test_at)
+    #[serde(default)]
+    pub measured_at: Option<String>,
+}
+
+impl TimingBreakdown {
+    /// 各阶段耗时之和
+    pub fn total_ms(&self) -> i32 {
+        [self.dns_ms, self.connect_ms, self.tls_ms, self.ttfb_ms]
+            .iter()
+            .flatten()
+            .sum()
+    }
 }
 
 /// 测试状态
@@ -85,6 +121,19 @@ impl TestStatus {
     }
 }
 
+/// 按测试新鲜度批量测试的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleConfigTestResult {
+    /// 本次测试的配置 ID
+    pub tested_config_ids: Vec<i64>,
+
+    /// 最近已测试、被跳过的配置 ID
+    pub skipped_config_ids: Vec<i64>,
+
+    /// 测试结果
+    pub results: Vec<TestResult>,
+}
+
 /// 创建测试结果的输入参数
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTestResultInput {
@@ -300,6 +349,7 @@ mod tests {
             response_text: None,
             test_model: None,
             attempt: None,
+            timing: None,
         };
         assert_eq!(result.latency_grade(), Some("excellent"));
 
@@ -366,6 +416,7 @@ mod tests {
             response_text: Some("Success".to_string()),
             test_model: Some("claude-haiku-4-5-20251001".to_string()),
             attempt: Some(1),
+            timing: None,
         };
         assert!(success_result.is_available());
 
@@ -479,4 +530,17 @@ mod tests {
         };
         assert!(!overloaded_result.is_available());
     }
+
+    #[test]
+    fn test_timing_breakdown_total() {
+        let timing = TimingBreakdown {
+            dns_ms: Some(40),
+            connect_ms: Some(25),
+            tls_ms: None,
+            ttfb_ms: Some(300),
+            measured_at: None,
+        };
+        assert_eq!(timing.total_ms(), 365);
+        assert_eq!(TimingBreakdown::default().total_ms(), 0);
+    }
 }
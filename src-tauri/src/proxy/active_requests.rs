@@ -0,0 +1,233 @@
+/**
+ * Active Request Registry
+ * 记录代理当前正在处理的请求，用于排查卡住的请求与并发情况
+ *
+ * 每个请求在进入 handle_request 时注册，返回的 ActiveRequestHandle 被 drop 时移除；
+ * 流式响应会把 handle 移入等待流结束的任务中，直到流结束才移除。
+ */
+
+use hyper::body::{Bytes, Frame};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+/// 正在处理中的请求 (返回给前端)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveRequestInfo {
+    /// 请求追踪 ID
+    pub request_id: String,
+    /// HTTP 方法
+    pub method: String,
+    /// 请求路径 (不含查询参数)
+    pub path: String,
+    /// 客户端地址
+    pub client_addr: String,
+    /// 使用的配置 ID (路由完成前为 None)
+    pub config_id: Option<i64>,
+    /// 开始时间 (RFC3339)
+    pub started_at: String,
+    /// 已持续时间 (毫秒)
+    pub elapsed_ms: u64,
+    /// 后端响应状态码 (仍在等待响应头时为 None)
+    pub response_status: Option<u16>,
+    /// 是否为流式响应
+    pub is_streaming: bool,
+    /// 已返回给客户端的响应字节数
+    pub bytes_sent: u64,
+}
+
+struct ActiveRequestEntry {
+    info: ActiveRequestInfo,
+    started: Instant,
+    bytes_sent: Arc<AtomicU64>,
+}
+
+/// 正在处理中的请求注册表
+#[derive(Default)]
+pub struct ActiveRequestRegistry {
+    entries: Mutex<HashMap<u64, ActiveRequestEntry>>,
+    next_key: AtomicU64,
+}
+
+impl ActiveRequestRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册新请求，返回的 handle 被 drop 时自动移除
+    pub fn register(
+        self: &Arc<Self>,
+        request_id: &str,
+        method: &str,
+        path: &str,
+        client_addr: &str,
+    ) -> ActiveRequestHandle {
+        let key = self.next_key.fetch_add(1, Ordering::Relaxed);
+        let bytes_sent = Arc::new(AtomicU64::new(0));
+        let entry = ActiveRequestEntry {
+            info: ActiveRequestInfo {
+                request_id: request_id.to_string(),
+                method: method.to_string(),
+                path: path.to_string(),
+                client_addr: client_addr.to_string(),
+                config_id: None,
+                started_at: crate::utils::time::now_rfc3339(),
+                elapsed_ms: 0,
+                response_status: None,
+                is_streaming: false,
+                bytes_sent: 0,
+            },
+            started: Instant::now(),
+            bytes_sent: bytes_sent.clone(),
+        };
+
+        self.lock().insert(key, entry);
+
+        ActiveRequestHandle {
+            registry: self.clone(),
+            key,
+            bytes_sent,
+        }
+    }
+
+    /// 列出正在处理中的请求 (持续时间最长的在前)
+    pub fn list(&self) -> Vec<ActiveRequestInfo> {
+        let mut requests: Vec<ActiveRequestInfo> = self
+            .lock()
+            .values()
+            .map(|entry| {
+                let mut info = entry.info.clone();
+                info.elapsed_ms = entry.started.elapsed().as_millis() as u64;
+                info.bytes_sent = entry.bytes_sent.load(Ordering::Relaxed);
+                info
+            })
+            .collect();
+        requests.sort_by(|a, b| b.elapsed_ms.cmp(&a.elapsed_ms));
+        requests
+    }
+
+    /// 正在处理中的请求数量
+    pub fn count(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn update(&self, key: u64, f: impl FnOnce(&mut ActiveRequestInfo)) {
+        if let Some(entry) = self.lock().get_mut(&key) {
+            f(&mut entry.info);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, ActiveRequestEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 注册表中单个请求的句柄
+pub struct ActiveRequestHandle {
+    registry: Arc<ActiveRequestRegistry>,
+    key: u64,
+    bytes_sent: Arc<AtomicU64>,
+}
+
+impl ActiveRequestHandle {
+    /// 记录路由到的配置
+    pub fn set_config_id(&self, config_id: i64) {
+        self.registry.update(self.key, |info| info.config_id = Some(config_id));
+    }
+
+    /// 记录后端响应状态
+    pub fn set_response(&self, status: u16, is_streaming: bool) {
+        self.registry.update(self.key, |info| {
+            info.response_status = Some(status);
+            info.is_streaming = is_streaming;
+        });
+    }
+
+    /// 包装响应体，统计已返回给客户端的字节数
+    pub fn count_body<B>(&self, body: B) -> ByteCountingBody<B> {
+        ByteCountingBody {
+            inner: body,
+            counter: self.bytes_sent.clone(),
+        }
+    }
+}
+
+impl Drop for ActiveRequestHandle {
+    fn drop(&mut self) {
+        self.registry.lock().remove(&self.key);
+    }
+}
+
+/// 统计已发送字节数的响应体
+pub struct ByteCountingBody<B> {
+    inner: B,
+    counter: Arc<AtomicU64>,
+}
+
+impl<B> http_body::Body for ByteCountingBody<B>
+where
+    B: http_body::Body<Data = Bytes> + Unpin,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        let result = Pin::new(&mut this.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &result {
+            if let Some(data) = frame.data_ref() {
+                this.counter.fetch_add(data.len() as u64, Ordering::Relaxed);
+            }
+        }
+        result
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    #[tokio::test]
+    async fn test_register_update_and_remove() {
+        let registry = Arc::new(ActiveRequestRegistry::new());
+
+        let handle = registry.register("req-1", "POST", "/v1/messages", "127.0.0.1:5000");
+        let other = registry.register("req-2", "GET", "/v1/models", "127.0.0.1:5001");
+        assert_eq!(registry.list().len(), 2);
+
+        handle.set_config_id(7);
+        handle.set_response(200, true);
+        let body = handle.count_body(http_body_util::Full::new(Bytes::from_static(b"hello")));
+        body.collect().await.unwrap();
+
+        let listed = registry.list();
+        let entry = listed.iter().find(|r| r.request_id == "req-1").unwrap();
+        assert_eq!(entry.config_id, Some(7));
+        assert_eq!(entry.response_status, Some(200));
+        assert!(entry.is_streaming);
+        assert_eq!(entry.bytes_sent, 5);
+
+        drop(handle);
+        assert_eq!(registry.list().len(), 1);
+        assert_eq!(registry.count(), 1);
+        drop(other);
+        assert!(registry.list().is_empty());
+        assert_eq!(registry.count(), 0);
+    }
+}
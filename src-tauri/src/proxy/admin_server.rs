@@ -0,0 +1,513 @@
+#![allow(dead_code)]
+
+/**
+ * Admin HTTP API Server
+ * 为无界面（headless）部署提供的最小管理接口
+ *
+ * 特性:
+ * - 独立端口，默认仅监听 127.0.0.1
+ * - Bearer Token 鉴权（必须显式提供非空 token 才能创建）
+ * - 复用 ProxyService / ApiConfigService，与 Tauri 命令行为一致
+ *
+ * 路由:
+ * - GET  /status                   获取代理服务状态
+ * - POST /start                    启动代理服务
+ * - POST /stop                     停止代理服务
+ * - POST /switch/config/{id}       切换当前配置
+ * - POST /switch/group/{id}        切换当前分组
+ * - GET  /configs[?group_id=N]     列出配置（API 密钥已脱敏）
+ */
+
+use crate::db::DbPool;
+use crate::models::api_config::ApiConfig;
+use crate::models::config_report::redact_key;
+use crate::models::error::{AppError, AppResult, ErrorResponse};
+use crate::services::api_config::ApiConfigService;
+use crate::services::proxy_service::ProxyService;
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{HeaderMap, Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, RwLock};
+
+/// 管理接口默认监听地址
+pub const DEFAULT_ADMIN_HOST: &str = "127.0.0.1";
+
+/// 管理接口默认端口
+pub const DEFAULT_ADMIN_PORT: u16 = 25351;
+
+/// 管理接口配置
+#[derive(Debug, Clone)]
+pub struct AdminServerConfig {
+    /// 监听地址
+    pub host: String,
+    /// 监听端口（0 表示由系统分配）
+    pub port: u16,
+    /// Bearer Token
+    pub token: String,
+}
+
+impl AdminServerConfig {
+    /// 使用默认地址和端口创建配置
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            host: DEFAULT_ADMIN_HOST.to_string(),
+            port: DEFAULT_ADMIN_PORT,
+            token: token.into(),
+        }
+    }
+}
+
+/// 配置列表项（不包含明文密钥）
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminConfigSummary {
+    pub id: i64,
+    pub name: String,
+    pub server_url: String,
+    pub group_id: Option<i64>,
+    pub sort_order: i32,
+    pub is_available: bool,
+    pub is_enabled: bool,
+    pub api_key: String,
+}
+
+impl From<&ApiConfig> for AdminConfigSummary {
+    fn from(config: &ApiConfig) -> Self {
+        Self {
+            id: config.id,
+            name: config.name.clone(),
+            server_url: config.server_url.clone(),
+            group_id: config.group_id,
+            sort_order: config.sort_order,
+            is_available: config.is_available,
+            is_enabled: config.is_enabled,
+            api_key: redact_key(&config.api_key),
+        }
+    }
+}
+
+/// 管理接口服务器
+pub struct AdminServer {
+    config: AdminServerConfig,
+    proxy_service: Arc<ProxyService>,
+    db_pool: Arc<DbPool>,
+    shutdown_tx: Arc<RwLock<Option<broadcast::Sender<()>>>>,
+    local_addr: Arc<RwLock<Option<SocketAddr>>>,
+}
+
+impl AdminServer {
+    /// 创建管理接口服务器（不会自动启动）
+    ///
+    /// # Errors
+    /// - token 为空时返回 `ValidationError`，管理接口必须鉴权
+    pub fn new(
+        config: AdminServerConfig,
+        proxy_service: Arc<ProxyService>,
+        db_pool: Arc<DbPool>,
+    ) -> AppResult<Self> {
+        if config.token.trim().is_empty() {
+            return Err(AppError::ValidationError {
+                field: "token".to_string(),
+                message: "管理接口必须配置非空的访问令牌".to_string(),
+            });
+        }
+
+        Ok(Self {
+            config,
+            proxy_service,
+            db_pool,
+            shutdown_tx: Arc::new(RwLock::new(None)),
+            local_addr: Arc::new(RwLock::new(None)),
+        })
+    }
+
+    /// 当前监听地址（未运行时为 None）
+    pub async fn local_addr(&self) -> Option<SocketAddr> {
+        *self.local_addr.read().await
+    }
+
+    /// 是否正在运行
+    pub async fn is_running(&self) -> bool {
+        self.shutdown_tx.read().await.is_some()
+    }
+
+    /// 启动管理接口
+    ///
+    /// # Returns
+    /// - 实际监听地址
+    pub async fn start(&self) -> AppResult<SocketAddr> {
+        let mut shutdown_guard = self.shutdown_tx.write().await;
+        if shutdown_guard.is_some() {
+            return Err(AppError::AlreadyRunning);
+        }
+
+        let addr = format!("{}:{}", self.config.host, self.config.port);
+        let listener = TcpListener::bind(&addr).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::AddrInUse {
+                AppError::PortInUse { port: self.config.port }
+            } else {
+                AppError::IoError {
+                    message: format!("管理接口监听 {} 失败: {}", addr, e),
+                }
+            }
+        })?;
+        let local_addr = listener.local_addr().map_err(|e| AppError::IoError {
+            message: format!("获取管理接口监听地址失败: {}", e),
+        })?;
+
+        let (shutdown_tx, _) = broadcast::channel::<()>(1);
+        *shutdown_guard = Some(shutdown_tx.clone());
+        drop(shutdown_guard);
+        *self.local_addr.write().await = Some(local_addr);
+
+        let token: Arc<str> = Arc::from(self.config.token.as_str());
+        let proxy_service = self.proxy_service.clone();
+        let db_pool = self.db_pool.clone();
+
+        log::info!("Admin API listening on {}", local_addr);
+
+        tokio::spawn(async move {
+            let mut shutdown_rx = shutdown_tx.subscribe();
+
+            loop {
+                tokio::select! {
+                    accept_result = listener.accept() => {
+                        let (stream, remote_addr) = match accept_result {
+                            Ok(accepted) => accepted,
+                            Err(e) => {
+                                log::error!("Admin API failed to accept connection: {}", e);
+                                continue;
+                            }
+                        };
+
+                        let token = token.clone();
+                        let proxy_service = proxy_service.clone();
+                        let db_pool = db_pool.clone();
+                        let mut conn_shutdown_rx = shutdown_tx.subscribe();
+
+                        tokio::spawn(async move {
+                            let io = TokioIo::new(stream);
+                            let service = service_fn(move |req: Request<Incoming>| {
+                                let token = token.clone();
+                                let proxy_service = proxy_service.clone();
+                                let db_pool = db_pool.clone();
+                                async move {
+                                    Ok::<_, hyper::Error>(
+                                        handle_request(req, &token, &proxy_service, &db_pool).await,
+                                    )
+                                }
+                            });
+
+                            let conn = http1::Builder::new().serve_connection(io, service);
+                            tokio::select! {
+                                result = conn => {
+                                    if let Err(e) = result {
+                                        log::debug!("Admin API connection error ({}): {}", remote_addr, e);
+                                    }
+                                }
+                                _ = conn_shutdown_rx.recv() => {}
+                            }
+                        });
+                    }
+                    _ = shutdown_rx.recv() => {
+                        log::info!("Admin API stopped");
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(local_addr)
+    }
+
+    /// 停止管理接口
+    pub async fn stop(&self) -> AppResult<()> {
+        let tx = self.shutdown_tx.write().await.take();
+        match tx {
+            Some(tx) => {
+                let _ = tx.send(());
+                *self.local_addr.write().await = None;
+                Ok(())
+            }
+            None => Err(AppError::AlreadyStopped),
+        }
+    }
+}
+
+/// 校验 Authorization 头（常量时间比较，避免计时侧信道）
+fn is_authorized(headers: &HeaderMap, token: &str) -> bool {
+    let provided = match headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        Some(value) => value.trim().as_bytes(),
+        None => return false,
+    };
+    let expected = token.as_bytes();
+
+    let mut diff = provided.len() ^ expected.len();
+    for (i, byte) in expected.iter().enumerate() {
+        diff |= (byte ^ provided.get(i).copied().unwrap_or(0)) as usize;
+    }
+    diff == 0
+}
+
+/// 将应用错误映射为 HTTP 状态码
+fn error_status(error: &AppError) -> StatusCode {
+    match error {
+        AppError::NotFound { .. } => StatusCode::NOT_FOUND,
+        AppError::ValidationError { .. }
+        | AppError::EmptyGroup { .. }
+        | AppError::ConfigNotInGroup { .. }
+        | AppError::ConfigUnavailable { .. } => StatusCode::BAD_REQUEST,
+        AppError::InvalidState { .. }
+        | AppError::AlreadyRunning
+        | AppError::AlreadyStopped
+        | AppError::PortInUse { .. } => StatusCode::CONFLICT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn json_response<T: Serialize>(status: StatusCode, body: &T) -> Response<Full<Bytes>> {
+    let payload = serde_json::to_vec(body).unwrap_or_else(|_| b"{}".to_vec());
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Full::new(Bytes::from(payload)))
+        .unwrap()
+}
+
+fn error_response(status: StatusCode, error: &str, message: impl Into<String>) -> Response<Full<Bytes>> {
+    json_response(
+        status,
+        &ErrorResponse {
+            error: error.to_string(),
+            message: message.into(),
+            details: None,
+        },
+    )
+}
+
+fn result_response<T: Serialize>(result: AppResult<T>) -> Response<Full<Bytes>> {
+    match result {
+        Ok(value) => json_response(StatusCode::OK, &value),
+        Err(e) => {
+            let status = error_status(&e);
+            json_response(status, &ErrorResponse::from(e))
+        }
+    }
+}
+
+/// 解析查询参数中的 group_id
+fn parse_group_id_query(query: Option<&str>) -> Result<Option<i64>, String> {
+    let Some(query) = query else {
+        return Ok(None);
+    };
+    for pair in query.split('&') {
+        if let Some(value) = pair.strip_prefix("group_id=") {
+            return value
+                .parse::<i64>()
+                .map(Some)
+                .map_err(|_| format!("无效的 group_id: {}", value));
+        }
+    }
+    Ok(None)
+}
+
+/// 处理单个管理请求
+async fn handle_request<B>(
+    req: Request<B>,
+    token: &str,
+    proxy_service: &ProxyService,
+    db_pool: &DbPool,
+) -> Response<Full<Bytes>>
+where
+    B: hyper::body::Body,
+{
+    if !is_authorized(req.headers(), token) {
+        return error_response(StatusCode::UNAUTHORIZED, "Unauthorized", "缺少或无效的访问令牌");
+    }
+
+    let method = req.method().clone();
+    let path = req.uri().path().trim_end_matches('/').to_string();
+    let query = req.uri().query().map(str::to_string);
+    // 管理接口不需要请求体，直接丢弃
+    let _ = req.into_body().collect().await;
+
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    match (&method, segments.as_slice()) {
+        (&Method::GET, ["status"]) => result_response(proxy_service.get_status().await),
+        (&Method::POST, ["start"]) => result_response(proxy_service.start().await),
+        (&Method::POST, ["stop"]) => result_response(proxy_service.stop().await),
+        (&Method::POST, ["switch", kind @ ("config" | "group"), id]) => {
+            let Ok(id) = id.parse::<i64>() else {
+                return error_response(StatusCode::BAD_REQUEST, "ValidationError", format!("无效的 ID: {}", id));
+            };
+            if *kind == "config" {
+                result_response(proxy_service.switch_config(id).await)
+            } else {
+                result_response(proxy_service.switch_group(id).await)
+            }
+        }
+        (&Method::GET, ["configs"]) => {
+            let group_id = match parse_group_id_query(query.as_deref()) {
+                Ok(group_id) => group_id,
+                Err(message) => {
+                    return error_response(StatusCode::BAD_REQUEST, "ValidationError", message);
+                }
+            };
+            result_response(
+                db_pool
+                    .with_connection(|conn| ApiConfigService::list_configs(conn, group_id))
+                    .map(|configs| configs.iter().map(AdminConfigSummary::from).collect::<Vec<_>>()),
+            )
+        }
+        _ => error_response(
+            StatusCode::NOT_FOUND,
+            "NotFound",
+            format!("未知的管理接口: {} {}", method, path),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn setup_pool() -> Arc<DbPool> {
+        let pool = DbPool::new(crate::db::test_db());
+        pool.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO ApiConfig (id, name, api_key, server_url)
+                 VALUES (1, 'primary', 'sk-admin-secret-key', 'https://api.example.com')",
+                [],
+            )
+            .unwrap();
+            Ok(())
+        })
+        .unwrap();
+        Arc::new(pool)
+    }
+
+    fn request(method: Method, uri: &str, token: Option<&str>) -> Request<Full<Bytes>> {
+        let mut builder = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            builder = builder.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+        builder.body(Full::new(Bytes::new())).unwrap()
+    }
+
+    async fn body_json(response: Response<Full<Bytes>>) -> serde_json::Value {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_admin_server_requires_token() {
+        let pool = setup_pool();
+        let proxy_service = Arc::new(ProxyService::new(pool.clone()));
+        let result = AdminServer::new(AdminServerConfig::new("  "), proxy_service, pool);
+        assert!(matches!(result, Err(AppError::ValidationError { .. })));
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_authorized(&headers, "secret"));
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        assert!(is_authorized(&headers, "secret"));
+        assert!(!is_authorized(&headers, "secret2"));
+        assert!(!is_authorized(&headers, "secre"));
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("secret"));
+        assert!(!is_authorized(&headers, "secret"));
+    }
+
+    #[test]
+    fn test_parse_group_id_query() {
+        assert_eq!(parse_group_id_query(None), Ok(None));
+        assert_eq!(parse_group_id_query(Some("group_id=3")), Ok(Some(3)));
+        assert_eq!(parse_group_id_query(Some("a=1&group_id=7")), Ok(Some(7)));
+        assert!(parse_group_id_query(Some("group_id=x")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handle_request_routes() {
+        let pool = setup_pool();
+        let proxy_service = ProxyService::new(pool.clone());
+
+        let unauthorized =
+            handle_request(request(Method::GET, "/status", None), "secret", &proxy_service, &pool).await;
+        assert_eq!(unauthorized.status(), StatusCode::UNAUTHORIZED);
+
+        let status =
+            handle_request(request(Method::GET, "/status", Some("secret")), "secret", &proxy_service, &pool).await;
+        assert_eq!(status.status(), StatusCode::OK);
+        assert_eq!(body_json(status).await["status"], "stopped");
+
+        let configs =
+            handle_request(request(Method::GET, "/configs", Some("secret")), "secret", &proxy_service, &pool).await;
+        assert_eq!(configs.status(), StatusCode::OK);
+        let configs = body_json(configs).await;
+        assert_eq!(configs[0]["name"], "primary");
+        assert_eq!(configs[0]["api_key"], "****-key");
+
+        let stop =
+            handle_request(request(Method::POST, "/stop", Some("secret")), "secret", &proxy_service, &pool).await;
+        assert!(stop.status().is_client_error() || stop.status().is_server_error());
+
+        let invalid = handle_request(
+            request(Method::POST, "/switch/config/abc", Some("secret")),
+            "secret",
+            &proxy_service,
+            &pool,
+        )
+        .await;
+        assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+
+        let unknown =
+            handle_request(request(Method::GET, "/nope", Some("secret")), "secret", &proxy_service, &pool).await;
+        assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_admin_server_start_stop() {
+        let pool = setup_pool();
+        let proxy_service = Arc::new(ProxyService::new(pool.clone()));
+        let config = AdminServerConfig {
+            port: 0,
+            ..AdminServerConfig::new("secret")
+        };
+        let server = AdminServer::new(config, proxy_service, pool).unwrap();
+
+        let addr = server.start().await.unwrap();
+        assert!(server.is_running().await);
+        assert!(matches!(server.start().await, Err(AppError::AlreadyRunning)));
+
+        let client = reqwest::Client::new();
+        let response = client
+            .get(format!("http://{}/status", addr))
+            .bearer_auth("secret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+
+        let response = client.get(format!("http://{}/status", addr)).send().await.unwrap();
+        assert_eq!(response.status().as_u16(), 401);
+
+        server.stop().await.unwrap();
+        assert!(!server.is_running().await);
+        assert!(matches!(server.stop().await, Err(AppError::AlreadyStopped)));
+    }
+}
@@ -0,0 +1,187 @@
+/**
+ * Canary Routing
+ * 按分组设置的百分比将部分请求路由到金丝雀配置，用于在全量切换前验证新配置
+ *
+ * 金丝雀请求不参与分组的自动切换：失败时不切换活跃配置，而是用缓冲的请求体在活跃配置上
+ * 重试一次，并累计连续失败次数，达到上限后将该分组的金丝雀百分比置为 0 (暂停金丝雀)。
+ */
+
+use crate::db::DbPool;
+use crate::models::config_group::ConfigGroup;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// 日志中金丝雀请求的路由来源标记
+pub const CANARY_ROUTING_SOURCE: &str = "canary";
+
+/// 连续失败达到该次数后暂停金丝雀
+pub const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// 分组 ID -> 金丝雀连续失败次数
+static CONSECUTIVE_FAILURES: Mutex<BTreeMap<i64, u32>> = Mutex::new(BTreeMap::new());
+
+/// 为本次请求选择金丝雀配置
+///
+/// 金丝雀配置与当前活跃配置相同时不分流
+pub fn pick_canary(group: &ConfigGroup, active_config_id: i64) -> Option<i64> {
+    let (canary_config_id, percentage) = group.active_canary()?;
+    if canary_config_id == active_config_id {
+        return None;
+    }
+    should_route(percentage, rand::random::<u32>()).then_some(canary_config_id)
+}
+
+/// 以随机数 `roll` 判断是否命中百分比
+fn should_route(percentage: i32, roll: u32) -> bool {
+    percentage > 0 && (roll % 100) < percentage as u32
+}
+
+/// 记录金丝雀请求结果，返回是否应暂停金丝雀
+fn note_outcome(group_id: i64, success: bool) -> bool {
+    let mut failures = CONSECUTIVE_FAILURES.lock().unwrap_or_else(|e| e.into_inner());
+    if success {
+        failures.remove(&group_id);
+        return false;
+    }
+    let count = failures.entry(group_id).or_insert(0);
+    *count += 1;
+    if *count >= MAX_CONSECUTIVE_FAILURES {
+        failures.remove(&group_id);
+        true
+    } else {
+        false
+    }
+}
+
+/// 清除分组的连续失败计数 (重新设置金丝雀时调用)
+pub fn reset(group_id: i64) {
+    CONSECUTIVE_FAILURES.lock().unwrap_or_else(|e| e.into_inner()).remove(&group_id);
+}
+
+/// 记录金丝雀请求结果，连续失败达到上限时暂停该分组的金丝雀
+pub fn record_outcome(db_pool: &DbPool, group_id: i64, canary_config_id: i64, success: bool) {
+    if !note_outcome(group_id, success) {
+        return;
+    }
+
+    log::warn!(
+        "Canary config {} failed {} times in a row, pausing canary for group {}",
+        canary_config_id,
+        MAX_CONSECUTIVE_FAILURES,
+        group_id
+    );
+    if let Err(e) = db_pool.with_connection(|conn| {
+        conn.execute(
+            "UPDATE ConfigGroup SET canary_percentage = 0, updated_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            [group_id],
+        )
+        .map_err(|e| crate::models::error::AppError::DatabaseError {
+            message: format!("暂停金丝雀失败: {}", e),
+        })
+    }) {
+        log::error!("Failed to pause canary for group {}: {}", group_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(canary_config_id: Option<i64>, canary_percentage: i32) -> ConfigGroup {
+        ConfigGroup {
+            id: 1,
+            name: "g".to_string(),
+            description: None,
+            auto_switch_enabled: false,
+            latency_threshold_ms: 100000,
+            retry_count: 3,
+            retry_base_delay_ms: 2000,
+            retry_max_delay_ms: 8000,
+            rate_limit_delay_ms: 30000,
+            retry_strategy_customized: false,
+            health_check_enabled: true,
+            health_check_interval_sec: 60,
+            filter_sse_keepalive: false,
+            body_transform: None,
+            forward_client_ip: false,
+            sse_retry_ms: None,
+            canary_config_id,
+            canary_percentage,
+            native_claude_only: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_pick_canary() {
+        assert_eq!(pick_canary(&group(None, 100), 1), None);
+        assert_eq!(pick_canary(&group(Some(2), 0), 1), None);
+        assert_eq!(pick_canary(&group(Some(1), 100), 1), None);
+        assert_eq!(pick_canary(&group(Some(2), 100), 1), Some(2));
+
+        assert!(should_route(10, 109));
+        assert!(!should_route(10, 110));
+        assert!(!should_route(0, 0));
+    }
+
+    #[test]
+    fn test_consecutive_failures_pause_canary() {
+        let group_id = 9001;
+        assert!(!note_outcome(group_id, false));
+        assert!(!note_outcome(group_id, false));
+        // 成功请求重置计数
+        assert!(!note_outcome(group_id, true));
+        assert!(!note_outcome(group_id, false));
+        assert!(!note_outcome(group_id, false));
+        assert!(note_outcome(group_id, false));
+        // 暂停后重新计数
+        assert!(!note_outcome(group_id, false));
+    }
+
+    #[test]
+    fn test_set_canary_and_pause() {
+        use crate::models::error::AppError;
+        use crate::services::config_manager::ConfigManager;
+
+        let conn = crate::db::test_db();
+        conn.execute_batch(
+            "INSERT INTO ConfigGroup (id, name) VALUES (9002, 'g');
+             INSERT INTO ConfigGroup (id, name) VALUES (9003, 'other');
+             INSERT INTO ApiConfig (id, name, api_key, server_url, server_port, group_id)
+                 VALUES (1, 'canary', 'k', 'https://example.com', 443, 9002);
+             INSERT INTO ApiConfig (id, name, api_key, server_url, server_port, group_id)
+                 VALUES (2, 'elsewhere', 'k', 'https://example.com', 443, 9003);
+             INSERT INTO ApiConfig (id, name, api_key, server_url, server_port, group_id, is_enabled)
+                 VALUES (3, 'disabled', 'k', 'https://example.com', 443, 9002, 0);
+             INSERT INTO ApiConfig (id, name, api_key, server_url, server_port, group_id, is_available)
+                 VALUES (4, 'unavailable', 'k', 'https://example.com', 443, 9002, 0);",
+        )
+        .unwrap();
+
+        assert!(ConfigManager::set_canary(&conn, 9002, Some(1), 101).is_err());
+        assert!(ConfigManager::set_canary(&conn, 9002, Some(99), 10).is_err());
+        for rejected in [2, 3, 4] {
+            assert!(matches!(
+                ConfigManager::set_canary(&conn, 9002, Some(rejected), 10),
+                Err(AppError::ValidationError { .. })
+            ));
+        }
+        let group = ConfigManager::set_canary(&conn, 9002, Some(1), 10).unwrap();
+        assert_eq!(group.active_canary(), Some((1, 10)));
+
+        let pool = DbPool::new(conn);
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            record_outcome(&pool, 9002, 1, false);
+        }
+        let group = pool.with_connection(|conn| ConfigManager::get_group_by_id(conn, 9002)).unwrap();
+        assert_eq!(group.canary_config_id, Some(1));
+        assert_eq!(group.active_canary(), None);
+
+        // 清除金丝雀配置时百分比同时归零
+        let group = pool
+            .with_connection(|conn| ConfigManager::set_canary(conn, 9002, None, 50))
+            .unwrap();
+        assert_eq!((group.canary_config_id, group.canary_percentage), (None, 0));
+    }
+}
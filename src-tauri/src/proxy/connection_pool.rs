@@ -0,0 +1,185 @@
+/**
+ * Backend Connection Stats
+ * 按后端主机统计 try_forward 建立的连接，并提供清除按主机缓存的状态的入口
+ *
+ * 当前每个转发请求都会重新解析 DNS 并建立独立的 TCP/TLS 连接，响应结束后连接即关闭，
+ * 没有空闲连接可以复用，因此空闲连接数与复用率始终为 0。
+ * 清除时重置统计，并丢弃按主机记录的请求体压缩支持情况 (见 request_compression)。
+ */
+
+use super::request_compression;
+use crate::utils::time::now_rfc3339;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// 单个后端主机的连接计数
+#[derive(Debug, Clone, Default)]
+struct HostCounters {
+    active: u32,
+    opened: u64,
+    failed: u64,
+    last_connected_at: Option<String>,
+}
+
+/// 后端地址 (host:port) -> 连接计数
+static HOSTS: Mutex<BTreeMap<String, HostCounters>> = Mutex::new(BTreeMap::new());
+
+/// 单个后端主机的连接统计
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HostConnectionStats {
+    /// 后端地址 (host:port)
+    pub host: String,
+    /// 正在使用的连接数
+    pub active: u32,
+    /// 空闲 (可复用) 连接数
+    pub idle: u32,
+    /// 累计建立的连接数
+    pub opened: u64,
+    /// 累计连接失败次数 (DNS / TCP / TLS / 超时)
+    pub failed: u64,
+    /// 最近一次成功建立连接的时间
+    pub last_connected_at: Option<String>,
+}
+
+/// 后端连接统计
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConnectionPoolStats {
+    /// 是否复用后端连接
+    pub pooling_enabled: bool,
+    /// 连接复用率 (复用次数 / 请求次数)
+    pub hit_rate: f64,
+    pub hosts: Vec<HostConnectionStats>,
+}
+
+/// 清除结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectionPoolClearResult {
+    /// 丢弃的空闲连接数
+    pub dropped_idle_connections: usize,
+    /// 重置统计的主机数
+    pub reset_hosts: usize,
+    /// 清除的按主机缓存状态条目数 (请求体压缩支持情况)
+    pub cleared_host_state: usize,
+}
+
+/// 使用中的后端连接，释放时减少主机的活跃连接数
+pub struct ActiveConnection {
+    host: String,
+}
+
+impl Drop for ActiveConnection {
+    fn drop(&mut self) {
+        let mut hosts = HOSTS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(counters) = hosts.get_mut(&self.host) {
+            counters.active = counters.active.saturating_sub(1);
+        }
+    }
+}
+
+/// 记录成功建立的连接，返回的句柄应在连接关闭时释放
+pub fn connection_opened(host: &str) -> ActiveConnection {
+    let mut hosts = HOSTS.lock().unwrap_or_else(|e| e.into_inner());
+    let counters = hosts.entry(host.to_string()).or_default();
+    counters.active += 1;
+    counters.opened += 1;
+    counters.last_connected_at = Some(now_rfc3339());
+    ActiveConnection { host: host.to_string() }
+}
+
+/// 记录连接失败
+pub fn connection_failed(host: &str) {
+    HOSTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(host.to_string())
+        .or_default()
+        .failed += 1;
+}
+
+/// 当前连接统计
+pub fn stats() -> ConnectionPoolStats {
+    let hosts = HOSTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|(host, counters)| HostConnectionStats {
+            host: host.clone(),
+            active: counters.active,
+            idle: 0,
+            opened: counters.opened,
+            failed: counters.failed,
+            last_connected_at: counters.last_connected_at.clone(),
+        })
+        .collect();
+
+    ConnectionPoolStats {
+        pooling_enabled: false,
+        hit_rate: 0.0,
+        hosts,
+    }
+}
+
+/// 重置连接统计并清除按主机缓存的状态
+pub fn clear() -> ConnectionPoolClearResult {
+    let reset_hosts = reset_counters();
+    let cleared_host_state = request_compression::clear();
+    log::info!(
+        "Connection stats reset for {} host(s), cleared {} cached host state entries",
+        reset_hosts,
+        cleared_host_state
+    );
+
+    ConnectionPoolClearResult {
+        dropped_idle_connections: 0,
+        reset_hosts,
+        cleared_host_state,
+    }
+}
+
+/// 重置连接计数，返回重置的主机数
+///
+/// 仍在使用的连接保留活跃计数，连接关闭时正常扣减
+fn reset_counters() -> usize {
+    let mut hosts = HOSTS.lock().unwrap_or_else(|e| e.into_inner());
+    let reset_hosts = hosts.len();
+    hosts.retain(|_, counters| counters.active > 0);
+    for counters in hosts.values_mut() {
+        counters.opened = 0;
+        counters.failed = 0;
+    }
+    reset_hosts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host_stats(host: &str) -> Option<HostConnectionStats> {
+        stats().hosts.into_iter().find(|h| h.host == host)
+    }
+
+    #[test]
+    fn test_connection_counters() {
+        let host = "pool-test.example.com:443";
+        let first = connection_opened(host);
+        let second = connection_opened(host);
+        connection_failed(host);
+
+        let current = host_stats(host).unwrap();
+        assert_eq!((current.active, current.idle, current.opened, current.failed), (2, 0, 2, 1));
+        assert!(current.last_connected_at.is_some());
+
+        drop(first);
+        assert_eq!(host_stats(host).unwrap().active, 1);
+
+        // 重置后保留仍在使用的连接
+        reset_counters();
+        let current = host_stats(host).unwrap();
+        assert_eq!((current.active, current.opened, current.failed), (1, 0, 0));
+
+        drop(second);
+        reset_counters();
+        assert!(host_stats(host).is_none());
+    }
+}
@@ -0,0 +1,92 @@
+/**
+ * Latency Injection (测试辅助)
+ * 为指定配置的转发请求注入人为延迟，用于验证高延迟自动切换
+ * (forward_request 中 latency > latency_threshold 触发 SwitchReason::HighLatency)
+ *
+ * 仅作为调试工具使用：设置保存在内存中，不写入数据库，应用重启后自动失效。
+ * 延迟在 try_forward 读取配置之后、连接后端之前注入，计入请求总耗时。
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 注入延迟上限 (毫秒)
+pub const MAX_ARTIFICIAL_LATENCY_MS: u64 = 10 * 60 * 1000;
+
+/// 配置 ID -> 注入的延迟 (毫秒)
+static ARTIFICIAL_LATENCY: Mutex<BTreeMap<i64, u64>> = Mutex::new(BTreeMap::new());
+
+/// 已注入延迟的配置 (返回给前端)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtificialLatency {
+    pub config_id: i64,
+    pub latency_ms: u64,
+}
+
+/// 为配置设置注入延迟，0 表示清除
+pub fn set(config_id: i64, latency_ms: u64) {
+    let mut latencies = ARTIFICIAL_LATENCY.lock().unwrap_or_else(|e| e.into_inner());
+    if latency_ms == 0 {
+        if latencies.remove(&config_id).is_some() {
+            log::info!("Artificial latency cleared for config {}", config_id);
+        }
+    } else {
+        let latency_ms = latency_ms.min(MAX_ARTIFICIAL_LATENCY_MS);
+        latencies.insert(config_id, latency_ms);
+        log::warn!("Artificial latency of {}ms injected for config {} (testing aid)", latency_ms, config_id);
+    }
+}
+
+/// 清除指定配置 (None 表示全部) 的注入延迟，返回清除的配置数
+pub fn clear(config_id: Option<i64>) -> usize {
+    let mut latencies = ARTIFICIAL_LATENCY.lock().unwrap_or_else(|e| e.into_inner());
+    let cleared = match config_id {
+        Some(id) => latencies.remove(&id).map_or(0, |_| 1),
+        None => std::mem::take(&mut *latencies).len(),
+    };
+    if cleared > 0 {
+        log::info!("Artificial latency cleared for {} config(s)", cleared);
+    }
+    cleared
+}
+
+/// 当前所有注入延迟
+pub fn list() -> Vec<ArtificialLatency> {
+    ARTIFICIAL_LATENCY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|(&config_id, &latency_ms)| ArtificialLatency { config_id, latency_ms })
+        .collect()
+}
+
+/// 配置的注入延迟
+pub fn delay_for(config_id: i64) -> Option<Duration> {
+    ARTIFICIAL_LATENCY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&config_id)
+        .map(|&ms| Duration::from_millis(ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_clear_latency() {
+        set(9001, 1500);
+        set(9002, MAX_ARTIFICIAL_LATENCY_MS * 2);
+        assert_eq!(delay_for(9001), Some(Duration::from_millis(1500)));
+        assert_eq!(delay_for(9002), Some(Duration::from_millis(MAX_ARTIFICIAL_LATENCY_MS)));
+        assert!(list().contains(&ArtificialLatency { config_id: 9001, latency_ms: 1500 }));
+
+        set(9001, 0);
+        assert_eq!(delay_for(9001), None);
+        assert_eq!(clear(Some(9002)), 1);
+        assert_eq!(clear(Some(9002)), 0);
+        assert_eq!(delay_for(9002), None);
+    }
+}
@@ -13,6 +13,7 @@
  * - Timing details
  */
 
+use crate::proxy::token_usage::TokenUsage;
 use chrono::{DateTime, Local};
 use hyper::{Method, StatusCode, Uri};
 use std::time::Instant;
@@ -69,6 +70,10 @@ pub struct RequestLogEntry {
     pub user_agent: Option<String>,
     /// Model name if available (e.g., claude-3-opus)
     pub model: Option<String>,
+    /// Request tracing ID (x-request-id)
+    pub request_id: Option<String>,
+    /// Token usage (backend-reported or locally estimated)
+    pub token_usage: TokenUsage,
 }
 
 impl RequestLogEntry {
@@ -106,9 +111,15 @@ impl RequestLogEntry {
             None => String::new(),
         };
 
+        let request_info = match &self.request_id {
+            Some(id) => format!(" [{}]", id),
+            None => String::new(),
+        };
+
         format!(
-            "{} {} {} -> {} {} {} {}ms{}",
+            "{}{} {} {} -> {} {} {} {}ms{}",
             self.timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            request_info,
             self.method,
             self.uri,
             self.target_url,
@@ -173,6 +184,8 @@ impl ProxyLogger {
             content_type: None,
             model: None,
             response_start_time: None,
+            request_id: None,
+            token_usage: TokenUsage::default(),
         }
     }
 }
@@ -195,6 +208,8 @@ pub struct RequestLogBuilder {
     content_type: Option<String>,
     model: Option<String>,
     response_start_time: Option<Instant>,
+    request_id: Option<String>,
+    token_usage: TokenUsage,
 }
 
 impl RequestLogBuilder {
@@ -242,6 +257,18 @@ impl RequestLogBuilder {
         self
     }
 
+    /// Set request tracing ID
+    pub fn with_request_id(mut self, request_id: String) -> Self {
+        self.request_id = Some(request_id);
+        self
+    }
+
+    /// Set token usage
+    pub fn with_token_usage(mut self, token_usage: TokenUsage) -> Self {
+        self.token_usage = token_usage;
+        self
+    }
+
     /// Mark response start time
     pub fn mark_response_start(&mut self) {
         self.response_start_time = Some(Instant::now());
@@ -280,6 +307,8 @@ impl RequestLogBuilder {
             content_type: self.content_type,
             user_agent: self.user_agent,
             model: self.model,
+            request_id: self.request_id,
+            token_usage: self.token_usage,
         }
     }
 
@@ -324,6 +353,8 @@ impl RequestLogBuilder {
             content_type: self.content_type,
             user_agent: self.user_agent,
             model: self.model,
+            request_id: self.request_id,
+            token_usage: self.token_usage,
         }
     }
 
@@ -361,6 +392,8 @@ impl RequestLogBuilder {
             content_type: self.content_type,
             user_agent: self.user_agent,
             model: self.model,
+            request_id: self.request_id,
+            token_usage: self.token_usage,
         }
     }
 }
@@ -396,6 +429,8 @@ mod tests {
             content_type: None,
             user_agent: None,
             model: None,
+            request_id: None,
+            token_usage: TokenUsage::default(),
         }
     }
 
@@ -474,6 +509,20 @@ mod tests {
         assert_eq!(entry.error, Some("Server down".to_string()));
     }
 
+    #[test]
+    fn test_request_log_builder_with_request_id() {
+        let entry = ProxyLogger::start_request(
+            Method::POST,
+            "/v1/messages".parse().unwrap(),
+            "127.0.0.1:12345".to_string(),
+        )
+        .with_request_id("req-20250101000000-000001".to_string())
+        .finish(StatusCode::OK);
+
+        assert_eq!(entry.request_id.as_deref(), Some("req-20250101000000-000001"));
+        assert!(entry.format_oneline().contains("[req-20250101000000-000001]"));
+    }
+
     #[test]
     fn test_request_log_builder_with_details() {
         let builder = ProxyLogger::start_request(
@@ -3,6 +3,8 @@
  * 提供 HTTP 代理服务器、请求路由、错误处理等功能
  */
 
+pub mod active_requests;
+pub mod admin_server;
 pub mod server;
 pub mod router;
 pub mod error_handler;
@@ -11,8 +13,16 @@ pub mod stream_converter;
 pub mod logger;
 pub mod protocol_detector;
 pub mod structured_logger;
+pub mod prometheus;
+pub mod request_trace;
+pub mod latency_injection;
+pub mod canary;
+pub mod request_compression;
+pub mod connection_pool;
+pub mod token_usage;
 pub mod client_detector;
 pub mod smart_router;
+pub mod sse_filter;
 
 // 重新导出公共类型
 #[allow(unused_imports)]
@@ -24,7 +34,7 @@ pub use error_handler::{ProxyErrorHandler, ProxyErrorType};
 #[allow(unused_imports)]
 pub use stream_converter::{
     OpenAIToClaudeStreamConverter, ClaudeToOpenAIStreamConverter,
-    StreamState, StreamIntegrityReport,
+    StreamState, StreamIntegrityReport, StreamEventTracker,
 };
 #[allow(unused_imports)]
 pub use structured_logger::{
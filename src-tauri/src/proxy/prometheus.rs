@@ -0,0 +1,145 @@
+/**
+ * Prometheus 指标导出
+ * 将 METRICS 中聚合的请求指标渲染为 Prometheus 文本格式 (exposition format 0.0.4)
+ *
+ * 指标:
+ * - claude_proxy_requests_total{config_id,config_name,status}     请求数 (counter)
+ * - claude_proxy_request_duration_ms{config_id,config_name,le}    请求延迟 (histogram)
+ * - claude_proxy_tokens_total{config_id,config_name,direction}    token 总数 (counter)
+ * - claude_proxy_streaming_requests_total                         流式请求数 (counter)
+ * - claude_proxy_active_requests                                  正在处理的请求数 (gauge)
+ */
+
+use super::structured_logger::{ConfigMetrics, MetricsCollector, LATENCY_BUCKETS_MS, METRICS};
+use std::fmt::Write;
+
+/// Prometheus 文本格式的 Content-Type
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// 渲染全局指标
+///
+/// `active_requests` 为正在处理的请求数 (流式请求在流结束前都计入)
+pub fn render_metrics(active_requests: usize) -> String {
+    render(&METRICS, active_requests)
+}
+
+/// 渲染指定收集器的指标
+pub fn render(collector: &MetricsCollector, active_requests: usize) -> String {
+    let totals = collector.snapshot();
+    let per_config = collector.config_snapshot();
+    let mut out = String::new();
+
+    out.push_str("# HELP claude_proxy_requests_total Proxied requests by config and response status.\n");
+    out.push_str("# TYPE claude_proxy_requests_total counter\n");
+    for (config_id, metrics) in &per_config {
+        for (status, count) in &metrics.requests_by_status {
+            let _ = writeln!(
+                out,
+                "claude_proxy_requests_total{{{},status=\"{}\"}} {}",
+                config_labels(*config_id, metrics),
+                status,
+                count
+            );
+        }
+    }
+
+    out.push_str("# HELP claude_proxy_request_duration_ms Proxied request latency in milliseconds.\n");
+    out.push_str("# TYPE claude_proxy_request_duration_ms histogram\n");
+    for (config_id, metrics) in &per_config {
+        let labels = config_labels(*config_id, metrics);
+        let mut cumulative = 0u64;
+        for (i, bound) in LATENCY_BUCKETS_MS.iter().enumerate() {
+            cumulative += metrics.latency_bucket_counts.get(i).copied().unwrap_or(0);
+            let _ = writeln!(
+                out,
+                "claude_proxy_request_duration_ms_bucket{{{},le=\"{}\"}} {}",
+                labels, bound, cumulative
+            );
+        }
+        let _ = writeln!(
+            out,
+            "claude_proxy_request_duration_ms_bucket{{{},le=\"+Inf\"}} {}",
+            labels, metrics.latency_count
+        );
+        let _ = writeln!(out, "claude_proxy_request_duration_ms_sum{{{}}} {}", labels, metrics.latency_sum_ms);
+        let _ = writeln!(out, "claude_proxy_request_duration_ms_count{{{}}} {}", labels, metrics.latency_count);
+    }
+
+    out.push_str("# HELP claude_proxy_tokens_total Tokens consumed by config (backend-reported or estimated).\n");
+    out.push_str("# TYPE claude_proxy_tokens_total counter\n");
+    for (config_id, metrics) in &per_config {
+        let labels = config_labels(*config_id, metrics);
+        let _ = writeln!(out, "claude_proxy_tokens_total{{{},direction=\"input\"}} {}", labels, metrics.input_tokens);
+        let _ = writeln!(out, "claude_proxy_tokens_total{{{},direction=\"output\"}} {}", labels, metrics.output_tokens);
+    }
+
+    out.push_str("# HELP claude_proxy_streaming_requests_total Proxied streaming requests.\n");
+    out.push_str("# TYPE claude_proxy_streaming_requests_total counter\n");
+    let _ = writeln!(out, "claude_proxy_streaming_requests_total {}", totals.streaming_requests);
+
+    out.push_str("# HELP claude_proxy_active_requests Requests currently being processed.\n");
+    out.push_str("# TYPE claude_proxy_active_requests gauge\n");
+    let _ = writeln!(out, "claude_proxy_active_requests {}", active_requests);
+
+    out
+}
+
+fn config_labels(config_id: i64, metrics: &ConfigMetrics) -> String {
+    format!(
+        "config_id=\"{}\",config_name=\"{}\"",
+        config_id,
+        escape_label_value(&metrics.config_name)
+    )
+}
+
+/// 转义标签值中的反斜杠、双引号与换行
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::logger::ProxyLogger;
+    use crate::proxy::token_usage::TokenUsage;
+    use hyper::{Method, StatusCode};
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!(escape_label_value("plain"), "plain");
+        assert_eq!(escape_label_value("a\"b\\c\nd\r"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn test_render_prometheus_metrics() {
+        let collector = MetricsCollector::new();
+        let entry = ProxyLogger::start_request(Method::POST, "/v1/messages".parse().unwrap(), "127.0.0.1:1".to_string())
+            .with_config(3, "team \"a\"".to_string())
+            .with_token_usage(TokenUsage {
+                input_tokens: Some(10),
+                output_tokens: Some(20),
+                ..Default::default()
+            })
+            .finish_with_error(StatusCode::BAD_GATEWAY, "upstream".to_string());
+        collector.record_entry(&entry);
+
+        let text = render(&collector, 2);
+        let labels = r#"config_id="3",config_name="team \"a\"""#;
+        assert!(text.contains(&format!("claude_proxy_requests_total{{{},status=\"502\"}} 1", labels)), "{}", text);
+        assert!(text.contains(&format!("claude_proxy_request_duration_ms_bucket{{{},le=\"+Inf\"}} 1", labels)));
+        assert!(text.contains(&format!("claude_proxy_request_duration_ms_count{{{}}} 1", labels)));
+        assert!(text.contains(&format!("claude_proxy_tokens_total{{{},direction=\"output\"}} 20", labels)));
+        assert!(text.contains("claude_proxy_active_requests 2\n"));
+        assert!(text.ends_with('\n'));
+    }
+}
@@ -0,0 +1,172 @@
+/**
+ * Request Body Compression
+ * 按配置对发往后端的大请求体进行 gzip 压缩，节省慢速上行链路的带宽
+ *
+ * 自动模式只对通过 `Accept-Encoding` 响应头声明支持 gzip 的后端压缩 (RFC 7694)；
+ * 曾以 415 拒绝压缩请求体的后端会被记录下来，此后即使强制压缩也不再压缩；
+ * 被拒绝的请求由转发层以未压缩的请求体重发一次。
+ */
+
+use crate::models::api_config::RequestCompression;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::sync::Mutex;
+
+/// 只压缩超过该大小的请求体 (64 KiB)，小请求压缩收益不足以抵消开销
+pub const MIN_COMPRESS_BODY_BYTES: usize = 64 * 1024;
+
+/// 响应头声明支持 gzip 请求体的后端主机
+static GZIP_CAPABLE_HOSTS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// 拒绝过压缩请求体的后端主机
+static GZIP_REJECTING_HOSTS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// 判断本次请求是否压缩请求体
+///
+/// 客户端已自行编码的请求体 (`content_encoding` 非空) 不再重复压缩
+pub fn should_compress(
+    mode: RequestCompression,
+    host: &str,
+    body_len: usize,
+    content_encoding: Option<&str>,
+) -> bool {
+    if mode == RequestCompression::Off || body_len < MIN_COMPRESS_BODY_BYTES {
+        return false;
+    }
+    if content_encoding.is_some_and(|v| !v.trim().is_empty() && !v.trim().eq_ignore_ascii_case("identity")) {
+        return false;
+    }
+    if is_known_rejecting(host) {
+        return false;
+    }
+    mode == RequestCompression::Force || is_known_capable(host)
+}
+
+/// gzip 压缩请求体
+pub fn gzip(body: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(body.len() / 4), Compression::default());
+    encoder.write_all(body)?;
+    encoder.finish()
+}
+
+/// 后端是否曾拒绝压缩的请求体
+pub fn is_known_rejecting(host: &str) -> bool {
+    GZIP_REJECTING_HOSTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(&host.to_ascii_lowercase())
+}
+
+/// 后端是否声明过支持 gzip 请求体
+fn is_known_capable(host: &str) -> bool {
+    GZIP_CAPABLE_HOSTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(&host.to_ascii_lowercase())
+}
+
+/// 清除记录的后端支持情况，返回清除的主机数
+pub fn clear() -> usize {
+    let capable = std::mem::take(&mut *GZIP_CAPABLE_HOSTS.lock().unwrap_or_else(|e| e.into_inner()));
+    let rejecting = std::mem::take(&mut *GZIP_REJECTING_HOSTS.lock().unwrap_or_else(|e| e.into_inner()));
+    capable.len() + rejecting.len()
+}
+
+/// 根据后端响应记录其对 gzip 请求体的支持情况
+///
+/// - 响应头 `Accept-Encoding` 包含 gzip (且未以 q=0 排除) 时记为支持
+/// - 发送了压缩请求体却收到 415 时记为拒绝
+pub fn observe_response(host: &str, status: hyper::StatusCode, headers: &hyper::HeaderMap, sent_compressed: bool) {
+    let host = host.to_ascii_lowercase();
+    if sent_compressed && status == hyper::StatusCode::UNSUPPORTED_MEDIA_TYPE {
+        log::warn!("Backend {} rejected gzip request body, disabling request compression for it", host);
+        GZIP_CAPABLE_HOSTS.lock().unwrap_or_else(|e| e.into_inner()).remove(&host);
+        GZIP_REJECTING_HOSTS.lock().unwrap_or_else(|e| e.into_inner()).insert(host);
+        return;
+    }
+
+    let advertises_gzip = headers
+        .get_all(hyper::header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(accepts_gzip);
+    if advertises_gzip && !is_known_rejecting(&host) {
+        GZIP_CAPABLE_HOSTS.lock().unwrap_or_else(|e| e.into_inner()).insert(host);
+    }
+}
+
+/// 解析 `Accept-Encoding` 头值，判断是否接受 gzip
+fn accepts_gzip(value: &str) -> bool {
+    value.split(',').any(|item| {
+        let mut parts = item.split(';').map(str::trim);
+        let coding = parts.next().unwrap_or_default();
+        if !coding.eq_ignore_ascii_case("gzip") && !coding.eq_ignore_ascii_case("x-gzip") {
+            return false;
+        }
+        // q=0 表示明确不接受
+        !parts.any(|p| {
+            p.strip_prefix("q=")
+                .and_then(|q| q.parse::<f32>().ok())
+                .is_some_and(|q| q <= 0.0)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn accept_encoding(value: &str) -> hyper::HeaderMap {
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert(hyper::header::ACCEPT_ENCODING, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_accepts_gzip() {
+        assert!(accepts_gzip("gzip"));
+        assert!(accepts_gzip("br, GZIP;q=0.5"));
+        assert!(!accepts_gzip("gzip;q=0"));
+        assert!(!accepts_gzip("br, deflate"));
+    }
+
+    #[test]
+    fn test_should_compress() {
+        let big = MIN_COMPRESS_BODY_BYTES;
+        let host = "compress-test.example.com";
+
+        assert!(!should_compress(RequestCompression::Off, host, big, None));
+        assert!(!should_compress(RequestCompression::Force, host, big - 1, None));
+        assert!(should_compress(RequestCompression::Force, host, big, None));
+        assert!(should_compress(RequestCompression::Force, host, big, Some("identity")));
+        assert!(!should_compress(RequestCompression::Force, host, big, Some("br")));
+
+        // 自动模式需要后端先声明支持
+        assert!(!should_compress(RequestCompression::Auto, host, big, None));
+        observe_response(host, hyper::StatusCode::OK, &accept_encoding("gzip, br"), false);
+        assert!(should_compress(RequestCompression::Auto, "Compress-Test.example.com", big, None));
+
+        // 拒绝压缩后即使强制也不再压缩
+        observe_response(host, hyper::StatusCode::UNSUPPORTED_MEDIA_TYPE, &hyper::HeaderMap::new(), true);
+        assert!(is_known_rejecting(host));
+        assert!(!should_compress(RequestCompression::Auto, host, big, None));
+        assert!(!should_compress(RequestCompression::Force, host, big, None));
+        observe_response(host, hyper::StatusCode::OK, &accept_encoding("gzip"), false);
+        assert!(!should_compress(RequestCompression::Auto, host, big, None));
+    }
+
+    #[test]
+    fn test_gzip_round_trip() {
+        let body = br#"{"messages":[{"role":"user","content":"hello"}]}"#.repeat(100);
+        let compressed = gzip(&body).unwrap();
+        assert!(compressed.len() < body.len());
+
+        let mut decoded = Vec::new();
+        GzDecoder::new(compressed.as_slice()).read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, body);
+    }
+}
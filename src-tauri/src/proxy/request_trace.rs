@@ -0,0 +1,309 @@
+/**
+ * Request Trace
+ * 单次请求的详细追踪：为指定配置的下一个请求记录完整耗时分解
+ * (DNS、TCP 连接、TLS 握手、首字节时间、每个流式块的到达时间) 与未截断的请求/响应体
+ *
+ * 追踪只对下一个匹配的请求生效，命中后自动解除，不影响普通请求日志。
+ * 追踪文件在最后一个 TraceHandle 释放时写入 {app_data_dir}/traces/trace-{request_id}.json，
+ * 流式响应在流结束 (或客户端断开) 后才写入。
+ */
+
+use super::structured_logger::{generate_request_id, RequestTracer, TraceChunk, TracePhase};
+use crate::models::config_report::redact_key;
+use crate::utils::paths;
+use crate::utils::time::now_rfc3339;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 等待追踪的配置 ID
+static ARMED_CONFIG: Mutex<Option<i64>> = Mutex::new(None);
+
+/// 最近写入的追踪文件
+static LAST_TRACE_FILE: Mutex<Option<String>> = Mutex::new(None);
+
+/// 追踪状态 (返回给前端)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestTraceStatus {
+    /// 等待追踪的配置 ID，未启用时为 None
+    pub armed_config_id: Option<i64>,
+    /// 最近写入的追踪文件路径
+    pub last_trace_file: Option<String>,
+}
+
+/// 启用追踪：记录该配置的下一个请求
+pub fn arm(config_id: i64) {
+    *ARMED_CONFIG.lock().unwrap_or_else(|e| e.into_inner()) = Some(config_id);
+    log::info!("Request trace armed for config {}", config_id);
+}
+
+/// 取消尚未命中的追踪，返回原先等待的配置 ID
+pub fn disarm() -> Option<i64> {
+    ARMED_CONFIG.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// 当前追踪状态
+pub fn status() -> RequestTraceStatus {
+    RequestTraceStatus {
+        armed_config_id: *ARMED_CONFIG.lock().unwrap_or_else(|e| e.into_inner()),
+        last_trace_file: LAST_TRACE_FILE.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+    }
+}
+
+/// 请求匹配等待追踪的配置时解除追踪并返回 true (只命中一次)
+fn take_if_armed(config_id: i64) -> bool {
+    let mut armed = ARMED_CONFIG.lock().unwrap_or_else(|e| e.into_inner());
+    if *armed == Some(config_id) {
+        *armed = None;
+        true
+    } else {
+        false
+    }
+}
+
+/// 追踪文件目录
+fn trace_dir() -> Result<PathBuf, String> {
+    Ok(paths::get_app_data_dir()?.join("traces"))
+}
+
+/// 追踪文件内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RequestTrace {
+    pub request_id: String,
+    pub config_id: i64,
+    pub config_name: Option<String>,
+    pub target_url: Option<String>,
+    pub client_addr: String,
+    /// 开始时间 (RFC3339)
+    pub started_at: String,
+    /// 总耗时 (毫秒，到追踪写入时为止)
+    pub total_ms: f64,
+    /// 阶段耗时
+    pub phases: Vec<TracePhase>,
+    /// 流式块到达时间
+    pub chunks: Vec<TraceChunk>,
+    pub method: Option<String>,
+    /// 发往后端的 URI
+    pub uri: Option<String>,
+    /// 发往后端的请求头（认证信息已脱敏）
+    pub request_headers: Vec<(String, String)>,
+    /// 发往后端的完整请求体
+    pub request_body: Option<String>,
+    pub request_body_size: u64,
+    pub status_code: Option<u16>,
+    pub response_headers: Vec<(String, String)>,
+    /// 后端返回的完整响应体 (格式转换前)
+    pub response_body: Option<String>,
+    pub response_body_size: u64,
+    /// 请求失败原因
+    pub error: Option<String>,
+}
+
+struct TraceRecorder {
+    tracer: RequestTracer,
+    trace: RequestTrace,
+    request_body: Vec<u8>,
+    response_body: Vec<u8>,
+    output_dir: PathBuf,
+}
+
+impl TraceRecorder {
+    /// 汇总耗时与请求/响应体
+    fn finish(&mut self) -> RequestTrace {
+        let mut trace = std::mem::take(&mut self.trace);
+        trace.total_ms = self.tracer.elapsed().as_secs_f64() * 1000.0;
+        trace.phases = self.tracer.phases().to_vec();
+        trace.chunks = self.tracer.chunk_arrivals().to_vec();
+        trace.request_body_size = self.request_body.len() as u64;
+        trace.request_body = (!self.request_body.is_empty())
+            .then(|| String::from_utf8_lossy(&self.request_body).to_string());
+        trace.response_body_size = self.response_body.len() as u64;
+        trace.response_body = (!self.response_body.is_empty())
+            .then(|| String::from_utf8_lossy(&self.response_body).to_string());
+        trace
+    }
+
+    fn write(&mut self) -> Result<PathBuf, String> {
+        let trace = self.finish();
+        paths::ensure_dir_exists(&self.output_dir)?;
+        let path = self.output_dir.join(format!("trace-{}.json", trace.request_id));
+        let content = serde_json::to_vec_pretty(&trace).map_err(|e| format!("序列化追踪失败: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("写入追踪文件失败: {}", e))?;
+        Ok(path)
+    }
+}
+
+impl Drop for TraceRecorder {
+    fn drop(&mut self) {
+        match self.write() {
+            Ok(path) => {
+                let path = path.to_string_lossy().to_string();
+                log::info!("Request trace {} written to {}", self.tracer.request_id(), path);
+                *LAST_TRACE_FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some(path);
+            }
+            Err(e) => log::warn!("Failed to write request trace {}: {}", self.tracer.request_id(), e),
+        }
+    }
+}
+
+/// 正在进行的请求追踪，可在转发流程与流式响应包装器之间共享
+#[derive(Clone)]
+pub struct TraceHandle(Arc<Mutex<TraceRecorder>>);
+
+impl TraceHandle {
+    /// 请求命中等待追踪的配置时开始追踪 (同时解除追踪)
+    pub fn start_if_armed(config_id: i64, request_id: Option<String>, client_addr: &str) -> Option<Self> {
+        if !take_if_armed(config_id) {
+            return None;
+        }
+        match trace_dir() {
+            Ok(dir) => Some(Self::new(config_id, request_id, client_addr, dir)),
+            Err(e) => {
+                log::warn!("Request trace for config {} skipped: {}", config_id, e);
+                None
+            }
+        }
+    }
+
+    fn new(config_id: i64, request_id: Option<String>, client_addr: &str, output_dir: PathBuf) -> Self {
+        let request_id = request_id.unwrap_or_else(generate_request_id);
+        let mut tracer = RequestTracer::with_id(request_id.clone());
+        tracer.set_remote_addr(client_addr);
+        Self(Arc::new(Mutex::new(TraceRecorder {
+            tracer,
+            trace: RequestTrace {
+                request_id,
+                config_id,
+                client_addr: client_addr.to_string(),
+                started_at: now_rfc3339(),
+                ..Default::default()
+            },
+            request_body: Vec::new(),
+            response_body: Vec::new(),
+            output_dir,
+        })))
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut TraceRecorder) -> R) -> R {
+        f(&mut self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// 记录配置信息
+    pub fn set_config(&self, name: &str, target_url: &str) {
+        self.with(|r| {
+            r.tracer.set_config(r.trace.config_id, name);
+            r.tracer.set_target_url(target_url);
+            r.trace.config_name = Some(name.to_string());
+            r.trace.target_url = Some(target_url.to_string());
+        });
+    }
+
+    /// 记录一个阶段的耗时
+    pub fn phase(&self, name: &str, duration: Duration) {
+        self.with(|r| r.tracer.record_phase(name, duration));
+    }
+
+    /// 记录发往后端的请求行与请求头
+    pub fn set_request(&self, method: &hyper::Method, uri: &hyper::Uri, headers: &hyper::HeaderMap) {
+        self.with(|r| {
+            r.trace.method = Some(method.to_string());
+            r.trace.uri = Some(uri.to_string());
+            r.trace.request_headers = redacted_headers(headers);
+        });
+    }
+
+    /// 追加发往后端的请求体
+    pub fn append_request_body(&self, data: &[u8]) {
+        self.with(|r| r.request_body.extend_from_slice(data));
+    }
+
+    /// 记录后端响应状态与响应头
+    pub fn set_response(&self, status: hyper::StatusCode, headers: &hyper::HeaderMap) {
+        self.with(|r| {
+            r.trace.status_code = Some(status.as_u16());
+            r.trace.response_headers = redacted_headers(headers);
+        });
+    }
+
+    /// 追加完整读取的响应体
+    pub fn append_response_body(&self, data: &[u8]) {
+        self.with(|r| r.response_body.extend_from_slice(data));
+    }
+
+    /// 记录流式块 (到达时间与内容)
+    pub fn record_chunk(&self, data: &[u8]) {
+        self.with(|r| {
+            r.tracer.record_chunk_arrival(data.len());
+            r.response_body.extend_from_slice(data);
+        });
+    }
+
+    /// 记录请求失败原因
+    pub fn set_error(&self, error: &str) {
+        self.with(|r| r.trace.error = Some(error.to_string()));
+    }
+}
+
+/// 请求头列表，认证信息脱敏
+fn redacted_headers(headers: &hyper::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = value.to_str().unwrap_or("<binary>");
+            let value = match name.as_str() {
+                "authorization" | "proxy-authorization" => match value.split_once(' ') {
+                    Some((scheme, token)) => format!("{} {}", scheme, redact_key(token)),
+                    None => redact_key(value),
+                },
+                "x-api-key" | "x-goog-api-key" => redact_key(value),
+                _ => value.to_string(),
+            };
+            (name.as_str().to_string(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arm_only_matches_once() {
+        arm(42);
+        assert_eq!(status().armed_config_id, Some(42));
+        assert!(!take_if_armed(7));
+        assert!(take_if_armed(42));
+        assert!(!take_if_armed(42));
+        assert_eq!(disarm(), None);
+    }
+
+    #[test]
+    fn test_trace_written_on_drop() {
+        let dir = std::env::temp_dir().join(format!("ccp-trace-{}", generate_request_id()));
+        let trace = TraceHandle::new(3, Some("req-trace-test".to_string()), "127.0.0.1:1", dir.clone());
+        trace.set_config("cfg", "https://api.example.com");
+        trace.phase("dns", Duration::from_millis(2));
+
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert("authorization", "Bearer sk-secret-123456789".parse().unwrap());
+        trace.set_request(&hyper::Method::POST, &"/v1/messages".parse().unwrap(), &headers);
+        trace.append_request_body(b"{\"model\":\"m\"}");
+        trace.set_response(hyper::StatusCode::OK, &hyper::HeaderMap::new());
+        trace.record_chunk(b"event: a\n\n");
+        trace.record_chunk(b"event: b\n\n");
+        drop(trace);
+
+        let content = std::fs::read_to_string(dir.join("trace-req-trace-test.json")).unwrap();
+        let written: RequestTrace = serde_json::from_str(&content).unwrap();
+        assert_eq!(written.config_name.as_deref(), Some("cfg"));
+        assert_eq!(written.phases[0].name, "dns");
+        assert_eq!(written.chunks.len(), 2);
+        assert_eq!(written.chunks[1].index, 1);
+        assert_eq!(written.response_body.as_deref(), Some("event: a\n\nevent: b\n\n"));
+        assert_eq!(written.request_body_size, 13);
+        assert_eq!(written.request_headers[0].1, "Bearer ****6789");
+        assert!(!content.contains("sk-secret"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -9,19 +9,42 @@
  */
 
 use crate::db::DbPool;
+use crate::models::api_config::{AnthropicBetaFilter, ApiConfig, ApiKeyMode, MetadataUserIdPolicy, ProviderType, RequestCompression};
+use crate::models::config_group::ConfigGroup;
+use crate::models::body_transform::BodyTransformSpec;
+use crate::models::config_report::{redact_key, redact_text};
 use crate::models::error::{AppError, AppResult};
+use crate::models::retry_strategy::RetryStrategy;
 use crate::models::switch_log::SwitchReason;
 // use crate::proxy::error_handler::{ProxyErrorHandler, ProxyErrorType};
 use crate::services::api_config::ApiConfigService;
+use crate::services::api_test::check_response_body_error;
+use crate::services::proxy_log::{truncate_body, ProxyRequestLogService, FULL_BODY_LIMIT};
 use crate::services::auto_switch::AutoSwitchService;
 use crate::services::model_mapping_service::ModelMappingService;
 use crate::converters::claude_types::ClaudeRequest;
 use crate::converters::claude_to_gemini::convert_claude_request_to_gemini;
 use crate::converters::gemini_to_claude::{convert_gemini_response_to_claude, convert_gemini_stream_chunk_to_claude_events};
 use crate::converters::gemini_types::GeminiResponse;
+use crate::converters::model_mapper::MODEL_MAPPER;
 use crate::converters::openai_types::OpenAIRequest;
+use super::canary;
+use super::connection_pool;
+use super::request_compression;
 use super::smart_router::{RoutingContext, ConversionDirection};
+use super::server::{
+    DEFAULT_CONNECT_TIMEOUT_SECS, DEFAULT_MAX_STREAM_CHUNKS, DEFAULT_MAX_STREAM_DURATION_SECS,
+    DEFAULT_REQUEST_TIMEOUT_SECS,
+};
+use super::sse_filter::SseKeepaliveFilter;
+use super::stream_converter::{StreamEventTracker, StreamIntegrityReport};
+use super::request_trace::TraceHandle;
+use super::latency_injection;
+use super::structured_logger::current_request_id;
+use super::token_usage::{StreamUsageTracker, TokenUsage};
+use crate::utils::server_url::{merge_query, parse_server_url, redact_query_values};
 use hyper::body::Incoming;
+use serde::Serialize;
 use hyper::{Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
 use http_body_util::{BodyExt, combinators::BoxBody, StreamBody};
@@ -39,34 +62,36 @@ use std::convert::Infallible;
 use std::task::{Context, Poll};
 use tokio::sync::mpsc;
 
-/// Request timeout in seconds (FR-012)
-/// Increased to 120s for streaming responses
-const REQUEST_TIMEOUT_SECS: u64 = 120;
-
 /// High latency threshold in milliseconds
 const HIGH_LATENCY_THRESHOLD_MS: u128 = 3000;
 
 /// 流式响应完成后的数据
 #[derive(Debug, Clone)]
 pub struct StreamCompletionData {
-    /// 响应体内容（截取前 8KB）
-    pub response_body: String,
+    /// 响应体内容（关闭请求体日志时为 None，二进制内容为占位符）
+    pub response_body: Option<String>,
     /// 响应体总大小
     pub response_body_size: u64,
     /// 流式 chunk 数量
     pub chunk_count: u32,
+    /// 流完整性报告（非 Claude SSE 格式时为 None）
+    pub integrity: Option<StreamIntegrityReport>,
+    /// 终止事件中检测到的软错误（HTTP 200 但内容为错误）
+    pub soft_error: Option<String>,
+    /// token 用量（后端未报告时为本地估算值）
+    pub token_usage: TokenUsage,
 }
 
 /// 转发请求的详细信息
 #[derive(Debug, Clone, Default)]
 pub struct ForwardDetails {
-    /// 请求体 (用于日志，可能被截断)
+    /// 请求体 (用于日志，可能被截断，二进制内容为占位符)
     pub request_body: Option<String>,
     /// 请求体大小
     pub request_body_size: u64,
     /// 响应头 (JSON 格式)
     pub response_headers: Option<String>,
-    /// 响应体 (用于日志，可能被截断)
+    /// 响应体 (用于日志，可能被截断，二进制内容为占位符)
     pub response_body: Option<String>,
     /// 响应体大小
     pub response_body_size: u64,
@@ -80,40 +105,355 @@ pub struct ForwardDetails {
     pub mapped_model: Option<String>,
     /// 目标 URL
     pub target_url: Option<String>,
+    /// token 用量（非流式响应；流式响应在流结束后通过 StreamCompletionData 返回）
+    pub token_usage: TokenUsage,
+    /// 金丝雀请求失败后实际响应请求的活跃配置（未回退时为 None）
+    pub canary_fallback_config_id: Option<i64>,
+}
+
+impl ForwardDetails {
+    /// 丢弃请求体与响应体内容，只保留大小、模型等元数据
+    pub fn strip_bodies(&mut self) {
+        self.request_body = None;
+        self.response_body = None;
+    }
+}
+
+/// 按文本记录时允许的最多无效 UTF-8 字节数
+/// (截断或裁剪捕获缓冲区可能切开多字节字符)
+const MAX_INVALID_UTF8_BYTES_IN_TEXT: usize = 8;
+
+/// 内容是否为二进制 (不是有效 UTF-8 文本)
+fn is_binary_body(bytes: &[u8]) -> bool {
+    bytes.utf8_chunks().map(|chunk| chunk.invalid().len()).sum::<usize>() > MAX_INVALID_UTF8_BYTES_IN_TEXT
+}
+
+/// 二进制内容在日志中的占位符
+fn binary_body_placeholder(content_type: Option<&str>, size: u64) -> String {
+    format!("[binary: {}, {} bytes]", content_type.unwrap_or("unknown"), size)
+}
+
+/// 用于日志的请求体/响应体文本，二进制内容 (图片等) 记录为占位符而不是乱码
+fn body_for_log(bytes: &[u8], content_type: Option<&str>) -> String {
+    if is_binary_body(bytes) {
+        binary_body_placeholder(content_type, bytes.len() as u64)
+    } else {
+        truncate_body(&String::from_utf8_lossy(bytes), FULL_BODY_LIMIT)
+    }
+}
+
+/// 请求头或响应头中的 Content-Type
+fn header_content_type(headers: &hyper::HeaderMap) -> Option<&str> {
+    headers
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+}
+
+/// 转发请求预览（与实际转发使用相同的转换流程，但不发送）
+#[derive(Debug, Clone, Serialize)]
+pub struct ForwardedRequestPreview {
+    /// 请求方法
+    pub method: String,
+    /// 后端连接地址（host:port）
+    pub target_addr: String,
+    /// 是否使用 HTTPS
+    pub is_https: bool,
+    /// 请求行中的 URI（改写后的路径与查询参数）
+    pub uri: String,
+    /// 请求头（认证信息已脱敏）
+    pub headers: Vec<(String, String)>,
+    /// 转换后的请求体（压缩时为压缩前的内容）
+    pub body: String,
+    /// 请求转换方向
+    pub conversion: String,
+    /// 映射后的模型名称
+    pub mapped_model: Option<String>,
+    /// 请求体是否原样流式透传（未经过缓冲与转换）
+    pub body_streamed: bool,
+    /// 请求体是否以 gzip 压缩发送（Content-Length 为压缩后的大小）
+    pub body_compressed: bool,
+}
+
+/// 生效配置项的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectiveValueSource {
+    /// 配置自身的字段
+    Config,
+    /// 所属分组的设置
+    Group,
+    /// 供应商预设
+    Preset,
+    /// 应用设置
+    AppSettings,
+    /// 代理服务的全局设置
+    ProxySettings,
+    /// 内置默认值
+    Default,
+}
+
+/// 单个生效配置项
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveConfigValue {
+    /// 配置项名称
+    pub key: String,
+    /// 生效值（密钥类信息已脱敏）
+    pub value: serde_json::Value,
+    /// 值的来源
+    pub source: EffectiveValueSource,
+}
+
+/// 配置在转发时实际生效的设置（合并配置、分组、应用设置与默认值后的结果）
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveConfig {
+    pub config_id: i64,
+    pub config_name: String,
+    pub group_id: Option<i64>,
+    /// 服务器地址匹配的供应商预设
+    pub preset_id: Option<String>,
+    pub values: Vec<EffectiveConfigValue>,
+}
+
+impl EffectiveConfig {
+    fn push(&mut self, key: &str, value: impl Serialize, source: EffectiveValueSource) {
+        self.values.push(EffectiveConfigValue {
+            key: key.to_string(),
+            value: serde_json::to_value(value).unwrap_or(serde_json::Value::Null),
+            source,
+        });
+    }
+}
+
+/// 请求体转换结果
+struct TransformedRequestBody {
+    /// 转换后的请求体
+    body: Vec<u8>,
+    /// 格式转换后需要改写的请求路径（无需改写时为 None）
+    target_path: Option<String>,
+    /// 客户端请求的原始模型
+    source_model: Option<String>,
+    /// 映射后的模型名称
+    mapped_model: Option<String>,
 }
 
+/// 单个流式响应的上限
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct StreamLimits {
+    /// 最长持续时间
+    max_duration: Option<Duration>,
+    /// 最多 chunk 数
+    max_chunks: Option<u32>,
+}
+
+impl StreamLimits {
+    /// 从秒数/数量构造，0 表示不限制
+    fn new(max_duration_secs: u64, max_chunks: u32) -> Self {
+        Self {
+            max_duration: (max_duration_secs > 0).then(|| Duration::from_secs(max_duration_secs)),
+            max_chunks: (max_chunks > 0).then_some(max_chunks),
+        }
+    }
+}
+
+/// 流式响应捕获缓冲区上限
+const MAX_CAPTURED_STREAM_BYTES: usize = 256 * 1024;
+
+/// 捕获缓冲区裁剪时保留的开头字节数（日志表只记录开头 8192 字节）
+const CAPTURED_STREAM_HEAD_BYTES: usize = 8192;
+
 /// 流式响应捕获包装器
 /// 在传输数据的同时收集数据，流结束后通过通道发送完整数据
+/// 启用保活过滤时按 SSE 事件边界切分数据，丢弃 ping/注释事件后再转发
+/// 配置了重连提示时在第一个事件之前先发送 `retry:` 字段 (不计入捕获数据与完整性检查)
+/// 关闭请求体日志时只统计字节数与 chunk 数，不保留响应内容
+/// 只在下游拉取时才读取上游 (遵循 poll_frame 背压)，捕获缓冲区只保留开头与最近的数据，内存占用有上限
+/// 超过最长持续时间或最多 chunk 数时断开上游，补发 Claude 收尾事件并按软错误上报
 struct StreamingBodyWrapper<B> {
-    inner: B,
+    /// 上游响应体，超限终止后置为 None 以释放连接
+    inner: Option<B>,
+    retry_prefix: Option<Bytes>,
+    /// 捕获的响应内容，超过 MAX_CAPTURED_STREAM_BYTES 时丢弃中间部分
     buffer: Vec<u8>,
+    /// 是否保留响应内容
+    retain_body: bool,
+    body_size: u64,
     chunk_count: u32,
     completion_tx: Option<mpsc::Sender<StreamCompletionData>>,
+    sse_filter: Option<SseKeepaliveFilter>,
+    inner_finished: bool,
+    integrity_tracker: StreamEventTracker,
+    usage_tracker: StreamUsageTracker,
+    /// 用于估算输入 token 的原始请求体
+    usage_request: Option<Bytes>,
+    /// 用于完整性报告的流 ID（请求追踪 ID）
+    stream_id: String,
+    limits: StreamLimits,
+    deadline: Option<Pin<Box<tokio::time::Sleep>>>,
+    /// 超限终止原因
+    limit_error: Option<String>,
+    /// 请求追踪（记录每个块的到达时间与完整内容）
+    trace: Option<TraceHandle>,
+    /// 响应的 Content-Type (二进制内容占位符使用)
+    content_type: Option<String>,
 }
 
 impl<B> StreamingBodyWrapper<B> {
-    fn new(inner: B, completion_tx: mpsc::Sender<StreamCompletionData>) -> Self {
+    fn new(
+        inner: B,
+        completion_tx: mpsc::Sender<StreamCompletionData>,
+        filter_keepalive: bool,
+        sse_retry_ms: Option<u32>,
+        retain_body: bool,
+    ) -> Self {
         Self {
-            inner,
+            inner: Some(inner),
+            retry_prefix: sse_retry_ms.map(sse_retry_frame),
             buffer: Vec::new(),
+            retain_body,
+            body_size: 0,
             chunk_count: 0,
             completion_tx: Some(completion_tx),
+            sse_filter: filter_keepalive.then(SseKeepaliveFilter::new),
+            inner_finished: false,
+            integrity_tracker: StreamEventTracker::new(),
+            usage_tracker: StreamUsageTracker::new(),
+            usage_request: None,
+            stream_id: current_request_id().unwrap_or_default(),
+            limits: StreamLimits::default(),
+            deadline: None,
+            limit_error: None,
+            trace: None,
+            content_type: None,
+        }
+    }
+
+    /// 设置请求追踪
+    fn with_trace(mut self, trace: Option<TraceHandle>) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// 设置响应的 Content-Type
+    fn with_content_type(mut self, content_type: Option<&str>) -> Self {
+        self.content_type = content_type.map(str::to_string);
+        self
+    }
+
+    /// 设置流式响应上限（需在 tokio 运行时中调用）
+    fn with_limits(mut self, limits: StreamLimits) -> Self {
+        self.deadline = limits
+            .max_duration
+            .map(|duration| Box::pin(tokio::time::sleep(duration)));
+        self.limits = limits;
+        self
+    }
+
+    /// 检查是否超过上限，超过时返回原因（同时注册超时唤醒）
+    fn exceeded_limit(&mut self, cx: &mut Context<'_>) -> Option<String> {
+        if let Some(deadline) = self.deadline.as_mut() {
+            if std::future::Future::poll(deadline.as_mut(), cx).is_ready() {
+                let duration = self.limits.max_duration.unwrap_or_default();
+                return Some(format!("Stream exceeded maximum duration of {}s", duration.as_secs()));
+            }
+        }
+        match self.limits.max_chunks {
+            Some(max_chunks) if self.chunk_count >= max_chunks => {
+                Some(format!("Stream exceeded maximum of {} chunks", max_chunks))
+            }
+            _ => None,
+        }
+    }
+
+    /// 超限终止：断开上游并返回补齐的收尾事件
+    fn terminate(&mut self, reason: String) -> Option<Bytes> {
+        log::warn!(
+            "Terminating stream {} after {} chunks / {} bytes: {}",
+            self.stream_id,
+            self.chunk_count,
+            self.body_size,
+            reason
+        );
+        self.inner = None;
+        self.deadline = None;
+        self.inner_finished = true;
+        self.limit_error = Some(reason);
+
+        let tail = self.integrity_tracker.termination_events();
+        if tail.is_empty() {
+            return None;
+        }
+        // 收尾事件计入捕获数据与完整性检查，但不计入 chunk 上限
+        self.capture(tail.as_bytes());
+        self.body_size += tail.len() as u64;
+        self.integrity_tracker.push(tail.as_bytes());
+        self.usage_tracker.push(tail.as_bytes());
+        Some(Bytes::from(tail))
+    }
+
+    /// 设置用于估算输入 token 的请求体（后端未报告 usage 时使用）
+    fn with_usage_request(mut self, request_body: Option<Bytes>) -> Self {
+        self.usage_request = request_body;
+        self
+    }
+
+    /// 收集数据到缓冲区，超过上限时丢弃中间部分
+    /// 保留开头用于日志，保留最近的数据用于识别终止事件中的软错误
+    fn capture(&mut self, data: &[u8]) {
+        if !self.retain_body {
+            return;
         }
+        self.buffer.extend_from_slice(data);
+        if self.buffer.len() > MAX_CAPTURED_STREAM_BYTES {
+            let keep_tail = (MAX_CAPTURED_STREAM_BYTES - CAPTURED_STREAM_HEAD_BYTES) / 2;
+            let drain_end = self.buffer.len() - keep_tail;
+            self.buffer.drain(CAPTURED_STREAM_HEAD_BYTES..drain_end);
+        }
+    }
+
+    fn record_chunk(&mut self, data: &[u8]) {
+        if let Some(trace) = &self.trace {
+            trace.record_chunk(data);
+        }
+        self.capture(data);
+        self.body_size += data.len() as u64;
+        self.chunk_count += 1;
+        self.integrity_tracker.push(data);
+        self.usage_tracker.push(data);
     }
 
     fn send_completion(&mut self) {
         if let Some(tx) = self.completion_tx.take() {
-            let body_str = String::from_utf8_lossy(&self.buffer);
-            let response_body = if body_str.len() > 8192 {
-                format!("{}...(truncated)", &body_str[..8192])
+            if let Some(filter) = &self.sse_filter {
+                if filter.dropped_events() > 0 {
+                    log::debug!("Filtered {} SSE keepalive events", filter.dropped_events());
+                }
+            }
+
+            let integrity = self.integrity_tracker.finish(&self.stream_id);
+
+            // 未保留响应内容或内容为二进制时只能依据完整性跟踪器识别到的 error 事件判断软错误
+            let (response_body, soft_error) = if self.retain_body && !is_binary_body(&self.buffer) {
+                let body_str = String::from_utf8_lossy(&self.buffer);
+                let response_body = truncate_body(&body_str, FULL_BODY_LIMIT);
+                let soft_error = check_response_body_error(&terminal_stream_payload(&body_str));
+                (Some(response_body), soft_error)
             } else {
-                body_str.to_string()
+                let placeholder = self
+                    .retain_body
+                    .then(|| binary_body_placeholder(self.content_type.as_deref(), self.body_size));
+                (placeholder, integrity.as_ref().and_then(|report| report.error.clone()))
             };
 
+            let token_usage = std::mem::take(&mut self.usage_tracker).finish(self.usage_request.as_deref());
+
             let data = StreamCompletionData {
                 response_body,
-                response_body_size: self.buffer.len() as u64,
+                response_body_size: self.body_size,
                 chunk_count: self.chunk_count,
+                integrity,
+                // 超限终止优先于响应内容中的软错误，用于触发故障切换
+                soft_error: self.limit_error.clone().or(soft_error),
+                token_usage,
             };
 
             // 使用 try_send 避免阻塞
@@ -131,30 +471,77 @@ where
     type Error = B::Error;
 
     fn poll_frame(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
-        let inner = Pin::new(&mut self.inner);
-        match inner.poll_frame(cx) {
-            Poll::Ready(Some(Ok(frame))) => {
-                if let Some(data) = frame.data_ref() {
-                    // 收集数据到缓冲区
-                    self.buffer.extend_from_slice(data);
-                    self.chunk_count += 1;
-                }
-                Poll::Ready(Some(Ok(frame)))
+        let this = self.get_mut();
+
+        if let Some(prefix) = this.retry_prefix.take() {
+            return Poll::Ready(Some(Ok(Frame::data(prefix))));
+        }
+
+        loop {
+            if this.inner_finished {
+                // 流结束，发送完整数据
+                this.send_completion();
+                return Poll::Ready(None);
             }
-            Poll::Ready(Some(Err(e))) => {
-                // 发生错误时也发送已收集的数据
-                self.send_completion();
-                Poll::Ready(Some(Err(e)))
+
+            if let Some(reason) = this.exceeded_limit(cx) {
+                match this.terminate(reason) {
+                    Some(tail) => return Poll::Ready(Some(Ok(Frame::data(tail)))),
+                    None => continue,
+                }
             }
-            Poll::Ready(None) => {
-                // 流结束，发送完整数据
-                self.send_completion();
-                Poll::Ready(None)
+
+            let Some(inner) = this.inner.as_mut() else {
+                this.inner_finished = true;
+                continue;
+            };
+
+            match Pin::new(inner).poll_frame(cx) {
+                Poll::Ready(Some(Ok(frame))) => {
+                    let frame = match this.sse_filter.as_mut() {
+                        Some(filter) => match frame.into_data() {
+                            Ok(data) => {
+                                let filtered = filter.push(&data);
+                                if filtered.is_empty() {
+                                    // 只有保活事件或不完整事件，继续读取
+                                    continue;
+                                }
+                                Frame::data(Bytes::from(filtered))
+                            }
+                            Err(frame) => frame,
+                        },
+                        None => frame,
+                    };
+
+                    if let Some(data) = frame.data_ref() {
+                        this.record_chunk(data);
+                    }
+                    return Poll::Ready(Some(Ok(frame)));
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    // 发生错误时也发送已收集的数据
+                    this.send_completion();
+                    return Poll::Ready(Some(Err(e)));
+                }
+                Poll::Ready(None) => {
+                    this.inner_finished = true;
+
+                    // 转发过滤器中剩余的不完整事件
+                    let remaining = this
+                        .sse_filter
+                        .as_mut()
+                        .map(|filter| filter.finish())
+                        .unwrap_or_default();
+                    if !remaining.is_empty() {
+                        this.record_chunk(&remaining);
+                        return Poll::Ready(Some(Ok(Frame::data(Bytes::from(remaining)))));
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
             }
-            Poll::Pending => Poll::Pending,
         }
     }
 
@@ -162,11 +549,540 @@ where
         false
     }
 
+    fn size_hint(&self) -> http_body::SizeHint {
+        match &self.inner {
+            // 过滤、插入重连提示或可能超限终止时长度不确定
+            Some(inner)
+                if self.sse_filter.is_none()
+                    && self.retry_prefix.is_none()
+                    && self.limits == StreamLimits::default() =>
+            {
+                inner.size_hint()
+            }
+            _ => http_body::SizeHint::default(),
+        }
+    }
+}
+
+/// 提取流式响应的终止事件内容
+///
+/// SSE 格式返回最后一个带 `data:` 的事件的数据；非 SSE 格式（如直接返回 JSON 错误）返回整个响应体
+fn terminal_stream_payload(body: &str) -> String {
+    let body = body.replace("\r\n", "\n");
+    let is_sse = body
+        .lines()
+        .any(|line| line.starts_with("data:") || line.starts_with("event:"));
+    if !is_sse {
+        return body.trim().to_string();
+    }
+
+    body.trim_end()
+        .rsplit("\n\n")
+        .find(|event| event.lines().any(|line| line.starts_with("data:")))
+        .map(|event| {
+            event
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(str::trim_start)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+/// SSE 重连提示：`retry:` 字段单独成块，客户端只会据此更新重连间隔，不会产生事件
+fn sse_retry_frame(retry_ms: u32) -> Bytes {
+    Bytes::from(format!("retry: {}\n\n", retry_ms))
+}
+
+/// 为已缓冲的请求体设置帧头
+///
+/// 请求体已完整读取时使用 Content-Length，并移除客户端原有的 Transfer-Encoding，
+/// 避免后端同时收到两种互相冲突的长度声明。
+fn set_buffered_body_framing(headers: &mut hyper::HeaderMap, body_len: usize) {
+    headers.remove(hyper::header::TRANSFER_ENCODING);
+    headers.insert(hyper::header::CONTENT_LENGTH, hyper::header::HeaderValue::from(body_len));
+}
+
+/// 需要转换/过滤而缓冲的请求体上限 (32 MiB)
+///
+/// 转换 (尤其是 Gemini 多模态请求) 必须完整读取请求体，超过上限直接拒绝而不是继续占用内存
+const MAX_BUFFERED_REQUEST_BODY_BYTES: u64 = 32 * 1024 * 1024;
+
+/// 读取完整请求体，超过 `limit` 时返回 `PayloadTooLarge`
+///
+/// Content-Length 已声明超限时不读取请求体直接拒绝
+async fn collect_request_body<B>(
+    body: B,
+    headers: &hyper::HeaderMap,
+    limit: u64,
+) -> AppResult<Bytes>
+where
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let declared_len = headers
+        .get(hyper::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok());
+    if declared_len.map_or(false, |len| len > limit) {
+        return Err(AppError::PayloadTooLarge { limit_bytes: limit });
+    }
+
+    http_body_util::Limited::new(body, limit as usize)
+        .collect()
+        .await
+        .map(|collected| collected.to_bytes())
+        .map_err(|e| {
+            if e.downcast_ref::<http_body_util::LengthLimitError>().is_some() {
+                AppError::PayloadTooLarge { limit_bytes: limit }
+            } else {
+                AppError::ServiceError {
+                    message: format!("Failed to read request body: {}", e),
+                }
+            }
+        })
+}
+
+/// 为原样透传的请求体保留合适的帧头
+///
+/// - 没有请求体：移除 Content-Length / Transfer-Encoding
+/// - 客户端使用 chunked：保留 Transfer-Encoding，移除冲突的 Content-Length
+/// - 长度已知但缺少 Content-Length：补充 Content-Length
+/// - 长度未知且缺少帧头：使用 chunked
+fn set_streamed_body_framing(
+    headers: &mut hyper::HeaderMap,
+    is_end_stream: bool,
+    exact_len: Option<u64>,
+) {
+    if is_end_stream || exact_len == Some(0) {
+        headers.remove(hyper::header::TRANSFER_ENCODING);
+        headers.remove(hyper::header::CONTENT_LENGTH);
+        return;
+    }
+
+    if headers.contains_key(hyper::header::TRANSFER_ENCODING) {
+        headers.remove(hyper::header::CONTENT_LENGTH);
+        return;
+    }
+
+    if headers.contains_key(hyper::header::CONTENT_LENGTH) {
+        return;
+    }
+
+    match exact_len {
+        Some(len) => {
+            headers.insert(hyper::header::CONTENT_LENGTH, hyper::header::HeaderValue::from(len));
+        }
+        None => {
+            headers.insert(
+                hyper::header::TRANSFER_ENCODING,
+                hyper::header::HeaderValue::from_static("chunked"),
+            );
+        }
+    }
+}
+
+/// 按模型系列从配置的模型覆盖中选择目标模型
+///
+/// haiku/sonnet/opus 系列优先使用对应字段，其次使用 default_model
+fn config_model_override(config: &ApiConfig, source_model: &str) -> Option<String> {
+    let source = source_model.to_ascii_lowercase();
+    let non_empty = |model: &Option<String>| model.clone().filter(|m| !m.trim().is_empty());
+
+    let family_override = if source.contains("haiku") {
+        non_empty(&config.haiku_model).or_else(|| non_empty(&config.small_fast_model))
+    } else if source.contains("sonnet") {
+        non_empty(&config.sonnet_model)
+    } else if source.contains("opus") {
+        non_empty(&config.opus_model)
+    } else {
+        None
+    };
+
+    family_override.or_else(|| non_empty(&config.default_model))
+}
+
+/// 跨提供商转换时解析目标模型（数据库映射规则未命中时使用）
+///
+/// 配置的模型覆盖优先，否则使用 MODEL_MAPPER 选择最接近的模型。
+fn resolve_cross_provider_model(
+    direction: ConversionDirection,
+    source_model: &str,
+    config: &ApiConfig,
+) -> Option<String> {
+    match direction {
+        ConversionDirection::ClaudeToOpenAI => Some(
+            config_model_override(config, source_model)
+                .unwrap_or_else(|| MODEL_MAPPER.claude_to_openai(source_model)),
+        ),
+        ConversionDirection::OpenAIToClaude => Some(
+            config_model_override(config, source_model)
+                .unwrap_or_else(|| MODEL_MAPPER.openai_to_claude(source_model)),
+        ),
+        _ => None,
+    }
+}
+
+/// 透传请求体时保留的前缀大小（用于请求日志与模型名提取）
+const STREAMED_BODY_CAPTURE_LIMIT: usize = 64 * 1024;
+
+/// 判断是否按原始字节透传请求体
+///
+/// 分组开启了原生 Claude 透传且配置为 Claude 提供商时，请求体不做任何解析与改写，
+/// 字段过滤、模型覆盖、分组请求体转换规则与请求体压缩均不生效
+fn is_native_passthrough(group: Option<&ConfigGroup>, config: &ApiConfig) -> bool {
+    group.is_some_and(|g| g.native_claude_only) && config.provider_type == ProviderType::Claude
+}
+
+/// 判断请求体是否需要完整缓冲
+///
+/// 只有在确实需要修改请求体时才缓冲，否则直接流式透传，
+/// 避免大请求体（如包含大量图片）占用双倍内存并增加延迟：
+/// - 需要格式转换
+/// - 分组配置了请求体变换规则
+/// - 配置设置了模型覆盖
+/// - 配置需要移除 context_management 以外的请求字段
+/// - 配置需要移除或匿名化 metadata.user_id
+/// - 需要移除 context_management 且客户端启用了 context-management beta（请求体可能包含该字段）
+/// - 请求体可能需要压缩（未声明长度或超过压缩阈值）
+fn request_body_needs_buffering(
+    conversion: ConversionDirection,
+    has_request_transform: bool,
+    config: &ApiConfig,
+    headers: &hyper::HeaderMap,
+) -> bool {
+    if conversion != ConversionDirection::NoConversion || has_request_transform {
+        return true;
+    }
+
+    let has_model_override = [
+        &config.default_model,
+        &config.haiku_model,
+        &config.sonnet_model,
+        &config.opus_model,
+        &config.small_fast_model,
+    ]
+    .iter()
+    .any(|model| model.as_deref().map_or(false, |m| !m.trim().is_empty()));
+    if has_model_override {
+        return true;
+    }
+
+    if config.metadata_user_id_policy != MetadataUserIdPolicy::Passthrough {
+        return true;
+    }
+
+    if config.request_compression != RequestCompression::Off {
+        let declared_len = headers
+            .get(hyper::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(usize::MAX);
+        let content_encoding = headers
+            .get(hyper::header::CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok());
+        if request_compression::should_compress(
+            config.request_compression,
+            &parse_server_url(&config.server_url).host,
+            declared_len,
+            content_encoding,
+        ) {
+            return true;
+        }
+    }
+
+    let strip_fields = config.request_fields_to_strip();
+    if strip_fields.iter().any(|f| f != "context_management") {
+        return true;
+    }
+    if strip_fields.is_empty() {
+        return false;
+    }
+
+    headers
+        .get_all("anthropic-beta")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| v.to_ascii_lowercase().contains("context-management"))
+}
+
+/// 用已缓冲的请求体重建请求（金丝雀失败后回退到活跃配置时重放）
+fn rebuild_request(
+    parts: &hyper::http::request::Parts,
+    body: Bytes,
+) -> Request<impl http_body::Body<Data = Bytes, Error = hyper::Error> + Send + Sync + Unpin + 'static> {
+    let mut req = Request::new(http_body_util::Full::new(body).map_err(|never| match never {}));
+    *req.method_mut() = parts.method.clone();
+    *req.uri_mut() = parts.uri.clone();
+    *req.version_mut() = parts.version;
+    *req.headers_mut() = parts.headers.clone();
+    req
+}
+
+/// 请求体缺少 `model` 字段（或为 null / 空字符串）时注入默认模型
+///
+/// 返回注入后的请求体；请求体不是 JSON 对象或已指定模型时返回 None
+fn inject_default_model(body_bytes: &[u8], default_model: &str) -> Option<Vec<u8>> {
+    let mut json = serde_json::from_slice::<serde_json::Value>(body_bytes).ok()?;
+    let obj = json.as_object_mut()?;
+    let has_model = obj
+        .get("model")
+        .and_then(|m| m.as_str())
+        .is_some_and(|m| !m.trim().is_empty());
+    if has_model {
+        return None;
+    }
+
+    obj.insert("model".to_string(), serde_json::Value::String(default_model.to_string()));
+    log::info!("Request has no model, applied default model: {}", default_model);
+    serde_json::to_vec(&json).ok()
+}
+
+/// 透传请求体的统计信息
+#[derive(Debug, Default)]
+struct StreamedBodyCapture {
+    /// 已发送的总字节数
+    total: u64,
+    /// 请求体前缀（最多 STREAMED_BODY_CAPTURE_LIMIT 字节）
+    prefix: Vec<u8>,
+}
+
+impl StreamedBodyCapture {
+    /// 从前缀中提取模型名称（不要求前缀是完整 JSON）
+    fn model(&self) -> Option<String> {
+        let text = String::from_utf8_lossy(&self.prefix);
+        let pattern = regex::Regex::new(r#""model"\s*:\s*"([^"]+)""#).ok()?;
+        pattern.captures(&text).map(|caps| caps[1].to_string())
+    }
+
+    /// 用于请求日志的请求体文本，二进制内容记录为占位符
+    fn logged_body(&self, content_type: Option<&str>) -> String {
+        if is_binary_body(&self.prefix) {
+            return binary_body_placeholder(content_type, self.total);
+        }
+        let text = String::from_utf8_lossy(&self.prefix).to_string();
+        if self.total > self.prefix.len() as u64 {
+            format!("{}...(streamed, {} bytes total)", text, self.total)
+        } else {
+            text
+        }
+    }
+}
+
+/// 流式透传请求体，同时记录大小与前缀
+struct TeeRequestBody<B> {
+    inner: B,
+    capture: Arc<std::sync::Mutex<StreamedBodyCapture>>,
+    /// 请求追踪（记录完整请求体）
+    trace: Option<TraceHandle>,
+}
+
+impl<B> http_body::Body for TeeRequestBody<B>
+where
+    B: http_body::Body<Data = Bytes> + Unpin,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        let result = Pin::new(&mut this.inner).poll_frame(cx);
+
+        if let Poll::Ready(Some(Ok(frame))) = &result {
+            if let Some(data) = frame.data_ref() {
+                let mut capture = this.capture.lock().unwrap_or_else(|e| e.into_inner());
+                capture.total += data.len() as u64;
+                let room = STREAMED_BODY_CAPTURE_LIMIT.saturating_sub(capture.prefix.len());
+                if room > 0 {
+                    capture.prefix.extend_from_slice(&data[..data.len().min(room)]);
+                }
+                if let Some(trace) = &this.trace {
+                    trace.append_request_body(data);
+                }
+            }
+        }
+
+        result
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
     fn size_hint(&self) -> http_body::SizeHint {
         self.inner.size_hint()
     }
 }
 
+/// 去除 `/session/{session_id}` 路径前缀，得到实际的 API 路径
+pub(crate) fn strip_session_prefix(raw_path_and_query: &str) -> &str {
+    match raw_path_and_query.strip_prefix("/session/") {
+        // Skip session_id to find the actual path
+        Some(rest) => rest.find('/').map_or("/", |slash_pos| &rest[slash_pos..]),
+        None => raw_path_and_query,
+    }
+}
+
+/// 配置级别的超时覆盖（秒），未设置或非正数时返回 None
+fn timeout_override(secs: Option<i32>) -> Option<u64> {
+    secs.filter(|secs| *secs > 0).map(|secs| secs as u64)
+}
+
+/// 改写发往后端的 Host 与 Authorization 头
+///
+/// `ApiKeyMode::Client` 时保留客户端发送的认证头，只改写 Host
+fn rewrite_backend_auth_headers(
+    headers: &mut hyper::HeaderMap,
+    backend_host: &str,
+    api_key: &str,
+    api_key_mode: ApiKeyMode,
+) -> AppResult<()> {
+    // 1. 设置Host头为后端主机名（88Code等服务会检查Host头来验证请求来源）
+    headers.insert("host", backend_host.parse().map_err(|_| {
+        AppError::ServiceError {
+            message: "Failed to parse backend host".to_string(),
+        }
+    })?);
+
+    if api_key_mode == ApiKeyMode::Client {
+        if !headers.contains_key("authorization") && !headers.contains_key("x-api-key") {
+            log::warn!("Config uses the client's API key, but the request has no authorization or x-api-key header");
+        }
+        return Ok(());
+    }
+
+    // 2. 替换 Authorization 头为后端服务的 API 密钥（使用 Bearer 格式）
+    // 注意：不删除，而是替换，因为后端服务需要 Authorization 头来认证
+    let auth_value = format!("Bearer {}", api_key);
+    headers.insert("authorization", auth_value.parse().map_err(|_| {
+        AppError::ServiceError {
+            message: "Failed to parse authorization header".to_string(),
+        }
+    })?);
+
+    Ok(())
+}
+
+/// 按配置的过滤规则改写 `anthropic-beta` 请求头，返回被移除的 beta 标志
+///
+/// 多行或逗号分隔的标志会合并为一行；全部被移除时删除该请求头。
+fn filter_anthropic_beta_headers(headers: &mut hyper::HeaderMap, filter: &AnthropicBetaFilter) -> Vec<String> {
+    if filter.is_passthrough() || !headers.contains_key("anthropic-beta") {
+        return Vec::new();
+    }
+
+    let flags: Vec<String> = headers
+        .get_all("anthropic-beta")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect();
+    let (kept, stripped) = filter.split(flags.iter().map(String::as_str));
+    if stripped.is_empty() {
+        return stripped;
+    }
+
+    headers.remove("anthropic-beta");
+    if !kept.is_empty() {
+        if let Ok(value) = hyper::header::HeaderValue::from_str(&kept.join(",")) {
+            headers.insert("anthropic-beta", value);
+        }
+    }
+    stripped
+}
+
+/// 用配置的 User-Agent 替换客户端的 User-Agent，返回是否已替换
+///
+/// 未配置时保持客户端的 User-Agent 不变
+fn apply_user_agent_override(headers: &mut hyper::HeaderMap, user_agent: Option<&str>) -> bool {
+    let Some(user_agent) = user_agent.filter(|ua| !ua.is_empty()) else {
+        return false;
+    };
+    match hyper::header::HeaderValue::from_str(user_agent) {
+        Ok(value) => {
+            headers.insert(hyper::header::USER_AGENT, value);
+            true
+        }
+        Err(_) => {
+            log::warn!("配置的 User-Agent 不是有效的请求头值，保留客户端 User-Agent");
+            false
+        }
+    }
+}
+
+/// 按请求强制指定模型的请求头 (用于模型 A/B 测试)
+const MODEL_OVERRIDE_HEADER: &str = "x-ccproxy-model";
+
+/// 取出并移除 `x-ccproxy-model` 请求头，返回指定的模型
+///
+/// 请求头只供代理使用，无论是否有效都不会转发给后端
+fn take_model_override(headers: &mut hyper::HeaderMap) -> Option<String> {
+    let value = headers.remove(MODEL_OVERRIDE_HEADER)?;
+    let model = value.to_str().ok().map(str::trim).filter(|m| !m.is_empty());
+    if model.is_none() {
+        log::warn!("忽略无效的 {} 请求头", MODEL_OVERRIDE_HEADER);
+    }
+    model.map(String::from)
+}
+
+/// 将客户端 IP 追加到 `X-Forwarded-For` 并设置 `X-Real-IP`
+///
+/// 已有的 `X-Forwarded-For` (可能有多行) 会被保留并在末尾追加，而不是替换。
+fn apply_client_ip_headers(headers: &mut hyper::HeaderMap, client_ip: std::net::IpAddr) {
+    let ip = client_ip.to_canonical().to_string();
+
+    let existing: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .collect();
+    let forwarded_for = if existing.is_empty() {
+        ip.clone()
+    } else {
+        format!("{}, {}", existing.join(", "), ip)
+    };
+
+    if let Ok(value) = hyper::header::HeaderValue::from_str(&forwarded_for) {
+        headers.insert("x-forwarded-for", value);
+    }
+    if let Ok(value) = hyper::header::HeaderValue::from_str(&ip) {
+        headers.insert("x-real-ip", value);
+    }
+}
+
+/// 对 JSON 请求体/响应体应用分组转换规则
+///
+/// 返回 None 表示无需改写（非 JSON 或没有规则生效），调用方应原样使用原始数据
+fn transform_json_body(
+    bytes: &[u8],
+    apply: impl FnOnce(&mut serde_json::Value) -> usize,
+) -> Option<Vec<u8>> {
+    let mut json = match serde_json::from_slice::<serde_json::Value>(bytes) {
+        Ok(json) => json,
+        Err(_) => {
+            log::debug!("Body is not JSON, skipping body transform");
+            return None;
+        }
+    };
+
+    let applied = apply(&mut json);
+    if applied == 0 {
+        return None;
+    }
+
+    log::info!("Body transform applied {} rule(s)", applied);
+    serde_json::to_vec(&json).ok()
+}
+
 /// Stream wrapper to support both HTTP and HTTPS connections
 enum MaybeHttpsStream {
     Http(TcpStream),
@@ -219,32 +1135,159 @@ impl AsyncWrite for MaybeHttpsStream {
     }
 }
 
-/// Request Router
-/// Forwards requests to Claude API backends based on configuration
-pub struct RequestRouter {
-    /// Database connection pool
-    db_pool: Arc<DbPool>,
-    /// Auto-switch service
-    auto_switch: Arc<AutoSwitchService>,
-    /// Proxy server configuration (shared with ProxyServer)
-    proxy_config: Option<Arc<tokio::sync::RwLock<crate::proxy::server::ProxyConfig>>>,
+/// OpenAI → Claude 流式转换状态
+///
+/// 按行缓冲 OpenAI SSE 数据，逐块转换为 Claude 事件，并保证以 `message_stop` 收尾
+struct OpenAIStreamConversion {
+    claude_model: String,
+    buffer: Vec<u8>,
+    is_first_chunk: bool,
+    message_stopped: bool,
+    done: bool,
 }
 
-impl RequestRouter {
-    /// Create a new request router
-    #[allow(dead_code)]
-    pub fn new(db_pool: Arc<DbPool>) -> Self {
-        let auto_switch = Arc::new(AutoSwitchService::new(db_pool.clone()));
+impl OpenAIStreamConversion {
+    fn new(claude_model: String) -> Self {
         Self {
-            db_pool,
-            auto_switch,
-            proxy_config: None,
+            claude_model,
+            buffer: Vec::new(),
+            is_first_chunk: true,
+            message_stopped: false,
+            done: false,
         }
     }
 
-    /// Create a new request router with proxy config reference and shared auto-switch service
-    pub fn new_with_config(
-        db_pool: Arc<DbPool>,
+    /// 是否已收到 `[DONE]` 或已输出终止事件
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// 追加一段原始数据，返回由完整行转换出的 Claude 事件
+    fn push(&mut self, data: &[u8]) -> String {
+        if self.done {
+            return String::new();
+        }
+        self.buffer.extend_from_slice(data);
+
+        let mut events = String::new();
+        while let Some(newline_pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line_bytes = self.buffer.drain(..=newline_pos).collect::<Vec<_>>();
+            events.push_str(&self.process_line(&String::from_utf8_lossy(&line_bytes)));
+            if self.done {
+                break;
+            }
+        }
+        events
+    }
+
+    /// 上游正常结束：处理残留行并补齐终止事件
+    fn finish(&mut self) -> String {
+        let mut events = String::new();
+        if !self.done && !self.buffer.is_empty() {
+            let rest = std::mem::take(&mut self.buffer);
+            events.push_str(&self.process_line(&String::from_utf8_lossy(&rest)));
+        }
+        events.push_str(&self.close());
+        events
+    }
+
+    /// 上游读取失败：输出 Claude 错误事件后结束
+    fn fail(&mut self, message: &str) -> String {
+        if self.done {
+            return String::new();
+        }
+        self.done = true;
+        let error = serde_json::json!({
+            "type": "error",
+            "error": { "type": "api_error", "message": message }
+        });
+        format!("event: error\ndata: {}\n\n", error)
+    }
+
+    fn process_line(&mut self, line: &str) -> String {
+        let Some(payload) = line.trim().strip_prefix("data:") else {
+            return String::new();
+        };
+        let payload = payload.trim();
+
+        if payload == "[DONE]" {
+            log::info!("OpenAI stream completed with [DONE] marker");
+            return self.close();
+        }
+
+        match serde_json::from_str::<crate::converters::openai_types::OpenAIStreamChunk>(payload) {
+            Ok(chunk) => {
+                if chunk.choices.iter().any(|c| c.finish_reason.is_some()) {
+                    self.message_stopped = true;
+                }
+                let events = crate::converters::openai_claude::convert_openai_stream_to_claude(
+                    &chunk,
+                    self.is_first_chunk,
+                    &self.claude_model,
+                );
+                self.is_first_chunk = false;
+                events.concat()
+            }
+            Err(e) => {
+                log::warn!("Skipping unparsable OpenAI stream chunk: {}", e);
+                String::new()
+            }
+        }
+    }
+
+    /// 补齐未发送的 `content_block_stop` / `message_delta` / `message_stop`
+    fn close(&mut self) -> String {
+        if self.done {
+            return String::new();
+        }
+        self.done = true;
+        if self.is_first_chunk || self.message_stopped {
+            return String::new();
+        }
+        self.message_stopped = true;
+
+        let block_stop = serde_json::json!({ "type": "content_block_stop", "index": 0 });
+        let message_delta = serde_json::json!({
+            "type": "message_delta",
+            "delta": { "stop_reason": "end_turn", "stop_sequence": null },
+            "usage": { "output_tokens": 0 }
+        });
+        let message_stop = serde_json::json!({ "type": "message_stop" });
+        format!(
+            "event: content_block_stop\ndata: {}\n\nevent: message_delta\ndata: {}\n\nevent: message_stop\ndata: {}\n\n",
+            block_stop, message_delta, message_stop
+        )
+    }
+}
+
+/// Request Router
+/// Forwards requests to Claude API backends based on configuration
+pub struct RequestRouter {
+    /// Database connection pool
+    db_pool: Arc<DbPool>,
+    /// Auto-switch service
+    auto_switch: Arc<AutoSwitchService>,
+    /// Proxy server configuration (shared with ProxyServer)
+    proxy_config: Option<Arc<tokio::sync::RwLock<crate::proxy::server::ProxyConfig>>>,
+    /// Whether failures and high latency may switch the group's active config
+    switch_on_failure: bool,
+}
+
+impl RequestRouter {
+    /// Create a new request router
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        let auto_switch = Arc::new(AutoSwitchService::new(db_pool.clone()));
+        Self {
+            db_pool,
+            auto_switch,
+            proxy_config: None,
+            switch_on_failure: true,
+        }
+    }
+
+    /// Create a new request router with proxy config reference and shared auto-switch service
+    pub fn new_with_config(
+        db_pool: Arc<DbPool>,
         proxy_config: Arc<tokio::sync::RwLock<crate::proxy::server::ProxyConfig>>,
         auto_switch: Arc<AutoSwitchService>,
     ) -> Self {
@@ -252,9 +1295,59 @@ impl RequestRouter {
             db_pool,
             auto_switch,
             proxy_config: Some(proxy_config),
+            switch_on_failure: true,
+        }
+    }
+
+    /// Never switch the active config on failure or high latency
+    ///
+    /// Used for session-pinned requests (terminal sessions, load tests): they are routed to a
+    /// fixed config, so their failures must not rewrite the global active config or its
+    /// failure counters.
+    pub fn without_auto_switch(mut self) -> Self {
+        self.switch_on_failure = false;
+        self
+    }
+
+    /// 解析实际生效的连接超时与请求超时
+    ///
+    /// 优先级: 配置级别覆盖 > ProxyConfig 全局设置 > 默认值
+    async fn resolve_timeouts(&self, config: &ApiConfig) -> (Duration, Duration) {
+        let (global_connect, global_request) = match &self.proxy_config {
+            Some(proxy_cfg) => {
+                let cfg = proxy_cfg.read().await;
+                (cfg.connect_timeout_secs, cfg.request_timeout_secs)
+            }
+            None => (DEFAULT_CONNECT_TIMEOUT_SECS, DEFAULT_REQUEST_TIMEOUT_SECS),
+        };
+
+        let connect_secs = timeout_override(config.connect_timeout_secs).unwrap_or(global_connect);
+        let request_secs = timeout_override(config.request_timeout_secs).unwrap_or(global_request);
+
+        (Duration::from_secs(connect_secs), Duration::from_secs(request_secs))
+    }
+
+    /// 流式响应上限：ProxyConfig 全局设置 > 默认值
+    async fn resolve_stream_limits(&self) -> StreamLimits {
+        match &self.proxy_config {
+            Some(proxy_cfg) => {
+                let cfg = proxy_cfg.read().await;
+                StreamLimits::new(cfg.max_stream_duration_secs, cfg.max_stream_chunks)
+            }
+            None => StreamLimits::new(DEFAULT_MAX_STREAM_DURATION_SECS, DEFAULT_MAX_STREAM_CHUNKS),
         }
     }
 
+    /// 分组配置的 SSE 重连提示 (毫秒)
+    fn group_sse_retry_ms(&self, group_id: i64) -> Option<u32> {
+        self.db_pool
+            .with_connection(|conn| {
+                use crate::services::config_manager::ConfigManager;
+                ConfigManager::get_group_by_id(conn, group_id).map(|g| g.sse_retry_hint())
+            })
+            .unwrap_or(None)
+    }
+
     /// 设置 Tauri app handle (for auto-switch events)
     #[allow(dead_code)]
     pub async fn set_app_handle(&self, handle: tauri::AppHandle) {
@@ -267,22 +1360,44 @@ impl RequestRouter {
     /// - `req`: Original HTTP request
     /// - `config_id`: Target configuration ID
     /// - `group_id`: Current group ID (for auto-switch)
+    /// - `client_addr`: Remote address of the client connection
     ///
     /// # Returns
     /// - Tuple of (forwarded response, forward details, optional stream completion receiver) or error
-    pub async fn forward_request(
+    pub async fn forward_request<B>(
         &self,
-        req: Request<Incoming>,
+        req: Request<B>,
         config_id: i64,
         group_id: i64,
-    ) -> AppResult<(Response<BoxBody<Bytes, hyper::Error>>, ForwardDetails, Option<mpsc::Receiver<StreamCompletionData>>)> {
+        client_addr: std::net::SocketAddr,
+    ) -> AppResult<(Response<BoxBody<Bytes, hyper::Error>>, ForwardDetails, Option<mpsc::Receiver<StreamCompletionData>>)>
+    where
+        B: http_body::Body<Data = Bytes, Error = hyper::Error> + Send + Sync + Unpin + 'static,
+    {
         let start_time = Instant::now();
 
+        // 关闭请求体日志时只保留元数据（大小、状态、模型、耗时）
+        let log_bodies = self
+            .db_pool
+            .with_connection(|conn| Ok(ProxyRequestLogService::body_logging_enabled(conn)))
+            .unwrap_or(true);
+
+        // 命中等待追踪的配置时记录该请求的完整耗时分解与请求/响应体
+        let trace = TraceHandle::start_if_armed(config_id, current_request_id(), &client_addr.to_string());
+
         // Try forwarding with current config
-        match self.try_forward(req, config_id, group_id).await {
-            Ok((response, details, stream_rx)) => {
+        let result = self.try_forward(req, config_id, group_id, client_addr, log_bodies, trace.clone()).await;
+        if let (Some(trace), Err(e)) = (&trace, &result) {
+            trace.set_error(&e.to_string());
+        }
+        match result {
+            Ok((response, mut details, stream_rx)) => {
                 let latency = start_time.elapsed().as_millis();
 
+                if !log_bodies {
+                    details.strip_bodies();
+                }
+
                 // Get group's latency threshold from database
                 let latency_threshold = self.db_pool.with_connection(|conn| {
                     use crate::services::config_manager::ConfigManager;
@@ -291,7 +1406,9 @@ impl RequestRouter {
                 }).unwrap_or(HIGH_LATENCY_THRESHOLD_MS);
 
                 // Check for high latency trigger (FR-016)
-                if latency > latency_threshold {
+                if latency > latency_threshold && !self.switch_on_failure {
+                    log::warn!("High latency on session-pinned config {}: {}ms", config_id, latency);
+                } else if latency > latency_threshold {
                     log::warn!(
                         "High latency detected: {}ms (threshold: {}ms)",
                         latency,
@@ -319,6 +1436,11 @@ impl RequestRouter {
 
                 Ok((response, details, stream_rx))
             }
+            // 请求体超限是客户端问题，不计入配置失败也不触发切换
+            Err(e @ AppError::PayloadTooLarge { .. }) => {
+                log::warn!("Rejected request: {}", e);
+                Err(e)
+            }
             Err(e) => {
                 // T045: 使用智能重试机制处理失败
                 let (_reason, error_msg) = self.classify_error(&e);
@@ -326,55 +1448,815 @@ impl RequestRouter {
 
                 log::error!("Request failed: {}, error_msg: {}", e, error_msg);
 
-                // T037-T044: 调用智能重试逻辑 (错误分类、可恢复性判断、重试决策)
-                match self
-                    .auto_switch
-                    .handle_failure_with_retry(config_id, group_id, error_msg.clone(), Some(latency))
-                    .await
-                {
-                    Ok(Some(new_config_id)) => {
-                        // 立即切换到新配置
-                        log::info!("立即切换到新配置: {}", new_config_id);
-
-                        // Update proxy config if we have reference
-                        if let Some(proxy_cfg) = &self.proxy_config {
-                            let mut cfg = proxy_cfg.write().await;
-                            cfg.active_config_id = Some(new_config_id);
-                            log::info!("Updated proxy active_config_id to {}", new_config_id);
-                        }
+                // Cannot retry because Request<Incoming> cannot be cloned
+                // The next request will use the new config
+                self.handle_failure_switch(config_id, group_id, error_msg, Some(latency)).await;
+                Err(e)
+            }
+        }
+    }
 
-                        // Update database ProxyService record
-                        if let Err(update_err) = self.update_proxy_service_config(new_config_id).await {
-                            log::error!("Failed to update ProxyService config: {}", update_err);
-                        } else {
-                            log::info!("Updated ProxyService current_config_id to {}", new_config_id);
-                        }
+    /// Forward a request routed to the group's canary config
+    ///
+    /// Unlike `forward_request`, failures and high latency never switch the group's
+    /// active config; the outcome only feeds the canary's consecutive-failure counter,
+    /// which pauses the canary once it keeps failing. The request body is buffered so
+    /// a failed canary request is retried once on the group's active config
+    /// (`fallback_config_id`), which goes through the normal auto-switch path.
+    pub async fn forward_canary_request(
+        &self,
+        req: Request<Incoming>,
+        canary_config_id: i64,
+        fallback_config_id: i64,
+        group_id: i64,
+        client_addr: std::net::SocketAddr,
+    ) -> AppResult<(Response<BoxBody<Bytes, hyper::Error>>, ForwardDetails, Option<mpsc::Receiver<StreamCompletionData>>)> {
+        let log_bodies = self
+            .db_pool
+            .with_connection(|conn| Ok(ProxyRequestLogService::body_logging_enabled(conn)))
+            .unwrap_or(true);
 
-                        // Cannot retry because Request<Incoming> cannot be cloned
-                        // The next request will use the new config
-                        Err(e)
-                    }
-                    Ok(None) => {
-                        // 决定重试当前配置（不切换）
-                        log::info!("决定重试当前配置: {}, 下次请求将继续使用", config_id);
-                        Err(e)
+        let (parts, body) = req.into_parts();
+        let body_bytes = collect_request_body(body, &parts.headers, MAX_BUFFERED_REQUEST_BODY_BYTES)
+            .await
+            .inspect_err(|e| log::warn!("Rejected canary request: {}", e))?;
+
+        let trace = TraceHandle::start_if_armed(canary_config_id, current_request_id(), &client_addr.to_string());
+
+        let result = self
+            .try_forward(
+                rebuild_request(&parts, body_bytes.clone()),
+                canary_config_id,
+                group_id,
+                client_addr,
+                log_bodies,
+                trace.clone(),
+            )
+            .await;
+        if let (Some(trace), Err(e)) = (&trace, &result) {
+            trace.set_error(&e.to_string());
+        }
+        match result {
+            Ok((response, mut details, stream_rx)) => {
+                if !log_bodies {
+                    details.strip_bodies();
+                }
+                // 流式响应的软错误在流结束后另行记录
+                if stream_rx.is_none() {
+                    canary::record_outcome(&self.db_pool, group_id, canary_config_id, true);
+                }
+                Ok((response, details, stream_rx))
+            }
+            Err(e @ AppError::PayloadTooLarge { .. }) => {
+                log::warn!("Rejected canary request: {}", e);
+                Err(e)
+            }
+            Err(e) => {
+                log::error!(
+                    "Canary request to config {} failed, retrying on active config {}: {}",
+                    canary_config_id,
+                    fallback_config_id,
+                    e
+                );
+                canary::record_outcome(&self.db_pool, group_id, canary_config_id, false);
+                if let Err(e) = self.db_pool.with_connection(|conn| {
+                    ApiConfigService::increment_failure_count(conn, canary_config_id)
+                }) {
+                    log::warn!("Failed to increment failure count for config {}: {}", canary_config_id, e);
+                }
+
+                let (response, mut details, stream_rx) = self
+                    .forward_request(rebuild_request(&parts, body_bytes), fallback_config_id, group_id, client_addr)
+                    .await?;
+                details.canary_fallback_config_id = Some(fallback_config_id);
+                Ok((response, details, stream_rx))
+            }
+        }
+    }
+
+    /// 处理流式响应终止事件中的软错误
+    ///
+    /// 响应已发送给客户端，按失败处理只影响后续请求的配置选择
+    pub async fn handle_stream_soft_error(&self, config_id: i64, group_id: i64, error_msg: String) {
+        self.handle_failure_switch(config_id, group_id, error_msg, None).await;
+    }
+
+    /// T037-T044: 调用智能重试逻辑 (错误分类、可恢复性判断、重试决策)，需要时切换配置
+    async fn handle_failure_switch(
+        &self,
+        config_id: i64,
+        group_id: i64,
+        error_msg: String,
+        latency: Option<i32>,
+    ) {
+        if !self.switch_on_failure {
+            log::info!("Config {} failed on a session-pinned request, not switching: {}", config_id, error_msg);
+            return;
+        }
+        match self
+            .auto_switch
+            .handle_failure_with_retry(config_id, group_id, error_msg, latency)
+            .await
+        {
+            Ok(Some(new_config_id)) => {
+                // 立即切换到新配置
+                log::info!("立即切换到新配置: {}", new_config_id);
+
+                // Update proxy config if we have reference
+                if let Some(proxy_cfg) = &self.proxy_config {
+                    let mut cfg = proxy_cfg.write().await;
+                    cfg.active_config_id = Some(new_config_id);
+                    log::info!("Updated proxy active_config_id to {}", new_config_id);
+                }
+
+                // Update database ProxyService record (named listeners only route through their own config)
+                if !self.auto_switch.updates_proxy_service() {
+                    log::debug!("Listener switch, ProxyService record left unchanged");
+                } else if let Err(update_err) = self.update_proxy_service_config(new_config_id).await {
+                    log::error!("Failed to update ProxyService config: {}", update_err);
+                } else {
+                    log::info!("Updated ProxyService current_config_id to {}", new_config_id);
+                }
+            }
+            Ok(None) => {
+                // 决定重试当前配置（不切换）
+                log::info!("决定重试当前配置: {}, 下次请求将继续使用", config_id);
+            }
+            Err(switch_err) => {
+                log::error!("智能重试处理失败: {}", switch_err);
+            }
+        }
+    }
+
+    /// 预览发往后端的请求
+    ///
+    /// 执行与 `try_forward` 相同的请求改写（路径拼接、Host/认证头、字段过滤、模型映射、格式转换、
+    /// 分组请求体转换规则），返回最终的方法、URI、请求头与请求体，不建立连接也不发送。
+    /// 客户端 IP 头依赖真实连接，预览中不会添加。
+    pub fn preview_request(
+        &self,
+        config_id: i64,
+        client_path_and_query: &str,
+        client_headers: hyper::HeaderMap,
+        body: &[u8],
+    ) -> AppResult<ForwardedRequestPreview> {
+        let (config, api_key) = self.db_pool.with_connection(|conn| {
+            let config = ApiConfigService::get_config_by_id(conn, config_id)?;
+            let api_key = ApiConfigService::get_api_key(conn, config_id)?;
+            Ok((config, api_key))
+        })?;
+
+        let client_path_and_query = strip_session_prefix(client_path_and_query);
+        let routing_ctx = RoutingContext::new(&client_headers, client_path_and_query, config.provider_type);
+
+        let parsed_url = parse_server_url(&config.server_url);
+        let mut uri = parsed_url.target_path(client_path_and_query);
+
+        let mut headers = client_headers;
+        let model_override = take_model_override(&mut headers);
+        rewrite_backend_auth_headers(&mut headers, parsed_url.host.as_str(), &api_key, config.api_key_mode)?;
+        apply_user_agent_override(&mut headers, config.user_agent.as_deref());
+
+        let group = config.group_id.and_then(|group_id| {
+            self.db_pool.with_connection(|conn| {
+                use crate::services::config_manager::ConfigManager;
+                ConfigManager::get_group_by_id(conn, group_id)
+            }).ok()
+        });
+        let native_passthrough = is_native_passthrough(group.as_ref(), &config);
+        let body_transform = group
+            .filter(|_| !native_passthrough)
+            .and_then(|g| g.body_transform_spec());
+        let has_request_transform = body_transform
+            .as_ref()
+            .is_some_and(|spec| !spec.request.is_empty());
+
+        let default_model = self.default_request_model();
+        let body_streamed = native_passthrough
+            || (default_model.is_none()
+                && model_override.is_none()
+                && !request_body_needs_buffering(
+                    routing_ctx.request_conversion,
+                    has_request_transform,
+                    &config,
+                    &headers,
+                ));
+
+        let (body, mapped_model) = if body_streamed {
+            (body.to_vec(), None)
+        } else {
+            let transformed = self.transform_request_body(
+                routing_ctx.request_conversion,
+                &config,
+                body,
+                body_transform.as_ref(),
+                default_model.as_deref(),
+                model_override.as_deref(),
+            )?;
+            if let Some(path) = transformed.target_path {
+                uri = path;
+            }
+            (transformed.body, transformed.mapped_model)
+        };
+        set_buffered_body_framing(&mut headers, body.len());
+
+        let body_compressed = !body_streamed
+            && request_compression::should_compress(
+                config.request_compression,
+                &parsed_url.host,
+                body.len(),
+                headers.get(hyper::header::CONTENT_ENCODING).and_then(|v| v.to_str().ok()),
+            );
+        if body_compressed {
+            let compressed = request_compression::gzip(&body).map_err(|e| AppError::ServiceError {
+                message: format!("Failed to gzip request body: {}", e),
+            })?;
+            headers.insert(
+                hyper::header::CONTENT_ENCODING,
+                hyper::header::HeaderValue::from_static("gzip"),
+            );
+            set_buffered_body_framing(&mut headers, compressed.len());
+        }
+
+        filter_anthropic_beta_headers(&mut headers, &config.beta_filter());
+
+        // 固定查询参数可能包含密钥，预览中隐藏参数值
+        if let Some(extra_query) = config.extra_query.as_deref() {
+            uri = merge_query(&uri, &redact_query_values(extra_query));
+        }
+
+        let headers = headers
+            .iter()
+            .map(|(name, value)| {
+                let value = value.to_str().unwrap_or("");
+                let value = match name.as_str() {
+                    "authorization" => match value.split_once(' ') {
+                        Some((scheme, key)) => format!("{} {}", scheme, redact_key(key)),
+                        None => redact_key(value),
+                    },
+                    "x-api-key" => redact_key(value),
+                    _ => value.to_string(),
+                };
+                (name.as_str().to_string(), value)
+            })
+            .collect();
+
+        Ok(ForwardedRequestPreview {
+            method: hyper::Method::POST.to_string(),
+            target_addr: parsed_url.target_addr,
+            is_https: parsed_url.is_https,
+            uri,
+            headers,
+            body: String::from_utf8_lossy(&body).to_string(),
+            conversion: routing_ctx.request_conversion.to_string(),
+            mapped_model,
+            body_streamed,
+            body_compressed,
+        })
+    }
+
+    /// 解析配置在转发时实际生效的设置，并标注每项的来源
+    ///
+    /// 与转发使用相同的优先级: 配置级别覆盖 > 分组设置 > 应用/代理全局设置 > 默认值。
+    /// `proxy_config` 为代理服务当前的全局设置，代理未创建时传 None 使用默认值。
+    pub fn effective_config(
+        &self,
+        config_id: i64,
+        proxy_config: Option<&crate::proxy::server::ProxyConfig>,
+    ) -> AppResult<EffectiveConfig> {
+        use crate::services::config_manager::ConfigManager;
+        use crate::services::provider_preset::ProviderPresetService;
+        use EffectiveValueSource::*;
+
+        let (config, group) = self.db_pool.with_connection(|conn| {
+            let config = ApiConfigService::get_config_by_id(conn, config_id)?;
+            let group = match config.group_id {
+                Some(group_id) => ConfigManager::get_group_by_id(conn, group_id).ok(),
+                None => None,
+            };
+            Ok((config, group))
+        })?;
+
+        let preset_id = ProviderPresetService::load_providers().ok().and_then(|presets| {
+            let server_url = config.server_url.trim_end_matches('/');
+            presets
+                .into_iter()
+                .find(|preset| preset.server_url.trim_end_matches('/') == server_url)
+                .map(|preset| preset.id)
+        });
+
+        let mut effective = EffectiveConfig {
+            config_id: config.id,
+            config_name: config.name.clone(),
+            group_id: config.group_id,
+            preset_id: preset_id.clone(),
+            values: Vec::new(),
+        };
+
+        let server_url_source = if preset_id.is_some() { Preset } else { Config };
+        effective.push("server_url", &config.server_url, server_url_source);
+        effective.push("provider_type", config.provider_type, Config);
+        match config.api_key_mode {
+            ApiKeyMode::Stored => effective.push("auth_scheme", "Authorization: Bearer <api_key>", Default),
+            ApiKeyMode::Client => effective.push("auth_scheme", "client authorization / x-api-key", Config),
+        }
+        match config.request_compression {
+            RequestCompression::Off => effective.push("request_compression", RequestCompression::Off.as_str(), Default),
+            mode => effective.push("request_compression", mode.as_str(), Config),
+        }
+
+        let (global_source, global_connect, global_request, stream_limits) = match proxy_config {
+            Some(cfg) => (
+                ProxySettings,
+                cfg.connect_timeout_secs,
+                cfg.request_timeout_secs,
+                StreamLimits::new(cfg.max_stream_duration_secs, cfg.max_stream_chunks),
+            ),
+            None => (
+                Default,
+                DEFAULT_CONNECT_TIMEOUT_SECS,
+                DEFAULT_REQUEST_TIMEOUT_SECS,
+                StreamLimits::new(DEFAULT_MAX_STREAM_DURATION_SECS, DEFAULT_MAX_STREAM_CHUNKS),
+            ),
+        };
+        for (key, config_value, global_value) in [
+            ("connect_timeout_secs", config.connect_timeout_secs, global_connect),
+            ("request_timeout_secs", config.request_timeout_secs, global_request),
+        ] {
+            match timeout_override(config_value) {
+                Some(secs) => effective.push(key, secs, Config),
+                None => effective.push(key, global_value, global_source),
+            }
+        }
+        effective.push(
+            "max_stream_duration_secs",
+            stream_limits.max_duration.map(|d| d.as_secs()),
+            global_source,
+        );
+        effective.push("max_stream_chunks", stream_limits.max_chunks, global_source);
+
+        match self.default_request_model() {
+            Some(model) => effective.push("default_request_model", model, AppSettings),
+            None => effective.push("default_request_model", None::<String>, Default),
+        }
+        for (key, model) in [
+            ("default_model", &config.default_model),
+            ("haiku_model", &config.haiku_model),
+            ("sonnet_model", &config.sonnet_model),
+            ("opus_model", &config.opus_model),
+            ("small_fast_model", &config.small_fast_model),
+        ] {
+            match model.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+                Some(model) => effective.push(key, model, Config),
+                None => effective.push(key, None::<String>, Default),
+            }
+        }
+
+        match config.extra_query.as_deref().filter(|q| !q.is_empty()) {
+            Some(query) => effective.push("extra_query", redact_query_values(query), Config),
+            None => effective.push("extra_query", None::<String>, Default),
+        }
+        match config.user_agent.as_deref().filter(|ua| !ua.is_empty()) {
+            Some(user_agent) => effective.push("user_agent", redact_text(user_agent, &[]), Config),
+            None => effective.push("user_agent", None::<String>, Default),
+        }
+        let strip_source = if config.strip_request_fields.is_some() { Config } else { Default };
+        effective.push("strip_request_fields", config.request_fields_to_strip(), strip_source);
+        let policy_source = if config.metadata_user_id_policy != MetadataUserIdPolicy::default() {
+            Config
+        } else {
+            Default
+        };
+        effective.push("metadata_user_id_policy", config.metadata_user_id_policy, policy_source);
+
+        match group.as_ref().and_then(|g| g.body_transform_spec()) {
+            Some(spec) => effective.push("body_transform", spec, Group),
+            None => effective.push("body_transform", None::<BodyTransformSpec>, Default),
+        }
+        let native_source = if group.as_ref().is_some_and(|g| g.native_claude_only) { Group } else { Default };
+        effective.push("native_passthrough", is_native_passthrough(group.as_ref(), &config), native_source);
+        match group.as_ref().filter(|g| g.retry_strategy_customized) {
+            Some(group) => effective.push("retry_strategy", group.retry_strategy(), Group),
+            None => effective.push("retry_strategy", RetryStrategy::default(), Default),
+        }
+
+        Ok(effective)
+    }
+
+    /// 读取客户端未指定模型时注入的默认模型 (应用设置)
+    fn default_request_model(&self) -> Option<String> {
+        self.db_pool
+            .with_connection(|conn| Ok(ModelMappingService::lookup_default_request_model(conn)))
+            .unwrap_or(None)
+    }
+
+    /// 转换已缓冲的请求体
+    ///
+    /// 依次执行默认模型注入、模型映射、字段过滤 / 格式转换、分组请求体转换规则，
+    /// 转发与请求预览共用同一流程。
+    /// `model_override` (来自 `x-ccproxy-model` 请求头) 优先于模型映射，作为最终发送的模型
+    fn transform_request_body(
+        &self,
+        conversion: ConversionDirection,
+        config: &ApiConfig,
+        body_bytes: &[u8],
+        body_transform: Option<&BodyTransformSpec>,
+        default_model: Option<&str>,
+        model_override: Option<&str>,
+    ) -> AppResult<TransformedRequestBody> {
+        // 客户端未指定模型时，在转换与模型映射之前注入默认模型
+        let injected = default_model.and_then(|model| inject_default_model(body_bytes, model));
+        let body_bytes = injected.as_deref().unwrap_or(body_bytes);
+
+        // 尝试从请求体提取模型名称
+        let source_model: Option<String> = serde_json::from_slice::<serde_json::Value>(body_bytes)
+            .ok()
+            .and_then(|json| json.get("model").and_then(|m| m.as_str()).map(String::from));
+        let mut target_path: Option<String> = None;
+
+        // 查询模型映射（如果需要转换）
+        // 优先级: 请求头指定的模型 > 数据库映射规则 > 配置的模型覆盖 > 内置 MODEL_MAPPER
+        let mapped_model: Option<String> = if let Some(model) = model_override {
+            log::info!(
+                "Model override applied via {}: requested {} -> {}",
+                MODEL_OVERRIDE_HEADER,
+                source_model.as_deref().unwrap_or("unknown"),
+                model
+            );
+            Some(model.to_string())
+        } else if conversion != ConversionDirection::NoConversion {
+            if let Some(ref src_model) = source_model {
+                let direction_str = conversion.to_string();
+                let db_pool = self.db_pool.clone();
+                db_pool.with_connection(|conn| {
+                    Ok(ModelMappingService::lookup_target_model(conn, src_model, &direction_str))
+                })
+                .unwrap_or(None)
+                .or_else(|| resolve_cross_provider_model(conversion, src_model, config))
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        if mapped_model.is_some() && model_override.is_none() {
+            log::info!(
+                "Model mapping applied: {} -> {}",
+                source_model.as_deref().unwrap_or("unknown"),
+                mapped_model.as_deref().unwrap_or("unknown")
+            );
+        }
+
+        // Check conversion direction and perform conversion if needed
+        let processed_bytes = match conversion {
+            ConversionDirection::NoConversion => {
+                // 无需转换 - 过滤不支持的字段后直接转发
+                log::info!("No request conversion needed, forwarding as-is");
+                match serde_json::from_slice::<serde_json::Value>(body_bytes) {
+                    Ok(mut json) => {
+                        // Remove unsupported fields (per-config list, defaults to context_management)
+                        if let Some(obj) = json.as_object_mut() {
+                            let strip_fields = config.request_fields_to_strip();
+                            let removed_fields: Vec<String> = obj.keys()
+                                .filter(|k| strip_fields.contains(k))
+                                .cloned()
+                                .collect();
+
+                            for field in &removed_fields {
+                                obj.remove(field);
+                            }
+                            if !removed_fields.is_empty() {
+                                log::info!(
+                                    "Stripped fields from request for config {}: {:?}",
+                                    config.id,
+                                    removed_fields
+                                );
+                            }
+                        }
+                        if let Some(model) = model_override {
+                            if let Some(obj) = json.as_object_mut() {
+                                obj.insert("model".to_string(), serde_json::Value::String(model.to_string()));
+                            }
+                        }
+                        if config.metadata_user_id_policy.apply(&mut json) {
+                            log::debug!(
+                                "Applied metadata.user_id policy '{}' for config {}",
+                                config.metadata_user_id_policy.as_str(),
+                                config.id
+                            );
+                        }
+                        serde_json::to_vec(&json)
+                            .map_err(|e| AppError::ServiceError {
+                                message: format!("Failed to serialize filtered request: {}", e),
+                            })?
                     }
-                    Err(switch_err) => {
-                        log::error!("智能重试处理失败: {}", switch_err);
-                        Err(e)
+                    Err(_) => {
+                        log::debug!("Request body is not JSON, forwarding as-is");
+                        body_bytes.to_vec()
                     }
                 }
+            },
+            ConversionDirection::ClaudeToOpenAI => {
+                log::info!("Converting Claude request to OpenAI format");
+                let claude_req: ClaudeRequest = serde_json::from_slice(body_bytes)
+                    .map_err(|e| AppError::ConversionError {
+                        message: format!("Failed to parse Claude request: {}", e),
+                    })?;
+
+                let mut openai_req = crate::converters::openai_claude::convert_claude_request_to_openai(&claude_req);
+
+                // 应用模型映射
+                if let Some(ref target_model) = mapped_model {
+                    openai_req.model = target_model.clone();
+                }
+
+                // Update target path to OpenAI API endpoint
+                let openai_path = "/v1/chat/completions";
+                target_path = Some(openai_path.to_string());
+
+                log::info!("Updated request URI to OpenAI endpoint: {}", openai_path);
+
+                serde_json::to_vec(&openai_req)
+                    .map_err(|e| AppError::ConversionError {
+                        message: format!("Failed to serialize OpenAI request: {}", e),
+                    })?
+            },
+            ConversionDirection::OpenAIToClaude => {
+                log::info!("Converting OpenAI request to Claude format");
+                let openai_req: OpenAIRequest = serde_json::from_slice(body_bytes)
+                    .map_err(|e| AppError::ConversionError {
+                        message: format!("Failed to parse OpenAI request: {}", e),
+                    })?;
+
+                let mut claude_req = crate::converters::openai_claude::convert_openai_request_to_claude(&openai_req);
+
+                // 应用模型映射
+                if let Some(ref target_model) = mapped_model {
+                    claude_req.model = target_model.clone();
+                }
+
+                // Update target path to Claude API endpoint
+                let claude_path = "/v1/messages";
+                target_path = Some(claude_path.to_string());
+
+                log::info!("Updated request URI to Claude endpoint: {}", claude_path);
+
+                serde_json::to_vec(&claude_req)
+                    .map_err(|e| AppError::ConversionError {
+                        message: format!("Failed to serialize Claude request: {}", e),
+                    })?
+            },
+            ConversionDirection::ClaudeToGemini => {
+                log::info!("Converting Claude request to Gemini format");
+                let claude_req: ClaudeRequest = serde_json::from_slice(body_bytes)
+                    .map_err(|e| AppError::ConversionError {
+                        message: format!("Failed to parse Claude request: {}", e),
+                    })?;
+
+                // 优先使用映射后的模型名，否则使用配置的默认模型
+                let gemini_model = mapped_model
+                    .as_deref()
+                    .or_else(|| config.default_model.as_ref().filter(|m| !m.is_empty()).map(|m| m.as_str()))
+                    .unwrap_or("gemini-pro");
+
+                let (gemini_req, gemini_path) = convert_claude_request_to_gemini(&claude_req, gemini_model)?;
+
+                target_path = Some(gemini_path.to_string());
+
+                log::info!("Updated request URI to Gemini endpoint: {}", gemini_path);
+
+                serde_json::to_vec(&gemini_req)
+                    .map_err(|e| AppError::ConversionError {
+                        message: format!("Failed to serialize Gemini request: {}", e),
+                    })?
+            },
+            ConversionDirection::OpenAIToGemini => {
+                log::info!("Converting OpenAI request to Gemini format");
+                // 先将 OpenAI 转为 Claude，再转为 Gemini
+                let openai_req: OpenAIRequest = serde_json::from_slice(body_bytes)
+                    .map_err(|e| AppError::ConversionError {
+                        message: format!("Failed to parse OpenAI request: {}", e),
+                    })?;
+
+                let claude_req = crate::converters::openai_claude::convert_openai_request_to_claude(&openai_req);
+
+                // 优先使用映射后的模型名，否则使用配置的默认模型
+                let gemini_model = mapped_model
+                    .as_deref()
+                    .or_else(|| config.default_model.as_ref().filter(|m| !m.is_empty()).map(|m| m.as_str()))
+                    .unwrap_or("gemini-pro");
+
+                let (gemini_req, gemini_path) = convert_claude_request_to_gemini(&claude_req, gemini_model)?;
+
+                target_path = Some(gemini_path.to_string());
+
+                log::info!("Updated request URI to Gemini endpoint: {}", gemini_path);
+
+                serde_json::to_vec(&gemini_req)
+                    .map_err(|e| AppError::ConversionError {
+                        message: format!("Failed to serialize Gemini request: {}", e),
+                    })?
+            },
+            ConversionDirection::GeminiToClaude | ConversionDirection::GeminiToOpenAI => {
+                // Gemini 客户端通常不会发送请求到代理，暂时不支持
+                log::warn!("Gemini-originated requests are not yet supported, forwarding as-is");
+                body_bytes.to_vec()
+            }
+        };
+
+        // 10.3 Apply group body transform rules to the outgoing request
+        let processed_bytes = match body_transform.filter(|spec| !spec.request.is_empty()) {
+            Some(spec) => transform_json_body(&processed_bytes, |json| spec.apply_request(json))
+                .unwrap_or(processed_bytes),
+            None => processed_bytes,
+        };
+
+
+        Ok(TransformedRequestBody {
+            body: processed_bytes,
+            target_path,
+            source_model,
+            mapped_model,
+        })
+    }
+
+    /// 通过完整转发流程发送请求（不触发自动切换），返回响应状态与完整响应体
+    ///
+    /// 请求经过与真实流量相同的路径改写、请求头改写、字段过滤与格式转换，
+    /// 用于验证配置在代理中的实际表现
+    pub async fn forward_test_request(
+        &self,
+        config_id: i64,
+        group_id: i64,
+        path: &str,
+        headers: hyper::HeaderMap,
+        body: Bytes,
+    ) -> AppResult<(StatusCode, String)> {
+        let body = http_body_util::Full::new(body).map_err(|never| match never {});
+        let mut req = Request::builder()
+            .method(hyper::Method::POST)
+            .uri(path)
+            .body(body)
+            .map_err(|e| AppError::ServiceError {
+                message: format!("Failed to build test request: {}", e),
+            })?;
+        *req.headers_mut() = headers;
+
+        let client_addr = std::net::SocketAddr::from(([127, 0, 0, 1], 0));
+        let (response, _details, _stream_rx) = self
+            .try_forward(req, config_id, group_id, client_addr, true, None)
+            .await?;
+
+        let status = response.status();
+        let body_bytes = response
+            .into_body()
+            .collect()
+            .await
+            .map_err(|e| AppError::ServiceError {
+                message: format!("Failed to read response body: {}", e),
+            })?
+            .to_bytes();
+
+        Ok((status, String::from_utf8_lossy(&body_bytes).to_string()))
+    }
+
+    /// 连接后端并完成 HTTP/1.1 握手
+    ///
+    /// 连接超时只覆盖 TCP 连接 + TLS 握手阶段，尽快暴露主机不可达
+    async fn connect_backend(
+        parsed_url: &crate::utils::server_url::ParsedServerUrl,
+        target_addr: &str,
+        connect_timeout: Duration,
+        trace: &Option<TraceHandle>,
+    ) -> AppResult<hyper::client::conn::http1::SendRequest<BoxBody<Bytes, hyper::Error>>> {
+        let connect_start = std::time::Instant::now();
+        let stream = timeout(connect_timeout, async {
+            let connect_failed = |e: std::io::Error| {
+                log::error!("Failed to connect to target server ({}): {}", target_addr, e);
+                AppError::ServiceError {
+                    message: format!("Connection failed: {}", e),
+                }
+            };
+
+            // 单独解析地址，便于追踪 DNS 耗时
+            let dns_start = Instant::now();
+            let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host(&target_addr)
+                .await
+                .map_err(connect_failed)?
+                .collect();
+            let tcp_start = Instant::now();
+            let tcp_stream = TcpStream::connect(addrs.as_slice())
+                .await
+                .map_err(connect_failed)?;
+            if let Some(trace) = &trace {
+                trace.phase("dns", tcp_start - dns_start);
+                trace.phase("tcp_connect", tcp_start.elapsed());
+            }
+
+            if !parsed_url.is_https {
+                // Plain HTTP connection
+                return Ok(MaybeHttpsStream::Http(tcp_stream));
+            }
+
+            // Extract hostname for TLS SNI
+            let hostname = parsed_url.host.as_str();
+
+            log::debug!("Performing TLS handshake for HTTPS connection to {}", hostname);
+
+            // Create TLS connector with explicit crypto provider
+            let mut root_store = rustls::RootCertStore::empty();
+            root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+            // Explicitly use ring crypto provider to avoid runtime panic
+            let tls_config = rustls::ClientConfig::builder_with_provider(
+                    rustls::crypto::ring::default_provider().into()
+                )
+                .with_safe_default_protocol_versions()
+                .expect("Failed to configure TLS protocol versions")
+                .with_root_certificates(root_store)
+                .with_no_client_auth();
+
+            let connector = TlsConnector::from(Arc::new(tls_config));
+
+            // Perform TLS handshake
+            let server_name = ServerName::try_from(hostname.to_string())
+                .map_err(|e| AppError::ServiceError {
+                    message: format!("Invalid hostname for TLS: {}", e),
+                })?;
+
+            let tls_start = Instant::now();
+            let tls_stream = connector
+                .connect(server_name, tcp_stream)
+                .await
+                .map_err(|e| {
+                    log::error!("TLS handshake failed: {}", e);
+                    AppError::ServiceError {
+                        message: format!("TLS handshake failed: {}", e),
+                    }
+                })?;
+            if let Some(trace) = &trace {
+                trace.phase("tls_handshake", tls_start.elapsed());
             }
+
+            Ok::<_, AppError>(MaybeHttpsStream::Https(tls_stream))
+        })
+        .await
+        .map_err(|_| {
+            log::error!(
+                "Connection timeout to target server: {} after {}ms (timeout: {}s)",
+                target_addr,
+                connect_start.elapsed().as_millis(),
+                connect_timeout.as_secs()
+            );
+            AppError::ServiceError {
+                message: "Connection timeout".to_string(),
+            }
+        })
+        .and_then(|connected| connected)
+        .inspect_err(|_| connection_pool::connection_failed(target_addr))?;
+
+        let io = TokioIo::new(stream);
+
+        // 创建 HTTP/1.1 连接
+        let handshake_start = Instant::now();
+        let (sender, conn) = hyper::client::conn::http1::handshake(io)
+            .await
+            .map_err(|e| {
+                log::error!("HTTP handshake failed: {}", e);
+                AppError::ServiceError {
+                    message: format!("HTTP handshake failed: {}", e),
+                }
+            })?;
+        if let Some(trace) = &trace {
+            trace.phase("http_handshake", handshake_start.elapsed());
         }
+
+        // 启动连接处理任务 (连接关闭时释放活跃连接计数)
+        let active_connection = connection_pool::connection_opened(target_addr);
+        tokio::spawn(async move {
+            let _active_connection = active_connection;
+            if let Err(e) = conn.await {
+                log::error!("Connection error: {}", e);
+            }
+        });
+
+        Ok(sender)
     }
 
     /// Try forwarding request without auto-switch
-    async fn try_forward(
+    async fn try_forward<B>(
         &self,
-        mut req: Request<Incoming>,
+        mut req: Request<B>,
         config_id: i64,
-        _group_id: i64,
-    ) -> AppResult<(Response<BoxBody<Bytes, hyper::Error>>, ForwardDetails, Option<mpsc::Receiver<StreamCompletionData>>)> {
+        group_id: i64,
+        client_addr: std::net::SocketAddr,
+        log_bodies: bool,
+        trace: Option<TraceHandle>,
+    ) -> AppResult<(Response<BoxBody<Bytes, hyper::Error>>, ForwardDetails, Option<mpsc::Receiver<StreamCompletionData>>)>
+    where
+        B: http_body::Body<Data = Bytes, Error = hyper::Error> + Send + Sync + Unpin + 'static,
+    {
         // 初始化详情收集器
         let mut details = ForwardDetails::default();
 
@@ -387,6 +2269,18 @@ impl RequestRouter {
 
         // 记录目标 URL
         details.target_url = Some(config.server_url.clone());
+        if let Some(trace) = &trace {
+            trace.set_config(&config.name, &config.server_url);
+        }
+
+        // 测试辅助：注入人为延迟，用于验证高延迟切换
+        if let Some(delay) = latency_injection::delay_for(config_id) {
+            log::warn!("Injecting artificial latency of {}ms for config {}", delay.as_millis(), config_id);
+            tokio::time::sleep(delay).await;
+            if let Some(trace) = &trace {
+                trace.phase("artificial_latency", delay);
+            }
+        }
 
         log::info!(
             "Forwarding request to config: {} ({})",
@@ -402,25 +2296,13 @@ impl RequestRouter {
 
         // Strip /session/{session_id} prefix if present
         // This allows session-based routing while keeping the actual API path clean
-        let client_path_and_query = if raw_path_and_query.starts_with("/session/") {
-            // Find the end of session_id (next '/' or end of path)
-            if let Some(rest) = raw_path_and_query.strip_prefix("/session/") {
-                // Skip session_id to find the actual path
-                if let Some(slash_pos) = rest.find('/') {
-                    &rest[slash_pos..]
-                } else {
-                    // Only session prefix, no further path
-                    "/"
-                }
-            } else {
-                raw_path_and_query
-            }
-        } else {
-            raw_path_and_query
-        };
+        let client_path_and_query = strip_session_prefix(raw_path_and_query);
 
         log::debug!("Client request path: {} (original: {})", client_path_and_query, raw_path_and_query);
 
+        // 取出 x-ccproxy-model 请求头 (不转发给后端)
+        let model_override = take_model_override(req.headers_mut());
+
         // Log original Authorization header for debugging session routing
         let original_auth = req.headers()
             .get("authorization")
@@ -447,384 +2329,201 @@ impl RequestRouter {
             routing_ctx.provider_type,
             routing_ctx.request_conversion,
             routing_ctx.response_conversion
-        );
-
-        // 3. Parse target address first (needed for Host header)
-        // 4. Parse target address and path from server_url
-        // Extract host, port, and path prefix from the full URL
-        let url_without_protocol = config
-            .server_url
-            .strip_prefix("https://")
-            .or_else(|| config.server_url.strip_prefix("http://"))
-            .unwrap_or(&config.server_url);
-
-        // Extract host, port, and path prefix
-        let parts: Vec<&str> = url_without_protocol.splitn(2, '/').collect();
-        let host_and_port = parts[0];
-        let backend_path_prefix = if parts.len() > 1 {
-            format!("/{}", parts[1])
-        } else {
-            String::new()
-        };
-
-        // Determine target address with port
-        let target_addr = if host_and_port.contains(':') {
-            // Port is explicitly specified in URL (e.g., "api.example.com:8443")
-            host_and_port.to_string()
-        } else {
-            // Use standard port based on protocol
-            let default_port = if config.server_url.starts_with("https://") {
-                443
-            } else {
-                80
-            };
-            format!("{}:{}", host_and_port, default_port)
-        };
-
-        // Build complete target path by combining backend prefix with client path
-        let target_path = if !backend_path_prefix.is_empty() {
-            format!("{}{}", backend_path_prefix, client_path_and_query)
-        } else {
-            client_path_and_query.to_string()
-        };
-
-        log::debug!("Target address: {}, Target path: {}", target_addr, target_path);
-
-        // 修改请求头：设置正确的Host头和API密钥
-        let headers = req.headers_mut();
-
-        // 1. 设置Host头为后端主机名（88Code等服务会检查Host头来验证请求来源）
-        // 提取主机名（不含端口）
-        let backend_host = host_and_port.split(':').next().unwrap_or(host_and_port);
-        headers.insert("host", backend_host.parse().map_err(|_| {
-            AppError::ServiceError {
-                message: "Failed to parse backend host".to_string(),
-            }
-        })?);
-
-        // 2. 替换 Authorization 头为后端服务的 API 密钥（使用 Bearer 格式）
-        // 注意：不删除，而是替换，因为后端服务需要 Authorization 头来认证
-        let auth_value = format!("Bearer {}", api_key);
-        headers.insert("authorization", auth_value.parse().map_err(|_| {
-            AppError::ServiceError {
-                message: "Failed to parse authorization header".to_string(),
-            }
-        })?);
-
-        log::info!("已修改请求头 - Host: {}, Authorization: Bearer xxx...", backend_host);
-
-        // 5. Check if HTTPS is required
-        let is_https = config.server_url.starts_with("https://");
-
-        // 6. Connect to target server with timeout
-        let tcp_stream = timeout(
-            Duration::from_secs(REQUEST_TIMEOUT_SECS),
-            TcpStream::connect(&target_addr),
-        )
-        .await
-        .map_err(|_| {
-            log::error!("Connection timeout to target server: {}", target_addr);
-            AppError::ServiceError {
-                message: "Connection timeout".to_string(),
-            }
-        })?
-        .map_err(|e| {
-            log::error!("Failed to connect to target server ({}): {}", target_addr, e);
-            AppError::ServiceError {
-                message: format!("Connection failed: {}", e),
-            }
-        })?;
-
-        // 7. Wrap stream based on protocol
-        let stream = if is_https {
-            // Extract hostname for TLS SNI
-            let hostname = url_without_protocol
-                .split('/')
-                .next()
-                .unwrap_or(url_without_protocol)
-                .split(':')
-                .next()
-                .unwrap_or(url_without_protocol);
-
-            log::debug!("Performing TLS handshake for HTTPS connection to {}", hostname);
-
-            // Create TLS connector with explicit crypto provider
-            let mut root_store = rustls::RootCertStore::empty();
-            root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        );
 
-            // Explicitly use ring crypto provider to avoid runtime panic
-            let tls_config = rustls::ClientConfig::builder_with_provider(
-                    rustls::crypto::ring::default_provider().into()
-                )
-                .with_safe_default_protocol_versions()
-                .expect("Failed to configure TLS protocol versions")
-                .with_root_certificates(root_store)
-                .with_no_client_auth();
+        // 3. Parse target address first (needed for Host header)
+        // 4. Parse target address and path from server_url
+        // Extract host, port, and path prefix from the full URL
+        let parsed_url = parse_server_url(&config.server_url);
+        let target_addr = parsed_url.target_addr.clone();
 
-            let connector = TlsConnector::from(Arc::new(tls_config));
+        // Build complete target path by combining backend prefix with client path
+        let target_path = parsed_url.target_path(client_path_and_query);
 
-            // Perform TLS handshake
-            let server_name = ServerName::try_from(hostname.to_string())
-                .map_err(|e| AppError::ServiceError {
-                    message: format!("Invalid hostname for TLS: {}", e),
-                })?;
+        log::debug!("Target address: {}, Target path: {}", target_addr, target_path);
 
-            let tls_stream = connector
-                .connect(server_name, tcp_stream)
-                .await
-                .map_err(|e| {
-                    log::error!("TLS handshake failed: {}", e);
-                    AppError::ServiceError {
-                        message: format!("TLS handshake failed: {}", e),
-                    }
-                })?;
+        // 修改请求头：设置正确的Host头和API密钥
+        let backend_host = parsed_url.host.as_str();
+        rewrite_backend_auth_headers(req.headers_mut(), backend_host, &api_key, config.api_key_mode)?;
 
-            MaybeHttpsStream::Https(tls_stream)
+        if config.api_key_mode == ApiKeyMode::Client {
+            log::info!("已修改请求头 - Host: {}, 透传客户端认证头", backend_host);
         } else {
-            // Plain HTTP connection
-            MaybeHttpsStream::Http(tcp_stream)
-        };
+            log::info!("已修改请求头 - Host: {}, Authorization: Bearer xxx...", backend_host);
+        }
 
-        let io = TokioIo::new(stream);
+        // 按配置覆盖 User-Agent（未配置时透传客户端 UA），日志中的值经过脱敏
+        if apply_user_agent_override(req.headers_mut(), config.user_agent.as_deref()) {
+            log::info!(
+                "已覆盖 User-Agent - config {}: {}",
+                config.id,
+                redact_text(config.user_agent.as_deref().unwrap_or_default(), std::slice::from_ref(&api_key))
+            );
+        }
 
-        // 8. Create HTTP/1.1 connection
-        let (mut sender, conn) = hyper::client::conn::http1::handshake(io)
-            .await
-            .map_err(|e| {
-                log::error!("HTTP handshake failed: {}", e);
-                AppError::ServiceError {
-                    message: format!("HTTP handshake failed: {}", e),
-                }
-            })?;
+        // 3. 按分组设置转发客户端真实 IP（默认关闭，部分后端会特殊处理这些头）
+        let forward_client_ip = self.db_pool.with_connection(|conn| {
+            use crate::services::config_manager::ConfigManager;
+            ConfigManager::get_group_by_id(conn, group_id)
+                .map(|g| g.forward_client_ip)
+        }).unwrap_or(false);
+        if forward_client_ip {
+            apply_client_ip_headers(req.headers_mut(), client_addr.ip());
+            log::debug!("已转发客户端 IP: {}", client_addr.ip());
+        }
 
-        // 9. Spawn connection handler task
-        tokio::spawn(async move {
-            if let Err(e) = conn.await {
-                log::error!("Connection error: {}", e);
-            }
-        });
+        // 6. Resolve connect / request timeouts
+        let (connect_timeout, request_timeout) = self.resolve_timeouts(&config).await;
+
+        // 7-9. Connect to target server (TCP + TLS + HTTP/1.1 handshake)
+        let mut sender = Self::connect_backend(&parsed_url, &target_addr, connect_timeout, &trace).await?;
 
         // 10. Modify request URI to target path
         // We need to create a new request with the modified URI
         let (mut parts, body) = req.into_parts();
+        let request_content_type = header_content_type(&parts.headers).map(str::to_string);
 
-        // Build new URI with target path
-        let new_uri = target_path.parse::<hyper::Uri>()
+        // Build new URI with target path (plus the config's fixed query parameters)
+        let extra_query = config.extra_query.as_deref().unwrap_or("");
+        let new_uri = merge_query(&target_path, extra_query).parse::<hyper::Uri>()
             .map_err(|e| AppError::ServiceError {
                 message: format!("Failed to parse target URI: {}", e),
             })?;
 
         parts.uri = new_uri;
 
-        // 10.1 Handle API conversion based on provider type
-        let body = if parts.method == hyper::Method::POST || parts.method == hyper::Method::PUT {
-            // Collect request body
-            let body_bytes = body.collect().await
-                .map_err(|e| AppError::ServiceError {
-                    message: format!("Failed to read request body: {}", e),
-                })?
-                .to_bytes();
-
-            // 记录请求体大小
-            details.request_body_size = body_bytes.len() as u64;
-
-            // 记录完整请求体
-            let body_str = String::from_utf8_lossy(&body_bytes);
-            details.request_body = Some(body_str.to_string());
-
-            // 尝试从请求体提取模型名称
-            let mut source_model: Option<String> = None;
-            if let Ok(json) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
-                if let Some(model) = json.get("model").and_then(|m| m.as_str()) {
-                    source_model = Some(model.to_string());
-                    details.model = Some(model.to_string());
-                }
+        // 分组级别的请求体/响应体转换规则（原生 Claude 透传时不生效）
+        let group = self.db_pool.with_connection(|conn| {
+            use crate::services::config_manager::ConfigManager;
+            ConfigManager::get_group_by_id(conn, group_id)
+        }).ok();
+        let native_passthrough = is_native_passthrough(group.as_ref(), &config);
+        let body_transform = group
+            .filter(|_| !native_passthrough)
+            .and_then(|g| g.body_transform_spec());
+        if native_passthrough {
+            if let Some(model) = &model_override {
+                log::warn!(
+                    "Group is native Claude only, ignoring {} override: {}",
+                    MODEL_OVERRIDE_HEADER,
+                    model
+                );
             }
+        }
 
-            // 查询模型映射（如果需要转换）
-            let mapped_model: Option<String> = if routing_ctx.request_conversion != ConversionDirection::NoConversion {
-                if let Some(ref src_model) = source_model {
-                    let direction_str = routing_ctx.request_conversion.to_string();
-                    let db_pool = self.db_pool.clone();
-                    db_pool.with_connection(|conn| {
-                        Ok(ModelMappingService::lookup_target_model(conn, src_model, &direction_str))
-                    }).unwrap_or(None)
-                } else {
-                    None
-                }
+        // 10.1 无需修改请求体时直接流式透传，只在确实需要转换/过滤时缓冲
+        let has_request_transform = body_transform
+            .as_ref()
+            .map_or(false, |spec| !spec.request.is_empty());
+        let mut streamed_capture = None;
+        // 客户端请求的原始模型，用于转换响应时还原模型名称
+        let mut requested_model: Option<String> = None;
+        // 压缩前的请求体（未压缩时为 None），后端以 415 拒绝压缩请求体时用于重发
+        let mut uncompressed_body: Option<Vec<u8>> = None;
+        // 客户端原始请求体，用于后端未报告 usage 时估算输入 token
+        let mut usage_request: Option<Bytes> = None;
+
+        let default_model = self.default_request_model();
+
+        // 10.2 Handle API conversion based on provider type
+        let body = if (parts.method == hyper::Method::POST || parts.method == hyper::Method::PUT)
+            && (native_passthrough
+                || default_model.is_none()
+                    && model_override.is_none()
+                    && !request_body_needs_buffering(
+                        routing_ctx.request_conversion,
+                        has_request_transform,
+                        &config,
+                        &parts.headers,
+                    ))
+        {
+            if native_passthrough {
+                log::info!("Group is native Claude only, forwarding request body byte-for-byte");
             } else {
-                None
-            };
-
-            // 记录映射后的模型名称
-            if mapped_model.is_some() {
-                details.mapped_model = mapped_model.clone();
-                log::info!(
-                    "Model mapping applied: {} -> {}",
-                    source_model.as_deref().unwrap_or("unknown"),
-                    mapped_model.as_deref().unwrap_or("unknown")
-                );
+                log::info!("No request transformation needed, streaming request body as-is");
             }
+            let size_hint = http_body::Body::size_hint(&body);
+            set_streamed_body_framing(
+                &mut parts.headers,
+                http_body::Body::is_end_stream(&body),
+                size_hint.exact(),
+            );
 
-            // Check conversion direction and perform conversion if needed
-            let processed_bytes = match routing_ctx.request_conversion {
-                ConversionDirection::NoConversion => {
-                    // 无需转换 - 过滤不支持的字段后直接转发
-                    log::info!("No request conversion needed, forwarding as-is");
-                    match serde_json::from_slice::<serde_json::Value>(&body_bytes) {
-                        Ok(mut json) => {
-                            // Remove unsupported fields
-                            if let Some(obj) = json.as_object_mut() {
-                                let removed_fields: Vec<String> = obj.keys()
-                                    .filter(|k| k.as_str() == "context_management")
-                                    .cloned()
-                                    .collect();
-
-                                for field in &removed_fields {
-                                    obj.remove(field);
-                                    log::debug!("Filtered unsupported field from request: {}", field);
-                                }
-                            }
-                            serde_json::to_vec(&json)
-                                .map_err(|e| AppError::ServiceError {
-                                    message: format!("Failed to serialize filtered request: {}", e),
-                                })?
-                        }
-                        Err(_) => {
-                            log::debug!("Request body is not JSON, forwarding as-is");
-                            body_bytes.to_vec()
-                        }
-                    }
-                },
-                ConversionDirection::ClaudeToOpenAI => {
-                    log::info!("Converting Claude request to OpenAI format");
-                    let claude_req: ClaudeRequest = serde_json::from_slice(&body_bytes)
-                        .map_err(|e| AppError::ConversionError {
-                            message: format!("Failed to parse Claude request: {}", e),
-                        })?;
-
-                    let mut openai_req = crate::converters::openai_claude::convert_claude_request_to_openai(&claude_req);
-
-                    // 应用模型映射
-                    if let Some(ref target_model) = mapped_model {
-                        openai_req.model = target_model.clone();
-                    }
-
-                    // Update target path to OpenAI API endpoint
-                    let openai_path = "/v1/chat/completions";
-                    let openai_uri = openai_path.parse::<hyper::Uri>()
-                        .map_err(|e| AppError::ServiceError {
-                            message: format!("Failed to parse OpenAI URI: {}", e),
-                        })?;
-                    parts.uri = openai_uri;
-
-                    log::info!("Updated request URI to OpenAI endpoint: {}", openai_path);
-
-                    serde_json::to_vec(&openai_req)
-                        .map_err(|e| AppError::ConversionError {
-                            message: format!("Failed to serialize OpenAI request: {}", e),
-                        })?
-                },
-                ConversionDirection::OpenAIToClaude => {
-                    log::info!("Converting OpenAI request to Claude format");
-                    let openai_req: OpenAIRequest = serde_json::from_slice(&body_bytes)
-                        .map_err(|e| AppError::ConversionError {
-                            message: format!("Failed to parse OpenAI request: {}", e),
-                        })?;
-
-                    let mut claude_req = crate::converters::openai_claude::convert_openai_request_to_claude(&openai_req);
-
-                    // 应用模型映射
-                    if let Some(ref target_model) = mapped_model {
-                        claude_req.model = target_model.clone();
-                    }
-
-                    // Update target path to Claude API endpoint
-                    let claude_path = "/v1/messages";
-                    let claude_uri = claude_path.parse::<hyper::Uri>()
-                        .map_err(|e| AppError::ServiceError {
-                            message: format!("Failed to parse Claude URI: {}", e),
-                        })?;
-                    parts.uri = claude_uri;
-
-                    log::info!("Updated request URI to Claude endpoint: {}", claude_path);
-
-                    serde_json::to_vec(&claude_req)
-                        .map_err(|e| AppError::ConversionError {
-                            message: format!("Failed to serialize Claude request: {}", e),
-                        })?
-                },
-                ConversionDirection::ClaudeToGemini => {
-                    log::info!("Converting Claude request to Gemini format");
-                    let claude_req: ClaudeRequest = serde_json::from_slice(&body_bytes)
-                        .map_err(|e| AppError::ConversionError {
-                            message: format!("Failed to parse Claude request: {}", e),
-                        })?;
-
-                    // 优先使用映射后的模型名，否则使用配置的默认模型
-                    let gemini_model = mapped_model
-                        .as_deref()
-                        .or_else(|| config.default_model.as_ref().filter(|m| !m.is_empty()).map(|m| m.as_str()))
-                        .unwrap_or("gemini-pro");
-
-                    let (gemini_req, gemini_path) = convert_claude_request_to_gemini(&claude_req, gemini_model)?;
-
-                    let gemini_uri = gemini_path.parse::<hyper::Uri>()
-                        .map_err(|e| AppError::ServiceError {
-                            message: format!("Failed to parse Gemini URI: {}", e),
-                        })?;
-                    parts.uri = gemini_uri;
+            let capture = Arc::new(std::sync::Mutex::new(StreamedBodyCapture::default()));
+            streamed_capture = Some(capture.clone());
+            TeeRequestBody { inner: body, capture, trace: trace.clone() }.boxed()
+        } else if parts.method == hyper::Method::POST || parts.method == hyper::Method::PUT {
+            // Collect request body (受缓冲上限保护，避免超大多模态请求占用大量内存)
+            let body_bytes =
+                collect_request_body(body, &parts.headers, MAX_BUFFERED_REQUEST_BODY_BYTES).await?;
 
-                    log::info!("Updated request URI to Gemini endpoint: {}", gemini_path);
+            // 记录请求体大小
+            details.request_body_size = body_bytes.len() as u64;
 
-                    serde_json::to_vec(&gemini_req)
-                        .map_err(|e| AppError::ConversionError {
-                            message: format!("Failed to serialize Gemini request: {}", e),
-                        })?
-                },
-                ConversionDirection::OpenAIToGemini => {
-                    log::info!("Converting OpenAI request to Gemini format");
-                    // 先将 OpenAI 转为 Claude，再转为 Gemini
-                    let openai_req: OpenAIRequest = serde_json::from_slice(&body_bytes)
-                        .map_err(|e| AppError::ConversionError {
-                            message: format!("Failed to parse OpenAI request: {}", e),
-                        })?;
+            // 记录完整请求体
+            details.request_body = Some(body_for_log(&body_bytes, request_content_type.as_deref()));
+            usage_request = Some(body_bytes.clone());
 
-                    let claude_req = crate::converters::openai_claude::convert_openai_request_to_claude(&openai_req);
+            let transformed = self.transform_request_body(
+                routing_ctx.request_conversion,
+                &config,
+                &body_bytes,
+                body_transform.as_ref(),
+                default_model.as_deref(),
+                model_override.as_deref(),
+            )?;
 
-                    // 优先使用映射后的模型名，否则使用配置的默认模型
-                    let gemini_model = mapped_model
-                        .as_deref()
-                        .or_else(|| config.default_model.as_ref().filter(|m| !m.is_empty()).map(|m| m.as_str()))
-                        .unwrap_or("gemini-pro");
+            details.model = transformed.source_model.clone();
+            requested_model = transformed.source_model;
 
-                    let (gemini_req, gemini_path) = convert_claude_request_to_gemini(&claude_req, gemini_model)?;
+            // 记录映射后的模型名称
+            if transformed.mapped_model.is_some() {
+                details.mapped_model = transformed.mapped_model;
+            }
 
-                    let gemini_uri = gemini_path.parse::<hyper::Uri>()
-                        .map_err(|e| AppError::ServiceError {
-                            message: format!("Failed to parse Gemini URI: {}", e),
-                        })?;
-                    parts.uri = gemini_uri;
+            // 格式转换后使用目标格式的 API 端点
+            if let Some(path) = transformed.target_path {
+                parts.uri = merge_query(&path, extra_query).parse::<hyper::Uri>()
+                    .map_err(|e| AppError::ServiceError {
+                        message: format!("Failed to parse target URI: {}", e),
+                    })?;
+            }
 
-                    log::info!("Updated request URI to Gemini endpoint: {}", gemini_path);
+            let mut processed_bytes = transformed.body;
+            if let Some(trace) = &trace {
+                trace.append_request_body(&processed_bytes);
+            }
 
-                    serde_json::to_vec(&gemini_req)
-                        .map_err(|e| AppError::ConversionError {
-                            message: format!("Failed to serialize Gemini request: {}", e),
-                        })?
-                },
-                ConversionDirection::GeminiToClaude | ConversionDirection::GeminiToOpenAI => {
-                    // Gemini 客户端通常不会发送请求到代理，暂时不支持
-                    log::warn!("Gemini-originated requests are not yet supported, forwarding as-is");
-                    body_bytes.to_vec()
+            // 按配置压缩大请求体 (跳过已知拒绝压缩请求体的后端)
+            let content_encoding = parts
+                .headers
+                .get(hyper::header::CONTENT_ENCODING)
+                .and_then(|v| v.to_str().ok());
+            if request_compression::should_compress(
+                config.request_compression,
+                backend_host,
+                processed_bytes.len(),
+                content_encoding,
+            ) {
+                match request_compression::gzip(&processed_bytes) {
+                    Ok(compressed) => {
+                        log::info!(
+                            "Compressed request body with gzip: {} -> {} bytes",
+                            processed_bytes.len(),
+                            compressed.len()
+                        );
+                        uncompressed_body = Some(std::mem::replace(&mut processed_bytes, compressed));
+                        parts.headers.insert(
+                            hyper::header::CONTENT_ENCODING,
+                            hyper::header::HeaderValue::from_static("gzip"),
+                        );
+                    }
+                    Err(e) => log::warn!("Failed to gzip request body, sending uncompressed: {}", e),
                 }
-            };
+            }
 
-            // Update Content-Length header
-            parts.headers.insert(
-                hyper::header::CONTENT_LENGTH,
-                processed_bytes.len().to_string().parse().unwrap()
-            );
+            // Update Content-Length header (body is buffered, chunked framing no longer applies)
+            set_buffered_body_framing(&mut parts.headers, processed_bytes.len());
 
             use http_body_util::Full;
             Full::new(Bytes::from(processed_bytes))
@@ -832,10 +2531,47 @@ impl RequestRouter {
                 .boxed()
         } else {
             // For GET/DELETE, just forward the body as-is
+            let size_hint = http_body::Body::size_hint(&body);
+            set_streamed_body_framing(
+                &mut parts.headers,
+                http_body::Body::is_end_stream(&body),
+                size_hint.exact(),
+            );
             body.boxed()
         };
 
+        let stripped_betas = filter_anthropic_beta_headers(&mut parts.headers, &config.beta_filter());
+        if !stripped_betas.is_empty() {
+            log::info!(
+                "Stripped anthropic-beta flags for config {} ({}): {}",
+                config.id,
+                config.name,
+                stripped_betas.join(",")
+            );
+        }
+
+        // 压缩的请求体被后端以 415 拒绝时，用未压缩的请求体重发一次
+        let sent_compressed = uncompressed_body.is_some();
+        let uncompressed_retry = uncompressed_body.map(|uncompressed| {
+            let mut headers = parts.headers.clone();
+            headers.remove(hyper::header::CONTENT_ENCODING);
+            set_buffered_body_framing(&mut headers, uncompressed.len());
+            let mut retry = Request::new(
+                http_body_util::Full::new(Bytes::from(uncompressed))
+                    .map_err(|e| match e {})
+                    .boxed(),
+            );
+            *retry.method_mut() = parts.method.clone();
+            *retry.uri_mut() = parts.uri.clone();
+            *retry.version_mut() = parts.version;
+            *retry.headers_mut() = headers;
+            retry
+        });
+
         let req = Request::from_parts(parts, body);
+        if let Some(trace) = &trace {
+            trace.set_request(req.method(), req.uri(), req.headers());
+        }
 
         log::debug!("Modified request URI to: {}", req.uri());
         log::info!("发送给后端的请求头 Final request headers: {:?}", req.headers());
@@ -844,14 +2580,11 @@ impl RequestRouter {
         log::info!("Sending HTTP request to backend...");
         let send_start = std::time::Instant::now();
 
-        let response = timeout(
-            Duration::from_secs(REQUEST_TIMEOUT_SECS),
-            sender.send_request(req),
-        )
+        let response = timeout(request_timeout, sender.send_request(req))
         .await
         .map_err(|_| {
             log::error!("Request timeout after {}ms (timeout: {}s)",
-                send_start.elapsed().as_millis(), REQUEST_TIMEOUT_SECS);
+                send_start.elapsed().as_millis(), request_timeout.as_secs());
             AppError::ServiceError {
                 message: "Request timeout".to_string(),
             }
@@ -863,8 +2596,58 @@ impl RequestRouter {
             }
         })?;
 
+        // 透传的请求体：记录已发送的大小、前缀与模型名称
+        if let Some(capture) = streamed_capture {
+            let capture = capture.lock().unwrap_or_else(|e| e.into_inner());
+            details.request_body_size = capture.total;
+            details.request_body = Some(capture.logged_body(request_content_type.as_deref()));
+            details.model = capture.model();
+            // 只有完整捕获的请求体才能用于估算
+            if capture.total == capture.prefix.len() as u64 {
+                usage_request = Some(Bytes::copy_from_slice(&capture.prefix));
+            }
+        }
+
+        if config.request_compression != RequestCompression::Off {
+            request_compression::observe_response(
+                backend_host,
+                response.status(),
+                response.headers(),
+                sent_compressed,
+            );
+        }
+
+        let response = match uncompressed_retry {
+            Some(retry) if response.status() == StatusCode::UNSUPPORTED_MEDIA_TYPE => {
+                log::warn!("Backend {} rejected gzip request body (415), resending uncompressed", backend_host);
+
+                // 读完 415 响应以便复用连接，后端已关闭连接时重新连接
+                let _ = response.into_body().collect().await;
+                if sender.ready().await.is_err() {
+                    sender = Self::connect_backend(&parsed_url, &target_addr, connect_timeout, &trace).await?;
+                }
+                if let Some(trace) = &trace {
+                    trace.set_request(retry.method(), retry.uri(), retry.headers());
+                }
+
+                timeout(request_timeout, sender.send_request(retry))
+                    .await
+                    .map_err(|_| AppError::ServiceError {
+                        message: "Request timeout".to_string(),
+                    })?
+                    .map_err(|e| AppError::ServiceError {
+                        message: format!("Request failed: {}", e),
+                    })?
+            }
+            _ => response,
+        };
+
         // 立即计算并记录延迟（首字节响应时间）
         let latency_ms = send_start.elapsed().as_millis() as i32;
+        if let Some(trace) = &trace {
+            trace.phase("ttfb", send_start.elapsed());
+            trace.set_response(response.status(), response.headers());
+        }
         log::info!(
             "Received response: status={}, headers={:?}, latency={}ms",
             response.status(),
@@ -911,16 +2694,15 @@ impl RequestRouter {
                 })?
                 .to_bytes();
 
+            if let Some(trace) = &trace {
+                trace.append_response_body(&body_bytes);
+            }
             let body_text = String::from_utf8_lossy(&body_bytes);
             log::error!("Error response body: {}", body_text);
 
             // 记录错误响应体
             details.response_body_size = body_bytes.len() as u64;
-            details.response_body = Some(if body_text.len() > 8192 {
-                format!("{}...(truncated)", &body_text[..8192])
-            } else {
-                body_text.to_string()
-            });
+            details.response_body = Some(body_for_log(&body_bytes, header_content_type(&headers)));
 
             // Check for critical errors that should trigger auto-switch
             let lower_text = body_text.to_lowercase();
@@ -993,13 +2775,30 @@ impl RequestRouter {
 
                 // 为流式响应创建通道
                 if details.is_streaming {
+                    // 分组级别的 SSE 保活事件过滤与重连提示
+                    let (filter_keepalive, sse_retry_ms) = self.db_pool.with_connection(|conn| {
+                        use crate::services::config_manager::ConfigManager;
+                        ConfigManager::get_group_by_id(conn, group_id)
+                            .map(|g| (g.filter_sse_keepalive, g.sse_retry_hint()))
+                    }).unwrap_or((false, None));
+
+                    let stream_limits = self.resolve_stream_limits().await;
                     let (tx, rx) = mpsc::channel::<StreamCompletionData>(1);
-                    let wrapped_body = StreamingBodyWrapper::new(body, tx);
+                    let wrapped_body =
+                        StreamingBodyWrapper::new(body, tx, filter_keepalive, sse_retry_ms, log_bodies)
+                            .with_usage_request(usage_request)
+                            .with_limits(stream_limits)
+                            .with_trace(trace)
+                            .with_content_type(header_content_type(&headers));
                     let boxed_body = wrapped_body.boxed();
 
                     let mut resp = Response::new(boxed_body);
                     *resp.status_mut() = status;
                     *resp.headers_mut() = headers;
+                    if sse_retry_ms.is_some() {
+                        // 插入重连提示后上游的 Content-Length 不再准确
+                        resp.headers_mut().remove(hyper::header::CONTENT_LENGTH);
+                    }
 
                     log::info!("Streaming response with capture wrapper (status: {})", status);
                     return Ok((resp, details, Some(rx)));
@@ -1013,14 +2812,34 @@ impl RequestRouter {
                         message: format!("Failed to read response body: {}", e),
                     })?
                     .to_bytes();
+                if let Some(trace) = &trace {
+                    trace.append_response_body(&body_bytes);
+                }
+
+                // 部分服务商返回 HTTP 200 但响应体为错误，计为失败以便触发切换
+                if let Some(error_msg) = check_response_body_error(&String::from_utf8_lossy(&body_bytes)) {
+                    log::warn!("Soft error in {} response from config {}: {}", status, config_id, error_msg);
+                    return Err(AppError::ServiceError {
+                        message: format!("Soft error in {} response: {}", status, error_msg),
+                    });
+                }
+
+                // 应用分组配置的响应体转换规则
+                let mut headers = headers;
+                let body_bytes = match body_transform.as_ref().filter(|spec| !spec.response.is_empty()) {
+                    Some(spec) => match transform_json_body(&body_bytes, |json| spec.apply_response(json)) {
+                        Some(transformed) => {
+                            set_buffered_body_framing(&mut headers, transformed.len());
+                            Bytes::from(transformed)
+                        }
+                        None => body_bytes,
+                    },
+                    None => body_bytes,
+                };
 
+                details.token_usage = TokenUsage::from_response(usage_request.as_deref(), &body_bytes);
                 details.response_body_size = body_bytes.len() as u64;
-                let response_str = String::from_utf8_lossy(&body_bytes);
-                details.response_body = Some(if response_str.len() > 8192 {
-                    format!("{}...(truncated)", &response_str[..8192])
-                } else {
-                    response_str.to_string()
-                });
+                details.response_body = Some(body_for_log(&body_bytes, header_content_type(&headers)));
 
                 use http_body_util::Full;
                 let boxed_body = Full::new(body_bytes).map_err(|e| match e {}).boxed();
@@ -1043,15 +2862,29 @@ impl RequestRouter {
 
                 if is_streaming {
                     log::info!("Converting OpenAI streaming response to Claude SSE format");
-                    let claude_model = "claude-sonnet-4-5-20250929".to_string();
+                    let claude_model = requested_model
+                        .clone()
+                        .unwrap_or_else(|| MODEL_MAPPER.default_claude_model().to_string());
                     let body = response.into_body();
 
+                    // 转换后的流与透传流一样受流式响应上限约束，并在结束后上报完成数据
                     let converted_stream = Self::convert_openai_stream(body, claude_model);
                     use futures_util::TryStreamExt;
                     let mapped_stream = converted_stream.map_err(|e: Infallible| match e {});
 
-                    let stream_body = StreamBody::new(mapped_stream);
-                    let boxed_body = BodyExt::boxed(stream_body);
+                    let stream_limits = self.resolve_stream_limits().await;
+                    let (tx, rx) = mpsc::channel::<StreamCompletionData>(1);
+                    let wrapped_body = StreamingBodyWrapper::new(
+                        StreamBody::new(mapped_stream),
+                        tx,
+                        false,
+                        self.group_sse_retry_ms(group_id),
+                        log_bodies,
+                    )
+                    .with_usage_request(usage_request)
+                    .with_limits(stream_limits)
+                    .with_trace(trace);
+                    let boxed_body = wrapped_body.boxed();
 
                     let mut resp = Response::new(boxed_body);
                     *resp.status_mut() = status;
@@ -1062,7 +2895,7 @@ impl RequestRouter {
                     );
 
                     log::info!("Streaming OpenAI→Claude response conversion started");
-                    Ok((resp, details, None))
+                    Ok((resp, details, Some(rx)))
                 } else {
                     let body_bytes = response.into_body()
                         .collect()
@@ -1071,6 +2904,9 @@ impl RequestRouter {
                             message: format!("Failed to read OpenAI response body: {}", e),
                         })?
                         .to_bytes();
+                    if let Some(trace) = &trace {
+                        trace.append_response_body(&body_bytes);
+                    }
 
                     let openai_resp: crate::converters::openai_types::OpenAIResponse =
                         serde_json::from_slice(&body_bytes)
@@ -1078,10 +2914,13 @@ impl RequestRouter {
                             message: format!("Failed to parse OpenAI response: {}", e),
                         })?;
 
-                    let claude_model = "claude-sonnet-4-5-20250929";
+                    // 还原为客户端请求的模型，未知时按 MODEL_MAPPER 反向映射
+                    let claude_model = requested_model
+                        .clone()
+                        .unwrap_or_else(|| MODEL_MAPPER.openai_to_claude(&openai_resp.model));
                     let claude_resp = crate::converters::openai_claude::convert_openai_response_to_claude(
                         &openai_resp,
-                        claude_model
+                        &claude_model
                     );
 
                     let claude_bytes = serde_json::to_vec(&claude_resp)
@@ -1091,13 +2930,10 @@ impl RequestRouter {
 
                     log::info!("Successfully converted OpenAI response to Claude format");
 
+                    details.token_usage = TokenUsage::from_response(usage_request.as_deref(), &body_bytes);
                     details.response_body_size = claude_bytes.len() as u64;
                     let response_str = String::from_utf8_lossy(&claude_bytes);
-                    details.response_body = Some(if response_str.len() > 8192 {
-                        format!("{}...(truncated)", &response_str[..8192])
-                    } else {
-                        response_str.to_string()
-                    });
+                    details.response_body = Some(truncate_body(&response_str, FULL_BODY_LIMIT));
 
                     let content_length = claude_bytes.len();
                     use http_body_util::Full;
@@ -1128,7 +2964,17 @@ impl RequestRouter {
                     let body = response.into_body();
 
                     // 使用 Claude → OpenAI 流转换器
-                    let converted_stream = Self::convert_claude_to_openai_stream(body);
+                    let openai_model = requested_model
+                        .clone()
+                        .unwrap_or_else(|| MODEL_MAPPER.default_openai_model().to_string());
+                    // 在转换前对上游 Claude 流施加上限，超限时补发的收尾事件会被转换为 OpenAI 结束块
+                    let stream_limits = self.resolve_stream_limits().await;
+                    let (tx, rx) = mpsc::channel::<StreamCompletionData>(1);
+                    let body = StreamingBodyWrapper::new(body, tx, false, None, log_bodies)
+                        .with_usage_request(usage_request)
+                        .with_limits(stream_limits)
+                        .with_trace(trace);
+                    let converted_stream = Self::convert_claude_to_openai_stream(body, openai_model);
                     use futures_util::TryStreamExt;
                     let mapped_stream = converted_stream.map_err(|e: Infallible| match e {});
 
@@ -1144,7 +2990,7 @@ impl RequestRouter {
                     );
 
                     log::info!("Streaming Claude→OpenAI response conversion started");
-                    Ok((resp, details, None))
+                    Ok((resp, details, Some(rx)))
                 } else {
                     let body_bytes = response.into_body()
                         .collect()
@@ -1153,6 +2999,9 @@ impl RequestRouter {
                             message: format!("Failed to read Claude response body: {}", e),
                         })?
                         .to_bytes();
+                    if let Some(trace) = &trace {
+                        trace.append_response_body(&body_bytes);
+                    }
 
                     let claude_resp: crate::converters::claude_types::ClaudeResponse =
                         serde_json::from_slice(&body_bytes)
@@ -1160,7 +3009,11 @@ impl RequestRouter {
                             message: format!("Failed to parse Claude response: {}", e),
                         })?;
 
-                    let openai_resp = crate::converters::openai_claude::convert_claude_response_to_openai(&claude_resp, "gpt-4");
+                    // 还原为客户端请求的模型，未知时按 MODEL_MAPPER 映射
+                    let openai_model = requested_model
+                        .clone()
+                        .unwrap_or_else(|| MODEL_MAPPER.claude_to_openai(&claude_resp.model));
+                    let openai_resp = crate::converters::openai_claude::convert_claude_response_to_openai(&claude_resp, &openai_model);
 
                     let openai_bytes = serde_json::to_vec(&openai_resp)
                         .map_err(|e| AppError::ConversionError {
@@ -1169,13 +3022,10 @@ impl RequestRouter {
 
                     log::info!("Successfully converted Claude response to OpenAI format");
 
+                    details.token_usage = TokenUsage::from_response(usage_request.as_deref(), &body_bytes);
                     details.response_body_size = openai_bytes.len() as u64;
                     let response_str = String::from_utf8_lossy(&openai_bytes);
-                    details.response_body = Some(if response_str.len() > 8192 {
-                        format!("{}...(truncated)", &response_str[..8192])
-                    } else {
-                        response_str.to_string()
-                    });
+                    details.response_body = Some(truncate_body(&response_str, FULL_BODY_LIMIT));
 
                     let content_length = openai_bytes.len();
                     use http_body_util::Full;
@@ -1206,12 +3056,24 @@ impl RequestRouter {
                     let claude_model = "claude-sonnet-4-5-20250929".to_string();
                     let body = response.into_body();
 
+                    // 转换后的流与透传流一样受流式响应上限约束，并在结束后上报完成数据
                     let converted_stream = Self::convert_gemini_stream(body, claude_model);
                     use futures_util::TryStreamExt;
                     let mapped_stream = converted_stream.map_err(|e: Infallible| match e {});
 
-                    let stream_body = StreamBody::new(mapped_stream);
-                    let boxed_body = BodyExt::boxed(stream_body);
+                    let stream_limits = self.resolve_stream_limits().await;
+                    let (tx, rx) = mpsc::channel::<StreamCompletionData>(1);
+                    let wrapped_body = StreamingBodyWrapper::new(
+                        StreamBody::new(mapped_stream),
+                        tx,
+                        false,
+                        self.group_sse_retry_ms(group_id),
+                        log_bodies,
+                    )
+                    .with_usage_request(usage_request)
+                    .with_limits(stream_limits)
+                    .with_trace(trace);
+                    let boxed_body = wrapped_body.boxed();
 
                     let mut resp = Response::new(boxed_body);
                     *resp.status_mut() = status;
@@ -1222,7 +3084,7 @@ impl RequestRouter {
                     );
 
                     log::info!("Streaming Gemini→Claude response conversion started");
-                    Ok((resp, details, None))
+                    Ok((resp, details, Some(rx)))
                 } else {
                     let body_bytes = response.into_body()
                         .collect()
@@ -1231,6 +3093,9 @@ impl RequestRouter {
                             message: format!("Failed to read Gemini response body: {}", e),
                         })?
                         .to_bytes();
+                    if let Some(trace) = &trace {
+                        trace.append_response_body(&body_bytes);
+                    }
 
                     let gemini_resp: GeminiResponse = serde_json::from_slice(&body_bytes)
                         .map_err(|e| AppError::ConversionError {
@@ -1247,13 +3112,10 @@ impl RequestRouter {
 
                     log::info!("Successfully converted Gemini response to Claude format");
 
+                    details.token_usage = TokenUsage::from_response(usage_request.as_deref(), &body_bytes);
                     details.response_body_size = claude_bytes.len() as u64;
                     let response_str = String::from_utf8_lossy(&claude_bytes);
-                    details.response_body = Some(if response_str.len() > 8192 {
-                        format!("{}...(truncated)", &response_str[..8192])
-                    } else {
-                        response_str.to_string()
-                    });
+                    details.response_body = Some(truncate_body(&response_str, FULL_BODY_LIMIT));
 
                     let content_length = claude_bytes.len();
                     use http_body_util::Full;
@@ -1301,6 +3163,9 @@ impl RequestRouter {
                             message: format!("Failed to read Gemini response body: {}", e),
                         })?
                         .to_bytes();
+                    if let Some(trace) = &trace {
+                        trace.append_response_body(&body_bytes);
+                    }
 
                     // 先转 Gemini → Claude
                     let gemini_resp: GeminiResponse = serde_json::from_slice(&body_bytes)
@@ -1321,13 +3186,10 @@ impl RequestRouter {
 
                     log::info!("Successfully converted Gemini response to OpenAI format (via Claude)");
 
+                    details.token_usage = TokenUsage::from_response(usage_request.as_deref(), &body_bytes);
                     details.response_body_size = openai_bytes.len() as u64;
                     let response_str = String::from_utf8_lossy(&openai_bytes);
-                    details.response_body = Some(if response_str.len() > 8192 {
-                        format!("{}...(truncated)", &response_str[..8192])
-                    } else {
-                        response_str.to_string()
-                    });
+                    details.response_body = Some(truncate_body(&response_str, FULL_BODY_LIMIT));
 
                     let content_length = openai_bytes.len();
                     use http_body_util::Full;
@@ -1417,135 +3279,33 @@ impl RequestRouter {
                                                 (body, claude_model, buffer, is_first_chunk),
                                             ));
                                         }
-                                        Err(e) => {
-                                            log::error!("Failed to convert Gemini stream chunk: {}", e);
-                                            // Return error as SSE event instead of failing the stream
-                                            let error_msg = format!("event: error\ndata: {{\"error\": \"{}\"}}\n\n", e);
-                                            let frame = Frame::data(Bytes::from(error_msg));
-                                            return Some((
-                                                Ok(frame),
-                                                (body, claude_model, buffer, is_first_chunk),
-                                            ));
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        Some(Err(e)) => {
-                            log::error!("Error reading Gemini stream: {}", e);
-                            // Return error as SSE event and end stream
-                            let error_msg = format!("event: error\ndata: {{\"error\": \"Stream error: {}\"}}\n\n", e);
-                            let frame = Frame::data(Bytes::from(error_msg));
-                            return Some((
-                                Ok(frame),
-                                (body, claude_model, Vec::new(), false),
-                            ));
-                        }
-                        None => {
-                            // Stream ended
-                            log::info!("Gemini stream conversion completed");
-                            return None;
-                        }
-                    }
-                }
-            },
-        ))
-    }
-
-    /// Convert OpenAI streaming response to Claude SSE format
-    ///
-    /// OpenAI streams SSE events with "data: {json}\n\n", we convert them to Claude SSE format
-    /// This stream never fails - all errors are converted to SSE error events
-    fn convert_openai_stream(
-        body: Incoming,
-        claude_model: String,
-    ) -> Pin<Box<dyn Stream<Item = Result<Frame<Bytes>, Infallible>> + Send + Sync>> {
-        Box::pin(futures_util::stream::unfold(
-            (body, claude_model, Vec::new(), true, String::new()),
-            |(mut body, claude_model, mut buffer, mut is_first_chunk, mut chunk_id)| async move {
-                loop {
-                    // Try to get the next frame from the body
-                    match body.frame().await {
-                        Some(Ok(frame)) => {
-                            // Only process data frames
-                            if let Some(data) = frame.data_ref() {
-                                buffer.extend_from_slice(data);
-
-                                // Process complete SSE lines (delimited by \n\n)
-                                while let Some(double_newline_pos) = buffer.windows(2).position(|w| w == b"\n\n") {
-                                    // Extract the SSE event
-                                    let event_bytes = buffer.drain(..double_newline_pos + 2).collect::<Vec<_>>();
-                                    let event_str = String::from_utf8_lossy(&event_bytes).trim().to_string();
-
-                                    // Skip empty events
-                                    if event_str.is_empty() {
-                                        continue;
-                                    }
-
-                                    // Check for [DONE] marker
-                                    if event_str.contains("[DONE]") {
-                                        log::info!("OpenAI stream completed with [DONE] marker");
-                                        return None;
-                                    }
-
-                                    // Parse "data: {json}" format
-                                    if let Some(json_str) = event_str.strip_prefix("data: ") {
-                                        // Parse OpenAI stream chunk
-                                        match serde_json::from_str::<crate::converters::openai_types::OpenAIStreamChunk>(json_str) {
-                                            Ok(openai_chunk) => {
-                                                // Generate chunk ID on first chunk
-                                                if chunk_id.is_empty() {
-                                                    chunk_id = openai_chunk.id.clone();
-                                                }
-
-                                                // Convert to Claude SSE events
-                                                let claude_events = crate::converters::openai_claude::convert_claude_stream_to_openai(
-                                                    &crate::converters::claude_types::ClaudeStreamEvent::ContentBlockDelta {
-                                                        index: 0,
-                                                        delta: crate::converters::claude_types::ClaudeContentDelta {
-                                                            delta_type: "text_delta".to_string(),
-                                                            text: openai_chunk.choices.first()
-                                                                .and_then(|c| c.delta.content.clone()),
-                                                        },
-                                                    },
-                                                    &claude_model,
-                                                    &chunk_id,
-                                                );
-
-                                                is_first_chunk = false;
-
-                                                // Return Claude SSE events if any
-                                                if let Some(event_str) = claude_events {
-                                                    let frame = Frame::data(Bytes::from(event_str));
-                                                    return Some((
-                                                        Ok(frame),
-                                                        (body, claude_model, buffer, is_first_chunk, chunk_id),
-                                                    ));
-                                                }
-                                            }
-                                            Err(e) => {
-                                                log::error!("Failed to parse OpenAI stream chunk: {}", e);
-                                                // Continue to next chunk instead of failing
-                                                continue;
-                                            }
+                                        Err(e) => {
+                                            log::error!("Failed to convert Gemini stream chunk: {}", e);
+                                            // Return error as SSE event instead of failing the stream
+                                            let error_msg = format!("event: error\ndata: {{\"error\": \"{}\"}}\n\n", e);
+                                            let frame = Frame::data(Bytes::from(error_msg));
+                                            return Some((
+                                                Ok(frame),
+                                                (body, claude_model, buffer, is_first_chunk),
+                                            ));
                                         }
                                     }
                                 }
                             }
                         }
                         Some(Err(e)) => {
-                            log::error!("Error reading OpenAI stream: {}", e);
+                            log::error!("Error reading Gemini stream: {}", e);
                             // Return error as SSE event and end stream
                             let error_msg = format!("event: error\ndata: {{\"error\": \"Stream error: {}\"}}\n\n", e);
                             let frame = Frame::data(Bytes::from(error_msg));
                             return Some((
                                 Ok(frame),
-                                (body, claude_model, Vec::new(), false, chunk_id),
+                                (body, claude_model, Vec::new(), false),
                             ));
                         }
                         None => {
                             // Stream ended
-                            log::info!("OpenAI stream conversion completed");
+                            log::info!("Gemini stream conversion completed");
                             return None;
                         }
                     }
@@ -1554,16 +3314,60 @@ impl RequestRouter {
         ))
     }
 
+    /// Convert OpenAI streaming response to Claude SSE format
+    ///
+    /// OpenAI streams `data: {json}` lines terminated by `data: [DONE]`; partial lines are
+    /// buffered across frames. This stream never fails - read errors become a Claude
+    /// `error` event and the message is always closed with `message_stop`.
+    fn convert_openai_stream(
+        body: Incoming,
+        claude_model: String,
+    ) -> Pin<Box<dyn Stream<Item = Result<Frame<Bytes>, Infallible>> + Send + Sync>> {
+        Box::pin(futures_util::stream::unfold(
+            (body, OpenAIStreamConversion::new(claude_model), false),
+            |(mut body, mut conversion, mut finished)| async move {
+                while !finished {
+                    let events = match body.frame().await {
+                        Some(Ok(frame)) => match frame.data_ref() {
+                            Some(data) => conversion.push(data),
+                            None => continue,
+                        },
+                        Some(Err(e)) => {
+                            log::error!("Error reading OpenAI stream: {}", e);
+                            finished = true;
+                            conversion.fail(&format!("Stream error: {}", e))
+                        }
+                        None => {
+                            log::info!("OpenAI stream conversion completed");
+                            finished = true;
+                            conversion.finish()
+                        }
+                    };
+
+                    finished |= conversion.is_done();
+                    if !events.is_empty() {
+                        return Some((Ok(Frame::data(Bytes::from(events))), (body, conversion, finished)));
+                    }
+                }
+                None
+            },
+        ))
+    }
+
     /// Convert Claude streaming response to OpenAI SSE format
     ///
     /// Claude streams SSE events, we convert them to OpenAI SSE format
     /// This stream never fails - all errors are converted to SSE error events
-    fn convert_claude_to_openai_stream(
-        body: Incoming,
-    ) -> Pin<Box<dyn Stream<Item = Result<Frame<Bytes>, Infallible>> + Send + Sync>> {
+    fn convert_claude_to_openai_stream<B>(
+        body: B,
+        openai_model: String,
+    ) -> Pin<Box<dyn Stream<Item = Result<Frame<Bytes>, Infallible>> + Send + Sync>>
+    where
+        B: http_body::Body<Data = Bytes, Error = hyper::Error> + Send + Sync + Unpin + 'static,
+    {
         Box::pin(futures_util::stream::unfold(
-            (body, Vec::new(), String::new(), 0u32),
-            |(mut body, mut buffer, mut request_id, mut chunk_index)| async move {
+            (body, Vec::new(), String::new(), 0u32, openai_model),
+            |(mut body, mut buffer, mut request_id, mut chunk_index, openai_model)| async move {
                 loop {
                     match body.frame().await {
                         Some(Ok(frame)) => {
@@ -1607,7 +3411,7 @@ impl RequestRouter {
                                                     .duration_since(std::time::UNIX_EPOCH)
                                                     .unwrap_or_default()
                                                     .as_secs() as i64,
-                                                model: "gpt-4".to_string(),
+                                                model: openai_model.clone(),
                                                 choices: vec![crate::converters::openai_types::OpenAIStreamChoice {
                                                     index: 0,
                                                     delta: crate::converters::openai_types::OpenAIDelta {
@@ -1638,7 +3442,7 @@ impl RequestRouter {
                                                             .duration_since(std::time::UNIX_EPOCH)
                                                             .unwrap_or_default()
                                                             .as_secs() as i64,
-                                                        model: "gpt-4".to_string(),
+                                                        model: openai_model.clone(),
                                                         choices: vec![crate::converters::openai_types::OpenAIStreamChoice {
                                                             index: 0,
                                                             delta: crate::converters::openai_types::OpenAIDelta {
@@ -1669,7 +3473,7 @@ impl RequestRouter {
                                                     .duration_since(std::time::UNIX_EPOCH)
                                                     .unwrap_or_default()
                                                     .as_secs() as i64,
-                                                model: "gpt-4".to_string(),
+                                                model: openai_model.clone(),
                                                 choices: vec![crate::converters::openai_types::OpenAIStreamChoice {
                                                     index: 0,
                                                     delta: crate::converters::openai_types::OpenAIDelta {
@@ -1691,7 +3495,7 @@ impl RequestRouter {
                                         let frame = Frame::data(Bytes::from(sse));
                                         return Some((
                                             Ok(frame),
-                                            (body, buffer, request_id, chunk_index),
+                                            (body, buffer, request_id, chunk_index, openai_model),
                                         ));
                                     }
                                 }
@@ -1703,7 +3507,7 @@ impl RequestRouter {
                             let frame = Frame::data(Bytes::from(error_msg));
                             return Some((
                                 Ok(frame),
-                                (body, Vec::new(), request_id, chunk_index),
+                                (body, Vec::new(), request_id, chunk_index, openai_model),
                             ));
                         }
                         None => {
@@ -1766,3 +3570,1157 @@ mod tests {
         assert_eq!(resp.body(), "Proxy service unavailable");
     }
 }
+
+#[cfg(test)]
+mod body_framing_tests {
+    use super::*;
+    use hyper::header::{HeaderValue, CONTENT_LENGTH, TRANSFER_ENCODING};
+    use hyper::HeaderMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn passthrough_config() -> ApiConfig {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": "c",
+            "api_key": "k",
+            "server_url": "https://api.example.com",
+            "server_port": 443,
+            "sort_order": 0,
+            "is_available": true,
+            "auto_balance_check": false,
+            "created_at": "",
+            "updated_at": ""
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cross_provider_model_round_trip() {
+        let config = passthrough_config();
+
+        // Claude 客户端 → OpenAI 后端，响应按 MODEL_MAPPER 反向映射
+        for (claude, openai) in [
+            ("claude-sonnet-4-5-20250929", "gpt-4o"),
+            ("claude-3-5-haiku-20241022", "gpt-4o-mini"),
+            ("claude-3-opus-20240229", "gpt-4-turbo"),
+            ("claude-3-haiku-20240307", "gpt-3.5-turbo"),
+        ] {
+            let mapped = resolve_cross_provider_model(ConversionDirection::ClaudeToOpenAI, claude, &config).unwrap();
+            assert_eq!(mapped, openai);
+            assert_eq!(MODEL_MAPPER.openai_to_claude(&mapped), claude);
+        }
+
+        // OpenAI 客户端 → Claude 后端
+        let mapped = resolve_cross_provider_model(ConversionDirection::OpenAIToClaude, "gpt-4o-2024-08-06", &config).unwrap();
+        assert_eq!(mapped, "claude-sonnet-4-5-20250929");
+        assert_eq!(MODEL_MAPPER.claude_to_openai(&mapped), "gpt-4o");
+
+        assert!(resolve_cross_provider_model(ConversionDirection::NoConversion, "gpt-4o", &config).is_none());
+    }
+
+    #[test]
+    fn test_config_model_override_takes_precedence() {
+        let mut config = passthrough_config();
+        config.haiku_model = Some("my-fast-model".to_string());
+        config.default_model = Some("my-default-model".to_string());
+
+        assert_eq!(
+            resolve_cross_provider_model(ConversionDirection::ClaudeToOpenAI, "claude-3-5-haiku-20241022", &config).as_deref(),
+            Some("my-fast-model")
+        );
+        assert_eq!(
+            resolve_cross_provider_model(ConversionDirection::ClaudeToOpenAI, "claude-3-opus-20240229", &config).as_deref(),
+            Some("my-default-model")
+        );
+    }
+
+    #[test]
+    fn test_request_body_needs_buffering() {
+        let config = passthrough_config();
+        let headers = HeaderMap::new();
+        assert!(!request_body_needs_buffering(ConversionDirection::NoConversion, false, &config, &headers));
+        assert!(request_body_needs_buffering(ConversionDirection::ClaudeToOpenAI, false, &config, &headers));
+        assert!(request_body_needs_buffering(ConversionDirection::NoConversion, true, &config, &headers));
+
+        let mut beta = HeaderMap::new();
+        beta.insert("anthropic-beta", HeaderValue::from_static("claude-code-20250219,context-management-2025-06-27"));
+        assert!(request_body_needs_buffering(ConversionDirection::NoConversion, false, &config, &beta));
+
+        let mut overridden = passthrough_config();
+        overridden.opus_model = Some("claude-opus-x".to_string());
+        assert!(request_body_needs_buffering(ConversionDirection::NoConversion, false, &overridden, &headers));
+
+        // 不移除任何字段时即使启用 context-management beta 也直接透传
+        let mut keep_all = passthrough_config();
+        keep_all.strip_request_fields = Some("[]".to_string());
+        assert!(!request_body_needs_buffering(ConversionDirection::NoConversion, false, &keep_all, &beta));
+
+        let mut custom = passthrough_config();
+        custom.strip_request_fields = Some(r#"["metadata"]"#.to_string());
+        assert!(request_body_needs_buffering(ConversionDirection::NoConversion, false, &custom, &headers));
+
+        let mut anonymized = passthrough_config();
+        anonymized.metadata_user_id_policy = MetadataUserIdPolicy::Anonymize;
+        assert!(request_body_needs_buffering(ConversionDirection::NoConversion, false, &anonymized, &headers));
+
+        // 开启压缩时，只有声明长度低于压缩阈值的请求体直接透传
+        let mut compressed = passthrough_config();
+        compressed.request_compression = RequestCompression::Force;
+        assert!(request_body_needs_buffering(ConversionDirection::NoConversion, false, &compressed, &headers));
+        let mut small = HeaderMap::new();
+        small.insert(hyper::header::CONTENT_LENGTH, HeaderValue::from(128));
+        assert!(!request_body_needs_buffering(ConversionDirection::NoConversion, false, &compressed, &small));
+    }
+
+    #[tokio::test]
+    async fn test_tee_request_body_records_size_and_model() {
+        let payload = format!(r#"{{"model":"claude-sonnet","data":"{}"}}"#, "x".repeat(STREAMED_BODY_CAPTURE_LIMIT));
+        let capture = Arc::new(std::sync::Mutex::new(StreamedBodyCapture::default()));
+        let body = TeeRequestBody {
+            inner: http_body_util::Full::new(Bytes::from(payload.clone())),
+            capture: capture.clone(),
+            trace: None,
+        };
+
+        let forwarded = body.collect().await.unwrap().to_bytes();
+        assert_eq!(forwarded.len(), payload.len());
+
+        let capture = capture.lock().unwrap();
+        assert_eq!(capture.total, payload.len() as u64);
+        assert_eq!(capture.prefix.len(), STREAMED_BODY_CAPTURE_LIMIT);
+        assert_eq!(capture.model().as_deref(), Some("claude-sonnet"));
+        assert!(capture.logged_body(None).ends_with(&format!("(streamed, {} bytes total)", payload.len())));
+    }
+
+    fn chunked_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+        headers
+    }
+
+    #[test]
+    fn test_buffered_body_replaces_chunked_framing() {
+        let mut headers = chunked_headers();
+        set_buffered_body_framing(&mut headers, 42);
+
+        assert!(!headers.contains_key(TRANSFER_ENCODING));
+        assert_eq!(headers.get(CONTENT_LENGTH).unwrap(), "42");
+    }
+
+    #[test]
+    fn test_streamed_body_keeps_chunked_framing() {
+        let mut headers = chunked_headers();
+        headers.insert(CONTENT_LENGTH, HeaderValue::from_static("10"));
+        set_streamed_body_framing(&mut headers, false, None);
+
+        assert_eq!(headers.get(TRANSFER_ENCODING).unwrap(), "chunked");
+        assert!(!headers.contains_key(CONTENT_LENGTH));
+    }
+
+    #[test]
+    fn test_client_ip_appends_to_existing_forwarded_for() {
+        let mut headers = HeaderMap::new();
+        headers.append("x-forwarded-for", HeaderValue::from_static("203.0.113.7"));
+        headers.append("x-forwarded-for", HeaderValue::from_static("10.0.0.2"));
+        apply_client_ip_headers(&mut headers, "192.168.1.20".parse().unwrap());
+
+        assert_eq!(headers.get_all("x-forwarded-for").iter().count(), 1);
+        assert_eq!(headers.get("x-forwarded-for").unwrap(), "203.0.113.7, 10.0.0.2, 192.168.1.20");
+        assert_eq!(headers.get("x-real-ip").unwrap(), "192.168.1.20");
+
+        let mut headers = HeaderMap::new();
+        apply_client_ip_headers(&mut headers, "::ffff:127.0.0.1".parse().unwrap());
+        assert_eq!(headers.get("x-forwarded-for").unwrap(), "127.0.0.1");
+        assert_eq!(headers.get("x-real-ip").unwrap(), "127.0.0.1");
+    }
+
+    #[test]
+    fn test_user_agent_override() {
+        let mut headers = HeaderMap::new();
+        headers.insert("user-agent", HeaderValue::from_static("claude-cli/1.0.0 (external, cli)"));
+
+        assert!(!apply_user_agent_override(&mut headers, None));
+        assert!(!apply_user_agent_override(&mut headers, Some("")));
+        assert_eq!(headers.get("user-agent").unwrap(), "claude-cli/1.0.0 (external, cli)");
+
+        assert!(apply_user_agent_override(&mut headers, Some("curl/8.4.0")));
+        assert_eq!(headers.get_all("user-agent").iter().count(), 1);
+        assert_eq!(headers.get("user-agent").unwrap(), "curl/8.4.0");
+
+        // 客户端未发送 User-Agent 时同样设置
+        let mut headers = HeaderMap::new();
+        assert!(apply_user_agent_override(&mut headers, Some("curl/8.4.0")));
+        assert_eq!(headers.get("user-agent").unwrap(), "curl/8.4.0");
+    }
+
+    #[test]
+    fn test_filter_anthropic_beta_headers() {
+        use crate::models::api_config::AnthropicBetaMode;
+
+        let mut headers = HeaderMap::new();
+        headers.append("anthropic-beta", HeaderValue::from_static("context-management-2025-06-27, interleaved-thinking-2025-05-14"));
+        headers.append("anthropic-beta", HeaderValue::from_static("fine-grained-tool-streaming-2025-05-14"));
+        let denylist = AnthropicBetaFilter {
+            mode: AnthropicBetaMode::Denylist,
+            flags: vec!["Context-Management-2025-06-27".to_string()],
+        };
+        let stripped = filter_anthropic_beta_headers(&mut headers, &denylist);
+
+        assert_eq!(stripped, vec!["context-management-2025-06-27".to_string()]);
+        assert_eq!(headers.get_all("anthropic-beta").iter().count(), 1);
+        assert_eq!(
+            headers.get("anthropic-beta").unwrap(),
+            "interleaved-thinking-2025-05-14,fine-grained-tool-streaming-2025-05-14"
+        );
+
+        let empty_allowlist = AnthropicBetaFilter {
+            mode: AnthropicBetaMode::Allowlist,
+            flags: Vec::new(),
+        };
+        assert_eq!(filter_anthropic_beta_headers(&mut headers, &empty_allowlist).len(), 2);
+        assert!(!headers.contains_key("anthropic-beta"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("anthropic-beta", HeaderValue::from_static("a, b"));
+        assert!(filter_anthropic_beta_headers(&mut headers, &AnthropicBetaFilter::default()).is_empty());
+        assert_eq!(headers.get("anthropic-beta").unwrap(), "a, b");
+    }
+
+    #[test]
+    fn test_streamed_body_without_framing_headers() {
+        let mut headers = HeaderMap::new();
+        set_streamed_body_framing(&mut headers, false, Some(5));
+        assert_eq!(headers.get(CONTENT_LENGTH).unwrap(), "5");
+
+        let mut headers = HeaderMap::new();
+        set_streamed_body_framing(&mut headers, false, None);
+        assert_eq!(headers.get(TRANSFER_ENCODING).unwrap(), "chunked");
+
+        let mut headers = chunked_headers();
+        set_streamed_body_framing(&mut headers, true, Some(0));
+        assert!(headers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_collect_request_body_enforces_limit() {
+        let body = || http_body_util::Full::new(Bytes::from(vec![b'a'; 64]));
+
+        let collected = collect_request_body(body(), &HeaderMap::new(), 64).await.unwrap();
+        assert_eq!(collected.len(), 64);
+
+        // 未声明长度 (chunked) 时在读取过程中拒绝
+        let err = collect_request_body(body(), &chunked_headers(), 32).await.unwrap_err();
+        assert!(matches!(err, AppError::PayloadTooLarge { limit_bytes: 32 }));
+
+        // 声明的 Content-Length 超限时直接拒绝
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_LENGTH, HeaderValue::from_static("1048576"));
+        let err = collect_request_body(body(), &headers, 1024).await.unwrap_err();
+        assert!(matches!(err, AppError::PayloadTooLarge { limit_bytes: 1024 }));
+    }
+
+    /// 客户端以 chunked 发送 POST，缓冲后后端应收到带 Content-Length 的合法请求
+    #[tokio::test]
+    async fn test_chunked_post_is_forwarded_with_content_length() {
+        const PAYLOAD: &[u8] = b"{\"model\":\"x\"}";
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let backend = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buf = [0u8; 1024];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                received.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&received);
+                if let Some(end) = text.find("\r\n\r\n") {
+                    if received.len() >= end + 4 + PAYLOAD.len() {
+                        break;
+                    }
+                }
+            }
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&received).to_string()
+        });
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let (mut sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .unwrap();
+        tokio::spawn(conn);
+
+        let mut req = Request::post("/v1/messages")
+            .header("host", "localhost")
+            .header(TRANSFER_ENCODING, "chunked")
+            .body(http_body_util::Full::new(Bytes::from_static(PAYLOAD)))
+            .unwrap();
+        set_buffered_body_framing(req.headers_mut(), PAYLOAD.len());
+
+        let resp = sender.send_request(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let raw = backend.await.unwrap().to_ascii_lowercase();
+        assert!(raw.contains(&format!("content-length: {}\r\n", PAYLOAD.len())));
+        assert!(!raw.contains("transfer-encoding"));
+        assert!(raw.ends_with("{\"model\":\"x\"}"));
+    }
+
+    /// 读取一个完整的 HTTP/1.1 请求，返回 (请求头文本, 请求体)
+    async fn read_http_request(socket: &mut TcpStream) -> (String, Vec<u8>) {
+        let mut received = Vec::new();
+        let mut buf = [0u8; 16 * 1024];
+        loop {
+            let n = socket.read(&mut buf).await.unwrap();
+            assert!(n > 0, "connection closed before the request was complete");
+            received.extend_from_slice(&buf[..n]);
+            let Some(end) = received.windows(4).position(|w| w == b"\r\n\r\n") else {
+                continue;
+            };
+            let head = String::from_utf8_lossy(&received[..end]).to_ascii_lowercase();
+            let content_length = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .map_or(0, |v| v.trim().parse::<usize>().unwrap());
+            if received.len() >= end + 4 + content_length {
+                return (head, received[end + 4..end + 4 + content_length].to_vec());
+            }
+        }
+    }
+
+    /// 转换后的 Gemini 请求体长度与客户端原请求不同，后端收到的 Content-Length 必须与转换结果一致
+    #[tokio::test]
+    async fn test_converted_gemini_request_framing() {
+        let claude_body = serde_json::to_vec(&serde_json::json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "max_tokens": 256,
+            "messages": [{"role": "user", "content": "hello"}]
+        }))
+        .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let backend = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let request = read_http_request(&mut socket).await;
+            let body = br#"{"candidates":[{"content":{"parts":[{"text":"hi"}],"role":"model"},"finishReason":"STOP","index":0}]}"#;
+            socket
+                .write_all(format!("HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n", body.len()).as_bytes())
+                .await
+                .unwrap();
+            socket.write_all(body).await.unwrap();
+            request
+        });
+
+        let pool = Arc::new(DbPool::new(crate::db::test_db()));
+        pool.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO ApiConfig (id, name, api_key, server_url, provider_type) VALUES (1, 'c', 'k', ?1, 'gemini')",
+                [format!("http://{}", addr)],
+            )
+            .unwrap();
+            Ok(())
+        })
+        .unwrap();
+        let router = RequestRouter::new(pool);
+
+        // 客户端声明的是原始 Claude 请求体的长度
+        let req = Request::post("/v1/messages")
+            .header("content-type", "application/json")
+            .header(CONTENT_LENGTH, claude_body.len())
+            .body(http_body_util::Full::new(Bytes::from(claude_body.clone())).map_err(|e| match e {}))
+            .unwrap();
+        router
+            .forward_request(req, 1, 0, "127.0.0.1:1".parse().unwrap())
+            .await
+            .unwrap();
+
+        let (head, body) = backend.await.unwrap();
+        assert_ne!(body.len(), claude_body.len());
+        assert!(head.contains(&format!("content-length: {}\r\n", body.len())), "{}", head);
+        assert!(!head.contains("transfer-encoding"));
+        let gemini_req: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(gemini_req["contents"].is_array());
+    }
+
+    /// 仅在客户端启用 context-management beta 时缓冲并移除 context_management，否则原样流式透传
+    #[tokio::test]
+    async fn test_context_management_stripped_only_with_beta_header() {
+        const PAYLOAD: &str = r#"{"model":"claude-sonnet-4-5-20250929","max_tokens":16,"messages":[],"context_management":{"edits":[]}}"#;
+
+        async fn forward_once(beta: Option<&'static str>) -> Vec<u8> {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let backend = tokio::spawn(async move {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let (_, body) = read_http_request(&mut socket).await;
+                let resp = br#"{"type":"message","content":[]}"#;
+                socket
+                    .write_all(format!("HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n", resp.len()).as_bytes())
+                    .await
+                    .unwrap();
+                socket.write_all(resp).await.unwrap();
+                body
+            });
+
+            let pool = Arc::new(DbPool::new(crate::db::test_db()));
+            pool.with_connection(|conn| {
+                conn.execute(
+                    "INSERT INTO ApiConfig (id, name, api_key, server_url, default_model, haiku_model, sonnet_model, opus_model, small_fast_model)
+                     VALUES (1, 'c', 'k', ?1, NULL, NULL, NULL, NULL, NULL)",
+                    [format!("http://{}", addr)],
+                )
+                .unwrap();
+                Ok(())
+            })
+            .unwrap();
+            let router = RequestRouter::new(pool);
+
+            let mut builder = Request::post("/v1/messages")
+                .header("content-type", "application/json")
+                .header(CONTENT_LENGTH, PAYLOAD.len());
+            if let Some(beta) = beta {
+                builder = builder.header("anthropic-beta", beta);
+            }
+            let req = builder
+                .body(http_body_util::Full::new(Bytes::from_static(PAYLOAD.as_bytes())).map_err(|e| match e {}))
+                .unwrap();
+            let (resp, _, _) = router
+                .forward_request(req, 1, 0, "127.0.0.1:1".parse().unwrap())
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            backend.await.unwrap()
+        }
+
+        // 未启用 beta：请求体不缓冲，后端收到的字节与客户端发送的完全一致
+        let streamed = forward_once(None).await;
+        assert_eq!(streamed, PAYLOAD.as_bytes());
+
+        // 启用 beta：请求体被缓冲并移除 context_management
+        let buffered = forward_once(Some("context-management-2025-06-27")).await;
+        let json: serde_json::Value = serde_json::from_slice(&buffered).unwrap();
+        assert!(json.get("context_management").is_none());
+        assert_eq!(json["model"], "claude-sonnet-4-5-20250929");
+    }
+
+    /// 后端以 415 拒绝 gzip 请求体（并关闭连接）时，以未压缩的请求体重发一次
+    #[tokio::test]
+    async fn test_gzip_rejected_with_415_is_resent_uncompressed() {
+        let payload = format!(
+            r#"{{"model":"claude-sonnet-4-5-20250929","max_tokens":16,"messages":[{{"role":"user","content":"{}"}}]}}"#,
+            "a".repeat(request_compression::MIN_COMPRESS_BODY_BYTES)
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let backend = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let (first_head, _) = read_http_request(&mut socket).await;
+            socket
+                .write_all(b"HTTP/1.1 415 Unsupported Media Type\r\nconnection: close\r\ncontent-length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            drop(socket);
+
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let (second_head, second_body) = read_http_request(&mut socket).await;
+            let body = br#"{"type":"message","content":[]}"#;
+            socket
+                .write_all(format!("HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n", body.len()).as_bytes())
+                .await
+                .unwrap();
+            socket.write_all(body).await.unwrap();
+            (first_head, second_head, second_body)
+        });
+
+        // 使用独立的主机名，避免与其他测试共享记录的拒绝压缩后端
+        let pool = Arc::new(DbPool::new(crate::db::test_db()));
+        pool.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO ApiConfig (id, name, api_key, server_url, request_compression) VALUES (1, 'c', 'k', ?1, 'force')",
+                [format!("http://localhost:{}", addr.port())],
+            )
+            .unwrap();
+            Ok(())
+        })
+        .unwrap();
+        let router = RequestRouter::new(pool);
+
+        let req = Request::post("/v1/messages")
+            .header("content-type", "application/json")
+            .body(http_body_util::Full::new(Bytes::from(payload.clone())).map_err(|e| match e {}))
+            .unwrap();
+        let (resp, _, _) = router
+            .forward_request(req, 1, 0, "127.0.0.1:1".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let (first_head, second_head, second_body) = backend.await.unwrap();
+        assert!(first_head.contains("content-encoding: gzip"));
+        assert!(!second_head.contains("content-encoding"));
+        // 重发的是处理后的未压缩 JSON 请求体
+        let resent: serde_json::Value = serde_json::from_slice(&second_body).unwrap();
+        let original: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(resent["messages"], original["messages"]);
+        assert!(request_compression::is_known_rejecting("localhost"));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_wrapper_sends_retry_hint_first() {
+        const EVENTS: &[u8] = b"event: message_start\ndata: {\"type\":\"message_start\"}\n\n";
+        let (tx, mut rx) = mpsc::channel::<StreamCompletionData>(1);
+        let inner = http_body_util::Full::new(Bytes::from_static(EVENTS));
+        let mut body = StreamingBodyWrapper::new(inner, tx, false, Some(3000), true);
+
+        let first = body.frame().await.unwrap().unwrap().into_data().unwrap();
+        assert_eq!(&first[..], b"retry: 3000\n\n");
+        let second = body.frame().await.unwrap().unwrap().into_data().unwrap();
+        assert_eq!(&second[..], EVENTS);
+        assert!(body.frame().await.is_none());
+
+        // 重连提示不计入捕获数据
+        let completion = rx.recv().await.unwrap();
+        assert_eq!(completion.chunk_count, 1);
+        assert_eq!(completion.response_body_size, EVENTS.len() as u64);
+        assert!(completion.response_body.unwrap().starts_with("event: message_start"));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_wrapper_detects_terminal_soft_error() {
+        const EVENTS: &[u8] = b"event: message_start\ndata: {\"type\":\"message_start\"}\n\nevent: error\ndata: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n";
+        let (tx, mut rx) = mpsc::channel::<StreamCompletionData>(1);
+        let inner = http_body_util::Full::new(Bytes::from_static(EVENTS));
+        let mut body = StreamingBodyWrapper::new(inner, tx, false, None, true);
+        while body.frame().await.is_some() {}
+
+        let completion = rx.recv().await.unwrap();
+        assert_eq!(completion.soft_error, Some("overloaded_error: Overloaded".to_string()));
+    }
+
+    #[test]
+    fn test_body_for_log_uses_placeholder_for_binary() {
+        assert_eq!(body_for_log("héllo".as_bytes(), Some("text/plain")), "héllo");
+        // 截断切开的多字节字符仍按文本处理
+        assert_eq!(body_for_log(&"日志".as_bytes()[..4], None), "日\u{FFFD}");
+
+        let png: Vec<u8> = [&b"\x89PNG\r\n\x1a\n"[..], &[0xFFu8; 64][..]].concat();
+        assert_eq!(body_for_log(&png, Some("image/png")), "[binary: image/png, 72 bytes]");
+        assert_eq!(body_for_log(&png, None), "[binary: unknown, 72 bytes]");
+    }
+
+    #[tokio::test]
+    async fn test_streaming_wrapper_binary_placeholder() {
+        let (tx, mut rx) = mpsc::channel::<StreamCompletionData>(1);
+        let inner = http_body_util::Full::new(Bytes::from(vec![0xFEu8; 100]));
+        let mut body = StreamingBodyWrapper::new(inner, tx, false, None, true)
+            .with_content_type(Some("application/octet-stream"));
+        while body.frame().await.is_some() {}
+
+        let completion = rx.recv().await.unwrap();
+        assert_eq!(
+            completion.response_body.as_deref(),
+            Some("[binary: application/octet-stream, 100 bytes]")
+        );
+    }
+
+    /// 先发送给定事件、之后永不结束的上游流
+    fn never_ending_stream(
+        events: &'static [&'static [u8]],
+    ) -> StreamBody<impl Stream<Item = Result<Frame<Bytes>, Infallible>> + Unpin> {
+        StreamBody::new(futures_util::StreamExt::chain(
+            futures_util::stream::iter(events.iter().map(|e| Ok(Frame::data(Bytes::from_static(e))))),
+            futures_util::stream::pending(),
+        ))
+    }
+
+    const OPEN_BLOCK_EVENTS: &[&[u8]] = &[
+        b"event: message_start\ndata: {\"type\":\"message_start\"}\n\n",
+        b"event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":0}\n\n",
+        b"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0}\n\n",
+    ];
+
+    /// 快速后端 + 慢速客户端：只在下游拉取时读取上游，捕获缓冲区不随响应大小增长
+    #[tokio::test]
+    async fn test_streaming_wrapper_backpressure_with_slow_consumer() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        const CHUNKS: usize = 64;
+        const ERROR_EVENT: &[u8] = b"event: error\ndata: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n";
+        let chunk = Bytes::from(format!("event: content_block_delta\ndata: {}\n\n", "x".repeat(16 * 1024)));
+
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let counter = pulled.clone();
+        let events = (0..CHUNKS)
+            .map(move |_| chunk.clone())
+            .chain(std::iter::once(Bytes::from_static(ERROR_EVENT)));
+        let inner = StreamBody::new(futures_util::StreamExt::map(futures_util::stream::iter(events), move |data| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, Infallible>(Frame::data(data))
+        }));
+
+        let (tx, mut rx) = mpsc::channel::<StreamCompletionData>(1);
+        let mut body = StreamingBodyWrapper::new(inner, tx, false, None, true);
+
+        let mut received = 0u64;
+        for read in 1..=CHUNKS + 1 {
+            let frame = body.frame().await.unwrap().unwrap().into_data().unwrap();
+            received += frame.len() as u64;
+            // 上游与下游同步推进，没有被提前读入内存
+            assert_eq!(pulled.load(Ordering::SeqCst), read);
+            assert!(body.buffer.len() <= MAX_CAPTURED_STREAM_BYTES);
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        assert!(body.frame().await.is_none());
+
+        let completion = rx.recv().await.unwrap();
+        assert_eq!(completion.response_body_size, received);
+        assert!(received > MAX_CAPTURED_STREAM_BYTES as u64 * 3);
+        assert!(completion.response_body.unwrap().starts_with("event: content_block_delta"));
+        // 裁剪后仍能识别终止事件中的软错误
+        assert_eq!(completion.soft_error, Some("overloaded_error: Overloaded".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_wrapper_terminates_after_max_chunks() {
+        let (tx, mut rx) = mpsc::channel::<StreamCompletionData>(1);
+        let mut body = StreamingBodyWrapper::new(never_ending_stream(OPEN_BLOCK_EVENTS), tx, false, None, true)
+            .with_limits(StreamLimits::new(0, 2));
+
+        let mut frames = Vec::new();
+        while let Some(frame) = body.frame().await {
+            frames.push(frame.unwrap().into_data().unwrap());
+        }
+        assert_eq!(frames.len(), 3);
+        let tail = String::from_utf8_lossy(&frames[2]).to_string();
+        assert!(tail.starts_with("event: content_block_stop"), "{}", tail);
+        assert!(tail.ends_with("event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n"));
+
+        let completion = rx.recv().await.unwrap();
+        assert_eq!(completion.chunk_count, 2);
+        assert!(completion.integrity.unwrap().ended_with_message_stop);
+        assert_eq!(completion.soft_error, Some("Stream exceeded maximum of 2 chunks".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_wrapper_terminates_after_max_duration() {
+        let (tx, mut rx) = mpsc::channel::<StreamCompletionData>(1);
+        let limits = StreamLimits {
+            max_duration: Some(Duration::from_millis(50)),
+            max_chunks: None,
+        };
+        let mut body = StreamingBodyWrapper::new(never_ending_stream(OPEN_BLOCK_EVENTS), tx, false, None, false)
+            .with_limits(limits);
+
+        let drained = tokio::time::timeout(Duration::from_secs(5), async {
+            let mut last = Bytes::new();
+            while let Some(frame) = body.frame().await {
+                last = frame.unwrap().into_data().unwrap();
+            }
+            last
+        })
+        .await
+        .expect("stream should be terminated by the duration limit");
+        assert!(String::from_utf8_lossy(&drained).ends_with("event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n"));
+
+        let completion = rx.recv().await.unwrap();
+        assert_eq!(completion.chunk_count, 3);
+        assert!(completion.soft_error.unwrap().contains("maximum duration"));
+    }
+
+    /// 转换后的 Gemini 流同样受 chunk 上限约束，超限后以 message_stop 收尾并按软错误上报
+    #[tokio::test]
+    async fn test_converted_gemini_stream_respects_chunk_limit() {
+        const CHUNK: &str = "{\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"hi\"}],\"role\":\"model\"},\"index\":0}]}\n";
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = socket.read(&mut buf).await;
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n")
+                .await
+                .unwrap();
+            // 后端持续输出，直到代理断开连接
+            while socket
+                .write_all(format!("{:x}\r\n{}\r\n", CHUNK.len(), CHUNK).as_bytes())
+                .await
+                .is_ok()
+            {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        });
+
+        let pool = Arc::new(DbPool::new(crate::db::test_db()));
+        pool.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO ApiConfig (id, name, api_key, server_url, provider_type) VALUES (1, 'c', 'k', ?1, 'gemini')",
+                [format!("http://{}", addr)],
+            )
+            .unwrap();
+            Ok(())
+        })
+        .unwrap();
+        let proxy_config = Arc::new(tokio::sync::RwLock::new(crate::proxy::server::ProxyConfig {
+            active_config_id: Some(1),
+            max_stream_chunks: 2,
+            ..Default::default()
+        }));
+        let auto_switch = Arc::new(AutoSwitchService::for_listener(pool.clone()));
+        let router = RequestRouter::new_with_config(pool, proxy_config, auto_switch);
+
+        let req = Request::post("/v1/messages")
+            .header("content-type", "application/json")
+            .body(
+                http_body_util::Full::new(Bytes::from_static(
+                    br#"{"model":"claude-sonnet-4-5-20250929","max_tokens":16,"stream":true,"messages":[{"role":"user","content":"hi"}]}"#,
+                ))
+                .map_err(|e| match e {}),
+            )
+            .unwrap();
+        let (resp, _, rx) = router
+            .forward_request(req, 1, 0, "127.0.0.1:1".parse().unwrap())
+            .await
+            .unwrap();
+        let mut rx = rx.expect("converted stream should report completion");
+
+        let body = tokio::time::timeout(Duration::from_secs(5), resp.into_body().collect())
+            .await
+            .expect("stream should be terminated by the chunk limit")
+            .unwrap()
+            .to_bytes();
+        assert!(String::from_utf8_lossy(&body).ends_with("event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n"));
+
+        let completion = rx.recv().await.unwrap();
+        assert_eq!(completion.chunk_count, 2);
+        assert_eq!(completion.soft_error, Some("Stream exceeded maximum of 2 chunks".to_string()));
+    }
+
+    #[test]
+    fn test_terminal_stream_payload() {
+        let sse = "event: message_delta\r\ndata: {\"type\":\"message_delta\"}\r\n\r\nevent: message_stop\r\ndata: {\"type\":\"message_stop\"}\r\n\r\n";
+        assert_eq!(terminal_stream_payload(sse), r#"{"type":"message_stop"}"#);
+        assert_eq!(check_response_body_error(&terminal_stream_payload(sse)), None);
+
+        let plain = r#"{"error":{"message":"quota exceeded"}}"#;
+        assert_eq!(terminal_stream_payload(plain), plain);
+    }
+
+    #[tokio::test]
+    async fn test_session_pinned_failure_does_not_switch() {
+        let pool = Arc::new(DbPool::new(crate::db::test_db()));
+        pool.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO ConfigGroup (id, name, auto_switch_enabled) VALUES (1, 'g', 1);
+                 INSERT INTO ApiConfig (id, name, api_key, server_url, group_id, sort_order) VALUES
+                     (1, 'primary', 'k', 'https://a.example.com', 1, 0),
+                     (2, 'backup', 'k', 'https://b.example.com', 1, 1);",
+            )
+            .unwrap();
+            Ok(())
+        })
+        .unwrap();
+        let error_msg = crate::services::auto_switch::synthetic_error_message("authentication");
+
+        let run = |pinned: bool| {
+            let pool = pool.clone();
+            let error_msg = error_msg.clone();
+            async move {
+                let proxy_config = Arc::new(tokio::sync::RwLock::new(crate::proxy::server::ProxyConfig {
+                    active_config_id: Some(1),
+                    ..Default::default()
+                }));
+                let auto_switch = Arc::new(AutoSwitchService::for_listener(pool.clone()));
+                let router = RequestRouter::new_with_config(pool, proxy_config.clone(), auto_switch);
+                let router = if pinned { router.without_auto_switch() } else { router };
+                router.handle_failure_switch(1, 1, error_msg, None).await;
+                let active = proxy_config.read().await.active_config_id;
+                active
+            }
+        };
+
+        assert_eq!(run(true).await, Some(1));
+        assert_eq!(run(false).await, Some(2));
+    }
+
+    fn preview_router(provider_type: &str) -> RequestRouter {
+        let pool = DbPool::new(crate::db::test_db());
+        pool.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO ApiConfig (id, name, api_key, server_url, provider_type,
+                                        default_model, haiku_model, sonnet_model, opus_model, small_fast_model)
+                 VALUES (1, 'c', 'sk-secret-key-1234', 'https://api.example.com/proxy', ?1, NULL, NULL, NULL, NULL, NULL)",
+                [provider_type],
+            )
+            .unwrap();
+            Ok(())
+        })
+        .unwrap();
+        RequestRouter::new(Arc::new(pool))
+    }
+
+    fn preview_header<'a>(preview: &'a ForwardedRequestPreview, name: &str) -> Option<&'a str> {
+        preview.headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn test_preview_request_passthrough() {
+        let router = preview_router("claude");
+        let body = br#"{"model":"claude-sonnet-4-5-20250929","max_tokens":16,"messages":[]}"#;
+        let preview = router
+            .preview_request(1, "/session/abc/v1/messages?beta=true", HeaderMap::new(), body)
+            .unwrap();
+
+        assert_eq!(preview.uri, "/proxy/v1/messages?beta=true");
+        assert_eq!(preview.target_addr, "api.example.com:443");
+        assert!(preview.body_streamed);
+        assert_eq!(preview.body.as_bytes(), body);
+        assert_eq!(preview_header(&preview, "host"), Some("api.example.com"));
+        assert_eq!(preview_header(&preview, "authorization"), Some("Bearer ****1234"));
+        assert_eq!(preview_header(&preview, "content-length"), Some(body.len().to_string().as_str()));
+    }
+
+    #[test]
+    fn test_preview_request_native_claude_only_group() {
+        let router = preview_router("claude");
+        router
+            .db_pool
+            .with_connection(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO ConfigGroup (id, name, native_claude_only, body_transform)
+                         VALUES (1, 'g', 1, '{\"request\":[{\"op\":\"remove\",\"path\":\"/metadata\"}]}');
+                     UPDATE ApiConfig SET group_id = 1, sonnet_model = 'sonnet-x' WHERE id = 1;",
+                )
+                .unwrap();
+                Ok(())
+            })
+            .unwrap();
+
+        // 模型覆盖、字段过滤与分组转换规则均不生效，请求体按原始字节转发
+        let mut headers = HeaderMap::new();
+        headers.insert("anthropic-beta", HeaderValue::from_static("context-management-2025-06-27"));
+        let body = br#"{"model":"claude-sonnet-4-5-20250929", "context_management":{"edits":[]},"metadata":{"user_id":"u"}}"#;
+        let preview = router.preview_request(1, "/v1/messages", headers, body).unwrap();
+        assert!(preview.body_streamed);
+        assert_eq!(preview.body.as_bytes(), body);
+        assert_eq!(preview.mapped_model, None);
+        assert_eq!(preview_header(&preview, "authorization"), Some("Bearer ****1234"));
+
+        let effective = router.effective_config(1, None).unwrap();
+        let native = effective.values.iter().find(|v| v.key == "native_passthrough").unwrap();
+        assert_eq!((native.value.clone(), native.source), (serde_json::json!(true), EffectiveValueSource::Group));
+    }
+
+    #[test]
+    fn test_preview_request_keeps_client_key() {
+        let router = preview_router("claude");
+        router
+            .db_pool
+            .with_connection(|conn| {
+                conn.execute("UPDATE ApiConfig SET api_key_mode = 'client' WHERE id = 1", [])
+                    .unwrap();
+                Ok(())
+            })
+            .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "sk-ant-client-9876".parse().unwrap());
+        let body = br#"{"model":"claude-sonnet-4-5-20250929","max_tokens":16,"messages":[]}"#;
+        let preview = router.preview_request(1, "/v1/messages", headers, body).unwrap();
+
+        assert_eq!(preview_header(&preview, "host"), Some("api.example.com"));
+        assert_eq!(preview_header(&preview, "authorization"), None);
+        assert_eq!(preview_header(&preview, "x-api-key"), Some("****9876"));
+    }
+
+    #[test]
+    fn test_effective_config_marks_value_sources() {
+        let router = preview_router("claude");
+        router
+            .db_pool
+            .with_connection(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO ConfigGroup (id, name, body_transform)
+                         VALUES (1, 'g', '{\"request\":[{\"op\":\"remove\",\"path\":\"/metadata\"}]}');
+                     UPDATE ApiConfig SET group_id = 1, connect_timeout_secs = 12, sonnet_model = 'sonnet-x',
+                                          extra_query = 'key=secret' WHERE id = 1;",
+                )
+                .unwrap();
+                Ok(())
+            })
+            .unwrap();
+
+        let effective = router.effective_config(1, None).unwrap();
+        let value = |key: &str| {
+            let item = effective.values.iter().find(|v| v.key == key).unwrap();
+            (item.value.clone(), item.source)
+        };
+        assert_eq!(value("connect_timeout_secs"), (serde_json::json!(12), EffectiveValueSource::Config));
+        assert_eq!(
+            value("request_timeout_secs"),
+            (serde_json::json!(DEFAULT_REQUEST_TIMEOUT_SECS), EffectiveValueSource::Default)
+        );
+        assert_eq!(value("sonnet_model"), (serde_json::json!("sonnet-x"), EffectiveValueSource::Config));
+        assert_eq!(value("opus_model").1, EffectiveValueSource::Default);
+        assert_eq!(value("body_transform").1, EffectiveValueSource::Group);
+        assert!(!value("extra_query").0.to_string().contains("secret"));
+
+        let proxy_config = crate::proxy::server::ProxyConfig {
+            request_timeout_secs: 77,
+            ..Default::default()
+        };
+        let effective = router.effective_config(1, Some(&proxy_config)).unwrap();
+        let request_timeout = effective.values.iter().find(|v| v.key == "request_timeout_secs").unwrap();
+        assert_eq!(request_timeout.value, serde_json::json!(77));
+        assert_eq!(request_timeout.source, EffectiveValueSource::ProxySettings);
+    }
+
+    #[test]
+    fn test_preview_request_strips_configured_fields() {
+        let router = preview_router("claude");
+        let mut headers = HeaderMap::new();
+        headers.insert("anthropic-beta", HeaderValue::from_static("context-management-2025-06-27"));
+        let body = br#"{"model":"claude-sonnet-4-5-20250929","messages":[],"context_management":{"edits":[]},"metadata":{"user_id":"u"}}"#;
+
+        // 默认移除 context_management
+        let preview = router.preview_request(1, "/v1/messages", headers.clone(), body).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&preview.body).unwrap();
+        assert!(json.get("context_management").is_none());
+        assert!(json.get("metadata").is_some());
+
+        // 兼容的后端可以保留该字段，并改为移除其他字段
+        router
+            .db_pool
+            .with_connection(|conn| {
+                conn.execute("UPDATE ApiConfig SET strip_request_fields = '[\"metadata\"]' WHERE id = 1", [])
+                    .unwrap();
+                Ok(())
+            })
+            .unwrap();
+        let preview = router.preview_request(1, "/v1/messages", headers, body).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&preview.body).unwrap();
+        assert!(json.get("context_management").is_some());
+        assert!(json.get("metadata").is_none());
+    }
+
+    #[test]
+    fn test_preview_request_filters_anthropic_beta() {
+        let router = preview_router("claude");
+        router
+            .db_pool
+            .with_connection(|conn| {
+                conn.execute(
+                    "UPDATE ApiConfig SET anthropic_beta_filter = '{\"mode\":\"denylist\",\"flags\":[\"context-management-2025-06-27\"]}' WHERE id = 1",
+                    [],
+                )
+                .unwrap();
+                Ok(())
+            })
+            .unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "anthropic-beta",
+            HeaderValue::from_static("context-management-2025-06-27,interleaved-thinking-2025-05-14"),
+        );
+        let body = br#"{"model":"claude-sonnet-4-5-20250929","messages":[]}"#;
+
+        let preview = router.preview_request(1, "/v1/messages", headers, body).unwrap();
+        assert_eq!(preview_header(&preview, "anthropic-beta"), Some("interleaved-thinking-2025-05-14"));
+    }
+
+    #[test]
+    fn test_preview_request_applies_default_model() {
+        let router = preview_router("claude");
+        router
+            .db_pool
+            .with_connection(|conn| {
+                conn.execute("INSERT INTO AppSettings (id, default_request_model) VALUES (1, 'claude-default')", [])
+                    .unwrap();
+                Ok(())
+            })
+            .unwrap();
+
+        let preview = router
+            .preview_request(1, "/v1/messages", HeaderMap::new(), br#"{"max_tokens":16,"messages":[]}"#)
+            .unwrap();
+        assert!(!preview.body_streamed);
+        let json: serde_json::Value = serde_json::from_str(&preview.body).unwrap();
+        assert_eq!(json["model"], "claude-default");
+
+        // 客户端指定的模型不被覆盖
+        let preview = router
+            .preview_request(1, "/v1/messages", HeaderMap::new(), br#"{"model":"claude-haiku","messages":[]}"#)
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&preview.body).unwrap();
+        assert_eq!(json["model"], "claude-haiku");
+    }
+
+    #[test]
+    fn test_inject_default_model() {
+        let injected = inject_default_model(br#"{"model":"","messages":[]}"#, "m").unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&injected).unwrap();
+        assert_eq!(json["model"], "m");
+        assert!(inject_default_model(br#"{"model":null}"#, "m").is_some());
+        assert!(inject_default_model(br#"{"model":"claude"}"#, "m").is_none());
+        assert!(inject_default_model(b"[1,2]", "m").is_none());
+        assert!(inject_default_model(b"not json", "m").is_none());
+    }
+
+    #[test]
+    fn test_preview_request_applies_model_override_header() {
+        let router = preview_router("claude");
+        let body = br#"{"model":"claude-sonnet-4-5-20250929","messages":[]}"#;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ccproxy-model", "claude-opus-4-1".parse().unwrap());
+        let preview = router.preview_request(1, "/v1/messages", headers, body).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&preview.body).unwrap();
+        assert_eq!(json["model"], "claude-opus-4-1");
+        assert_eq!(preview.mapped_model.as_deref(), Some("claude-opus-4-1"));
+        assert!(!preview.body_streamed);
+        assert!(preview_header(&preview, "x-ccproxy-model").is_none());
+
+        // 空值不生效，但同样不转发
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ccproxy-model", " ".parse().unwrap());
+        let preview = router.preview_request(1, "/v1/messages", headers, body).unwrap();
+        assert!(preview.mapped_model.is_none());
+        assert!(preview_header(&preview, "x-ccproxy-model").is_none());
+    }
+
+    #[test]
+    fn test_preview_request_applies_metadata_user_id_policy() {
+        let router = preview_router("claude");
+        let body = br#"{"model":"claude-sonnet-4-5-20250929","messages":[],"metadata":{"user_id":"user_abc_session_1"}}"#;
+
+        // 默认原样转发
+        let preview = router.preview_request(1, "/v1/messages", HeaderMap::new(), body).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&preview.body).unwrap();
+        assert_eq!(json["metadata"]["user_id"], "user_abc_session_1");
+
+        router
+            .db_pool
+            .with_connection(|conn| {
+                conn.execute("UPDATE ApiConfig SET metadata_user_id_policy = 'strip' WHERE id = 1", [])
+                    .unwrap();
+                Ok(())
+            })
+            .unwrap();
+        let preview = router.preview_request(1, "/v1/messages", HeaderMap::new(), body).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&preview.body).unwrap();
+        assert!(json["metadata"].get("user_id").is_none());
+    }
+
+    #[test]
+    fn test_preview_request_compresses_large_body() {
+        let router = preview_router("claude");
+        router
+            .db_pool
+            .with_connection(|conn| {
+                conn.execute("UPDATE ApiConfig SET request_compression = 'force' WHERE id = 1", [])
+                    .unwrap();
+                Ok(())
+            })
+            .unwrap();
+
+        // 小于阈值的请求体不压缩
+        let small = br#"{"model":"claude-sonnet-4-5-20250929","messages":[]}"#;
+        let preview = router.preview_request(1, "/v1/messages", HeaderMap::new(), small).unwrap();
+        assert!(!preview.body_compressed);
+        assert_eq!(preview_header(&preview, "content-encoding"), None);
+
+        let text = "a".repeat(request_compression::MIN_COMPRESS_BODY_BYTES);
+        let large = serde_json::to_vec(&serde_json::json!({
+            "model": "claude-sonnet-4-5-20250929",
+            "messages": [{"role": "user", "content": text}],
+        }))
+        .unwrap();
+        let preview = router.preview_request(1, "/v1/messages", HeaderMap::new(), &large).unwrap();
+        assert!(preview.body_compressed);
+        assert!(!preview.body_streamed);
+        assert_eq!(preview_header(&preview, "content-encoding"), Some("gzip"));
+        let content_length: usize = preview_header(&preview, "content-length").unwrap().parse().unwrap();
+        assert!(content_length < large.len());
+        assert_eq!(preview.body.as_bytes(), large.as_slice());
+    }
+
+    #[test]
+    fn test_preview_request_converts_for_gemini_backend() {
+        let router = preview_router("gemini");
+        let body = br#"{"model":"claude-sonnet-4-5-20250929","max_tokens":16,"messages":[{"role":"user","content":"hi"}]}"#;
+        let preview = router.preview_request(1, "/v1/messages", HeaderMap::new(), body).unwrap();
+
+        assert_eq!(preview.conversion, ConversionDirection::ClaudeToGemini.to_string());
+        assert!(!preview.body_streamed);
+        assert!(preview.uri.contains(":generateContent"), "{}", preview.uri);
+        let json: serde_json::Value = serde_json::from_str(&preview.body).unwrap();
+        assert!(json.get("contents").is_some());
+        assert_eq!(preview_header(&preview, "content-length"), Some(preview.body.len().to_string().as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_wrapper_without_body_retention() {
+        const EVENTS: &[u8] = b"event: message_start\ndata: {\"type\":\"message_start\"}\n\nevent: error\ndata: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n";
+        let (tx, mut rx) = mpsc::channel::<StreamCompletionData>(1);
+        let inner = http_body_util::Full::new(Bytes::from_static(EVENTS));
+        let mut body = StreamingBodyWrapper::new(inner, tx, false, None, false);
+        while body.frame().await.is_some() {}
+
+        // 不保留内容，但仍统计大小与 chunk 数，并通过完整性跟踪识别错误事件
+        let completion = rx.recv().await.unwrap();
+        assert!(completion.response_body.is_none());
+        assert_eq!(completion.response_body_size, EVENTS.len() as u64);
+        assert_eq!(completion.chunk_count, 1);
+        assert!(completion.soft_error.is_some());
+    }
+
+    #[test]
+    fn test_openai_stream_conversion_buffers_partial_lines() {
+        let mut conversion = OpenAIStreamConversion::new("claude-sonnet-4-5-20250929".to_string());
+        let chunk = r#"data: {"id":"chatcmpl-1","object":"chat.completion.chunk","created":0,"model":"gpt-4o","choices":[{"index":0,"delta":{"content":"Hi"}}]}"#;
+        let (head, tail) = chunk.split_at(40);
+
+        assert!(conversion.push(head.as_bytes()).is_empty());
+        let events = conversion.push(format!("{}\n\n", tail).as_bytes());
+        assert!(events.contains("event: message_start"));
+        assert!(events.contains("\"text\":\"Hi\""));
+        assert!(events.contains("\"model\":\"claude-sonnet-4-5-20250929\""));
+
+        // 未收到 finish_reason 即 [DONE]，补齐终止事件
+        let events = conversion.push(b"data: [DONE]\n\n");
+        assert!(events.contains("event: content_block_stop"));
+        assert!(events.ends_with("event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n"));
+        assert!(conversion.is_done());
+        assert!(conversion.finish().is_empty());
+    }
+
+    #[test]
+    fn test_openai_stream_conversion_terminal_events() {
+        let finished = r#"data: {"id":"chatcmpl-1","object":"chat.completion.chunk","created":0,"model":"gpt-4o","choices":[{"index":0,"delta":{},"finish_reason":"stop"}]}"#;
+
+        // finish_reason 已触发 message_stop，[DONE] 不再重复输出
+        let mut conversion = OpenAIStreamConversion::new("claude".to_string());
+        let events = conversion.push(format!("{}\n\ndata: not-json\n\n", finished).as_bytes());
+        assert_eq!(events.matches("event: message_stop").count(), 1);
+        assert!(conversion.push(b"data: [DONE]\n\n").is_empty());
+
+        // 读取失败转换为 Claude 错误事件
+        let mut conversion = OpenAIStreamConversion::new("claude".to_string());
+        let events = conversion.fail("Stream error: reset");
+        assert!(events.starts_with("event: error\n"));
+        assert!(events.contains("Stream error: reset"));
+        assert!(conversion.is_done());
+        assert!(conversion.finish().is_empty());
+    }
+}
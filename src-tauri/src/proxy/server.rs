@@ -5,48 +5,175 @@
 
 use crate::db::DbPool;
 use crate::models::error::{AppError, AppResult};
+use crate::proxy::canary;
+use crate::proxy::active_requests::{ActiveRequestHandle, ActiveRequestInfo, ActiveRequestRegistry};
 use crate::proxy::logger::ProxyLogger;
 use crate::proxy::router::RequestRouter;
+use crate::proxy::structured_logger::{generate_request_id, CURRENT_REQUEST_ID, METRICS};
 use crate::services::api_config::ApiConfigService;
 use crate::services::auto_switch::AutoSwitchService;
+use crate::services::config_manager::ConfigManager;
+use crate::services::log_sampling::{LogSamplingPolicy, LogSamplingService};
 use crate::services::proxy_log::ProxyRequestLogService;
-use crate::services::session_config::SESSION_CONFIG_MAP;
+use crate::services::session_config::{env_session_config_id, SESSION_CONFIG_MAP};
 use crate::utils::constants::default_proxy_port;
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
 use hyper::{body::Incoming, Request, Response};
 use hyper_util::rt::TokioIo;
 use http_body_util::combinators::BoxBody;
+use http_body_util::BodyExt;
 use hyper::body::Bytes;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tokio::sync::RwLock;
 
+/// 请求追踪 ID 头
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// 后端返回的原始请求 ID 头（与代理请求 ID 不同时保留）
+const BACKEND_REQUEST_ID_HEADER: &str = "x-backend-request-id";
+
+/// Default connect timeout in seconds (TCP connect + TLS handshake)
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// Default request timeout in seconds (send request + wait for response headers)
+/// Kept long for slow generation on streaming responses (FR-012)
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 120;
+
+/// Default maximum duration of a single streaming response in seconds (0 = unlimited)
+pub const DEFAULT_MAX_STREAM_DURATION_SECS: u64 = 1800;
+
+/// Default maximum number of chunks in a single streaming response (0 = unlimited)
+pub const DEFAULT_MAX_STREAM_CHUNKS: u32 = 100_000;
+
+/// Environment variable overriding the default listen port
+pub const ENV_PROXY_PORT: &str = "CCPROXY_PORT";
+
+/// Environment variable overriding the default listen host
+pub const ENV_PROXY_HOST: &str = "CCPROXY_HOST";
+
+/// Environment variable disabling automatic port fallback when the port is taken
+pub const ENV_PROXY_STRICT_PORT: &str = "CCPROXY_STRICT_PORT";
+
 /// Proxy server configuration
+///
+/// Listen address precedence: a persisted / user-set value (applied through
+/// `update_config`) > `CCPROXY_HOST` / `CCPROXY_PORT` / `CCPROXY_STRICT_PORT`
+/// environment variables > compiled defaults. Invalid environment values are
+/// logged and ignored.
 #[derive(Debug, Clone)]
 pub struct ProxyConfig {
     /// Listen host
     pub host: String,
     /// Listen port (default: 25341 for production, 15341 for development)
     pub port: u16,
+    /// Fail instead of trying the next ports when `port` is occupied
+    pub strict_port: bool,
     /// Currently active config group ID
     pub active_group_id: Option<i64>,
     /// Currently active config ID
     pub active_config_id: Option<i64>,
+    /// Connect timeout in seconds, overridable per config
+    pub connect_timeout_secs: u64,
+    /// Request/read timeout in seconds, overridable per config
+    pub request_timeout_secs: u64,
+    /// Maximum streaming response duration in seconds (0 = unlimited)
+    pub max_stream_duration_secs: u64,
+    /// Maximum streaming response chunk count (0 = unlimited)
+    pub max_stream_chunks: u32,
 }
 
 impl Default for ProxyConfig {
     fn default() -> Self {
+        Self::from_env_vars(|key| std::env::var(key).ok())
+    }
+}
+
+impl ProxyConfig {
+    /// Build the default configuration, applying listen address overrides from
+    /// the given environment lookup
+    pub fn from_env_vars(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let mut config = Self::compiled_default();
+
+        if let Some(host) = env_override(&lookup, ENV_PROXY_HOST, parse_listen_host) {
+            config.host = host;
+        }
+        if let Some(port) = env_override(&lookup, ENV_PROXY_PORT, parse_listen_port) {
+            config.port = port;
+        }
+        if let Some(strict) = env_override(&lookup, ENV_PROXY_STRICT_PORT, parse_bool_flag) {
+            config.strict_port = strict;
+        }
+
+        config
+    }
+
+    fn compiled_default() -> Self {
         Self {
             host: "127.0.0.1".to_string(),
             port: default_proxy_port(),
+            strict_port: false,
             active_group_id: None,
             active_config_id: None,
+            connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            max_stream_duration_secs: DEFAULT_MAX_STREAM_DURATION_SECS,
+            max_stream_chunks: DEFAULT_MAX_STREAM_CHUNKS,
         }
     }
 }
 
+/// Read and validate an environment override, ignoring (and logging) invalid values
+fn env_override<T>(
+    lookup: &impl Fn(&str) -> Option<String>,
+    key: &str,
+    parse: fn(&str) -> Result<T, String>,
+) -> Option<T> {
+    let raw = lookup(key)?;
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    match parse(raw) {
+        Ok(value) => {
+            log::info!("Using {}={} from environment", key, raw);
+            Some(value)
+        }
+        Err(e) => {
+            log::warn!("Ignoring invalid {}={:?}: {}", key, raw, e);
+            None
+        }
+    }
+}
+
+fn parse_listen_port(value: &str) -> Result<u16, String> {
+    match value.parse::<u16>() {
+        Ok(0) | Err(_) => Err("expected a port between 1 and 65535".to_string()),
+        Ok(port) => Ok(port),
+    }
+}
+
+fn parse_listen_host(value: &str) -> Result<String, String> {
+    let is_hostname = value
+        .split('.')
+        .all(|label| !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+    if value.parse::<std::net::IpAddr>().is_ok() || is_hostname {
+        Ok(value.to_string())
+    } else {
+        Err("expected an IP address or hostname".to_string())
+    }
+}
+
+fn parse_bool_flag(value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err("expected true/false".to_string()),
+    }
+}
+
 /// Proxy server status
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProxyServerStatus {
@@ -74,16 +201,30 @@ pub struct ProxyServer {
     db_pool: Arc<DbPool>,
     /// Auto-switch service (shared across all requests)
     auto_switch_service: Arc<AutoSwitchService>,
+    /// Registry of in-flight requests
+    active_requests: Arc<ActiveRequestRegistry>,
 }
 
 impl ProxyServer {
     /// Create new proxy server instance
     pub fn new(config: ProxyConfig, db_pool: Arc<DbPool>) -> Self {
+        let auto_switch_service = Arc::new(AutoSwitchService::new(db_pool.clone()));
+        Self::with_auto_switch(config, db_pool, auto_switch_service)
+    }
+
+    /// Create proxy server with the given auto-switch service
+    /// (used by additional named listeners, which keep their own routing state)
+    pub fn with_auto_switch(
+        config: ProxyConfig,
+        db_pool: Arc<DbPool>,
+        auto_switch_service: Arc<AutoSwitchService>,
+    ) -> Self {
         Self {
             config: Arc::new(RwLock::new(config)),
             status: Arc::new(RwLock::new(ProxyServerStatus::Stopped)),
             shutdown_tx: Arc::new(RwLock::new(None)),
-            auto_switch_service: Arc::new(AutoSwitchService::new(db_pool.clone())),
+            auto_switch_service,
+            active_requests: Arc::new(ActiveRequestRegistry::new()),
             db_pool,
         }
     }
@@ -93,6 +234,16 @@ impl ProxyServer {
         self.auto_switch_service.clone()
     }
 
+    /// List requests currently being processed (longest running first)
+    pub fn active_requests(&self) -> Vec<ActiveRequestInfo> {
+        self.active_requests.list()
+    }
+
+    /// Number of requests currently being processed (streaming responses count until the stream ends)
+    pub fn active_request_count(&self) -> usize {
+        self.active_requests.count()
+    }
+
     /// Get current status
     pub async fn status(&self) -> ProxyServerStatus {
         *self.status.read().await
@@ -134,7 +285,8 @@ impl ProxyServer {
 
         let config = self.config.read().await.clone();
         let mut port = config.port;
-        let max_attempts = 10; // 最多尝试10个端口
+        // 最多尝试10个端口，strict_port 时只尝试配置的端口
+        let max_attempts = if config.strict_port { 1 } else { 10 };
 
         log::info!("Starting proxy server on {}:{}", config.host, port);
 
@@ -210,6 +362,7 @@ impl ProxyServer {
         let status_arc = self.status.clone();
         let db_pool_arc = self.db_pool.clone();
         let auto_switch_arc = self.auto_switch_service.clone();
+        let active_requests_arc = self.active_requests.clone();
 
         // Spawn async task to handle connections
         tokio::spawn(async move {
@@ -229,6 +382,7 @@ impl ProxyServer {
                                 let config = config_arc.clone();
                                 let db_pool = db_pool_arc.clone();
                                 let auto_switch = auto_switch_arc.clone();
+                                let active_requests = active_requests_arc.clone();
                                 let mut conn_shutdown_rx = shutdown_tx.subscribe();
 
                                 // Create async task for each connection
@@ -240,8 +394,9 @@ impl ProxyServer {
                                         let config = config.clone();
                                         let db_pool = db_pool.clone();
                                         let auto_switch = auto_switch.clone();
+                                        let active_requests = active_requests.clone();
                                         async move {
-                                            Self::handle_request(req, remote_addr, config, db_pool, auto_switch).await
+                                            Self::handle_request(req, remote_addr, config, db_pool, auto_switch, active_requests).await
                                         }
                                     });
 
@@ -333,11 +488,81 @@ impl ProxyServer {
 
     /// Handle proxy request
     async fn handle_request(
+        mut req: Request<Incoming>,
+        remote_addr: SocketAddr,
+        config: Arc<RwLock<ProxyConfig>>,
+        db_pool: Arc<DbPool>,
+        auto_switch_service: Arc<AutoSwitchService>,
+        active_requests: Arc<ActiveRequestRegistry>,
+    ) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+        // 请求追踪 ID：优先使用客户端提供的 x-request-id，否则生成新的并转发给后端
+        let request_id = match Self::client_request_id(&req) {
+            Some(id) => id,
+            None => {
+                let id = generate_request_id();
+                if let Ok(value) = hyper::header::HeaderValue::from_str(&id) {
+                    req.headers_mut().insert(REQUEST_ID_HEADER, value);
+                }
+                id
+            }
+        };
+
+        // 登记到正在处理的请求列表，请求（含流式响应）结束时自动移除
+        let active = active_requests.register(
+            &request_id,
+            req.method().as_str(),
+            req.uri().path(),
+            &remote_addr.to_string(),
+        );
+
+        // 在任务范围内绑定请求 ID，使该请求的所有日志行都带上请求 ID
+        let mut response = CURRENT_REQUEST_ID
+            .scope(
+                request_id.clone(),
+                Self::handle_traced_request(
+                    req,
+                    request_id.clone(),
+                    remote_addr,
+                    config,
+                    db_pool,
+                    auto_switch_service,
+                    active,
+                ),
+            )
+            .await?;
+
+        // 将请求 ID 返回给客户端；后端自带的 x-request-id 保留在 x-backend-request-id 中
+        if let Ok(value) = hyper::header::HeaderValue::from_str(&request_id) {
+            let headers = response.headers_mut();
+            if let Some(backend_id) = headers.insert(REQUEST_ID_HEADER, value.clone()) {
+                if backend_id != value {
+                    headers.insert(BACKEND_REQUEST_ID_HEADER, backend_id);
+                }
+            }
+        }
+
+        Ok(response)
+    }
+
+    /// 提取客户端提供的请求 ID（忽略空值或过长的值）
+    fn client_request_id(req: &Request<Incoming>) -> Option<String> {
+        req.headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty() && v.len() <= 128)
+            .map(|v| v.to_string())
+    }
+
+    /// 处理单个请求（已绑定请求追踪 ID）
+    async fn handle_traced_request(
         req: Request<Incoming>,
+        request_id: String,
         remote_addr: SocketAddr,
         config: Arc<RwLock<ProxyConfig>>,
         db_pool: Arc<DbPool>,
         auto_switch_service: Arc<AutoSwitchService>,
+        active: ActiveRequestHandle,
     ) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
         let method = req.method().clone();
         let uri = req.uri().clone();
@@ -367,7 +592,8 @@ impl ProxyServer {
             method.clone(),
             uri.clone(),
             remote_addr.to_string(),
-        );
+        )
+        .with_request_id(request_id);
 
         // 添加请求头信息到日志构建器
         if let Some(headers) = request_headers_json {
@@ -424,6 +650,21 @@ impl ProxyServer {
                     sid, session_config_id
                 );
                 (Some(session_config_id), format!("session:{}", sid))
+            } else if let Some(config) = env_session_config_id(sid).and_then(|id| {
+                db_pool
+                    .with_connection(|conn| {
+                        use crate::services::api_config::ApiConfigService;
+                        ApiConfigService::get_config_by_id(conn, id)
+                    })
+                    .ok()
+            }) {
+                // Exported env sessions (env-<config_id>) survive restarts: re-register on first use
+                log::info!(
+                    "[Session Routing] Restored env session: session={}, config_id={}",
+                    sid, config.id
+                );
+                SESSION_CONFIG_MAP.register(sid.clone(), config.id, Some(config.name));
+                (Some(config.id), format!("session:{}", sid))
             } else {
                 // Session not found, fall back to global
                 log::warn!(
@@ -463,6 +704,7 @@ impl ProxyServer {
                         "No active configuration".to_string(),
                     );
                 ProxyLogger::log_request(&log_entry);
+                METRICS.record_entry(&log_entry);
 
                 // Save to database
                 let db = db_pool.clone();
@@ -476,12 +718,37 @@ impl ProxyServer {
             }
         };
 
+        // Canary: route a share of global traffic to the group's canary config.
+        // Session-pinned requests keep their config.
+        let active_config_id = config_id;
+        let (config_id, routing_source) = match (routing_source.as_str(), group_id) {
+            ("global", gid) if gid > 0 => {
+                let canary_id = db_pool
+                    .with_connection(|conn| ConfigManager::get_group_by_id(conn, gid))
+                    .ok()
+                    .and_then(|group| canary::pick_canary(&group, config_id));
+                match canary_id {
+                    Some(id) => {
+                        log::info!("[Canary] Routing request to canary config {} (group {})", id, gid);
+                        (id, canary::CANARY_ROUTING_SOURCE.to_string())
+                    }
+                    None => (config_id, routing_source),
+                }
+            }
+            _ => (config_id, routing_source),
+        };
+        let is_canary = routing_source == canary::CANARY_ROUTING_SOURCE;
+
+        active.set_config_id(config_id);
+
         // Create router and forward request (with config reference and shared auto-switch service)
-        let router = RequestRouter::new_with_config(
-            db_pool.clone(),
-            config.clone(),
-            auto_switch_service,
-        );
+        let router = RequestRouter::new_with_config(db_pool.clone(), config.clone(), auto_switch_service);
+        // Session-pinned requests never switch the global active config
+        let router = Arc::new(if routing_source.starts_with("session:") {
+            router.without_auto_switch()
+        } else {
+            router
+        });
 
         // Get config name for logging
         let config_name = db_pool
@@ -500,8 +767,38 @@ impl ProxyServer {
             log_builder
         };
 
-        match router.forward_request(req, config_id, group_id).await {
+        let result = if is_canary {
+            router
+                .forward_canary_request(req, config_id, active_config_id, group_id, remote_addr)
+                .await
+        } else {
+            router.forward_request(req, config_id, group_id, remote_addr).await
+        };
+        match result {
             Ok((response, forward_details, stream_rx)) => {
+                // 金丝雀失败后由活跃配置响应：后续统计与日志都记在活跃配置上
+                let (config_id, is_canary, log_builder) = match forward_details.canary_fallback_config_id {
+                    Some(fallback_id) => {
+                        active.set_config_id(fallback_id);
+                        let fallback_name = db_pool
+                            .with_connection(|conn| {
+                                use crate::services::api_config::ApiConfigService;
+                                ApiConfigService::get_config_by_id(conn, fallback_id).map(|c| c.name)
+                            })
+                            .ok();
+                        let log_builder =
+                            log_builder.with_target(format!("config:{} (canary_fallback)", fallback_id));
+                        let log_builder = match fallback_name {
+                            Some(name) => log_builder.with_config(fallback_id, name),
+                            None => log_builder,
+                        };
+                        (fallback_id, false, log_builder)
+                    }
+                    None => (config_id, is_canary, log_builder),
+                };
+                active.set_response(response.status().as_u16(), stream_rx.is_some());
+                let response = response.map(|body| active.count_body(body).boxed());
+
                 // 使用详细信息构建日志
                 let mut log_builder = log_builder;
 
@@ -515,6 +812,9 @@ impl ProxyServer {
                     log_builder = log_builder.with_model(model);
                 }
 
+                // 添加 token 用量（流式响应在流结束后更新）
+                log_builder = log_builder.with_token_usage(forward_details.token_usage);
+
                 // 标记响应开始
                 log_builder.mark_response_start();
 
@@ -532,49 +832,138 @@ impl ProxyServer {
 
                     ProxyLogger::log_request(&initial_log_entry);
 
+                    // 非全部记录的采样策略下，成功的流式请求推迟到流结束后按最终结果决定是否保存
+                    let sampling_policy = LogSamplingService::current_policy(&db_pool);
+                    let deferred_log_entry = (sampling_policy != LogSamplingPolicy::All
+                        && initial_log_entry.is_success())
+                    .then(|| initial_log_entry.clone());
+
                     // 保存初始日志并获取 ID
                     let db = db_pool.clone();
-                    let log_id = match ProxyRequestLogService::save_log(&db, &initial_log_entry) {
-                        Ok(id) => id,
-                        Err(e) => {
-                            log::warn!("Failed to save initial proxy request log: {}", e);
-                            return Ok(response);
-                        }
+                    let log_id = match deferred_log_entry {
+                        Some(_) => None,
+                        None => match ProxyRequestLogService::save_log(&db, &initial_log_entry) {
+                            Ok(id) => Some(id),
+                            Err(e) => {
+                                log::warn!("Failed to save initial proxy request log: {}", e);
+                                // 仍然保持登记直到流结束
+                                tokio::spawn(async move {
+                                    let _active = active;
+                                    let _ = rx.recv().await;
+                                });
+                                return Ok(response);
+                            }
+                        },
                     };
 
                     // 启动后台任务等待流结束并更新日志
                     let db_for_update = db_pool.clone();
                     let response_headers = forward_details.response_headers;
                     let stream_config_id = config_id;
+                    let stream_router = router.clone();
+                    let mut metrics_entry = initial_log_entry;
                     tokio::spawn(async move {
-                        // 等待流式响应完成
+                        // 等待流式响应完成 (完成后 handle 被 drop，请求从登记列表移除)
+                        let _active = active;
                         if let Some(completion_data) = rx.recv().await {
+                            // 流结束后以完整耗时与最终 token 用量计入指标
+                            metrics_entry.latency_ms = (chrono::Local::now() - metrics_entry.timestamp)
+                                .num_milliseconds()
+                                .max(0) as u64;
+                            metrics_entry.token_usage = completion_data.token_usage;
+                            METRICS.record_entry(&metrics_entry);
+
                             log::info!(
                                 "Stream completed: {} bytes, {} chunks",
                                 completion_data.response_body_size,
                                 completion_data.chunk_count
                             );
 
+                            if let Some(report) = completion_data.integrity.as_ref().filter(|r| !r.is_complete) {
+                                log::warn!(
+                                    "Stream integrity problem from config {}: started_with_message_start={}, ended_with_message_stop={}, out_of_order={:?}, error={:?}",
+                                    stream_config_id,
+                                    report.started_with_message_start,
+                                    report.ended_with_message_stop,
+                                    report.out_of_order_events,
+                                    report.error
+                                );
+                            }
+
+                            // 推迟的日志按流的最终结果与完整耗时决定是否保存
+                            let log_id = match deferred_log_entry {
+                                Some(entry) => {
+                                    let is_error = completion_data.soft_error.is_some()
+                                        || completion_data.integrity.as_ref().is_some_and(|r| !r.is_complete);
+                                    if sampling_policy.should_persist(is_error, metrics_entry.latency_ms) {
+                                        ProxyRequestLogService::save_log(&db_for_update, &entry)
+                                            .map_err(|e| log::warn!("Failed to save proxy request log: {}", e))
+                                            .ok()
+                                    } else {
+                                        None
+                                    }
+                                }
+                                None => log_id,
+                            };
+
                             // 更新日志记录
-                            if let Err(e) = ProxyRequestLogService::update_streaming_log(
-                                &db_for_update,
-                                log_id,
-                                response_headers,
-                                Some(completion_data.response_body),
-                                completion_data.response_body_size as i64,
-                                completion_data.chunk_count as i32,
-                            ) {
-                                log::warn!("Failed to update streaming log: {}", e);
+                            if let Some(log_id) = log_id {
+                                if let Err(e) = ProxyRequestLogService::update_streaming_log(
+                                    &db_for_update,
+                                    log_id,
+                                    response_headers,
+                                    completion_data.response_body,
+                                    completion_data.response_body_size as i64,
+                                    completion_data.chunk_count as i32,
+                                    completion_data.integrity.as_ref(),
+                                    &completion_data.token_usage,
+                                ) {
+                                    log::warn!("Failed to update streaming log: {}", e);
+                                }
                             }
 
-                            // 更新成功记录和权重分数
-                            if let Err(e) = db_for_update.with_connection(|conn| {
-                                ApiConfigService::record_success(conn, stream_config_id)
-                            }) {
-                                log::warn!("Failed to record success for config {}: {}", stream_config_id, e);
+                            if let Some(soft_error) = completion_data.soft_error.as_ref() {
+                                // HTTP 200 但终止事件为错误，计为失败
+                                log::warn!(
+                                    "Soft error in streaming response from config {}: {}",
+                                    stream_config_id,
+                                    soft_error
+                                );
+                                if let Err(e) = db_for_update.with_connection(|conn| {
+                                    ApiConfigService::increment_failure_count(conn, stream_config_id)
+                                }) {
+                                    log::warn!("Failed to increment failure count for config {}: {}", stream_config_id, e);
+                                }
+                                if is_canary {
+                                    canary::record_outcome(&db_for_update, group_id, stream_config_id, false);
+                                } else {
+                                    stream_router
+                                        .handle_stream_soft_error(stream_config_id, group_id, soft_error.clone())
+                                        .await;
+                                }
+                            } else {
+                                if is_canary {
+                                    canary::record_outcome(&db_for_update, group_id, stream_config_id, true);
+                                }
+                                if let Err(e) = db_for_update.with_connection(|conn| {
+                                    // 更新成功记录和权重分数
+                                    ApiConfigService::record_success(conn, stream_config_id)
+                                }) {
+                                    log::warn!("Failed to record success for config {}: {}", stream_config_id, e);
+                                }
                             }
                         } else {
                             log::warn!("Stream receiver closed without completion data");
+                            // 流未正常结束，推迟的日志按失败保存
+                            if let Some(mut entry) = deferred_log_entry {
+                                entry.latency_ms = (chrono::Local::now() - entry.timestamp)
+                                    .num_milliseconds()
+                                    .max(0) as u64;
+                                entry.error = Some("Stream ended without completion data".to_string());
+                                if let Err(e) = ProxyRequestLogService::save_log(&db_for_update, &entry) {
+                                    log::warn!("Failed to save proxy request log: {}", e);
+                                }
+                            }
                         }
                     });
                 } else {
@@ -588,13 +977,18 @@ impl ProxyServer {
                         forward_details.stream_chunk_count as u32,
                     );
                     ProxyLogger::log_request(&log_entry);
+                    METRICS.record_entry(&log_entry);
 
                     // Save to database and update weight (async, don't block response)
                     let db = db_pool.clone();
                     let success_config_id = config_id;
                     tokio::spawn(async move {
-                        if let Err(e) = ProxyRequestLogService::save_log(&db, &log_entry) {
-                            log::warn!("Failed to save proxy request log: {}", e);
+                        // 按日志采样策略决定是否保存 (失败请求始终保存)
+                        let sampling_policy = LogSamplingService::current_policy(&db);
+                        if sampling_policy.should_persist(!log_entry.is_success(), log_entry.latency_ms) {
+                            if let Err(e) = ProxyRequestLogService::save_log(&db, &log_entry) {
+                                log::warn!("Failed to save proxy request log: {}", e);
+                            }
                         }
                         // 更新成功记录和权重分数
                         if let Err(e) = db.with_connection(|conn| {
@@ -609,17 +1003,22 @@ impl ProxyServer {
             }
             Err(e) => {
                 let error_msg = e.to_string();
+                // 请求体超限属于客户端错误：返回 413，且不计入配置失败次数
+                let client_error = matches!(e, AppError::PayloadTooLarge { .. });
+                let status = if client_error {
+                    hyper::StatusCode::PAYLOAD_TOO_LARGE
+                } else {
+                    hyper::StatusCode::BAD_GATEWAY
+                };
                 let response = RequestRouter::default_response(
-                    hyper::StatusCode::BAD_GATEWAY,
+                    status,
                     &format!("Failed to forward request: {}", error_msg),
                 );
 
                 // Log failed request
-                let log_entry = log_builder.finish_with_error(
-                    hyper::StatusCode::BAD_GATEWAY,
-                    error_msg,
-                );
+                let log_entry = log_builder.finish_with_error(status, error_msg);
                 ProxyLogger::log_request(&log_entry);
+                METRICS.record_entry(&log_entry);
 
                 // Save to database and update failure count (async, don't block response)
                 let db = db_pool.clone();
@@ -628,6 +1027,9 @@ impl ProxyServer {
                     if let Err(e) = ProxyRequestLogService::save_log(&db, &log_entry) {
                         log::warn!("Failed to save proxy request log: {}", e);
                     }
+                    if client_error {
+                        return;
+                    }
                     // 增加失败计数
                     if let Err(e) = db.with_connection(|conn| {
                         ApiConfigService::increment_failure_count(conn, failed_config_id)
@@ -688,8 +1090,13 @@ mod tests {
         let config = ProxyConfig {
             host: "127.0.0.1".to_string(),
             port: 25342, // Use different port to avoid conflicts
+            strict_port: false,
             active_group_id: None,
             active_config_id: None,
+            connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            max_stream_duration_secs: DEFAULT_MAX_STREAM_DURATION_SECS,
+            max_stream_chunks: DEFAULT_MAX_STREAM_CHUNKS,
         };
 
         let server = ProxyServer::new(config, db_pool);
@@ -727,8 +1134,13 @@ mod tests {
         let new_config = ProxyConfig {
             host: "0.0.0.0".to_string(),
             port: 8080,
+            strict_port: false,
             active_group_id: Some(1),
             active_config_id: Some(2),
+            connect_timeout_secs: 5,
+            request_timeout_secs: 300,
+            max_stream_duration_secs: 600,
+            max_stream_chunks: 0,
         };
 
         server.update_config(new_config.clone()).await;
@@ -738,6 +1150,10 @@ mod tests {
         assert_eq!(current_config.port, 8080);
         assert_eq!(current_config.active_group_id, Some(1));
         assert_eq!(current_config.active_config_id, Some(2));
+        assert_eq!(current_config.connect_timeout_secs, 5);
+        assert_eq!(current_config.request_timeout_secs, 300);
+        assert_eq!(current_config.max_stream_duration_secs, 600);
+        assert_eq!(current_config.max_stream_chunks, 0);
     }
 
     #[test]
@@ -787,4 +1203,33 @@ mod tests {
         let uri: hyper::Uri = "/?foo=bar".parse().unwrap();
         assert_eq!(ProxyServer::extract_session_id(&uri), None);
     }
+
+    #[test]
+    fn test_config_from_env_vars() {
+        let config = ProxyConfig::from_env_vars(|key| match key {
+            ENV_PROXY_HOST => Some("0.0.0.0".to_string()),
+            ENV_PROXY_PORT => Some(" 30000 ".to_string()),
+            ENV_PROXY_STRICT_PORT => Some("yes".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.host, "0.0.0.0");
+        assert_eq!(config.port, 30000);
+        assert!(config.strict_port);
+
+        // Invalid values fall back to the compiled defaults
+        let config = ProxyConfig::from_env_vars(|key| match key {
+            ENV_PROXY_HOST => Some("bad host!".to_string()),
+            ENV_PROXY_PORT => Some("0".to_string()),
+            ENV_PROXY_STRICT_PORT => Some("maybe".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.host, "127.0.0.1");
+        assert_eq!(config.port, default_proxy_port());
+        assert!(!config.strict_port);
+
+        assert_eq!(ProxyConfig::from_env_vars(|_| None).port, default_proxy_port());
+        assert!(parse_listen_port("70000").is_err());
+        assert!(parse_listen_host("proxy.local").is_ok());
+        assert!(parse_listen_host("::1").is_ok());
+    }
 }
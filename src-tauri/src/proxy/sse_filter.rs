@@ -0,0 +1,186 @@
+/**
+ * SSE 保活事件过滤器
+ *
+ * 部分后端会频繁发送 `event: ping` 或 `: keepalive` 注释行作为保活信号,
+ * 这些事件会放大 chunk 计数并让日志中的响应体充满无意义内容。
+ *
+ * 过滤器按 SSE 事件边界（空行）切分字节流:
+ * - 仅包含注释行的事件会被丢弃
+ * - `event: ping` 事件会被丢弃
+ * - 其他事件连同其原始结束空行一起原样输出,因此丢弃事件不会导致相邻事件被合并
+ * - 尚未收到结束空行的不完整事件会被暂存,直到后续数据补齐或流结束
+ */
+
+/// SSE 保活事件过滤器
+#[derive(Debug, Default)]
+pub struct SseKeepaliveFilter {
+    /// 尚未形成完整事件的数据
+    pending: Vec<u8>,
+    /// 已丢弃的保活事件数量
+    dropped_events: u32,
+}
+
+impl SseKeepaliveFilter {
+    /// 创建新的过滤器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 写入一段上游数据,返回可以转发给客户端的完整事件
+    ///
+    /// 返回值为空表示当前数据全部为保活事件或尚未形成完整事件
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.pending.extend_from_slice(chunk);
+
+        let mut output = Vec::new();
+        let mut consumed = 0;
+
+        while let Some(len) = find_event_end(&self.pending[consumed..]) {
+            let event = &self.pending[consumed..consumed + len];
+            if is_keepalive_event(event) {
+                self.dropped_events += 1;
+            } else {
+                output.extend_from_slice(event);
+            }
+            consumed += len;
+        }
+
+        self.pending.drain(..consumed);
+        output
+    }
+
+    /// 流结束时取出剩余数据
+    ///
+    /// 末尾不完整的事件无法判断是否完整,原样返回给客户端
+    pub fn finish(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// 已丢弃的保活事件数量
+    pub fn dropped_events(&self) -> u32 {
+        self.dropped_events
+    }
+}
+
+/// 查找第一个完整事件的结束位置（包含结束空行）
+///
+/// 支持 `\n`、`\r\n`、`\r` 三种行结束符。缓冲区末尾的单独 `\r` 可能是
+/// `\r\n` 的前半部分,此时视为不完整,等待更多数据。
+fn find_event_end(buf: &[u8]) -> Option<usize> {
+    let mut i = 0;
+    let mut line_start = 0;
+
+    while i < buf.len() {
+        match buf[i] {
+            b'\n' => {
+                let empty_line = i == line_start;
+                i += 1;
+                if empty_line {
+                    return Some(i);
+                }
+                line_start = i;
+            }
+            b'\r' => {
+                if i + 1 >= buf.len() {
+                    return None;
+                }
+                let empty_line = i == line_start;
+                i += if buf[i + 1] == b'\n' { 2 } else { 1 };
+                if empty_line {
+                    return Some(i);
+                }
+                line_start = i;
+            }
+            _ => i += 1,
+        }
+    }
+
+    None
+}
+
+/// 判断一个完整事件是否为保活事件
+///
+/// 保活事件: 所有非空行都是注释（以 `:` 开头）,或事件类型为 `ping`
+pub fn is_keepalive_event(event: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(event);
+    let lines: Vec<&str> = text
+        .split(|c| c == '\n' || c == '\r')
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    if lines.is_empty() {
+        return false;
+    }
+
+    if lines.iter().all(|line| line.starts_with(':')) {
+        return true;
+    }
+
+    lines.iter().any(|line| {
+        line.strip_prefix("event:")
+            .map(|value| value.trim() == "ping")
+            .unwrap_or(false)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drops_ping_and_comment_events() {
+        let mut filter = SseKeepaliveFilter::new();
+        let input = b"event: ping\ndata: {\"type\": \"ping\"}\n\n: keepalive\n\nevent: message_stop\ndata: {\"type\":\"message_stop\"}\n\n";
+
+        let output = filter.push(input);
+
+        assert_eq!(
+            output,
+            b"event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n".to_vec()
+        );
+        assert_eq!(filter.dropped_events(), 2);
+        assert!(filter.finish().is_empty());
+    }
+
+    #[test]
+    fn test_adjacent_events_are_not_merged() {
+        let mut filter = SseKeepaliveFilter::new();
+        let input = b"event: a\ndata: 1\n\nevent: ping\ndata: {}\n\nevent: b\ndata: 2\n\n";
+
+        let output = filter.push(input);
+
+        assert_eq!(output, b"event: a\ndata: 1\n\nevent: b\ndata: 2\n\n".to_vec());
+    }
+
+    #[test]
+    fn test_events_split_across_chunks() {
+        let mut filter = SseKeepaliveFilter::new();
+
+        assert!(filter.push(b"event: pi").is_empty());
+        assert!(filter.push(b"ng\ndata: {}\n").is_empty());
+        assert!(filter.push(b"\nevent: content_block_delta\r").is_empty());
+        assert!(filter.push(b"\ndata: x\r\n").is_empty());
+        assert_eq!(
+            filter.push(b"\r\n"),
+            b"event: content_block_delta\r\ndata: x\r\n\r\n".to_vec()
+        );
+        assert_eq!(filter.dropped_events(), 1);
+    }
+
+    #[test]
+    fn test_finish_returns_incomplete_tail() {
+        let mut filter = SseKeepaliveFilter::new();
+
+        assert!(filter.push(b"data: partial").is_empty());
+        assert_eq!(filter.finish(), b"data: partial".to_vec());
+    }
+
+    #[test]
+    fn test_is_keepalive_event() {
+        assert!(is_keepalive_event(b": ping\n\n"));
+        assert!(is_keepalive_event(b"event:ping\ndata: {}\n\n"));
+        assert!(!is_keepalive_event(b"event: message_start\ndata: {}\n\n"));
+        assert!(!is_keepalive_event(b": note\ndata: {}\n\n"));
+        assert!(!is_keepalive_event(b"\n"));
+    }
+}
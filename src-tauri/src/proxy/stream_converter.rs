@@ -469,6 +469,18 @@ pub struct StreamIntegrityReport {
     pub stop_reason: Option<String>,
     /// 错误消息（如果有）
     pub error: Option<String>,
+    /// 首个事件是否为 message_start
+    #[serde(default)]
+    pub started_with_message_start: bool,
+    /// 是否以 message_stop 结束
+    #[serde(default)]
+    pub ended_with_message_stop: bool,
+    /// 事件数量（不含 ping）
+    #[serde(default)]
+    pub event_count: u32,
+    /// 顺序异常的事件描述
+    #[serde(default)]
+    pub out_of_order_events: Vec<String>,
 }
 
 impl StreamIntegrityReport {
@@ -482,6 +494,276 @@ impl StreamIntegrityReport {
             output_tokens: state.output_tokens,
             stop_reason: state.stop_reason.clone(),
             error: error.map(String::from),
+            started_with_message_start: state.first_event_sent,
+            ended_with_message_stop: state.completed,
+            event_count: 0,
+            out_of_order_events: Vec::new(),
+        }
+    }
+}
+
+/// 单个流最多记录的顺序异常数量
+const MAX_RECORDED_ANOMALIES: usize = 20;
+
+/// Claude SSE 事件流完整性跟踪器
+///
+/// 按行解析透传给客户端的 SSE 数据，检查:
+/// - 首个事件是否为 `message_start`
+/// - 是否以 `message_stop` 结束
+/// - 内容块事件是否成对、是否出现在 `message_start` 之前或 `message_stop` 之后
+#[derive(Debug)]
+pub struct StreamEventTracker {
+    /// 尚未形成完整行的数据
+    pending_line: Vec<u8>,
+    /// 当前事件类型
+    current_event: Option<String>,
+    /// 当前事件数据
+    current_data: String,
+    /// 是否出现过带 `event:` 字段的事件（用于识别 Claude 格式）
+    saw_named_event: bool,
+    /// 是否出现过 data 行
+    saw_data: bool,
+    event_count: u32,
+    first_event: Option<String>,
+    message_started: bool,
+    message_stopped: bool,
+    /// 尚未结束的内容块索引
+    open_blocks: Vec<u64>,
+    anomalies: Vec<String>,
+    error: Option<String>,
+    stop_reason: Option<String>,
+    output_tokens: i32,
+    content_length: usize,
+    start_time: Instant,
+}
+
+impl Default for StreamEventTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamEventTracker {
+    /// 创建新的跟踪器
+    pub fn new() -> Self {
+        Self {
+            pending_line: Vec::new(),
+            current_event: None,
+            current_data: String::new(),
+            saw_named_event: false,
+            saw_data: false,
+            event_count: 0,
+            first_event: None,
+            message_started: false,
+            message_stopped: false,
+            open_blocks: Vec::new(),
+            anomalies: Vec::new(),
+            error: None,
+            stop_reason: None,
+            output_tokens: 0,
+            content_length: 0,
+            start_time: Instant::now(),
+        }
+    }
+
+    /// 写入一段转发给客户端的数据
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.content_length += chunk.len();
+
+        for &byte in chunk {
+            if byte == b'\n' {
+                let line = std::mem::take(&mut self.pending_line);
+                let line = String::from_utf8_lossy(&line);
+                self.process_line(line.trim_end_matches('\r'));
+            } else {
+                self.pending_line.push(byte);
+            }
+        }
+    }
+
+    /// 流结束时生成报告
+    ///
+    /// 非 Claude 格式的流（没有任何 `event:` 字段但有数据）返回 None
+    pub fn finish(&mut self, stream_id: &str) -> Option<StreamIntegrityReport> {
+        if !self.pending_line.is_empty() {
+            let line = std::mem::take(&mut self.pending_line);
+            let line = String::from_utf8_lossy(&line).to_string();
+            self.process_line(line.trim_end_matches('\r'));
+        }
+        self.dispatch_event();
+
+        if self.saw_data && !self.saw_named_event {
+            return None;
+        }
+
+        let started_with_message_start = self.first_event.as_deref() == Some("message_start");
+        let ended_with_message_stop = self.message_stopped;
+
+        Some(StreamIntegrityReport {
+            is_complete: started_with_message_start
+                && ended_with_message_stop
+                && self.anomalies.is_empty()
+                && self.error.is_none(),
+            stream_id: stream_id.to_string(),
+            duration_ms: self.start_time.elapsed().as_millis() as u64,
+            content_length: self.content_length,
+            output_tokens: self.output_tokens,
+            stop_reason: self.stop_reason.clone(),
+            error: self.error.clone(),
+            started_with_message_start,
+            ended_with_message_stop,
+            event_count: self.event_count,
+            out_of_order_events: self.anomalies.clone(),
+        })
+    }
+
+    /// 提前终止流时补齐的收尾事件
+    ///
+    /// 关闭未结束的内容块，补发 message_delta (尚未发送时) 与 message_stop，
+    /// 使客户端看到一个结构完整的 Claude 流；非 Claude 格式或已结束的流返回空
+    pub fn termination_events(&self) -> String {
+        if !self.saw_named_event || !self.message_started || self.message_stopped {
+            return String::new();
+        }
+
+        let mut events = String::new();
+        // 先结束被截断的行与事件
+        if !self.pending_line.is_empty() {
+            events.push('\n');
+        }
+        if !self.pending_line.is_empty() || self.current_event.is_some() || !self.current_data.is_empty() {
+            events.push('\n');
+        }
+
+        for index in &self.open_blocks {
+            events.push_str(&format!(
+                "event: content_block_stop\ndata: {{\"type\":\"content_block_stop\",\"index\":{}}}\n\n",
+                index
+            ));
+        }
+        if self.stop_reason.is_none() {
+            events.push_str(&format!(
+                "event: message_delta\ndata: {{\"type\":\"message_delta\",\"delta\":{{\"stop_reason\":\"end_turn\",\"stop_sequence\":null}},\"usage\":{{\"output_tokens\":{}}}}}\n\n",
+                self.output_tokens
+            ));
+        }
+        events.push_str("event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n");
+        events
+    }
+
+    fn process_line(&mut self, line: &str) {
+        if line.is_empty() {
+            self.dispatch_event();
+            return;
+        }
+
+        if let Some(value) = line.strip_prefix("event:") {
+            self.current_event = Some(value.trim().to_string());
+            self.saw_named_event = true;
+        } else if let Some(value) = line.strip_prefix("data:") {
+            if !self.current_data.is_empty() {
+                self.current_data.push('\n');
+            }
+            self.current_data.push_str(value.trim_start());
+            self.saw_data = true;
+        }
+    }
+
+    fn dispatch_event(&mut self) {
+        let data = std::mem::take(&mut self.current_data);
+        let Some(event) = self.current_event.take() else {
+            return;
+        };
+
+        if event == "ping" {
+            return;
+        }
+
+        self.event_count += 1;
+        if self.first_event.is_none() {
+            self.first_event = Some(event.clone());
+        }
+
+        if self.message_stopped {
+            self.record_anomaly(format!("{} after message_stop", event));
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&data).unwrap_or_default();
+        let index = json.get("index").and_then(|i| i.as_u64());
+
+        match event.as_str() {
+            "message_start" => {
+                if self.message_started {
+                    self.record_anomaly("duplicate message_start".to_string());
+                }
+                self.message_started = true;
+            }
+            "content_block_start" => {
+                if !self.message_started {
+                    self.record_anomaly("content_block_start before message_start".to_string());
+                }
+                if let Some(index) = index {
+                    if self.open_blocks.contains(&index) {
+                        self.record_anomaly(format!("duplicate content_block_start for block {}", index));
+                    } else {
+                        self.open_blocks.push(index);
+                    }
+                }
+            }
+            "content_block_delta" => {
+                if let Some(index) = index {
+                    if !self.open_blocks.contains(&index) {
+                        self.record_anomaly(format!("content_block_delta for unopened block {}", index));
+                    }
+                }
+            }
+            "content_block_stop" => {
+                if let Some(index) = index {
+                    if let Some(pos) = self.open_blocks.iter().position(|i| *i == index) {
+                        self.open_blocks.remove(pos);
+                    } else {
+                        self.record_anomaly(format!("content_block_stop for unopened block {}", index));
+                    }
+                }
+            }
+            "message_delta" => {
+                if !self.message_started {
+                    self.record_anomaly("message_delta before message_start".to_string());
+                }
+                if !self.open_blocks.is_empty() {
+                    self.record_anomaly("message_delta before content_block_stop".to_string());
+                }
+                if let Some(reason) = json.pointer("/delta/stop_reason").and_then(|r| r.as_str()) {
+                    self.stop_reason = Some(reason.to_string());
+                }
+                if let Some(tokens) = json.pointer("/usage/output_tokens").and_then(|t| t.as_i64()) {
+                    self.output_tokens = tokens as i32;
+                }
+            }
+            "message_stop" => {
+                if !self.message_started {
+                    self.record_anomaly("message_stop before message_start".to_string());
+                }
+                if !self.open_blocks.is_empty() {
+                    self.record_anomaly(format!("message_stop with {} unclosed content block(s)", self.open_blocks.len()));
+                }
+                self.message_stopped = true;
+            }
+            "error" => {
+                let message = json
+                    .pointer("/error/message")
+                    .and_then(|m| m.as_str())
+                    .map(String::from)
+                    .unwrap_or(data);
+                self.error = Some(message);
+            }
+            _ => {}
+        }
+    }
+
+    fn record_anomaly(&mut self, anomaly: String) {
+        if self.anomalies.len() < MAX_RECORDED_ANOMALIES {
+            self.anomalies.push(anomaly);
         }
     }
 }
@@ -623,4 +905,92 @@ mod tests {
         assert_eq!(report.stop_reason, Some("end_turn".to_string()));
         assert!(report.error.is_none());
     }
+
+    const COMPLETE_STREAM: &str = "event: message_start\ndata: {\"type\":\"message_start\"}\n\n\
+        event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":0}\n\n\
+        event: ping\ndata: {\"type\":\"ping\"}\n\n\
+        event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0}\n\n\
+        event: content_block_stop\ndata: {\"type\":\"content_block_stop\",\"index\":0}\n\n\
+        event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":12}}\n\n\
+        event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n";
+
+    #[test]
+    fn test_event_tracker_complete_stream() {
+        let mut tracker = StreamEventTracker::new();
+        // 按任意位置切分，验证跨 chunk 解析
+        for chunk in COMPLETE_STREAM.as_bytes().chunks(7) {
+            tracker.push(chunk);
+        }
+
+        let report = tracker.finish("req-1").unwrap();
+        assert!(report.is_complete, "{:?}", report.out_of_order_events);
+        assert!(report.started_with_message_start);
+        assert!(report.ended_with_message_stop);
+        assert_eq!(report.event_count, 6);
+        assert_eq!(report.output_tokens, 12);
+        assert_eq!(report.stop_reason.as_deref(), Some("end_turn"));
+    }
+
+    #[test]
+    fn test_event_tracker_truncated_stream() {
+        let truncated = &COMPLETE_STREAM[..COMPLETE_STREAM.find("event: content_block_stop").unwrap()];
+        let mut tracker = StreamEventTracker::new();
+        tracker.push(truncated.as_bytes());
+
+        let report = tracker.finish("req-2").unwrap();
+        assert!(!report.is_complete);
+        assert!(report.started_with_message_start);
+        assert!(!report.ended_with_message_stop);
+    }
+
+    #[test]
+    fn test_event_tracker_termination_events_close_stream() {
+        // 在内容块中途截断（含半行数据）
+        let cut = COMPLETE_STREAM.find("event: content_block_stop").unwrap() - 5;
+        let mut tracker = StreamEventTracker::new();
+        tracker.push(COMPLETE_STREAM[..cut].as_bytes());
+
+        let tail = tracker.termination_events();
+        assert!(tail.ends_with("event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n"));
+        tracker.push(tail.as_bytes());
+
+        let report = tracker.finish("req-5").unwrap();
+        assert!(report.ended_with_message_stop);
+        assert!(report.out_of_order_events.is_empty(), "{:?}", report.out_of_order_events);
+        assert_eq!(report.stop_reason.as_deref(), Some("end_turn"));
+
+        // 已完整结束的流不再补发事件
+        let mut tracker = StreamEventTracker::new();
+        tracker.push(COMPLETE_STREAM.as_bytes());
+        assert!(tracker.termination_events().is_empty());
+    }
+
+    #[test]
+    fn test_event_tracker_out_of_order_events() {
+        let mut tracker = StreamEventTracker::new();
+        tracker.push(
+            b"event: content_block_delta\ndata: {\"index\":0}\n\n\
+              event: message_start\ndata: {}\n\n\
+              event: message_stop\ndata: {}\n\n\
+              event: message_delta\ndata: {}\n\n",
+        );
+
+        let report = tracker.finish("req-3").unwrap();
+        assert!(!report.is_complete);
+        assert!(!report.started_with_message_start);
+        assert_eq!(
+            report.out_of_order_events,
+            vec![
+                "content_block_delta for unopened block 0".to_string(),
+                "message_delta after message_stop".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_event_tracker_ignores_non_claude_stream() {
+        let mut tracker = StreamEventTracker::new();
+        tracker.push(b"data: {\"choices\":[]}\n\ndata: [DONE]\n\n");
+        assert!(tracker.finish("req-4").is_none());
+    }
 }
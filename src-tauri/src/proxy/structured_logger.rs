@@ -14,6 +14,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 
 use super::protocol_detector::RequestFormat;
+use super::logger::RequestLogEntry;
 
 // ════════════════════════════════════════════════════════════════════════════
 // 请求 ID 生成
@@ -28,6 +29,16 @@ pub fn generate_request_id() -> String {
     format!("req-{}-{:06}", timestamp, counter % 1000000)
 }
 
+tokio::task_local! {
+    /// 当前正在处理的请求 ID（在代理请求处理任务内有效）
+    pub static CURRENT_REQUEST_ID: String;
+}
+
+/// 获取当前任务关联的请求 ID
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
 // ════════════════════════════════════════════════════════════════════════════
 // 日志事件类型
 // ════════════════════════════════════════════════════════════════════════════
@@ -284,6 +295,32 @@ pub struct RequestTracer {
     remote_addr: Option<String>,
     /// 目标 URL
     target_url: Option<String>,
+    /// 已记录的阶段耗时
+    phases: Vec<TracePhase>,
+    /// 已记录的流式块到达时间
+    chunk_arrivals: Vec<TraceChunk>,
+}
+
+/// 请求阶段耗时
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TracePhase {
+    /// 阶段名称 (dns / tcp_connect / tls_handshake / ttfb ...)
+    pub name: String,
+    /// 阶段耗时 (毫秒)
+    pub duration_ms: f64,
+    /// 阶段结束时距请求开始的时间 (毫秒)
+    pub offset_ms: f64,
+}
+
+/// 流式块到达记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceChunk {
+    /// 块序号
+    pub index: u32,
+    /// 到达时距请求开始的时间 (毫秒)
+    pub offset_ms: f64,
+    /// 块大小 (字节)
+    pub size: usize,
 }
 
 impl RequestTracer {
@@ -302,6 +339,8 @@ impl RequestTracer {
             config_name: None,
             remote_addr: None,
             target_url: None,
+            phases: Vec::new(),
+            chunk_arrivals: Vec::new(),
         }
     }
 
@@ -320,6 +359,8 @@ impl RequestTracer {
             config_name: None,
             remote_addr: None,
             target_url: None,
+            phases: Vec::new(),
+            chunk_arrivals: Vec::new(),
         }
     }
 
@@ -379,6 +420,36 @@ impl RequestTracer {
         self.target_url = Some(url.to_string());
     }
 
+    /// 记录一个阶段的耗时
+    pub fn record_phase(&mut self, name: &str, duration: Duration) {
+        self.phases.push(TracePhase {
+            name: name.to_string(),
+            duration_ms: duration.as_secs_f64() * 1000.0,
+            offset_ms: self.elapsed().as_secs_f64() * 1000.0,
+        });
+        self.last_event_time = Instant::now();
+    }
+
+    /// 记录流式块到达
+    pub fn record_chunk_arrival(&mut self, size: usize) {
+        self.chunk_arrivals.push(TraceChunk {
+            index: self.chunk_count,
+            offset_ms: self.elapsed().as_secs_f64() * 1000.0,
+            size,
+        });
+        self.chunk_count += 1;
+    }
+
+    /// 已记录的阶段耗时
+    pub fn phases(&self) -> &[TracePhase] {
+        &self.phases
+    }
+
+    /// 已记录的流式块到达时间
+    pub fn chunk_arrivals(&self) -> &[TraceChunk] {
+        &self.chunk_arrivals
+    }
+
     /// 生成基础日志字段
     fn base_fields(&self) -> LogFields {
         LogFields {
@@ -639,11 +710,51 @@ pub struct PerformanceMetrics {
     pub total_output_tokens: i64,
 }
 
+/// 延迟直方图的桶上界 (毫秒)，最后隐含 +Inf 桶
+pub const LATENCY_BUCKETS_MS: [u64; 10] = [100, 250, 500, 1000, 2500, 5000, 10000, 30000, 60000, 120000];
+
+/// 单个配置的请求指标
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigMetrics {
+    /// 配置名称 (最近一次记录时的名称)
+    pub config_name: String,
+    /// 按状态码统计的请求数
+    pub requests_by_status: BTreeMap<u16, u64>,
+    /// 各延迟桶内的请求数 (非累计，长度为 LATENCY_BUCKETS_MS.len() + 1)
+    pub latency_bucket_counts: Vec<u64>,
+    /// 延迟总和 (毫秒)
+    pub latency_sum_ms: u64,
+    /// 延迟样本数
+    pub latency_count: u64,
+    /// 输入 token 总数
+    pub input_tokens: i64,
+    /// 输出 token 总数
+    pub output_tokens: i64,
+}
+
+impl ConfigMetrics {
+    fn observe_latency(&mut self, latency_ms: u64) {
+        if self.latency_bucket_counts.len() != LATENCY_BUCKETS_MS.len() + 1 {
+            self.latency_bucket_counts = vec![0; LATENCY_BUCKETS_MS.len() + 1];
+        }
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| latency_ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.latency_bucket_counts[bucket] += 1;
+        self.latency_sum_ms += latency_ms;
+        self.latency_count += 1;
+    }
+}
+
+use std::collections::BTreeMap;
 use std::sync::RwLock;
 
 /// 全局性能指标收集器
 pub struct MetricsCollector {
     metrics: RwLock<PerformanceMetrics>,
+    /// 按配置 ID 聚合的请求指标
+    per_config: RwLock<BTreeMap<i64, ConfigMetrics>>,
 }
 
 impl MetricsCollector {
@@ -651,6 +762,40 @@ impl MetricsCollector {
     pub fn new() -> Self {
         Self {
             metrics: RwLock::new(PerformanceMetrics::default()),
+            per_config: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// 记录一条已完成的代理请求 (全局指标 + 按配置指标)
+    ///
+    /// 流式请求应在流结束、token 用量确定后记录
+    pub fn record_entry(&self, entry: &RequestLogEntry) {
+        let input_tokens = entry.token_usage.input_tokens;
+        let output_tokens = entry.token_usage.output_tokens;
+
+        self.record_request(
+            entry.is_success(),
+            entry.latency_ms,
+            entry.is_streaming,
+            input_tokens.map(|t| t as i32),
+            output_tokens.map(|t| t as i32),
+        );
+
+        let Some(config_id) = entry.config_id else {
+            return;
+        };
+        if let Ok(mut per_config) = self.per_config.write() {
+            let metrics = per_config.entry(config_id).or_default();
+            if let Some(name) = &entry.config_name {
+                metrics.config_name = name.clone();
+            }
+            *metrics
+                .requests_by_status
+                .entry(entry.status_code.as_u16())
+                .or_default() += 1;
+            metrics.observe_latency(entry.latency_ms);
+            metrics.input_tokens += input_tokens.unwrap_or(0);
+            metrics.output_tokens += output_tokens.unwrap_or(0);
         }
     }
 
@@ -711,11 +856,22 @@ impl MetricsCollector {
             .unwrap_or_default()
     }
 
+    /// 获取按配置聚合的指标快照 (按配置 ID 排序)
+    pub fn config_snapshot(&self) -> BTreeMap<i64, ConfigMetrics> {
+        self.per_config
+            .read()
+            .map(|m| m.clone())
+            .unwrap_or_default()
+    }
+
     /// 重置指标
     pub fn reset(&self) {
         if let Ok(mut metrics) = self.metrics.write() {
             *metrics = PerformanceMetrics::default();
         }
+        if let Ok(mut per_config) = self.per_config.write() {
+            per_config.clear();
+        }
     }
 }
 
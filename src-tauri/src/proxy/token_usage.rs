@@ -0,0 +1,314 @@
+/**
+ * 请求 Token 用量统计
+ *
+ * 优先使用后端响应中报告的 usage (Claude / OpenAI / Gemini 格式)，
+ * 后端未报告时使用本地分词器估算，并标记为估算值
+ */
+
+use crate::converters::token_estimator::estimate_request_tokens;
+use crate::converters::tokenizer::active_tokenizer;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 流式输出文本的最大累计长度，超出部分按比例折算
+const MAX_TRACKED_OUTPUT_CHARS: usize = 1024 * 1024;
+
+/// 一次请求的 token 用量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// 输入 token 数
+    pub input_tokens: Option<i64>,
+    /// 输出 token 数
+    pub output_tokens: Option<i64>,
+    /// 输入 token 数是否为本地估算 (false 表示后端报告)
+    pub input_tokens_estimated: bool,
+    /// 输出 token 数是否为本地估算 (false 表示后端报告)
+    pub output_tokens_estimated: bool,
+}
+
+/// 后端响应中报告的 token 数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReportedUsage {
+    pub input_tokens: Option<i64>,
+    pub output_tokens: Option<i64>,
+}
+
+impl ReportedUsage {
+    /// 从响应 JSON (或 SSE 事件数据) 中读取 usage
+    ///
+    /// 支持 Claude `usage.input_tokens/output_tokens` (含 message_start 中的 `message.usage`)、
+    /// OpenAI `usage.prompt_tokens/completion_tokens` 与 Gemini `usageMetadata`。
+    /// 值为 0 视为未报告 (部分聚合商以 0 占位)
+    pub fn from_json(value: &Value) -> Self {
+        let read = |obj: &Value, key: &str| obj.get(key).and_then(Value::as_i64).filter(|n| *n > 0);
+
+        let mut reported = Self::default();
+        let usages = [
+            value.get("usage"),
+            value.get("message").and_then(|m| m.get("usage")),
+        ];
+        for usage in usages.into_iter().flatten() {
+            reported.merge(Self {
+                input_tokens: read(usage, "input_tokens").or_else(|| read(usage, "prompt_tokens")),
+                output_tokens: read(usage, "output_tokens").or_else(|| read(usage, "completion_tokens")),
+            });
+        }
+        if let Some(metadata) = value.get("usageMetadata") {
+            reported.merge(Self {
+                input_tokens: read(metadata, "promptTokenCount"),
+                output_tokens: read(metadata, "candidatesTokenCount"),
+            });
+        }
+        reported
+    }
+
+    /// 合并后出现的值 (流式响应中后续事件的累计值覆盖先前的值)
+    pub fn merge(&mut self, other: Self) {
+        self.input_tokens = other.input_tokens.or(self.input_tokens);
+        self.output_tokens = other.output_tokens.or(self.output_tokens);
+    }
+}
+
+impl TokenUsage {
+    /// 合并后端报告值与本地估算值
+    ///
+    /// 仅当请求体是可识别的对话请求 (含 messages) 时才进行估算，
+    /// 避免为模型列表等非生成类请求生成无意义的估算值
+    pub fn resolve(reported: ReportedUsage, request_body: Option<&[u8]>, output_text: &str) -> Self {
+        let mut usage = Self {
+            input_tokens: reported.input_tokens,
+            output_tokens: reported.output_tokens,
+            input_tokens_estimated: false,
+            output_tokens_estimated: false,
+        };
+        if usage.input_tokens.is_some() && usage.output_tokens.is_some() {
+            return usage;
+        }
+
+        let estimate = request_body
+            .and_then(|body| serde_json::from_slice::<Value>(body).ok())
+            .and_then(|body| estimate_request_tokens(&body).ok());
+        let Some(estimate) = estimate else {
+            return usage;
+        };
+
+        if usage.input_tokens.is_none() {
+            usage.input_tokens = Some(estimate.input_tokens as i64);
+            usage.input_tokens_estimated = true;
+        }
+        if usage.output_tokens.is_none() {
+            usage.output_tokens = Some(active_tokenizer().count_tokens(output_text) as i64);
+            usage.output_tokens_estimated = true;
+        }
+        usage
+    }
+
+    /// 根据非流式响应体计算用量
+    pub fn from_response(request_body: Option<&[u8]>, response_body: &[u8]) -> Self {
+        let response = serde_json::from_slice::<Value>(response_body).ok();
+        let reported = response.as_ref().map(ReportedUsage::from_json).unwrap_or_default();
+        let mut output_text = String::new();
+        if let Some(response) = &response {
+            collect_output_text(response, &mut output_text);
+        }
+        Self::resolve(reported, request_body, &output_text)
+    }
+}
+
+/// 提取响应中的生成内容 (文本、思考过程、工具调用参数)
+fn collect_output_text(value: &Value, out: &mut String) {
+    let mut push = |text: Option<&str>| {
+        if let Some(text) = text {
+            out.push_str(text);
+        }
+    };
+
+    // Claude: content 块 / 流式 delta
+    for block in value.get("content").and_then(Value::as_array).into_iter().flatten() {
+        push(block.get("text").and_then(Value::as_str));
+        push(block.get("thinking").and_then(Value::as_str));
+        if let Some(input) = block.get("input").filter(|v| !v.is_null()) {
+            push(Some(&input.to_string()));
+        }
+    }
+    if let Some(delta) = value.get("delta") {
+        push(delta.get("text").and_then(Value::as_str));
+        push(delta.get("thinking").and_then(Value::as_str));
+        push(delta.get("partial_json").and_then(Value::as_str));
+    }
+
+    // OpenAI: choices[].message / choices[].delta
+    for choice in value.get("choices").and_then(Value::as_array).into_iter().flatten() {
+        for message in [choice.get("message"), choice.get("delta")].into_iter().flatten() {
+            push(message.get("content").and_then(Value::as_str));
+            for call in message.get("tool_calls").and_then(Value::as_array).into_iter().flatten() {
+                push(call.pointer("/function/arguments").and_then(Value::as_str));
+            }
+        }
+    }
+
+    // Gemini: candidates[].content.parts[].text
+    for candidate in value.get("candidates").and_then(Value::as_array).into_iter().flatten() {
+        let parts = candidate.pointer("/content/parts").and_then(Value::as_array);
+        for part in parts.into_iter().flatten() {
+            push(part.get("text").and_then(Value::as_str));
+        }
+    }
+}
+
+/// 流式响应 token 用量跟踪器
+///
+/// 逐行解析 SSE `data:` 数据，记录后端报告的 usage 并累计生成文本用于估算
+#[derive(Debug, Default)]
+pub struct StreamUsageTracker {
+    pending: Vec<u8>,
+    reported: ReportedUsage,
+    output_text: String,
+    /// 超出累计上限后未保留的字符数
+    skipped_chars: usize,
+}
+
+impl StreamUsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理一段响应数据
+    pub fn push(&mut self, data: &[u8]) {
+        self.pending.extend_from_slice(data);
+        while let Some(pos) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            self.process_line(&line);
+        }
+    }
+
+    fn process_line(&mut self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let Some(data) = line.trim_end().strip_prefix("data:") else {
+            return;
+        };
+        let Ok(value) = serde_json::from_str::<Value>(data.trim()) else {
+            return;
+        };
+
+        self.reported.merge(ReportedUsage::from_json(&value));
+
+        let mut text = String::new();
+        collect_output_text(&value, &mut text);
+        let room = MAX_TRACKED_OUTPUT_CHARS.saturating_sub(self.output_text.len());
+        if text.len() <= room {
+            self.output_text.push_str(&text);
+        } else {
+            self.skipped_chars += text.chars().count();
+        }
+    }
+
+    /// 流结束时计算最终用量
+    pub fn finish(mut self, request_body: Option<&[u8]>) -> TokenUsage {
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+            self.process_line(&line);
+        }
+
+        let mut usage = TokenUsage::resolve(self.reported, request_body, &self.output_text);
+        if usage.output_tokens_estimated && self.skipped_chars > 0 {
+            // 超出上限的部分按已统计部分的 token/字符比例折算
+            let tracked_chars = self.output_text.chars().count().max(1);
+            let tracked = usage.output_tokens.unwrap_or(0) as f64;
+            let extra = tracked * self.skipped_chars as f64 / tracked_chars as f64;
+            usage.output_tokens = Some((tracked + extra).round() as i64);
+        }
+        usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUEST: &[u8] =
+        br#"{"model":"claude-sonnet-4-5","max_tokens":64,"messages":[{"role":"user","content":"Hello there"}]}"#;
+
+    #[test]
+    fn test_reported_usage_formats() {
+        let claude: Value = serde_json::json!({"usage": {"input_tokens": 12, "output_tokens": 5}});
+        assert_eq!(
+            ReportedUsage::from_json(&claude),
+            ReportedUsage { input_tokens: Some(12), output_tokens: Some(5) }
+        );
+
+        let openai: Value = serde_json::json!({"usage": {"prompt_tokens": 7, "completion_tokens": 3}});
+        assert_eq!(
+            ReportedUsage::from_json(&openai),
+            ReportedUsage { input_tokens: Some(7), output_tokens: Some(3) }
+        );
+
+        let gemini: Value =
+            serde_json::json!({"usageMetadata": {"promptTokenCount": 9, "candidatesTokenCount": 4}});
+        assert_eq!(
+            ReportedUsage::from_json(&gemini),
+            ReportedUsage { input_tokens: Some(9), output_tokens: Some(4) }
+        );
+
+        let zeros: Value = serde_json::json!({"usage": {"input_tokens": 0, "output_tokens": 0}});
+        assert_eq!(ReportedUsage::from_json(&zeros), ReportedUsage::default());
+    }
+
+    #[test]
+    fn test_from_response_prefers_backend_counts() {
+        let response = br#"{"content":[{"type":"text","text":"Hi"}],"usage":{"input_tokens":20,"output_tokens":2}}"#;
+        let usage = TokenUsage::from_response(Some(REQUEST), response);
+        assert_eq!(usage.input_tokens, Some(20));
+        assert_eq!(usage.output_tokens, Some(2));
+        assert!(!usage.input_tokens_estimated);
+        assert!(!usage.output_tokens_estimated);
+    }
+
+    #[test]
+    fn test_from_response_estimates_missing_counts() {
+        let response = br#"{"content":[{"type":"text","text":"Hello, how can I help you today?"}]}"#;
+        let usage = TokenUsage::from_response(Some(REQUEST), response);
+        assert!(usage.input_tokens.unwrap() > 0);
+        assert!(usage.output_tokens.unwrap() > 0);
+        assert!(usage.input_tokens_estimated);
+        assert!(usage.output_tokens_estimated);
+
+        // 仅缺少输出时只估算输出
+        let response = br#"{"content":[{"type":"text","text":"Hi"}],"usage":{"input_tokens":20}}"#;
+        let usage = TokenUsage::from_response(Some(REQUEST), response);
+        assert_eq!(usage.input_tokens, Some(20));
+        assert!(!usage.input_tokens_estimated);
+        assert!(usage.output_tokens_estimated);
+
+        // 非对话请求不估算
+        let usage = TokenUsage::from_response(None, br#"{"data":[]}"#);
+        assert_eq!(usage, TokenUsage::default());
+    }
+
+    #[test]
+    fn test_stream_tracker_reads_claude_usage() {
+        let mut tracker = StreamUsageTracker::new();
+        tracker.push(b"event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":30,\"output_tokens\":1}}}\n\n");
+        tracker.push(b"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi\"}}\n\n");
+        tracker.push(b"event: message_delta\ndata: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":");
+        tracker.push(b"8}}\n\n");
+
+        let usage = tracker.finish(Some(REQUEST));
+        assert_eq!(usage.input_tokens, Some(30));
+        assert_eq!(usage.output_tokens, Some(8));
+        assert!(!usage.input_tokens_estimated);
+        assert!(!usage.output_tokens_estimated);
+    }
+
+    #[test]
+    fn test_stream_tracker_estimates_without_usage() {
+        let mut tracker = StreamUsageTracker::new();
+        tracker.push(b"data: {\"choices\":[{\"delta\":{\"content\":\"Hello \"}}]}\n\n");
+        tracker.push(b"data: {\"choices\":[{\"delta\":{\"content\":\"world\"}}]}\n\ndata: [DONE]\n\n");
+
+        let usage = tracker.finish(Some(REQUEST));
+        assert!(usage.input_tokens_estimated);
+        assert!(usage.output_tokens_estimated);
+        assert_eq!(usage.output_tokens, Some(active_tokenizer().count_tokens("Hello world") as i64));
+    }
+}
@@ -1,6 +1,9 @@
-use crate::models::api_config::{ApiConfig, CreateApiConfigInput, UpdateApiConfigInput, VendorCategory, ProviderType};
+use crate::models::api_config::{AnthropicBetaFilter, ApiConfig, CreateApiConfigInput, GroupModelOverrides, RequestCompression, UpdateApiConfigInput, VendorCategory, ProviderType};
 use crate::models::error::{AppError, AppResult};
-use crate::utils::time::now_rfc3339;
+use crate::models::health_check::HealthCheckMode;
+use crate::proxy::request_compression;
+use crate::utils::server_url::{normalize_extra_query, normalize_server_url, parse_server_url};
+use crate::utils::time::{now_rfc3339, to_rfc3339_utc};
 use rusqlite::{Connection, Row};
 
 /// API 配置管理服务
@@ -16,7 +19,10 @@ pub struct ApiConfigService;
 /// default_model, haiku_model, sonnet_model, opus_model, small_fast_model,
 /// api_timeout_ms, max_output_tokens, balance_query_url, last_balance, balance_currency,
 /// last_balance_check_at, balance_query_status, balance_query_error, auto_balance_check,
-/// balance_check_interval_sec, created_at, updated_at
+/// balance_check_interval_sec, organization_id, created_at, updated_at,
+/// connect_timeout_secs, request_timeout_secs, disabled_until, extra_query, strip_request_fields,
+/// health_check_mode, metadata_user_id_policy, anthropic_beta_filter, api_key_mode,
+/// request_compression, user_agent, last_healthy_at
 #[allow(deprecated)]
 fn map_row_to_config(row: &Row) -> rusqlite::Result<ApiConfig> {
     // 解析 provider_type 字段
@@ -76,10 +82,127 @@ fn map_row_to_config(row: &Row) -> rusqlite::Result<ApiConfig> {
         organization_id: row.get(36).ok(), // OpenAI Organization ID (可选)
         created_at: row.get(37)?,
         updated_at: row.get(38)?,
+        connect_timeout_secs: row.get(39)?,
+        request_timeout_secs: row.get(40)?,
+        disabled_until: row.get(41)?,
+        extra_query: row.get(42)?,
+        strip_request_fields: row.get(43)?,
+        health_check_mode: row.get::<_, String>(44)?.parse().unwrap_or_default(),
+        metadata_user_id_policy: row.get::<_, String>(45)?.parse().unwrap_or_default(),
+        anthropic_beta_filter: row.get(46)?,
+        api_key_mode: row.get::<_, String>(47)?.parse().unwrap_or_default(),
+        request_compression: row.get::<_, String>(48)?.parse().unwrap_or_default(),
+        user_agent: row.get(49)?,
+        last_healthy_at: row.get(50)?,
     })
 }
 
 impl ApiConfigService {
+    /// 校验并规范化用户输入的固定查询参数
+    fn normalize_input_extra_query(query: &str) -> AppResult<Option<String>> {
+        normalize_extra_query(query).map_err(|e| AppError::ValidationError {
+            field: "extra_query".to_string(),
+            message: e,
+        })
+    }
+
+    /// 校验并规范化自定义 User-Agent（空字符串表示透传客户端 UA）
+    fn normalize_input_user_agent(user_agent: &str) -> AppResult<Option<String>> {
+        ApiConfig::normalize_user_agent(user_agent).map_err(|e| AppError::ValidationError {
+            field: "user_agent".to_string(),
+            message: e,
+        })
+    }
+
+    /// 将需要移除的请求字段序列化为 JSON 数组（去除首尾空白与重复项）
+    fn strip_fields_json(fields: &[String]) -> AppResult<String> {
+        let mut normalized: Vec<String> = Vec::with_capacity(fields.len());
+        for field in fields {
+            let field = field.trim().to_string();
+            if !normalized.contains(&field) {
+                normalized.push(field);
+            }
+        }
+        serde_json::to_string(&normalized).map_err(|e| AppError::ValidationError {
+            field: "strip_request_fields".to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// 校验后端是否可以启用请求体压缩
+    ///
+    /// 后端曾以 415 拒绝压缩的请求体时不允许开启压缩
+    fn validate_request_compression(compression: RequestCompression, server_url: &str) -> AppResult<()> {
+        let host = parse_server_url(server_url).host;
+        if compression != RequestCompression::Off && request_compression::is_known_rejecting(&host) {
+            return Err(AppError::ValidationError {
+                field: "request_compression".to_string(),
+                message: format!("后端 {} 曾拒绝压缩的请求体，不能开启请求体压缩", host),
+            });
+        }
+        Ok(())
+    }
+
+    /// 校验配置能否加入分组
+    ///
+    /// 开启了原生 Claude 透传的分组只接受 Claude 提供商的配置
+    fn validate_native_claude_group(conn: &Connection, group_id: i64, provider_type: ProviderType) -> AppResult<()> {
+        if provider_type == ProviderType::Claude {
+            return Ok(());
+        }
+        let native_claude_only: bool = conn
+            .query_row(
+                "SELECT native_claude_only FROM ConfigGroup WHERE id = ?1",
+                [group_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("查询分组透传设置失败: {}", e),
+            })?;
+
+        if native_claude_only {
+            return Err(AppError::ValidationError {
+                field: "provider_type".to_string(),
+                message: format!("分组已开启原生 Claude 透传，不能加入 {} 提供商的配置", provider_type),
+            });
+        }
+        Ok(())
+    }
+
+    /// 将 anthropic-beta 过滤规则序列化为 JSON（原样转发时为 None）
+    fn beta_filter_json(filter: &AnthropicBetaFilter) -> AppResult<Option<String>> {
+        if filter.is_passthrough() {
+            return Ok(None);
+        }
+        let normalized = AnthropicBetaFilter {
+            mode: filter.mode,
+            flags: filter.flags.iter().map(|f| f.trim().to_string()).collect(),
+        };
+        serde_json::to_string(&normalized)
+            .map(Some)
+            .map_err(|e| AppError::ValidationError {
+                field: "anthropic_beta_filter".to_string(),
+                message: e.to_string(),
+            })
+    }
+
+    /// 规范化用户输入的服务器地址
+    fn normalize_input_url(url: &str) -> AppResult<String> {
+        let result = normalize_server_url(url).map_err(|e| AppError::ValidationError {
+            field: "server_url".to_string(),
+            message: e,
+        })?;
+
+        if result.changed {
+            log::info!("服务器地址已规范化: {} -> {}", result.original, result.normalized);
+        }
+        for warning in &result.warnings {
+            log::warn!("服务器地址提示 ({}): {}", result.normalized, warning);
+        }
+
+        Ok(result.normalized)
+    }
+
     /// 创建 API 配置
     ///
     /// # 参数
@@ -92,11 +215,19 @@ impl ApiConfigService {
     pub fn create_config(conn: &Connection, input: &CreateApiConfigInput) -> AppResult<ApiConfig> {
         log::info!("正在创建 API 配置: {}", input.name);
 
+        // 规范化服务器地址，保证保存的值与代理转发规则一致
+        let normalized_input = CreateApiConfigInput {
+            server_url: Self::normalize_input_url(&input.server_url)?,
+            ..input.clone()
+        };
+        let input = &normalized_input;
+
         // 验证输入
         input.validate().map_err(|e| AppError::ValidationError {
             field: "input".to_string(),
             message: e,
         })?;
+        Self::validate_request_compression(input.request_compression.unwrap_or_default(), &input.server_url)?;
 
         // 检查配置名称是否重复
         let exists: bool = conn
@@ -134,6 +265,8 @@ impl ApiConfigService {
                     id: group_id.to_string(),
                 });
             }
+
+            Self::validate_native_claude_group(conn, group_id, input.provider_type.unwrap_or_default())?;
         }
 
         // 获取排序顺序(如果未指定,则使用当前分组的最大值+1)
@@ -171,6 +304,28 @@ impl ApiConfigService {
         let auto_balance_check = input.auto_balance_check.unwrap_or(true);
         let balance_currency = input.balance_currency.as_deref().unwrap_or("CNY");
 
+        // 超时覆盖: 0 视为未设置，使用代理全局设置
+        let connect_timeout_secs = input.connect_timeout_secs.filter(|secs| *secs > 0);
+        let request_timeout_secs = input.request_timeout_secs.filter(|secs| *secs > 0);
+
+        let extra_query = match input.extra_query.as_deref() {
+            Some(query) => Self::normalize_input_extra_query(query)?,
+            None => None,
+        };
+        let user_agent = match input.user_agent.as_deref() {
+            Some(user_agent) => Self::normalize_input_user_agent(user_agent)?,
+            None => None,
+        };
+        let strip_request_fields = input
+            .strip_request_fields
+            .as_deref()
+            .map(Self::strip_fields_json)
+            .transpose()?;
+        let anthropic_beta_filter = match input.anthropic_beta_filter.as_ref() {
+            Some(filter) => Self::beta_filter_json(filter)?,
+            None => None,
+        };
+
         // 插入配置(API密钥直接存储到数据库)
         // 使用命名参数以避免 Rusqlite 的 16 参数限制
         conn.execute(
@@ -179,13 +334,17 @@ impl ApiConfigService {
                                     default_model, haiku_model, sonnet_model, opus_model, small_fast_model,
                                     api_timeout_ms, max_output_tokens,
                                     balance_query_url, auto_balance_check, balance_check_interval_sec, balance_currency,
-                                    created_at, updated_at)
+                                    connect_timeout_secs, request_timeout_secs, extra_query, strip_request_fields,
+                                    health_check_mode, metadata_user_id_policy, anthropic_beta_filter, api_key_mode,
+                                    request_compression, user_agent, created_at, updated_at)
              VALUES (:name, :api_key, :server_url, :server_port, :group_id, :sort_order,
                      :provider_type, :organization_id, :category, :is_partner, :theme_icon, :theme_bg_color, :theme_text_color, :meta,
                      :default_model, :haiku_model, :sonnet_model, :opus_model, :small_fast_model,
                      :api_timeout_ms, :max_output_tokens,
                      :balance_query_url, :auto_balance_check, :balance_check_interval_sec, :balance_currency,
-                     CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+                     :connect_timeout_secs, :request_timeout_secs, :extra_query, :strip_request_fields,
+                     :health_check_mode, :metadata_user_id_policy, :anthropic_beta_filter, :api_key_mode,
+                     :request_compression, :user_agent, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
             rusqlite::named_params! {
                 ":name": &input.name,
                 ":api_key": &input.api_key,
@@ -212,6 +371,16 @@ impl ApiConfigService {
                 ":auto_balance_check": auto_balance_check,
                 ":balance_check_interval_sec": &input.balance_check_interval_sec,
                 ":balance_currency": balance_currency,
+                ":connect_timeout_secs": connect_timeout_secs,
+                ":request_timeout_secs": request_timeout_secs,
+                ":extra_query": extra_query,
+                ":strip_request_fields": strip_request_fields,
+                ":health_check_mode": input.health_check_mode.unwrap_or_default().as_str(),
+                ":metadata_user_id_policy": input.metadata_user_id_policy.unwrap_or_default().as_str(),
+                ":anthropic_beta_filter": anthropic_beta_filter,
+                ":api_key_mode": input.api_key_mode.unwrap_or_default().as_str(),
+                ":request_compression": input.request_compression.unwrap_or_default().as_str(),
+                ":user_agent": user_agent,
             },
         )
         .map_err(|e| AppError::DatabaseError {
@@ -236,7 +405,10 @@ impl ApiConfigService {
                     api_timeout_ms, max_output_tokens,
                     balance_query_url, last_balance, balance_currency, last_balance_check_at,
                     balance_query_status, balance_query_error, auto_balance_check, balance_check_interval_sec,
-                    organization_id, created_at, updated_at
+                    organization_id, created_at, updated_at,
+                    connect_timeout_secs, request_timeout_secs, disabled_until, extra_query,
+                        strip_request_fields, health_check_mode, metadata_user_id_policy, anthropic_beta_filter,
+                        api_key_mode, request_compression, user_agent, last_healthy_at
              FROM ApiConfig WHERE id = ?1",
             [id],
             map_row_to_config,
@@ -271,7 +443,10 @@ impl ApiConfigService {
                         api_timeout_ms, max_output_tokens,
                         balance_query_url, last_balance, balance_currency, last_balance_check_at,
                         balance_query_status, balance_query_error, auto_balance_check, balance_check_interval_sec,
-                        organization_id, created_at, updated_at
+                        organization_id, created_at, updated_at,
+                        connect_timeout_secs, request_timeout_secs, disabled_until, extra_query,
+                        strip_request_fields, health_check_mode, metadata_user_id_policy, anthropic_beta_filter,
+                        api_key_mode, request_compression, user_agent, last_healthy_at
                  FROM ApiConfig WHERE group_id = ?1 ORDER BY sort_order ASC".to_string(),
                 vec![Some(gid)],
             )
@@ -285,7 +460,10 @@ impl ApiConfigService {
                         api_timeout_ms, max_output_tokens,
                         balance_query_url, last_balance, balance_currency, last_balance_check_at,
                         balance_query_status, balance_query_error, auto_balance_check, balance_check_interval_sec,
-                        organization_id, created_at, updated_at
+                        organization_id, created_at, updated_at,
+                        connect_timeout_secs, request_timeout_secs, disabled_until, extra_query,
+                        strip_request_fields, health_check_mode, metadata_user_id_policy, anthropic_beta_filter,
+                        api_key_mode, request_compression, user_agent, last_healthy_at
                  FROM ApiConfig ORDER BY group_id ASC, sort_order ASC".to_string(),
                 vec![],
             )
@@ -332,6 +510,17 @@ impl ApiConfigService {
     pub fn update_config(conn: &Connection, input: &UpdateApiConfigInput) -> AppResult<ApiConfig> {
         log::info!("正在更新 API 配置: ID {}", input.id);
 
+        // 规范化服务器地址，保证保存的值与代理转发规则一致
+        let normalized_input = UpdateApiConfigInput {
+            server_url: input
+                .server_url
+                .as_deref()
+                .map(Self::normalize_input_url)
+                .transpose()?,
+            ..input.clone()
+        };
+        let input = &normalized_input;
+
         // 验证输入
         input.validate().map_err(|e| AppError::ValidationError {
             field: "input".to_string(),
@@ -356,6 +545,28 @@ impl ApiConfigService {
             });
         }
 
+        // 密钥来源与提供商类型任一变化时，按更新后的组合校验
+        if input.api_key_mode.is_some() || input.provider_type.is_some() {
+            let current = Self::get_config_by_id(conn, input.id)?;
+            ApiConfig::validate_api_key_mode(
+                input.api_key_mode.unwrap_or(current.api_key_mode),
+                input.provider_type.unwrap_or(current.provider_type),
+            )
+            .map_err(|e| AppError::ValidationError {
+                field: "api_key_mode".to_string(),
+                message: e,
+            })?;
+        }
+
+        // 压缩方式与后端地址任一变化时，按更新后的组合校验
+        if input.request_compression.is_some() || input.server_url.is_some() {
+            let current = Self::get_config_by_id(conn, input.id)?;
+            Self::validate_request_compression(
+                input.request_compression.unwrap_or(current.request_compression),
+                input.server_url.as_deref().unwrap_or(&current.server_url),
+            )?;
+        }
+
         // 如果更新了名称,检查是否重复
         if let Some(ref name) = input.name {
             let duplicate: bool = conn
@@ -396,6 +607,18 @@ impl ApiConfigService {
             }
         }
 
+        // 分组与提供商类型任一变化时，按更新后的组合校验分组透传限制
+        if input.group_id.is_some() || input.provider_type.is_some() {
+            let current = Self::get_config_by_id(conn, input.id)?;
+            if let Some(group_id) = input.group_id.or(current.group_id) {
+                Self::validate_native_claude_group(
+                    conn,
+                    group_id,
+                    input.provider_type.unwrap_or(current.provider_type),
+                )?;
+            }
+        }
+
         // 构建动态 UPDATE SQL
         let mut updates = Vec::new();
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
@@ -411,15 +634,8 @@ impl ApiConfigService {
         }
 
         if let Some(server_port) = input.server_port {
-            // server_port 字段已弃用，但数据库约束要求值在 1-65535 范围内
-            // 如果传入的值无效，使用默认值 443
-            let valid_port = if server_port >= 1 && server_port <= 65535 {
-                server_port
-            } else {
-                443
-            };
-            updates.push("server_port = ?");
-            params.push(Box::new(valid_port));
+            // server_port 字段已弃用且不参与转发，端口只能通过 server_url 指定
+            log::debug!("忽略已弃用的 server_port: {} (端口请写在 server_url 中)", server_port);
         }
 
         if let Some(group_id) = input.group_id {
@@ -472,6 +688,65 @@ impl ApiConfigService {
             params.push(Box::new(max_output_tokens));
         }
 
+        // 超时覆盖: 0 表示清除覆盖，恢复使用代理全局设置
+        if let Some(secs) = input.connect_timeout_secs {
+            updates.push("connect_timeout_secs = ?");
+            params.push(Box::new(if secs > 0 { Some(secs) } else { None }));
+        }
+
+        if let Some(secs) = input.request_timeout_secs {
+            updates.push("request_timeout_secs = ?");
+            params.push(Box::new(if secs > 0 { Some(secs) } else { None }));
+        }
+
+        // 固定查询参数: 空字符串表示清除
+        if let Some(ref query) = input.extra_query {
+            updates.push("extra_query = ?");
+            params.push(Box::new(Self::normalize_input_extra_query(query)?));
+        }
+
+        // 透传时移除的请求字段: 空列表表示不移除任何字段
+        if let Some(ref fields) = input.strip_request_fields {
+            updates.push("strip_request_fields = ?");
+            params.push(Box::new(Self::strip_fields_json(fields)?));
+        }
+
+        // 定时健康检查的探测方式
+        if let Some(mode) = input.health_check_mode {
+            updates.push("health_check_mode = ?");
+            params.push(Box::new(mode.as_str()));
+        }
+
+        // 转发 Claude 请求时对 metadata.user_id 的处理方式
+        if let Some(policy) = input.metadata_user_id_policy {
+            updates.push("metadata_user_id_policy = ?");
+            params.push(Box::new(policy.as_str()));
+        }
+
+        // anthropic-beta 请求头过滤规则: passthrough 表示清除
+        if let Some(ref filter) = input.anthropic_beta_filter {
+            updates.push("anthropic_beta_filter = ?");
+            params.push(Box::new(Self::beta_filter_json(filter)?));
+        }
+
+        // 转发时使用的 API 密钥来源
+        if let Some(mode) = input.api_key_mode {
+            updates.push("api_key_mode = ?");
+            params.push(Box::new(mode.as_str()));
+        }
+
+        // 发往后端的请求体压缩方式
+        if let Some(compression) = input.request_compression {
+            updates.push("request_compression = ?");
+            params.push(Box::new(compression.as_str()));
+        }
+
+        // 覆盖发往后端的 User-Agent: 空字符串表示清除
+        if let Some(ref user_agent) = input.user_agent {
+            updates.push("user_agent = ?");
+            params.push(Box::new(Self::normalize_input_user_agent(user_agent)?));
+        }
+
         // 如果更新了 API 密钥,更新数据库
         if let Some(ref api_key) = input.api_key {
             updates.push("api_key = ?");
@@ -562,6 +837,87 @@ impl ApiConfigService {
         Self::get_config_by_id(conn, input.id)
     }
 
+    /// 批量设置分组内所有配置的健康检查探测方式
+    ///
+    /// # 参数
+    /// - `conn`: 数据库连接
+    /// - `group_id`: 分组ID
+    /// - `mode`: 探测方式
+    ///
+    /// # 返回
+    /// - `Ok(usize)`: 更新的配置数量
+    pub fn set_group_health_check_mode(
+        conn: &Connection,
+        group_id: i64,
+        mode: HealthCheckMode,
+    ) -> AppResult<usize> {
+        let updated = conn
+            .execute(
+                "UPDATE ApiConfig SET health_check_mode = ?1, updated_at = CURRENT_TIMESTAMP WHERE group_id = ?2",
+                rusqlite::params![mode.as_str(), group_id],
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("更新健康检查探测方式失败: {}", e),
+            })?;
+
+        log::info!(
+            "分组 {} 的 {} 个配置健康检查探测方式已设置为 {}",
+            group_id,
+            updated,
+            mode.as_str()
+        );
+        Ok(updated)
+    }
+
+    /// 批量设置分组内所有配置的模型覆盖（单个事务）
+    ///
+    /// # 参数
+    /// - `conn`: 数据库连接
+    /// - `group_id`: 分组ID
+    /// - `overrides`: 模型覆盖，`only_fill_blanks` 为 true 时只填充未设置的字段
+    ///
+    /// # 返回
+    /// - `Ok(Vec<ApiConfig>)`: 更新后的分组配置列表
+    pub fn set_group_model_overrides(
+        conn: &Connection,
+        group_id: i64,
+        overrides: &GroupModelOverrides,
+    ) -> AppResult<Vec<ApiConfig>> {
+        let tx = conn.unchecked_transaction().map_err(|e| AppError::DatabaseError {
+            message: format!("开启事务失败: {}", e),
+        })?;
+
+        for (column, value) in overrides.columns() {
+            // 列名来自固定列表，可以安全拼接
+            let blank_filter = if overrides.only_fill_blanks {
+                format!(" AND ({0} IS NULL OR TRIM({0}) = '')", column)
+            } else {
+                String::new()
+            };
+            let sql = format!(
+                "UPDATE ApiConfig SET {} = ?1, updated_at = CURRENT_TIMESTAMP WHERE group_id = ?2{}",
+                column, blank_filter
+            );
+            let updated = tx
+                .execute(&sql, rusqlite::params![value, group_id])
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("更新模型覆盖 {} 失败: {}", column, e),
+                })?;
+            log::debug!("分组 {} 更新 {}: {} 个配置", group_id, column, updated);
+        }
+
+        tx.commit().map_err(|e| AppError::DatabaseError {
+            message: format!("提交事务失败: {}", e),
+        })?;
+
+        log::info!(
+            "分组 {} 的模型覆盖已批量更新 (只填充空值: {})",
+            group_id,
+            overrides.only_fill_blanks
+        );
+        Self::list_configs(conn, Some(group_id))
+    }
+
     /// 删除 API 配置
     ///
     /// # 参数
@@ -607,12 +963,28 @@ impl ApiConfigService {
             log::info!("已删除 {} 条引用该配置的切换日志", deleted_logs);
         }
 
+        // 记录所属分组，删除后用于整理排序
+        let group_id: Option<i64> = conn
+            .query_row(
+                "SELECT group_id FROM ApiConfig WHERE id = ?1",
+                [config_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("获取配置分组失败: {}", e),
+            })?;
+
         // 删除数据库中的配置
         conn.execute("DELETE FROM ApiConfig WHERE id = ?1", [config_id])
             .map_err(|e| AppError::DatabaseError {
                 message: format!("删除配置失败: {}", e),
             })?;
 
+        // 删除会在分组内留下排序空洞，顺便整理 (失败不影响删除结果)
+        if let Err(e) = Self::normalize_sort_order(conn, group_id) {
+            log::warn!("删除后整理排序失败 (group_id={:?}): {}", group_id, e);
+        }
+
         log::info!("API 配置已删除: ID {}", config_id);
         Ok(())
     }
@@ -639,13 +1011,13 @@ impl ApiConfigService {
         );
 
         // 检查配置是否存在
-        let (exists, old_order, group_id): (bool, i32, Option<i64>) = conn
+        let (exists, group_id): (bool, Option<i64>) = conn
             .query_row(
-                "SELECT 1, sort_order, group_id FROM ApiConfig WHERE id = ?1",
+                "SELECT 1, group_id FROM ApiConfig WHERE id = ?1",
                 [config_id],
-                |row| Ok((true, row.get(1)?, row.get(2)?)),
+                |row| Ok((true, row.get(1)?)),
             )
-            .unwrap_or((false, 0, None));
+            .unwrap_or((false, None));
 
         if !exists {
             return Err(AppError::NotFound {
@@ -654,48 +1026,107 @@ impl ApiConfigService {
             });
         }
 
-        // 如果排序顺序没有变化,直接返回
-        if old_order == new_sort_order {
-            return Ok(());
+        if new_sort_order < 0 {
+            return Err(AppError::ValidationError {
+                field: "new_sort_order".to_string(),
+                message: "排序顺序必须大于等于 0".to_string(),
+            });
         }
 
-        // 更新其他配置的排序顺序
-        // 向上移动: old_order=5, new_order=2 -> [2,5) 的配置 +1
-        // 向下移动: old_order=2, new_order=5 -> (2,5] 的配置 -1
-        if new_sort_order < old_order {
-            // 向上移动
-            conn.execute(
-                "UPDATE ApiConfig
-                 SET sort_order = sort_order + 1
-                 WHERE group_id IS ?1 AND sort_order >= ?2 AND sort_order < ?3",
-                (group_id, new_sort_order, old_order),
+        // 基于当前相对顺序整体移动，而非对区间做 +1/-1 运算，
+        // 这样即使分组内已存在重复或不连续的 sort_order 也能得到正确结果
+        // (超出范围的目标位置会移动到末尾，完成后分组排序为 0..n-1)
+        let tx = conn.unchecked_transaction().map_err(|e| AppError::DatabaseError {
+            message: format!("开启事务失败: {}", e),
+        })?;
+
+        let mut ordered: Vec<i64> = Self::list_sort_orders(&tx, group_id)?
+            .into_iter()
+            .map(|(id, _)| id)
+            .filter(|id| *id != config_id)
+            .collect();
+        let target_index = (new_sort_order as usize).min(ordered.len());
+        ordered.insert(target_index, config_id);
+
+        Self::write_sort_orders(&tx, &ordered)?;
+
+        tx.commit().map_err(|e| AppError::DatabaseError {
+            message: format!("提交事务失败: {}", e),
+        })?;
+
+        log::info!("配置重新排序完成: ID {}", config_id);
+        Ok(())
+    }
+
+    /// 整理分组内的排序顺序
+    ///
+    /// 多次重新排序/删除后 sort_order 可能出现空洞或重复，
+    /// 按当前相对顺序 (sort_order, id) 重新编号为 0..n-1。
+    ///
+    /// # 参数
+    /// - `conn`: 数据库连接
+    /// - `group_id`: 分组ID (None 表示未分组的配置)
+    ///
+    /// # 返回
+    /// - `Ok(usize)`: 实际被修改排序的配置数量 (0 表示原本已连续)
+    /// - `Err(AppError)`: 整理失败
+    pub fn normalize_sort_order(conn: &Connection, group_id: Option<i64>) -> AppResult<usize> {
+        let tx = conn.unchecked_transaction().map_err(|e| AppError::DatabaseError {
+            message: format!("开启事务失败: {}", e),
+        })?;
+
+        let current = Self::list_sort_orders(&tx, group_id)?;
+        let changed = current
+            .iter()
+            .enumerate()
+            .filter(|(index, (_, order))| *order != *index as i32)
+            .count();
+
+        if changed > 0 {
+            let ordered: Vec<i64> = current.into_iter().map(|(id, _)| id).collect();
+            Self::write_sort_orders(&tx, &ordered)?;
+            log::info!("分组排序已整理: group_id={:?}, 修改 {} 个配置", group_id, changed);
+        }
+
+        tx.commit().map_err(|e| AppError::DatabaseError {
+            message: format!("提交事务失败: {}", e),
+        })?;
+
+        Ok(changed)
+    }
+
+    /// 按当前顺序列出分组内配置的 (id, sort_order)
+    fn list_sort_orders(conn: &Connection, group_id: Option<i64>) -> AppResult<Vec<(i64, i32)>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, sort_order FROM ApiConfig
+                 WHERE group_id IS ?1
+                 ORDER BY sort_order ASC, id ASC",
             )
             .map_err(|e| AppError::DatabaseError {
-                message: format!("更新排序顺序失败: {}", e),
+                message: format!("准备查询失败: {}", e),
             })?;
-        } else {
-            // 向下移动
+
+        stmt.query_map([group_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("查询排序顺序失败: {}", e),
+            })
+    }
+
+    /// 按列表顺序写入 0..n-1 的排序值 (仅更新发生变化的行)
+    fn write_sort_orders(conn: &Connection, ordered_ids: &[i64]) -> AppResult<()> {
+        for (index, id) in ordered_ids.iter().enumerate() {
             conn.execute(
-                "UPDATE ApiConfig
-                 SET sort_order = sort_order - 1
-                 WHERE group_id IS ?1 AND sort_order > ?2 AND sort_order <= ?3",
-                (group_id, old_order, new_sort_order),
+                "UPDATE ApiConfig SET sort_order = ?1, updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?2 AND sort_order != ?1",
+                (index as i32, id),
             )
             .map_err(|e| AppError::DatabaseError {
-                message: format!("更新排序顺序失败: {}", e),
+                message: format!("更新配置排序失败: {}", e),
             })?;
         }
 
-        // 更新目标配置的排序顺序
-        conn.execute(
-            "UPDATE ApiConfig SET sort_order = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
-            (new_sort_order, config_id),
-        )
-        .map_err(|e| AppError::DatabaseError {
-            message: format!("更新配置排序失败: {}", e),
-        })?;
-
-        log::info!("配置重新排序完成: ID {}", config_id);
         Ok(())
     }
 
@@ -796,6 +1227,126 @@ impl ApiConfigService {
         Self::get_config_by_id(conn, config_id)
     }
 
+    /// 定时停用配置直到指定时间
+    ///
+    /// 停用期内配置标记为不可用，不参与自动切换；到期后由调度器自动恢复
+    ///
+    /// # 参数
+    /// - `conn`: 数据库连接
+    /// - `config_id`: 配置ID
+    /// - `until`: 停用截止时间（RFC3339，必须晚于当前时间）
+    pub fn set_disabled_until(conn: &Connection, config_id: i64, until: &str) -> AppResult<ApiConfig> {
+        let until = ApiConfig::validate_disabled_until(until, chrono::Utc::now())
+            .map_err(|e| AppError::ValidationError {
+                field: "disabled_until".to_string(),
+                message: e,
+            })
+            .map(|until| to_rfc3339_utc(&until))?;
+
+        let updated = conn
+            .execute(
+                "UPDATE ApiConfig SET disabled_until = ?1, is_available = 0, updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?2",
+                (&until, config_id),
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("设置定时停用失败: {}", e),
+            })?;
+
+        if updated == 0 {
+            return Err(AppError::NotFound {
+                resource: "ApiConfig".to_string(),
+                id: config_id.to_string(),
+            });
+        }
+
+        log::info!("配置已定时停用: ID {} 直到 {}", config_id, until);
+
+        Self::get_config_by_id(conn, config_id)
+    }
+
+    /// 清除配置的定时停用并恢复可用状态
+    pub fn clear_disabled_until(conn: &Connection, config_id: i64) -> AppResult<ApiConfig> {
+        let updated = conn
+            .execute(
+                "UPDATE ApiConfig SET disabled_until = NULL, is_available = 1, updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?1",
+                [config_id],
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("清除定时停用失败: {}", e),
+            })?;
+
+        if updated == 0 {
+            return Err(AppError::NotFound {
+                resource: "ApiConfig".to_string(),
+                id: config_id.to_string(),
+            });
+        }
+
+        log::info!("配置定时停用已清除: ID {}", config_id);
+
+        Self::get_config_by_id(conn, config_id)
+    }
+
+    /// 手动重置配置的失败与可用状态
+    ///
+    /// 清零连续失败次数并标记为可用；不影响用户设置的启用状态和定时停用
+    pub fn reset_config_state(conn: &Connection, config_id: i64) -> AppResult<ApiConfig> {
+        let updated = conn
+            .execute(
+                "UPDATE ApiConfig SET consecutive_failures = 0, is_available = 1, updated_at = ?1
+                 WHERE id = ?2",
+                (now_rfc3339(), config_id),
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("重置配置状态失败: {}", e),
+            })?;
+
+        if updated == 0 {
+            return Err(AppError::NotFound {
+                resource: "ApiConfig".to_string(),
+                id: config_id.to_string(),
+            });
+        }
+
+        log::info!("配置失败状态已手动重置: ID {}", config_id);
+
+        Self::get_config_by_id(conn, config_id)
+    }
+
+    /// 恢复所有定时停用已到期的配置
+    ///
+    /// # 返回
+    /// - `Ok(Vec<i64>)`: 已恢复的配置ID列表
+    pub fn restore_expired_disabled_configs(conn: &Connection) -> AppResult<Vec<i64>> {
+        let mut stmt = conn
+            .prepare(
+                "UPDATE ApiConfig SET disabled_until = NULL, is_available = 1, updated_at = CURRENT_TIMESTAMP
+                 WHERE disabled_until IS NOT NULL AND julianday(disabled_until) <= julianday('now')
+                 RETURNING id",
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("准备恢复定时停用配置失败: {}", e),
+            })?;
+
+        let restored = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("恢复定时停用配置失败: {}", e),
+            })?
+            .collect::<Result<Vec<i64>, _>>()
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("解析恢复结果失败: {}", e),
+            })?;
+
+        if !restored.is_empty() {
+            log::info!("定时停用已到期，已恢复配置: {:?}", restored);
+        }
+
+        Ok(restored)
+    }
+
     /// 更新配置的权重分数
     ///
     /// # 参数
@@ -830,7 +1381,7 @@ impl ApiConfigService {
 
     /// 更新配置的成功状态（请求成功时调用）
     ///
-    /// 重置连续失败次数，更新最后成功时间
+    /// 重置连续失败次数，更新最后成功时间与最后健康时间
     ///
     /// # 参数
     /// - `conn`: 数据库连接
@@ -846,6 +1397,7 @@ impl ApiConfigService {
             "UPDATE ApiConfig SET
                 consecutive_failures = 0,
                 last_success_time = ?1,
+                last_healthy_at = ?1,
                 is_available = 1,
                 updated_at = ?1
              WHERE id = ?2",
@@ -860,6 +1412,21 @@ impl ApiConfigService {
         Ok(())
     }
 
+    /// 更新配置的最后健康时间（健康检查成功时调用）
+    ///
+    /// 只刷新权重时间衰减使用的 last_healthy_at，不改动代理请求相关的状态
+    pub fn record_healthy(conn: &Connection, config_id: i64) -> AppResult<()> {
+        conn.execute(
+            "UPDATE ApiConfig SET last_healthy_at = ?1 WHERE id = ?2",
+            (now_rfc3339(), config_id),
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("记录健康状态失败: {}", e),
+        })?;
+
+        Ok(())
+    }
+
     /// 增加配置的连续失败次数
     ///
     /// # 参数
@@ -920,9 +1487,13 @@ impl ApiConfigService {
                         api_timeout_ms, max_output_tokens,
                         balance_query_url, last_balance, balance_currency, last_balance_check_at,
                         balance_query_status, balance_query_error, auto_balance_check, balance_check_interval_sec,
-                        organization_id, created_at, updated_at
+                        organization_id, created_at, updated_at,
+                        connect_timeout_secs, request_timeout_secs, disabled_until, extra_query,
+                        strip_request_fields, health_check_mode, metadata_user_id_policy, anthropic_beta_filter,
+                        api_key_mode, request_compression, user_agent, last_healthy_at
                  FROM ApiConfig
                  WHERE group_id = ?1 AND is_enabled = 1 AND is_available = 1
+                   AND (disabled_until IS NULL OR julianday(disabled_until) <= julianday('now'))
                  ORDER BY weight_score DESC, sort_order ASC",
             )
             .map_err(|e| AppError::DatabaseError {
@@ -1222,3 +1793,201 @@ mod tests {
         assert!(result.is_err());
     }
 }
+
+#[cfg(test)]
+mod sort_order_tests {
+    use super::*;
+
+    fn setup_conn() -> Connection {
+        let conn = crate::db::test_db();
+        conn.execute("INSERT INTO ConfigGroup (id, name) VALUES (1, 'g')", [])
+            .unwrap();
+        conn
+    }
+
+    fn insert_config(conn: &Connection, id: i64, sort_order: i32) {
+        conn.execute(
+            "INSERT INTO ApiConfig (id, name, api_key, server_url, server_port, group_id, sort_order)
+             VALUES (?1, ?2, 'k', 'https://example.com', 443, 1, ?3)",
+            (id, format!("config-{}", id), sort_order),
+        )
+        .unwrap();
+    }
+
+    fn orders(conn: &Connection) -> Vec<(i64, i32)> {
+        ApiConfigService::list_sort_orders(conn, Some(1)).unwrap()
+    }
+
+    #[test]
+    fn test_set_group_model_overrides() {
+        let conn = setup_conn();
+        insert_config(&conn, 10, 0);
+        insert_config(&conn, 11, 1);
+        conn.execute("INSERT INTO ConfigGroup (id, name) VALUES (2, 'other')", []).unwrap();
+        conn.execute(
+            "INSERT INTO ApiConfig (id, name, api_key, server_url, server_port, group_id, sonnet_model)
+             VALUES (20, 'other', 'k', 'https://example.com', 443, 2, 'keep')",
+            [],
+        )
+        .unwrap();
+        conn.execute("UPDATE ApiConfig SET sonnet_model = NULL, opus_model = '' WHERE group_id = 1", []).unwrap();
+        conn.execute("UPDATE ApiConfig SET sonnet_model = 'custom' WHERE id = 11", []).unwrap();
+
+        let model = |configs: &[ApiConfig], id: i64| {
+            let config = configs.iter().find(|c| c.id == id).unwrap();
+            (config.sonnet_model.clone(), config.opus_model.clone())
+        };
+
+        // 只填充空值
+        let configs = ApiConfigService::set_group_model_overrides(&conn, 1, &GroupModelOverrides {
+            sonnet_model: Some("sonnet-v2".to_string()),
+            opus_model: Some("opus-v2".to_string()),
+            only_fill_blanks: true,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(configs.len(), 2);
+        assert_eq!(model(&configs, 10), (Some("sonnet-v2".to_string()), Some("opus-v2".to_string())));
+        assert_eq!(model(&configs, 11), (Some("custom".to_string()), Some("opus-v2".to_string())));
+
+        // 覆盖全部，空字符串清除
+        let configs = ApiConfigService::set_group_model_overrides(&conn, 1, &GroupModelOverrides {
+            sonnet_model: Some("sonnet-v3".to_string()),
+            opus_model: Some(String::new()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(model(&configs, 11), (Some("sonnet-v3".to_string()), None));
+
+        // 其他分组不受影响
+        let other = ApiConfigService::get_config_by_id(&conn, 20).unwrap();
+        assert_eq!(other.sonnet_model.as_deref(), Some("keep"));
+    }
+
+    #[test]
+    fn test_normalize_duplicates_and_gaps() {
+        let conn = setup_conn();
+        insert_config(&conn, 10, 5);
+        insert_config(&conn, 11, 2);
+        insert_config(&conn, 12, 2);
+        insert_config(&conn, 13, 9);
+
+        let changed = ApiConfigService::normalize_sort_order(&conn, Some(1)).unwrap();
+        assert_eq!(changed, 4);
+        assert_eq!(orders(&conn), vec![(11, 0), (12, 1), (10, 2), (13, 3)]);
+
+        // 再次整理应保持稳定
+        assert_eq!(ApiConfigService::normalize_sort_order(&conn, Some(1)).unwrap(), 0);
+        assert_eq!(orders(&conn), vec![(11, 0), (12, 1), (10, 2), (13, 3)]);
+    }
+
+    #[test]
+    fn test_reorder_with_duplicates_and_delete() {
+        let conn = setup_conn();
+        insert_config(&conn, 10, 1);
+        insert_config(&conn, 11, 1);
+        insert_config(&conn, 12, 1);
+
+        ApiConfigService::reorder_config(&conn, 12, 0).unwrap();
+        assert_eq!(orders(&conn), vec![(12, 0), (10, 1), (11, 2)]);
+
+        ApiConfigService::reorder_config(&conn, 12, 99).unwrap();
+        assert_eq!(orders(&conn), vec![(10, 0), (11, 1), (12, 2)]);
+
+        ApiConfigService::delete_config(&conn, 10).unwrap();
+        assert_eq!(orders(&conn), vec![(11, 0), (12, 1)]);
+    }
+
+    #[test]
+    fn test_reset_config_state() {
+        let conn = setup_conn();
+        insert_config(&conn, 10, 0);
+        conn.execute(
+            "UPDATE ApiConfig SET consecutive_failures = 5, is_available = 0 WHERE id = 10",
+            [],
+        )
+        .unwrap();
+
+        let config = ApiConfigService::reset_config_state(&conn, 10).unwrap();
+        assert_eq!(config.consecutive_failures, 0);
+        assert!(config.is_available);
+
+        assert!(matches!(
+            ApiConfigService::reset_config_state(&conn, 99),
+            Err(AppError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_restore_expired_disabled_configs() {
+        let conn = setup_conn();
+        for id in 10..=13 {
+            insert_config(&conn, id, (id - 10) as i32);
+        }
+
+        // 10 尚未到期；11、12 已到期（设置接口不接受过去的时间，直接写入）；13 未定时停用
+        let future = to_rfc3339_utc(&(chrono::Utc::now() + chrono::Duration::hours(1)));
+        ApiConfigService::set_disabled_until(&conn, 10, &future).unwrap();
+        let past = to_rfc3339_utc(&(chrono::Utc::now() - chrono::Duration::minutes(1)));
+        conn.execute(
+            "UPDATE ApiConfig SET disabled_until = ?1, is_available = 0 WHERE id IN (11, 12)",
+            [&past],
+        )
+        .unwrap();
+
+        let mut restored = ApiConfigService::restore_expired_disabled_configs(&conn).unwrap();
+        restored.sort();
+        assert_eq!(restored, vec![11, 12]);
+
+        let expired = ApiConfigService::get_config_by_id(&conn, 11).unwrap();
+        assert!(expired.is_available);
+        assert_eq!(expired.disabled_until, None);
+
+        let pending = ApiConfigService::get_config_by_id(&conn, 10).unwrap();
+        assert!(!pending.is_available);
+        assert_eq!(pending.disabled_until.as_deref(), Some(future.as_str()));
+        assert!(ApiConfigService::get_config_by_id(&conn, 13).unwrap().is_available);
+
+        // 已恢复的配置不会重复返回
+        assert!(ApiConfigService::restore_expired_disabled_configs(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_native_claude_only_group_rejects_other_providers() {
+        use crate::services::config_manager::ConfigManager;
+
+        let conn = setup_conn();
+        insert_config(&conn, 10, 0);
+        insert_config(&conn, 11, 1);
+        conn.execute("UPDATE ApiConfig SET provider_type = 'gemini' WHERE id = 11", []).unwrap();
+
+        // 分组内仍有非 Claude 配置时不能开启
+        let mut group = ConfigManager::get_group_by_id(&conn, 1).unwrap();
+        group.native_claude_only = true;
+        assert!(matches!(
+            ConfigManager::update_group(&conn, &group),
+            Err(AppError::ValidationError { ref field, .. }) if field == "native_claude_only"
+        ));
+
+        ApiConfigService::delete_config(&conn, 11).unwrap();
+        assert!(ConfigManager::update_group(&conn, &group).unwrap().native_claude_only);
+
+        // 开启后不能把配置改为其他提供商
+        let result = ApiConfigService::update_config(&conn, &UpdateApiConfigInput {
+            id: 10,
+            provider_type: Some(ProviderType::Gemini),
+            ..Default::default()
+        });
+        assert!(matches!(result, Err(AppError::ValidationError { ref field, .. }) if field == "provider_type"));
+        assert_eq!(
+            ApiConfigService::update_config(&conn, &UpdateApiConfigInput {
+                id: 10,
+                group_id: Some(1),
+                ..Default::default()
+            })
+            .unwrap()
+            .provider_type,
+            ProviderType::Claude
+        );
+    }
+}
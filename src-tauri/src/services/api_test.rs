@@ -5,16 +5,26 @@
  * Features:
  * - 单个配置测试
  * - 批量分组测试
+ * - 仅测试长时间未测试的配置
  * - 延迟测量
  * - 结果记录
  */
 
 use crate::db::DbPool;
+use crate::models::api_config::ApiKeyMode;
 use crate::models::error::{AppError, AppResult};
-use crate::models::test_result::{TestResult, TestStatus};
+use crate::models::provider_preset::{PresetValidationResult, ProviderPreset};
+use crate::models::test_result::{StaleConfigTestResult, TestResult, TestStatus, TimingBreakdown};
+use crate::proxy::router::RequestRouter;
 use crate::services::api_config::ApiConfigService;
-use crate::services::claude_test_request::{add_claude_code_headers, build_test_request_body, TEST_REQUEST_TIMEOUT_SECS};
+use crate::services::latency_test::LatencyTestService;
+use crate::services::provider_preset::ProviderPresetService;
+use crate::services::claude_test_request::{
+    add_claude_code_headers, build_test_request_body, claude_code_headers, TEST_REQUEST_TIMEOUT_SECS,
+};
+use crate::utils::server_url::normalize_server_url;
 use crate::utils::time::now_rfc3339;
+use futures_util::stream::{self, StreamExt};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tauri::AppHandle;
@@ -24,6 +34,15 @@ use tokio::time::timeout;
 /// API 测试超时时间(秒)
 const TEST_TIMEOUT_SECS: u64 = TEST_REQUEST_TIMEOUT_SECS;
 
+/// 连接耗时分解测量超时时间(毫秒)
+const TIMING_TIMEOUT_MS: u64 = 5000;
+
+/// 批量测试时同时进行的测试数量上限
+const MAX_CONCURRENT_CONFIG_TESTS: usize = 4;
+
+/// 经代理测试时客户端使用的占位令牌（转发时会被替换为配置的 API 密钥）
+const PROXY_TEST_CLIENT_TOKEN: &str = "proxy-pipeline-test";
+
 /// API 测试响应结构
 struct ApiTestResponse {
     response_text: String,
@@ -75,13 +94,10 @@ fn parse_api_error(response_text: &str, status_code: u16) -> String {
     }
 }
 
-/// 检查错误是否不应该重试
-/// 认证错误、配额错误等不会因为换模型而解决，不应重试
-fn is_non_retryable_error(error: &str) -> bool {
+/// 检查错误是否为认证失败
+fn is_auth_error(error: &str) -> bool {
     let error_lower = error.to_lowercase();
-
-    // 认证相关错误
-    if error_lower.contains("authentication")
+    error_lower.contains("authentication")
         || error_lower.contains("auth")
         || error_lower.contains("api key")
         || error_lower.contains("apikey")
@@ -92,7 +108,15 @@ fn is_non_retryable_error(error: &str) -> bool {
         || error_lower.contains("403")
         || error_lower.contains("invalid_api_key")
         || error_lower.contains("unauthorized")
-    {
+}
+
+/// 检查错误是否不应该重试
+/// 认证错误、配额错误等不会因为换模型而解决，不应重试
+fn is_non_retryable_error(error: &str) -> bool {
+    let error_lower = error.to_lowercase();
+
+    // 认证相关错误
+    if is_auth_error(error) {
         return true;
     }
 
@@ -124,7 +148,8 @@ fn is_non_retryable_error(error: &str) -> bool {
 
 /// 检查响应体是否包含错误信息（即使 HTTP 状态码是 200）
 /// 一些代理服务商会返回 HTTP 200 但在响应体中包含错误
-fn check_response_body_error(response_text: &str) -> Option<String> {
+/// 代理转发的成功响应也复用此检测，将这类"软错误"计为失败
+pub(crate) fn check_response_body_error(response_text: &str) -> Option<String> {
     // 尝试解析 JSON 错误响应
     if let Ok(json) = serde_json::from_str::<serde_json::Value>(response_text) {
         // 检查 error 字段
@@ -171,6 +196,9 @@ fn check_response_body_error(response_text: &str) -> Option<String> {
                 return Some("服务商返回错误类型响应".to_string());
             }
         }
+
+        // 合法 JSON 且无顶层错误字段：内容中（如工具调用参数）出现的 error/message 不视为错误
+        return None;
     }
 
     // 检查响应是否包含明显的错误关键词（非 JSON 情况）
@@ -182,6 +210,14 @@ fn check_response_body_error(response_text: &str) -> Option<String> {
     None
 }
 
+/// 判断配置的上次测试是否早于截止时间（从未测试或时间无法解析视为过期）
+fn is_test_stale(last_test_at: Option<&str>, cutoff: chrono::DateTime<chrono::Local>) -> bool {
+    match last_test_at.and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok()) {
+        Some(tested_at) => tested_at < cutoff,
+        None => true,
+    }
+}
+
 /// 提取 URL 的基础部分（scheme://host:port），移除路径
 #[allow(dead_code)]
 fn extract_base_url(url: &str) -> String {
@@ -260,6 +296,12 @@ impl ApiTestService {
         let api_key = &config.api_key;
         let user_model = config.default_model.as_deref();
 
+        // 透传客户端密钥的配置没有可用于测试的密钥，只检测连通性
+        if config.api_key_mode == ApiKeyMode::Client {
+            let test_result = self.test_reachability(config_id, &config.server_url).await;
+            return self.finish_test(test_result).await;
+        }
+
         // 检查 API Key 是否为空
         if api_key.is_empty() {
             log::error!("❌ 配置 {} 的 API Key 为空!", config.name);
@@ -275,17 +317,32 @@ impl ApiTestService {
         log::debug!("🔑 API Key 长度: {} 字符", api_key.len());
 
         // 第一次尝试：使用 haiku（最快最便宜）
+        // 同时测量连接阶段耗时（DNS / TCP / TLS / 首字节），失败不影响 API 测试，
+        // 并发执行避免后端不可达时测试额外等待探测超时
         let start_time = Instant::now();
-        let first_result = timeout(
-            Duration::from_secs(TEST_TIMEOUT_SECS),
-            self.perform_api_test(&config.server_url, &api_key, Some("claude-haiku-4-5-20251001")),
-        )
-        .await;
+        let (timing, (first_result, first_elapsed)) = tokio::join!(
+            LatencyTestService::measure_timing(&config.server_url, Some(TIMING_TIMEOUT_MS)),
+            async {
+                let result = timeout(
+                    Duration::from_secs(TEST_TIMEOUT_SECS),
+                    self.perform_api_test(&config.server_url, &api_key, Some("claude-haiku-4-5-20251001")),
+                )
+                .await;
+                (result, start_time.elapsed())
+            },
+        );
+        let timing = match timing {
+            Ok(timing) => Some(timing),
+            Err(e) => {
+                log::warn!("Config {} timing breakdown failed: {}", config_id, e);
+                None
+            }
+        };
 
-        let test_result = match first_result {
+        let mut test_result = match first_result {
             // 第一次成功
             Ok(Ok(response)) => {
-                let latency_ms = start_time.elapsed().as_millis() as i64;
+                let latency_ms = first_elapsed.as_millis() as i64;
                 log::info!(
                     "Config {} test passed (attempt 1), latency: {}ms",
                     config_id,
@@ -370,7 +427,7 @@ impl ApiTestService {
             }
             // 第一次失败，不进行重试（用户未指定模型、模型相同、或错误不可重试）
             Ok(Err(e)) => {
-                let latency_ms = start_time.elapsed().as_millis() as i64;
+                let latency_ms = first_elapsed.as_millis() as i64;
 
                 // 记录跳过重试的原因
                 if is_non_retryable_error(&e) {
@@ -412,6 +469,51 @@ impl ApiTestService {
             }
         };
 
+        test_result.timing = timing.map(|timing| TimingBreakdown {
+            measured_at: Some(test_result.test_at.clone()),
+            ..timing
+        });
+
+        self.finish_test(test_result).await
+    }
+
+    /// 仅检测连通性的测试 (DNS / TCP / TLS / 首字节)，不携带密钥
+    async fn test_reachability(&self, config_id: i64, server_url: &str) -> TestResult {
+        match LatencyTestService::measure_timing(server_url, Some(TIMING_TIMEOUT_MS)).await {
+            Ok(timing) => {
+                log::info!("Config {} uses client keys, reachability test passed", config_id);
+                let test_at = now_rfc3339();
+                TestResult {
+                    id: 0,
+                    config_id,
+                    group_id: None,
+                    test_at: test_at.clone(),
+                    status: TestStatus::Success,
+                    latency_ms: Some(timing.total_ms()),
+                    error_message: None,
+                    is_valid_key: None,
+                    response_text: Some("透传客户端密钥，仅检测连通性".to_string()),
+                    test_model: None,
+                    attempt: Some(1),
+                    timing: Some(TimingBreakdown {
+                        measured_at: Some(test_at),
+                        ..timing
+                    }),
+                }
+            }
+            Err(e) => {
+                log::warn!("Config {} uses client keys, reachability test failed: {}", config_id, e);
+                let mut result = self.create_failed_result(config_id, 0, &e, None, 1);
+                result.is_valid_key = None;
+                result
+            }
+        }
+    }
+
+    /// 保存测试结果、更新配置状态并推送事件
+    async fn finish_test(&self, test_result: TestResult) -> AppResult<TestResult> {
+        let config_id = test_result.config_id;
+
         // 更新配置的测试结果
         self.update_config_test_result(config_id, &test_result)?;
 
@@ -424,6 +526,79 @@ impl ApiTestService {
         Ok(test_result)
     }
 
+    /// 通过代理转发流程测试单个配置
+    ///
+    /// 与 `test_single_config` 使用相同的测试请求，但请求经过 `RequestRouter` 的完整转发流程
+    /// （路径拼接、请求头改写、字段过滤、模型映射、格式转换），用于发现直连测试通过、
+    /// 实际代理使用却失败的配置。不会触发自动切换，结果也不写入测试历史。
+    ///
+    /// # Arguments
+    /// - `config_id`: API 配置 ID
+    pub async fn test_config_via_proxy(&self, config_id: i64) -> AppResult<TestResult> {
+        log::info!("Testing API config via proxy pipeline: {}", config_id);
+
+        let config = self.db_pool.with_connection(|conn| {
+            ApiConfigService::get_config_by_id(conn, config_id)
+        })?;
+
+        // 模拟 Claude Code 客户端发往代理的请求（认证头由代理替换为配置的密钥）
+        let mut headers = hyper::HeaderMap::new();
+        for (name, value) in claude_code_headers(PROXY_TEST_CLIENT_TOKEN) {
+            if name == "x-api-key" {
+                continue;
+            }
+            let value = hyper::header::HeaderValue::from_str(&value).map_err(|e| AppError::ServiceError {
+                message: format!("构建测试请求头失败: {}", e),
+            })?;
+            headers.insert(name, value);
+        }
+        let body = serde_json::to_vec(&build_test_request_body()).map_err(|e| AppError::ServiceError {
+            message: format!("构建测试请求体失败: {}", e),
+        })?;
+
+        let router = RequestRouter::new(self.db_pool.clone());
+        let start_time = Instant::now();
+        let outcome = timeout(
+            Duration::from_secs(TEST_TIMEOUT_SECS),
+            router.forward_test_request(
+                config_id,
+                config.group_id.unwrap_or_default(),
+                "/v1/messages",
+                headers,
+                body.into(),
+            ),
+        )
+        .await;
+        let latency_ms = start_time.elapsed().as_millis() as i64;
+
+        let mut test_result = match outcome {
+            Ok(Ok((status, response_text))) => {
+                match Self::evaluate_test_response(status.as_u16(), &response_text) {
+                    Ok(response) => self.create_success_result(
+                        config_id,
+                        latency_ms,
+                        Some(response.response_text),
+                        response.model,
+                        1,
+                    ),
+                    Err(e) => self.create_failed_result(config_id, latency_ms, &e, None, 1),
+                }
+            }
+            Ok(Err(e)) => self.create_failed_result(config_id, latency_ms, &e.to_string(), None, 1),
+            Err(_) => self.create_timeout_result(config_id, None, 1),
+        };
+        test_result.group_id = config.group_id;
+
+        log::info!(
+            "Config {} proxy pipeline test {}: {}ms",
+            config_id,
+            if test_result.is_success() { "passed" } else { "failed" },
+            latency_ms
+        );
+
+        Ok(test_result)
+    }
+
     /// 测试分组内所有配置
     ///
     /// # Arguments
@@ -476,6 +651,155 @@ impl ApiTestService {
         Ok(results)
     }
 
+    /// 仅测试超过指定时间未测试（或从未测试）的配置
+    ///
+    /// 用于定期的新鲜度巡检，避免重复测试刚验证过的后端
+    ///
+    /// # Arguments
+    /// - `max_age_minutes`: 距上次测试超过该分钟数的配置才会被测试
+    pub async fn test_stale_configs(&self, max_age_minutes: i64) -> AppResult<StaleConfigTestResult> {
+        if max_age_minutes < 0 {
+            return Err(AppError::ValidationError {
+                field: "max_age_minutes".to_string(),
+                message: "时间阈值不能为负数".to_string(),
+            });
+        }
+
+        let configs = self.db_pool.with_connection(|conn| ApiConfigService::list_configs(conn, None))?;
+
+        let cutoff = chrono::Local::now() - chrono::Duration::minutes(max_age_minutes);
+        let (stale, fresh): (Vec<_>, Vec<_>) = configs
+            .iter()
+            .partition(|config| is_test_stale(config.last_test_at.as_deref(), cutoff));
+        let tested_config_ids: Vec<i64> = stale.iter().map(|config| config.id).collect();
+        let skipped_config_ids: Vec<i64> = fresh.iter().map(|config| config.id).collect();
+
+        log::info!(
+            "Testing {} stale configs (older than {} minutes), skipping {} fresh",
+            tested_config_ids.len(),
+            max_age_minutes,
+            skipped_config_ids.len()
+        );
+
+        let results: Vec<TestResult> = stream::iter(tested_config_ids.clone())
+            .map(|config_id| {
+                let service = ApiTestService::new(self.db_pool.clone());
+                async move {
+                    match service.test_single_config(config_id).await {
+                        Ok(result) => Some(result),
+                        Err(e) => {
+                            log::error!("Test task failed: {}", e);
+                            None
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_CONFIG_TESTS)
+            .filter_map(|result| async move { result })
+            .collect()
+            .await;
+
+        Ok(StaleConfigTestResult {
+            tested_config_ids,
+            skipped_config_ids,
+            results,
+        })
+    }
+
+    /// 使用示例密钥实测供应商预设
+    ///
+    /// 规范化预设的服务器地址后依次检查连通性与认证（复用配置测试的请求），
+    /// 用于在推荐或创建配置前发现地址拼写错误等问题。结果不写入测试历史。
+    ///
+    /// # Arguments
+    /// - `preset_id`: 供应商预设 ID
+    /// - `sample_key`: 用于测试的 API 密钥
+    pub async fn validate_provider_preset(
+        &self,
+        preset_id: &str,
+        sample_key: &str,
+    ) -> AppResult<PresetValidationResult> {
+        let preset = ProviderPresetService::get_provider_by_id(preset_id)?;
+        self.validate_preset(&preset, sample_key).await
+    }
+
+    async fn validate_preset(
+        &self,
+        preset: &ProviderPreset,
+        sample_key: &str,
+    ) -> AppResult<PresetValidationResult> {
+        let sample_key = sample_key.trim();
+        if sample_key.is_empty() {
+            return Err(AppError::ValidationError {
+                field: "sample_key".to_string(),
+                message: "测试密钥不能为空".to_string(),
+            });
+        }
+
+        let mut result = PresetValidationResult {
+            preset_id: preset.id.clone(),
+            server_url: None,
+            reachable: false,
+            authenticated: None,
+            usable: false,
+            latency_ms: None,
+            warnings: Vec::new(),
+            error_message: None,
+        };
+
+        let normalized = match normalize_server_url(&preset.server_url) {
+            Ok(normalized) => normalized,
+            Err(e) => {
+                log::warn!("Provider preset {} has invalid server URL: {}", preset.id, e);
+                result.error_message = Some(format!("服务器地址无效: {}", e));
+                return Ok(result);
+            }
+        };
+        let server_url = normalized.normalized;
+        result.warnings = normalized.warnings;
+        result.server_url = Some(server_url.clone());
+
+        if let Err(e) = LatencyTestService::measure_timing(&server_url, Some(TIMING_TIMEOUT_MS)).await {
+            log::warn!("Provider preset {} unreachable: {}", preset.id, e);
+            result.error_message = Some(format!("无法连接服务器: {}", e));
+            return Ok(result);
+        }
+        result.reachable = true;
+
+        let start_time = Instant::now();
+        let test_result = timeout(
+            Duration::from_secs(TEST_TIMEOUT_SECS),
+            self.perform_api_test(&server_url, sample_key, preset.default_model.as_deref()),
+        )
+        .await;
+        result.latency_ms = Some(start_time.elapsed().as_millis() as i64);
+
+        match test_result {
+            Ok(Ok(_)) => {
+                result.authenticated = Some(true);
+                result.usable = true;
+            }
+            Ok(Err(e)) => {
+                if is_auth_error(&e) {
+                    result.authenticated = Some(false);
+                }
+                result.error_message = Some(e);
+            }
+            Err(_) => {
+                result.error_message = Some(format!("测试请求超时（>{}秒）", TEST_TIMEOUT_SECS));
+            }
+        }
+
+        log::info!(
+            "Provider preset {} validated: usable={}, reachable={}, authenticated={:?}",
+            preset.id,
+            result.usable,
+            result.reachable,
+            result.authenticated
+        );
+        Ok(result)
+    }
+
     /// 执行真实的 Claude Code API 测试
     ///
     /// 使用与真实 Claude Code 完全相同的请求格式，包含：
@@ -544,15 +868,22 @@ impl ApiTestService {
         log::info!("📥 响应体大小: {} 字节", response_text.len());
         log::debug!("响应体内容: {}", if response_text.len() > 500 { format!("{}...(截断)", &response_text[..500]) } else { response_text.clone() });
 
+        Self::evaluate_test_response(status_code, &response_text)
+    }
+
+    /// 根据响应状态码与响应体判定测试结果
+    ///
+    /// 直连测试与经代理转发的测试共用同一判定逻辑
+    fn evaluate_test_response(status_code: u16, response_text: &str) -> Result<ApiTestResponse, String> {
         // 首先检查响应体是否包含错误信息（即使 HTTP 状态码是 200）
         // 一些代理服务商会返回 HTTP 200/500 但在响应体中包含实际的错误
-        if let Some(body_error) = check_response_body_error(&response_text) {
+        if let Some(body_error) = check_response_body_error(response_text) {
             log::error!("❌ 响应体包含错误: {}", body_error);
             log::info!("╚══════════════════════════════════════════════════════════════╝");
             return Err(format!("服务商错误: {}", body_error));
         }
 
-        if status.is_success() {
+        if (200..300).contains(&status_code) {
             log::info!("📊 解析响应内容...");
             // 解析流式响应，提取实际内容
             let mut content = String::new();
@@ -604,9 +935,9 @@ impl ApiTestService {
             if !has_valid_content && !response_text.contains("data: ") {
                 log::info!("📊 非流式响应格式，检查 JSON 内容...");
                 // 可能是非流式 JSON 响应
-                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&response_text) {
+                if let Ok(json) = serde_json::from_str::<serde_json::Value>(response_text) {
                     if json.get("error").is_some() {
-                        let error_msg = check_response_body_error(&response_text)
+                        let error_msg = check_response_body_error(response_text)
                             .unwrap_or_else(|| "未知错误".to_string());
                         log::error!("❌ 非流式响应包含错误: {}", error_msg);
                         log::info!("╚══════════════════════════════════════════════════════════════╝");
@@ -629,7 +960,7 @@ impl ApiTestService {
             })
         } else if status_code == 401 || status_code == 403 {
             // 认证问题
-            let error_msg = parse_api_error(&response_text, status_code);
+            let error_msg = parse_api_error(response_text, status_code);
             log::error!("❌ 认证失败: {}", error_msg);
             log::info!("╚══════════════════════════════════════════════════════════════╝");
             Err(error_msg)
@@ -640,13 +971,13 @@ impl ApiTestService {
             Err(format!("API 限流 (HTTP {})", status_code))
         } else if status_code >= 500 && status_code < 600 {
             // 服务器错误
-            let error_msg = parse_api_error(&response_text, status_code);
+            let error_msg = parse_api_error(response_text, status_code);
             log::error!("❌ 服务器错误: {}", error_msg);
             log::info!("╚══════════════════════════════════════════════════════════════╝");
             Err(error_msg)
         } else {
             // 其他错误
-            let error_msg = parse_api_error(&response_text, status_code);
+            let error_msg = parse_api_error(response_text, status_code);
             log::error!("❌ API 错误: {}", error_msg);
             log::info!("╚══════════════════════════════════════════════════════════════╝");
             Err(error_msg)
@@ -674,6 +1005,7 @@ impl ApiTestService {
             response_text,
             test_model: Some(test_model),
             attempt: Some(attempt),
+            timing: None,
         }
     }
 
@@ -698,6 +1030,7 @@ impl ApiTestService {
             response_text: None,
             test_model,
             attempt: Some(attempt),
+            timing: None,
         }
     }
 
@@ -720,6 +1053,7 @@ impl ApiTestService {
             response_text: None,
             test_model,
             attempt: Some(attempt),
+            timing: None,
         }
     }
 
@@ -761,9 +1095,12 @@ impl ApiTestService {
         self.db_pool.with_connection(|conn| {
             use rusqlite::params;
 
+            let timing = result.timing.clone().unwrap_or_default();
+
             conn.execute(
-                "INSERT INTO TestResult (config_id, group_id, test_at, status, latency_ms, error_message, is_valid_key, response_text, test_model, attempt)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                "INSERT INTO TestResult (config_id, group_id, test_at, status, latency_ms, error_message, is_valid_key, response_text, test_model, attempt,
+                                         dns_ms, connect_ms, tls_ms, ttfb_ms)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
                 params![
                     result.config_id,
                     result.group_id,
@@ -775,6 +1112,10 @@ impl ApiTestService {
                     result.response_text,
                     result.test_model,
                     result.attempt,
+                    timing.dns_ms,
+                    timing.connect_ms,
+                    timing.tls_ms,
+                    timing.ttfb_ms,
                 ],
             ).map_err(|e| AppError::DatabaseError {
                 message: format!("保存测试结果失败: {}", e),
@@ -791,7 +1132,8 @@ impl ApiTestService {
 
             let mut stmt = conn
                 .prepare(
-                    "SELECT id, config_id, group_id, test_at, status, latency_ms, error_message, is_valid_key, response_text, test_model, attempt
+                    "SELECT id, config_id, group_id, test_at, status, latency_ms, error_message, is_valid_key, response_text, test_model, attempt,
+                            dns_ms, connect_ms, tls_ms, ttfb_ms
                      FROM TestResult
                      WHERE config_id = ?1
                      ORDER BY test_at DESC
@@ -819,6 +1161,7 @@ impl ApiTestService {
                         response_text: row.get(8)?,
                         test_model: row.get(9)?,
                         attempt: row.get(10)?,
+                        timing: Self::map_row_to_timing(row, 11, 3)?,
                     })
                 })
                 .map_err(|e| AppError::DatabaseError {
@@ -832,6 +1175,51 @@ impl ApiTestService {
             Ok(results)
         })
     }
+
+    /// 获取配置最近一次测量的连接耗时分解
+    pub fn get_latest_timing_breakdown(&self, config_id: i64) -> AppResult<Option<TimingBreakdown>> {
+        self.db_pool.with_connection(|conn| {
+            use rusqlite::{params, OptionalExtension};
+
+            let timing = conn
+                .query_row(
+                    "SELECT dns_ms, connect_ms, tls_ms, ttfb_ms, test_at
+                     FROM TestResult
+                     WHERE config_id = ?1 AND (dns_ms IS NOT NULL OR connect_ms IS NOT NULL)
+                     ORDER BY test_at DESC
+                     LIMIT 1",
+                    params![config_id],
+                    |row| Self::map_row_to_timing(row, 0, 4),
+                )
+                .optional()
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("查询连接耗时分解失败: {}", e),
+                })?;
+
+            Ok(timing.flatten())
+        })
+    }
+
+    /// 从查询结果行中读取连接耗时分解（各阶段均为空时返回 None）
+    fn map_row_to_timing(
+        row: &rusqlite::Row,
+        offset: usize,
+        measured_at_index: usize,
+    ) -> rusqlite::Result<Option<TimingBreakdown>> {
+        let timing = TimingBreakdown {
+            dns_ms: row.get(offset)?,
+            connect_ms: row.get(offset + 1)?,
+            tls_ms: row.get(offset + 2)?,
+            ttfb_ms: row.get(offset + 3)?,
+            measured_at: row.get(measured_at_index)?,
+        };
+
+        if timing.dns_ms.is_none() && timing.connect_ms.is_none() && timing.ttfb_ms.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(timing))
+    }
 }
 
 #[cfg(test)]
@@ -839,6 +1227,18 @@ mod tests {
     use super::*;
     use crate::db::initialize_database;
 
+    #[test]
+    fn test_is_test_stale() {
+        let cutoff = chrono::Local::now() - chrono::Duration::minutes(30);
+        let recent = (chrono::Local::now() - chrono::Duration::minutes(5)).to_rfc3339();
+        let old = (chrono::Local::now() - chrono::Duration::hours(2)).to_rfc3339();
+
+        assert!(!is_test_stale(Some(&recent), cutoff));
+        assert!(is_test_stale(Some(&old), cutoff));
+        assert!(is_test_stale(None, cutoff));
+        assert!(is_test_stale(Some("not a timestamp"), cutoff));
+    }
+
     #[tokio::test]
     async fn test_create_test_results() {
         let conn = initialize_database().expect("Failed to initialize database");
@@ -883,4 +1283,153 @@ mod tests {
         assert!(timeout_result.error_message.is_some());
         assert_eq!(timeout_result.attempt, Some(2));
     }
+
+    #[test]
+    fn test_check_response_body_error() {
+        let error_body = r#"{"error":{"type":"rate_limit_error","message":"Too many requests"}}"#;
+        assert_eq!(
+            check_response_body_error(error_body),
+            Some("rate_limit_error: Too many requests".to_string())
+        );
+
+        // 工具调用参数中的 error/message 字段不应被误判
+        let tool_use_body = r#"{"type":"message","content":[{"type":"tool_use","input":{"error":"x","message":"y"}}]}"#;
+        assert_eq!(check_response_body_error(tool_use_body), None);
+
+        assert!(check_response_body_error(r#"upstream: {"error": 1, "message": "down"}"#).is_some());
+    }
+
+    /// 接收一个完整的 HTTP 请求并返回原始文本
+    fn preset_with_url(server_url: &str) -> ProviderPreset {
+        serde_json::from_value(serde_json::json!({
+            "id": "local",
+            "name": "Local",
+            "category": "custom",
+            "websiteUrl": "https://example.com",
+            "serverUrl": server_url,
+            "hotnessScore": 0,
+            "region": "domestic",
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_validate_preset_reports_rejected_key() {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            // 连通性探测 (HEAD) 与测试请求各占一个连接
+            for _ in 0..2 {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                read_http_request(&mut socket).await;
+                let body = r#"{"type":"error","error":{"type":"authentication_error","message":"invalid x-api-key"}}"#;
+                let response = format!(
+                    "HTTP/1.1 401 Unauthorized\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let service = ApiTestService::new(Arc::new(DbPool::new_in_memory().unwrap()));
+        let preset = preset_with_url(&format!("http://127.0.0.1:{}/", port));
+        let result = service.validate_preset(&preset, "sk-sample-key").await.unwrap();
+
+        assert_eq!(result.server_url, Some(format!("http://127.0.0.1:{}", port)));
+        assert!(result.reachable);
+        assert_eq!(result.authenticated, Some(false));
+        assert!(!result.usable);
+        assert!(result.error_message.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_validate_preset_rejects_invalid_url_and_empty_key() {
+        let service = ApiTestService::new(Arc::new(DbPool::new_in_memory().unwrap()));
+
+        let result = service
+            .validate_preset(&preset_with_url("not a url"), "sk-sample-key")
+            .await
+            .unwrap();
+        assert!(result.server_url.is_none());
+        assert!(!result.reachable);
+        assert!(!result.usable);
+        assert!(result.error_message.is_some());
+
+        let preset = preset_with_url("https://api.example.com");
+        assert!(service.validate_preset(&preset, "  ").await.is_err());
+    }
+
+    async fn read_http_request(socket: &mut tokio::net::TcpStream) -> String {
+        use tokio::io::AsyncReadExt;
+
+        let mut raw = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = socket.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            raw.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&raw).to_string();
+            if let Some(header_end) = text.find("\r\n\r\n") {
+                let content_length = text[..header_end]
+                    .lines()
+                    .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(0);
+                if raw.len() >= header_end + 4 + content_length {
+                    return text;
+                }
+            }
+        }
+        String::from_utf8_lossy(&raw).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_config_via_proxy_uses_forwarding_pipeline() {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let backend = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let request = read_http_request(&mut socket).await;
+            let events = "event: message_start\ndata: {\"type\":\"message_start\"}\n\n\
+                          event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi\"}}\n\n\
+                          event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n";
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {}\r\n\r\n{}",
+                events.len(),
+                events
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            request
+        });
+
+        let pool = DbPool::new(crate::db::test_db());
+        pool.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO ApiConfig (id, name, api_key, server_url,
+                                        default_model, haiku_model, sonnet_model, opus_model, small_fast_model)
+                 VALUES (1, 'local', 'sk-config-key', ?1, NULL, NULL, NULL, NULL, NULL)",
+                [format!("http://127.0.0.1:{}/prefix", port)],
+            )
+            .unwrap();
+            Ok(())
+        })
+        .unwrap();
+
+        let service = ApiTestService::new(Arc::new(pool));
+        let result = service.test_config_via_proxy(1).await.unwrap();
+        assert!(result.is_success(), "{:?}", result.error_message);
+        assert_eq!(result.response_text.as_deref(), Some("API 响应: Hi"));
+
+        let request = backend.await.unwrap().to_ascii_lowercase();
+        assert!(request.starts_with("post /prefix/v1/messages http/1.1"));
+        assert!(request.contains("authorization: bearer sk-config-key"));
+        assert!(!request.contains(PROXY_TEST_CLIENT_TOKEN));
+    }
 }
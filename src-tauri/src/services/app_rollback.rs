@@ -0,0 +1,316 @@
+/**
+ * App Rollback Service
+ * 保留已校验的应用更新包，用于新版本有问题时回退到之前的版本
+ *
+ * 每次更新包下载并校验通过后复制一份到归档目录，并在 manifest.json 中记录版本与 SHA-256，
+ * 再次更新后即可回退到该版本。当前运行版本尚无归档时 (例如首次更新)，
+ * 在新版本下载完成后于后台从该版本的 GitHub Release 补充下载归档。
+ * 只有版本低于当前版本、文件存在且 SHA-256 与记录一致的归档才可用于回退。
+ */
+
+use crate::services::app_updater::AppUpdater;
+use crate::utils::paths;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// 最多保留的归档版本数
+const MAX_ARCHIVED_VERSIONS: usize = 3;
+
+/// 归档清单文件名
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// 已归档的更新包
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollbackArtifact {
+    /// 应用版本 (不含 `v` 前缀)
+    pub version: String,
+    pub file_name: String,
+    pub path: String,
+    pub size: u64,
+    /// 下载时校验通过的 SHA-256
+    pub sha256: String,
+    pub archived_at: String,
+}
+
+/// 应用回退服务
+pub struct AppRollbackService {
+    dir: PathBuf,
+}
+
+impl AppRollbackService {
+    /// 使用指定的归档目录
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// 使用应用数据目录下的 `updates` 目录
+    pub fn with_default_dir() -> Result<Self, String> {
+        Ok(Self::new(paths::get_app_data_dir()?.join("updates")))
+    }
+
+    /// 归档已校验的更新包，超出保留数量时删除最旧的版本
+    pub fn archive(&self, artifact: &Path, version: &str, sha256: &str) -> Result<RollbackArtifact, String> {
+        let version = version.trim_start_matches('v').to_string();
+        AppUpdater::cmp_version_strings(&version, &version)
+            .ok_or_else(|| format!("无效的版本号格式: {}", version))?;
+        let file_name = artifact
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| "无效的更新包文件名".to_string())?
+            .to_string();
+
+        let version_dir = self.dir.join(&version);
+        fs::create_dir_all(&version_dir).map_err(|e| format!("创建归档目录失败: {}", e))?;
+        let target = version_dir.join(&file_name);
+        let size = fs::copy(artifact, &target).map_err(|e| format!("归档更新包失败: {}", e))?;
+
+        let entry = RollbackArtifact {
+            version: version.clone(),
+            file_name,
+            path: target.to_string_lossy().to_string(),
+            size,
+            sha256: sha256.to_ascii_lowercase(),
+            archived_at: crate::utils::time::now_rfc3339(),
+        };
+
+        let mut manifest = self.read_manifest();
+        manifest.retain(|a| a.version != version);
+        manifest.push(entry.clone());
+        manifest.sort_by(|a, b| {
+            AppUpdater::cmp_version_strings(&b.version, &a.version).unwrap_or(Ordering::Equal)
+        });
+        for removed in manifest.split_off(MAX_ARCHIVED_VERSIONS.min(manifest.len())) {
+            let _ = fs::remove_dir_all(self.dir.join(&removed.version));
+            log::info!("已删除旧版本更新包归档: {}", removed.version);
+        }
+        self.write_manifest(&manifest)?;
+
+        log::info!("已归档更新包: {} ({})", entry.version, entry.path);
+        Ok(entry)
+    }
+
+    /// 下载当前运行版本的安装包并归档，供安装新版本后回退
+    ///
+    /// 当前版本已有完整归档 (通常是上次更新时保留的安装包) 时不重复下载，返回 None
+    pub async fn archive_current_release(
+        &self,
+        updater: &AppUpdater,
+        current_version: &str,
+    ) -> Result<Option<RollbackArtifact>, String> {
+        if self.has_archive(current_version) {
+            log::debug!("当前版本 {} 已有归档，跳过", current_version);
+            return Ok(None);
+        }
+
+        let asset = updater
+            .current_release_asset()
+            .await?
+            .ok_or_else(|| format!("{} 版本的 Release 中没有适合当前平台的安装包", current_version))?;
+        let sha256 = asset
+            .sha256()
+            .ok_or_else(|| format!("无法获取 {} 的 SHA-256 校验值", asset.name))?;
+
+        let download_dir = self.dir.join(".download");
+        fs::create_dir_all(&download_dir).map_err(|e| format!("创建归档目录失败: {}", e))?;
+        let download_path = download_dir.join(&asset.name);
+        let result = async {
+            updater
+                .download_update(&asset.browser_download_url, &download_path.to_string_lossy(), &sha256, |_| {})
+                .await?;
+            self.archive(&download_path, current_version, &sha256)
+        }
+        .await;
+        let _ = fs::remove_dir_all(&download_dir);
+        result.map(Some)
+    }
+
+    /// 指定版本是否已有完整的归档
+    fn has_archive(&self, version: &str) -> bool {
+        let version = version.trim_start_matches('v');
+        self.read_manifest()
+            .iter()
+            .any(|a| a.version == version && Self::verify(a).is_ok())
+    }
+
+    /// 可用于回退的归档 (版本低于当前版本且完整性校验通过)，版本从新到旧
+    pub fn list_available(&self, current_version: &str) -> Vec<RollbackArtifact> {
+        self.read_manifest()
+            .into_iter()
+            .filter(|a| {
+                AppUpdater::cmp_version_strings(&a.version, current_version) == Some(Ordering::Less)
+            })
+            .filter(|a| match Self::verify(a) {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!("更新包归档 {} 不可用: {}", a.version, e);
+                    false
+                }
+            })
+            .collect()
+    }
+
+    /// 选择回退目标：指定版本，或可用的最新版本
+    pub fn find_rollback(&self, current_version: &str, version: Option<&str>) -> Result<RollbackArtifact, String> {
+        let available = self.list_available(current_version);
+        match version.map(|v| v.trim_start_matches('v')) {
+            Some(version) => available
+                .into_iter()
+                .find(|a| a.version == version)
+                .ok_or_else(|| format!("没有可用于回退的 {} 版本更新包", version)),
+            None => available
+                .into_iter()
+                .next()
+                .ok_or_else(|| "没有可用于回退的旧版本更新包".to_string()),
+        }
+    }
+
+    /// 校验归档文件存在且 SHA-256 与记录一致
+    fn verify(artifact: &RollbackArtifact) -> Result<(), String> {
+        let actual = sha256_file(Path::new(&artifact.path))?;
+        if actual != artifact.sha256 {
+            return Err(format!("SHA-256 不匹配 (期望 {}, 实际 {})", artifact.sha256, actual));
+        }
+        Ok(())
+    }
+
+    fn read_manifest(&self) -> Vec<RollbackArtifact> {
+        fs::read_to_string(self.dir.join(MANIFEST_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn write_manifest(&self, manifest: &[RollbackArtifact]) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(manifest).map_err(|e| format!("序列化归档清单失败: {}", e))?;
+        fs::write(self.dir.join(MANIFEST_FILE), content).map_err(|e| format!("写入归档清单失败: {}", e))
+    }
+}
+
+/// 计算文件的 SHA-256 (小写十六进制)
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("读取更新包失败: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).map_err(|e| format!("读取更新包失败: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 从 GitHub Release 下载链接中提取版本号 (`.../releases/download/v1.2.3/...`)
+pub fn version_from_download_url(url: &str) -> Option<String> {
+    let tag = url.split("/releases/download/").nth(1)?.split('/').next()?;
+    let version = tag.trim_start_matches('v');
+    AppUpdater::cmp_version_strings(version, version).map(|_| version.to_string())
+}
+
+/// 启动归档的安装包 (Linux AppImage 直接运行，其余交给系统默认程序打开)
+pub fn launch_artifact(artifact: &RollbackArtifact) -> Result<(), String> {
+    let path = Path::new(&artifact.path);
+
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(path)
+            .spawn()
+            .map_err(|e| format!("打开安装包失败: {}", e))?;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("cmd")
+            .args(["/C", "start", ""])
+            .arg(path)
+            .spawn()
+            .map_err(|e| format!("打开安装包失败: {}", e))?;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if artifact.file_name.ends_with(".AppImage") {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+                .map_err(|e| format!("设置执行权限失败: {}", e))?;
+            std::process::Command::new(path)
+                .spawn()
+                .map_err(|e| format!("启动旧版本失败: {}", e))?;
+        } else {
+            std::process::Command::new("xdg-open")
+                .arg(path)
+                .spawn()
+                .map_err(|e| format!("打开安装包失败: {}", e))?;
+        }
+    }
+
+    log::info!("已启动回退版本 {}: {}", artifact.version, artifact.path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ccp-rollback-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_artifact(dir: &Path, name: &str, content: &[u8]) -> (PathBuf, String) {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        let sha256 = sha256_file(&path).unwrap();
+        (path, sha256)
+    }
+
+    #[test]
+    fn test_archive_and_list_rollbacks() {
+        let downloads = temp_dir("downloads");
+        let service = AppRollbackService::new(temp_dir("archive"));
+
+        for version in ["1.0.0", "1.1.0", "1.2.0", "1.3.0"] {
+            let (path, sha256) = write_artifact(&downloads, &format!("app_{}.dmg", version), version.as_bytes());
+            service.archive(&path, &format!("v{}", version), &sha256).unwrap();
+        }
+
+        // 只保留最新的 3 个版本，且只列出低于当前版本的归档
+        let versions: Vec<String> = service.list_available("1.3.0").into_iter().map(|a| a.version).collect();
+        assert_eq!(versions, vec!["1.2.0", "1.1.0"]);
+        assert_eq!(service.find_rollback("1.3.0", None).unwrap().version, "1.2.0");
+        assert_eq!(service.find_rollback("1.3.0", Some("v1.1.0")).unwrap().version, "1.1.0");
+        assert!(service.find_rollback("1.3.0", Some("1.0.0")).is_err());
+        assert!(service.has_archive("v1.3.0"));
+        assert!(!service.has_archive("1.0.0"));
+
+        // 文件被篡改后不再提供回退
+        let tampered = service.find_rollback("1.3.0", None).unwrap();
+        fs::write(&tampered.path, b"tampered").unwrap();
+        assert_eq!(service.find_rollback("1.3.0", None).unwrap().version, "1.1.0");
+        assert!(!service.has_archive("1.2.0"));
+
+        let _ = fs::remove_dir_all(&downloads);
+        let _ = fs::remove_dir_all(&service.dir);
+    }
+
+    #[test]
+    fn test_version_from_download_url() {
+        assert_eq!(
+            version_from_download_url(
+                "https://github.com/sunjackson/claude-code-proxy/releases/download/v1.4.2/app_1.4.2_x64.dmg"
+            )
+            .as_deref(),
+            Some("1.4.2")
+        );
+        assert_eq!(version_from_download_url("https://example.com/app.dmg"), None);
+        assert_eq!(version_from_download_url("https://x/releases/download/latest/app.dmg"), None);
+    }
+}
@@ -1,5 +1,12 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+
+/// 下载进度事件的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
 
 /// GitHub Release 信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +26,43 @@ pub struct GithubAsset {
     pub browser_download_url: String,
     pub size: i64,
     pub content_type: String,
+    /// GitHub 计算的资源摘要，格式为 `sha256:<hex>` (较早上传的资源可能没有)
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+impl GithubAsset {
+    /// 资源的 SHA-256 (小写十六进制)
+    pub fn sha256(&self) -> Option<String> {
+        self.digest
+            .as_deref()
+            .and_then(|d| d.strip_prefix("sha256:"))
+            .filter(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+            .map(|hex| hex.to_ascii_lowercase())
+    }
+}
+
+/// 更新包下载阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadStage {
+    Downloading,
+    Verifying,
+    Complete,
+    Failed,
+}
+
+/// 更新包下载进度 (通过 `app-update-progress` 事件推送)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadProgress {
+    pub stage: DownloadStage,
+    pub progress: f32, // 0.0 - 1.0，总大小未知时为 0
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+    /// 平均下载速度 (字节/秒)
+    pub speed_bytes_per_sec: u64,
+    pub message: String,
+    pub success: bool,
 }
 
 /// 应用版本信息
@@ -34,6 +78,9 @@ pub struct AppVersionInfo {
     pub release_notes: Option<String>,
     /// 下载链接
     pub download_url: Option<String>,
+    /// 安装包的 SHA-256，下载完成后用于校验
+    #[serde(default)]
+    pub download_sha256: Option<String>,
     /// 发布页面链接
     pub release_page_url: Option<String>,
     /// 发布时间
@@ -74,7 +121,9 @@ impl AppUpdater {
         let has_update = self.compare_versions(&self.current_version, &latest_version)?;
 
         // 获取适合当前平台的下载链接
-        let download_url = self.get_platform_download_url(&latest_release.assets);
+        let asset = self.get_platform_asset(&latest_release.assets);
+        let download_url = asset.map(|a| a.browser_download_url.clone());
+        let download_sha256 = asset.and_then(GithubAsset::sha256);
 
         log::info!(
             "版本检查完成: 当前={}, 最新={}, 有更新={}",
@@ -89,6 +138,7 @@ impl AppUpdater {
             has_update,
             release_notes: Some(latest_release.body),
             download_url,
+            download_sha256,
             release_page_url: Some(latest_release.html_url),
             published_at: Some(latest_release.published_at),
         })
@@ -96,9 +146,14 @@ impl AppUpdater {
 
     /// 从 GitHub API 获取最新 Release
     async fn fetch_latest_release(&self) -> Result<GithubRelease, String> {
+        self.fetch_release("latest").await
+    }
+
+    /// 从 GitHub API 获取 Release (`latest` 或 `tags/<tag>`)
+    async fn fetch_release(&self, which: &str) -> Result<GithubRelease, String> {
         let url = format!(
-            "https://api.github.com/repos/{}/{}/releases/latest",
-            self.owner, self.repo
+            "https://api.github.com/repos/{}/{}/releases/{}",
+            self.owner, self.repo, which
         );
 
         log::debug!("请求 GitHub API: {}", url);
@@ -145,6 +200,18 @@ impl AppUpdater {
             .collect()
     }
 
+    /// 比较两个版本号字符串 (可带 `v` 前缀)，任一格式无效时返回 None
+    pub(crate) fn cmp_version_strings(a: &str, b: &str) -> Option<Ordering> {
+        let parse = |version: &str| {
+            version
+                .trim_start_matches('v')
+                .split('.')
+                .map(|part| part.parse::<u32>().ok())
+                .collect::<Option<Vec<u32>>>()
+        };
+        Some(Self::version_cmp(&parse(a)?, &parse(b)?))
+    }
+
     /// 比较版本号数组
     fn version_cmp(v1: &[u32], v2: &[u32]) -> Ordering {
         let max_len = v1.len().max(v2.len());
@@ -163,8 +230,25 @@ impl AppUpdater {
         Ordering::Equal
     }
 
-    /// 获取适合当前平台的下载链接
-    fn get_platform_download_url(&self, assets: &[GithubAsset]) -> Option<String> {
+    /// 查找下载链接对应的最新 Release 资源的 SHA-256
+    pub async fn find_asset_sha256(&self, url: &str) -> Result<Option<String>, String> {
+        let release = self.fetch_latest_release().await?;
+        Ok(release
+            .assets
+            .iter()
+            .find(|asset| asset.browser_download_url == url)
+            .and_then(GithubAsset::sha256))
+    }
+
+    /// 获取当前运行版本的 Release 中适合当前平台的安装包
+    pub async fn current_release_asset(&self) -> Result<Option<GithubAsset>, String> {
+        let tag = format!("tags/v{}", self.current_version.trim_start_matches('v'));
+        let release = self.fetch_release(&tag).await?;
+        Ok(self.get_platform_asset(&release.assets).cloned())
+    }
+
+    /// 获取适合当前平台的安装包
+    fn get_platform_asset<'a>(&self, assets: &'a [GithubAsset]) -> Option<&'a GithubAsset> {
         let platform = std::env::consts::OS;
         let arch = std::env::consts::ARCH;
 
@@ -207,7 +291,7 @@ impl AppUpdater {
             for p in &pattern {
                 if name_lower.contains(p) {
                     log::info!("找到匹配的安装包: {}", asset.name);
-                    return Some(asset.browser_download_url.clone());
+                    return Some(asset);
                 }
             }
         }
@@ -217,12 +301,65 @@ impl AppUpdater {
     }
 
     /// 下载更新包
-    pub async fn download_update(&self, url: &str, save_path: &str) -> Result<(), String> {
+    ///
+    /// 以流式方式写入 `<save_path>.part`，期间通过 `on_progress` 报告进度；
+    /// 下载完成后校验 SHA-256，校验通过才重命名为 `save_path`，失败时删除临时文件
+    pub async fn download_update(
+        &self,
+        url: &str,
+        save_path: &str,
+        expected_sha256: &str,
+        on_progress: impl Fn(DownloadProgress),
+    ) -> Result<(), String> {
         log::info!("开始下载更新: {} -> {}", url, save_path);
 
+        let part_path = PathBuf::from(format!("{}.part", save_path));
+        let result = Self::download_and_verify(url, &part_path, expected_sha256, &on_progress).await;
+        match result {
+            Ok((downloaded, total)) => {
+                tokio::fs::rename(&part_path, save_path)
+                    .await
+                    .map_err(|e| format!("保存文件失败: {}", e))?;
+                on_progress(DownloadProgress {
+                    stage: DownloadStage::Complete,
+                    progress: 1.0,
+                    downloaded_bytes: downloaded,
+                    total_bytes: total,
+                    speed_bytes_per_sec: 0,
+                    message: "下载完成，校验通过".to_string(),
+                    success: true,
+                });
+                log::info!("下载完成: {} ({} 字节)", save_path, downloaded);
+                Ok(())
+            }
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&part_path).await;
+                on_progress(DownloadProgress {
+                    stage: DownloadStage::Failed,
+                    progress: 0.0,
+                    downloaded_bytes: 0,
+                    total_bytes: None,
+                    speed_bytes_per_sec: 0,
+                    message: e.clone(),
+                    success: false,
+                });
+                log::error!("下载更新失败: {}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// 流式下载到 `part_path` 并校验，返回 (已下载字节数, 总字节数)
+    async fn download_and_verify(
+        url: &str,
+        part_path: &Path,
+        expected_sha256: &str,
+        on_progress: &impl Fn(DownloadProgress),
+    ) -> Result<(u64, Option<u64>), String> {
         let client = reqwest::Client::new();
-        let response = client
+        let mut response = client
             .get(url)
+            .header("User-Agent", "claude-code-proxy")
             .send()
             .await
             .map_err(|e| format!("下载失败: {}", e))?;
@@ -231,16 +368,71 @@ impl AppUpdater {
             return Err(format!("下载失败,状态码: {}", response.status()));
         }
 
-        let bytes = response
-            .bytes()
+        let total = response.content_length();
+        let mut file = tokio::fs::File::create(part_path)
             .await
-            .map_err(|e| format!("读取下载内容失败: {}", e))?;
+            .map_err(|e| format!("创建文件失败: {}", e))?;
+        let mut hasher = Sha256::new();
+        let mut downloaded: u64 = 0;
+        let started = Instant::now();
+        let mut last_report: Option<Instant> = None;
+
+        let progress_of = |downloaded: u64| DownloadProgress {
+            stage: DownloadStage::Downloading,
+            progress: total.filter(|&t| t > 0).map_or(0.0, |t| (downloaded as f64 / t as f64).min(1.0) as f32),
+            downloaded_bytes: downloaded,
+            total_bytes: total,
+            speed_bytes_per_sec: (downloaded as f64 / started.elapsed().as_secs_f64().max(0.001)) as u64,
+            message: "正在下载更新...".to_string(),
+            success: true,
+        };
 
-        std::fs::write(save_path, bytes)
-            .map_err(|e| format!("保存文件失败: {}", e))?;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("读取下载内容失败: {}", e))?
+        {
+            hasher.update(&chunk);
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| format!("写入文件失败: {}", e))?;
+            downloaded += chunk.len() as u64;
+
+            if last_report.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL) {
+                on_progress(progress_of(downloaded));
+                last_report = Some(Instant::now());
+            }
+        }
+        file.flush().await.map_err(|e| format!("写入文件失败: {}", e))?;
+        drop(file);
+        on_progress(progress_of(downloaded));
+
+        if let Some(total) = total.filter(|&t| t != downloaded) {
+            return Err(format!("下载不完整: {} / {} 字节", downloaded, total));
+        }
+
+        on_progress(DownloadProgress {
+            stage: DownloadStage::Verifying,
+            progress: 1.0,
+            downloaded_bytes: downloaded,
+            total_bytes: total,
+            speed_bytes_per_sec: 0,
+            message: "正在校验安装包...".to_string(),
+            success: true,
+        });
+        let actual = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        if !actual.eq_ignore_ascii_case(expected_sha256.trim()) {
+            return Err(format!(
+                "安装包校验失败: SHA-256 不匹配 (期望 {}, 实际 {})",
+                expected_sha256, actual
+            ));
+        }
 
-        log::info!("下载完成: {}", save_path);
-        Ok(())
+        Ok((downloaded, total))
     }
 }
 
@@ -278,4 +470,71 @@ mod tests {
         let parts = updater.parse_version("1.0.0").unwrap();
         assert_eq!(parts, vec![1, 0, 0]);
     }
+
+    #[test]
+    fn test_asset_sha256() {
+        let asset = |digest: Option<&str>| GithubAsset {
+            name: "app.dmg".to_string(),
+            browser_download_url: "https://example.com/app.dmg".to_string(),
+            size: 1,
+            content_type: "application/octet-stream".to_string(),
+            digest: digest.map(str::to_string),
+        };
+        let hex = "AB".repeat(32);
+        assert_eq!(asset(Some(&format!("sha256:{}", hex))).sha256(), Some(hex.to_ascii_lowercase()));
+        assert_eq!(asset(Some("sha512:abcd")).sha256(), None);
+        assert_eq!(asset(Some("sha256:xyz")).sha256(), None);
+        assert_eq!(asset(None).sha256(), None);
+    }
+
+    /// 启动只返回固定内容的 HTTP 服务，返回下载地址
+    async fn serve_bytes(body: &'static [u8]) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/app.dmg", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).await;
+                let header = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+                let _ = stream.write_all(header.as_bytes()).await;
+                let _ = stream.write_all(body).await;
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_download_update_verifies_checksum() {
+        let updater = AppUpdater::new("test".to_string(), "test".to_string(), "1.0.0".to_string());
+        let url = serve_bytes(b"update payload").await;
+        let dir = std::env::temp_dir().join(format!("ccp-update-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let save_path = dir.join("app.dmg");
+        let save = save_path.to_str().unwrap();
+        let sha256: String = Sha256::digest(b"update payload").iter().map(|b| format!("{:02x}", b)).collect();
+
+        let stages = std::sync::Mutex::new(Vec::new());
+        updater
+            .download_update(&url, save, &sha256, |p| stages.lock().unwrap().push((p.stage, p.downloaded_bytes)))
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&save_path).unwrap(), b"update payload");
+        let stages = stages.into_inner().unwrap();
+        assert_eq!(stages.first().map(|s| s.0), Some(DownloadStage::Downloading));
+        assert!(stages.contains(&(DownloadStage::Verifying, 14)));
+        assert_eq!(stages.last().map(|s| s.0), Some(DownloadStage::Complete));
+
+        // 校验失败时不保留任何文件
+        std::fs::remove_file(&save_path).unwrap();
+        let err = updater
+            .download_update(&url, save, &"0".repeat(64), |_| {})
+            .await
+            .unwrap_err();
+        assert!(err.contains("SHA-256 不匹配"));
+        assert!(!save_path.exists());
+        assert!(!dir.join("app.dmg.part").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -12,15 +12,37 @@
 
 use crate::db::DbPool;
 use crate::models::error::{AppError, AppResult};
-use crate::models::retry_strategy::RetryStrategy;
-use crate::models::switch_log::{CreateSwitchLogInput, SwitchLogDetail, SwitchReason, ErrorType};
+use crate::models::retry_strategy::{ConfigRetryState, RetryStrategy};
+use crate::models::switch_log::{
+    parse_switch_at, CreateSwitchLogInput, ErrorType, SwitchLogDetail, SwitchLogExportFormat,
+    SwitchLogExportRow, SwitchLogTimeWindow, SwitchReason,
+};
 use crate::services::error_classifier::ErrorClassifier;
 use crate::services::retry_manager::RetryManager;
-use crate::utils::time::now_rfc3339;
+use crate::utils::time::{now_rfc3339, to_rfc3339_utc};
+use std::io::Write;
 use std::sync::Arc;
 use tauri::AppHandle;
 use tokio::sync::RwLock;
 
+/// 最近切换事件的最大返回数量
+const MAX_RECENT_SWITCH_EVENTS: i32 = 500;
+
+/// 切换日志详情查询 (列顺序与 `map_switch_log_detail` 对应)
+const SWITCH_LOG_DETAIL_SELECT: &str = "SELECT
+        sl.id, sl.switch_at, sl.reason,
+        sc.name as source_name,
+        tc.name as target_name,
+        g.name as group_name,
+        sl.latency_before_ms, sl.latency_after_ms,
+        sl.error_message,
+        sl.group_id, sl.source_config_id, sl.target_config_id,
+        sl.retry_count, sl.error_type, sl.error_details
+    FROM SwitchLog sl
+    LEFT JOIN ApiConfig sc ON sl.source_config_id = sc.id
+    JOIN ApiConfig tc ON sl.target_config_id = tc.id
+    JOIN ConfigGroup g ON sl.group_id = g.id";
+
 /// 切换完成回调类型
 pub type SwitchCallback = Arc<dyn Fn(i64) -> () + Send + Sync>;
 
@@ -32,28 +54,40 @@ pub struct AutoSwitchService {
     error_classifier: ErrorClassifier,
     /// 切换完成回调（用于通知 ProxyService 更新状态）
     on_switch_callback: Arc<RwLock<Option<SwitchCallback>>>,
+    /// 切换时是否写入 ProxyService 记录（命名监听实例只更新自身的 ProxyConfig）
+    updates_proxy_service: bool,
 }
 
 impl AutoSwitchService {
     /// 创建新的自动切换服务
     pub fn new(db_pool: Arc<DbPool>) -> Self {
-        // 创建默认重试策略（可后续从配置读取）
-        let default_strategy = RetryStrategy {
-            max_retries: 3,
-            base_delay_ms: 2000,
-            max_delay_ms: 8000,
-            rate_limit_delay_ms: 30000,
-        };
-
         Self {
             db_pool,
             app_handle: Arc::new(RwLock::new(None)),
-            retry_manager: Arc::new(RetryManager::new(default_strategy)),
+            // 默认策略仅在无法读取分组策略时使用
+            retry_manager: Arc::new(RetryManager::with_default_strategy()),
             error_classifier: ErrorClassifier,
             on_switch_callback: Arc::new(RwLock::new(None)),
+            updates_proxy_service: true,
         }
     }
 
+    /// 创建命名监听实例使用的自动切换服务
+    ///
+    /// 拥有独立的重试状态，切换时不写入 ProxyService 记录，
+    /// 避免监听实例的故障切换覆盖主代理的当前配置
+    pub fn for_listener(db_pool: Arc<DbPool>) -> Self {
+        Self {
+            updates_proxy_service: false,
+            ..Self::new(db_pool)
+        }
+    }
+
+    /// 切换时是否写入 ProxyService 记录
+    pub fn updates_proxy_service(&self) -> bool {
+        self.updates_proxy_service
+    }
+
     /// 设置 Tauri app handle 用于事件推送
     #[allow(dead_code)]
     pub async fn set_app_handle(&self, handle: AppHandle) {
@@ -197,7 +231,7 @@ impl AutoSwitchService {
     /// 1. T038: 使用 ErrorClassifier 分类错误类型和可恢复性
     /// 2. T039: 如果是不可恢复错误 → 立即切换到下一个配置
     /// 3. T040: 如果是可恢复错误 → 查询 RetryManager 是否应该重试
-    /// 4. T041: 如果是限流错误 → 使用特殊的 30 秒延迟
+    /// 4. T041: 如果是限流错误 → 使用分组重试策略中的限流延迟
     /// 5. T042: 使用 RetryManager 管理失败计数
     /// 6. T044: 添加详细日志记录
     pub async fn handle_failure_with_retry(
@@ -257,10 +291,11 @@ impl AutoSwitchService {
             ).await;
         }
 
-        // T040 + T042: 可恢复错误 → 检查是否应该重试
+        // T040 + T042: 可恢复错误 → 按分组重试策略检查是否应该重试
+        let strategy = self.load_retry_strategy(group_id);
         let (should_retry, current_retry_count) = self
             .retry_manager
-            .should_retry(current_config_id, &recoverability);
+            .should_retry_with_strategy(current_config_id, &recoverability, &strategy);
 
         if !should_retry {
             // 达到最大重试次数 → 切换到下一个配置
@@ -269,11 +304,12 @@ impl AutoSwitchService {
                  ║  ⏭️  达到最大重试次数 - 切换到下一个配置                   ║\n\
                  ╠════════════════════════════════════════════════════════════╣\n\
                  ║  配置ID: {}                                                 \n\
-                 ║  重试次数: {} / 3                                           \n\
+                 ║  重试次数: {} / {}                                          \n\
                  ║  错误类型: {:?}                                            \n\
                  ╚════════════════════════════════════════════════════════════╝",
                 current_config_id,
                 current_retry_count,
+                strategy.max_retries,
                 error_type
             );
 
@@ -291,10 +327,11 @@ impl AutoSwitchService {
         // T041: 计算重试延迟（限流错误使用特殊延迟）
         let retry_delay_ms = self
             .retry_manager
-            .calculate_delay(current_config_id, &recoverability);
+            .calculate_delay_with_strategy(current_config_id, &recoverability, &strategy);
 
         // 增加失败计数
         let new_retry_count = self.retry_manager.increment_failure(current_config_id);
+        self.retry_manager.schedule_retry(current_config_id, retry_delay_ms);
 
         // T044: 详细日志记录
         if recoverability.needs_rate_limit_delay() {
@@ -303,12 +340,13 @@ impl AutoSwitchService {
                  │  ⏸️  限流错误 - 等待后重试                              │\n\
                  ├─────────────────────────────────────────────────────────┤\n\
                  │  配置ID: {}                                              \n\
-                 │  重试次数: {} / 3                                        \n\
+                 │  重试次数: {} / {}                                       \n\
                  │  等待时间: {} 毫秒                                       \n\
                  │  错误类型: {:?}                                         \n\
                  └─────────────────────────────────────────────────────────┘",
                 current_config_id,
                 new_retry_count,
+                strategy.max_retries,
                 retry_delay_ms,
                 error_type
             );
@@ -318,12 +356,13 @@ impl AutoSwitchService {
                  │  🔄 可恢复错误 - 准备重试                               │\n\
                  ├─────────────────────────────────────────────────────────┤\n\
                  │  配置ID: {}                                              \n\
-                 │  重试次数: {} / 3                                        \n\
+                 │  重试次数: {} / {}                                       \n\
                  │  延迟时间: {} 毫秒                                       \n\
                  │  错误类型: {:?}                                         \n\
                  └─────────────────────────────────────────────────────────┘",
                 current_config_id,
                 new_retry_count,
+                strategy.max_retries,
                 retry_delay_ms,
                 error_type
             );
@@ -430,16 +469,101 @@ impl AutoSwitchService {
         }
     }
 
+    /// 注入模拟故障（仅用于测试）
+    ///
+    /// 不发送任何真实请求，直接把合成的错误信息交给 `handle_failure_with_retry`，
+    /// 走与真实请求失败完全相同的分类、重试计数、切换、日志和事件推送流程。
+    ///
+    /// # Arguments
+    /// - `config_id`: 模拟失败的配置 ID
+    /// - `group_id`: 配置所属分组 ID
+    /// - `reason`: 错误类型（如 `timeout`、`rate_limit`）或任意错误信息
+    ///
+    /// # Returns
+    /// - (合成的错误信息, 切换到的新配置 ID)
+    pub async fn inject_failure(
+        &self,
+        config_id: i64,
+        group_id: i64,
+        reason: &str,
+    ) -> AppResult<(String, Option<i64>)> {
+        let error_message = synthetic_error_message(reason);
+
+        log::warn!(
+            "🧪 注入模拟故障: config_id={}, group_id={}, error={}",
+            config_id,
+            group_id,
+            error_message
+        );
+
+        let switched_to = self
+            .handle_failure_with_retry(config_id, group_id, error_message.clone(), None)
+            .await?;
+
+        Ok((error_message, switched_to))
+    }
+
+    /// 读取分组的重试策略（分组不存在时使用默认策略）
+    fn load_retry_strategy(&self, group_id: i64) -> RetryStrategy {
+        self.db_pool
+            .with_connection(|conn| {
+                use crate::services::config_manager::ConfigManager;
+                ConfigManager::get_group_by_id(conn, group_id).map(|g| g.retry_strategy())
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("读取分组 {} 重试策略失败,使用默认策略: {}", group_id, e);
+                RetryStrategy::default()
+            })
+    }
+
     /// T043: 重置失败计数器（成功响应后调用）
     pub fn reset_failure_counter(&self, config_id: i64) {
         self.retry_manager.reset_counter(config_id);
         log::debug!("重置失败计数器: config_id={}", config_id);
     }
 
+    /// 获取分组内每个配置的重试状态（重试次数、最大重试次数、下一次允许重试的时间）
+    pub fn get_retry_state(&self, group_id: i64) -> AppResult<Vec<ConfigRetryState>> {
+        use crate::services::api_config::ApiConfigService;
+
+        let configs = self
+            .db_pool
+            .with_connection(|conn| ApiConfigService::list_configs(conn, Some(group_id)))?;
+        let strategy = self.load_retry_strategy(group_id);
+        let now = chrono::Local::now();
+
+        Ok(configs
+            .into_iter()
+            .map(|config| {
+                let retry_count = self.retry_manager.get_failure_count(config.id);
+                // 计数已被重置时不再处于退避中
+                let next_retry_at = self
+                    .retry_manager
+                    .get_next_retry_at(config.id)
+                    .filter(|_| retry_count > 0);
+                ConfigRetryState {
+                    config_id: config.id,
+                    config_name: config.name,
+                    retry_count,
+                    max_retries: strategy.max_retries,
+                    in_backoff: next_retry_at.is_some_and(|at| at > now),
+                    next_retry_at: next_retry_at.as_ref().map(to_rfc3339_utc),
+                }
+            })
+            .collect())
+    }
+
+    /// 手动重置配置的重试状态（修复后端后解除退避）
+    pub fn reset_retry_state(&self, config_id: i64) {
+        self.retry_manager.reset_counter(config_id);
+        log::info!("已手动重置重试状态: config_id={}", config_id);
+    }
+
     /// 查找下一个可用配置
     ///
     /// 策略:
-    /// 1. 获取分组内所有启用且可用的配置(is_enabled = true AND is_available = true)
+    /// 1. 获取分组内所有启用且可用的配置(is_enabled = true AND is_available = true)，
+    ///    排除仍处于定时停用期内的配置
     /// 2. 按权重分数排序（优先）或 sort_order 排序（兜底）
     /// 3. 找到当前配置的位置
     /// 4. 返回下一个配置(循环到第一个)
@@ -459,6 +583,7 @@ impl AutoSwitchService {
                 .prepare(
                     "SELECT id FROM ApiConfig
                      WHERE group_id = ?1 AND is_enabled = 1 AND is_available = 1
+                       AND (disabled_until IS NULL OR julianday(disabled_until) <= julianday('now'))
                      ORDER BY weight_score DESC, sort_order ASC",
                 )
                 .map_err(|e| AppError::DatabaseError {
@@ -517,6 +642,7 @@ impl AutoSwitchService {
             let result = conn.query_row(
                 "SELECT id FROM ApiConfig
                  WHERE group_id = ?1 AND is_enabled = 1 AND is_available = 1
+                   AND (disabled_until IS NULL OR julianday(disabled_until) <= julianday('now'))
                  ORDER BY weight_score DESC, sort_order ASC
                  LIMIT 1",
                 params![group_id],
@@ -560,8 +686,9 @@ impl AutoSwitchService {
             conn.execute(
                 "INSERT INTO SwitchLog (
                     switch_at, reason, source_config_id, target_config_id,
-                    group_id, is_cross_group, latency_before_ms, latency_after_ms, error_message
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    group_id, is_cross_group, latency_before_ms, latency_after_ms, error_message,
+                    retry_count, error_type, error_details
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
                 params![
                     now,
                     input.reason.as_str(),
@@ -572,6 +699,9 @@ impl AutoSwitchService {
                     input.latency_before_ms,
                     input.latency_after_ms,
                     input.error_message,
+                    input.retry_count.unwrap_or(0),
+                    input.error_type.as_ref().map(|t| t.as_str()),
+                    input.error_details,
                 ],
             )
             .map_err(|e| AppError::DatabaseError {
@@ -714,6 +844,10 @@ impl AutoSwitchService {
     /// # Arguments
     /// - `new_config_id`: 新的配置 ID
     async fn update_proxy_service_config(&self, new_config_id: i64) -> AppResult<()> {
+        if !self.updates_proxy_service {
+            return Ok(());
+        }
+
         self.db_pool.with_connection(|conn| {
             use rusqlite::params;
 
@@ -757,47 +891,9 @@ impl AutoSwitchService {
             use rusqlite::params;
 
             conn.query_row(
-                "SELECT
-                    sl.id, sl.switch_at, sl.reason,
-                    sc.name as source_name,
-                    tc.name as target_name,
-                    g.name as group_name,
-                    sl.latency_before_ms, sl.latency_after_ms,
-                    sl.error_message
-                FROM SwitchLog sl
-                LEFT JOIN ApiConfig sc ON sl.source_config_id = sc.id
-                JOIN ApiConfig tc ON sl.target_config_id = tc.id
-                JOIN ConfigGroup g ON sl.group_id = g.id
-                WHERE sl.id = ?1",
+                &format!("{} WHERE sl.id = ?1", SWITCH_LOG_DETAIL_SELECT),
                 params![log_id],
-                |row| {
-                    let latency_before: Option<i32> = row.get(6)?;
-                    let latency_after: Option<i32> = row.get(7)?;
-                    let latency_improvement = match (latency_before, latency_after) {
-                        (Some(before), Some(after)) => Some(before - after),
-                        _ => None,
-                    };
-
-                    let reason_str: String = row.get(2)?;
-                    let reason = SwitchReason::from_str(&reason_str)
-                        .unwrap_or(SwitchReason::Manual);
-
-                    Ok(SwitchLogDetail {
-                        id: row.get(0)?,
-                        switch_at: row.get(1)?,
-                        reason,
-                        source_config_name: row.get(3)?,
-                        target_config_name: row.get(4)?,
-                        group_name: row.get(5)?,
-                        latency_before_ms: latency_before,
-                        latency_after_ms: latency_after,
-                        latency_improvement_ms: latency_improvement,
-                        error_message: row.get(8)?,
-                        retry_count: 0,
-                        error_type: None,
-                        error_details: None,
-                    })
-                },
+                map_switch_log_detail,
             )
             .map_err(|e| AppError::DatabaseError {
                 message: format!("查询切换日志详情失败: {}", e),
@@ -823,37 +919,18 @@ impl AutoSwitchService {
         self.db_pool.with_connection(|conn| {
             let (query, params): (String, Vec<Box<dyn rusqlite::ToSql>>) = if let Some(gid) = group_id {
                 (
-                    "SELECT
-                        sl.id, sl.switch_at, sl.reason,
-                        sc.name as source_name,
-                        tc.name as target_name,
-                        g.name as group_name,
-                        sl.latency_before_ms, sl.latency_after_ms,
-                        sl.error_message
-                    FROM SwitchLog sl
-                    LEFT JOIN ApiConfig sc ON sl.source_config_id = sc.id
-                    JOIN ApiConfig tc ON sl.target_config_id = tc.id
-                    JOIN ConfigGroup g ON sl.group_id = g.id
-                    WHERE sl.group_id = ?1
-                    ORDER BY sl.switch_at DESC
-                    LIMIT ?2 OFFSET ?3".to_string(),
+                    format!(
+                        "{} WHERE sl.group_id = ?1 ORDER BY sl.switch_at DESC LIMIT ?2 OFFSET ?3",
+                        SWITCH_LOG_DETAIL_SELECT
+                    ),
                     vec![Box::new(gid), Box::new(limit), Box::new(offset)],
                 )
             } else {
                 (
-                    "SELECT
-                        sl.id, sl.switch_at, sl.reason,
-                        sc.name as source_name,
-                        tc.name as target_name,
-                        g.name as group_name,
-                        sl.latency_before_ms, sl.latency_after_ms,
-                        sl.error_message
-                    FROM SwitchLog sl
-                    LEFT JOIN ApiConfig sc ON sl.source_config_id = sc.id
-                    JOIN ApiConfig tc ON sl.target_config_id = tc.id
-                    JOIN ConfigGroup g ON sl.group_id = g.id
-                    ORDER BY sl.switch_at DESC
-                    LIMIT ?1 OFFSET ?2".to_string(),
+                    format!(
+                        "{} ORDER BY sl.switch_at DESC LIMIT ?1 OFFSET ?2",
+                        SWITCH_LOG_DETAIL_SELECT
+                    ),
                     vec![Box::new(limit), Box::new(offset)],
                 )
             };
@@ -866,34 +943,7 @@ impl AutoSwitchService {
                 params.iter().map(|p| p.as_ref()).collect();
 
             let logs = stmt
-                .query_map(&params_ref[..], |row| {
-                    let latency_before: Option<i32> = row.get(6)?;
-                    let latency_after: Option<i32> = row.get(7)?;
-                    let latency_improvement = match (latency_before, latency_after) {
-                        (Some(before), Some(after)) => Some(before - after),
-                        _ => None,
-                    };
-
-                    let reason_str: String = row.get(2)?;
-                    let reason = SwitchReason::from_str(&reason_str)
-                        .unwrap_or(SwitchReason::Manual);
-
-                    Ok(SwitchLogDetail {
-                        id: row.get(0)?,
-                        switch_at: row.get(1)?,
-                        reason,
-                        source_config_name: row.get(3)?,
-                        target_config_name: row.get(4)?,
-                        group_name: row.get(5)?,
-                        latency_before_ms: latency_before,
-                        latency_after_ms: latency_after,
-                        latency_improvement_ms: latency_improvement,
-                        error_message: row.get(8)?,
-                        retry_count: 0,
-                        error_type: None,
-                        error_details: None,
-                    })
-                })
+                .query_map(&params_ref[..], map_switch_log_detail)
                 .map_err(|e| AppError::DatabaseError {
                     message: format!("查询切换日志失败: {}", e),
                 })?
@@ -906,6 +956,164 @@ impl AutoSwitchService {
         })
     }
 
+    /// 获取最近的切换事件
+    ///
+    /// 与 `auto-switch-triggered` 事件携带的内容一致 (含配置名、原因、重试次数和错误类型)，
+    /// 供前端在订阅事件前补齐最近的历史
+    ///
+    /// # Arguments
+    /// - `limit`: 返回数量 (1-500)
+    ///
+    /// # Returns
+    /// - Vec<SwitchLogDetail>: 按时间倒序的切换事件
+    pub fn get_recent_switch_events(&self, limit: i32) -> AppResult<Vec<SwitchLogDetail>> {
+        let limit = limit.clamp(1, MAX_RECENT_SWITCH_EVENTS);
+
+        self.db_pool.with_connection(|conn| {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "{} ORDER BY sl.switch_at DESC, sl.id DESC LIMIT ?1",
+                    SWITCH_LOG_DETAIL_SELECT
+                ))
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("准备查询失败: {}", e),
+                })?;
+
+            let events = stmt
+                .query_map([limit], map_switch_log_detail)
+                .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("查询最近切换事件失败: {}", e),
+                })?;
+
+            Ok(events)
+        })
+    }
+
+    /// 导出切换日志时间线
+    ///
+    /// 不分页，按时间升序逐行写入 `writer`，并计算距上一次切换的间隔
+    ///
+    /// # Arguments
+    /// - `group_id`: 分组 ID(可选,用于筛选)
+    /// - `window`: 时间窗口
+    /// - `format`: 导出格式 (CSV / JSON 数组)
+    /// - `writer`: 输出目标
+    ///
+    /// # Returns
+    /// - usize: 导出的记录数
+    pub fn export_switch_logs<W: Write>(
+        &self,
+        group_id: Option<i64>,
+        window: &SwitchLogTimeWindow,
+        format: SwitchLogExportFormat,
+        writer: &mut W,
+    ) -> AppResult<usize> {
+        let io_error = |e: std::io::Error| AppError::IoError {
+            message: format!("写入切换日志导出失败: {}", e),
+        };
+
+        self.db_pool.with_connection(|conn| {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT
+                        sl.id, sl.switch_at, sl.reason,
+                        sl.group_id, g.name,
+                        sl.source_config_id, sc.name,
+                        sl.target_config_id, tc.name,
+                        sl.latency_before_ms, sl.latency_after_ms,
+                        sl.retry_count, sl.error_type, sl.error_message
+                    FROM SwitchLog sl
+                    LEFT JOIN ApiConfig sc ON sl.source_config_id = sc.id
+                    LEFT JOIN ApiConfig tc ON sl.target_config_id = tc.id
+                    LEFT JOIN ConfigGroup g ON sl.group_id = g.id
+                    WHERE (?1 IS NULL OR sl.group_id = ?1)
+                      AND (?2 IS NULL OR julianday(sl.switch_at) >= julianday(?2))
+                      AND (?3 IS NULL OR julianday(sl.switch_at) <= julianday(?3))
+                    ORDER BY julianday(sl.switch_at) ASC, sl.id ASC",
+                )
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("准备查询失败: {}", e),
+                })?;
+
+            let rows = stmt
+                .query_map(
+                    rusqlite::params![group_id, window.start, window.end],
+                    |row| {
+                        Ok(SwitchLogExportRow {
+                            id: row.get(0)?,
+                            switch_at: row.get(1)?,
+                            reason: row.get(2)?,
+                            group_id: row.get(3)?,
+                            group_name: row.get(4)?,
+                            source_config_id: row.get(5)?,
+                            source_config_name: row.get(6)?,
+                            target_config_id: row.get(7)?,
+                            target_config_name: row.get(8)?,
+                            latency_before_ms: row.get(9)?,
+                            latency_after_ms: row.get(10)?,
+                            retry_count: row.get(11)?,
+                            error_type: row.get(12)?,
+                            error_message: row.get(13)?,
+                            ms_since_previous: None,
+                        })
+                    },
+                )
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("查询切换日志失败: {}", e),
+                })?;
+
+            match format {
+                SwitchLogExportFormat::Csv => {
+                    writeln!(writer, "{}", SwitchLogExportRow::CSV_HEADER).map_err(io_error)?
+                }
+                SwitchLogExportFormat::Json => writer.write_all(b"[").map_err(io_error)?,
+            }
+
+            let mut count = 0;
+            let mut previous_at: Option<chrono::DateTime<chrono::Utc>> = None;
+            for row in rows {
+                let mut row = row.map_err(|e| AppError::DatabaseError {
+                    message: format!("解析切换日志失败: {}", e),
+                })?;
+
+                let switch_at = parse_switch_at(&row.switch_at);
+                row.ms_since_previous = match (previous_at, switch_at) {
+                    (Some(prev), Some(current)) => Some((current - prev).num_milliseconds()),
+                    _ => None,
+                };
+                if switch_at.is_some() {
+                    previous_at = switch_at;
+                }
+
+                match format {
+                    SwitchLogExportFormat::Csv => {
+                        writeln!(writer, "{}", row.to_csv_record()).map_err(io_error)?
+                    }
+                    SwitchLogExportFormat::Json => {
+                        if count > 0 {
+                            writer.write_all(b",").map_err(io_error)?;
+                        }
+                        writer.write_all(b"\n  ").map_err(io_error)?;
+                        serde_json::to_writer(&mut *writer, &row).map_err(|e| {
+                            AppError::IoError {
+                                message: format!("序列化切换日志失败: {}", e),
+                            }
+                        })?;
+                    }
+                }
+                count += 1;
+            }
+
+            if format == SwitchLogExportFormat::Json {
+                writer.write_all(b"\n]\n").map_err(io_error)?;
+            }
+            writer.flush().map_err(io_error)?;
+
+            Ok(count)
+        })
+    }
+
     /// 清空切换日志
     ///
     /// # Arguments
@@ -933,6 +1141,60 @@ impl AutoSwitchService {
     }
 }
 
+/// 将 `SWITCH_LOG_DETAIL_SELECT` 的查询结果映射为 SwitchLogDetail
+fn map_switch_log_detail(row: &rusqlite::Row) -> rusqlite::Result<SwitchLogDetail> {
+    let latency_before: Option<i32> = row.get(6)?;
+    let latency_after: Option<i32> = row.get(7)?;
+    let latency_improvement = match (latency_before, latency_after) {
+        (Some(before), Some(after)) => Some(before - after),
+        _ => None,
+    };
+
+    let reason_str: String = row.get(2)?;
+    let reason = SwitchReason::from_str(&reason_str).unwrap_or(SwitchReason::Manual);
+    let error_type = row
+        .get::<_, Option<String>>(13)?
+        .and_then(|t| ErrorType::from_str(&t).ok());
+
+    Ok(SwitchLogDetail {
+        id: row.get(0)?,
+        group_id: row.get(9)?,
+        source_config_id: row.get(10)?,
+        target_config_id: row.get(11)?,
+        switch_at: row.get(1)?,
+        reason,
+        source_config_name: row.get(3)?,
+        target_config_name: row.get(4)?,
+        group_name: row.get(5)?,
+        latency_before_ms: latency_before,
+        latency_after_ms: latency_after,
+        latency_improvement_ms: latency_improvement,
+        error_message: row.get(8)?,
+        retry_count: row.get(12)?,
+        error_type,
+        error_details: row.get(14)?,
+    })
+}
+
+/// 生成模拟故障的错误信息
+///
+/// `reason` 为已知错误类型时返回能被 `ErrorClassifier` 归入该类型的典型错误信息，
+/// 否则原样使用 `reason` 作为错误信息。统一加上 `[simulated]` 前缀以便在日志中区分。
+pub fn synthetic_error_message(reason: &str) -> String {
+    let message = match ErrorType::from_str(reason.trim()) {
+        Ok(ErrorType::Network) => "Connection failed: connection refused".to_string(),
+        Ok(ErrorType::Timeout) => "Request timeout".to_string(),
+        Ok(ErrorType::Authentication) => "Authentication failed: 401 invalid api key".to_string(),
+        Ok(ErrorType::InsufficientBalance) => "Insufficient balance: 402 payment required".to_string(),
+        Ok(ErrorType::AccountBanned) => "Account banned or suspended: 403".to_string(),
+        Ok(ErrorType::RateLimit) => "Rate limit or quota exceeded: 429 too many requests".to_string(),
+        Ok(ErrorType::ServerError) => "Server error (503 Service Unavailable)".to_string(),
+        Ok(ErrorType::Unknown) | Err(_) => reason.trim().to_string(),
+    };
+
+    format!("[simulated] {}", message)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -949,6 +1211,85 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[tokio::test]
+    async fn test_recent_switch_events_include_retry_context() {
+        let db_pool = Arc::new(DbPool::new(crate::db::test_db()));
+        db_pool
+            .with_connection(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO ConfigGroup (id, name) VALUES (1, 'g');
+                     INSERT INTO ApiConfig (id, name, api_key, server_url, group_id) VALUES
+                         (1, 'primary', 'k', 'https://a.example.com', 1),
+                         (2, 'backup', 'k', 'https://b.example.com', 1);",
+                )
+                .unwrap();
+                Ok(())
+            })
+            .unwrap();
+        let service = AutoSwitchService::new(db_pool);
+
+        for (reason, retry_count, error_type) in [
+            (SwitchReason::Manual, None, None),
+            (SwitchReason::RetryFailed, Some(3), Some(ErrorType::RateLimit)),
+        ] {
+            service
+                .log_switch(CreateSwitchLogInput {
+                    reason,
+                    source_config_id: Some(1),
+                    target_config_id: 2,
+                    group_id: 1,
+                    latency_before_ms: None,
+                    latency_after_ms: None,
+                    error_message: None,
+                    retry_count,
+                    error_type,
+                    error_details: Some("{}".to_string()).filter(|_| retry_count.is_some()),
+                })
+                .await
+                .unwrap();
+        }
+
+        let events = service.get_recent_switch_events(10).unwrap();
+        assert_eq!(events.len(), 2);
+        let latest = &events[0];
+        assert_eq!(latest.reason, SwitchReason::RetryFailed);
+        assert_eq!(latest.retry_count, 3);
+        assert_eq!(latest.error_type, Some(ErrorType::RateLimit));
+        assert_eq!(latest.error_details.as_deref(), Some("{}"));
+        assert_eq!(latest.source_config_name.as_deref(), Some("primary"));
+        assert_eq!(latest.target_config_name, "backup");
+        assert_eq!(latest.target_config_id, 2);
+        assert_eq!(events[1].retry_count, 0);
+        assert!(events[1].error_type.is_none());
+
+        assert_eq!(service.get_recent_switch_events(0).unwrap().len(), 1);
+        assert_eq!(service.get_switch_log_detail(latest.id).unwrap().retry_count, 3);
+    }
+
+    #[test]
+    fn test_synthetic_error_message_classification() {
+        let classifier = ErrorClassifier::new();
+        for error_type in [
+            ErrorType::Network,
+            ErrorType::Timeout,
+            ErrorType::Authentication,
+            ErrorType::InsufficientBalance,
+            ErrorType::AccountBanned,
+            ErrorType::RateLimit,
+            ErrorType::ServerError,
+        ] {
+            let message = synthetic_error_message(error_type.as_str());
+            assert!(message.starts_with("[simulated] "));
+            assert_eq!(classifier.classify(&message).0, error_type);
+        }
+
+        // 非已知类型时原样使用
+        assert_eq!(
+            synthetic_error_message("upstream exploded"),
+            "[simulated] upstream exploded"
+        );
+    }
+
     #[test]
     fn test_switch_reason() {
         assert!(SwitchReason::ConnectionFailed.is_automatic());
@@ -0,0 +1,262 @@
+/**
+ * Backend Models Service
+ * 查询后端实际支持的模型列表
+ *
+ * Features:
+ * - 按供应商类型调用对应的模型列表接口
+ * - 使用配置的 API 密钥,认证方式与代理转发一致
+ * - 按配置短时间缓存查询结果
+ */
+
+use crate::db::DbPool;
+use crate::models::api_config::ProviderType;
+use crate::models::error::{AppError, AppResult};
+use crate::services::api_config::ApiConfigService;
+use crate::utils::time::now_rfc3339;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// HTTP 请求超时时间(秒)
+const REQUEST_TIMEOUT_SECS: u64 = 15;
+
+/// 模型列表缓存有效期(秒)
+const CACHE_TTL_SECS: u64 = 300;
+
+/// 按配置缓存的模型列表
+static MODEL_CACHE: LazyLock<Mutex<HashMap<i64, (Instant, BackendModelList)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 后端模型列表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendModelList {
+    /// 配置 ID
+    pub config_id: i64,
+    /// 模型 ID 列表
+    pub models: Vec<String>,
+    /// 查询时间
+    pub fetched_at: String,
+    /// 是否来自缓存
+    pub from_cache: bool,
+}
+
+/// 后端模型查询服务
+pub struct BackendModelsService;
+
+impl BackendModelsService {
+    /// 查询配置对应后端的模型列表
+    ///
+    /// # Arguments
+    /// - `db_pool`: 数据库连接池
+    /// - `config_id`: API 配置 ID
+    /// - `force_refresh`: 是否忽略缓存
+    pub async fn fetch_models(
+        db_pool: &Arc<DbPool>,
+        config_id: i64,
+        force_refresh: bool,
+    ) -> AppResult<BackendModelList> {
+        if !force_refresh {
+            if let Some(cached) = Self::get_cached(config_id) {
+                log::debug!("Using cached model list for config {}", config_id);
+                return Ok(cached);
+            }
+        }
+
+        let (config, api_key) = db_pool.with_connection(|conn| {
+            let config = ApiConfigService::get_config_by_id(conn, config_id)?;
+            let api_key = ApiConfigService::get_api_key(conn, config_id)?;
+            Ok((config, api_key))
+        })?;
+
+        let url = Self::models_url(&config.server_url, &config.provider_type);
+        log::info!("Fetching backend models for config {} from {}", config_id, url);
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| AppError::ServiceError {
+                message: format!("创建 HTTP 客户端失败: {}", e),
+            })?;
+
+        // 认证方式与代理转发保持一致（Bearer）,同时添加各供应商的原生认证头
+        let mut request = client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", api_key));
+        request = match config.provider_type {
+            ProviderType::Claude => request
+                .header("x-api-key", &api_key)
+                .header("anthropic-version", "2023-06-01"),
+            ProviderType::Gemini => request.header("x-goog-api-key", &api_key),
+            ProviderType::OpenAI => request,
+        };
+
+        let response = request.send().await.map_err(|e| AppError::ServiceError {
+            message: if e.is_timeout() {
+                "查询模型列表超时".to_string()
+            } else {
+                format!("查询模型列表失败: {}", e)
+            },
+        })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::ServiceError {
+                message: format!(
+                    "查询模型列表失败 (HTTP {}): {}",
+                    status.as_u16(),
+                    body.chars().take(200).collect::<String>()
+                ),
+            });
+        }
+
+        let json: serde_json::Value = response.json().await.map_err(|e| AppError::ParseError {
+            message: format!("解析模型列表响应失败: {}", e),
+        })?;
+
+        let models = parse_model_ids(&json);
+        if models.is_empty() {
+            return Err(AppError::ParseError {
+                message: "后端未返回任何模型".to_string(),
+            });
+        }
+
+        log::info!("Fetched {} models for config {}", models.len(), config_id);
+
+        let list = BackendModelList {
+            config_id,
+            models,
+            fetched_at: now_rfc3339(),
+            from_cache: false,
+        };
+
+        if let Ok(mut cache) = MODEL_CACHE.lock() {
+            cache.insert(config_id, (Instant::now(), list.clone()));
+        }
+
+        Ok(list)
+    }
+
+    /// 清除指定配置的缓存（配置的地址或密钥变更后调用）
+    pub fn invalidate(config_id: i64) {
+        if let Ok(mut cache) = MODEL_CACHE.lock() {
+            cache.remove(&config_id);
+        }
+    }
+
+    fn get_cached(config_id: i64) -> Option<BackendModelList> {
+        let cache = MODEL_CACHE.lock().ok()?;
+        let (cached_at, list) = cache.get(&config_id)?;
+        if cached_at.elapsed() > Duration::from_secs(CACHE_TTL_SECS) {
+            return None;
+        }
+
+        let mut list = list.clone();
+        list.from_cache = true;
+        Some(list)
+    }
+
+    /// 构建模型列表接口地址
+    fn models_url(server_url: &str, provider: &ProviderType) -> String {
+        let base = server_url.trim_end_matches('/');
+        match provider {
+            // Anthropic 模型列表默认分页 20 条
+            ProviderType::Claude => format!("{}/v1/models?limit=1000", base),
+            ProviderType::OpenAI => format!("{}/v1/models", base),
+            ProviderType::Gemini => format!("{}/v1beta/models?pageSize=1000", base),
+        }
+    }
+}
+
+/// 从模型列表响应中提取模型 ID
+///
+/// 支持 Claude/OpenAI 格式（`data[].id`）和 Gemini 格式（`models[].name`,去除 `models/` 前缀）
+pub fn parse_model_ids(json: &serde_json::Value) -> Vec<String> {
+    let mut models: Vec<String> = Vec::new();
+
+    let ids = json
+        .get("data")
+        .and_then(|d| d.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|m| m.get("id").and_then(|id| id.as_str()))
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+        })
+        .or_else(|| {
+            json.get("models").and_then(|d| d.as_array()).map(|items| {
+                items
+                    .iter()
+                    .filter_map(|m| m.get("name").and_then(|n| n.as_str()))
+                    .map(|name| name.strip_prefix("models/").unwrap_or(name).to_string())
+                    .collect::<Vec<_>>()
+            })
+        })
+        .unwrap_or_default();
+
+    for id in ids {
+        if !id.is_empty() && !models.contains(&id) {
+            models.push(id);
+        }
+    }
+
+    models
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_claude_and_openai_models() {
+        let json = json!({
+            "data": [
+                {"id": "claude-sonnet-4-5-20250929", "type": "model"},
+                {"id": "claude-haiku-4-5-20251001", "type": "model"},
+                {"id": "claude-sonnet-4-5-20250929", "type": "model"}
+            ],
+            "has_more": false
+        });
+
+        assert_eq!(
+            parse_model_ids(&json),
+            vec!["claude-sonnet-4-5-20250929", "claude-haiku-4-5-20251001"]
+        );
+    }
+
+    #[test]
+    fn test_parse_gemini_models() {
+        let json = json!({
+            "models": [
+                {"name": "models/gemini-2.5-pro"},
+                {"name": "models/gemini-2.5-flash"}
+            ]
+        });
+
+        assert_eq!(parse_model_ids(&json), vec!["gemini-2.5-pro", "gemini-2.5-flash"]);
+    }
+
+    #[test]
+    fn test_parse_unknown_format() {
+        assert!(parse_model_ids(&json!({"error": "unauthorized"})).is_empty());
+    }
+
+    #[test]
+    fn test_models_url() {
+        assert_eq!(
+            BackendModelsService::models_url("https://api.example.com/", &ProviderType::Claude),
+            "https://api.example.com/v1/models?limit=1000"
+        );
+        assert_eq!(
+            BackendModelsService::models_url("https://relay.example.com/openai", &ProviderType::OpenAI),
+            "https://relay.example.com/openai/v1/models"
+        );
+        assert_eq!(
+            BackendModelsService::models_url("https://generativelanguage.googleapis.com", &ProviderType::Gemini),
+            "https://generativelanguage.googleapis.com/v1beta/models?pageSize=1000"
+        );
+    }
+}
@@ -1,8 +1,7 @@
 use crate::models::config_backup::{ConfigBackup, Platform};
 use crate::models::error::{AppError, AppResult};
 use crate::utils::paths;
-use crate::utils::time::now_rfc3339;
-use chrono::Local;
+use crate::utils::time::{now_rfc3339, to_rfc3339_utc};
 use std::fs;
 use std::path::PathBuf;
 
@@ -76,7 +75,7 @@ impl BackupService {
                             let modified = metadata.modified().map_err(|e| AppError::IoError {
                                 message: format!("获取文件修改时间失败: {}", e),
                             })?;
-                            let backup_time = chrono::DateTime::<Local>::from(modified).to_rfc3339();
+                            let backup_time = to_rfc3339_utc(&chrono::DateTime::<chrono::Utc>::from(modified));
 
                             log::info!(
                                 "备份时间已更新: {} -> {}",
@@ -118,7 +117,7 @@ impl BackupService {
                             let modified = metadata.modified().map_err(|e| AppError::IoError {
                                 message: format!("获取文件修改时间失败: {}", e),
                             })?;
-                            let backup_time = chrono::DateTime::<Local>::from(modified).to_rfc3339();
+                            let backup_time = to_rfc3339_utc(&chrono::DateTime::<chrono::Utc>::from(modified));
 
                             log::info!(
                                 "备份时间已更新: {} -> {}",
@@ -267,7 +266,7 @@ impl BackupService {
             })?;
 
             // 转换为 RFC3339 时间
-            let backup_time = chrono::DateTime::<Local>::from(modified).to_rfc3339();
+            let backup_time = to_rfc3339_utc(&chrono::DateTime::<chrono::Utc>::from(modified));
 
             // 读取备份内容
             let content = fs::read_to_string(&path).unwrap_or_default();
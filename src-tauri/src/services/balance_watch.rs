@@ -0,0 +1,191 @@
+/**
+ * Balance Watch
+ * 对单个配置高频轮询余额，用于大量使用期间的实时监控
+ *
+ * Features:
+ * - 与 balance_scheduler 的定时查询相互独立
+ * - 每个配置最多一个监视任务，重复启动会替换原任务 (可用于修改轮询间隔)
+ * - 超过最长监视时间或配置不可查询时自动停止
+ */
+
+use crate::db::DbPool;
+use crate::models::balance::BalanceInfo;
+use crate::models::error::{AppError, AppResult};
+use crate::services::api_config::ApiConfigService;
+use crate::services::balance_service::BalanceService;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration, Instant, MissedTickBehavior};
+
+/// 余额更新事件名称
+pub const BALANCE_UPDATED_EVENT: &str = "balance-updated";
+
+/// 轮询间隔下限（秒）
+pub const MIN_WATCH_INTERVAL_SECS: u64 = 2;
+
+/// 轮询间隔上限（秒）
+pub const MAX_WATCH_INTERVAL_SECS: u64 = 300;
+
+/// 单次监视的最长时间，超过后自动停止
+const MAX_WATCH_DURATION: Duration = Duration::from_secs(60 * 60);
+
+struct WatchTask {
+    /// 任务序号，任务结束时只移除自己的记录
+    generation: u64,
+    handle: JoinHandle<()>,
+}
+
+/// 余额监视管理器
+pub struct BalanceWatchManager {
+    db_pool: Arc<DbPool>,
+    watches: Arc<Mutex<HashMap<i64, WatchTask>>>,
+    next_generation: AtomicU64,
+}
+
+impl BalanceWatchManager {
+    /// 创建新的余额监视管理器
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        Self {
+            db_pool,
+            watches: Arc::new(Mutex::new(HashMap::new())),
+            next_generation: AtomicU64::new(0),
+        }
+    }
+
+    /// 开始监视配置余额，每次查询完成后调用 `on_update`
+    ///
+    /// 该配置已有监视任务时先停止原任务
+    pub fn start<F>(&self, config_id: i64, interval_secs: u64, on_update: F) -> AppResult<()>
+    where
+        F: Fn(&BalanceInfo) + Send + 'static,
+    {
+        if !(MIN_WATCH_INTERVAL_SECS..=MAX_WATCH_INTERVAL_SECS).contains(&interval_secs) {
+            return Err(AppError::ValidationError {
+                field: "interval_secs".to_string(),
+                message: format!(
+                    "轮询间隔必须在 {}-{} 秒之间",
+                    MIN_WATCH_INTERVAL_SECS, MAX_WATCH_INTERVAL_SECS
+                ),
+            });
+        }
+
+        let config = self
+            .db_pool
+            .with_connection(|conn| ApiConfigService::get_config_by_id(conn, config_id))?;
+        if config.balance_query_url.as_deref().is_none_or(str::is_empty) {
+            return Err(AppError::ValidationError {
+                field: "balance_query_url".to_string(),
+                message: "未配置余额查询接口".to_string(),
+            });
+        }
+
+        let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+        let db_pool = self.db_pool.clone();
+        let watches = self.watches.clone();
+
+        let mut guard = self.watches.lock().unwrap_or_else(|e| e.into_inner());
+        let handle = tokio::spawn(async move {
+            let service = BalanceService::new(db_pool);
+            let started = Instant::now();
+            let mut ticker = interval(Duration::from_secs(interval_secs));
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            while started.elapsed() < MAX_WATCH_DURATION {
+                ticker.tick().await;
+                match service.query_balance(config_id).await {
+                    Ok(info) => on_update(&info),
+                    Err(e) => {
+                        log::warn!("Balance watch for config {} stopped: {}", config_id, e);
+                        break;
+                    }
+                }
+            }
+
+            let mut watches = watches.lock().unwrap_or_else(|e| e.into_inner());
+            if watches.get(&config_id).is_some_and(|w| w.generation == generation) {
+                watches.remove(&config_id);
+            }
+            log::info!("Balance watch for config {} finished", config_id);
+        });
+
+        if let Some(previous) = guard.insert(config_id, WatchTask { generation, handle }) {
+            previous.handle.abort();
+        }
+        log::info!("Balance watch started for config {} (every {}s)", config_id, interval_secs);
+        Ok(())
+    }
+
+    /// 停止监视配置余额，返回是否有正在运行的监视任务
+    pub fn stop(&self, config_id: i64) -> bool {
+        let task = self.watches.lock().unwrap_or_else(|e| e.into_inner()).remove(&config_id);
+        match task {
+            Some(task) => {
+                task.handle.abort();
+                log::info!("Balance watch stopped for config {}", config_id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 正在监视的配置 ID
+    pub fn active_watches(&self) -> Vec<i64> {
+        let mut ids: Vec<i64> = self
+            .watches
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    fn setup_pool() -> Arc<DbPool> {
+        let conn = crate::db::test_db();
+        conn.execute_batch(
+            "INSERT INTO ConfigGroup (id, name) VALUES (1, 'g');
+             INSERT INTO ApiConfig (id, name, api_key, server_url, server_port, group_id, balance_query_url)
+                 VALUES (1, 'watched', 'k', 'https://example.com', 443, 1, 'http://127.0.0.1:1/balance');
+             INSERT INTO ApiConfig (id, name, api_key, server_url, server_port, group_id)
+                 VALUES (2, 'no-balance-url', 'k', 'https://example.com', 443, 1);",
+        )
+        .unwrap();
+        Arc::new(DbPool::new(conn))
+    }
+
+    #[tokio::test]
+    async fn test_watch_emits_updates_and_stops() {
+        let manager = BalanceWatchManager::new(setup_pool());
+        assert!(manager.start(1, 1, |_| {}).is_err());
+        assert!(manager.start(2, 5, |_| {}).is_err());
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        manager.start(1, 5, move |info| {
+            let _ = tx.send(info.config_id);
+        })
+        .unwrap();
+        // 重复启动替换原任务
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        manager.start(1, 5, move |info| {
+            let _ = tx.send(info.config_id);
+        })
+        .unwrap();
+        assert_eq!(manager.active_watches(), vec![1]);
+
+        let first = tokio::time::timeout(Duration::from_secs(10), rx.recv()).await.unwrap();
+        assert_eq!(first, Some(1));
+
+        assert!(manager.stop(1));
+        assert!(!manager.stop(1));
+        assert!(manager.active_watches().is_empty());
+    }
+}
@@ -18,7 +18,362 @@ pub struct ProxyConfig {
     pub port: u16,
 }
 
+/// Claude Code 与代理集成检查中的不匹配项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrationMismatch {
+    /// 字段 (如 env.ANTHROPIC_BASE_URL)
+    pub field: String,
+    /// 期望值
+    pub expected: Option<String>,
+    /// 实际值
+    pub actual: Option<String>,
+    /// 说明
+    pub message: String,
+}
+
+/// Claude Code 与代理集成检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeIntegrationReport {
+    /// 是否通过
+    pub passed: bool,
+    /// 代理是否正在运行
+    pub proxy_running: bool,
+    /// Claude Code 配置文件路径
+    pub settings_path: String,
+    /// 期望的 ANTHROPIC_BASE_URL (代理实际监听地址)
+    pub expected_base_url: String,
+    /// 配置中的 ANTHROPIC_BASE_URL
+    pub actual_base_url: Option<String>,
+    /// 不匹配项
+    pub mismatches: Vec<IntegrationMismatch>,
+    /// 建议的修复命令
+    pub suggested_fix: Option<String>,
+}
+
+/// 端口同步时对单个字段的改写
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortChange {
+    /// 字段 (env.ANTHROPIC_BASE_URL / http.proxy)
+    pub field: String,
+    /// 当前值
+    pub from: String,
+    /// 改写后的值
+    pub to: String,
+}
+
+/// 代理端口与 Claude Code 配置端口的比对结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortReconcileReport {
+    /// 代理是否正在运行 (未运行时不知道实际绑定端口，不做改写)
+    pub proxy_running: bool,
+    /// 代理实际绑定的端口
+    pub listen_port: u16,
+    /// Claude Code 配置中 ANTHROPIC_BASE_URL 指向本地代理时的端口
+    pub configured_port: Option<u16>,
+    /// Claude Code 配置文件路径
+    pub settings_path: String,
+    /// 需要改写的字段 (只改端口，主机与路径保持不变)
+    pub changes: Vec<PortChange>,
+    /// 是否已写入 settings.json
+    pub applied: bool,
+}
+
 impl ClaudeConfigService {
+    /// 检查 Claude Code 配置是否指向正在运行的代理
+    ///
+    /// # 参数
+    /// - `proxy`: 代理实际监听的地址
+    /// - `proxy_running`: 代理是否正在运行
+    pub fn verify_integration(proxy: &ProxyConfig, proxy_running: bool) -> AppResult<ClaudeIntegrationReport> {
+        let settings_path = paths::get_claude_code_settings_path()?;
+
+        let settings = if settings_path.exists() {
+            let content = fs::read_to_string(&settings_path).map_err(|e| AppError::IoError {
+                message: format!("读取配置文件失败: {}", e),
+            })?;
+            Some(serde_json::from_str::<Value>(&content).map_err(|e| AppError::InvalidData {
+                message: format!("解析配置文件失败: {}", e),
+            })?)
+        } else {
+            None
+        };
+
+        let mut mismatches = Vec::new();
+        if !proxy_running {
+            mismatches.push(IntegrationMismatch {
+                field: "proxy".to_string(),
+                expected: Some("running".to_string()),
+                actual: Some("stopped".to_string()),
+                message: "代理服务未运行，Claude Code 的请求无法被转发".to_string(),
+            });
+        }
+        match &settings {
+            Some(settings) => mismatches.extend(Self::integration_mismatches(settings, proxy)),
+            None => mismatches.push(IntegrationMismatch {
+                field: "settings.json".to_string(),
+                expected: Some(settings_path.to_string_lossy().to_string()),
+                actual: None,
+                message: "Claude Code 配置文件不存在".to_string(),
+            }),
+        }
+
+        let actual_base_url = settings
+            .as_ref()
+            .and_then(|s| s.get("env"))
+            .and_then(|env| env.get("ANTHROPIC_BASE_URL"))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string());
+
+        let config_mismatch = mismatches.iter().any(|m| m.field != "proxy");
+        let suggested_fix = if config_mismatch {
+            Some(format!(
+                "enable_claude_code_proxy {{ host: \"{}\", port: {} }}",
+                Self::client_host(&proxy.host),
+                proxy.port
+            ))
+        } else if !proxy_running {
+            Some("start_proxy_service".to_string())
+        } else {
+            None
+        };
+
+        Ok(ClaudeIntegrationReport {
+            passed: mismatches.is_empty(),
+            proxy_running,
+            settings_path: settings_path.to_string_lossy().to_string(),
+            expected_base_url: Self::expected_base_url(proxy),
+            actual_base_url,
+            mismatches,
+            suggested_fix,
+        })
+    }
+
+    /// 代理对应的 ANTHROPIC_BASE_URL
+    pub(crate) fn expected_base_url(proxy: &ProxyConfig) -> String {
+        format!("http://{}:{}", Self::client_host(&proxy.host), proxy.port)
+    }
+
+    /// 监听在通配地址时，客户端应使用回环地址访问
+    fn client_host(host: &str) -> &str {
+        match host {
+            "0.0.0.0" | "::" | "[::]" => "127.0.0.1",
+            other => other,
+        }
+    }
+
+    /// 两个主机名是否指向同一个本地监听地址
+    pub(crate) fn host_matches(configured: &str, listen_host: &str) -> bool {
+        let is_loopback = |h: &str| matches!(h, "127.0.0.1" | "localhost" | "::1" | "[::1]");
+        let listen_host = listen_host.trim_matches(|c| c == '[' || c == ']');
+        let configured = configured.trim_matches(|c| c == '[' || c == ']');
+        configured.eq_ignore_ascii_case(listen_host)
+            || (is_loopback(configured) && is_loopback(listen_host))
+            || (matches!(listen_host, "0.0.0.0" | "::") && is_loopback(configured))
+    }
+
+    /// 比较 settings.json 与代理实际监听地址，返回不匹配项
+    fn integration_mismatches(settings: &Value, proxy: &ProxyConfig) -> Vec<IntegrationMismatch> {
+        let expected = Self::expected_base_url(proxy);
+        let mut mismatches = Vec::new();
+        let env = settings.get("env").and_then(|v| v.as_object());
+
+        let base_url = env
+            .and_then(|env| env.get("ANTHROPIC_BASE_URL"))
+            .and_then(|v| v.as_str());
+        match base_url.map(|url| (url, reqwest::Url::parse(url.trim()))) {
+            None => mismatches.push(IntegrationMismatch {
+                field: "env.ANTHROPIC_BASE_URL".to_string(),
+                expected: Some(expected.clone()),
+                actual: None,
+                message: "未设置 ANTHROPIC_BASE_URL，Claude Code 会直连官方 API".to_string(),
+            }),
+            Some((url, Err(_))) => mismatches.push(IntegrationMismatch {
+                field: "env.ANTHROPIC_BASE_URL".to_string(),
+                expected: Some(expected.clone()),
+                actual: Some(url.to_string()),
+                message: "ANTHROPIC_BASE_URL 不是有效的 URL".to_string(),
+            }),
+            Some((url, Ok(parsed))) => {
+                let host_ok = parsed
+                    .host_str()
+                    .map_or(false, |h| Self::host_matches(h, &proxy.host));
+                if !host_ok || parsed.port_or_known_default() != Some(proxy.port) {
+                    mismatches.push(IntegrationMismatch {
+                        field: "env.ANTHROPIC_BASE_URL".to_string(),
+                        expected: Some(expected.clone()),
+                        actual: Some(url.to_string()),
+                        message: "ANTHROPIC_BASE_URL 未指向代理实际监听的地址".to_string(),
+                    });
+                } else if parsed.scheme() != "http" {
+                    mismatches.push(IntegrationMismatch {
+                        field: "env.ANTHROPIC_BASE_URL".to_string(),
+                        expected: Some(expected.clone()),
+                        actual: Some(url.to_string()),
+                        message: "本地代理只支持 http 协议".to_string(),
+                    });
+                } else if parsed.path() != "/" && !parsed.path().is_empty() {
+                    mismatches.push(IntegrationMismatch {
+                        field: "env.ANTHROPIC_BASE_URL".to_string(),
+                        expected: Some(expected.clone()),
+                        actual: Some(url.to_string()),
+                        message: "ANTHROPIC_BASE_URL 不应包含路径，否则请求路径会与代理路由不一致"
+                            .to_string(),
+                    });
+                }
+            }
+        }
+
+        // http.proxy 是旧版本写入的备用字段，存在时也必须一致
+        if let Some(http_proxy) = settings.get("http.proxy").and_then(|v| v.as_str()) {
+            let matches = Self::parse_proxy_url(http_proxy).map_or(false, |p| {
+                Self::host_matches(&p.host, &proxy.host) && p.port == proxy.port
+            });
+            if !matches {
+                mismatches.push(IntegrationMismatch {
+                    field: "http.proxy".to_string(),
+                    expected: Some(expected.clone()),
+                    actual: Some(http_proxy.to_string()),
+                    message: "http.proxy 指向的地址与代理实际监听地址不一致".to_string(),
+                });
+            }
+        }
+
+        // 上游 HTTP(S)_PROXY 会拦截发往本地代理的请求，除非本地地址在 NO_PROXY 中
+        let no_proxy = env
+            .and_then(|env| env.get("NO_PROXY").or_else(|| env.get("no_proxy")))
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let bypassed = no_proxy.split(',').map(str::trim).any(|entry| {
+            entry == "*" || Self::host_matches(entry, &proxy.host)
+        });
+        for key in ["HTTPS_PROXY", "HTTP_PROXY", "https_proxy", "http_proxy"] {
+            if let Some(value) = env.and_then(|env| env.get(key)).and_then(|v| v.as_str()) {
+                if !value.trim().is_empty() && !bypassed {
+                    mismatches.push(IntegrationMismatch {
+                        field: format!("env.{}", key),
+                        expected: None,
+                        actual: Some(value.to_string()),
+                        message: format!(
+                            "设置了 {} 但 NO_PROXY 未包含 {}，发往本地代理的请求可能被转发到上游代理",
+                            key,
+                            Self::client_host(&proxy.host)
+                        ),
+                    });
+                }
+            }
+        }
+
+        mismatches
+    }
+
+    /// 比对代理实际绑定端口与 Claude Code 配置的端口，可选择将配置改写为当前端口
+    ///
+    /// 代理启动时端口被占用会自动递增，settings.json 中保存的端口可能与本次绑定的端口不一致。
+    /// 只改写指向本地代理 (主机一致) 但端口不同的 ANTHROPIC_BASE_URL / http.proxy；
+    /// `apply` 为 false 时只返回将要做的改写
+    ///
+    /// # 参数
+    /// - `proxy`: 代理实际监听的地址
+    /// - `proxy_running`: 代理是否正在运行
+    /// - `apply`: 是否写入 settings.json (写入前自动备份)
+    pub fn reconcile_port(proxy: &ProxyConfig, proxy_running: bool, apply: bool) -> AppResult<PortReconcileReport> {
+        let settings_path = paths::get_claude_code_settings_path()?;
+        let mut settings = if settings_path.exists() {
+            let content = fs::read_to_string(&settings_path).map_err(|e| AppError::IoError {
+                message: format!("读取配置文件失败: {}", e),
+            })?;
+            serde_json::from_str::<Value>(&content).map_err(|e| AppError::InvalidData {
+                message: format!("解析配置文件失败: {}", e),
+            })?
+        } else {
+            serde_json::json!({})
+        };
+
+        let configured_port = settings
+            .get("env")
+            .and_then(|env| env.get("ANTHROPIC_BASE_URL"))
+            .and_then(|v| v.as_str())
+            .and_then(|url| reqwest::Url::parse(url.trim()).ok())
+            .filter(|url| url.host_str().is_some_and(|h| Self::host_matches(h, &proxy.host)))
+            .and_then(|url| url.port_or_known_default());
+        let changes = if proxy_running {
+            Self::port_changes(&settings, proxy)
+        } else {
+            Vec::new()
+        };
+
+        let applied = apply && !changes.is_empty();
+        if applied {
+            BackupService::create_backup("同步代理端口前自动备份")?;
+            Self::apply_port_changes(&mut settings, &changes);
+            let content = serde_json::to_string_pretty(&settings).map_err(|e| AppError::InvalidData {
+                message: format!("序列化配置失败: {}", e),
+            })?;
+            fs::write(&settings_path, content).map_err(|e| AppError::IoError {
+                message: format!("写入配置文件失败: {}", e),
+            })?;
+            log::info!("已将 Claude Code 配置中的代理端口同步为 {}: {:?}", proxy.port, changes);
+        }
+
+        Ok(PortReconcileReport {
+            proxy_running,
+            listen_port: proxy.port,
+            configured_port,
+            settings_path: settings_path.to_string_lossy().to_string(),
+            changes,
+            applied,
+        })
+    }
+
+    /// 主机指向本地代理但端口不同的字段，以及改写为当前端口后的值
+    fn port_changes(settings: &Value, proxy: &ProxyConfig) -> Vec<PortChange> {
+        let mut changes = Vec::new();
+
+        let base_url = settings
+            .get("env")
+            .and_then(|env| env.get("ANTHROPIC_BASE_URL"))
+            .and_then(|v| v.as_str());
+        if let Some(url) = base_url {
+            let parsed = reqwest::Url::parse(url.trim()).ok();
+            if let Some((parsed, host)) = parsed.as_ref().and_then(|p| p.host_str().map(|h| (p, h))) {
+                if Self::host_matches(host, &proxy.host) && parsed.port_or_known_default() != Some(proxy.port) {
+                    let path = if parsed.path() == "/" { "" } else { parsed.path() };
+                    changes.push(PortChange {
+                        field: "env.ANTHROPIC_BASE_URL".to_string(),
+                        from: url.to_string(),
+                        to: format!("{}://{}:{}{}", parsed.scheme(), host, proxy.port, path),
+                    });
+                }
+            }
+        }
+
+        if let Some(http_proxy) = settings.get("http.proxy").and_then(|v| v.as_str()) {
+            if let Some(parsed) = Self::parse_proxy_url(http_proxy) {
+                if Self::host_matches(&parsed.host, &proxy.host) && parsed.port != proxy.port {
+                    changes.push(PortChange {
+                        field: "http.proxy".to_string(),
+                        from: http_proxy.to_string(),
+                        to: format!("http://{}:{}", parsed.host, proxy.port),
+                    });
+                }
+            }
+        }
+
+        changes
+    }
+
+    fn apply_port_changes(settings: &mut Value, changes: &[PortChange]) {
+        for change in changes {
+            let target = match change.field.as_str() {
+                "env.ANTHROPIC_BASE_URL" => settings.get_mut("env").and_then(|env| env.get_mut("ANTHROPIC_BASE_URL")),
+                field => settings.get_mut(field),
+            };
+            if let Some(value) = target {
+                *value = Value::String(change.to.clone());
+            }
+        }
+    }
+
     /// 启用 Claude Code 代理
     ///
     /// 修改 settings.json 中的 http.proxy 配置
@@ -376,6 +731,88 @@ mod tests {
         assert!(ClaudeConfigService::parse_proxy_url("host:abc").is_none());
     }
 
+    #[test]
+    fn test_integration_mismatches() {
+        let proxy = ProxyConfig {
+            host: "127.0.0.1".to_string(),
+            port: 25341,
+        };
+
+        let ok = serde_json::json!({
+            "env": { "ANTHROPIC_BASE_URL": "http://localhost:25341" },
+            "http.proxy": "http://127.0.0.1:25341"
+        });
+        assert!(ClaudeConfigService::integration_mismatches(&ok, &proxy).is_empty());
+
+        let missing = serde_json::json!({});
+        let mismatches = ClaudeConfigService::integration_mismatches(&missing, &proxy);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].expected.as_deref(), Some("http://127.0.0.1:25341"));
+
+        // 代理因端口占用改为 25342 后，旧配置应被识别为不匹配
+        let stale = serde_json::json!({
+            "env": {
+                "ANTHROPIC_BASE_URL": "http://127.0.0.1:25341",
+                "HTTPS_PROXY": "http://corp-proxy:8080"
+            },
+            "http.proxy": "http://127.0.0.1:25341"
+        });
+        let moved = ProxyConfig { host: "127.0.0.1".to_string(), port: 25342 };
+        let fields: Vec<String> = ClaudeConfigService::integration_mismatches(&stale, &moved)
+            .into_iter()
+            .map(|m| m.field)
+            .collect();
+        assert_eq!(fields, vec!["env.ANTHROPIC_BASE_URL", "http.proxy", "env.HTTPS_PROXY"]);
+
+        let bypassed = serde_json::json!({
+            "env": {
+                "ANTHROPIC_BASE_URL": "http://127.0.0.1:25341",
+                "HTTPS_PROXY": "http://corp-proxy:8080",
+                "NO_PROXY": "localhost,127.0.0.1"
+            }
+        });
+        assert!(ClaudeConfigService::integration_mismatches(&bypassed, &proxy).is_empty());
+    }
+
+    #[test]
+    fn test_port_changes() {
+        let proxy = ProxyConfig { host: "127.0.0.1".to_string(), port: 25341 };
+        let mut settings = serde_json::json!({
+            "env": { "ANTHROPIC_BASE_URL": "http://localhost:25342" },
+            "http.proxy": "http://127.0.0.1:25342"
+        });
+
+        let changes = ClaudeConfigService::port_changes(&settings, &proxy);
+        assert_eq!(
+            changes,
+            vec![
+                PortChange {
+                    field: "env.ANTHROPIC_BASE_URL".to_string(),
+                    from: "http://localhost:25342".to_string(),
+                    to: "http://localhost:25341".to_string(),
+                },
+                PortChange {
+                    field: "http.proxy".to_string(),
+                    from: "http://127.0.0.1:25342".to_string(),
+                    to: "http://127.0.0.1:25341".to_string(),
+                },
+            ]
+        );
+
+        ClaudeConfigService::apply_port_changes(&mut settings, &changes);
+        assert!(ClaudeConfigService::port_changes(&settings, &proxy).is_empty());
+        assert!(ClaudeConfigService::integration_mismatches(&settings, &proxy).is_empty());
+
+        // 路径保留；未指向本地代理的地址不改写
+        let session = serde_json::json!({ "env": { "ANTHROPIC_BASE_URL": "http://127.0.0.1:25342/session/abc" } });
+        assert_eq!(
+            ClaudeConfigService::port_changes(&session, &proxy)[0].to,
+            "http://127.0.0.1:25341/session/abc"
+        );
+        let remote = serde_json::json!({ "env": { "ANTHROPIC_BASE_URL": "https://api.anthropic.com" } });
+        assert!(ClaudeConfigService::port_changes(&remote, &proxy).is_empty());
+    }
+
     #[test]
     fn test_proxy_config_serialization() {
         let config = ProxyConfig {
@@ -40,6 +40,94 @@ pub struct InstallProgress {
 }
 
 
+/// 安装计划中的单条命令
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    /// 完整命令行 (用于展示)
+    pub command_line: String,
+    pub description: String,
+}
+
+impl PlannedCommand {
+    fn new(program: &str, args: &[&str], description: &str) -> Self {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        let command_line = std::iter::once(program.to_string())
+            .chain(args.iter().map(|a| {
+                if a.contains(' ') {
+                    format!("\"{}\"", a)
+                } else {
+                    a.clone()
+                }
+            }))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Self {
+            program: program.to_string(),
+            args,
+            command_line,
+            description: description.to_string(),
+        }
+    }
+
+    fn to_command(&self) -> AsyncCommand {
+        let mut cmd = AsyncCommand::new(&self.program);
+        cmd.args(&self.args);
+        cmd
+    }
+}
+
+/// 安装计划 (dry run 结果)
+///
+/// 仅描述安装将要执行的命令与所选环境，生成时不会修改系统
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallPlan {
+    pub method: InstallMethod,
+    /// 将要安装的版本 (npm registry 最新版本，查询失败时为 None)
+    pub target_version: Option<String>,
+    /// 当前已安装的版本
+    pub current_version: Option<String>,
+    /// 所选的 Node 环境 (数据库中的默认环境)
+    pub node_environment: Option<String>,
+    /// PATH 中实际会被调用的 npm
+    pub npm_path: Option<String>,
+    /// npm 全局安装目录 (npm prefix -g)
+    pub npm_global_prefix: Option<String>,
+    /// 将按顺序执行的命令
+    pub commands: Vec<PlannedCommand>,
+    /// 提示信息
+    pub notes: Vec<String>,
+}
+
+impl InstallPlan {
+    /// 生成可读的计划摘要
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("安装方式: {:?}", self.method)];
+        lines.push(format!(
+            "目标版本: {}",
+            self.target_version.as_deref().unwrap_or("未知 (latest)")
+        ));
+        if let Some(current) = &self.current_version {
+            lines.push(format!("当前版本: {}", current));
+        }
+        if let Some(env) = &self.node_environment {
+            lines.push(format!("Node 环境: {}", env));
+        }
+        if let Some(npm) = &self.npm_path {
+            lines.push(format!("npm: {}", npm));
+        }
+        if let Some(prefix) = &self.npm_global_prefix {
+            lines.push(format!("npm 全局安装目录: {}", prefix));
+        }
+        for (i, cmd) in self.commands.iter().enumerate() {
+            lines.push(format!("{}. {}  # {}", i + 1, cmd.command_line, cmd.description));
+        }
+        lines.extend(self.notes.iter().cloned());
+        lines
+    }
+}
+
 /// 版本信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionInfo {
@@ -51,7 +139,126 @@ pub struct VersionInfo {
 
 pub struct ClaudeInstaller;
 
+/// Claude Code 的 npm 包名
+const NPM_PACKAGE: &str = "@anthropic-ai/claude-code";
+
 impl ClaudeInstaller {
+    /// 通过 NPM 安装的命令
+    fn npm_install_command() -> PlannedCommand {
+        PlannedCommand::new("npm", &["install", "-g", NPM_PACKAGE], "通过 npm 全局安装 Claude Code")
+    }
+
+    /// 通过 Homebrew 安装的命令
+    fn homebrew_install_command() -> PlannedCommand {
+        PlannedCommand::new("brew", &["install", "--cask", "claude-code"], "通过 Homebrew 安装 Claude Code")
+    }
+
+    /// 通过官方脚本安装的命令
+    fn native_install_command() -> PlannedCommand {
+        #[cfg(target_os = "windows")]
+        {
+            PlannedCommand::new(
+                "powershell",
+                &["-Command", "irm https://claude.ai/install.ps1 | iex"],
+                "下载并执行官方安装脚本",
+            )
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            PlannedCommand::new(
+                "bash",
+                &["-c", "curl -fsSL https://claude.ai/install.sh | bash"],
+                "下载并执行官方安装脚本",
+            )
+        }
+    }
+
+    /// 按安装方式生成将要执行的命令
+    fn install_commands(options: &InstallOptions) -> Result<Vec<PlannedCommand>, String> {
+        let install = match options.method {
+            InstallMethod::NPM => Self::npm_install_command(),
+            InstallMethod::Native => Self::native_install_command(),
+            InstallMethod::Homebrew => {
+                if !cfg!(target_os = "macos") {
+                    return Err("Homebrew 仅支持 macOS".to_string());
+                }
+                Self::homebrew_install_command()
+            }
+        };
+
+        let mut commands = vec![
+            install,
+            PlannedCommand::new("claude", &["--version"], "验证安装"),
+        ];
+        if options.auto_test {
+            commands.push(PlannedCommand::new("node", &["--version"], "健康检查: Node.js"));
+            commands.push(PlannedCommand::new("npm", &["--version"], "健康检查: npm"));
+            commands.push(PlannedCommand::new("rg", &["--version"], "健康检查: ripgrep"));
+        }
+        Ok(commands)
+    }
+
+    /// 生成安装计划 (dry run)，不执行任何安装命令
+    ///
+    /// 只运行只读查询 (版本、npm 全局目录) 来补充计划信息
+    ///
+    /// # 参数
+    /// - `node_environment`: 所选的 Node 环境描述 (来自默认环境配置)
+    pub async fn plan(
+        options: &InstallOptions,
+        node_environment: Option<String>,
+    ) -> Result<InstallPlan, String> {
+        let commands = Self::install_commands(options)?;
+
+        let target_version = match options.method {
+            InstallMethod::Homebrew => None,
+            _ => Self::fetch_latest_version().await.ok(),
+        };
+        let current_version = Self::get_version().await.ok();
+        let npm_path = find_in_path("npm").map(|p| p.display().to_string());
+        let npm_global_prefix = match options.method {
+            InstallMethod::NPM => Self::npm_global_prefix().await,
+            _ => None,
+        };
+
+        let mut notes = Vec::new();
+        if matches!(options.method, InstallMethod::NPM) && npm_path.is_none() {
+            notes.push("⚠️ PATH 中未找到 npm，安装将失败".to_string());
+        }
+        if node_environment.is_some() && matches!(options.method, InstallMethod::NPM) {
+            notes.push("💡 安装使用 PATH 中的 npm，请确认其属于所选 Node 环境".to_string());
+        }
+        if current_version.is_some() {
+            notes.push("💡 已安装 Claude Code，执行安装将覆盖当前版本".to_string());
+        }
+
+        Ok(InstallPlan {
+            method: options.method.clone(),
+            target_version,
+            current_version,
+            node_environment,
+            npm_path,
+            npm_global_prefix,
+            commands,
+            notes,
+        })
+    }
+
+    /// 查询 npm 全局安装目录
+    async fn npm_global_prefix() -> Option<String> {
+        let output = AsyncCommand::new("npm")
+            .args(&["prefix", "-g"])
+            .output()
+            .await
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let prefix = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!prefix.is_empty()).then_some(prefix)
+    }
+
     /// 安装 Claude Code
     pub async fn install(
         options: InstallOptions,
@@ -139,8 +346,8 @@ impl ClaudeInstaller {
                 success: true,
             });
 
-            let output = AsyncCommand::new("brew")
-                .args(&["install", "--cask", "claude-code"])
+            let output = Self::homebrew_install_command()
+                .to_command()
                 .output()
                 .await
                 .map_err(|e| format!("执行 brew 命令失败: {}", e))?;
@@ -170,15 +377,6 @@ impl ClaudeInstaller {
             success: true,
         });
 
-        #[cfg(target_os = "macos")]
-        let install_cmd = "curl -fsSL https://claude.ai/install.sh | bash";
-
-        #[cfg(target_os = "linux")]
-        let install_cmd = "curl -fsSL https://claude.ai/install.sh | bash";
-
-        #[cfg(target_os = "windows")]
-        let install_cmd = "irm https://claude.ai/install.ps1 | iex";
-
         progress_callback(InstallProgress {
             stage: InstallStage::Installing,
             progress: 0.5,
@@ -186,34 +384,15 @@ impl ClaudeInstaller {
             success: true,
         });
 
-        #[cfg(not(target_os = "windows"))]
-        {
-            let output = AsyncCommand::new("bash")
-                .arg("-c")
-                .arg(install_cmd)
-                .output()
-                .await
-                .map_err(|e| format!("执行安装脚本失败: {}", e))?;
-
-            if !output.status.success() {
-                let error = String::from_utf8_lossy(&output.stderr);
-                return Err(format!("安装脚本执行失败: {}", error));
-            }
-        }
-
-        #[cfg(target_os = "windows")]
-        {
-            let output = AsyncCommand::new("powershell")
-                .arg("-Command")
-                .arg(install_cmd)
-                .output()
-                .await
-                .map_err(|e| format!("执行安装脚本失败: {}", e))?;
+        let output = Self::native_install_command()
+            .to_command()
+            .output()
+            .await
+            .map_err(|e| format!("执行安装脚本失败: {}", e))?;
 
-            if !output.status.success() {
-                let error = String::from_utf8_lossy(&output.stderr);
-                return Err(format!("安装脚本执行失败: {}", error));
-            }
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("安装脚本执行失败: {}", error));
         }
 
         Ok(())
@@ -230,8 +409,8 @@ impl ClaudeInstaller {
             success: true,
         });
 
-        let output = AsyncCommand::new("npm")
-            .args(&["install", "-g", "@anthropic-ai/claude-code"])
+        let output = Self::npm_install_command()
+            .to_command()
             .output()
             .await
             .map_err(|e| format!("执行 npm 命令失败: {}", e))?;
@@ -598,10 +777,74 @@ impl ClaudeInstaller {
     }
 }
 
+/// 在 PATH 中查找可执行文件 (Windows 下同时尝试 PATHEXT 扩展名)
+fn find_in_path(program: &str) -> Option<std::path::PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    #[cfg(target_os = "windows")]
+    let extensions: Vec<String> = std::env::var("PATHEXT")
+        .unwrap_or_else(|_| ".EXE;.CMD;.BAT".to_string())
+        .split(';')
+        .map(|ext| ext.to_string())
+        .collect();
+    #[cfg(not(target_os = "windows"))]
+    let extensions: Vec<String> = vec![String::new()];
+
+    std::env::split_paths(&path).find_map(|dir| {
+        extensions
+            .iter()
+            .map(|ext| dir.join(format!("{}{}", program, ext)))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn options(method: InstallMethod, auto_test: bool) -> InstallOptions {
+        InstallOptions {
+            method,
+            auto_configure: false,
+            auto_backup: false,
+            auto_test,
+            auto_start_proxy: false,
+        }
+    }
+
+    #[test]
+    fn test_install_commands() {
+        let commands = ClaudeInstaller::install_commands(&options(InstallMethod::NPM, false)).unwrap();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].command_line, "npm install -g @anthropic-ai/claude-code");
+        assert_eq!(commands[1].command_line, "claude --version");
+
+        let commands = ClaudeInstaller::install_commands(&options(InstallMethod::Native, true)).unwrap();
+        assert_eq!(commands.len(), 5);
+        assert!(commands[0].command_line.contains("https://claude.ai/install."));
+
+        #[cfg(not(target_os = "macos"))]
+        assert!(ClaudeInstaller::install_commands(&options(InstallMethod::Homebrew, false)).is_err());
+    }
+
+    #[test]
+    fn test_install_plan_summary() {
+        let plan = InstallPlan {
+            method: InstallMethod::NPM,
+            target_version: Some("1.0.0".to_string()),
+            current_version: None,
+            node_environment: Some("nvm v20.10.0".to_string()),
+            npm_path: Some("/usr/local/bin/npm".to_string()),
+            npm_global_prefix: Some("/usr/local".to_string()),
+            commands: ClaudeInstaller::install_commands(&options(InstallMethod::NPM, false)).unwrap(),
+            notes: vec![],
+        };
+
+        let lines = plan.summary_lines();
+        assert!(lines.contains(&"目标版本: 1.0.0".to_string()));
+        assert!(lines.contains(&"npm 全局安装目录: /usr/local".to_string()));
+        assert!(lines.iter().any(|l| l.starts_with("1. npm install -g @anthropic-ai/claude-code")));
+    }
+
     #[tokio::test]
     async fn test_verify_installation() {
         let result = ClaudeInstaller::verify_installation().await;
@@ -129,33 +129,41 @@ pub fn build_test_request_body() -> serde_json::Value {
     })
 }
 
-/// 添加 Claude Code 特有的请求头到 reqwest RequestBuilder
+/// Claude Code 特有的请求头
 /// 注意：使用 Authorization: Bearer 格式，与代理转发保持一致
 /// 同时也添加 x-api-key 以兼容 Anthropic 官方 API
-pub fn add_claude_code_headers(builder: reqwest::RequestBuilder, api_key: &str) -> reqwest::RequestBuilder {
-    builder
-        .header("Content-Type", "application/json")
-        .header("Accept", "application/json")
+pub fn claude_code_headers(api_key: &str) -> Vec<(&'static str, String)> {
+    vec![
+        ("Content-Type", "application/json".to_string()),
+        ("Accept", "application/json".to_string()),
         // 使用 Bearer Token 格式（代理服务商通常使用这种格式）
-        .header("Authorization", format!("Bearer {}", api_key))
+        ("Authorization", format!("Bearer {}", api_key)),
         // 同时添加 x-api-key 以兼容 Anthropic 官方 API
-        .header("x-api-key", api_key)
-        .header("anthropic-version", "2023-06-01")
+        ("x-api-key", api_key.to_string()),
+        ("anthropic-version", "2023-06-01".to_string()),
         // Claude Code 特有的 beta 功能标识
-        .header("anthropic-beta", "claude-code-20250219,interleaved-thinking-2025-05-14")
+        ("anthropic-beta", "claude-code-20250219,interleaved-thinking-2025-05-14".to_string()),
         // 允许直接浏览器访问（重要）
-        .header("anthropic-dangerous-direct-browser-access", "true")
+        ("anthropic-dangerous-direct-browser-access", "true".to_string()),
         // 模拟真实的 Claude Code User-Agent
-        .header("User-Agent", "claude-cli/2.0.55 (external, claude-vscode, agent-sdk/0.1.55)")
+        ("User-Agent", "claude-cli/2.0.55 (external, claude-vscode, agent-sdk/0.1.55)".to_string()),
         // Stainless SDK 请求头（Claude Code 使用的 SDK）
-        .header("x-stainless-lang", "js")
-        .header("x-stainless-runtime", "node")
-        .header("x-stainless-runtime-version", "v24.3.0")
-        .header("x-stainless-package-version", "0.70.0")
-        .header("x-stainless-arch", "arm64")
-        .header("x-stainless-os", "MacOS")
-        .header("x-stainless-retry-count", "0")
-        .header("x-app", "cli")
+        ("x-stainless-lang", "js".to_string()),
+        ("x-stainless-runtime", "node".to_string()),
+        ("x-stainless-runtime-version", "v24.3.0".to_string()),
+        ("x-stainless-package-version", "0.70.0".to_string()),
+        ("x-stainless-arch", "arm64".to_string()),
+        ("x-stainless-os", "MacOS".to_string()),
+        ("x-stainless-retry-count", "0".to_string()),
+        ("x-app", "cli".to_string()),
+    ]
+}
+
+/// 添加 Claude Code 特有的请求头到 reqwest RequestBuilder
+pub fn add_claude_code_headers(builder: reqwest::RequestBuilder, api_key: &str) -> reqwest::RequestBuilder {
+    claude_code_headers(api_key)
+        .into_iter()
+        .fold(builder, |builder, (name, value)| builder.header(name, value))
 }
 
 /// 测试请求超时时间（秒）
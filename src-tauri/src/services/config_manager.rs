@@ -1,6 +1,7 @@
-use crate::models::config_group::ConfigGroup;
+use crate::models::config_group::{ConfigGroup, UpdateGroupRetryStrategyInput};
+use crate::models::retry_strategy::RetryStrategy;
 use crate::models::error::{AppError, AppResult};
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
 
 /// 配置分组管理服务
 pub struct ConfigManager;
@@ -41,13 +42,18 @@ impl ConfigManager {
 
         // 插入分组
         conn.execute(
-            "INSERT INTO ConfigGroup (name, description, auto_switch_enabled, latency_threshold_ms, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+            "INSERT INTO ConfigGroup (name, description, auto_switch_enabled, latency_threshold_ms, filter_sse_keepalive, body_transform, forward_client_ip, sse_retry_ms, native_claude_only, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
             (
                 &group.name,
                 &group.description,
                 &group.auto_switch_enabled,
                 &group.latency_threshold_ms,
+                &group.filter_sse_keepalive,
+                &group.body_transform,
+                &group.forward_client_ip,
+                &group.sse_retry_ms,
+                &group.native_claude_only,
             ),
         )
         .map_err(|e| AppError::DatabaseError {
@@ -66,8 +72,9 @@ impl ConfigManager {
         conn.query_row(
             "SELECT id, name, description, auto_switch_enabled, latency_threshold_ms,
                     retry_count, retry_base_delay_ms, retry_max_delay_ms, rate_limit_delay_ms,
-                    health_check_enabled, health_check_interval_sec,
-                    created_at, updated_at
+                    health_check_enabled, health_check_interval_sec, filter_sse_keepalive,
+                    body_transform, retry_strategy_customized, forward_client_ip, created_at, updated_at,
+                    sse_retry_ms, canary_config_id, canary_percentage, native_claude_only
              FROM ConfigGroup WHERE id = ?1",
             [id],
             |row| {
@@ -81,10 +88,18 @@ impl ConfigManager {
                     retry_base_delay_ms: row.get(6)?,
                     retry_max_delay_ms: row.get(7)?,
                     rate_limit_delay_ms: row.get(8)?,
+                    retry_strategy_customized: row.get(13)?,
                     health_check_enabled: row.get(9)?,
                     health_check_interval_sec: row.get(10)?,
-                    created_at: row.get(11)?,
-                    updated_at: row.get(12)?,
+                    filter_sse_keepalive: row.get(11)?,
+                    body_transform: row.get(12)?,
+                    forward_client_ip: row.get(14)?,
+                    sse_retry_ms: row.get(17)?,
+                    canary_config_id: row.get(18)?,
+                    canary_percentage: row.get(19)?,
+                    native_claude_only: row.get(20)?,
+                    created_at: row.get(15)?,
+                    updated_at: row.get(16)?,
                 })
             },
         )
@@ -105,8 +120,9 @@ impl ConfigManager {
             .prepare(
                 "SELECT id, name, description, auto_switch_enabled, latency_threshold_ms,
                         retry_count, retry_base_delay_ms, retry_max_delay_ms, rate_limit_delay_ms,
-                        health_check_enabled, health_check_interval_sec,
-                        created_at, updated_at
+                        health_check_enabled, health_check_interval_sec, filter_sse_keepalive,
+                        body_transform, retry_strategy_customized, forward_client_ip, created_at, updated_at,
+                    sse_retry_ms, canary_config_id, canary_percentage, native_claude_only
                  FROM ConfigGroup ORDER BY id ASC",
             )
             .map_err(|e| AppError::DatabaseError {
@@ -125,10 +141,18 @@ impl ConfigManager {
                     retry_base_delay_ms: row.get(6)?,
                     retry_max_delay_ms: row.get(7)?,
                     rate_limit_delay_ms: row.get(8)?,
+                    retry_strategy_customized: row.get(13)?,
                     health_check_enabled: row.get(9)?,
                     health_check_interval_sec: row.get(10)?,
-                    created_at: row.get(11)?,
-                    updated_at: row.get(12)?,
+                    filter_sse_keepalive: row.get(11)?,
+                    body_transform: row.get(12)?,
+                    forward_client_ip: row.get(14)?,
+                    sse_retry_ms: row.get(17)?,
+                    canary_config_id: row.get(18)?,
+                    canary_percentage: row.get(19)?,
+                    native_claude_only: row.get(20)?,
+                    created_at: row.get(15)?,
+                    updated_at: row.get(16)?,
                 })
             })
             .map_err(|e| AppError::DatabaseError {
@@ -194,12 +218,34 @@ impl ConfigManager {
             });
         }
 
+        // 开启原生 Claude 透传前，分组内不能有其他提供商的配置
+        if group.native_claude_only {
+            let non_claude: i64 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM ApiConfig WHERE group_id = ?1 AND provider_type != 'claude'",
+                    [group.id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("检查分组配置提供商失败: {}", e),
+                })?;
+
+            if non_claude > 0 {
+                return Err(AppError::ValidationError {
+                    field: "native_claude_only".to_string(),
+                    message: format!("分组内有 {} 个非 Claude 配置，无法开启原生 Claude 透传", non_claude),
+                });
+            }
+        }
+
         // 更新分组
         conn.execute(
             "UPDATE ConfigGroup
              SET name = ?1, description = ?2, auto_switch_enabled = ?3, latency_threshold_ms = ?4,
-                 health_check_enabled = ?5, health_check_interval_sec = ?6, updated_at = CURRENT_TIMESTAMP
-             WHERE id = ?7",
+                 health_check_enabled = ?5, health_check_interval_sec = ?6, filter_sse_keepalive = ?7,
+                 body_transform = ?8, forward_client_ip = ?9, sse_retry_ms = ?10,
+                 native_claude_only = ?11, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?12",
             (
                 &group.name,
                 &group.description,
@@ -207,6 +253,11 @@ impl ConfigManager {
                 &group.latency_threshold_ms,
                 &group.health_check_enabled,
                 &group.health_check_interval_sec,
+                &group.filter_sse_keepalive,
+                &group.body_transform,
+                &group.forward_client_ip,
+                &group.sse_retry_ms,
+                &group.native_claude_only,
                 group.id,
             ),
         )
@@ -219,6 +270,202 @@ impl ConfigManager {
         Self::get_group_by_id(conn, group.id)
     }
 
+    /// 更新分组重试策略
+    ///
+    /// 未提供的字段沿用分组当前生效的策略,保存后分组标记为使用自定义策略
+    ///
+    /// # 参数
+    /// - `conn`: 数据库连接
+    /// - `input`: 重试策略参数
+    ///
+    /// # 返回
+    /// - `Ok(ConfigGroup)`: 更新后的分组
+    /// - `Err(AppError)`: 更新失败
+    pub fn update_retry_strategy(
+        conn: &Connection,
+        input: &UpdateGroupRetryStrategyInput,
+    ) -> AppResult<ConfigGroup> {
+        log::info!("正在更新分组重试策略: ID {}", input.group_id);
+
+        input.validate()?;
+
+        let current = Self::get_group_by_id(conn, input.group_id)?.retry_strategy();
+        let strategy = RetryStrategy::new(
+            input.retry_count.map(|v| v as u32).unwrap_or(current.max_retries),
+            input.retry_base_delay_ms.map(|v| v as u32).unwrap_or(current.base_delay_ms),
+            input.retry_max_delay_ms.map(|v| v as u32).unwrap_or(current.max_delay_ms),
+            input.rate_limit_delay_ms.map(|v| v as u32).unwrap_or(current.rate_limit_delay_ms),
+        );
+        strategy.validate()?;
+
+        conn.execute(
+            "UPDATE ConfigGroup
+             SET retry_count = ?1, retry_base_delay_ms = ?2, retry_max_delay_ms = ?3,
+                 rate_limit_delay_ms = ?4, retry_strategy_customized = 1,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?5",
+            (
+                strategy.max_retries,
+                strategy.base_delay_ms,
+                strategy.max_delay_ms,
+                strategy.rate_limit_delay_ms,
+                input.group_id,
+            ),
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("更新分组重试策略失败: {}", e),
+        })?;
+
+        log::info!(
+            "分组重试策略已更新: ID {} (重试 {} 次, 延迟 {}-{}ms, 限流延迟 {}ms)",
+            input.group_id,
+            strategy.max_retries,
+            strategy.base_delay_ms,
+            strategy.max_delay_ms,
+            strategy.rate_limit_delay_ms
+        );
+
+        Self::get_group_by_id(conn, input.group_id)
+    }
+
+    /// 设置分组的高延迟切换阈值
+    ///
+    /// 阈值越低自动切换越激进，范围见 `ConfigGroup::validate_latency_threshold`
+    pub fn set_latency_threshold(conn: &Connection, group_id: i64, threshold_ms: i32) -> AppResult<ConfigGroup> {
+        log::info!("正在设置分组延迟阈值: ID {} -> {}ms", group_id, threshold_ms);
+
+        ConfigGroup::validate_latency_threshold(threshold_ms).map_err(|message| AppError::ValidationError {
+            field: "latency_threshold_ms".to_string(),
+            message,
+        })?;
+
+        let affected = conn
+            .execute(
+                "UPDATE ConfigGroup SET latency_threshold_ms = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+                (threshold_ms, group_id),
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("更新分组延迟阈值失败: {}", e),
+            })?;
+
+        if affected == 0 {
+            return Err(AppError::NotFound {
+                resource: "ConfigGroup".to_string(),
+                id: group_id.to_string(),
+            });
+        }
+
+        let group = Self::get_group_by_id(conn, group_id)?;
+        if !group.auto_switch_enabled {
+            log::info!("分组 {} 未启用自动切换，延迟阈值暂不生效", group.name);
+        }
+        Ok(group)
+    }
+
+    /// 设置分组的金丝雀配置与流量百分比 (可在代理运行时调整，下一个请求即生效)
+    ///
+    /// `canary_config_id` 为空时清除金丝雀配置
+    pub fn set_canary(
+        conn: &Connection,
+        group_id: i64,
+        canary_config_id: Option<i64>,
+        percentage: i32,
+    ) -> AppResult<ConfigGroup> {
+        log::info!("正在设置分组金丝雀配置: ID {} -> {:?} ({}%)", group_id, canary_config_id, percentage);
+
+        ConfigGroup::validate_canary_percentage(percentage).map_err(|message| AppError::ValidationError {
+            field: "canary_percentage".to_string(),
+            message,
+        })?;
+
+        if let Some(config_id) = canary_config_id {
+            let (config_group_id, is_enabled, is_available): (Option<i64>, bool, bool) = conn
+                .query_row(
+                    "SELECT group_id, is_enabled, is_available FROM ApiConfig WHERE id = ?1",
+                    [config_id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .optional()
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("查询金丝雀配置失败: {}", e),
+                })?
+                .ok_or_else(|| AppError::NotFound {
+                    resource: "ApiConfig".to_string(),
+                    id: config_id.to_string(),
+                })?;
+
+            let invalid = if config_group_id != Some(group_id) {
+                Some("金丝雀配置必须属于该分组")
+            } else if !is_enabled {
+                Some("金丝雀配置已被禁用")
+            } else if !is_available {
+                Some("金丝雀配置当前不可用")
+            } else {
+                None
+            };
+            if let Some(message) = invalid {
+                return Err(AppError::ValidationError {
+                    field: "canary_config_id".to_string(),
+                    message: message.to_string(),
+                });
+            }
+        }
+
+        let percentage = if canary_config_id.is_some() { percentage } else { 0 };
+        let affected = conn
+            .execute(
+                "UPDATE ConfigGroup SET canary_config_id = ?1, canary_percentage = ?2, updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?3",
+                (canary_config_id, percentage, group_id),
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("更新分组金丝雀配置失败: {}", e),
+            })?;
+
+        if affected == 0 {
+            return Err(AppError::NotFound {
+                resource: "ConfigGroup".to_string(),
+                id: group_id.to_string(),
+            });
+        }
+
+        Self::get_group_by_id(conn, group_id)
+    }
+
+    /// 恢复分组使用默认重试策略
+    pub fn reset_retry_strategy(conn: &Connection, group_id: i64) -> AppResult<ConfigGroup> {
+        log::info!("正在恢复分组默认重试策略: ID {}", group_id);
+
+        let defaults = RetryStrategy::default();
+        let affected = conn
+            .execute(
+                "UPDATE ConfigGroup
+                 SET retry_count = ?1, retry_base_delay_ms = ?2, retry_max_delay_ms = ?3,
+                     rate_limit_delay_ms = ?4, retry_strategy_customized = 0,
+                     updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?5",
+                (
+                    defaults.max_retries,
+                    defaults.base_delay_ms,
+                    defaults.max_delay_ms,
+                    defaults.rate_limit_delay_ms,
+                    group_id,
+                ),
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("恢复分组默认重试策略失败: {}", e),
+            })?;
+
+        if affected == 0 {
+            return Err(AppError::NotFound {
+                resource: "ConfigGroup".to_string(),
+                id: group_id.to_string(),
+            });
+        }
+
+        Self::get_group_by_id(conn, group_id)
+    }
+
     /// 删除配置分组
     ///
     /// # 参数
@@ -0,0 +1,81 @@
+/**
+ * Config Re-enable Scheduler
+ * 定时停用到期后自动恢复配置
+ *
+ * Features:
+ * - 定期检查 disabled_until 已到期的配置
+ * - 清除定时停用并恢复可用状态
+ * - 支持启动/停止调度器
+ */
+
+use crate::db::DbPool;
+use crate::models::error::AppResult;
+use crate::services::api_config::ApiConfigService;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration};
+
+/// 调度器检查间隔（秒）
+const SCHEDULER_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// 定时停用恢复调度器
+pub struct ConfigReenableScheduler {
+    db_pool: Arc<DbPool>,
+    task_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
+}
+
+impl ConfigReenableScheduler {
+    /// 创建新的定时停用恢复调度器
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        Self {
+            db_pool,
+            task_handle: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// 启动调度器
+    pub async fn start(&self) -> AppResult<()> {
+        let mut task_handle = self.task_handle.write().await;
+
+        if task_handle.is_some() {
+            log::warn!("定时停用恢复调度器已在运行");
+            return Ok(());
+        }
+
+        let db_pool = self.db_pool.clone();
+
+        let handle = tokio::spawn(async move {
+            log::info!(
+                "定时停用恢复调度器后台任务已启动，检查间隔: {}秒",
+                SCHEDULER_CHECK_INTERVAL_SECS
+            );
+
+            let mut ticker = interval(Duration::from_secs(SCHEDULER_CHECK_INTERVAL_SECS));
+
+            loop {
+                ticker.tick().await;
+
+                if let Err(e) =
+                    db_pool.with_connection(ApiConfigService::restore_expired_disabled_configs)
+                {
+                    log::error!("恢复定时停用配置失败: {}", e);
+                }
+            }
+        });
+
+        *task_handle = Some(handle);
+
+        log::info!("定时停用恢复调度器已启动");
+        Ok(())
+    }
+
+    /// 停止调度器
+    #[allow(dead_code)]
+    pub async fn stop(&self) {
+        if let Some(handle) = self.task_handle.write().await.take() {
+            handle.abort();
+            log::info!("定时停用恢复调度器已停止");
+        }
+    }
+}
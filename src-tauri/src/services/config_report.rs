@@ -0,0 +1,160 @@
+/**
+ * Config Report Service
+ * 生成可分享的只读配置报告 (分组、配置、自动切换设置、最近切换原因、健康摘要)
+ *
+ * 报告中的密钥只保留后 4 位，服务器地址去除用户信息与查询参数，
+ * 切换记录中的错误信息会替换掉已知密钥；余额仅在显式请求时包含。
+ */
+
+use crate::db::DbPool;
+use crate::models::api_config::ApiConfig;
+use crate::models::config_report::{
+    redact_key, redact_text, sanitize_server_url, BalanceReportEntry, ConfigReport, ConfigReportEntry,
+    GroupReportEntry, HealthReportEntry, SwitchReportEntry,
+};
+use crate::models::error::AppResult;
+use crate::models::health_check::ConfigHealthSummary;
+use crate::services::api_config::ApiConfigService;
+use crate::services::auto_switch::AutoSwitchService;
+use crate::services::config_manager::ConfigManager;
+use crate::services::health_check_service::HealthCheckService;
+use crate::utils::time::now_rfc3339;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// 报告中包含的最近切换记录数量
+const RECENT_SWITCH_LIMIT: i32 = 20;
+
+/// 健康摘要统计的时间范围 (小时)
+const HEALTH_SUMMARY_HOURS: i64 = 24;
+
+/// 配置报告服务
+pub struct ConfigReportService;
+
+impl ConfigReportService {
+    /// 生成配置报告
+    ///
+    /// # 参数
+    /// - `include_balances`: 是否包含余额 (默认应为 false)
+    pub fn generate(pool: &Arc<DbPool>, include_balances: bool) -> AppResult<ConfigReport> {
+        let (groups, configs) = pool.with_connection(|conn| {
+            Ok((
+                ConfigManager::list_groups(conn)?,
+                ApiConfigService::list_configs(conn, None)?,
+            ))
+        })?;
+
+        let health: HashMap<i64, ConfigHealthSummary> =
+            HealthCheckService::get_all_summaries(pool, HEALTH_SUMMARY_HOURS)?
+                .into_iter()
+                .map(|summary| (summary.config_id, summary))
+                .collect();
+
+        let known_keys: Vec<String> = configs.iter().map(|c| c.api_key.clone()).collect();
+
+        let switches = AutoSwitchService::new(pool.clone())
+            .get_switch_logs(None, RECENT_SWITCH_LIMIT, 0)?
+            .into_iter()
+            .map(|log| SwitchReportEntry {
+                switch_at: log.switch_at,
+                reason: log.reason.as_str().to_string(),
+                group_name: log.group_name,
+                source_config_name: log.source_config_name,
+                target_config_name: log.target_config_name,
+                error_message: log.error_message.map(|msg| redact_text(&msg, &known_keys)),
+            })
+            .collect();
+
+        let mut grouped: HashMap<i64, Vec<ConfigReportEntry>> = HashMap::new();
+        let mut ungrouped_configs = Vec::new();
+        for config in &configs {
+            let entry = Self::config_entry(config, health.get(&config.id), include_balances);
+            match config.group_id {
+                Some(group_id) if groups.iter().any(|g| g.id == group_id) => {
+                    grouped.entry(group_id).or_default().push(entry)
+                }
+                _ => ungrouped_configs.push(entry),
+            }
+        }
+
+        let groups = groups
+            .into_iter()
+            .map(|group| GroupReportEntry {
+                configs: grouped.remove(&group.id).unwrap_or_default(),
+                id: group.id,
+                name: group.name,
+                auto_switch_enabled: group.auto_switch_enabled,
+                latency_threshold_ms: group.latency_threshold_ms,
+                retry_count: group.retry_count,
+                health_check_enabled: group.health_check_enabled,
+                health_check_interval_sec: group.health_check_interval_sec,
+                filter_sse_keepalive: group.filter_sse_keepalive,
+                sse_retry_ms: group.sse_retry_ms,
+                forward_client_ip: group.forward_client_ip,
+                has_body_transform: group.body_transform.is_some(),
+            })
+            .collect();
+
+        Ok(ConfigReport {
+            generated_at: now_rfc3339(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            include_balances,
+            groups,
+            ungrouped_configs,
+            recent_switches: switches,
+        })
+    }
+
+    fn config_entry(
+        config: &ApiConfig,
+        health: Option<&ConfigHealthSummary>,
+        include_balances: bool,
+    ) -> ConfigReportEntry {
+        let model_overrides: BTreeMap<String, String> = [
+            ("default_model", &config.default_model),
+            ("haiku_model", &config.haiku_model),
+            ("sonnet_model", &config.sonnet_model),
+            ("opus_model", &config.opus_model),
+            ("small_fast_model", &config.small_fast_model),
+        ]
+        .into_iter()
+        .filter_map(|(field, model)| {
+            model
+                .as_ref()
+                .filter(|m| !m.trim().is_empty())
+                .map(|m| (field.to_string(), m.clone()))
+        })
+        .collect();
+
+        let balance = if include_balances {
+            config.last_balance.map(|amount| BalanceReportEntry {
+                amount,
+                currency: config.balance_currency.clone(),
+                checked_at: config.last_balance_check_at.clone(),
+            })
+        } else {
+            None
+        };
+
+        ConfigReportEntry {
+            id: config.id,
+            name: config.name.clone(),
+            provider_type: config.provider_type.to_string(),
+            category: config.category.to_string(),
+            server: sanitize_server_url(&config.server_url),
+            api_key: redact_key(&config.api_key),
+            is_enabled: config.is_enabled,
+            is_available: config.is_available,
+            model_overrides,
+            connect_timeout_secs: config.connect_timeout_secs,
+            request_timeout_secs: config.request_timeout_secs,
+            health: health.map(|summary| HealthReportEntry {
+                availability: summary.availability_24h,
+                avg_latency_ms: summary.avg_latency_24h,
+                last_status: summary.last_check.as_ref().map(|r| r.status.as_str().to_string()),
+                last_check_at: summary.last_check.as_ref().map(|r| r.check_at.clone()),
+            }),
+            balance,
+        }
+    }
+}
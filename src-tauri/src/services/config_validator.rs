@@ -618,6 +618,18 @@ mod tests {
             balance_query_error: None,
             auto_balance_check: false,
             balance_check_interval_sec: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
+            disabled_until: None,
+            extra_query: None,
+            strip_request_fields: None,
+            health_check_mode: Default::default(),
+            metadata_user_id_policy: Default::default(),
+            anthropic_beta_filter: None,
+            api_key_mode: Default::default(),
+            request_compression: Default::default(),
+            user_agent: None,
+            last_healthy_at: None,
             created_at: "2025-01-01".to_string(),
             updated_at: "2025-01-01".to_string(),
         }
@@ -0,0 +1,574 @@
+/**
+ * 格式转换自检服务
+ * 使用内置的代表性请求/响应样本，逐一经过各转换器与转换矩阵 (ConversionMatrix)，
+ * 用 validator 校验转换结果，生成通过/失败矩阵。
+ *
+ * 自检完全在本地运行，不访问任何后端，可用于在切换真实流量前确认当前平台上的转换路径正常。
+ */
+
+use crate::converters::claude_to_gemini::convert_claude_request_to_gemini;
+use crate::converters::claude_types::{ClaudeRequest, ClaudeResponse, ClaudeStreamEvent};
+use crate::converters::gemini_to_claude::{convert_gemini_response_to_claude, convert_gemini_stream_chunk_to_claude_events};
+use crate::converters::gemini_types::GeminiResponse;
+use crate::converters::openai_claude::{
+    convert_claude_request_to_openai, convert_claude_response_to_openai, convert_claude_stream_to_openai,
+    convert_openai_request_to_claude, convert_openai_response_to_claude, convert_openai_stream_to_claude,
+};
+use crate::converters::openai_types::{OpenAIRequest, OpenAIResponse, OpenAIStreamChunk};
+use crate::converters::validator::{ClaudeRequestValidator, OpenAIRequestValidator, ValidationResult};
+use crate::models::api_config::ProviderType;
+use crate::proxy::protocol_detector::{ConversionMatrix, RequestFormat};
+use crate::proxy::smart_router::{ConversionDirection, RoutingContext};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Instant;
+
+/// 自检使用的 Claude 模型名称
+const CLAUDE_MODEL: &str = "claude-sonnet-4-5-20250929";
+/// 自检使用的 OpenAI 模型名称
+const OPENAI_MODEL: &str = "gpt-4o";
+/// 自检使用的 Gemini 模型名称
+const GEMINI_MODEL: &str = "gemini-2.5-pro";
+
+/// 包含工具调用的样本名称
+const TOOL_SAMPLE: &str = "tool_round_trip";
+/// Gemini 转换器尚不支持工具调用，工具样本对 Gemini 跳过
+const GEMINI_TOOLS_UNSUPPORTED: &str = "Gemini 转换暂不支持工具调用 (tool_use / tool_result)";
+
+/// Claude 请求样本
+const CLAUDE_REQUESTS: &[(&str, &str)] = &[
+    (
+        "simple_text",
+        r#"{"model":"claude-sonnet-4-5-20250929","max_tokens":256,
+            "messages":[{"role":"user","content":"Hello"}]}"#,
+    ),
+    (
+        "system_multi_turn",
+        r#"{"model":"claude-sonnet-4-5-20250929","max_tokens":512,"temperature":0.3,"stream":true,
+            "system":[{"type":"text","text":"You are terse.","cache_control":{"type":"ephemeral"}}],
+            "stop_sequences":["END"],
+            "messages":[
+                {"role":"user","content":"What is 2+2?"},
+                {"role":"assistant","content":[{"type":"text","text":"4"}]},
+                {"role":"user","content":[{"type":"text","text":"And 3+3?"}]}
+            ]}"#,
+    ),
+    (
+        "tool_round_trip",
+        r#"{"model":"claude-sonnet-4-5-20250929","max_tokens":256,
+            "messages":[
+                {"role":"user","content":"Weather in Paris?"},
+                {"role":"assistant","content":[
+                    {"type":"text","text":"Checking."},
+                    {"type":"tool_use","id":"toolu_01","name":"get_weather","input":{"city":"Paris"}}
+                ]},
+                {"role":"user","content":[
+                    {"type":"tool_result","tool_use_id":"toolu_01","content":"18C, cloudy"}
+                ]}
+            ]}"#,
+    ),
+    (
+        "image",
+        r#"{"model":"claude-sonnet-4-5-20250929","max_tokens":256,
+            "messages":[{"role":"user","content":[
+                {"type":"image","source":{"type":"base64","media_type":"image/png","data":"iVBORw0KGgo="}},
+                {"type":"text","text":"Describe this image."}
+            ]}]}"#,
+    ),
+];
+
+/// OpenAI 请求样本
+const OPENAI_REQUESTS: &[(&str, &str)] = &[
+    (
+        "simple_text",
+        r#"{"model":"gpt-4o","max_tokens":256,"messages":[{"role":"user","content":"Hello"}]}"#,
+    ),
+    (
+        "system_multi_turn",
+        r#"{"model":"gpt-4o","max_tokens":512,"temperature":0.3,"stream":true,"stop":["END"],
+            "messages":[
+                {"role":"system","content":"You are terse."},
+                {"role":"user","content":"What is 2+2?"},
+                {"role":"assistant","content":"4"},
+                {"role":"user","content":"And 3+3?"}
+            ]}"#,
+    ),
+    (
+        "tool_round_trip",
+        r#"{"model":"gpt-4o","max_tokens":256,
+            "messages":[
+                {"role":"user","content":"Weather in Paris?"},
+                {"role":"assistant","content":null,"tool_calls":[
+                    {"id":"call_01","type":"function","function":{"name":"get_weather","arguments":"{\"city\":\"Paris\"}"}}
+                ]},
+                {"role":"tool","tool_call_id":"call_01","content":"18C, cloudy"}
+            ]}"#,
+    ),
+];
+
+/// Claude 响应样本
+const CLAUDE_RESPONSES: &[(&str, &str)] = &[
+    (
+        "text",
+        r#"{"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5-20250929",
+            "content":[{"type":"text","text":"Hi there"}],
+            "stop_reason":"end_turn","stop_sequence":null,
+            "usage":{"input_tokens":10,"output_tokens":3}}"#,
+    ),
+    (
+        "tool_use",
+        r#"{"id":"msg_02","type":"message","role":"assistant","model":"claude-sonnet-4-5-20250929",
+            "content":[{"type":"tool_use","id":"toolu_01","name":"get_weather","input":{"city":"Paris"}}],
+            "stop_reason":"tool_use","stop_sequence":null,
+            "usage":{"input_tokens":20,"output_tokens":8}}"#,
+    ),
+];
+
+/// OpenAI 响应样本
+const OPENAI_RESPONSES: &[(&str, &str)] = &[
+    (
+        "text",
+        r#"{"id":"chatcmpl-01","object":"chat.completion","created":1700000000,"model":"gpt-4o",
+            "choices":[{"index":0,"message":{"role":"assistant","content":"Hi there"},"finish_reason":"stop"}],
+            "usage":{"prompt_tokens":10,"completion_tokens":3,"total_tokens":13}}"#,
+    ),
+    (
+        "tool_calls",
+        r#"{"id":"chatcmpl-02","object":"chat.completion","created":1700000000,"model":"gpt-4o",
+            "choices":[{"index":0,"message":{"role":"assistant","content":null,"tool_calls":[
+                {"id":"call_01","type":"function","function":{"name":"get_weather","arguments":"{\"city\":\"Paris\"}"}}
+            ]},"finish_reason":"tool_calls"}],
+            "usage":{"prompt_tokens":20,"completion_tokens":8,"total_tokens":28}}"#,
+    ),
+];
+
+/// Gemini 响应样本
+const GEMINI_RESPONSES: &[(&str, &str)] = &[
+    (
+        "text",
+        r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Hi there"}]},"finishReason":"STOP","index":0}],
+            "usageMetadata":{"promptTokenCount":10,"candidatesTokenCount":3,"totalTokenCount":13}}"#,
+    ),
+    (
+        "max_tokens",
+        r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Once upon"},{"text":" a time"}]},
+            "finishReason":"MAX_TOKENS","index":0}]}"#,
+    ),
+];
+
+/// OpenAI 流式响应块样本 (按顺序)
+const OPENAI_STREAM_CHUNKS: &[&str] = &[
+    r#"{"id":"chatcmpl-01","object":"chat.completion.chunk","created":1700000000,"model":"gpt-4o",
+        "choices":[{"index":0,"delta":{"role":"assistant","content":"Hi"}}]}"#,
+    r#"{"id":"chatcmpl-01","object":"chat.completion.chunk","created":1700000000,"model":"gpt-4o",
+        "choices":[{"index":0,"delta":{"content":" there"},"finish_reason":"stop"}],
+        "usage":{"prompt_tokens":10,"completion_tokens":3,"total_tokens":13}}"#,
+];
+
+/// Gemini 流式响应块样本 (按顺序)
+const GEMINI_STREAM_CHUNKS: &[&str] = &[
+    r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Hi"}]},"index":0}],
+        "usageMetadata":{"promptTokenCount":10}}"#,
+    r#"{"candidates":[{"content":{"role":"model","parts":[{"text":" there"}]},"finishReason":"STOP","index":0}],
+        "usageMetadata":{"promptTokenCount":10,"candidatesTokenCount":3,"totalTokenCount":13}}"#,
+];
+
+/// Claude 流式事件样本 (按顺序)
+const CLAUDE_STREAM_EVENTS: &[&str] = &[
+    r#"{"type":"message_start","message":{"id":"msg_01","role":"assistant","content":[],
+        "model":"claude-sonnet-4-5-20250929","stop_reason":null,"stop_sequence":null,
+        "usage":{"input_tokens":10,"output_tokens":0}}}"#,
+    r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+    r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hi there"}}"#,
+    r#"{"type":"content_block_stop","index":0}"#,
+    r#"{"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},
+        "usage":{"input_tokens":10,"output_tokens":3}}"#,
+    r#"{"type":"message_stop"}"#,
+];
+
+/// 单项检查状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversionCheckStatus {
+    Passed,
+    Failed,
+    /// 该转换路径尚未实现，跳过
+    Skipped,
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversionCheck {
+    /// 检查名称 (如 `request:tool_round_trip`、`stream`)
+    pub name: String,
+    pub status: ConversionCheckStatus,
+    /// 失败原因或跳过说明
+    pub message: Option<String>,
+}
+
+/// 矩阵单元状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversionCellStatus {
+    Passed,
+    Failed,
+    /// 代理尚不支持该转换路径
+    Unsupported,
+}
+
+/// 转换矩阵单元 (客户端请求格式 × 后端提供商)
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversionMatrixCell {
+    /// 客户端请求格式
+    pub source_format: String,
+    /// 后端提供商
+    pub target_provider: ProviderType,
+    /// 转换矩阵中的规则描述
+    pub description: String,
+    /// 是否直通 (无需转换)
+    pub passthrough: bool,
+    pub status: ConversionCellStatus,
+    pub checks: Vec<ConversionCheck>,
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversionSelfTestReport {
+    pub cells: Vec<ConversionMatrixCell>,
+    pub passed: usize,
+    pub failed: usize,
+    pub unsupported: usize,
+    pub duration_ms: u64,
+}
+
+impl ConversionSelfTestReport {
+    /// 所有已支持的转换路径均通过
+    pub fn all_passed(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+/// 格式转换自检服务
+pub struct ConversionSelfTestService;
+
+impl ConversionSelfTestService {
+    /// 运行全部转换路径的自检
+    pub fn run() -> ConversionSelfTestReport {
+        let start = Instant::now();
+        let mut cells = Vec::new();
+
+        for source in [RequestFormat::Claude, RequestFormat::OpenAI, RequestFormat::Gemini] {
+            for target in [ProviderType::Claude, ProviderType::OpenAI, ProviderType::Gemini] {
+                cells.push(Self::run_cell(source, target));
+            }
+        }
+
+        let count = |status| cells.iter().filter(|c| c.status == status).count();
+        let report = ConversionSelfTestReport {
+            passed: count(ConversionCellStatus::Passed),
+            failed: count(ConversionCellStatus::Failed),
+            unsupported: count(ConversionCellStatus::Unsupported),
+            duration_ms: start.elapsed().as_millis() as u64,
+            cells,
+        };
+        log::info!(
+            "Conversion self-test finished: {} passed, {} failed, {} unsupported",
+            report.passed,
+            report.failed,
+            report.unsupported
+        );
+        report
+    }
+
+    /// 运行单个矩阵单元的检查
+    fn run_cell(source: RequestFormat, target: ProviderType) -> ConversionMatrixCell {
+        let rule = ConversionMatrix::get_conversion_rule(source, target);
+        let mut checks = Vec::new();
+
+        match (source, target) {
+            (RequestFormat::Claude, _) => {
+                // 代理的客户端格式固定为 Claude，矩阵规则应与实际路由的转换方向一致
+                let direction = RoutingContext::new(&hyper::HeaderMap::new(), "/v1/messages", target).request_conversion;
+                checks.push(check("matrix", || {
+                    if rule.is_passthrough == (direction == ConversionDirection::NoConversion) {
+                        Ok(())
+                    } else {
+                        Err(format!("matrix says passthrough={}, router uses {}", rule.is_passthrough, direction))
+                    }
+                }));
+            }
+            (RequestFormat::Gemini, _) => {
+                return ConversionMatrixCell {
+                    source_format: source.to_string(),
+                    target_provider: target,
+                    description: rule.description.to_string(),
+                    passthrough: rule.is_passthrough,
+                    status: ConversionCellStatus::Unsupported,
+                    checks: vec![skipped("request", "Gemini 格式的客户端请求尚未支持")],
+                };
+            }
+            _ => {}
+        }
+
+        match (source, target) {
+            (RequestFormat::Claude, ProviderType::Claude) => {
+                for (name, raw) in CLAUDE_REQUESTS {
+                    checks.push(check(&format!("request:{}", name), || {
+                        let req: ClaudeRequest = parse(raw)?;
+                        validated(ClaudeRequestValidator::validate(&req))?;
+                        round_trip(&req)
+                    }));
+                }
+            }
+            (RequestFormat::Claude, ProviderType::OpenAI) => {
+                for (name, raw) in CLAUDE_REQUESTS {
+                    checks.push(check(&format!("request:{}", name), || {
+                        let mut req = convert_claude_request_to_openai(&parse::<ClaudeRequest>(raw)?);
+                        req.model = OPENAI_MODEL.to_string();
+                        validated(OpenAIRequestValidator::validate(&req))?;
+                        round_trip(&req)
+                    }));
+                }
+                for (name, raw) in OPENAI_RESPONSES {
+                    checks.push(check(&format!("response:{}", name), || {
+                        let resp = convert_openai_response_to_claude(&parse::<OpenAIResponse>(raw)?, CLAUDE_MODEL);
+                        check_claude_response(&resp)
+                    }));
+                }
+                checks.push(check("stream", || {
+                    let mut events = Vec::new();
+                    for (i, raw) in OPENAI_STREAM_CHUNKS.iter().enumerate() {
+                        events.extend(convert_openai_stream_to_claude(&parse::<OpenAIStreamChunk>(raw)?, i == 0, CLAUDE_MODEL));
+                    }
+                    check_sse_events(&events, "message_start")
+                }));
+            }
+            (RequestFormat::Claude, ProviderType::Gemini) => {
+                for (name, raw) in CLAUDE_REQUESTS {
+                    if *name == TOOL_SAMPLE {
+                        checks.push(skipped(&format!("request:{}", name), GEMINI_TOOLS_UNSUPPORTED));
+                        continue;
+                    }
+                    checks.push(check(&format!("request:{}", name), || {
+                        let (req, path) = convert_claude_request_to_gemini(&parse::<ClaudeRequest>(raw)?, GEMINI_MODEL)
+                            .map_err(|e| e.to_string())?;
+                        check_gemini_request(&req, &path)
+                    }));
+                }
+                for (name, raw) in GEMINI_RESPONSES {
+                    checks.push(check(&format!("response:{}", name), || {
+                        let resp = convert_gemini_response_to_claude(&parse::<GeminiResponse>(raw)?, CLAUDE_MODEL)
+                            .map_err(|e| e.to_string())?;
+                        check_claude_response(&resp)
+                    }));
+                }
+                checks.push(check("stream", || {
+                    let mut events = Vec::new();
+                    for (i, raw) in GEMINI_STREAM_CHUNKS.iter().enumerate() {
+                        events.extend(
+                            convert_gemini_stream_chunk_to_claude_events(raw, CLAUDE_MODEL, i == 0)
+                                .map_err(|e| e.to_string())?,
+                        );
+                    }
+                    check_sse_events(&events, "message_start")
+                }));
+            }
+            (RequestFormat::OpenAI, ProviderType::OpenAI) => {
+                for (name, raw) in OPENAI_REQUESTS {
+                    checks.push(check(&format!("request:{}", name), || {
+                        let req: OpenAIRequest = parse(raw)?;
+                        validated(OpenAIRequestValidator::validate(&req))?;
+                        round_trip(&req)
+                    }));
+                }
+            }
+            (RequestFormat::OpenAI, ProviderType::Claude) => {
+                for (name, raw) in OPENAI_REQUESTS {
+                    checks.push(check(&format!("request:{}", name), || {
+                        let mut req = convert_openai_request_to_claude(&parse::<OpenAIRequest>(raw)?);
+                        req.model = CLAUDE_MODEL.to_string();
+                        validated(ClaudeRequestValidator::validate(&req))?;
+                        round_trip(&req)
+                    }));
+                }
+                for (name, raw) in CLAUDE_RESPONSES {
+                    checks.push(check(&format!("response:{}", name), || {
+                        let resp = convert_claude_response_to_openai(&parse::<ClaudeResponse>(raw)?, OPENAI_MODEL);
+                        check_openai_response(&resp)
+                    }));
+                }
+                checks.push(check("stream", || {
+                    let mut events = Vec::new();
+                    for raw in CLAUDE_STREAM_EVENTS {
+                        events.extend(convert_claude_stream_to_openai(&parse::<ClaudeStreamEvent>(raw)?, OPENAI_MODEL, "selftest"));
+                    }
+                    check_sse_events(&events, "chat.completion.chunk")
+                }));
+            }
+            (RequestFormat::OpenAI, ProviderType::Gemini) => {
+                // 与转发一致: 先转换为 Claude，再转换为 Gemini
+                for (name, raw) in OPENAI_REQUESTS {
+                    if *name == TOOL_SAMPLE {
+                        checks.push(skipped(&format!("request:{}", name), GEMINI_TOOLS_UNSUPPORTED));
+                        continue;
+                    }
+                    checks.push(check(&format!("request:{}", name), || {
+                        let claude_req = convert_openai_request_to_claude(&parse::<OpenAIRequest>(raw)?);
+                        let (req, path) = convert_claude_request_to_gemini(&claude_req, GEMINI_MODEL)
+                            .map_err(|e| e.to_string())?;
+                        check_gemini_request(&req, &path)
+                    }));
+                }
+                for (name, raw) in GEMINI_RESPONSES {
+                    checks.push(check(&format!("response:{}", name), || {
+                        let claude_resp = convert_gemini_response_to_claude(&parse::<GeminiResponse>(raw)?, CLAUDE_MODEL)
+                            .map_err(|e| e.to_string())?;
+                        check_openai_response(&convert_claude_response_to_openai(&claude_resp, OPENAI_MODEL))
+                    }));
+                }
+                checks.push(skipped("stream", "Gemini → OpenAI 流式响应尚未转换，按原样透传"));
+            }
+            _ => {}
+        }
+
+        let status = if checks.iter().any(|c| c.status == ConversionCheckStatus::Failed) {
+            ConversionCellStatus::Failed
+        } else {
+            ConversionCellStatus::Passed
+        };
+
+        ConversionMatrixCell {
+            source_format: source.to_string(),
+            target_provider: target,
+            description: rule.description.to_string(),
+            passthrough: rule.is_passthrough,
+            status,
+            checks,
+        }
+    }
+}
+
+/// 执行单项检查 (捕获 panic，避免个别转换器异常中断整个自检)
+fn check(name: &str, run: impl FnOnce() -> Result<(), String>) -> ConversionCheck {
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(run))
+        .unwrap_or_else(|_| Err("converter panicked".to_string()));
+    ConversionCheck {
+        name: name.to_string(),
+        status: if result.is_ok() { ConversionCheckStatus::Passed } else { ConversionCheckStatus::Failed },
+        message: result.err(),
+    }
+}
+
+fn skipped(name: &str, reason: &str) -> ConversionCheck {
+    ConversionCheck {
+        name: name.to_string(),
+        status: ConversionCheckStatus::Skipped,
+        message: Some(reason.to_string()),
+    }
+}
+
+fn parse<T: DeserializeOwned>(raw: &str) -> Result<T, String> {
+    serde_json::from_str(raw).map_err(|e| format!("invalid sample: {}", e))
+}
+
+fn validated(result: ValidationResult) -> Result<(), String> {
+    result.map_err(|errors| {
+        errors
+            .iter()
+            .map(|e| format!("{}: {}", e.field, e.message))
+            .collect::<Vec<_>>()
+            .join("; ")
+    })
+}
+
+/// 转换结果必须能序列化为 JSON (即实际发往后端的请求体)
+fn round_trip<T: Serialize>(value: &T) -> Result<(), String> {
+    serde_json::to_vec(value).map(|_| ()).map_err(|e| format!("serialize failed: {}", e))
+}
+
+fn check_gemini_request(req: &crate::converters::gemini_types::GeminiRequest, path: &str) -> Result<(), String> {
+    if req.contents.is_empty() {
+        return Err("contents is empty".to_string());
+    }
+    if let Some(i) = req.contents.iter().position(|c| c.parts.is_empty()) {
+        return Err(format!("contents[{}] has no parts", i));
+    }
+    if !path.contains(GEMINI_MODEL) {
+        return Err(format!("path {} does not target model {}", path, GEMINI_MODEL));
+    }
+    round_trip(req)
+}
+
+fn check_claude_response(resp: &ClaudeResponse) -> Result<(), String> {
+    if resp.content.is_empty() {
+        return Err("content is empty".to_string());
+    }
+    if resp.stop_reason.is_none() {
+        return Err("stop_reason is missing".to_string());
+    }
+    round_trip(resp)
+}
+
+fn check_openai_response(resp: &OpenAIResponse) -> Result<(), String> {
+    let choice = resp.choices.first().ok_or("choices is empty")?;
+    if choice.finish_reason.is_none() {
+        return Err("finish_reason is missing".to_string());
+    }
+    round_trip(resp)
+}
+
+/// 校验 SSE 事件: 每个事件的 data 行都是 JSON (或 [DONE])，且包含期望的事件标记
+fn check_sse_events(events: &[String], expected_marker: &str) -> Result<(), String> {
+    if events.is_empty() {
+        return Err("no events produced".to_string());
+    }
+    for event in events {
+        for data in event.lines().filter_map(|line| line.strip_prefix("data: ")) {
+            if data != "[DONE]" {
+                serde_json::from_str::<serde_json::Value>(data)
+                    .map_err(|e| format!("invalid event data {}: {}", data, e))?;
+            }
+        }
+    }
+    if !events.iter().any(|e| e.contains(expected_marker)) {
+        return Err(format!("no {} event produced", expected_marker));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_self_test_passes() {
+        let report = ConversionSelfTestService::run();
+        assert_eq!(report.cells.len(), 9);
+
+        let failures: Vec<_> = report
+            .cells
+            .iter()
+            .flat_map(|cell| {
+                cell.checks
+                    .iter()
+                    .filter(|c| c.status == ConversionCheckStatus::Failed)
+                    .map(move |c| format!("{}→{:?} {}: {:?}", cell.source_format, cell.target_provider, c.name, c.message))
+            })
+            .collect();
+        assert!(failures.is_empty(), "{:#?}", failures);
+        assert!(report.all_passed());
+        assert_eq!(report.unsupported, 3);
+        assert_eq!(report.passed, 6);
+    }
+
+    #[test]
+    fn test_check_reports_failures() {
+        let failed = check("x", || Err("boom".to_string()));
+        assert_eq!(failed.status, ConversionCheckStatus::Failed);
+        assert_eq!(failed.message.as_deref(), Some("boom"));
+
+        assert!(check_sse_events(&[], "message_start").is_err());
+        assert!(check_sse_events(&["data: {not json}\n\n".to_string()], "x").is_err());
+        assert!(check_sse_events(&["data: [DONE]\n\n".to_string()], "[DONE]").is_ok());
+    }
+}
@@ -0,0 +1,175 @@
+/**
+ * Curl Repro Service
+ * 将已记录的代理请求还原为可直接运行的 curl 命令，便于向供应商反馈问题
+ *
+ * 目标地址 = 配置当前的 server_url + 客户端请求路径 (+ 固定查询参数，值已隐藏)；
+ * 认证信息统一替换为 `$API_KEY`，运行前需先 `export API_KEY=...`。请求体为格式转换前的原始请求体。
+ */
+
+use crate::db::DbPool;
+use crate::models::error::{AppError, AppResult};
+use crate::proxy::router::strip_session_prefix;
+use crate::services::api_config::ApiConfigService;
+use crate::services::proxy_log::{ProxyRequestLogDetail, ProxyRequestLogService};
+use crate::utils::server_url::{merge_query, parse_server_url, redact_query_values};
+use std::collections::BTreeMap;
+
+/// 不写入 curl 命令的请求头（由 curl 自动生成或只对代理本身有意义）
+const SKIPPED_HEADERS: &[&str] = &[
+    "host",
+    "content-length",
+    "transfer-encoding",
+    "connection",
+    "keep-alive",
+    "accept-encoding",
+    "proxy-authorization",
+    "x-forwarded-for",
+    "x-real-ip",
+];
+
+/// 认证请求头，值替换为 `$API_KEY`
+const AUTH_HEADERS: &[&str] = &["authorization", "x-api-key", "x-goog-api-key"];
+
+/// 为指定日志生成 curl 复现命令
+pub fn generate_curl_repro(pool: &DbPool, log_id: i64) -> AppResult<String> {
+    let detail = ProxyRequestLogService::get_log_detail(pool, log_id)?.ok_or_else(|| AppError::NotFound {
+        resource: "ProxyRequestLog".to_string(),
+        id: log_id.to_string(),
+    })?;
+    let config_id = detail.config_id.ok_or_else(|| AppError::ValidationError {
+        field: "log_id".to_string(),
+        message: "该请求未关联配置，无法确定目标地址".to_string(),
+    })?;
+    let config = pool.with_connection(|conn| ApiConfigService::get_config_by_id(conn, config_id))?;
+    Ok(build_curl_command(&detail, &config.server_url, config.extra_query.as_deref()))
+}
+
+/// 根据日志详情与配置的 server_url / 固定查询参数拼接 curl 命令
+pub fn build_curl_command(detail: &ProxyRequestLogDetail, server_url: &str, extra_query: Option<&str>) -> String {
+    let parsed = parse_server_url(server_url);
+    let scheme = if parsed.is_https { "https" } else { "http" };
+    // 固定查询参数可能包含密钥，只保留参数名
+    let extra_query = extra_query.filter(|q| !q.is_empty()).map(redact_query_values);
+    let path = merge_query(strip_session_prefix(&detail.uri), extra_query.as_deref().unwrap_or(""));
+    let url = format!("{}://{}{}", scheme, parsed.host_and_port, parsed.target_path(&path));
+
+    let headers: BTreeMap<String, String> = detail
+        .request_headers
+        .as_deref()
+        .and_then(|json| serde_json::from_str::<BTreeMap<String, String>>(json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, value)| (name.to_ascii_lowercase(), value))
+        .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+        .collect();
+
+    let mut lines = Vec::new();
+    if let Some(note) = body_note(detail) {
+        lines.push(format!("# {}", note));
+    }
+    if extra_query.is_some() {
+        lines.push("# 注意: 固定查询参数的值已隐藏 (***)，运行前需替换".to_string());
+    }
+
+    let mut args = vec![format!("curl -X {} {}", detail.method, shell_quote(&url))];
+    // 代理转发时总是以 Bearer 形式设置 Authorization
+    if !headers.contains_key("authorization") {
+        args.push("-H \"authorization: Bearer $API_KEY\"".to_string());
+    }
+    for (name, value) in &headers {
+        if AUTH_HEADERS.contains(&name.as_str()) {
+            let value = match value.split_once(' ') {
+                Some((scheme, _)) if name == "authorization" => format!("{} $API_KEY", scheme),
+                _ => "$API_KEY".to_string(),
+            };
+            args.push(format!("-H \"{}: {}\"", name, value));
+        } else {
+            args.push(format!("-H {}", shell_quote(&format!("{}: {}", name, value))));
+        }
+    }
+    if let Some(body) = detail.request_body.as_deref().filter(|b| !b.is_empty() && !is_binary_placeholder(b)) {
+        args.push(format!("--data-raw {}", shell_quote(body)));
+    }
+
+    lines.push(args.join(" \\\n  "));
+    lines.join("\n")
+}
+
+/// 请求体不完整时的提示
+fn body_note(detail: &ProxyRequestLogDetail) -> Option<String> {
+    let logged = detail.request_body.as_deref().map_or(0, str::len) as i64;
+    if detail.request_body_size > 0 && detail.request_body.is_none() {
+        Some(format!("注意: 未记录请求体 (原始大小 {} 字节)", detail.request_body_size))
+    } else if detail.request_body.as_deref().is_some_and(is_binary_placeholder) {
+        Some("注意: 原始请求体为二进制数据，未包含在命令中".to_string())
+    } else if detail.request_body_size > logged {
+        Some(format!(
+            "注意: 日志中的请求体已截断 ({} / {} 字节)",
+            logged, detail.request_body_size
+        ))
+    } else {
+        None
+    }
+}
+
+/// 二进制请求体在日志中以占位文本记录
+fn is_binary_placeholder(body: &str) -> bool {
+    body.starts_with("[binary:")
+}
+
+/// 用单引号包裹参数，内部单引号转义为 '\''
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detail() -> ProxyRequestLogDetail {
+        let body = r#"{"model":"m","messages":[{"role":"user","content":"it's"}]}"#;
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "request_at": "",
+            "method": "POST",
+            "uri": "/session/abc/v1/messages?beta=true",
+            "target_url": "config:1 (global)",
+            "latency_ms": 10,
+            "status_code": 400,
+            "is_success": false,
+            "request_headers": r#"{"Host":"127.0.0.1:25341","x-api-key":"sk-secret","anthropic-version":"2023-06-01","content-length":"42"}"#,
+            "request_body": body,
+            "request_body_size": body.len(),
+            "response_body_size": 0,
+            "is_streaming": false,
+            "stream_chunk_count": 0,
+            "input_tokens_estimated": false,
+            "output_tokens_estimated": false
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_build_curl_command() {
+        let command = build_curl_command(&detail(), "https://api.example.com/api", None);
+
+        assert!(command.starts_with("curl -X POST 'https://api.example.com/api/v1/messages?beta=true'"));
+        assert!(command.contains("-H \"authorization: Bearer $API_KEY\""));
+        assert!(command.contains("-H \"x-api-key: $API_KEY\""));
+        assert!(command.contains("-H 'anthropic-version: 2023-06-01'"));
+        assert!(command.contains(r#""content":"it'\''s"}]}'"#));
+        assert!(!command.contains("sk-secret"));
+        assert!(!command.contains("127.0.0.1"));
+        assert!(!command.contains("content-length"));
+    }
+
+    #[test]
+    fn test_truncated_body_note() {
+        let mut detail = detail();
+        detail.request_body_size = 100_000;
+        let command = build_curl_command(&detail, "https://api.example.com", Some("key=secret"));
+        assert!(command.starts_with("# 注意: 日志中的请求体已截断"));
+        assert!(command.contains("'https://api.example.com/v1/messages?beta=true&key=***'"));
+        assert!(!command.contains("secret"));
+    }
+}
@@ -0,0 +1,193 @@
+/**
+ * Database Maintenance Service
+ * 数据库维护：回收已删除数据占用的磁盘空间
+ *
+ * SQLite 删除记录后不会自动缩小数据库文件，
+ * 需要执行 `PRAGMA wal_checkpoint(TRUNCATE)` 与 `VACUUM` 才能释放空间。
+ */
+
+use crate::db::DbPool;
+use crate::models::error::{AppError, AppResult};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// 压缩阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompactStage {
+    /// 合并 WAL 日志
+    Checkpoint,
+    /// 重建数据库文件
+    Vacuum,
+    /// 完成
+    Completed,
+}
+
+/// 压缩进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactProgress {
+    pub stage: CompactStage,
+    pub progress: f32, // 0.0 - 1.0
+    pub message: String,
+}
+
+/// 压缩结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactResult {
+    /// 压缩前大小（数据库文件 + WAL 文件，字节）
+    pub size_before: u64,
+    /// 压缩后大小（字节）
+    pub size_after: u64,
+    /// 释放的字节数
+    pub freed_bytes: u64,
+    /// 耗时（毫秒）
+    pub duration_ms: u64,
+}
+
+/// 数据库维护服务
+pub struct DbMaintenanceService;
+
+impl DbMaintenanceService {
+    /// 压缩数据库（WAL checkpoint + VACUUM）
+    ///
+    /// VACUUM 期间同时持有主连接与日志写入连接的锁，其他数据库操作
+    /// (包括代理请求的日志写入) 需等待完成，不会因忙等待超时而被丢弃。
+    ///
+    /// # 参数
+    /// - `pool`: 数据库连接池
+    /// - `on_progress`: 进度回调
+    pub fn compact<F>(pool: &DbPool, on_progress: F) -> AppResult<CompactResult>
+    where
+        F: Fn(CompactProgress),
+    {
+        let start = Instant::now();
+
+        pool.with_exclusive_connection(|conn| {
+            let db_file = Self::main_db_file(conn)?;
+            let size_before = Self::files_size(db_file.as_ref());
+            log::info!("开始压缩数据库: {:?} ({} 字节)", db_file, size_before);
+
+            on_progress(CompactProgress {
+                stage: CompactStage::Checkpoint,
+                progress: 0.1,
+                message: "正在合并 WAL 日志...".to_string(),
+            });
+            conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("WAL checkpoint 失败: {}", e),
+                })?;
+
+            on_progress(CompactProgress {
+                stage: CompactStage::Vacuum,
+                progress: 0.3,
+                message: "正在重建数据库文件，数据库较大时可能需要一段时间...".to_string(),
+            });
+            conn.execute_batch("VACUUM").map_err(|e| AppError::DatabaseError {
+                message: format!("VACUUM 失败: {}", e),
+            })?;
+
+            let size_after = Self::files_size(db_file.as_ref());
+            let result = CompactResult {
+                size_before,
+                size_after,
+                freed_bytes: size_before.saturating_sub(size_after),
+                duration_ms: start.elapsed().as_millis() as u64,
+            };
+
+            log::info!(
+                "数据库压缩完成: {} -> {} 字节 (释放 {} 字节, 耗时 {}ms)",
+                result.size_before,
+                result.size_after,
+                result.freed_bytes,
+                result.duration_ms
+            );
+
+            on_progress(CompactProgress {
+                stage: CompactStage::Completed,
+                progress: 1.0,
+                message: format!("压缩完成，释放 {} 字节", result.freed_bytes),
+            });
+
+            Ok(result)
+        })
+    }
+
+    /// 获取主数据库文件路径（内存数据库返回 None）
+    fn main_db_file(conn: &Connection) -> AppResult<Option<PathBuf>> {
+        let path: String = conn
+            .query_row("PRAGMA database_list", [], |row| row.get(2))
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("获取数据库文件路径失败: {}", e),
+            })?;
+
+        Ok(if path.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(path))
+        })
+    }
+
+    /// 数据库文件与 WAL 文件的总大小
+    fn files_size(db_file: Option<&PathBuf>) -> u64 {
+        let Some(db_file) = db_file else {
+            return 0;
+        };
+
+        let mut wal_file = db_file.clone().into_os_string();
+        wal_file.push("-wal");
+
+        [db_file.clone(), PathBuf::from(wal_file)]
+            .iter()
+            .filter_map(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_compact_reclaims_space() {
+        let db_path = std::env::temp_dir().join(format!(
+            "compact_test_{}.db",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&db_path);
+
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE Filler (id INTEGER PRIMARY KEY, data TEXT);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 2000)
+             INSERT INTO Filler (data) SELECT hex(randomblob(512)) FROM n;
+             DELETE FROM Filler;",
+        )
+        .unwrap();
+        let pool = DbPool::new(conn);
+
+        let stages = Mutex::new(Vec::new());
+        let result = DbMaintenanceService::compact(&pool, |p| stages.lock().unwrap().push(p.stage)).unwrap();
+
+        assert!(result.size_before > result.size_after);
+        assert_eq!(result.freed_bytes, result.size_before - result.size_after);
+        assert_eq!(
+            *stages.lock().unwrap(),
+            vec![CompactStage::Checkpoint, CompactStage::Vacuum, CompactStage::Completed]
+        );
+
+        drop(pool);
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    #[test]
+    fn test_compact_in_memory() {
+        let pool = DbPool::new_in_memory().unwrap();
+        let result = DbMaintenanceService::compact(&pool, |_| {}).unwrap();
+        assert_eq!(result.size_before, 0);
+        assert_eq!(result.freed_bytes, 0);
+    }
+}
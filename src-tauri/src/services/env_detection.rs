@@ -3,7 +3,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::process::Command;
-use crate::models::error::AppResult;
+use crate::models::error::{AppError, AppResult};
 use crate::models::node_environment::{EnhancedEnvironmentStatus, NodeEnvironment};
 use crate::services::node_scanner::NodeScanner;
 
@@ -615,6 +615,28 @@ impl EnvironmentStatus {
     }
 }
 
+/// 需要检测可达性的大模型服务端点 (提供商, URL)
+const LLM_ENDPOINTS: &[(&str, &str)] = &[
+    ("anthropic", "https://api.anthropic.com"),
+    ("gemini", "https://generativelanguage.googleapis.com"),
+    ("openai", "https://api.openai.com"),
+];
+
+/// 大模型端点探测超时（秒）
+const LLM_ENDPOINT_TIMEOUT_SECS: u64 = 5;
+
+/// 大模型服务端点可达性
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmEndpointReachability {
+    pub provider: String,
+    pub url: String,
+    /// 收到任意 HTTP 响应即视为可达（未认证的 401/404 也算）
+    pub reachable: bool,
+    pub status_code: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
 // ============================================
 // 增强的环境检测功能
 // ============================================
@@ -691,7 +713,7 @@ impl EnhancedEnvironmentDetector {
             git_bash_installed,
             ripgrep_installed,
             network_available,
-            detected_at: chrono::Utc::now().to_rfc3339(),
+            detected_at: crate::utils::time::now_rfc3339(),
             detection_duration_ms: duration.as_millis() as u64,
         })
     }
@@ -770,6 +792,64 @@ impl EnhancedEnvironmentDetector {
         Self::check_network_ping()
     }
 
+    /// 检测常用大模型服务端点的可达性
+    ///
+    /// 并发探测各提供商域名，用于提前发现被地区屏蔽的服务（如需改用中转服务）
+    pub async fn check_llm_endpoints_reachability() -> AppResult<Vec<LlmEndpointReachability>> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(LLM_ENDPOINT_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| AppError::ServiceError {
+                message: format!("创建 HTTP 客户端失败: {}", e),
+            })?;
+
+        let probes = LLM_ENDPOINTS
+            .iter()
+            .map(|(provider, url)| Self::probe_llm_endpoint(&client, provider, url));
+        Ok(futures_util::future::join_all(probes).await)
+    }
+
+    /// 探测单个端点
+    async fn probe_llm_endpoint(
+        client: &reqwest::Client,
+        provider: &str,
+        url: &str,
+    ) -> LlmEndpointReachability {
+        let start = std::time::Instant::now();
+        let result = client.get(url).send().await;
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(response) => {
+                log::info!("端点 {} 可达 (状态: {}, {}ms)", url, response.status(), latency_ms);
+                LlmEndpointReachability {
+                    provider: provider.to_string(),
+                    url: url.to_string(),
+                    reachable: true,
+                    status_code: Some(response.status().as_u16()),
+                    latency_ms: Some(latency_ms),
+                    error: None,
+                }
+            }
+            Err(e) => {
+                log::warn!("端点 {} 不可达: {}", url, e);
+                let error = if e.is_timeout() {
+                    format!("连接超时 ({}秒)", LLM_ENDPOINT_TIMEOUT_SECS)
+                } else {
+                    e.to_string()
+                };
+                LlmEndpointReachability {
+                    provider: provider.to_string(),
+                    url: url.to_string(),
+                    reachable: false,
+                    status_code: None,
+                    latency_ms: None,
+                    error: Some(error),
+                }
+            }
+        }
+    }
+
     /// 使用 ping 检测网络连接（备用方案）
     fn check_network_ping() -> bool {
         #[cfg(target_os = "windows")]
@@ -818,4 +898,36 @@ mod tests {
             println!("缺失依赖: {:?}", missing);
         }
     }
+
+    #[tokio::test]
+    async fn test_probe_llm_endpoint() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let reachable_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let _ = socket
+                .write_all(b"HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\n\r\n")
+                .await;
+        });
+
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let unreachable_url = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+
+        let client = reqwest::Client::new();
+
+        // 未认证返回 404 也说明网络可达
+        let result = EnhancedEnvironmentDetector::probe_llm_endpoint(&client, "test", &reachable_url).await;
+        assert!(result.reachable);
+        assert_eq!(result.status_code, Some(404));
+        assert!(result.latency_ms.is_some());
+
+        let result = EnhancedEnvironmentDetector::probe_llm_endpoint(&client, "test", &unreachable_url).await;
+        assert!(!result.reachable);
+        assert!(result.error.is_some());
+    }
 }
@@ -0,0 +1,468 @@
+/**
+ * 环境变量片段导入服务
+ * 解析供应商提供的 `export ANTHROPIC_BASE_URL=...` / `.env` 配置片段并创建 API 配置，
+ * 以及反向生成指向代理会话的导出命令，便于在自己的终端中手动启动 Claude Code
+ */
+
+use crate::models::api_config::{ApiConfig, ApiKeyMode, CreateApiConfigInput};
+use crate::models::environment_variable::{ConfigEnvExports, EnvSnippetImportResult};
+use crate::models::error::{AppError, AppResult};
+use crate::services::api_config::ApiConfigService;
+use crate::services::env_var::{
+    ENV_KEY_ANTHROPIC_API_KEY, ENV_KEY_ANTHROPIC_AUTH_TOKEN, ENV_KEY_ANTHROPIC_BASE_URL,
+};
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+/// 模型相关环境变量
+pub const ENV_KEY_ANTHROPIC_MODEL: &str = "ANTHROPIC_MODEL";
+pub const ENV_KEY_ANTHROPIC_DEFAULT_HAIKU_MODEL: &str = "ANTHROPIC_DEFAULT_HAIKU_MODEL";
+pub const ENV_KEY_ANTHROPIC_DEFAULT_SONNET_MODEL: &str = "ANTHROPIC_DEFAULT_SONNET_MODEL";
+pub const ENV_KEY_ANTHROPIC_DEFAULT_OPUS_MODEL: &str = "ANTHROPIC_DEFAULT_OPUS_MODEL";
+pub const ENV_KEY_ANTHROPIC_SMALL_FAST_MODEL: &str = "ANTHROPIC_SMALL_FAST_MODEL";
+
+/// 高级设置相关环境变量
+pub const ENV_KEY_API_TIMEOUT_MS: &str = "API_TIMEOUT_MS";
+pub const ENV_KEY_CLAUDE_CODE_MAX_OUTPUT_TOKENS: &str = "CLAUDE_CODE_MAX_OUTPUT_TOKENS";
+
+/// 可选的导入字段: (环境变量, 配置字段)
+const OPTIONAL_FIELDS: &[(&str, &str)] = &[
+    (ENV_KEY_ANTHROPIC_MODEL, "default_model"),
+    (ENV_KEY_ANTHROPIC_DEFAULT_HAIKU_MODEL, "haiku_model"),
+    (ENV_KEY_ANTHROPIC_DEFAULT_SONNET_MODEL, "sonnet_model"),
+    (ENV_KEY_ANTHROPIC_DEFAULT_OPUS_MODEL, "opus_model"),
+    (ENV_KEY_ANTHROPIC_SMALL_FAST_MODEL, "small_fast_model"),
+    (ENV_KEY_API_TIMEOUT_MS, "api_timeout_ms"),
+    (ENV_KEY_CLAUDE_CODE_MAX_OUTPUT_TOKENS, "max_output_tokens"),
+];
+
+/// 客户端密钥透传时导出的密钥占位符
+pub const CLIENT_KEY_PLACEHOLDER: &str = "<YOUR_API_KEY>";
+
+/// 片段解析结果
+#[derive(Debug, Clone)]
+pub struct ParsedEnvSnippet {
+    /// 创建配置的输入参数
+    pub input: CreateApiConfigInput,
+    /// 已解析的变量名
+    pub parsed_fields: Vec<String>,
+    /// 未提供的可识别变量名
+    pub missing_fields: Vec<String>,
+    /// 被忽略的变量名
+    pub ignored_keys: Vec<String>,
+}
+
+/// 环境变量片段导入服务
+pub struct EnvSnippetService;
+
+impl EnvSnippetService {
+    /// 解析 `.env` / shell export 片段
+    ///
+    /// 支持:
+    /// - `KEY=value` 与 `export KEY=value`，同一行可包含多个赋值
+    /// - 单引号/双引号包裹的值
+    /// - `#` 开头的注释行与行尾注释
+    ///
+    /// 同名变量以最后一次出现为准
+    pub fn parse(text: &str) -> HashMap<String, String> {
+        let mut vars = HashMap::new();
+
+        for line in text.lines() {
+            for token in Self::split_words(line) {
+                if token == "export" {
+                    continue;
+                }
+                let Some((key, value)) = token.split_once('=') else {
+                    continue;
+                };
+                if Self::is_valid_key(key) {
+                    vars.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+
+        vars
+    }
+
+    /// 将解析出的变量映射为创建配置的输入
+    pub fn build_input(vars: &HashMap<String, String>, group_id: Option<i64>) -> AppResult<ParsedEnvSnippet> {
+        let get = |key: &str| vars.get(key).map(|v| v.trim()).filter(|v| !v.is_empty());
+
+        let mut parsed_fields = Vec::new();
+        let mut missing_fields = Vec::new();
+
+        let server_url = get(ENV_KEY_ANTHROPIC_BASE_URL);
+        match server_url {
+            Some(_) => parsed_fields.push(ENV_KEY_ANTHROPIC_BASE_URL.to_string()),
+            None => missing_fields.push(ENV_KEY_ANTHROPIC_BASE_URL.to_string()),
+        }
+
+        // ANTHROPIC_AUTH_TOKEN 优先于 ANTHROPIC_API_KEY，与 Claude Code 的行为一致
+        let api_key = match (get(ENV_KEY_ANTHROPIC_AUTH_TOKEN), get(ENV_KEY_ANTHROPIC_API_KEY)) {
+            (Some(token), _) => {
+                parsed_fields.push(ENV_KEY_ANTHROPIC_AUTH_TOKEN.to_string());
+                Some(token)
+            }
+            (None, Some(key)) => {
+                parsed_fields.push(ENV_KEY_ANTHROPIC_API_KEY.to_string());
+                Some(key)
+            }
+            (None, None) => {
+                missing_fields.push(format!("{}/{}", ENV_KEY_ANTHROPIC_AUTH_TOKEN, ENV_KEY_ANTHROPIC_API_KEY));
+                None
+            }
+        };
+
+        let (Some(server_url), Some(api_key)) = (server_url, api_key) else {
+            return Err(AppError::ValidationError {
+                field: "text".to_string(),
+                message: format!("配置片段缺少必要的环境变量: {}", missing_fields.join(", ")),
+            });
+        };
+
+        let mut optional = |key: &str| {
+            let value = get(key).map(str::to_string);
+            match value {
+                Some(_) => parsed_fields.push(key.to_string()),
+                None => missing_fields.push(key.to_string()),
+            }
+            value
+        };
+
+        let default_model = optional(ENV_KEY_ANTHROPIC_MODEL);
+        let haiku_model = optional(ENV_KEY_ANTHROPIC_DEFAULT_HAIKU_MODEL);
+        let sonnet_model = optional(ENV_KEY_ANTHROPIC_DEFAULT_SONNET_MODEL);
+        let opus_model = optional(ENV_KEY_ANTHROPIC_DEFAULT_OPUS_MODEL);
+        let small_fast_model = optional(ENV_KEY_ANTHROPIC_SMALL_FAST_MODEL);
+        let api_timeout_ms = Self::parse_number(optional(ENV_KEY_API_TIMEOUT_MS), ENV_KEY_API_TIMEOUT_MS)?;
+        let max_output_tokens = Self::parse_number(
+            optional(ENV_KEY_CLAUDE_CODE_MAX_OUTPUT_TOKENS),
+            ENV_KEY_CLAUDE_CODE_MAX_OUTPUT_TOKENS,
+        )?;
+
+        let mut ignored_keys: Vec<String> = vars
+            .keys()
+            .filter(|key| {
+                key.as_str() != ENV_KEY_ANTHROPIC_BASE_URL
+                    && key.as_str() != ENV_KEY_ANTHROPIC_AUTH_TOKEN
+                    && key.as_str() != ENV_KEY_ANTHROPIC_API_KEY
+                    && !OPTIONAL_FIELDS.iter().any(|(env_key, _)| env_key == key)
+            })
+            .cloned()
+            .collect();
+        ignored_keys.sort();
+
+        #[allow(deprecated)]
+        let input = CreateApiConfigInput {
+            name: Self::name_from_url(server_url),
+            api_key: api_key.to_string(),
+            server_url: server_url.to_string(),
+            server_port: None,
+            group_id,
+            sort_order: None,
+            provider_type: None,
+            organization_id: None,
+            category: None,
+            is_partner: None,
+            theme_icon: None,
+            theme_bg_color: None,
+            theme_text_color: None,
+            meta: None,
+            default_model,
+            haiku_model,
+            sonnet_model,
+            opus_model,
+            small_fast_model,
+            api_timeout_ms,
+            max_output_tokens,
+            balance_query_url: None,
+            auto_balance_check: None,
+            balance_check_interval_sec: None,
+            balance_currency: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
+            extra_query: None,
+            strip_request_fields: None,
+            health_check_mode: None,
+            metadata_user_id_policy: None,
+            anthropic_beta_filter: None,
+            api_key_mode: None,
+            request_compression: None,
+            user_agent: None,
+        };
+
+        Ok(ParsedEnvSnippet {
+            input,
+            parsed_fields,
+            missing_fields,
+            ignored_keys,
+        })
+    }
+
+    /// 解析片段并创建配置，配置名取自服务器主机名（重名时追加序号）
+    pub fn create_config(conn: &Connection, text: &str, group_id: Option<i64>) -> AppResult<EnvSnippetImportResult> {
+        let vars = Self::parse(text);
+        let ParsedEnvSnippet {
+            mut input,
+            parsed_fields,
+            missing_fields,
+            ignored_keys,
+        } = Self::build_input(&vars, group_id)?;
+        input.name = Self::unique_name(conn, &input.name)?;
+
+        let config = ApiConfigService::create_config(conn, &input)?;
+        log::info!(
+            "已从环境变量片段创建配置: {} (解析 {} 项, 缺失 {} 项)",
+            config.name,
+            parsed_fields.len(),
+            missing_fields.len()
+        );
+
+        Ok(EnvSnippetImportResult {
+            config,
+            parsed_fields,
+            missing_fields,
+            ignored_keys,
+        })
+    }
+
+    /// 按 shell 规则切分单词（支持引号与行尾注释），去除引号
+    fn split_words(line: &str) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut current = String::new();
+        let mut in_word = false;
+        let mut quote: Option<char> = None;
+
+        for c in line.trim().chars() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => current.push(c),
+                None => match c {
+                    '\'' | '"' => {
+                        quote = Some(c);
+                        in_word = true;
+                    }
+                    '#' if !in_word => break,
+                    c if c.is_whitespace() => {
+                        if in_word {
+                            words.push(std::mem::take(&mut current));
+                            in_word = false;
+                        }
+                    }
+                    c => {
+                        current.push(c);
+                        in_word = true;
+                    }
+                },
+            }
+        }
+        if in_word {
+            words.push(current);
+        }
+
+        words
+    }
+
+    fn is_valid_key(key: &str) -> bool {
+        !key.is_empty()
+            && !key.starts_with(|c: char| c.is_ascii_digit())
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    fn parse_number(value: Option<String>, key: &str) -> AppResult<Option<i32>> {
+        value
+            .map(|v| {
+                v.parse::<i32>().map_err(|_| AppError::ValidationError {
+                    field: key.to_string(),
+                    message: format!("{} 不是有效的数字: {}", key, v),
+                })
+            })
+            .transpose()
+    }
+
+    /// 以服务器主机名作为默认配置名
+    fn name_from_url(server_url: &str) -> String {
+        reqwest::Url::parse(server_url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| server_url.to_string())
+    }
+
+    fn unique_name(conn: &Connection, base: &str) -> AppResult<String> {
+        let exists = |name: &str| -> AppResult<bool> {
+            conn.query_row("SELECT EXISTS(SELECT 1 FROM ApiConfig WHERE name = ?1)", [name], |row| row.get(0))
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("检查配置名称失败: {}", e),
+                })
+        };
+
+        if !exists(base)? {
+            return Ok(base.to_string());
+        }
+        let mut suffix = 2;
+        loop {
+            let name = format!("{} ({})", base, suffix);
+            if !exists(&name)? {
+                return Ok(name);
+            }
+            suffix += 1;
+        }
+    }
+}
+
+impl EnvSnippetService {
+    /// 生成使用指定配置启动 Claude Code 的导出命令
+    ///
+    /// ANTHROPIC_BASE_URL 指向代理会话，认证令牌为 `proxy-session:{session_id}`，
+    /// 由代理替换为配置中保存的密钥，因此导出内容不包含真实密钥。
+    /// 配置使用客户端密钥透传时后端需要客户端自己的密钥，令牌改为占位符。
+    /// 同时清除 ANTHROPIC_API_KEY，避免与 ANTHROPIC_AUTH_TOKEN 冲突。
+    pub fn config_exports(config: &ApiConfig, proxy_base_url: &str, session_id: &str) -> ConfigEnvExports {
+        let base_url = format!("{}/session/{}", proxy_base_url.trim_end_matches('/'), session_id);
+        let requires_client_key = config.api_key_mode == ApiKeyMode::Client;
+        let auth_token = if requires_client_key {
+            CLIENT_KEY_PLACEHOLDER.to_string()
+        } else {
+            format!("proxy-session:{}", session_id)
+        };
+
+        let mut variables = vec![
+            (ENV_KEY_ANTHROPIC_BASE_URL.to_string(), base_url.clone()),
+            (ENV_KEY_ANTHROPIC_AUTH_TOKEN.to_string(), auth_token),
+        ];
+        let optional = [
+            (ENV_KEY_ANTHROPIC_MODEL, config.default_model.clone()),
+            (ENV_KEY_ANTHROPIC_DEFAULT_HAIKU_MODEL, config.haiku_model.clone()),
+            (ENV_KEY_ANTHROPIC_DEFAULT_SONNET_MODEL, config.sonnet_model.clone()),
+            (ENV_KEY_ANTHROPIC_DEFAULT_OPUS_MODEL, config.opus_model.clone()),
+            (ENV_KEY_ANTHROPIC_SMALL_FAST_MODEL, config.small_fast_model.clone()),
+            (ENV_KEY_API_TIMEOUT_MS, config.api_timeout_ms.map(|v| v.to_string())),
+            (ENV_KEY_CLAUDE_CODE_MAX_OUTPUT_TOKENS, config.max_output_tokens.map(|v| v.to_string())),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+                variables.push((key.to_string(), value));
+            }
+        }
+
+        let mut posix = vec![format!("unset {}", ENV_KEY_ANTHROPIC_API_KEY)];
+        let mut powershell = vec![format!(
+            "Remove-Item Env:{} -ErrorAction SilentlyContinue",
+            ENV_KEY_ANTHROPIC_API_KEY
+        )];
+        for (key, value) in &variables {
+            posix.push(format!("export {}='{}'", key, value.replace('\'', "'\\''")));
+            powershell.push(format!("$env:{} = '{}'", key, value.replace('\'', "''")));
+        }
+
+        ConfigEnvExports {
+            config_id: config.id,
+            config_name: config.name.clone(),
+            session_id: session_id.to_string(),
+            base_url,
+            variables,
+            posix: posix.join("\n"),
+            powershell: powershell.join("\n"),
+            requires_client_key,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_export_and_dotenv_lines() {
+        let vars = EnvSnippetService::parse(
+            "# provider setup\n\
+             export ANTHROPIC_BASE_URL=\"https://api.example.com\" ANTHROPIC_AUTH_TOKEN='sk-abc 123'\n\
+             ANTHROPIC_MODEL=claude-sonnet-4 # default model\n\
+             not a variable\n\
+             API_TIMEOUT_MS=600000\n",
+        );
+        assert_eq!(vars.get(ENV_KEY_ANTHROPIC_BASE_URL).unwrap(), "https://api.example.com");
+        assert_eq!(vars.get(ENV_KEY_ANTHROPIC_AUTH_TOKEN).unwrap(), "sk-abc 123");
+        assert_eq!(vars.get(ENV_KEY_ANTHROPIC_MODEL).unwrap(), "claude-sonnet-4");
+        assert_eq!(vars.get(ENV_KEY_API_TIMEOUT_MS).unwrap(), "600000");
+        assert_eq!(vars.len(), 4);
+    }
+
+    #[test]
+    fn test_build_input_reports_parsed_and_missing_fields() {
+        let vars = EnvSnippetService::parse(
+            "export ANTHROPIC_BASE_URL=https://api.example.com/anthropic\n\
+             export ANTHROPIC_API_KEY=sk-key\n\
+             export ANTHROPIC_SMALL_FAST_MODEL=claude-haiku\n\
+             export CLAUDE_CODE_MAX_OUTPUT_TOKENS=32000\n\
+             export HTTPS_PROXY=http://127.0.0.1:7890\n",
+        );
+        let ParsedEnvSnippet {
+            input,
+            parsed_fields: parsed,
+            missing_fields: missing,
+            ignored_keys: ignored,
+        } = EnvSnippetService::build_input(&vars, Some(3)).unwrap();
+
+        assert_eq!(input.name, "api.example.com");
+        assert_eq!(input.server_url, "https://api.example.com/anthropic");
+        assert_eq!(input.api_key, "sk-key");
+        assert_eq!(input.group_id, Some(3));
+        assert_eq!(input.small_fast_model.as_deref(), Some("claude-haiku"));
+        assert_eq!(input.max_output_tokens, Some(32000));
+        assert!(parsed.contains(&ENV_KEY_ANTHROPIC_API_KEY.to_string()));
+        assert!(missing.contains(&ENV_KEY_ANTHROPIC_MODEL.to_string()));
+        assert_eq!(ignored, vec!["HTTPS_PROXY".to_string()]);
+    }
+
+    #[test]
+    fn test_build_input_requires_base_url_and_credentials() {
+        let vars = EnvSnippetService::parse("export ANTHROPIC_BASE_URL=https://api.example.com");
+        let err = EnvSnippetService::build_input(&vars, None).unwrap_err();
+        assert!(err.to_string().contains(ENV_KEY_ANTHROPIC_AUTH_TOKEN));
+
+        let vars = EnvSnippetService::parse("API_TIMEOUT_MS=abc\nANTHROPIC_BASE_URL=https://a.com\nANTHROPIC_API_KEY=k");
+        assert!(EnvSnippetService::build_input(&vars, None).is_err());
+    }
+
+    #[test]
+    fn test_config_exports() {
+        let mut config: ApiConfig = serde_json::from_value(serde_json::json!({
+            "id": 7,
+            "name": "relay",
+            "api_key": "sk-secret-key",
+            "server_url": "https://api.example.com",
+            "server_port": 443,
+            "sort_order": 0,
+            "is_available": true,
+            "auto_balance_check": false,
+            "sonnet_model": "sonnet-x",
+            "opus_model": " ",
+            "api_timeout_ms": 600000,
+            "created_at": "",
+            "updated_at": ""
+        }))
+        .unwrap();
+
+        let exports = EnvSnippetService::config_exports(&config, "http://127.0.0.1:25341/", "env-7");
+        assert_eq!(exports.base_url, "http://127.0.0.1:25341/session/env-7");
+        assert!(!exports.requires_client_key);
+        assert!(!exports.posix.contains("sk-secret-key"));
+        assert!(!exports.powershell.contains("sk-secret-key"));
+        assert!(exports.posix.starts_with("unset ANTHROPIC_API_KEY\n"));
+        assert!(exports.powershell.contains("$env:ANTHROPIC_DEFAULT_SONNET_MODEL = 'sonnet-x'"));
+
+        // bash 导出可以被导入解析器原样读回
+        let vars = EnvSnippetService::parse(&exports.posix);
+        assert_eq!(vars.get(ENV_KEY_ANTHROPIC_AUTH_TOKEN).unwrap(), "proxy-session:env-7");
+        assert_eq!(vars.get(ENV_KEY_ANTHROPIC_DEFAULT_SONNET_MODEL).unwrap(), "sonnet-x");
+        assert_eq!(vars.get(ENV_KEY_API_TIMEOUT_MS).unwrap(), "600000");
+        assert!(!vars.contains_key(ENV_KEY_ANTHROPIC_DEFAULT_OPUS_MODEL));
+        assert_eq!(vars.len(), exports.variables.len());
+
+        config.api_key_mode = ApiKeyMode::Client;
+        let exports = EnvSnippetService::config_exports(&config, "http://127.0.0.1:25341", "env-7");
+        assert!(exports.requires_client_key);
+        assert!(exports.posix.contains(&format!("export ANTHROPIC_AUTH_TOKEN='{}'", CLIENT_KEY_PLACEHOLDER)));
+    }
+}
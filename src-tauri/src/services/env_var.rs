@@ -5,12 +5,16 @@
 
 use crate::models::error::{AppError, AppResult};
 use crate::models::api_config::ApiConfig;
+use crate::models::config_report::redact_key;
+use crate::models::environment_variable::{AnthropicEnvCheck, AnthropicEnvConflict, AnthropicEnvValue};
+use crate::services::claude_config::{ClaudeConfigService, ProxyConfig};
 use std::collections::HashMap;
 use std::env;
 
 /// 环境变量键名常量
 pub const ENV_KEY_ANTHROPIC_API_KEY: &str = "ANTHROPIC_API_KEY";
 pub const ENV_KEY_ANTHROPIC_BASE_URL: &str = "ANTHROPIC_BASE_URL";
+pub const ENV_KEY_ANTHROPIC_AUTH_TOKEN: &str = "ANTHROPIC_AUTH_TOKEN";
 
 /// 环境变量服务
 pub struct EnvironmentVariableService;
@@ -104,20 +108,120 @@ impl EnvironmentVariableService {
     pub fn clear_anthropic_env(&self) -> AppResult<()> {
         self.unset_env(ENV_KEY_ANTHROPIC_API_KEY)?;
         self.unset_env(ENV_KEY_ANTHROPIC_BASE_URL)?;
+        self.unset_env(ENV_KEY_ANTHROPIC_AUTH_TOKEN)?;
         log::info!("已清除 Anthropic 环境变量");
         Ok(())
     }
 
-    /// 检查必需的 Anthropic 环境变量是否已设置
+    /// 检查 Anthropic 环境变量并与代理监听地址比对
+    ///
+    /// # 参数
+    /// - `proxy`: 代理实际监听的地址 (代理未运行时传 None)
     ///
     /// # 返回
-    /// - `Ok(true)`: 所有必需的环境变量都已设置
-    /// - `Ok(false)`: 有环境变量未设置
-    pub fn check_anthropic_env(&self) -> AppResult<bool> {
-        let api_key = self.get_env(ENV_KEY_ANTHROPIC_API_KEY)?;
-        let base_url = self.get_env(ENV_KEY_ANTHROPIC_BASE_URL)?;
+    /// 各变量的取值 (密钥已脱敏)、凭据来源，以及与代理冲突的变量和清除建议
+    pub fn check_anthropic_env(&self, proxy: Option<&ProxyConfig>) -> AppResult<AnthropicEnvCheck> {
+        Ok(Self::analyze_anthropic_env(
+            |key| self.get_env(key).ok().flatten(),
+            proxy,
+        ))
+    }
+
+    /// 根据变量取值生成检查结果
+    fn analyze_anthropic_env(
+        lookup: impl Fn(&str) -> Option<String>,
+        proxy: Option<&ProxyConfig>,
+    ) -> AnthropicEnvCheck {
+        let read = |key: &str| lookup(key).filter(|v| !v.trim().is_empty());
+        let base_url = read(ENV_KEY_ANTHROPIC_BASE_URL);
+        let api_key = read(ENV_KEY_ANTHROPIC_API_KEY);
+        let auth_token = read(ENV_KEY_ANTHROPIC_AUTH_TOKEN);
+
+        let variables = vec![
+            AnthropicEnvValue {
+                key: ENV_KEY_ANTHROPIC_BASE_URL.to_string(),
+                is_set: base_url.is_some(),
+                value: base_url.clone(),
+            },
+            AnthropicEnvValue {
+                key: ENV_KEY_ANTHROPIC_API_KEY.to_string(),
+                is_set: api_key.is_some(),
+                value: api_key.as_deref().map(redact_key),
+            },
+            AnthropicEnvValue {
+                key: ENV_KEY_ANTHROPIC_AUTH_TOKEN.to_string(),
+                is_set: auth_token.is_some(),
+                value: auth_token.as_deref().map(redact_key),
+            },
+        ];
+
+        let auth_token_source = if auth_token.is_some() {
+            Some(ENV_KEY_ANTHROPIC_AUTH_TOKEN.to_string())
+        } else if api_key.is_some() {
+            Some(ENV_KEY_ANTHROPIC_API_KEY.to_string())
+        } else {
+            None
+        };
+
+        let expected_base_url = proxy.map(ClaudeConfigService::expected_base_url);
+        let mut conflicts = Vec::new();
+
+        if let (Some(url), Some(proxy)) = (&base_url, proxy) {
+            let points_to_proxy = reqwest::Url::parse(url.trim()).is_ok_and(|parsed| {
+                parsed
+                    .host_str()
+                    .is_some_and(|h| ClaudeConfigService::host_matches(h, &proxy.host))
+                    && parsed.port_or_known_default() == Some(proxy.port)
+            });
+            if !points_to_proxy {
+                conflicts.push(AnthropicEnvConflict {
+                    key: ENV_KEY_ANTHROPIC_BASE_URL.to_string(),
+                    expected: expected_base_url.clone(),
+                    actual: Some(url.clone()),
+                    message: "环境变量 ANTHROPIC_BASE_URL 未指向正在运行的代理，Claude Code 可能绕过代理直连该地址"
+                        .to_string(),
+                    clear_hint: Self::clear_hint(ENV_KEY_ANTHROPIC_BASE_URL),
+                });
+            }
+        }
+
+        if let (Some(key), Some(token)) = (&api_key, &auth_token) {
+            if key != token {
+                conflicts.push(AnthropicEnvConflict {
+                    key: ENV_KEY_ANTHROPIC_API_KEY.to_string(),
+                    expected: None,
+                    actual: Some(redact_key(key)),
+                    message: "同时设置了 ANTHROPIC_API_KEY 与 ANTHROPIC_AUTH_TOKEN 且取值不同，Claude Code 实际使用的凭据可能与预期不一致"
+                        .to_string(),
+                    clear_hint: Self::clear_hint(ENV_KEY_ANTHROPIC_API_KEY),
+                });
+            }
+        }
 
-        Ok(api_key.is_some() && base_url.is_some())
+        AnthropicEnvCheck {
+            is_configured: base_url.is_some() && auth_token_source.is_some(),
+            base_url,
+            auth_token_source,
+            proxy_running: proxy.is_some(),
+            expected_base_url,
+            variables,
+            conflicts,
+        }
+    }
+
+    /// 清除 shell 中导出的环境变量的操作建议
+    fn clear_hint(key: &str) -> String {
+        if cfg!(windows) {
+            format!(
+                "在 PowerShell 中执行 [Environment]::SetEnvironmentVariable(\"{}\", $null, \"User\")，然后重新打开终端",
+                key
+            )
+        } else {
+            format!(
+                "在终端执行 unset {}，并从 ~/.bashrc、~/.zshrc 等 shell 配置文件中删除对应的 export 行，然后重新打开终端",
+                key
+            )
+        }
     }
 }
 
@@ -213,6 +317,18 @@ mod tests {
             balance_query_error: None,
             auto_balance_check: false,
             balance_check_interval_sec: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
+            disabled_until: None,
+            extra_query: None,
+            strip_request_fields: None,
+            health_check_mode: Default::default(),
+            metadata_user_id_policy: Default::default(),
+            anthropic_beta_filter: None,
+            api_key_mode: Default::default(),
+            request_compression: Default::default(),
+            user_agent: None,
+            last_healthy_at: None,
             organization_id: None,
             created_at: now_rfc3339(),
             updated_at: now_rfc3339(),
@@ -275,6 +391,18 @@ mod tests {
             balance_query_error: None,
             auto_balance_check: false,
             balance_check_interval_sec: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
+            disabled_until: None,
+            extra_query: None,
+            strip_request_fields: None,
+            health_check_mode: Default::default(),
+            metadata_user_id_policy: Default::default(),
+            anthropic_beta_filter: None,
+            api_key_mode: Default::default(),
+            request_compression: Default::default(),
+            user_agent: None,
+            last_healthy_at: None,
             organization_id: None,
             created_at: now_rfc3339(),
             updated_at: now_rfc3339(),
@@ -282,11 +410,72 @@ mod tests {
 
         // 设置环境变量
         service.apply_from_config(&config).unwrap();
-        assert!(service.check_anthropic_env().unwrap());
+        assert!(service.check_anthropic_env(None).unwrap().is_configured);
 
         // 清除后应该返回 false
         service.clear_anthropic_env().unwrap();
-        assert!(!service.check_anthropic_env().unwrap());
+        assert!(!service.check_anthropic_env(None).unwrap().is_configured);
+    }
+
+    #[test]
+    fn test_analyze_anthropic_env_flags_conflicts() {
+        let proxy = ProxyConfig {
+            host: "127.0.0.1".to_string(),
+            port: 25341,
+        };
+        let env: HashMap<&str, &str> = [
+            (ENV_KEY_ANTHROPIC_BASE_URL, "https://api.anthropic.com"),
+            (ENV_KEY_ANTHROPIC_API_KEY, "sk-ant-shell-exported-key"),
+            (ENV_KEY_ANTHROPIC_AUTH_TOKEN, "sk-proxy-token-value"),
+        ]
+        .into_iter()
+        .collect();
+
+        let check = EnvironmentVariableService::analyze_anthropic_env(
+            |key| env.get(key).map(|v| v.to_string()),
+            Some(&proxy),
+        );
+
+        assert!(check.is_configured);
+        assert!(check.proxy_running);
+        assert_eq!(check.auth_token_source.as_deref(), Some(ENV_KEY_ANTHROPIC_AUTH_TOKEN));
+        assert_eq!(check.expected_base_url.as_deref(), Some("http://127.0.0.1:25341"));
+        assert_eq!(check.variables[1].value.as_deref(), Some("****-key"));
+        let keys: Vec<&str> = check.conflicts.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(keys, vec![ENV_KEY_ANTHROPIC_BASE_URL, ENV_KEY_ANTHROPIC_API_KEY]);
+        assert!(check.conflicts[0].clear_hint.contains(ENV_KEY_ANTHROPIC_BASE_URL));
+    }
+
+    #[test]
+    fn test_analyze_anthropic_env_matching_proxy() {
+        let proxy = ProxyConfig {
+            host: "0.0.0.0".to_string(),
+            port: 25341,
+        };
+        let env: HashMap<&str, &str> = [
+            (ENV_KEY_ANTHROPIC_BASE_URL, "http://localhost:25341"),
+            (ENV_KEY_ANTHROPIC_API_KEY, " "),
+        ]
+        .into_iter()
+        .collect();
+
+        let check = EnvironmentVariableService::analyze_anthropic_env(
+            |key| env.get(key).map(|v| v.to_string()),
+            Some(&proxy),
+        );
+        assert!(check.conflicts.is_empty());
+        assert!(!check.is_configured);
+        assert_eq!(check.auth_token_source, None);
+
+        // 代理未运行时不比对 Base URL
+        let env: HashMap<&str, &str> =
+            [(ENV_KEY_ANTHROPIC_BASE_URL, "https://api.anthropic.com")].into_iter().collect();
+        let check = EnvironmentVariableService::analyze_anthropic_env(
+            |key| env.get(key).map(|v| v.to_string()),
+            None,
+        );
+        assert!(check.conflicts.is_empty());
+        assert!(!check.proxy_running);
     }
 
     #[test]
@@ -0,0 +1,318 @@
+/**
+ * Failure Diagnosis Service
+ * 诊断配置最近一次失败的原因，并给出可执行的处理建议
+ *
+ * 数据来源:
+ * - ProxyRequestLog: 实际代理请求中失败的请求
+ * - TestResult: 失败或超时的连通性测试
+ *
+ * 两者中取时间最近的一条，交给 ErrorClassifier 分类后映射为诊断说明与处理步骤。
+ */
+
+use crate::db::DbPool;
+use crate::models::error::{AppError, AppResult};
+use crate::models::error_classifier::ErrorRecoverability;
+use crate::models::switch_log::ErrorType;
+use crate::services::error_classifier::ErrorClassifier;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// 失败记录来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureSource {
+    /// 代理请求 (ProxyRequestLog)
+    ProxyRequest,
+    /// 连通性测试 (TestResult)
+    ConnectionTest,
+}
+
+/// 最近一次失败的诊断结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureDiagnosis {
+    pub config_id: i64,
+    pub config_name: String,
+    pub source: FailureSource,
+    /// 失败发生时间
+    pub occurred_at: String,
+    /// HTTP 状态码 (连通性测试没有状态码)
+    pub status_code: Option<i32>,
+    /// 原始错误信息
+    pub error_message: Option<String>,
+    pub error_type: ErrorType,
+    pub recoverability: ErrorRecoverability,
+    /// 一句话诊断
+    pub summary: String,
+    /// 建议的处理步骤
+    pub remediation: Vec<String>,
+}
+
+/// 失败记录
+#[derive(Debug, Clone)]
+struct FailureRecord {
+    source: FailureSource,
+    occurred_at: String,
+    status_code: Option<i32>,
+    error_message: Option<String>,
+    /// 连通性测试的状态 (failed / timeout)
+    test_status: Option<String>,
+    /// 连通性测试判断的密钥是否有效
+    is_valid_key: Option<bool>,
+}
+
+impl FailureRecord {
+    /// 拼接送入错误分类器的文本
+    ///
+    /// 状态码与测试状态也参与分类，避免错误信息为空或过于笼统时无法识别
+    fn classifier_input(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(code) = self.status_code {
+            parts.push(format!("HTTP {}", code));
+        }
+        if self.test_status.as_deref() == Some("timeout") {
+            parts.push("timeout".to_string());
+        }
+        if self.is_valid_key == Some(false) {
+            parts.push("invalid api key".to_string());
+        }
+        if let Some(message) = self.error_message.as_deref() {
+            parts.push(message.to_string());
+        }
+        parts.join(" ")
+    }
+}
+
+/// 失败诊断服务
+pub struct FailureDiagnosisService;
+
+impl FailureDiagnosisService {
+    /// 诊断配置最近一次失败，没有失败记录时返回 None
+    pub fn diagnose_last_failure(pool: &DbPool, config_id: i64) -> AppResult<Option<FailureDiagnosis>> {
+        pool.with_connection(|conn| {
+            let config_name: String = conn
+                .query_row("SELECT name FROM ApiConfig WHERE id = ?1", [config_id], |row| row.get(0))
+                .optional()
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("获取配置失败: {}", e),
+                })?
+                .ok_or_else(|| AppError::NotFound {
+                    resource: "ApiConfig".to_string(),
+                    id: config_id.to_string(),
+                })?;
+
+            let Some(record) = Self::last_failure(conn, config_id)? else {
+                return Ok(None);
+            };
+
+            let (error_type, recoverability) = ErrorClassifier::new().classify(&record.classifier_input());
+            let (summary, remediation) = advice(&error_type);
+
+            Ok(Some(FailureDiagnosis {
+                config_id,
+                config_name,
+                source: record.source,
+                occurred_at: record.occurred_at,
+                status_code: record.status_code,
+                error_message: record.error_message,
+                error_type,
+                recoverability,
+                summary: summary.to_string(),
+                remediation: remediation.iter().map(|s| s.to_string()).collect(),
+            }))
+        })
+    }
+
+    /// 读取代理请求与连通性测试中时间最近的一条失败记录
+    fn last_failure(conn: &Connection, config_id: i64) -> AppResult<Option<FailureRecord>> {
+        conn.query_row(
+            "SELECT source, occurred_at, status_code, error_message, test_status, is_valid_key FROM (
+                 SELECT 'proxy_request' AS source, request_at AS occurred_at, status_code, error_message,
+                        NULL AS test_status, NULL AS is_valid_key, id
+                 FROM ProxyRequestLog WHERE config_id = ?1 AND is_success = 0
+                 UNION ALL
+                 SELECT 'connection_test', test_at, NULL, error_message, status, is_valid_key, id
+                 FROM TestResult WHERE config_id = ?1 AND status != 'success'
+             )
+             ORDER BY julianday(occurred_at) DESC, id DESC
+             LIMIT 1",
+            [config_id],
+            |row| {
+                let source = match row.get::<_, String>(0)?.as_str() {
+                    "proxy_request" => FailureSource::ProxyRequest,
+                    _ => FailureSource::ConnectionTest,
+                };
+                Ok(FailureRecord {
+                    source,
+                    occurred_at: row.get(1)?,
+                    status_code: row.get(2)?,
+                    error_message: row.get(3)?,
+                    test_status: row.get(4)?,
+                    is_valid_key: row.get(5)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("查询失败记录失败: {}", e),
+        })
+    }
+}
+
+/// 错误类型对应的诊断说明与处理步骤
+fn advice(error_type: &ErrorType) -> (&'static str, &'static [&'static str]) {
+    match error_type {
+        ErrorType::Authentication => (
+            "认证失败 — 请检查 API 密钥",
+            &[
+                "确认配置中的 API 密钥完整且没有多余的空格或换行",
+                "到服务商后台确认密钥未被删除、轮换或过期",
+                "如果配置使用客户端密钥透传，确认客户端发送了有效的密钥",
+            ],
+        ),
+        ErrorType::InsufficientBalance => (
+            "余额不足 — 请充值",
+            &[
+                "到服务商后台充值或提高额度上限",
+                "充值后刷新余额，确认额度已恢复",
+                "充值到账前可切换到同分组的其他配置",
+            ],
+        ),
+        ErrorType::AccountBanned => (
+            "账号被封禁或无权访问 — 请检查账号状态",
+            &[
+                "登录服务商后台确认账号是否被停用",
+                "确认密钥有权访问所请求的模型",
+                "账号状态异常时联系服务商处理，并暂时禁用该配置",
+            ],
+        ),
+        ErrorType::RateLimit => (
+            "触发限流 — 请求过于频繁",
+            &[
+                "降低并发或稍后重试",
+                "在分组重试策略中调大限流延迟",
+                "为分组添加更多配置并开启自动切换以分摊请求",
+            ],
+        ),
+        ErrorType::Network => (
+            "网络连接失败 — 请检查网络连通性",
+            &[
+                "检查本机网络、VPN 与系统代理设置",
+                "确认服务器地址拼写正确且域名可以解析",
+                "运行连通性测试确认后端是否可达",
+            ],
+        ),
+        ErrorType::Timeout => (
+            "请求超时 — 请检查网络连通性",
+            &[
+                "检查网络延迟是否异常，必要时更换网络",
+                "请求较大或模型较慢时，适当调大配置的连接/请求超时",
+                "后端持续超时时切换到同分组的其他配置",
+            ],
+        ),
+        ErrorType::ServerError => (
+            "后端服务异常 — 服务商暂时不可用",
+            &[
+                "稍后重试，502/503 通常是临时故障",
+                "查看服务商状态页确认是否有故障公告",
+                "开启分组自动切换，故障期间自动使用其他配置",
+            ],
+        ),
+        ErrorType::Unknown => (
+            "无法识别的错误 — 请查看完整错误信息",
+            &[
+                "在请求日志中查看完整的错误响应",
+                "运行连通性测试复现问题",
+                "问题持续时复制 curl 复现命令联系服务商",
+            ],
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_pool() -> DbPool {
+        let pool = DbPool::new(crate::db::test_db());
+        pool.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO ApiConfig (id, name, api_key, server_url) VALUES (1, 'c', 'k', 'https://example.com')",
+                [],
+            )
+            .unwrap();
+            Ok(())
+        })
+        .unwrap();
+        pool
+    }
+
+    #[test]
+    fn test_diagnoses_most_recent_failure() {
+        let pool = setup_pool();
+        assert!(FailureDiagnosisService::diagnose_last_failure(&pool, 1).unwrap().is_none());
+        assert!(matches!(
+            FailureDiagnosisService::diagnose_last_failure(&pool, 99),
+            Err(AppError::NotFound { .. })
+        ));
+
+        pool.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO ProxyRequestLog (request_at, method, uri, target_url, config_id, latency_ms, status_code, is_success, error_message)
+                     VALUES ('2026-01-01T10:00:00Z', 'POST', '/v1/messages', 'https://example.com', 1, 10, 402, 0, 'Insufficient credit');
+                 INSERT INTO TestResult (config_id, test_at, status, error_message)
+                     VALUES (1, '2026-01-01T09:00:00Z', 'timeout', NULL);",
+            )
+            .unwrap();
+            Ok(())
+        })
+        .unwrap();
+
+        let diagnosis = FailureDiagnosisService::diagnose_last_failure(&pool, 1).unwrap().unwrap();
+        assert_eq!(diagnosis.source, FailureSource::ProxyRequest);
+        assert_eq!(diagnosis.status_code, Some(402));
+        assert_eq!(diagnosis.error_type, ErrorType::InsufficientBalance);
+        assert_eq!(diagnosis.recoverability, ErrorRecoverability::Unrecoverable);
+        assert!(!diagnosis.remediation.is_empty());
+
+        // 更新的测试失败优先，状态本身参与分类
+        pool.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO TestResult (config_id, test_at, status, error_message)
+                     VALUES (1, '2026-01-01T11:00:00Z', 'timeout', NULL)",
+                [],
+            )
+            .unwrap();
+            Ok(())
+        })
+        .unwrap();
+
+        let diagnosis = FailureDiagnosisService::diagnose_last_failure(&pool, 1).unwrap().unwrap();
+        assert_eq!(diagnosis.source, FailureSource::ConnectionTest);
+        assert_eq!(diagnosis.error_type, ErrorType::Timeout);
+        assert_eq!(diagnosis.status_code, None);
+    }
+
+    #[test]
+    fn test_classifier_input_includes_status() {
+        let record = FailureRecord {
+            source: FailureSource::ProxyRequest,
+            occurred_at: String::new(),
+            status_code: Some(401),
+            error_message: None,
+            test_status: None,
+            is_valid_key: None,
+        };
+        let (error_type, _) = ErrorClassifier::new().classify(&record.classifier_input());
+        assert_eq!(error_type, ErrorType::Authentication);
+
+        let record = FailureRecord {
+            source: FailureSource::ConnectionTest,
+            status_code: None,
+            is_valid_key: Some(false),
+            error_message: Some("request rejected".to_string()),
+            ..record
+        };
+        let (error_type, _) = ErrorClassifier::new().classify(&record.classifier_input());
+        assert_eq!(error_type, ErrorType::Authentication);
+    }
+}
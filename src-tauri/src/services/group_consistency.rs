@@ -0,0 +1,187 @@
+/**
+ * Group Provider Consistency
+ * 检查分组内配置的提供商类型是否一致
+ *
+ * 分组混用 Claude / Gemini 等不同提供商时，自动切换会在会话中途改变响应语义
+ * (经过格式转换的响应与原生响应并不完全等价)，容易让客户端困惑。
+ * 检查结果按提供商列出配置，便于用户拆分分组；用户也可以确认当前组合，
+ * 确认记录的是当时的提供商组合，组合变化 (加入新的提供商) 后会重新提示。
+ */
+
+use crate::models::api_config::ProviderType;
+use crate::models::error::{AppError, AppResult};
+use crate::services::api_config::ApiConfigService;
+use crate::services::config_manager::ConfigManager;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// 分组内某一提供商类型的配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderConfigs {
+    pub provider_type: ProviderType,
+    pub config_ids: Vec<i64>,
+    pub config_names: Vec<String>,
+}
+
+/// 分组提供商一致性检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupProviderConsistency {
+    pub group_id: i64,
+    pub group_name: String,
+    /// 分组内所有配置是否使用同一提供商类型
+    pub is_consistent: bool,
+    /// 按提供商类型分组的配置 (按配置排序顺序中首次出现的先后排列)
+    pub providers: Vec<ProviderConfigs>,
+    /// 用户是否已确认当前的提供商组合
+    pub acknowledged: bool,
+    /// 需要提示用户时的警告信息 (一致或已确认时为 None)
+    pub warning: Option<String>,
+}
+
+/// 分组提供商一致性服务
+pub struct GroupConsistencyService;
+
+impl GroupConsistencyService {
+    /// 检查分组内配置的提供商类型是否一致
+    pub fn check(conn: &Connection, group_id: i64) -> AppResult<GroupProviderConsistency> {
+        let group = ConfigManager::get_group_by_id(conn, group_id)?;
+        let providers = Self::providers_in_group(conn, group_id)?;
+        let is_consistent = providers.len() <= 1;
+
+        let acknowledged = !is_consistent
+            && Self::acknowledged_mix(conn, group_id)?.as_deref() == Some(mix_signature(&providers).as_str());
+
+        let warning = (!is_consistent && !acknowledged).then(|| {
+            let summary = providers
+                .iter()
+                .map(|p| format!("{} ({} 个配置)", p.provider_type, p.config_ids.len()))
+                .collect::<Vec<_>>()
+                .join("、");
+            if group.auto_switch_enabled {
+                format!(
+                    "分组「{}」混用了多种提供商: {}。自动切换可能在会话中途切换到其他提供商，响应格式与行为会随之变化，建议按提供商拆分分组",
+                    group.name, summary
+                )
+            } else {
+                format!(
+                    "分组「{}」混用了多种提供商: {}。开启自动切换后可能在会话中途切换到其他提供商，建议按提供商拆分分组",
+                    group.name, summary
+                )
+            }
+        });
+
+        Ok(GroupProviderConsistency {
+            group_id,
+            group_name: group.name,
+            is_consistent,
+            providers,
+            acknowledged,
+            warning,
+        })
+    }
+
+    /// 确认分组当前的提供商组合，之后不再对该组合发出警告
+    pub fn acknowledge(conn: &Connection, group_id: i64) -> AppResult<GroupProviderConsistency> {
+        ConfigManager::get_group_by_id(conn, group_id)?;
+        let providers = Self::providers_in_group(conn, group_id)?;
+        if providers.len() <= 1 {
+            return Err(AppError::InvalidState {
+                message: "分组内配置的提供商类型一致，无需确认".to_string(),
+            });
+        }
+
+        conn.execute(
+            "UPDATE ConfigGroup SET acknowledged_provider_mix = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            params![mix_signature(&providers), group_id],
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("保存提供商组合确认失败: {}", e),
+        })?;
+
+        log::info!("分组 {} 已确认混合提供商组合: {}", group_id, mix_signature(&providers));
+        Self::check(conn, group_id)
+    }
+
+    /// 按提供商类型归类分组内的配置
+    fn providers_in_group(conn: &Connection, group_id: i64) -> AppResult<Vec<ProviderConfigs>> {
+        let mut providers: Vec<ProviderConfigs> = Vec::new();
+        for config in ApiConfigService::list_configs(conn, Some(group_id))? {
+            match providers.iter_mut().find(|p| p.provider_type == config.provider_type) {
+                Some(entry) => {
+                    entry.config_ids.push(config.id);
+                    entry.config_names.push(config.name);
+                }
+                None => providers.push(ProviderConfigs {
+                    provider_type: config.provider_type,
+                    config_ids: vec![config.id],
+                    config_names: vec![config.name],
+                }),
+            }
+        }
+        Ok(providers)
+    }
+
+    fn acknowledged_mix(conn: &Connection, group_id: i64) -> AppResult<Option<String>> {
+        conn.query_row(
+            "SELECT acknowledged_provider_mix FROM ConfigGroup WHERE id = ?1",
+            [group_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("查询提供商组合确认失败: {}", e),
+        })
+    }
+}
+
+/// 提供商组合签名 (排序后的提供商类型，逗号分隔)
+fn mix_signature(providers: &[ProviderConfigs]) -> String {
+    let mut types: Vec<String> = providers.iter().map(|p| p.provider_type.to_string()).collect();
+    types.sort();
+    types.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DbPool;
+
+    #[test]
+    fn test_mixed_providers_warn_until_acknowledged() {
+        let pool = DbPool::new(crate::db::test_db());
+        pool.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO ConfigGroup (id, name, auto_switch_enabled) VALUES (1, 'g', 1);
+                 INSERT INTO ApiConfig (id, name, api_key, server_url, group_id, provider_type)
+                     VALUES (1, 'claude-a', 'k', 'https://a.example.com', 1, 'claude');",
+            )
+            .unwrap();
+
+            let report = GroupConsistencyService::check(conn, 1)?;
+            assert!(report.is_consistent);
+            assert!(report.warning.is_none());
+            assert!(GroupConsistencyService::acknowledge(conn, 1).is_err());
+
+            conn.execute(
+                "INSERT INTO ApiConfig (id, name, api_key, server_url, group_id, provider_type)
+                     VALUES (2, 'gemini-a', 'k', 'https://b.example.com', 1, 'gemini')",
+                [],
+            )
+            .unwrap();
+
+            let report = GroupConsistencyService::check(conn, 1)?;
+            assert!(!report.is_consistent);
+            assert!(!report.acknowledged);
+            assert!(report.warning.as_deref().unwrap().contains("自动切换"));
+            assert_eq!(report.providers.len(), 2);
+            assert_eq!(report.providers[1].config_names, vec!["gemini-a".to_string()]);
+
+            let report = GroupConsistencyService::acknowledge(conn, 1)?;
+            assert!(report.acknowledged);
+            assert!(report.warning.is_none());
+
+            assert!(GroupConsistencyService::check(conn, 99).is_err());
+            Ok(())
+        })
+        .unwrap();
+    }
+}
@@ -4,19 +4,25 @@
  *
  * Features:
  * - 每5分钟自动检查 /v1/health 端点
+ * - 按配置选择探测方式: health (/v1/health) / full (/v1/messages，计费) /
+ *   reachability (TCP+TLS+HEAD，不携带密钥) / models (GET /v1/models)
  * - 直接向各服务商发送请求（不通过代理）
  * - 记录检查结果到数据库
  * - 支持启动/停止/配置检查间隔
  * - 根据检查结果自动更新配置可用状态
  * - 服务商恢复可用时自动切换到最高权重服务商
+ * - 每轮检查共享一个 HTTP 客户端（连接复用），并发探测多个配置
  */
 
 use crate::db::DbPool;
-use crate::models::api_config::UpdateApiConfigInput;
+use crate::models::api_config::{ApiConfig, UpdateApiConfigInput};
 use crate::models::error::{AppError, AppResult};
-use crate::models::health_check::{CreateHealthCheckRecordInput, HealthCheckStatus};
+use crate::models::health_check::{CreateHealthCheckRecordInput, HealthCheckMode, HealthCheckStatus};
 use crate::services::api_config::ApiConfigService;
+use crate::services::claude_test_request::{add_claude_code_headers, build_test_request_body};
 use crate::services::health_check_service::HealthCheckService;
+use crate::services::latency_test::LatencyTestService;
+use futures_util::stream::{self, StreamExt};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
@@ -25,6 +31,12 @@ use tokio::time::{interval, Duration as TokioDuration};
 /// 默认健康检查间隔（秒）- 5分钟
 const DEFAULT_HEALTH_CHECK_INTERVAL_SECS: u64 = 300;
 
+/// 每轮同时进行的探测数量上限
+const MAX_CONCURRENT_HEALTH_CHECKS: usize = 8;
+
+/// 单个配置的探测结果: Ok((延迟ms, HTTP状态码)) / Err((状态, 错误信息, HTTP状态码))
+type ProbeResult = Result<(i64, Option<i32>), (HealthCheckStatus, String, Option<i32>)>;
+
 /// 健康检查调度器状态
 #[derive(Debug, Clone, PartialEq)]
 pub enum HealthCheckSchedulerStatus {
@@ -241,17 +253,23 @@ impl HealthCheckScheduler {
 
         log::info!("📋 共有 {} 个配置需要检查", all_configs.len());
 
+        // 本轮所有探测共享一个客户端，复用连接减少 TLS 握手
+        let client = Self::build_client().map_err(|e| AppError::ServiceError {
+            message: format!("创建健康检查 HTTP 客户端失败: {}", e),
+        })?;
+        let results = Self::probe_configs(
+            &client,
+            &all_configs,
+            TokioDuration::from_secs(Self::HEALTH_CHECK_TIMEOUT_SECS + 2),
+        )
+        .await;
+
         // 记录状态变化的配置
         let mut recovered_configs: Vec<(i64, i64)> = Vec::new(); // (config_id, group_id)
         let mut success_count = 0;
         let mut failed_count = 0;
 
-        for (index, config) in all_configs.iter().enumerate() {
-            log::info!("────────────────────────────────────────────────────────────────");
-            log::info!("📌 正在检查配置 [{}/{}]: {} (ID: {})", index + 1, all_configs.len(), config.name, config.id);
-
-            // 对每个配置执行健康检查
-            let result = Self::check_single_config(&config.server_url, &config.api_key).await;
+        for (config, result) in all_configs.iter().zip(results) {
             let was_available = config.is_available;
 
             // 判断是否成功
@@ -269,7 +287,7 @@ impl HealthCheckScheduler {
                     status: HealthCheckStatus::Success,
                     latency_ms: Some(*latency_ms),
                     error_message: None,
-                    http_status_code: Some(*http_status),
+                    http_status_code: *http_status,
                 },
                 Err((status, error_msg, http_status)) => CreateHealthCheckRecordInput {
                     config_id: config.id,
@@ -288,6 +306,15 @@ impl HealthCheckScheduler {
                 log::debug!("💾 健康检查记录已保存 (config_id={})", config.id);
             }
 
+            // 刷新最后健康时间，避免空闲的备用配置权重随时间衰减
+            if is_success {
+                if let Err(e) = db_pool.with_connection(|conn| {
+                    ApiConfigService::record_healthy(conn, config.id)
+                }) {
+                    log::error!("更新最后健康时间失败 (config_id={}): {}", config.id, e);
+                }
+            }
+
             // 更新配置的可用状态
             let new_is_available = is_success;
             if new_is_available != was_available {
@@ -373,6 +400,7 @@ impl HealthCheckScheduler {
                             let mut stmt = conn.prepare(
                                 "SELECT id, name, weight_score FROM ApiConfig
                                  WHERE group_id = ?1 AND is_enabled = 1 AND is_available = 1
+                                   AND (disabled_until IS NULL OR julianday(disabled_until) <= julianday('now'))
                                  ORDER BY weight_score DESC, sort_order ASC"
                             ).map_err(|e| AppError::DatabaseError {
                                 message: format!("准备查询失败: {}", e),
@@ -470,32 +498,116 @@ impl HealthCheckScheduler {
     /// 健康检查超时时间（秒）- 比 API 测试短，用于快速检测服务可用性
     const HEALTH_CHECK_TIMEOUT_SECS: u64 = 10;
 
+    /// 健康检查连接超时时间（秒）
+    const HEALTH_CHECK_CONNECT_TIMEOUT_SECS: u64 = 5;
+
+    /// 创建健康检查共享的 HTTP 客户端（带连接池与超时）
+    fn build_client() -> reqwest::Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .connect_timeout(std::time::Duration::from_secs(Self::HEALTH_CHECK_CONNECT_TIMEOUT_SECS))
+            .timeout(std::time::Duration::from_secs(Self::HEALTH_CHECK_TIMEOUT_SECS))
+            .pool_max_idle_per_host(MAX_CONCURRENT_HEALTH_CHECKS)
+            .pool_idle_timeout(std::time::Duration::from_secs(60))
+            .build()
+    }
+
+    /// 并发探测所有配置，结果顺序与 `configs` 一致
+    ///
+    /// 每个探测额外包一层 `probe_timeout`，单个探测挂起不会拖住整轮检查
+    async fn probe_configs(
+        client: &reqwest::Client,
+        configs: &[ApiConfig],
+        probe_timeout: TokioDuration,
+    ) -> Vec<ProbeResult> {
+        let total = configs.len();
+        let targets: Vec<(usize, i64, String, String, String, HealthCheckMode)> = configs
+            .iter()
+            .enumerate()
+            .map(|(index, c)| {
+                (index, c.id, c.name.clone(), c.server_url.clone(), c.api_key.clone(), c.effective_health_check_mode())
+            })
+            .collect();
+
+        stream::iter(targets)
+            .map(|(index, id, name, server_url, api_key, mode)| {
+                let client = client.clone();
+                async move {
+                    log::info!(
+                        "📌 正在检查配置 [{}/{}]: {} (ID: {}, 方式: {}{})",
+                        index + 1,
+                        total,
+                        name,
+                        id,
+                        mode.as_str(),
+                        if mode.is_billable() { ", 计费" } else { "" }
+                    );
+                    match tokio::time::timeout(
+                        probe_timeout,
+                        Self::check_single_config(&client, &server_url, &api_key, mode),
+                    )
+                    .await
+                    {
+                        Ok(result) => result,
+                        Err(_) => {
+                            log::error!("⏰ 配置 {} (ID: {}) 健康检查超时", name, id);
+                            Err((
+                                HealthCheckStatus::Timeout,
+                                format!("请求超时: 超过 {}ms", probe_timeout.as_millis()),
+                                None,
+                            ))
+                        }
+                    }
+                }
+            })
+            .buffered(MAX_CONCURRENT_HEALTH_CHECKS)
+            .collect()
+            .await
+    }
+
+    /// 仅检查连通性 (TCP + TLS + HEAD)，不携带密钥，不产生计费请求
+    async fn check_reachability(server_url: &str) -> ProbeResult {
+        log::info!("🔗 连通性探测: {}", server_url);
+        match LatencyTestService::measure_timing(server_url, Some(Self::HEALTH_CHECK_TIMEOUT_SECS * 1000)).await {
+            Ok(timing) => {
+                let latency_ms = timing.total_ms() as i64;
+                log::info!("✅ 服务可达 - 延迟: {}ms", latency_ms);
+                Ok((latency_ms, None))
+            }
+            Err(e) if e.contains("超时") => {
+                log::error!("⏰ 连通性探测超时: {}", e);
+                Err((HealthCheckStatus::Timeout, e, None))
+            }
+            Err(e) => {
+                log::error!("❌ 连通性探测失败: {}", e);
+                Err((HealthCheckStatus::Failed, e, None))
+            }
+        }
+    }
+
     /// 检查单个配置的健康状态
-    /// 使用 /v1/health 端点进行轻量级健康检查
+    /// 默认使用 /v1/health 端点进行轻量级健康检查，也可按配置改用其他探测方式
     async fn check_single_config(
+        client: &reqwest::Client,
         server_url: &str,
         api_key: &str,
-    ) -> Result<(i64, i32), (HealthCheckStatus, String, Option<i32>)> {
+        mode: HealthCheckMode,
+    ) -> ProbeResult {
+        if mode == HealthCheckMode::Reachability {
+            return Self::check_reachability(server_url).await;
+        }
+
         log::info!("┌──────────────────────────────────────────────────────────────┐");
         log::info!("│           🏥 健康检查开始                                      │");
         log::info!("└──────────────────────────────────────────────────────────────┘");
         log::info!("🔗 服务器地址: {}", server_url);
         log::info!("🔑 API Key: {}...{}", &api_key[..8.min(api_key.len())], &api_key[api_key.len().saturating_sub(4)..]);
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(Self::HEALTH_CHECK_TIMEOUT_SECS))
-            .build()
-            .map_err(|e| {
-                log::error!("❌ 创建 HTTP 客户端失败: {}", e);
-                (
-                    HealthCheckStatus::Failed,
-                    format!("创建HTTP客户端失败: {}", e),
-                    None,
-                )
-            })?;
-
-        // 使用 /v1/health 端点进行轻量级健康检查
-        let url = format!("{}/v1/health", server_url.trim_end_matches('/'));
+        let base_url = server_url.trim_end_matches('/');
+        let url = match mode {
+            HealthCheckMode::Full => format!("{}/v1/messages", base_url),
+            HealthCheckMode::Models => format!("{}/v1/models", base_url),
+            _ => format!("{}/v1/health", base_url),
+        };
 
         log::info!("📤 健康检查端点: {}", url);
         log::info!("⏱️  超时配置: {}s", Self::HEALTH_CHECK_TIMEOUT_SECS);
@@ -503,13 +615,17 @@ impl HealthCheckScheduler {
 
         let start_time = std::time::Instant::now();
 
-        // 发送 GET 请求到 /v1/health 端点，携带 API Key 用于认证
-        let response = client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", api_key))
-            .header("x-api-key", api_key)
-            .send()
-            .await;
+        // full 模式发送与 Claude Code 相同的测试请求，其他模式发送携带 API Key 的 GET 请求
+        let request = if mode == HealthCheckMode::Full {
+            add_claude_code_headers(client.post(&url), api_key).json(&build_test_request_body())
+        } else {
+            client
+                .get(&url)
+                .header("Authorization", format!("Bearer {}", api_key))
+                .header("x-api-key", api_key)
+                .header("anthropic-version", "2023-06-01")
+        };
+        let response = request.send().await;
 
         let latency_ms = start_time.elapsed().as_millis() as i64;
 
@@ -520,7 +636,21 @@ impl HealthCheckScheduler {
                 log::info!("📥 HTTP 状态码: {}", status_code);
 
                 // 2xx 状态码表示服务健康
-                if resp.status().is_success() {
+                if mode == HealthCheckMode::Full
+                    && !resp.status().is_success()
+                    && status_code != 429
+                {
+                    // full 模式要求测试请求真正成功（认证失败等同样视为不健康）
+                    let error_body = resp.text().await.unwrap_or_default();
+                    log::error!("❌ 测试请求失败 - 状态码: {}, 延迟: {}ms", status_code, latency_ms);
+                    log::warn!("响应体: {}", error_body);
+                    log::info!("└──────────────────────────────────────────────────────────────┘");
+                    Err((
+                        HealthCheckStatus::Failed,
+                        format!("测试请求失败: HTTP {}", status_code),
+                        Some(status_code),
+                    ))
+                } else if resp.status().is_success() {
                     let body = resp.text().await.unwrap_or_default();
                     log::info!("📥 响应体大小: {} 字节", body.len());
                     log::debug!("响应体内容: {}", if body.len() > 200 { format!("{}...(截断)", &body[..200]) } else { body.clone() });
@@ -531,7 +661,7 @@ impl HealthCheckScheduler {
                         latency_ms
                     );
                     log::info!("└──────────────────────────────────────────────────────────────┘");
-                    Ok((latency_ms, status_code))
+                    Ok((latency_ms, Some(status_code)))
                 } else if status_code == 404 {
                     // 404 表示端点不存在，但服务可达，视为健康
                     log::info!(
@@ -539,7 +669,7 @@ impl HealthCheckScheduler {
                         latency_ms
                     );
                     log::info!("└──────────────────────────────────────────────────────────────┘");
-                    Ok((latency_ms, status_code))
+                    Ok((latency_ms, Some(status_code)))
                 } else if status_code == 401 || status_code == 403 {
                     // 认证失败，但服务可达，视为健康（健康检查不关心认证）
                     log::info!(
@@ -547,7 +677,7 @@ impl HealthCheckScheduler {
                         latency_ms
                     );
                     log::info!("└──────────────────────────────────────────────────────────────┘");
-                    Ok((latency_ms, status_code))
+                    Ok((latency_ms, Some(status_code)))
                 } else if status_code == 429 {
                     // 限流，但服务可达
                     log::info!(
@@ -555,7 +685,7 @@ impl HealthCheckScheduler {
                         latency_ms
                     );
                     log::info!("└──────────────────────────────────────────────────────────────┘");
-                    Ok((latency_ms, status_code))
+                    Ok((latency_ms, Some(status_code)))
                 } else if status_code >= 500 {
                     // 5xx 服务器错误，视为不健康
                     let error_body = resp.text().await.unwrap_or_default();
@@ -579,7 +709,7 @@ impl HealthCheckScheduler {
                         latency_ms
                     );
                     log::info!("└──────────────────────────────────────────────────────────────┘");
-                    Ok((latency_ms, status_code))
+                    Ok((latency_ms, Some(status_code)))
                 }
             }
             Err(e) => {
@@ -623,3 +753,98 @@ impl Drop for HealthCheckScheduler {
         log::debug!("健康检查调度器正在被销毁");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn config(id: i64, server_url: String) -> ApiConfig {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": format!("config-{}", id),
+            "api_key": "sk-test-key-123456",
+            "server_url": server_url,
+            "server_port": 443,
+            "sort_order": 0,
+            "is_available": true,
+            "auto_balance_check": false,
+            "created_at": "",
+            "updated_at": ""
+        }))
+        .unwrap()
+    }
+
+    /// 单个挂起的端点不应拖慢其他配置的检查
+    #[tokio::test]
+    async fn test_probe_configs_isolates_hung_endpoint() {
+        let hung = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let hung_url = format!("http://{}", hung.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = hung.accept().await {
+                held.push(stream);
+            }
+        });
+
+        let healthy = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let healthy_url = format!("http://{}", healthy.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = healthy.accept().await {
+                let _ = stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok")
+                    .await;
+            }
+        });
+
+        let configs = vec![config(1, hung_url), config(2, healthy_url.clone()), config(3, healthy_url)];
+        let client = HealthCheckScheduler::build_client().unwrap();
+
+        let start = std::time::Instant::now();
+        let results =
+            HealthCheckScheduler::probe_configs(&client, &configs, TokioDuration::from_millis(500)).await;
+
+        assert!(start.elapsed() < TokioDuration::from_secs(2));
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[0], Err((HealthCheckStatus::Timeout, _, None))));
+        assert_eq!(results[1].as_ref().unwrap().1, Some(200));
+        assert_eq!(results[2].as_ref().unwrap().1, Some(200));
+    }
+
+    /// 各探测方式请求不同的端点，full 模式下认证失败视为不健康
+    #[tokio::test]
+    async fn test_probe_modes() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                let response: &[u8] = if request.starts_with("GET /v1/models ") || request.starts_with("HEAD ") {
+                    b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok"
+                } else {
+                    b"HTTP/1.1 401 Unauthorized\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                };
+                let _ = stream.write_all(response).await;
+            }
+        });
+
+        let mut configs = vec![config(1, url.clone()), config(2, url.clone()), config(3, url.clone()), config(4, url)];
+        configs[0].health_check_mode = HealthCheckMode::Models;
+        configs[1].health_check_mode = HealthCheckMode::Reachability;
+        configs[2].health_check_mode = HealthCheckMode::Full;
+        // 透传客户端密钥的配置只做连通性探测
+        configs[3].health_check_mode = HealthCheckMode::Full;
+        configs[3].api_key_mode = crate::models::api_config::ApiKeyMode::Client;
+        let client = HealthCheckScheduler::build_client().unwrap();
+
+        let results = HealthCheckScheduler::probe_configs(&client, &configs, TokioDuration::from_secs(5)).await;
+
+        assert_eq!(results[0].as_ref().unwrap().1, Some(200));
+        assert_eq!(results[1].as_ref().unwrap().1, None);
+        assert!(matches!(results[2], Err((HealthCheckStatus::Failed, _, Some(401)))));
+        assert_eq!(results[3].as_ref().unwrap().1, None);
+    }
+}
@@ -6,9 +6,10 @@
 use crate::db::DbPool;
 use crate::models::error::{AppError, AppResult};
 use crate::models::health_check::{
-    ConfigHealthSummary, CreateHealthCheckRecordInput, HealthCheckHourlyStats, HealthCheckRecord,
-    HealthCheckStatus,
+    ConfigHealthSummary, CreateHealthCheckRecordInput, HealthCheckHourlyStats, HealthCheckMode,
+    HealthCheckRecord, HealthCheckStatus,
 };
+use crate::utils::time::{now_rfc3339, rfc3339_ago};
 use rusqlite::Connection;
 use std::sync::Arc;
 
@@ -25,11 +26,12 @@ impl HealthCheckService {
         conn.execute(
             r#"
             INSERT INTO HealthCheckRecord (
-                config_id, status, latency_ms, error_message, http_status_code
-            ) VALUES (?1, ?2, ?3, ?4, ?5)
+                config_id, check_at, status, latency_ms, error_message, http_status_code
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
             "#,
             rusqlite::params![
                 input.config_id,
+                now_rfc3339(),
                 input.status.as_str(),
                 input.latency_ms,
                 input.error_message,
@@ -48,9 +50,9 @@ impl HealthCheckService {
                 r#"
                 DELETE FROM HealthCheckRecord
                 WHERE config_id = ?1
-                  AND check_at < datetime('now', 'localtime', '-24 hours')
+                  AND check_at < ?2
                 "#,
-                rusqlite::params![input.config_id],
+                rusqlite::params![input.config_id, rfc3339_ago(chrono::Duration::hours(24))],
             )
             .unwrap_or(0);
 
@@ -199,13 +201,13 @@ impl HealthCheckService {
         pool.with_connection(|conn| {
             // 获取所有配置
             let mut stmt = conn
-                .prepare("SELECT id, name FROM ApiConfig ORDER BY sort_order")
+                .prepare("SELECT id, name, health_check_mode FROM ApiConfig ORDER BY sort_order")
                 .map_err(|e| AppError::DatabaseError {
                     message: format!("准备查询失败: {}", e),
                 })?;
 
-            let configs: Vec<(i64, String)> = stmt
-                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            let configs: Vec<(i64, String, String)> = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
                 .map_err(|e| AppError::DatabaseError {
                     message: format!("查询配置失败: {}", e),
                 })?
@@ -216,7 +218,9 @@ impl HealthCheckService {
 
             let mut summaries = Vec::new();
 
-            for (config_id, config_name) in configs {
+            for (config_id, config_name, probe_mode) in configs {
+                let probe_mode: HealthCheckMode = probe_mode.parse().unwrap_or_default();
+
                 // 获取小时统计
                 let hourly_stats = Self::get_hourly_stats(conn, config_id, hours)?;
 
@@ -236,6 +240,8 @@ impl HealthCheckService {
                     last_check,
                     availability_24h,
                     avg_latency_24h,
+                    probe_mode,
+                    billable: probe_mode.is_billable(),
                 });
             }
 
@@ -284,9 +290,9 @@ impl HealthCheckService {
             .execute(
                 r#"
             DELETE FROM HealthCheckRecord
-            WHERE check_at < datetime('now', 'localtime', '-' || ?1 || ' days')
+            WHERE check_at < ?1
             "#,
-                [retain_days],
+                [rfc3339_ago(chrono::Duration::days(retain_days))],
             )
             .map_err(|e| AppError::DatabaseError {
                 message: format!("清理旧记录失败: {}", e),
@@ -8,9 +8,16 @@
  * - 支持批量测试
  * - 支持热身请求（避免首包惩罚）
  * - 统一的测试接口供所有模块使用
+ * - 连接阶段耗时分解（DNS / TCP / TLS / 首字节）
  */
 
+use crate::models::test_result::TimingBreakdown;
+use rustls::pki_types::ServerName;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
 
 /// 延迟测试结果
 #[derive(Debug, Clone)]
@@ -200,6 +207,131 @@ impl LatencyTestService {
         results
     }
 
+    /// 测量连接各阶段耗时
+    ///
+    /// 依次执行 DNS 解析、TCP 连接、TLS 握手（仅 HTTPS），再发送一个 HEAD 请求
+    /// 测量首字节时间，用于区分延迟来自 DNS、网络还是后端
+    ///
+    /// # 参数
+    /// - `url`: 要测试的 URL
+    /// - `timeout_ms`: 整体超时时间（毫秒），默认 8000ms
+    pub async fn measure_timing(url: &str, timeout_ms: Option<u64>) -> Result<TimingBreakdown, String> {
+        let timeout = timeout_ms.unwrap_or(8000);
+
+        tokio::time::timeout(Duration::from_millis(timeout), Self::measure_timing_phases(url))
+            .await
+            .map_err(|_| format!("连接超时（>{}ms）", timeout))?
+    }
+
+    async fn measure_timing_phases(url: &str) -> Result<TimingBreakdown, String> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| format!("URL 无效: {}", e))?;
+        let host = parsed.host_str().ok_or("URL 缺少主机名")?.to_string();
+        let port = parsed.port_or_known_default().ok_or("URL 缺少端口")?;
+        let is_https = parsed.scheme() == "https";
+
+        // DNS 解析
+        let dns_start = Instant::now();
+        let addr = tokio::net::lookup_host((host.as_str(), port))
+            .await
+            .map_err(|e| format!("DNS解析失败：{}", e))?
+            .next()
+            .ok_or_else(|| format!("DNS解析失败：{} 无可用地址", host))?;
+        let dns_ms = dns_start.elapsed().as_millis() as i32;
+
+        // TCP 连接
+        let connect_start = Instant::now();
+        let tcp_stream = TcpStream::connect(addr)
+            .await
+            .map_err(|e| format!("连接失败：{}", e))?;
+        let connect_ms = connect_start.elapsed().as_millis() as i32;
+
+        let host_header = match parsed.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.clone(),
+        };
+        let path = match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        };
+        let request = format!(
+            "HEAD {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: claude-code-proxy-latency/1.0\r\nAccept: */*\r\nConnection: close\r\n\r\n",
+            path, host_header
+        );
+
+        let (tls_ms, ttfb_ms) = if is_https {
+            // TLS 握手
+            let tls_start = Instant::now();
+            let server_name = ServerName::try_from(host.clone())
+                .map_err(|e| format!("TLS 主机名无效: {}", e))?;
+            let mut tls_stream = Self::tls_connector()?
+                .connect(server_name, tcp_stream)
+                .await
+                .map_err(|e| format!("TLS 握手失败：{}", e))?;
+            let tls_ms = tls_start.elapsed().as_millis() as i32;
+
+            (Some(tls_ms), Self::time_first_byte(&mut tls_stream, request.as_bytes()).await?)
+        } else {
+            let mut tcp_stream = tcp_stream;
+            (None, Self::time_first_byte(&mut tcp_stream, request.as_bytes()).await?)
+        };
+
+        log::debug!(
+            "连接耗时分解: {} - DNS {}ms, TCP {}ms, TLS {:?}ms, TTFB {}ms",
+            url,
+            dns_ms,
+            connect_ms,
+            tls_ms,
+            ttfb_ms
+        );
+
+        Ok(TimingBreakdown {
+            dns_ms: Some(dns_ms),
+            connect_ms: Some(connect_ms),
+            tls_ms,
+            ttfb_ms: Some(ttfb_ms),
+            measured_at: None,
+        })
+    }
+
+    /// 发送请求并测量收到首字节的耗时
+    async fn time_first_byte<S>(stream: &mut S, request: &[u8]) -> Result<i32, String>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let start = Instant::now();
+        stream
+            .write_all(request)
+            .await
+            .map_err(|e| format!("发送请求失败：{}", e))?;
+
+        let mut first_byte = [0u8; 1];
+        let read = stream
+            .read(&mut first_byte)
+            .await
+            .map_err(|e| format!("读取响应失败：{}", e))?;
+        if read == 0 {
+            return Err("连接被服务器关闭，未收到响应".to_string());
+        }
+
+        Ok(start.elapsed().as_millis() as i32)
+    }
+
+    /// 创建 TLS 连接器（与代理转发使用相同的 ring 加密提供者与根证书）
+    fn tls_connector() -> Result<TlsConnector, String> {
+        let mut root_store = rustls::RootCertStore::empty();
+        root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+        let tls_config = rustls::ClientConfig::builder_with_provider(
+            rustls::crypto::ring::default_provider().into(),
+        )
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("TLS 配置失败: {}", e))?
+        .with_root_certificates(root_store)
+        .with_no_client_auth();
+
+        Ok(TlsConnector::from(Arc::new(tls_config)))
+    }
+
     /// 错误分类（参考 latency-tester.js）
     ///
     /// 提供详细的错误分类和提示信息
@@ -256,4 +388,30 @@ mod tests {
         let results = LatencyTestService::test_multiple_urls(&urls, Some(3000), false).await;
         assert_eq!(results.len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_measure_timing_http() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let _ = socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").await;
+        });
+
+        let timing = LatencyTestService::measure_timing(&format!("http://{}/v1", addr), Some(3000))
+            .await
+            .unwrap();
+
+        assert!(timing.dns_ms.is_some());
+        assert!(timing.connect_ms.is_some());
+        assert!(timing.tls_ms.is_none());
+        assert!(timing.ttfb_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_measure_timing_invalid_url() {
+        assert!(LatencyTestService::measure_timing("not a url", Some(1000)).await.is_err());
+    }
 }
@@ -0,0 +1,348 @@
+/**
+ * Load Test Service
+ * 通过本地代理并发发送合成请求，测量吞吐量、错误率与延迟百分位
+ *
+ * 请求经由真实的代理监听端口进入完整的转发流程 (会话路由、请求改写、连接池、重试与切换)；
+ * 使用临时会话 `/session/<id>` 固定到指定配置，不影响全局路由，测试结束后移除该会话。
+ * 每个请求是 `max_tokens: 1` 的最小 Messages 请求，会产生少量真实的上游调用费用。
+ */
+
+use crate::models::error::{AppError, AppResult};
+use crate::services::session_config::SESSION_CONFIG_MAP;
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// 最大并发数
+pub const MAX_LOAD_TEST_CONCURRENCY: usize = 64;
+
+/// 单次测试最大请求数
+pub const MAX_LOAD_TEST_REQUESTS: usize = 2000;
+
+/// 合成请求使用的模型 (经配置的模型映射改写)
+const LOAD_TEST_MODEL: &str = "claude-haiku-4-5-20251001";
+
+/// 单个请求超时
+const LOAD_TEST_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 进度推送的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 报告中保留的不同错误信息条数
+const MAX_ERROR_SAMPLES: usize = 5;
+
+/// 压测进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadTestProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// 压测报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadTestReport {
+    pub config_id: i64,
+    pub concurrency: usize,
+    pub total_requests: usize,
+    /// 返回 2xx 的请求数
+    pub succeeded: usize,
+    pub failed: usize,
+    /// 失败占比 (0.0 - 1.0)
+    pub error_rate: f64,
+    /// 总耗时 (毫秒)
+    pub duration_ms: u64,
+    /// 每秒完成的请求数
+    pub throughput_rps: f64,
+    /// 延迟百分位 (毫秒，包含失败请求)
+    pub latency_min_ms: Option<u64>,
+    pub latency_p50_ms: Option<u64>,
+    pub latency_p90_ms: Option<u64>,
+    pub latency_p99_ms: Option<u64>,
+    pub latency_max_ms: Option<u64>,
+    /// 按 HTTP 状态码统计，连接失败记为 "error"
+    pub status_counts: BTreeMap<String, usize>,
+    /// 不同的错误信息 (最多 MAX_ERROR_SAMPLES 条)
+    pub error_samples: Vec<String>,
+}
+
+/// 单个请求的结果
+struct RequestOutcome {
+    latency_ms: u64,
+    /// HTTP 状态码，连接失败时为 None
+    status: Option<u16>,
+    /// 连接失败或非 2xx 响应的错误信息
+    error: Option<String>,
+}
+
+impl RequestOutcome {
+    fn is_success(&self) -> bool {
+        self.status.is_some_and(|status| (200..300).contains(&status))
+    }
+}
+
+/// 校验压测参数
+pub fn validate_params(concurrency: usize, total_requests: usize) -> AppResult<()> {
+    if concurrency == 0 || concurrency > MAX_LOAD_TEST_CONCURRENCY {
+        return Err(AppError::ValidationError {
+            field: "concurrency".to_string(),
+            message: format!("并发数必须在 1-{} 之间", MAX_LOAD_TEST_CONCURRENCY),
+        });
+    }
+    if total_requests == 0 || total_requests > MAX_LOAD_TEST_REQUESTS {
+        return Err(AppError::ValidationError {
+            field: "total_requests".to_string(),
+            message: format!("请求总数必须在 1-{} 之间", MAX_LOAD_TEST_REQUESTS),
+        });
+    }
+    Ok(())
+}
+
+/// 压测期间注册的临时会话，离开作用域 (包括压测被取消) 时从 SESSION_CONFIG_MAP 注销
+struct LoadTestSession(String);
+
+impl LoadTestSession {
+    fn register(config_id: i64) -> Self {
+        let session_id = format!("loadtest-{}", uuid::Uuid::new_v4().simple());
+        SESSION_CONFIG_MAP.register(session_id.clone(), config_id, Some("load test".to_string()));
+        Self(session_id)
+    }
+}
+
+impl Drop for LoadTestSession {
+    fn drop(&mut self) {
+        SESSION_CONFIG_MAP.remove(&self.0);
+    }
+}
+
+/// 通过代理对指定配置执行压测
+///
+/// # 参数
+/// - `proxy_base_url`: 代理监听地址 (如 `http://127.0.0.1:25341`)
+/// - `config_id`: 请求固定路由到的配置
+/// - `concurrency`: 同时进行的请求数
+/// - `total_requests`: 请求总数
+/// - `on_progress`: 进度回调 (节流，最后一次必定推送)
+pub async fn run_load_test(
+    proxy_base_url: &str,
+    config_id: i64,
+    concurrency: usize,
+    total_requests: usize,
+    on_progress: impl Fn(LoadTestProgress),
+) -> AppResult<LoadTestReport> {
+    validate_params(concurrency, total_requests)?;
+
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(LOAD_TEST_REQUEST_TIMEOUT)
+        .pool_max_idle_per_host(concurrency)
+        .build()
+        .map_err(|e| AppError::ServiceError {
+            message: format!("创建 HTTP 客户端失败: {}", e),
+        })?;
+
+    let session = LoadTestSession::register(config_id);
+    let url = format!("{}/session/{}/v1/messages", proxy_base_url.trim_end_matches('/'), session.0);
+    let body = serde_json::json!({
+        "model": LOAD_TEST_MODEL,
+        "max_tokens": 1,
+        "messages": [{"role": "user", "content": "ping"}],
+    });
+
+    log::info!(
+        "Starting load test: config_id={}, concurrency={}, total_requests={}",
+        config_id,
+        concurrency,
+        total_requests
+    );
+
+    let started = Instant::now();
+    let mut requests = stream::iter(0..total_requests)
+        .map(|_| send_request(&client, &url, &body))
+        .buffer_unordered(concurrency);
+
+    let mut outcomes = Vec::with_capacity(total_requests);
+    let mut succeeded = 0;
+    let mut last_progress: Option<Instant> = None;
+    while let Some(outcome) = requests.next().await {
+        if outcome.is_success() {
+            succeeded += 1;
+        }
+        outcomes.push(outcome);
+
+        let done = outcomes.len() == total_requests;
+        if done || last_progress.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL) {
+            last_progress = Some(Instant::now());
+            on_progress(LoadTestProgress {
+                completed: outcomes.len(),
+                total: total_requests,
+                succeeded,
+                failed: outcomes.len() - succeeded,
+            });
+        }
+    }
+    let duration = started.elapsed();
+    drop(session);
+
+    let report = build_report(config_id, concurrency, &outcomes, duration);
+    log::info!(
+        "Load test finished: {}/{} succeeded, {:.1} req/s, p50={:?}ms p99={:?}ms",
+        report.succeeded,
+        report.total_requests,
+        report.throughput_rps,
+        report.latency_p50_ms,
+        report.latency_p99_ms
+    );
+    Ok(report)
+}
+
+async fn send_request(client: &reqwest::Client, url: &str, body: &serde_json::Value) -> RequestOutcome {
+    let started = Instant::now();
+    let (status, error) = match client
+        .post(url)
+        .header("anthropic-version", "2023-06-01")
+        .json(body)
+        .send()
+        .await
+    {
+        Ok(response) => {
+            let status = response.status();
+            // 读完响应体，计入完整延迟并让连接回到连接池
+            match response.bytes().await {
+                Ok(_) if status.is_success() => (Some(status.as_u16()), None),
+                Ok(bytes) => {
+                    let excerpt: String = String::from_utf8_lossy(&bytes).chars().take(200).collect();
+                    (Some(status.as_u16()), Some(format!("HTTP {}: {}", status.as_u16(), excerpt)))
+                }
+                Err(e) => (Some(status.as_u16()), Some(format!("读取响应失败: {}", e))),
+            }
+        }
+        Err(e) => (None, Some(e.to_string())),
+    };
+    RequestOutcome {
+        latency_ms: started.elapsed().as_millis() as u64,
+        status,
+        error,
+    }
+}
+
+fn build_report(config_id: i64, concurrency: usize, outcomes: &[RequestOutcome], duration: Duration) -> LoadTestReport {
+    let mut latencies: Vec<u64> = outcomes.iter().map(|o| o.latency_ms).collect();
+    latencies.sort_unstable();
+
+    let mut status_counts = BTreeMap::new();
+    let mut error_samples: Vec<String> = Vec::new();
+    let mut succeeded = 0;
+    for outcome in outcomes {
+        let key = outcome.status.map_or_else(|| "error".to_string(), |status| status.to_string());
+        *status_counts.entry(key).or_insert(0) += 1;
+
+        if outcome.is_success() {
+            succeeded += 1;
+        } else if let Some(error) = &outcome.error {
+            if error_samples.len() < MAX_ERROR_SAMPLES && !error_samples.contains(error) {
+                error_samples.push(error.clone());
+            }
+        }
+    }
+
+    let total = outcomes.len();
+    let secs = duration.as_secs_f64();
+    LoadTestReport {
+        config_id,
+        concurrency,
+        total_requests: total,
+        succeeded,
+        failed: total - succeeded,
+        error_rate: if total > 0 { (total - succeeded) as f64 / total as f64 } else { 0.0 },
+        duration_ms: duration.as_millis() as u64,
+        throughput_rps: if secs > 0.0 { total as f64 / secs } else { 0.0 },
+        latency_min_ms: latencies.first().copied(),
+        latency_p50_ms: percentile(&latencies, 0.50),
+        latency_p90_ms: percentile(&latencies, 0.90),
+        latency_p99_ms: percentile(&latencies, 0.99),
+        latency_max_ms: latencies.last().copied(),
+        status_counts,
+        error_samples,
+    }
+}
+
+/// 最近秩法计算百分位（输入需已排序）
+fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 本地假代理：每隔 `fail_every` 个请求返回一次 529
+    async fn serve_fake_proxy(fail_every: usize) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let counter = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let counter = counter.clone();
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let _ = stream.read(&mut buf).await;
+                    let n = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                    let (status, body) = if n % fail_every == 0 {
+                        ("529 Overloaded", r#"{"type":"error","error":{"type":"overloaded_error"}}"#)
+                    } else {
+                        ("200 OK", r#"{"type":"message","content":[]}"#)
+                    };
+                    let response = format!(
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_run_load_test_reports_errors_and_latency() {
+        let url = serve_fake_proxy(4).await;
+        let progress = std::sync::Mutex::new(Vec::new());
+        let report = run_load_test(&url, 7, 4, 20, |p| progress.lock().unwrap().push(p.completed))
+            .await
+            .unwrap();
+
+        assert_eq!(report.total_requests, 20);
+        assert_eq!((report.succeeded, report.failed), (15, 5));
+        assert!((report.error_rate - 0.25).abs() < f64::EPSILON);
+        assert_eq!(report.status_counts.get("200"), Some(&15));
+        assert_eq!(report.status_counts.get("529"), Some(&5));
+        assert_eq!(report.error_samples.len(), 1);
+        assert!(report.latency_p50_ms <= report.latency_p99_ms);
+        assert_eq!(progress.into_inner().unwrap().last(), Some(&20));
+        // 临时会话在测试结束后移除
+        assert!(!SESSION_CONFIG_MAP.list_sessions().iter().any(|(id, _)| id.starts_with("loadtest-")));
+    }
+
+    #[test]
+    fn test_validate_params_and_percentile() {
+        assert!(validate_params(1, 1).is_ok());
+        assert!(validate_params(0, 10).is_err());
+        assert!(validate_params(MAX_LOAD_TEST_CONCURRENCY + 1, 10).is_err());
+        assert!(validate_params(4, MAX_LOAD_TEST_REQUESTS + 1).is_err());
+
+        let sorted: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&sorted, 0.50), Some(50));
+        assert_eq!(percentile(&sorted, 0.99), Some(99));
+        assert_eq!(percentile(&[], 0.50), None);
+    }
+}
@@ -0,0 +1,86 @@
+/**
+ * Log Cleanup Scheduler
+ * 按保留策略定期清理请求日志
+ *
+ * Features:
+ * - 每轮读取最新的保留策略，修改清理间隔无需重启
+ * - 按批次删除，避免阻塞请求日志写入
+ * - 支持启动/停止调度器
+ */
+
+use crate::db::DbPool;
+use crate::models::error::AppResult;
+use crate::services::log_retention::{LogRetentionPolicy, LogRetentionService};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+
+/// 请求日志清理调度器
+pub struct LogCleanupScheduler {
+    db_pool: Arc<DbPool>,
+    task_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
+}
+
+impl LogCleanupScheduler {
+    /// 创建新的日志清理调度器
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        Self {
+            db_pool,
+            task_handle: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// 启动调度器
+    pub async fn start(&self) -> AppResult<()> {
+        let mut task_handle = self.task_handle.write().await;
+
+        if task_handle.is_some() {
+            log::warn!("日志清理调度器已在运行");
+            return Ok(());
+        }
+
+        let db_pool = self.db_pool.clone();
+
+        let handle = tokio::spawn(async move {
+            log::info!("日志清理调度器后台任务已启动");
+
+            loop {
+                let policy = db_pool
+                    .with_connection(LogRetentionService::get_policy)
+                    .unwrap_or_else(|e| {
+                        log::warn!("读取日志保留策略失败，使用默认策略: {}", e);
+                        LogRetentionPolicy::default()
+                    });
+
+                sleep(Duration::from_secs(policy.cleanup_interval_minutes.max(1) as u64 * 60)).await;
+
+                let pool = db_pool.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    LogRetentionService::run_cleanup(&pool, &policy)
+                })
+                .await;
+
+                match result {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => log::error!("定期清理日志失败: {}", e),
+                    Err(e) => log::error!("日志清理任务异常退出: {}", e),
+                }
+            }
+        });
+
+        *task_handle = Some(handle);
+
+        log::info!("日志清理调度器已启动");
+        Ok(())
+    }
+
+    /// 停止调度器
+    #[allow(dead_code)]
+    pub async fn stop(&self) {
+        if let Some(handle) = self.task_handle.write().await.take() {
+            handle.abort();
+            log::info!("日志清理调度器已停止");
+        }
+    }
+}
@@ -0,0 +1,415 @@
+/**
+ * Log Retention Service
+ * 请求日志保留策略：按条数和/或天数清理代理请求日志
+ *
+ * 删除按批次执行 (每批 LIMIT 条)，每批之间释放数据库连接，
+ * 避免长时间持锁阻塞正在进行的请求日志写入。
+ * 同时清理所属配置已删除的 TestResult / HealthCheckRecord 孤儿记录，
+ * 以及所属日志已删除的 ProxyRequestBody 完整请求体/响应体。
+ * 代理指标快照 (MetricsSnapshot) 按相同的条数/天数限制清理。
+ */
+
+use crate::db::DbPool;
+use crate::models::error::{AppError, AppResult};
+use crate::utils::time::rfc3339_ago;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// 每批删除的最大行数
+const CLEANUP_BATCH_SIZE: i64 = 500;
+
+/// 清理间隔下限（分钟）
+const MIN_CLEANUP_INTERVAL_MINUTES: i32 = 1;
+
+/// 日志保留策略
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogRetentionPolicy {
+    /// 最多保留的日志条数，None 表示不限制
+    pub max_count: Option<i64>,
+    /// 日志最长保留天数，None 表示不限制
+    pub max_age_days: Option<i64>,
+    /// 后台清理间隔（分钟）
+    pub cleanup_interval_minutes: i32,
+}
+
+impl Default for LogRetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_count: Some(10000),
+            max_age_days: None,
+            cleanup_interval_minutes: 60,
+        }
+    }
+}
+
+impl LogRetentionPolicy {
+    /// 验证策略参数
+    pub fn validate(&self) -> AppResult<()> {
+        if matches!(self.max_count, Some(count) if count < 0) {
+            return Err(AppError::ValidationError {
+                field: "max_count".to_string(),
+                message: "保留条数不能为负数".to_string(),
+            });
+        }
+
+        if matches!(self.max_age_days, Some(days) if days <= 0) {
+            return Err(AppError::ValidationError {
+                field: "max_age_days".to_string(),
+                message: "保留天数必须大于 0".to_string(),
+            });
+        }
+
+        if self.cleanup_interval_minutes < MIN_CLEANUP_INTERVAL_MINUTES {
+            return Err(AppError::ValidationError {
+                field: "cleanup_interval_minutes".to_string(),
+                message: format!("清理间隔不能小于 {} 分钟", MIN_CLEANUP_INTERVAL_MINUTES),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// 单次清理结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogCleanupResult {
+    /// 删除的请求日志条数
+    pub deleted_logs: i64,
+    /// 删除的孤儿测试结果条数
+    pub deleted_test_results: i64,
+    /// 删除的孤儿健康检查记录条数
+    pub deleted_health_checks: i64,
+    /// 删除的孤儿完整请求体/响应体条数
+    pub deleted_bodies: i64,
+    /// 删除的代理指标快照条数
+    pub deleted_metrics_snapshots: i64,
+}
+
+/// 日志保留服务
+pub struct LogRetentionService;
+
+impl LogRetentionService {
+    /// 读取日志保留策略
+    pub fn get_policy(conn: &Connection) -> AppResult<LogRetentionPolicy> {
+        conn.query_row(
+            "SELECT log_retention_max_count, log_retention_max_age_days, log_cleanup_interval_minutes
+             FROM AppSettings WHERE id = 1",
+            [],
+            |row| {
+                Ok(LogRetentionPolicy {
+                    max_count: row.get(0)?,
+                    max_age_days: row.get(1)?,
+                    cleanup_interval_minutes: row.get(2)?,
+                })
+            },
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("读取日志保留策略失败: {}", e),
+        })
+    }
+
+    /// 保存日志保留策略
+    pub fn set_policy(conn: &Connection, policy: &LogRetentionPolicy) -> AppResult<()> {
+        policy.validate()?;
+
+        let updated = conn
+            .execute(
+                "UPDATE AppSettings SET
+                    log_retention_max_count = ?1,
+                    log_retention_max_age_days = ?2,
+                    log_cleanup_interval_minutes = ?3,
+                    updated_at = CURRENT_TIMESTAMP
+                 WHERE id = 1",
+                params![policy.max_count, policy.max_age_days, policy.cleanup_interval_minutes],
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("保存日志保留策略失败: {}", e),
+            })?;
+
+        if updated == 0 {
+            return Err(AppError::NotFound {
+                resource: "AppSettings".to_string(),
+                id: "1".to_string(),
+            });
+        }
+
+        log::info!(
+            "日志保留策略已更新: 最多 {:?} 条, 最长 {:?} 天, 每 {} 分钟清理一次",
+            policy.max_count,
+            policy.max_age_days,
+            policy.cleanup_interval_minutes
+        );
+        Ok(())
+    }
+
+    /// 按策略执行一次清理
+    pub fn run_cleanup(pool: &DbPool, policy: &LogRetentionPolicy) -> AppResult<LogCleanupResult> {
+        let mut result = LogCleanupResult::default();
+
+        if let Some(days) = policy.max_age_days {
+            let cutoff = rfc3339_ago(chrono::Duration::days(days));
+            result.deleted_logs += Self::delete_in_batches(pool, |conn| {
+                conn.execute(
+                    "DELETE FROM ProxyRequestLog WHERE id IN (
+                        SELECT id FROM ProxyRequestLog WHERE request_at < ?1 LIMIT ?2
+                    )",
+                    params![cutoff, CLEANUP_BATCH_SIZE],
+                )
+            })?;
+            result.deleted_metrics_snapshots += Self::delete_in_batches(pool, |conn| {
+                conn.execute(
+                    "DELETE FROM MetricsSnapshot WHERE id IN (
+                        SELECT id FROM MetricsSnapshot WHERE captured_at < ?1 LIMIT ?2
+                    )",
+                    params![cutoff, CLEANUP_BATCH_SIZE],
+                )
+            })?;
+        }
+
+        if let Some(max_count) = policy.max_count {
+            result.deleted_logs += Self::delete_in_batches(pool, |conn| {
+                conn.execute(
+                    "DELETE FROM ProxyRequestLog WHERE id IN (
+                        SELECT id FROM ProxyRequestLog
+                        ORDER BY request_at DESC
+                        LIMIT ?1 OFFSET ?2
+                    )",
+                    params![CLEANUP_BATCH_SIZE, max_count],
+                )
+            })?;
+            result.deleted_metrics_snapshots += Self::delete_in_batches(pool, |conn| {
+                conn.execute(
+                    "DELETE FROM MetricsSnapshot WHERE id IN (
+                        SELECT id FROM MetricsSnapshot
+                        ORDER BY captured_at DESC
+                        LIMIT ?1 OFFSET ?2
+                    )",
+                    params![CLEANUP_BATCH_SIZE, max_count],
+                )
+            })?;
+        }
+
+        result.deleted_test_results = Self::delete_in_batches(pool, |conn| {
+            conn.execute(
+                "DELETE FROM TestResult WHERE id IN (
+                    SELECT id FROM TestResult
+                    WHERE config_id NOT IN (SELECT id FROM ApiConfig)
+                    LIMIT ?1
+                )",
+                params![CLEANUP_BATCH_SIZE],
+            )
+        })?;
+
+        result.deleted_health_checks = Self::delete_in_batches(pool, |conn| {
+            conn.execute(
+                "DELETE FROM HealthCheckRecord WHERE id IN (
+                    SELECT id FROM HealthCheckRecord
+                    WHERE config_id NOT IN (SELECT id FROM ApiConfig)
+                    LIMIT ?1
+                )",
+                params![CLEANUP_BATCH_SIZE],
+            )
+        })?;
+
+        result.deleted_bodies = Self::delete_in_batches(pool, |conn| {
+            conn.execute(
+                "DELETE FROM ProxyRequestBody WHERE rowid IN (
+                    SELECT rowid FROM ProxyRequestBody
+                    WHERE log_id NOT IN (SELECT id FROM ProxyRequestLog)
+                    LIMIT ?1
+                )",
+                params![CLEANUP_BATCH_SIZE],
+            )
+        })?;
+
+        if result.deleted_logs
+            + result.deleted_test_results
+            + result.deleted_health_checks
+            + result.deleted_bodies
+            + result.deleted_metrics_snapshots
+            > 0
+        {
+            log::info!(
+                "日志清理完成: 请求日志 {} 条, 孤儿测试结果 {} 条, 孤儿健康检查记录 {} 条, 完整请求体 {} 条, 指标快照 {} 条",
+                result.deleted_logs,
+                result.deleted_test_results,
+                result.deleted_health_checks,
+                result.deleted_bodies,
+                result.deleted_metrics_snapshots
+            );
+        }
+
+        Ok(result)
+    }
+
+    /// 重复执行单批删除直到没有可删除的行，每批单独获取连接
+    fn delete_in_batches<F>(pool: &DbPool, delete_batch: F) -> AppResult<i64>
+    where
+        F: Fn(&Connection) -> rusqlite::Result<usize>,
+    {
+        let mut total = 0i64;
+        loop {
+            let deleted = pool.with_connection(|conn| {
+                delete_batch(conn).map_err(|e| AppError::DatabaseError {
+                    message: format!("批量清理日志失败: {}", e),
+                })
+            })?;
+
+            total += deleted as i64;
+            if (deleted as i64) < CLEANUP_BATCH_SIZE {
+                return Ok(total);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::time::to_rfc3339_utc;
+
+    fn setup_pool() -> DbPool {
+        let conn = crate::db::test_db();
+        conn.execute("INSERT INTO AppSettings (id) VALUES (1)", []).unwrap();
+        DbPool::new(conn)
+    }
+
+    fn insert_log(conn: &Connection, request_at: &str) {
+        conn.execute(
+            "INSERT INTO ProxyRequestLog (request_at, method, uri, target_url, latency_ms, status_code)
+             VALUES (?1, 'POST', '/v1/messages', 'https://example.com', 100, 200)",
+            [request_at],
+        )
+        .unwrap();
+    }
+
+    fn log_count(pool: &DbPool) -> i64 {
+        pool.with_connection(|conn| {
+            Ok(conn
+                .query_row("SELECT COUNT(*) FROM ProxyRequestLog", [], |row| row.get(0))
+                .unwrap())
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_policy_round_trip() {
+        let pool = setup_pool();
+        pool.with_connection(|conn| {
+            assert_eq!(LogRetentionService::get_policy(conn)?, LogRetentionPolicy::default());
+
+            let policy = LogRetentionPolicy {
+                max_count: None,
+                max_age_days: Some(7),
+                cleanup_interval_minutes: 30,
+            };
+            LogRetentionService::set_policy(conn, &policy)?;
+            assert_eq!(LogRetentionService::get_policy(conn)?, policy);
+
+            let invalid = LogRetentionPolicy {
+                cleanup_interval_minutes: 0,
+                ..policy
+            };
+            assert!(LogRetentionService::set_policy(conn, &invalid).is_err());
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_cleanup_by_count_and_age() {
+        let pool = setup_pool();
+        let now = chrono::Utc::now();
+        pool.with_connection(|conn| {
+            for i in 0..(CLEANUP_BATCH_SIZE + 20) {
+                insert_log(conn, &to_rfc3339_utc(&(now - chrono::Duration::seconds(i))));
+            }
+            insert_log(conn, &to_rfc3339_utc(&(now - chrono::Duration::days(30))));
+            Ok(())
+        })
+        .unwrap();
+
+        // 超过保留天数的日志被删除
+        let policy = LogRetentionPolicy {
+            max_count: None,
+            max_age_days: Some(7),
+            cleanup_interval_minutes: 60,
+        };
+        let result = LogRetentionService::run_cleanup(&pool, &policy).unwrap();
+        assert_eq!(result.deleted_logs, 1);
+
+        // 超过保留条数的部分跨多个批次删除
+        let policy = LogRetentionPolicy {
+            max_count: Some(10),
+            ..policy
+        };
+        let result = LogRetentionService::run_cleanup(&pool, &policy).unwrap();
+        assert_eq!(result.deleted_logs, CLEANUP_BATCH_SIZE + 10);
+        assert_eq!(log_count(&pool), 10);
+    }
+
+    #[test]
+    fn test_cleanup_prunes_orphaned_records() {
+        let pool = setup_pool();
+        pool.with_connection(|conn| {
+            conn.execute_batch(
+                "PRAGMA foreign_keys = OFF;
+                 INSERT INTO ConfigGroup (id, name) VALUES (1, 'g');
+                 INSERT INTO ApiConfig (id, name, api_key, server_url, server_port, group_id)
+                     VALUES (1, 'c', 'k', 'https://example.com', 443, 1);
+                 INSERT INTO TestResult (config_id, status) VALUES (1, 'success'), (99, 'failed');
+                 INSERT INTO HealthCheckRecord (config_id, status) VALUES (1, 'success'), (99, 'timeout');
+                 INSERT INTO ProxyRequestBody (log_id, kind, content, size) VALUES (99, 'request', 'x', 1);",
+            )
+            .unwrap();
+            Ok(())
+        })
+        .unwrap();
+
+        let result = LogRetentionService::run_cleanup(&pool, &LogRetentionPolicy::default()).unwrap();
+        assert_eq!(result.deleted_test_results, 1);
+        assert_eq!(result.deleted_health_checks, 1);
+        assert_eq!(result.deleted_bodies, 1);
+    }
+
+    #[test]
+    fn test_cleanup_prunes_metrics_snapshots() {
+        let pool = setup_pool();
+        let now = chrono::Utc::now();
+        pool.with_connection(|conn| {
+            for i in 0..5 {
+                conn.execute(
+                    "INSERT INTO MetricsSnapshot (captured_at) VALUES (?1)",
+                    [to_rfc3339_utc(&(now - chrono::Duration::minutes(i)))],
+                )
+                .unwrap();
+            }
+            conn.execute(
+                "INSERT INTO MetricsSnapshot (captured_at) VALUES (?1)",
+                [to_rfc3339_utc(&(now - chrono::Duration::days(30)))],
+            )
+            .unwrap();
+            Ok(())
+        })
+        .unwrap();
+
+        let policy = LogRetentionPolicy {
+            max_count: None,
+            max_age_days: Some(7),
+            cleanup_interval_minutes: 60,
+        };
+        let result = LogRetentionService::run_cleanup(&pool, &policy).unwrap();
+        assert_eq!(result.deleted_metrics_snapshots, 1);
+
+        let policy = LogRetentionPolicy {
+            max_count: Some(2),
+            ..policy
+        };
+        let result = LogRetentionService::run_cleanup(&pool, &policy).unwrap();
+        assert_eq!(result.deleted_metrics_snapshots, 3);
+        let remaining: i64 = pool
+            .with_connection(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM MetricsSnapshot", [], |row| row.get(0)).unwrap()))
+            .unwrap();
+        assert_eq!(remaining, 2);
+    }
+}
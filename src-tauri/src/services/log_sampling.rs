@@ -0,0 +1,226 @@
+/**
+ * Log Sampling Service
+ * 请求日志采样策略：决定代理请求日志是否写入数据库
+ *
+ * 策略只影响日志持久化，所有请求仍计入指标 (METRICS)。
+ * 失败请求在任何策略下都会被记录；流式请求按流结束后的最终结果判断
+ * (软错误或流不完整视为失败，耗时取完整耗时)。
+ */
+
+use crate::db::DbPool;
+use crate::models::error::{AppError, AppResult};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// 默认慢请求阈值（毫秒）
+const DEFAULT_SLOW_THRESHOLD_MS: u64 = 10_000;
+
+/// 当前生效策略的缓存，避免每个请求都读取 AppSettings
+static CACHED_POLICY: RwLock<Option<LogSamplingPolicy>> = RwLock::new(None);
+
+/// 日志采样策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum LogSamplingPolicy {
+    /// 记录全部请求（默认）
+    #[default]
+    All,
+    /// 仅记录失败请求
+    ErrorsOnly,
+    /// 记录失败请求和耗时超过阈值的慢请求
+    ErrorsAndSlow { slow_threshold_ms: u64 },
+    /// 记录全部失败请求，成功请求按百分比采样
+    Sampled { percent: u8 },
+}
+
+impl LogSamplingPolicy {
+    /// 验证策略参数
+    pub fn validate(&self) -> AppResult<()> {
+        match *self {
+            LogSamplingPolicy::ErrorsAndSlow { slow_threshold_ms: 0 } => Err(AppError::ValidationError {
+                field: "slow_threshold_ms".to_string(),
+                message: "慢请求阈值必须大于 0".to_string(),
+            }),
+            LogSamplingPolicy::Sampled { percent } if !(1..=100).contains(&percent) => {
+                Err(AppError::ValidationError {
+                    field: "percent".to_string(),
+                    message: "采样比例必须在 1-100 范围内".to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 判断请求日志是否应写入数据库
+    pub fn should_persist(&self, is_error: bool, latency_ms: u64) -> bool {
+        self.should_persist_with_roll(is_error, latency_ms, rand::random::<u32>())
+    }
+
+    /// 以随机数 `roll` 判断请求日志是否应写入数据库
+    fn should_persist_with_roll(&self, is_error: bool, latency_ms: u64, roll: u32) -> bool {
+        if is_error {
+            return true;
+        }
+        match *self {
+            LogSamplingPolicy::All => true,
+            LogSamplingPolicy::ErrorsOnly => false,
+            LogSamplingPolicy::ErrorsAndSlow { slow_threshold_ms } => latency_ms > slow_threshold_ms,
+            LogSamplingPolicy::Sampled { percent } => (roll % 100) < percent as u32,
+        }
+    }
+
+    fn mode_str(&self) -> &'static str {
+        match self {
+            LogSamplingPolicy::All => "all",
+            LogSamplingPolicy::ErrorsOnly => "errors_only",
+            LogSamplingPolicy::ErrorsAndSlow { .. } => "errors_and_slow",
+            LogSamplingPolicy::Sampled { .. } => "sampled",
+        }
+    }
+}
+
+/// 日志采样服务
+pub struct LogSamplingService;
+
+impl LogSamplingService {
+    /// 读取日志采样策略
+    pub fn get_policy(conn: &Connection) -> AppResult<LogSamplingPolicy> {
+        let (mode, slow_threshold_ms, percent) = conn
+            .query_row(
+                "SELECT log_sampling_mode, log_slow_threshold_ms, log_sample_percent FROM AppSettings WHERE id = 1",
+                [],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?)),
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("读取日志采样策略失败: {}", e),
+            })?;
+
+        Ok(match mode.as_str() {
+            "errors_only" => LogSamplingPolicy::ErrorsOnly,
+            "errors_and_slow" => LogSamplingPolicy::ErrorsAndSlow {
+                slow_threshold_ms: u64::try_from(slow_threshold_ms).unwrap_or(DEFAULT_SLOW_THRESHOLD_MS),
+            },
+            "sampled" => LogSamplingPolicy::Sampled {
+                percent: percent.clamp(1, 100) as u8,
+            },
+            _ => LogSamplingPolicy::All,
+        })
+    }
+
+    /// 读取当前生效的采样策略 (首次读取后缓存)，读取失败时按记录全部处理
+    pub fn current_policy(pool: &DbPool) -> LogSamplingPolicy {
+        if let Some(policy) = *CACHED_POLICY.read().unwrap_or_else(|e| e.into_inner()) {
+            return policy;
+        }
+        match pool.with_log_connection(Self::get_policy) {
+            Ok(policy) => {
+                Self::cache_policy(policy);
+                policy
+            }
+            Err(e) => {
+                log::warn!("读取日志采样策略失败，按记录全部处理: {}", e);
+                LogSamplingPolicy::All
+            }
+        }
+    }
+
+    /// 更新缓存的采样策略 (保存新策略后调用，下一个请求即生效)
+    pub fn cache_policy(policy: LogSamplingPolicy) {
+        *CACHED_POLICY.write().unwrap_or_else(|e| e.into_inner()) = Some(policy);
+    }
+
+    /// 保存日志采样策略
+    ///
+    /// 未使用的阈值/比例保持原值，切换回对应模式时沿用
+    pub fn set_policy(conn: &Connection, policy: &LogSamplingPolicy) -> AppResult<()> {
+        policy.validate()?;
+
+        let (slow_threshold_ms, percent) = match *policy {
+            LogSamplingPolicy::ErrorsAndSlow { slow_threshold_ms } => (Some(slow_threshold_ms as i64), None),
+            LogSamplingPolicy::Sampled { percent } => (None, Some(percent as i64)),
+            _ => (None, None),
+        };
+
+        let updated = conn
+            .execute(
+                "UPDATE AppSettings SET
+                    log_sampling_mode = ?1,
+                    log_slow_threshold_ms = COALESCE(?2, log_slow_threshold_ms),
+                    log_sample_percent = COALESCE(?3, log_sample_percent),
+                    updated_at = CURRENT_TIMESTAMP
+                 WHERE id = 1",
+                params![policy.mode_str(), slow_threshold_ms, percent],
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("保存日志采样策略失败: {}", e),
+            })?;
+
+        if updated == 0 {
+            return Err(AppError::NotFound {
+                resource: "AppSettings".to_string(),
+                id: "1".to_string(),
+            });
+        }
+
+        log::info!("日志采样策略已更新: {:?}", policy);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_round_trip() {
+        let conn = crate::db::test_db();
+        conn.execute("INSERT INTO AppSettings (id) VALUES (1)", []).unwrap();
+
+        assert_eq!(LogSamplingService::get_policy(&conn).unwrap(), LogSamplingPolicy::All);
+
+        let slow = LogSamplingPolicy::ErrorsAndSlow { slow_threshold_ms: 3000 };
+        LogSamplingService::set_policy(&conn, &slow).unwrap();
+        assert_eq!(LogSamplingService::get_policy(&conn).unwrap(), slow);
+
+        // 切换到其他模式后阈值保留
+        LogSamplingService::set_policy(&conn, &LogSamplingPolicy::Sampled { percent: 10 }).unwrap();
+        assert_eq!(
+            LogSamplingService::get_policy(&conn).unwrap(),
+            LogSamplingPolicy::Sampled { percent: 10 }
+        );
+        conn.execute("UPDATE AppSettings SET log_sampling_mode = 'errors_and_slow'", []).unwrap();
+        assert_eq!(LogSamplingService::get_policy(&conn).unwrap(), slow);
+
+        assert!(LogSamplingService::set_policy(&conn, &LogSamplingPolicy::Sampled { percent: 0 }).is_err());
+        assert!(LogSamplingService::set_policy(&conn, &LogSamplingPolicy::ErrorsAndSlow { slow_threshold_ms: 0 }).is_err());
+    }
+
+    #[test]
+    fn test_should_persist() {
+        let slow = LogSamplingPolicy::ErrorsAndSlow { slow_threshold_ms: 1000 };
+        let sampled = LogSamplingPolicy::Sampled { percent: 25 };
+
+        for policy in [LogSamplingPolicy::All, LogSamplingPolicy::ErrorsOnly, slow, sampled] {
+            assert!(policy.should_persist_with_roll(true, 10, 99), "{:?} 应记录失败请求", policy);
+        }
+
+        assert!(LogSamplingPolicy::All.should_persist_with_roll(false, 10, 99));
+        assert!(!LogSamplingPolicy::ErrorsOnly.should_persist_with_roll(false, 10_000, 0));
+        assert!(!slow.should_persist_with_roll(false, 1000, 0));
+        assert!(slow.should_persist_with_roll(false, 1001, 99));
+        assert!(sampled.should_persist_with_roll(false, 10, 24));
+        assert!(!sampled.should_persist_with_roll(false, 10, 125));
+    }
+
+    #[test]
+    fn test_policy_serialization() {
+        let policy: LogSamplingPolicy =
+            serde_json::from_value(serde_json::json!({ "mode": "errors_and_slow", "slow_threshold_ms": 500 })).unwrap();
+        assert_eq!(policy, LogSamplingPolicy::ErrorsAndSlow { slow_threshold_ms: 500 });
+        assert_eq!(
+            serde_json::to_value(LogSamplingPolicy::ErrorsOnly).unwrap(),
+            serde_json::json!({ "mode": "errors_only" })
+        );
+    }
+}
@@ -1,10 +1,19 @@
 use crate::models::error::{AppError, AppResult};
-use crate::models::mcp::{McpServerConfig, McpServerInfo, McpServerTemplate};
+use crate::models::mcp::{
+    McpBatchTestProgress, McpBatchTestResult, McpImportEntry, McpImportResult, McpImportStatus,
+    McpImportStrategy, McpServerConfig, McpServerDiagnostics, McpServerInfo, McpServerTemplate,
+    McpServerTestSummary,
+};
+use crate::services::mcp_probe::{self, ProbeTarget};
+use futures_util::stream::{self, StreamExt};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 
+/// 批量测试时同时进行的最大握手数
+const MAX_CONCURRENT_SERVER_TESTS: usize = 4;
+
 /// MCP 配置管理服务
 pub struct McpConfigService;
 
@@ -281,57 +290,279 @@ impl McpConfigService {
         Self::add_server(name, server_config)
     }
 
-    /// 测试 MCP 服务器配置
-    pub async fn test_server(name: String) -> AppResult<String> {
+    /// 诊断 MCP 服务器连通性
+    ///
+    /// 按配置的传输方式 (stdio/sse/http) 连接服务器并执行 initialize 握手，
+    /// 返回每个阶段的结果、耗时与具体错误
+    pub async fn diagnose_server(name: String) -> AppResult<McpServerDiagnostics> {
         let config = Self::read_config_json()?;
         let servers_obj = config
             .get(Self::mcp_servers_key(&config))
             .and_then(|v| v.as_object())
-            .ok_or_else(|| {
-            AppError::InvalidData {
+            .ok_or_else(|| AppError::InvalidData {
                 message: "MCP 配置中缺少 mcpServers".to_string(),
-            }
-        })?;
+            })?;
 
         let server_value = servers_obj.get(&name).ok_or_else(|| AppError::InvalidData {
             message: format!("MCP 服务器 '{}' 不存在", name),
         })?;
 
-        let server_config: McpServerConfig = serde_json::from_value(server_value.clone()).map_err(|e| {
-            AppError::InvalidData {
-                message: format!("MCP 服务器 '{}' 配置无法解析为 stdio 格式: {}", name, e),
-            }
+        let target = ProbeTarget::from_config(server_value).map_err(|e| AppError::InvalidData {
+            message: format!("MCP 服务器 '{}' 配置无效: {}", name, e),
         })?;
 
-        // 这里简单验证命令是否可执行
-        // 实际的 MCP 服务器测试需要启动服务器并进行通信,这里简化处理
-        let test_result = format!(
-            "MCP 服务器 '{}' 配置有效\n命令: {}\n参数: {:?}",
-            name, server_config.command, server_config.args
-        );
+        Ok(mcp_probe::probe(&name, &target).await)
+    }
+
+    /// 测试 MCP 服务器配置
+    ///
+    /// 基于 diagnose_server 的结果生成文本摘要
+    pub async fn test_server(name: String) -> AppResult<String> {
+        let diag = Self::diagnose_server(name).await?;
+
+        let mut lines = vec![format!("MCP 服务器 '{}' ({:?}): {}", diag.name, diag.transport, diag.target)];
+        if diag.handshake_ok {
+            lines.push(format!(
+                "握手成功: {} {} (协议 {})",
+                diag.server_name.as_deref().unwrap_or("unknown"),
+                diag.server_version.as_deref().unwrap_or(""),
+                diag.protocol_version.as_deref().unwrap_or("unknown")
+            ));
+            lines.push(format!("工具: {}, 资源: {}", diag.tools.len(), diag.resources.len()));
+        }
+        if let Some(error) = &diag.error {
+            lines.push(format!(
+                "失败阶段: {}\n错误: {}",
+                diag.failed_stage.as_deref().unwrap_or("unknown"),
+                error
+            ));
+        }
+        lines.push(format!("耗时: {} ms", diag.total_ms));
+
+        Ok(lines.join("\n"))
+    }
+
+    /// 并发测试所有 MCP 服务器
+    ///
+    /// 对每个服务器执行与 diagnose_server 相同的握手 (最多同时 MAX_CONCURRENT_SERVER_TESTS 个)，
+    /// 每完成一个调用一次 `on_progress`；配置无法解析的服务器记为 config 阶段失败
+    pub async fn test_all_servers(
+        on_progress: impl Fn(McpBatchTestProgress),
+    ) -> AppResult<McpBatchTestResult> {
+        let config = Self::read_config_json()?;
+        let targets = config
+            .get(Self::mcp_servers_key(&config))
+            .and_then(|v| v.as_object())
+            .map(|servers| {
+                servers
+                    .iter()
+                    .map(|(name, value)| (name.clone(), ProbeTarget::from_config(value)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self::test_targets(targets, on_progress).await)
+    }
 
-        log::info!("测试 MCP 服务器: {}", name);
-        Ok(test_result)
+    async fn test_targets(
+        targets: Vec<(String, Result<ProbeTarget, String>)>,
+        on_progress: impl Fn(McpBatchTestProgress),
+    ) -> McpBatchTestResult {
+        let total = targets.len();
+        let mut results = stream::iter(targets)
+            .map(|(name, target)| async move {
+                match target {
+                    Ok(target) => McpServerTestSummary::from(mcp_probe::probe(&name, &target).await),
+                    Err(e) => McpServerTestSummary {
+                        name,
+                        transport: None,
+                        target: None,
+                        handshake_ok: false,
+                        tool_count: 0,
+                        total_ms: 0,
+                        failed_stage: Some("config".to_string()),
+                        error: Some(format!("配置无效: {}", e)),
+                    },
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_SERVER_TESTS);
+
+        let mut result = McpBatchTestResult::default();
+        while let Some(summary) = results.next().await {
+            if summary.handshake_ok {
+                result.passed += 1;
+            } else {
+                log::warn!(
+                    "MCP 服务器 '{}' 测试失败 ({}): {}",
+                    summary.name,
+                    summary.failed_stage.as_deref().unwrap_or("unknown"),
+                    summary.error.as_deref().unwrap_or("")
+                );
+                result.failed += 1;
+            }
+            result.servers.push(summary.clone());
+            on_progress(McpBatchTestProgress {
+                completed: result.servers.len(),
+                total,
+                server: summary,
+            });
+        }
+
+        result.servers.sort_by(|a, b| a.name.cmp(&b.name));
+        result
     }
 
     /// 批量导入 MCP 服务器
-    pub fn import_servers(servers: HashMap<String, McpServerConfig>) -> AppResult<()> {
+    ///
+    /// 逐条校验 (名称非空、命令可执行或为内置模板命令、名称不重复)，
+    /// 同名冲突按 `strategy` 处理；单条失败不影响其它条目
+    pub fn import_servers(
+        servers: HashMap<String, McpServerConfig>,
+        strategy: McpImportStrategy,
+    ) -> AppResult<McpImportResult> {
         let mut config = Self::read_config_json()?;
         let servers_obj = Self::ensure_mcp_servers_object_mut(&mut config)?;
 
-        for (name, server_config) in servers {
-            servers_obj.insert(
-                name,
-                serde_json::to_value(server_config).map_err(|e| AppError::InvalidData {
-                    message: format!("序列化 MCP 服务器配置失败: {}", e),
-                })?,
-            );
+        let result = Self::merge_servers(servers_obj, servers, strategy, Self::command_available);
+
+        if result.added + result.overwritten > 0 {
+            Self::write_config_json(&config)?;
         }
 
-        Self::write_config_json(&config)?;
+        log::info!(
+            "导入 MCP 服务器完成: 新增 {}, 覆盖 {}, 跳过 {}, 错误 {}",
+            result.added,
+            result.overwritten,
+            result.skipped,
+            result.errors
+        );
+        Ok(result)
+    }
 
-        log::info!("成功导入 MCP 服务器配置");
-        Ok(())
+    /// 将导入条目合并到 mcpServers 对象中
+    fn merge_servers(
+        servers_obj: &mut serde_json::Map<String, Value>,
+        servers: HashMap<String, McpServerConfig>,
+        strategy: McpImportStrategy,
+        command_available: impl Fn(&str) -> bool,
+    ) -> McpImportResult {
+        let template_commands: HashSet<String> = Self::get_builtin_templates()
+            .into_iter()
+            .map(|t| t.config.command)
+            .collect();
+
+        let mut entries: Vec<(String, McpServerConfig)> = servers.into_iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut seen = HashSet::new();
+        let mut result = McpImportResult::default();
+
+        for (raw_name, server_config) in entries {
+            let name = raw_name.trim().to_string();
+            let error = |message: String| McpImportEntry {
+                name: raw_name.clone(),
+                imported_as: None,
+                status: McpImportStatus::Error,
+                message: Some(message),
+            };
+
+            if name.is_empty() {
+                result.push(error("服务器名称不能为空".to_string()));
+                continue;
+            }
+            if !seen.insert(name.clone()) {
+                result.push(error(format!("导入数据中存在重复的服务器名称 '{}'", name)));
+                continue;
+            }
+
+            let command = server_config.command.trim();
+            if command.is_empty() {
+                result.push(error("命令不能为空".to_string()));
+                continue;
+            }
+            if !template_commands.contains(command) && !command_available(command) {
+                result.push(error(format!("找不到命令 '{}'", command)));
+                continue;
+            }
+            if let Some(key) = server_config
+                .env
+                .as_ref()
+                .and_then(|env| env.keys().find(|k| k.trim().is_empty()))
+            {
+                result.push(error(format!("环境变量名称无效: '{}'", key)));
+                continue;
+            }
+
+            let (target_name, status) = if !servers_obj.contains_key(&name) {
+                (name.clone(), McpImportStatus::Added)
+            } else {
+                match strategy {
+                    McpImportStrategy::Skip => {
+                        result.push(McpImportEntry {
+                            name: raw_name.clone(),
+                            imported_as: None,
+                            status: McpImportStatus::Skipped,
+                            message: Some(format!("MCP 服务器 '{}' 已存在", name)),
+                        });
+                        continue;
+                    }
+                    McpImportStrategy::Overwrite => (name.clone(), McpImportStatus::Overwritten),
+                    McpImportStrategy::Rename => {
+                        let renamed = (2..)
+                            .map(|n| format!("{}-{}", name, n))
+                            .find(|candidate| !servers_obj.contains_key(candidate) && !seen.contains(candidate))
+                            .expect("unbounded suffix search");
+                        seen.insert(renamed.clone());
+                        (renamed, McpImportStatus::Renamed)
+                    }
+                }
+            };
+
+            match serde_json::to_value(&server_config) {
+                Ok(value) => {
+                    servers_obj.insert(target_name.clone(), value);
+                    result.push(McpImportEntry {
+                        name: raw_name.clone(),
+                        imported_as: Some(target_name),
+                        status,
+                        message: None,
+                    });
+                }
+                Err(e) => result.push(error(format!("序列化 MCP 服务器配置失败: {}", e))),
+            }
+        }
+
+        result
+    }
+
+    /// 命令是否可执行 (路径存在，或能在 PATH 中找到)
+    fn command_available(command: &str) -> bool {
+        let path = std::path::Path::new(command);
+        if path.components().count() > 1 || path.is_absolute() {
+            return path.is_file();
+        }
+
+        let Some(paths) = std::env::var_os("PATH") else {
+            return false;
+        };
+
+        // Windows 上需要尝试 PATHEXT 中的扩展名 (npx.cmd 等)
+        let extensions: Vec<String> = if cfg!(target_os = "windows") {
+            std::env::var("PATHEXT")
+                .unwrap_or_else(|_| ".EXE;.CMD;.BAT;.COM".to_string())
+                .split(';')
+                .map(|ext| ext.to_string())
+                .chain(std::iter::once(String::new()))
+                .collect()
+        } else {
+            vec![String::new()]
+        };
+
+        std::env::split_paths(&paths).any(|dir| {
+            extensions
+                .iter()
+                .any(|ext| dir.join(format!("{}{}", command, ext)).is_file())
+        })
     }
 
     /// 导出 MCP 服务器配置
@@ -361,7 +592,7 @@ impl McpConfigService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::mcp::McpConfig;
+    use crate::models::mcp::{McpConfig, McpTransport};
 
     #[test]
     fn test_get_builtin_templates() {
@@ -377,9 +608,95 @@ mod tests {
         }
     }
 
+    fn server(command: &str) -> McpServerConfig {
+        McpServerConfig {
+            command: command.to_string(),
+            args: vec![],
+            env: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_servers_validates_and_applies_strategy() {
+        let existing = || {
+            let mut obj = serde_json::Map::new();
+            obj.insert("git".to_string(), serde_json::json!({"command": "uvx", "args": []}));
+            obj.insert("git-2".to_string(), serde_json::json!({"command": "uvx", "args": []}));
+            obj
+        };
+        let servers = HashMap::from([
+            ("git".to_string(), server("npx")),
+            ("fetch".to_string(), server("my-tool")),
+            ("  ".to_string(), server("npx")),
+            ("broken".to_string(), server("/no/such/binary")),
+            ("empty".to_string(), server("")),
+        ]);
+        let available = |cmd: &str| cmd == "my-tool";
+
+        let mut obj = existing();
+        let result = McpConfigService::merge_servers(&mut obj, servers.clone(), McpImportStrategy::Skip, available);
+        assert_eq!((result.added, result.overwritten, result.skipped, result.errors), (1, 0, 1, 3));
+        assert_eq!(obj["git"]["command"], "uvx");
+        assert!(obj.contains_key("fetch"));
+        assert!(!obj.contains_key("broken"));
+
+        let mut obj = existing();
+        let result = McpConfigService::merge_servers(&mut obj, servers.clone(), McpImportStrategy::Rename, available);
+        assert_eq!(result.added, 2);
+        let git = result.entries.iter().find(|e| e.name == "git").unwrap();
+        assert_eq!(git.status, McpImportStatus::Renamed);
+        assert_eq!(git.imported_as.as_deref(), Some("git-3"));
+        assert_eq!(obj["git"]["command"], "uvx");
+
+        let mut obj = existing();
+        let result = McpConfigService::merge_servers(&mut obj, servers, McpImportStrategy::Overwrite, available);
+        assert_eq!(result.overwritten, 1);
+        assert_eq!(obj["git"]["command"], "npx");
+    }
+
+    #[test]
+    fn test_merge_servers_rejects_names_duplicated_after_trim() {
+        let mut obj = serde_json::Map::new();
+        let servers = HashMap::from([
+            ("git".to_string(), server("npx")),
+            ("git ".to_string(), server("npx")),
+        ]);
+
+        let result = McpConfigService::merge_servers(&mut obj, servers, McpImportStrategy::Overwrite, |_| false);
+        assert_eq!(result.added, 1);
+        assert_eq!(result.errors, 1);
+        assert_eq!(obj.len(), 1);
+    }
+
     #[test]
     fn test_mcp_config_default() {
         let config = McpConfig::default();
         assert!(config.mcp_servers.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_targets_reports_each_failure() {
+        let targets = vec![
+            (
+                "moved".to_string(),
+                ProbeTarget::from_config(&serde_json::json!({"command": "/nonexistent/ccp-mcp-server"})),
+            ),
+            ("broken".to_string(), ProbeTarget::from_config(&serde_json::json!({"args": []}))),
+        ];
+
+        let progress = std::sync::Mutex::new(Vec::new());
+        let result = McpConfigService::test_targets(targets, |p| {
+            progress.lock().unwrap().push((p.completed, p.total));
+        })
+        .await;
+
+        assert_eq!((result.passed, result.failed), (0, 2));
+        let names: Vec<&str> = result.servers.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["broken", "moved"]);
+        assert_eq!(result.servers[0].failed_stage.as_deref(), Some("config"));
+        assert_eq!(result.servers[1].transport, Some(McpTransport::Stdio));
+        assert_eq!(result.servers[1].failed_stage.as_deref(), Some("spawn"));
+        assert!(result.servers[1].error.is_some());
+        assert_eq!(*progress.lock().unwrap(), vec![(1, 2), (2, 2)]);
+    }
 }
@@ -0,0 +1,810 @@
+/**
+ * MCP Probe
+ * 对 MCP 服务器执行真实的 initialize 握手，收集服务器能力、工具与资源列表
+ *
+ * 支持的传输方式:
+ * - stdio: 启动子进程，通过 stdin/stdout 按行收发 JSON-RPC
+ * - http:  Streamable HTTP，POST JSON-RPC，响应为 JSON 或 SSE
+ * - sse:   旧版 HTTP + SSE，GET 建立事件流，从 `endpoint` 事件获取 POST 地址
+ */
+
+use crate::models::mcp::{McpResourceInfo, McpServerDiagnostics, McpToolInfo, McpTransport};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+
+/// 诊断使用的 MCP 协议版本
+pub const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// initialize 握手超时（npx 首次运行需要下载依赖，适当放宽）
+const INITIALIZE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// tools/list、resources/list 超时
+const LIST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 远程服务器建立连接超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 保留的 stderr 行数
+const STDERR_TAIL_LINES: usize = 20;
+
+/// 诊断目标
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProbeTarget {
+    /// 本地进程
+    Stdio {
+        command: String,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+    },
+    /// 远程服务器 (SSE / Streamable HTTP)
+    Remote {
+        transport: McpTransport,
+        url: String,
+        headers: HashMap<String, String>,
+    },
+}
+
+impl ProbeTarget {
+    /// 从 ~/.claude.json 中单个服务器的配置解析诊断目标
+    pub fn from_config(value: &Value) -> Result<Self, String> {
+        let obj = value.as_object().ok_or("服务器配置不是 JSON 对象")?;
+        let type_hint = obj.get("type").and_then(|v| v.as_str()).unwrap_or("");
+
+        let string_map = |key: &str| -> HashMap<String, String> {
+            obj.get(key)
+                .and_then(|v| v.as_object())
+                .map(|m| {
+                    m.iter()
+                        .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        if let Some(command) = obj.get("command").and_then(|v| v.as_str()) {
+            if type_hint.is_empty() || type_hint == "stdio" {
+                let args = obj
+                    .get("args")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                    .unwrap_or_default();
+
+                return Ok(ProbeTarget::Stdio {
+                    command: command.to_string(),
+                    args,
+                    env: string_map("env"),
+                });
+            }
+        }
+
+        if let Some(url) = obj.get("url").and_then(|v| v.as_str()) {
+            let transport = match type_hint {
+                "sse" => McpTransport::Sse,
+                "http" | "streamable-http" | "streamable_http" => McpTransport::Http,
+                _ if url.trim_end_matches('/').ends_with("/sse") => McpTransport::Sse,
+                _ => McpTransport::Http,
+            };
+
+            return Ok(ProbeTarget::Remote {
+                transport,
+                url: url.to_string(),
+                headers: string_map("headers"),
+            });
+        }
+
+        Err("服务器配置缺少 command 或 url 字段".to_string())
+    }
+
+    /// 传输方式
+    pub fn transport(&self) -> McpTransport {
+        match self {
+            ProbeTarget::Stdio { .. } => McpTransport::Stdio,
+            ProbeTarget::Remote { transport, .. } => *transport,
+        }
+    }
+
+    /// 用于展示的连接目标
+    pub fn describe(&self) -> String {
+        match self {
+            ProbeTarget::Stdio { command, args, .. } => {
+                std::iter::once(command.as_str())
+                    .chain(args.iter().map(String::as_str))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+            ProbeTarget::Remote { url, .. } => url.clone(),
+        }
+    }
+}
+
+/// 对 MCP 服务器执行诊断
+///
+/// 不会返回错误：所有失败都记录在 `failed_stage` / `error` 中
+pub async fn probe(name: &str, target: &ProbeTarget) -> McpServerDiagnostics {
+    let started = Instant::now();
+    let mut diag = McpServerDiagnostics::new(name, target.transport(), target.describe());
+
+    log::info!("开始诊断 MCP 服务器 '{}' ({:?}): {}", name, diag.transport, diag.target);
+
+    let connect_start = Instant::now();
+    let session = match target {
+        ProbeTarget::Stdio { command, args, env } => {
+            let spawned = StdioSession::spawn(command, args, env).map(Session::Stdio);
+            diag.process_spawned = Some(spawned.is_ok());
+            spawned.map_err(|e| ("spawn", e))
+        }
+        ProbeTarget::Remote { transport: McpTransport::Sse, url, headers } => {
+            match timeout(CONNECT_TIMEOUT, SseSession::connect(url, headers)).await {
+                Ok(result) => result.map(Session::Sse).map_err(|e| ("connect", e)),
+                Err(_) => Err(("connect", format!("建立 SSE 连接超时 ({}s)", CONNECT_TIMEOUT.as_secs()))),
+            }
+        }
+        ProbeTarget::Remote { url, headers, .. } => {
+            HttpSession::new(url, headers).map(Session::Http).map_err(|e| ("connect", e))
+        }
+    };
+
+    let mut session = match session {
+        Ok(session) => session,
+        Err((stage, error)) => {
+            diag.fail(stage, error);
+            diag.total_ms = started.elapsed().as_millis() as u64;
+            return diag;
+        }
+    };
+    diag.connect_ms = Some(connect_start.elapsed().as_millis() as u64);
+
+    run_handshake(&mut session, &mut diag).await;
+
+    diag.stderr_tail = session.stderr_tail();
+    session.close().await;
+    diag.total_ms = started.elapsed().as_millis() as u64;
+
+    log::info!(
+        "MCP 服务器 '{}' 诊断完成: handshake_ok={}, tools={}, resources={}, error={:?}",
+        name,
+        diag.handshake_ok,
+        diag.tools.len(),
+        diag.resources.len(),
+        diag.error
+    );
+
+    diag
+}
+
+/// initialize 握手 + 列出工具/资源
+async fn run_handshake(session: &mut Session, diag: &mut McpServerDiagnostics) {
+    let handshake_start = Instant::now();
+    let result = match timeout(INITIALIZE_TIMEOUT, session.request(1, "initialize", initialize_params())).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => return diag.fail("initialize", e),
+        Err(_) => {
+            return diag.fail(
+                "initialize",
+                format!("initialize 握手超时 ({}s)", INITIALIZE_TIMEOUT.as_secs()),
+            )
+        }
+    };
+
+    diag.handshake_ms = Some(handshake_start.elapsed().as_millis() as u64);
+    diag.handshake_ok = true;
+    diag.protocol_version = result
+        .get("protocolVersion")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    diag.server_name = result
+        .pointer("/serverInfo/name")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    diag.server_version = result
+        .pointer("/serverInfo/version")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    let capabilities = result.get("capabilities").cloned().unwrap_or_else(|| json!({}));
+
+    if let Err(e) = session.notify("notifications/initialized").await {
+        log::debug!("发送 notifications/initialized 失败: {}", e);
+    }
+
+    if capabilities.get("tools").is_some() {
+        match timeout(LIST_TIMEOUT, session.request(2, "tools/list", json!({}))).await {
+            Ok(Ok(result)) => diag.tools = parse_tools(&result),
+            Ok(Err(e)) => diag.fail("tools/list", e),
+            Err(_) => diag.fail("tools/list", format!("tools/list 超时 ({}s)", LIST_TIMEOUT.as_secs())),
+        }
+    }
+
+    if capabilities.get("resources").is_some() {
+        match timeout(LIST_TIMEOUT, session.request(3, "resources/list", json!({}))).await {
+            Ok(Ok(result)) => diag.resources = parse_resources(&result),
+            Ok(Err(e)) => diag.fail("resources/list", e),
+            Err(_) => diag.fail(
+                "resources/list",
+                format!("resources/list 超时 ({}s)", LIST_TIMEOUT.as_secs()),
+            ),
+        }
+    }
+
+    diag.capabilities = Some(capabilities);
+}
+
+fn initialize_params() -> Value {
+    json!({
+        "protocolVersion": MCP_PROTOCOL_VERSION,
+        "capabilities": {},
+        "clientInfo": {
+            "name": "claude-code-proxy",
+            "version": env!("CARGO_PKG_VERSION"),
+        }
+    })
+}
+
+fn request_message(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+fn notification_message(method: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method })
+}
+
+/// 是否为指定请求 ID 的响应
+fn is_response_to(message: &Value, id: u64) -> bool {
+    message.get("id").and_then(|v| v.as_u64()) == Some(id)
+        && (message.get("result").is_some() || message.get("error").is_some())
+}
+
+/// 从 JSON-RPC 响应中提取 result，error 转换为错误信息
+fn extract_result(message: &Value) -> Result<Value, String> {
+    if let Some(error) = message.get("error") {
+        let text = error
+            .get("message")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown error");
+        return Err(match error.get("code").and_then(|v| v.as_i64()) {
+            Some(code) => format!("{} (code {})", text, code),
+            None => text.to_string(),
+        });
+    }
+
+    Ok(message.get("result").cloned().unwrap_or(Value::Null))
+}
+
+/// 在单条消息或批量消息中查找指定 ID 的响应
+fn find_response(message: &Value, id: u64) -> Option<Result<Value, String>> {
+    match message {
+        Value::Array(items) => items.iter().find(|m| is_response_to(m, id)).map(extract_result),
+        _ if is_response_to(message, id) => Some(extract_result(message)),
+        _ => None,
+    }
+}
+
+fn parse_tools(result: &Value) -> Vec<McpToolInfo> {
+    result
+        .get("tools")
+        .and_then(|v| v.as_array())
+        .map(|tools| {
+            tools
+                .iter()
+                .filter_map(|tool| {
+                    Some(McpToolInfo {
+                        name: tool.get("name")?.as_str()?.to_string(),
+                        description: tool.get("description").and_then(|v| v.as_str()).map(String::from),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_resources(result: &Value) -> Vec<McpResourceInfo> {
+    result
+        .get("resources")
+        .and_then(|v| v.as_array())
+        .map(|resources| {
+            resources
+                .iter()
+                .filter_map(|resource| {
+                    Some(McpResourceInfo {
+                        uri: resource.get("uri")?.as_str()?.to_string(),
+                        name: resource.get("name").and_then(|v| v.as_str()).map(String::from),
+                        description: resource
+                            .get("description")
+                            .and_then(|v| v.as_str())
+                            .map(String::from),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 展开形如 `${VAR}` 的环境变量占位符（与 Claude Code 行为一致），未设置时保持原样
+fn expand_env_placeholder(value: &str) -> String {
+    value
+        .strip_prefix("${")
+        .and_then(|rest| rest.strip_suffix('}'))
+        .and_then(|name| std::env::var(name).ok())
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// SSE 事件
+#[derive(Debug, Clone, PartialEq)]
+struct SseEvent {
+    event: Option<String>,
+    data: String,
+}
+
+/// 增量 SSE 解析器（按字节缓冲，避免多字节字符被分块截断）
+#[derive(Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend(chunk.iter().copied().filter(|b| *b != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let raw: Vec<u8> = self.buffer.drain(..pos + 2).collect();
+            if let Some(event) = Self::parse_event(&String::from_utf8_lossy(&raw)) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    fn parse_event(text: &str) -> Option<SseEvent> {
+        let mut event = None;
+        let mut data: Vec<&str> = Vec::new();
+
+        for line in text.lines() {
+            if line.is_empty() || line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => event = Some(value.to_string()),
+                "data" => data.push(value),
+                _ => {}
+            }
+        }
+
+        if event.is_none() && data.is_empty() {
+            return None;
+        }
+
+        Some(SseEvent {
+            event,
+            data: data.join("\n"),
+        })
+    }
+}
+
+/// 诊断会话
+enum Session {
+    Stdio(StdioSession),
+    Http(HttpSession),
+    Sse(SseSession),
+}
+
+impl Session {
+    async fn request(&mut self, id: u64, method: &str, params: Value) -> Result<Value, String> {
+        let message = request_message(id, method, params);
+        match self {
+            Session::Stdio(s) => s.request(id, &message).await,
+            Session::Http(s) => s.request(id, &message).await,
+            Session::Sse(s) => s.request(id, &message).await,
+        }
+    }
+
+    async fn notify(&mut self, method: &str) -> Result<(), String> {
+        let message = notification_message(method);
+        match self {
+            Session::Stdio(s) => s.send(&message).await,
+            Session::Http(s) => s.post(&message).await.map(|_| ()),
+            Session::Sse(s) => s.post(&message).await,
+        }
+    }
+
+    fn stderr_tail(&self) -> Option<String> {
+        match self {
+            Session::Stdio(s) => s.stderr_tail(),
+            _ => None,
+        }
+    }
+
+    async fn close(self) {
+        match self {
+            Session::Stdio(mut s) => {
+                let _ = s.child.kill().await;
+            }
+            Session::Sse(s) => drop(s),
+            Session::Http(_) => {}
+        }
+    }
+}
+
+/// stdio 会话
+struct StdioSession {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    stderr: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl StdioSession {
+    fn spawn(command: &str, args: &[String], env: &HashMap<String, String>) -> Result<Self, String> {
+        // Windows 上 npx 等命令是 .cmd 脚本，需要通过 cmd /C 启动
+        let mut cmd = if cfg!(target_os = "windows") {
+            let mut cmd = Command::new("cmd");
+            cmd.arg("/C").arg(command);
+            cmd
+        } else {
+            Command::new(command)
+        };
+
+        cmd.args(args)
+            .envs(env.iter().map(|(k, v)| (k, expand_env_placeholder(v))))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("无法启动进程 '{}': {}", command, e))?;
+
+        let stdin = child.stdin.take().ok_or("无法获取进程 stdin")?;
+        let stdout = child.stdout.take().ok_or("无法获取进程 stdout")?;
+
+        let stderr = Arc::new(Mutex::new(VecDeque::new()));
+        if let Some(child_stderr) = child.stderr.take() {
+            let tail = stderr.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(child_stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let mut tail = tail.lock().unwrap_or_else(|e| e.into_inner());
+                    if tail.len() >= STDERR_TAIL_LINES {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line);
+                }
+            });
+        }
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+            stderr,
+        })
+    }
+
+    async fn send(&mut self, message: &Value) -> Result<(), String> {
+        let mut line = message.to_string();
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("写入进程 stdin 失败: {}", e))?;
+        self.stdin
+            .flush()
+            .await
+            .map_err(|e| format!("写入进程 stdin 失败: {}", e))
+    }
+
+    async fn request(&mut self, id: u64, message: &Value) -> Result<Value, String> {
+        self.send(message).await?;
+
+        loop {
+            let line = self
+                .stdout
+                .next_line()
+                .await
+                .map_err(|e| format!("读取进程 stdout 失败: {}", e))?;
+
+            let Some(line) = line else {
+                let status = self
+                    .child
+                    .try_wait()
+                    .ok()
+                    .flatten()
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| "running".to_string());
+                return Err(format!("进程在响应前关闭了 stdout (状态: {})", status));
+            };
+
+            match serde_json::from_str::<Value>(line.trim()) {
+                Ok(message) => {
+                    if let Some(result) = find_response(&message, id) {
+                        return result;
+                    }
+                }
+                Err(_) => log::debug!("忽略 MCP 服务器的非 JSON 输出: {}", line),
+            }
+        }
+    }
+
+    fn stderr_tail(&self) -> Option<String> {
+        let tail = self.stderr.lock().unwrap_or_else(|e| e.into_inner());
+        if tail.is_empty() {
+            None
+        } else {
+            Some(tail.iter().cloned().collect::<Vec<_>>().join("\n"))
+        }
+    }
+}
+
+fn build_http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))
+}
+
+fn apply_headers(
+    mut builder: reqwest::RequestBuilder,
+    headers: &HashMap<String, String>,
+) -> reqwest::RequestBuilder {
+    for (key, value) in headers {
+        builder = builder.header(key.as_str(), expand_env_placeholder(value));
+    }
+    builder
+}
+
+/// 检查 HTTP 状态码，失败时附带响应体片段
+async fn ensure_success(response: reqwest::Response) -> Result<reqwest::Response, String> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    let snippet: String = body.chars().take(300).collect();
+    Err(format!("HTTP {}: {}", status, snippet))
+}
+
+/// Streamable HTTP 会话
+struct HttpSession {
+    client: reqwest::Client,
+    url: String,
+    headers: HashMap<String, String>,
+    session_id: Option<String>,
+}
+
+impl HttpSession {
+    fn new(url: &str, headers: &HashMap<String, String>) -> Result<Self, String> {
+        Ok(Self {
+            client: build_http_client()?,
+            url: url.to_string(),
+            headers: headers.clone(),
+            session_id: None,
+        })
+    }
+
+    async fn post(&mut self, message: &Value) -> Result<reqwest::Response, String> {
+        let mut builder = self
+            .client
+            .post(&self.url)
+            .header("Accept", "application/json, text/event-stream")
+            .json(message);
+        builder = apply_headers(builder, &self.headers);
+        if let Some(session_id) = &self.session_id {
+            builder = builder.header("Mcp-Session-Id", session_id.as_str());
+        }
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| format!("HTTP 请求失败: {}", e))?;
+
+        if let Some(session_id) = response
+            .headers()
+            .get("mcp-session-id")
+            .and_then(|v| v.to_str().ok())
+        {
+            self.session_id = Some(session_id.to_string());
+        }
+
+        ensure_success(response).await
+    }
+
+    async fn request(&mut self, id: u64, message: &Value) -> Result<Value, String> {
+        let response = self.post(message).await?;
+
+        let is_sse = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|ct| ct.contains("text/event-stream"))
+            .unwrap_or(false);
+
+        if !is_sse {
+            let body: Value = response
+                .json()
+                .await
+                .map_err(|e| format!("解析 JSON 响应失败: {}", e))?;
+            return find_response(&body, id)
+                .unwrap_or_else(|| Err("响应中没有匹配的 JSON-RPC 结果".to_string()));
+        }
+
+        let mut parser = SseParser::default();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| format!("读取 SSE 响应失败: {}", e))?;
+            for event in parser.push(&chunk) {
+                if let Ok(message) = serde_json::from_str::<Value>(&event.data) {
+                    if let Some(result) = find_response(&message, id) {
+                        return result;
+                    }
+                }
+            }
+        }
+
+        Err("SSE 响应结束前没有收到匹配的 JSON-RPC 结果".to_string())
+    }
+}
+
+/// 旧版 SSE 会话
+struct SseSession {
+    client: reqwest::Client,
+    post_url: String,
+    headers: HashMap<String, String>,
+    events: mpsc::Receiver<SseEvent>,
+    reader: JoinHandle<()>,
+}
+
+impl SseSession {
+    async fn connect(url: &str, headers: &HashMap<String, String>) -> Result<Self, String> {
+        let client = build_http_client()?;
+        let builder = apply_headers(client.get(url).header("Accept", "text/event-stream"), headers);
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| format!("建立 SSE 连接失败: {}", e))?;
+        let response = ensure_success(response).await?;
+
+        let (tx, mut events) = mpsc::channel(64);
+        let reader = tokio::spawn(async move {
+            let mut parser = SseParser::default();
+            let mut stream = response.bytes_stream();
+            while let Some(Ok(chunk)) = stream.next().await {
+                for event in parser.push(&chunk) {
+                    if tx.send(event).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        // 第一个 endpoint 事件给出 POST 消息的地址 (可能是相对路径)
+        let endpoint = loop {
+            match events.recv().await {
+                Some(event) if event.event.as_deref() == Some("endpoint") => break event.data,
+                Some(_) => continue,
+                None => {
+                    reader.abort();
+                    return Err("SSE 连接在收到 endpoint 事件前关闭".to_string());
+                }
+            }
+        };
+
+        let post_url = reqwest::Url::parse(url)
+            .and_then(|base| base.join(endpoint.trim()))
+            .map_err(|e| format!("无效的 endpoint 地址 '{}': {}", endpoint, e))?
+            .to_string();
+
+        Ok(Self {
+            client,
+            post_url,
+            headers: headers.clone(),
+            events,
+            reader,
+        })
+    }
+
+    async fn post(&mut self, message: &Value) -> Result<(), String> {
+        let builder = apply_headers(self.client.post(&self.post_url).json(message), &self.headers);
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| format!("HTTP 请求失败: {}", e))?;
+        ensure_success(response).await.map(|_| ())
+    }
+
+    async fn request(&mut self, id: u64, message: &Value) -> Result<Value, String> {
+        self.post(message).await?;
+
+        while let Some(event) = self.events.recv().await {
+            if let Ok(message) = serde_json::from_str::<Value>(&event.data) {
+                if let Some(result) = find_response(&message, id) {
+                    return result;
+                }
+            }
+        }
+
+        Err("SSE 连接在收到响应前关闭".to_string())
+    }
+}
+
+impl Drop for SseSession {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_target_from_config() {
+        let stdio = ProbeTarget::from_config(&json!({
+            "command": "npx",
+            "args": ["-y", "@modelcontextprotocol/server-git"],
+            "env": {"TOKEN": "x"}
+        }))
+        .unwrap();
+        assert_eq!(stdio.transport(), McpTransport::Stdio);
+        assert_eq!(stdio.describe(), "npx -y @modelcontextprotocol/server-git");
+
+        let sse = ProbeTarget::from_config(&json!({"url": "https://example.com/sse"})).unwrap();
+        assert_eq!(sse.transport(), McpTransport::Sse);
+
+        let http = ProbeTarget::from_config(&json!({"type": "http", "url": "https://example.com/mcp"})).unwrap();
+        assert_eq!(http.transport(), McpTransport::Http);
+
+        assert!(ProbeTarget::from_config(&json!({"args": []})).is_err());
+    }
+
+    #[test]
+    fn test_sse_parser_handles_split_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b"event: endpoint\r\ndata: /messages?session").is_empty());
+
+        let events = parser.push(b"_id=1\r\n\r\n: keepalive\n\ndata: {\"id\":1}\n\n");
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: Some("endpoint".to_string()),
+                    data: "/messages?session_id=1".to_string(),
+                },
+                SseEvent {
+                    event: None,
+                    data: "{\"id\":1}".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_response_and_errors() {
+        let ok = json!({"jsonrpc": "2.0", "id": 1, "result": {"tools": [{"name": "read", "description": "Read"}, {"x": 1}]}});
+        let result = find_response(&ok, 1).unwrap().unwrap();
+        let tools = parse_tools(&result);
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "read");
+
+        assert!(find_response(&ok, 2).is_none());
+        assert!(find_response(&json!({"jsonrpc": "2.0", "method": "notifications/message"}), 1).is_none());
+
+        let err = json!([{"jsonrpc": "2.0", "id": 3, "error": {"code": -32601, "message": "Method not found"}}]);
+        assert_eq!(
+            find_response(&err, 3).unwrap().unwrap_err(),
+            "Method not found (code -32601)"
+        );
+    }
+}
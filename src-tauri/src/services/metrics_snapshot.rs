@@ -0,0 +1,347 @@
+/**
+ * Metrics Snapshot Service
+ * 将内存中的代理请求指标 (METRICS) 定期持久化，提供轻量的历史吞吐量/延迟视图
+ *
+ * 每个快照记录的是上一周期内的增量 (请求数、平均延迟、token 数)，
+ * 而不是启动以来的累计值，因此应用重启或手动重置指标后数据仍可直接按时间范围汇总。
+ * 旧快照由日志保留策略 (LogRetentionService) 按相同的条数/天数限制清理。
+ */
+
+use crate::models::error::{AppError, AppResult};
+use crate::proxy::structured_logger::{ConfigMetrics, MetricsCollector, PerformanceMetrics};
+use crate::utils::time::{normalize_timestamp, now_rfc3339, NaiveZone};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 快照间隔上限（分钟）
+const MAX_SNAPSHOT_INTERVAL_MINUTES: i32 = 24 * 60;
+
+/// 单次查询返回的最大快照数
+const MAX_QUERY_LIMIT: i64 = 10000;
+
+/// 单个配置在一个周期内的指标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigMetricsSnapshot {
+    pub config_id: i64,
+    pub config_name: String,
+    pub total_requests: u64,
+    /// 非 2xx 响应数
+    pub failed_requests: u64,
+    pub avg_response_time_ms: f64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+}
+
+/// 一个周期内的代理指标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub id: Option<i64>,
+    /// 快照时间 (RFC3339)
+    pub captured_at: String,
+    /// 快照覆盖的时间长度（秒）
+    pub period_secs: i64,
+    pub total_requests: u64,
+    pub successful_requests: u64,
+    pub failed_requests: u64,
+    pub streaming_requests: u64,
+    pub avg_response_time_ms: f64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    /// 按配置拆分的指标
+    pub configs: Vec<ConfigMetricsSnapshot>,
+}
+
+/// 某一时刻的累计指标，用于计算两次快照之间的增量
+#[derive(Debug, Clone, Default)]
+pub struct MetricsBaseline {
+    totals: PerformanceMetrics,
+    configs: BTreeMap<i64, ConfigMetrics>,
+}
+
+impl MetricsBaseline {
+    /// 读取收集器当前的累计指标
+    pub fn capture(collector: &MetricsCollector) -> Self {
+        Self {
+            totals: collector.snapshot(),
+            configs: collector.config_snapshot(),
+        }
+    }
+
+    /// 计算自 `previous` 以来的增量，周期内没有请求时返回 None
+    ///
+    /// 累计值变小说明指标被重置过，此时以当前值作为增量
+    pub fn since(&self, previous: &MetricsBaseline, period_secs: i64) -> Option<MetricsSnapshot> {
+        let reset = self.totals.total_requests < previous.totals.total_requests;
+        let empty = MetricsBaseline::default();
+        let previous = if reset { &empty } else { previous };
+
+        let cur = &self.totals;
+        let prev = &previous.totals;
+        let total_requests = cur.total_requests.saturating_sub(prev.total_requests);
+        if total_requests == 0 {
+            return None;
+        }
+        let response_time_ms = cur.total_response_time_ms.saturating_sub(prev.total_response_time_ms);
+
+        let configs = self
+            .configs
+            .iter()
+            .filter_map(|(config_id, metrics)| {
+                let prev = previous.configs.get(config_id);
+                let count = |m: &ConfigMetrics| m.latency_count;
+                let requests = metrics.latency_count.saturating_sub(prev.map_or(0, count));
+                if requests == 0 {
+                    return None;
+                }
+                let failed = failed_count(metrics).saturating_sub(prev.map_or(0, failed_count));
+                let latency_ms = metrics.latency_sum_ms.saturating_sub(prev.map_or(0, |m| m.latency_sum_ms));
+                Some(ConfigMetricsSnapshot {
+                    config_id: *config_id,
+                    config_name: metrics.config_name.clone(),
+                    total_requests: requests,
+                    failed_requests: failed,
+                    avg_response_time_ms: latency_ms as f64 / requests as f64,
+                    input_tokens: metrics.input_tokens - prev.map_or(0, |m| m.input_tokens),
+                    output_tokens: metrics.output_tokens - prev.map_or(0, |m| m.output_tokens),
+                })
+            })
+            .collect();
+
+        Some(MetricsSnapshot {
+            id: None,
+            captured_at: now_rfc3339(),
+            period_secs,
+            total_requests,
+            successful_requests: cur.successful_requests.saturating_sub(prev.successful_requests),
+            failed_requests: cur.failed_requests.saturating_sub(prev.failed_requests),
+            streaming_requests: cur.streaming_requests.saturating_sub(prev.streaming_requests),
+            avg_response_time_ms: response_time_ms as f64 / total_requests as f64,
+            input_tokens: cur.total_input_tokens - prev.total_input_tokens,
+            output_tokens: cur.total_output_tokens - prev.total_output_tokens,
+            configs,
+        })
+    }
+}
+
+/// 非 2xx 响应数
+fn failed_count(metrics: &ConfigMetrics) -> u64 {
+    metrics
+        .requests_by_status
+        .iter()
+        .filter(|(status, _)| !(200..300).contains(*status))
+        .map(|(_, count)| count)
+        .sum()
+}
+
+/// 指标快照服务
+pub struct MetricsSnapshotService;
+
+impl MetricsSnapshotService {
+    /// 读取快照间隔（分钟），0 表示关闭
+    pub fn get_interval(conn: &Connection) -> AppResult<i32> {
+        conn.query_row(
+            "SELECT metrics_snapshot_interval_minutes FROM AppSettings WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("读取指标快照间隔失败: {}", e),
+        })
+    }
+
+    /// 设置快照间隔（分钟），0 表示关闭
+    pub fn set_interval(conn: &Connection, minutes: i32) -> AppResult<()> {
+        if !(0..=MAX_SNAPSHOT_INTERVAL_MINUTES).contains(&minutes) {
+            return Err(AppError::ValidationError {
+                field: "interval_minutes".to_string(),
+                message: format!("快照间隔必须在 0-{} 分钟之间 (0 表示关闭)", MAX_SNAPSHOT_INTERVAL_MINUTES),
+            });
+        }
+
+        let updated = conn
+            .execute(
+                "UPDATE AppSettings SET metrics_snapshot_interval_minutes = ?1, updated_at = CURRENT_TIMESTAMP
+                 WHERE id = 1",
+                params![minutes],
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("保存指标快照间隔失败: {}", e),
+            })?;
+
+        if updated == 0 {
+            return Err(AppError::NotFound {
+                resource: "AppSettings".to_string(),
+                id: "1".to_string(),
+            });
+        }
+
+        log::info!("指标快照间隔已设置为 {} 分钟", minutes);
+        Ok(())
+    }
+
+    /// 保存快照，返回快照 ID
+    pub fn save(conn: &Connection, snapshot: &MetricsSnapshot) -> AppResult<i64> {
+        let config_metrics = serde_json::to_string(&snapshot.configs).map_err(|e| AppError::DatabaseError {
+            message: format!("序列化配置指标失败: {}", e),
+        })?;
+
+        conn.execute(
+            "INSERT INTO MetricsSnapshot (
+                captured_at, period_secs, total_requests, successful_requests, failed_requests,
+                streaming_requests, avg_response_time_ms, input_tokens, output_tokens, config_metrics
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                snapshot.captured_at,
+                snapshot.period_secs,
+                snapshot.total_requests as i64,
+                snapshot.successful_requests as i64,
+                snapshot.failed_requests as i64,
+                snapshot.streaming_requests as i64,
+                snapshot.avg_response_time_ms,
+                snapshot.input_tokens,
+                snapshot.output_tokens,
+                config_metrics,
+            ],
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("保存指标快照失败: {}", e),
+        })?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// 按时间范围查询快照（按时间升序）
+    ///
+    /// `start` / `end` 为 RFC3339 时间，为空表示不限制
+    pub fn query(
+        conn: &Connection,
+        start: Option<&str>,
+        end: Option<&str>,
+        limit: i64,
+    ) -> AppResult<Vec<MetricsSnapshot>> {
+        let query_failed = |e: rusqlite::Error| AppError::DatabaseError {
+            message: format!("查询指标快照失败: {}", e),
+        };
+        // 参数可能带有时区偏移，规范化为 UTC 后才能与存储值按字符串比较
+        let normalize = |field: &str, value: Option<&str>| -> AppResult<Option<String>> {
+            value
+                .map(|v| {
+                    normalize_timestamp(v, NaiveZone::Utc).ok_or_else(|| AppError::ValidationError {
+                        field: field.to_string(),
+                        message: format!("无效的时间格式: {}", v),
+                    })
+                })
+                .transpose()
+        };
+        let start = normalize("start", start)?;
+        let end = normalize("end", end)?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, captured_at, period_secs, total_requests, successful_requests, failed_requests,
+                        streaming_requests, avg_response_time_ms, input_tokens, output_tokens, config_metrics
+                 FROM MetricsSnapshot
+                 WHERE (?1 IS NULL OR captured_at >= ?1) AND (?2 IS NULL OR captured_at <= ?2)
+                 ORDER BY captured_at ASC
+                 LIMIT ?3",
+            )
+            .map_err(query_failed)?;
+
+        let snapshots = stmt
+            .query_map(params![start, end, limit.clamp(1, MAX_QUERY_LIMIT)], |row| {
+                let config_metrics: String = row.get(10)?;
+                Ok(MetricsSnapshot {
+                    id: row.get(0)?,
+                    captured_at: row.get(1)?,
+                    period_secs: row.get(2)?,
+                    total_requests: row.get::<_, i64>(3)? as u64,
+                    successful_requests: row.get::<_, i64>(4)? as u64,
+                    failed_requests: row.get::<_, i64>(5)? as u64,
+                    streaming_requests: row.get::<_, i64>(6)? as u64,
+                    avg_response_time_ms: row.get(7)?,
+                    input_tokens: row.get(8)?,
+                    output_tokens: row.get(9)?,
+                    configs: serde_json::from_str(&config_metrics).unwrap_or_default(),
+                })
+            })
+            .map_err(query_failed)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(query_failed)?;
+
+        Ok(snapshots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::logger::ProxyLogger;
+    use hyper::{Method, StatusCode};
+
+    fn record(collector: &MetricsCollector, config_id: i64, status: StatusCode) {
+        let entry = ProxyLogger::start_request(Method::POST, "/v1/messages".parse().unwrap(), "127.0.0.1:1".to_string())
+            .with_config(config_id, format!("config-{}", config_id));
+        let entry = if status.is_success() {
+            entry.finish(status)
+        } else {
+            entry.finish_with_error(status, "upstream".to_string())
+        };
+        collector.record_entry(&entry);
+    }
+
+    #[test]
+    fn test_snapshot_records_increments() {
+        let collector = MetricsCollector::new();
+        let start = MetricsBaseline::capture(&collector);
+        assert!(MetricsBaseline::capture(&collector).since(&start, 60).is_none());
+
+        record(&collector, 1, StatusCode::OK);
+        record(&collector, 1, StatusCode::BAD_GATEWAY);
+        let first = MetricsBaseline::capture(&collector);
+        let snapshot = first.since(&start, 60).unwrap();
+        assert_eq!(snapshot.total_requests, 2);
+        assert_eq!(snapshot.failed_requests, 1);
+        assert_eq!(snapshot.configs[0].failed_requests, 1);
+
+        record(&collector, 2, StatusCode::OK);
+        let snapshot = MetricsBaseline::capture(&collector).since(&first, 60).unwrap();
+        assert_eq!(snapshot.total_requests, 1);
+        assert_eq!(snapshot.configs.len(), 1);
+        assert_eq!(snapshot.configs[0].config_id, 2);
+
+        // 重置后以当前值作为增量
+        collector.reset();
+        record(&collector, 1, StatusCode::OK);
+        let snapshot = MetricsBaseline::capture(&collector).since(&first, 60).unwrap();
+        assert_eq!(snapshot.total_requests, 1);
+    }
+
+    #[test]
+    fn test_save_and_query_snapshots() {
+        let conn = crate::db::test_db();
+        conn.execute("INSERT INTO AppSettings (id) VALUES (1)", []).unwrap();
+
+        assert_eq!(MetricsSnapshotService::get_interval(&conn).unwrap(), 15);
+        MetricsSnapshotService::set_interval(&conn, 0).unwrap();
+        assert!(MetricsSnapshotService::set_interval(&conn, -1).is_err());
+
+        let collector = MetricsCollector::new();
+        record(&collector, 1, StatusCode::OK);
+        let mut snapshot = MetricsBaseline::capture(&collector)
+            .since(&MetricsBaseline::default(), 900)
+            .unwrap();
+        for captured_at in ["2026-01-01T00:00:00+08:00", "2026-01-02T00:00:00+08:00"] {
+            snapshot.captured_at = captured_at.to_string();
+            MetricsSnapshotService::save(&conn, &snapshot).unwrap();
+        }
+
+        let all = MetricsSnapshotService::query(&conn, None, None, 100).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].configs, snapshot.configs);
+
+        let ranged = MetricsSnapshotService::query(&conn, Some("2026-01-01T12:00:00+08:00"), None, 100).unwrap();
+        assert_eq!(ranged.len(), 1);
+        assert_eq!(ranged[0].captured_at, "2026-01-02T00:00:00+08:00");
+    }
+}
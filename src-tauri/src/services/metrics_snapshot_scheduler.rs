@@ -0,0 +1,99 @@
+/**
+ * Metrics Snapshot Scheduler
+ * 按间隔将内存中的代理请求指标写入 MetricsSnapshot 表
+ *
+ * Features:
+ * - 每轮读取最新的快照间隔，修改间隔无需重启
+ * - 间隔为 0 时暂停写入，每分钟重新检查设置
+ * - 支持启动/停止调度器
+ */
+
+use crate::db::DbPool;
+use crate::models::error::AppResult;
+use crate::proxy::structured_logger::METRICS;
+use crate::services::metrics_snapshot::{MetricsBaseline, MetricsSnapshotService};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration, Instant};
+
+/// 快照关闭时重新检查设置的间隔
+const DISABLED_RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 代理指标快照调度器
+pub struct MetricsSnapshotScheduler {
+    db_pool: Arc<DbPool>,
+    task_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
+}
+
+impl MetricsSnapshotScheduler {
+    /// 创建新的指标快照调度器
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        Self {
+            db_pool,
+            task_handle: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// 启动调度器
+    pub async fn start(&self) -> AppResult<()> {
+        let mut task_handle = self.task_handle.write().await;
+
+        if task_handle.is_some() {
+            log::warn!("指标快照调度器已在运行");
+            return Ok(());
+        }
+
+        let db_pool = self.db_pool.clone();
+
+        let handle = tokio::spawn(async move {
+            log::info!("指标快照调度器后台任务已启动");
+
+            let mut baseline = MetricsBaseline::capture(&METRICS);
+            let mut baseline_at = Instant::now();
+
+            loop {
+                let interval_minutes = db_pool
+                    .with_connection(MetricsSnapshotService::get_interval)
+                    .unwrap_or_else(|e| {
+                        log::warn!("读取指标快照间隔失败，暂停快照: {}", e);
+                        0
+                    });
+
+                if interval_minutes <= 0 {
+                    // 关闭期间的指标不计入下一次快照
+                    sleep(DISABLED_RECHECK_INTERVAL).await;
+                    baseline = MetricsBaseline::capture(&METRICS);
+                    baseline_at = Instant::now();
+                    continue;
+                }
+
+                sleep(Duration::from_secs(interval_minutes as u64 * 60)).await;
+
+                let current = MetricsBaseline::capture(&METRICS);
+                let period_secs = baseline_at.elapsed().as_secs() as i64;
+                if let Some(snapshot) = current.since(&baseline, period_secs) {
+                    if let Err(e) = db_pool.with_connection(|conn| MetricsSnapshotService::save(conn, &snapshot)) {
+                        log::error!("保存指标快照失败: {}", e);
+                    }
+                }
+                baseline = current;
+                baseline_at = Instant::now();
+            }
+        });
+
+        *task_handle = Some(handle);
+
+        log::info!("指标快照调度器已启动");
+        Ok(())
+    }
+
+    /// 停止调度器
+    #[allow(dead_code)]
+    pub async fn stop(&self) {
+        if let Some(handle) = self.task_handle.write().await.take() {
+            handle.abort();
+            log::info!("指标快照调度器已停止");
+        }
+    }
+}
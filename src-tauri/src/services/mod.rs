@@ -1,37 +1,60 @@
 pub mod api_config;
 pub mod api_test;
+pub mod app_rollback;
 pub mod app_updater;
 pub mod auto_switch;
+pub mod backend_models;
 pub mod backup;
 pub mod balance_scheduler;
+pub mod balance_watch;
 pub mod balance_service;
 pub mod claude_config;
 pub mod claude_installer;
 pub mod claude_test_request;
 pub mod config_manager;
+pub mod config_reenable_scheduler;
+pub mod config_report;
 pub mod config_validator;
+pub mod conversion_self_test;
+pub mod curl_repro;
+pub mod db_maintenance;
 pub mod env_detection;
+pub mod env_snippet;
 pub mod env_var;
 pub mod error_classifier;
+pub mod failure_diagnosis;
+pub mod group_consistency;
 pub mod health_check_scheduler;
 pub mod health_check_service;
 pub mod keychain;
 pub mod latency_test;
+pub mod load_test;
+pub mod log_cleanup_scheduler;
+pub mod log_retention;
+pub mod log_sampling;
 pub mod mcp_config;
+pub mod mcp_probe;
+pub mod metrics_snapshot;
+pub mod metrics_snapshot_scheduler;
 pub mod model_mapping_service;
 pub mod node_scanner;
 pub mod permissions_config;
+pub mod port_diagnosis;
 pub mod project_context;
 pub mod provider_preset;
 pub mod proxy_log;
 pub mod proxy_service;
 pub mod recommendation;
 pub mod retry_manager;
+pub mod routing_snapshot;
+pub mod server_port_migration;
 pub mod session_config;
 pub mod slash_commands;
 pub mod pty_manager;
 pub mod status_notifier;
 pub mod terminal_session_service;
+pub mod test_comparison;
+pub mod timestamp_audit;
 pub mod weight_calculator;
 
 // 重新导出常用类型
@@ -40,10 +63,12 @@ pub use app_updater::{AppUpdater, AppVersionInfo};
 pub use backup::BackupService;
 pub use balance_service::BalanceService;
 pub use claude_config::{ClaudeConfigService, ProxyConfig};
-pub use claude_installer::{ClaudeInstaller, InstallMethod, InstallOptions, InstallProgress, VersionInfo};
+pub use claude_installer::{
+    ClaudeInstaller, InstallMethod, InstallOptions, InstallPlan, InstallProgress, VersionInfo,
+};
 pub use config_manager::ConfigManager;
 pub use config_validator::{ConfigValidator, ConfigValidationResult, EndpointTestResult};
-pub use env_detection::{EnhancedEnvironmentDetector, EnvironmentStatus};
+pub use env_detection::{EnhancedEnvironmentDetector, EnvironmentStatus, LlmEndpointReachability};
 pub use latency_test::LatencyTestService;
 pub use mcp_config::McpConfigService;
 pub use permissions_config::PermissionsConfigService;
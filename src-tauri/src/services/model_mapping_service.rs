@@ -443,7 +443,7 @@ impl ModelMappingService {
 
         Ok(ModelMappingExport {
             version: "1.0".to_string(),
-            exported_at: chrono::Utc::now().to_rfc3339(),
+            exported_at: crate::utils::time::now_rfc3339(),
             mappings: export_items,
         })
     }
@@ -560,6 +560,88 @@ impl ModelMappingService {
         })?
     }
 
+    /// 获取客户端未指定模型时注入的默认模型
+    pub async fn get_default_request_model(&self) -> AppResult<Option<String>> {
+        let pool = self.pool.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn_arc = pool.get_connection();
+            let conn = conn_arc.lock().unwrap();
+
+            conn.query_row(
+                "SELECT default_request_model FROM AppSettings WHERE id = 1",
+                [],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()
+            .map(Option::flatten)
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("读取默认模型失败: {}", e),
+            })
+        })
+        .await
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("读取默认模型任务失败: {}", e),
+        })?
+    }
+
+    /// 设置客户端未指定模型时注入的默认模型 (None 或空字符串表示清除)
+    pub async fn set_default_request_model(&self, model: Option<String>) -> AppResult<()> {
+        let model = model
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty());
+        let pool = self.pool.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn_arc = pool.get_connection();
+            let conn = conn_arc.lock().unwrap();
+
+            let updated = conn
+                .execute(
+                    "UPDATE AppSettings SET default_request_model = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+                    params![model],
+                )
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("更新默认模型失败: {}", e),
+                })?;
+
+            if updated == 0 {
+                return Err(AppError::NotFound {
+                    resource: "AppSettings".to_string(),
+                    id: "1".to_string(),
+                });
+            }
+
+            match &model {
+                Some(model) => log::info!("默认请求模型已设置为 {}", model),
+                None => log::info!("默认请求模型已清除"),
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("更新默认模型任务失败: {}", e),
+        })?
+    }
+
+    /// 查询默认请求模型（同步版本，用于 router 中的请求处理）
+    pub fn lookup_default_request_model(conn: &rusqlite::Connection) -> Option<String> {
+        match conn
+            .query_row(
+                "SELECT default_request_model FROM AppSettings WHERE id = 1",
+                [],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()
+        {
+            Ok(model) => model.flatten().filter(|m| !m.trim().is_empty()),
+            Err(e) => {
+                log::warn!("Default request model lookup error: {}", e);
+                None
+            }
+        }
+    }
+
     /// 查询模型映射（同步版本，用于 router 中的请求处理）
     ///
     /// # Arguments
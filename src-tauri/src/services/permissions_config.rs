@@ -1,10 +1,41 @@
-use crate::models::claude_advanced::PermissionsConfig;
+use crate::models::claude_advanced::{
+    PermissionRuleChange, PermissionRuleListDiff, PermissionsConfig, PermissionsDiff,
+};
 use crate::models::error::{AppError, AppResult};
 use crate::utils::paths;
 use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
 
+/// 已知的 Claude Code 内置工具名称 (权限规则 `Tool` 或 `Tool(specifier)` 中的 Tool 部分)
+///
+/// 新版本可能增加工具，不在列表中的名称只给出警告
+const KNOWN_TOOLS: &[&str] = &[
+    "Agent",
+    "Bash",
+    "BashOutput",
+    "Edit",
+    "ExitPlanMode",
+    "Glob",
+    "Grep",
+    "KillShell",
+    "LS",
+    "MultiEdit",
+    "NotebookEdit",
+    "NotebookRead",
+    "Read",
+    "Skill",
+    "SlashCommand",
+    "Task",
+    "TodoWrite",
+    "WebFetch",
+    "WebSearch",
+    "Write",
+];
+
+/// MCP 工具规则前缀 (`mcp__server` 或 `mcp__server__tool`)
+const MCP_RULE_PREFIX: &str = "mcp__";
+
 /// Permissions 配置管理服务
 pub struct PermissionsConfigService;
 
@@ -81,8 +112,132 @@ impl PermissionsConfigService {
         }
     }
 
+    /// 按 Claude Code 的权限规则格式校验配置
+    ///
+    /// 规则格式错误 (`Tool` / `Tool(specifier)` / `mcp__server[__tool]`) 与同一列表内的重复规则
+    /// 合并为一个 ValidationError 返回；未知的工具名称和同时出现在 allow 与 deny 中的规则
+    /// 不阻止写入，作为警告返回
+    pub fn validate_permissions_config(permissions: &PermissionsConfig) -> AppResult<Vec<String>> {
+        let mut problems = Vec::new();
+        let mut warnings = Vec::new();
+
+        for (list, rules) in [("allow", &permissions.allow), ("deny", &permissions.deny)] {
+            for (index, rule) in rules.iter().enumerate() {
+                match Self::validate_rule(rule) {
+                    Err(e) => problems.push(format!("{}[{}] \"{}\": {}", list, index, rule, e)),
+                    Ok(_) if rules[..index].contains(rule) => {
+                        problems.push(format!("{}[{}] \"{}\": 规则重复", list, index, rule))
+                    }
+                    Ok(Some(warning)) => warnings.push(format!("{}[{}] \"{}\": {}", list, index, rule, warning)),
+                    Ok(None) => {}
+                }
+            }
+        }
+
+        for rule in permissions.allow.iter().filter(|rule| permissions.deny.contains(rule)) {
+            warnings.push(format!("\"{}\" 同时出现在 allow 与 deny 中 (deny 优先，allow 规则不会生效)", rule));
+        }
+
+        if problems.is_empty() {
+            Ok(warnings)
+        } else {
+            Err(AppError::ValidationError {
+                field: "permissions".to_string(),
+                message: problems.join("; "),
+            })
+        }
+    }
+
+    /// 校验单条权限规则，格式正确时返回可能的警告
+    fn validate_rule(rule: &str) -> Result<Option<String>, String> {
+        if rule.trim().is_empty() {
+            return Err("规则不能为空".to_string());
+        }
+        if rule.trim() != rule {
+            return Err("规则首尾不能包含空白字符".to_string());
+        }
+
+        let (tool, specifier) = match rule.split_once('(') {
+            Some((tool, rest)) => {
+                let specifier = rest
+                    .strip_suffix(')')
+                    .ok_or_else(|| "括号未闭合，格式应为 Tool(specifier)".to_string())?;
+                if specifier.trim().is_empty() {
+                    return Err("括号内的匹配条件不能为空".to_string());
+                }
+                (tool, Some(specifier))
+            }
+            None if rule.contains(')') => return Err("括号不匹配".to_string()),
+            None => (rule, None),
+        };
+        if tool.is_empty() {
+            return Err("工具名称不能为空".to_string());
+        }
+
+        if let Some(mcp) = tool.strip_prefix(MCP_RULE_PREFIX) {
+            if mcp.is_empty() || mcp.split("__").any(str::is_empty) {
+                return Err("MCP 规则格式应为 mcp__server 或 mcp__server__tool".to_string());
+            }
+            if specifier.is_some() {
+                return Err("MCP 规则不支持括号内的匹配条件".to_string());
+            }
+            return Ok(None);
+        }
+
+        if tool == "WebFetch" && specifier.is_some_and(|s| !s.starts_with("domain:")) {
+            return Err("WebFetch 规则格式应为 WebFetch(domain:example.com)".to_string());
+        }
+        if !KNOWN_TOOLS.contains(&tool) {
+            return Ok(Some(format!("未知的工具名称 \"{}\" (区分大小写)", tool)));
+        }
+        Ok(None)
+    }
+
+    /// 比较拟写入的配置与当前 settings.json 中的配置
+    pub fn diff_permissions_config(new_permissions: &PermissionsConfig) -> AppResult<PermissionsDiff> {
+        let current = Self::read_permissions()?;
+        Ok(Self::diff_permissions(&current, new_permissions))
+    }
+
+    /// 计算两份配置的差异
+    pub fn diff_permissions(current: &PermissionsConfig, new_permissions: &PermissionsConfig) -> PermissionsDiff {
+        let allow = Self::diff_rules(&current.allow, &new_permissions.allow);
+        let deny = Self::diff_rules(&current.deny, &new_permissions.deny);
+        let may_broaden = !allow.added.is_empty()
+            || !allow.modified.is_empty()
+            || !deny.removed.is_empty()
+            || !deny.modified.is_empty();
+        PermissionsDiff { allow, deny, may_broaden }
+    }
+
+    /// 比较规则列表：同一工具一条移除、一条新增的规则按顺序配对为修改
+    fn diff_rules(current: &[String], new_rules: &[String]) -> PermissionRuleListDiff {
+        let mut removed: Vec<String> = current.iter().filter(|r| !new_rules.contains(r)).cloned().collect();
+        let mut added: Vec<String> = new_rules.iter().filter(|r| !current.contains(r)).cloned().collect();
+        let mut modified = Vec::new();
+
+        let tool_of = |rule: &str| rule.split_once('(').map_or(rule, |(tool, _)| tool).to_string();
+        let mut i = 0;
+        while i < removed.len() {
+            let tool = tool_of(&removed[i]);
+            match added.iter().position(|rule| tool_of(rule) == tool) {
+                Some(j) => modified.push(PermissionRuleChange {
+                    from: removed.remove(i),
+                    to: added.remove(j),
+                }),
+                None => i += 1,
+            }
+        }
+
+        PermissionRuleListDiff { added, removed, modified }
+    }
+
     /// 写入 Permissions 配置
     pub fn write_permissions(permissions: &PermissionsConfig) -> AppResult<()> {
+        for warning in Self::validate_permissions_config(permissions)? {
+            log::warn!("Permissions 配置警告: {}", warning);
+        }
+
         let mut settings = Self::read_settings()?;
 
         // 更新 permissions 字段
@@ -127,4 +282,95 @@ mod tests {
         assert!(config.allow.is_empty());
         assert!(config.deny.is_empty());
     }
+
+    fn config(allow: &[&str], deny: &[&str]) -> PermissionsConfig {
+        PermissionsConfig {
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+            deny: deny.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_validate_permissions_config() {
+        let valid = config(
+            &["Bash(npm run test:*)", "Read", "WebFetch(domain:docs.rs)", "mcp__github", "mcp__github__get_issue"],
+            &["Read(./.env)", "Bash(curl:*)"],
+        );
+        assert_eq!(PermissionsConfigService::validate_permissions_config(&valid).unwrap(), Vec::<String>::new());
+        assert!(PermissionsConfigService::validate_permissions_config(&PermissionsConfig::default()).is_ok());
+
+        // 未知工具只给出警告
+        let newer_tools = config(&["SlashCommand", "BashOutput", "KillShell", "ExitPlanMode", "Skill"], &[]);
+        assert!(PermissionsConfigService::validate_permissions_config(&newer_tools).unwrap().is_empty());
+        let warnings = PermissionsConfigService::validate_permissions_config(&config(&["FutureTool(x)"], &[])).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("未知的工具名称 \"FutureTool\""));
+
+        for rule in [
+            "",
+            " Read",
+            "(npm)",
+            "Bash(npm",
+            "Bash()",
+            "Read)",
+            "mcp__",
+            "mcp__github__",
+            "mcp__github(x)",
+            "WebFetch(https://docs.rs)",
+        ] {
+            assert!(
+                PermissionsConfigService::validate_permissions_config(&config(&[rule], &[])).is_err(),
+                "rule {:?} should be rejected",
+                rule
+            );
+        }
+    }
+
+    #[test]
+    fn test_diff_permissions() {
+        let current = config(&["Read", "Bash(npm test)", "WebSearch"], &["Read(./.env)"]);
+        let proposed = config(&["Read", "Bash(npm run:*)", "Write"], &[]);
+
+        let diff = PermissionsConfigService::diff_permissions(&current, &proposed);
+        assert!(diff.has_changes());
+        assert!(diff.may_broaden);
+        assert_eq!(diff.allow.added, vec!["Write"]);
+        assert_eq!(diff.allow.removed, vec!["WebSearch"]);
+        assert_eq!(
+            diff.allow.modified,
+            vec![PermissionRuleChange {
+                from: "Bash(npm test)".to_string(),
+                to: "Bash(npm run:*)".to_string(),
+            }]
+        );
+        assert_eq!(diff.deny.removed, vec!["Read(./.env)"]);
+
+        // 只收紧权限
+        let narrowed = config(&["Read"], &["Read(./.env)", "Bash(rm:*)"]);
+        let diff = PermissionsConfigService::diff_permissions(&current, &narrowed);
+        assert!(!diff.may_broaden);
+        assert_eq!(diff.deny.added, vec!["Bash(rm:*)"]);
+
+        let diff = PermissionsConfigService::diff_permissions(&current, &current);
+        assert!(!diff.has_changes());
+    }
+
+    #[test]
+    fn test_validate_reports_all_problems() {
+        let invalid = config(&["Read", "Read", "Bash("], &["Read"]);
+        match PermissionsConfigService::validate_permissions_config(&invalid) {
+            Err(AppError::ValidationError { message, .. }) => {
+                assert!(message.contains("allow[1] \"Read\": 规则重复"));
+                assert!(message.contains("allow[2] \"Bash(\": 括号未闭合"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // allow 与 deny 重叠只是警告
+        let overlapping = config(&["Read", "Shell"], &["Read"]);
+        let warnings = PermissionsConfigService::validate_permissions_config(&overlapping).unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("allow[1] \"Shell\": 未知的工具名称"));
+        assert!(warnings[1].contains("同时出现在 allow 与 deny 中"));
+    }
 }
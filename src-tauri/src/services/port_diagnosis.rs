@@ -0,0 +1,294 @@
+/**
+ * Port Conflict Diagnosis
+ * 检查代理端口是否被占用，并识别占用端口的进程
+ *
+ * 代理启动时端口被占用会自动改用后续端口 (见 ProxyServer::start)，
+ * 此处用于向用户解释自动切换的原因，例如崩溃后残留的旧代理进程仍占用端口。
+ *
+ * 占用进程识别:
+ * - macOS / Linux: `lsof`，Linux 上不可用时回退到 `ss`
+ * - Windows: `netstat -ano` 获取 PID，`tasklist` 获取进程名
+ */
+
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// 占用端口的进程
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortHolder {
+    pub pid: u32,
+    pub process_name: Option<String>,
+}
+
+/// 端口冲突诊断结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortConflictDiagnosis {
+    pub host: String,
+    pub port: u16,
+    /// 端口是否被占用
+    pub in_use: bool,
+    /// 是否由本应用自身占用 (代理正在该端口上运行)
+    pub held_by_self: bool,
+    /// 占用端口的进程，无法识别时为空
+    pub holders: Vec<PortHolder>,
+    /// 当前平台能否识别占用进程 (所需的系统工具不可用时为 false)
+    pub holder_lookup_supported: bool,
+    /// 代理实际监听的端口 (代理未运行时为 None)
+    pub active_port: Option<u16>,
+    /// 面向用户的说明
+    pub message: String,
+}
+
+/// 端口冲突诊断服务
+pub struct PortDiagnosisService;
+
+impl PortDiagnosisService {
+    /// 诊断端口占用情况
+    ///
+    /// # 参数
+    /// - `host`: 代理监听地址
+    /// - `port`: 要检查的端口
+    /// - `active_port`: 代理当前实际监听的端口 (未运行时为 None)
+    pub fn diagnose(host: &str, port: u16, active_port: Option<u16>) -> PortConflictDiagnosis {
+        let held_by_self = active_port == Some(port);
+        let in_use = held_by_self || Self::is_port_in_use(host, port);
+
+        let (holders, holder_lookup_supported) = if in_use && !held_by_self {
+            match Self::find_port_holders(port) {
+                Some(holders) => (holders, true),
+                None => (Vec::new(), false),
+            }
+        } else {
+            (Vec::new(), true)
+        };
+
+        let message = Self::describe(port, in_use, held_by_self, &holders, active_port);
+        if in_use && !held_by_self {
+            log::warn!("{}", message);
+        }
+
+        PortConflictDiagnosis {
+            host: host.to_string(),
+            port,
+            in_use,
+            held_by_self,
+            holders,
+            holder_lookup_supported,
+            active_port,
+            message,
+        }
+    }
+
+    /// 尝试绑定端口判断是否被占用
+    fn is_port_in_use(host: &str, port: u16) -> bool {
+        match std::net::TcpListener::bind((host, port)) {
+            Ok(_) => false,
+            Err(e) => e.kind() == std::io::ErrorKind::AddrInUse,
+        }
+    }
+
+    /// 查找监听端口的进程，系统工具不可用时返回 None
+    fn find_port_holders(port: u16) -> Option<Vec<PortHolder>> {
+        #[cfg(target_os = "windows")]
+        {
+            let netstat = command_output("netstat", &["-ano", "-p", "TCP"])?;
+            let holders = parse_netstat_output(&netstat, port)
+                .into_iter()
+                .map(|pid| PortHolder {
+                    pid,
+                    process_name: command_output("tasklist", &["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+                        .and_then(|output| parse_tasklist_output(&output)),
+                })
+                .collect();
+            Some(holders)
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let port_filter = format!("-iTCP:{}", port);
+            // lsof 在没有匹配进程时也以非零状态退出，只有命令本身不可用才视为不支持
+            if let Some(output) = command_output_any_status("lsof", &["-nP", &port_filter, "-sTCP:LISTEN", "-Fpc"]) {
+                return Some(parse_lsof_output(&output));
+            }
+            if cfg!(target_os = "linux") {
+                let output = command_output("ss", &["-ltnpH", &format!("sport = :{}", port)])?;
+                return Some(parse_ss_output(&output));
+            }
+            None
+        }
+    }
+
+    /// 生成面向用户的说明
+    fn describe(
+        port: u16,
+        in_use: bool,
+        held_by_self: bool,
+        holders: &[PortHolder],
+        active_port: Option<u16>,
+    ) -> String {
+        if held_by_self {
+            return format!("端口 {} 由正在运行的代理使用", port);
+        }
+        if !in_use {
+            return format!("端口 {} 未被占用", port);
+        }
+
+        let holder = match holders {
+            [] => format!("端口 {} 已被其他程序占用 (无法识别占用进程)", port),
+            holders => format!(
+                "端口 {} 被 {} 占用",
+                port,
+                holders
+                    .iter()
+                    .map(|h| match &h.process_name {
+                        Some(name) => format!("PID {} ({})", h.pid, name),
+                        None => format!("PID {}", h.pid),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("、")
+            ),
+        };
+        match active_port {
+            Some(active) => format!("{}，代理已自动改用端口 {}", holder, active),
+            None => format!("{}，代理启动时会自动改用后续的空闲端口", holder),
+        }
+    }
+}
+
+/// 执行命令并返回成功时的标准输出
+fn command_output(cmd: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(cmd).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 执行命令并返回标准输出，忽略退出状态
+#[cfg(not(target_os = "windows"))]
+fn command_output_any_status(cmd: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(cmd).args(args).output().ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 解析 `lsof -F pc` 输出: `p<pid>` 行后跟 `c<进程名>` 行
+fn parse_lsof_output(output: &str) -> Vec<PortHolder> {
+    let mut holders: Vec<PortHolder> = Vec::new();
+    for line in output.lines() {
+        if let Some(pid) = line.strip_prefix('p').and_then(|v| v.trim().parse().ok()) {
+            holders.push(PortHolder { pid, process_name: None });
+        } else if let Some(name) = line.strip_prefix('c') {
+            if let Some(holder) = holders.last_mut() {
+                holder.process_name = Some(name.trim().to_string());
+            }
+        }
+    }
+    holders
+}
+
+/// 解析 `ss -ltnp` 输出中的 `users:(("node",pid=1234,fd=20))`
+fn parse_ss_output(output: &str) -> Vec<PortHolder> {
+    let mut holders: Vec<PortHolder> = Vec::new();
+    for user in output.split("((").skip(1).flat_map(|users| users.split("),(")) {
+        let mut parts = user.split(',');
+        let name = parts.next().map(|n| n.trim_matches('"').to_string());
+        let pid = parts.find_map(|p| p.strip_prefix("pid=")).and_then(|p| p.parse().ok());
+        if let Some(pid) = pid {
+            if !holders.iter().any(|h| h.pid == pid) {
+                holders.push(PortHolder { pid, process_name: name });
+            }
+        }
+    }
+    holders
+}
+
+/// 解析 `netstat -ano` 输出，返回监听指定端口的 PID
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_netstat_output(output: &str, port: u16) -> Vec<u32> {
+    let suffix = format!(":{}", port);
+    let mut pids = Vec::new();
+    for line in output.lines() {
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        // 协议  本地地址  外部地址  状态  PID
+        let [_, local, _, state, pid] = columns.as_slice() else {
+            continue;
+        };
+        if !local.ends_with(&suffix) || !state.eq_ignore_ascii_case("LISTENING") {
+            continue;
+        }
+        if let Ok(pid) = pid.parse::<u32>() {
+            if !pids.contains(&pid) {
+                pids.push(pid);
+            }
+        }
+    }
+    pids
+}
+
+/// 解析 `tasklist /FO CSV /NH` 输出的第一列进程名
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_tasklist_output(output: &str) -> Option<String> {
+    let line = output.lines().next()?.trim();
+    let name = line.strip_prefix('"')?.split('"').next()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_holder_outputs() {
+        assert_eq!(
+            parse_lsof_output("p1234\ncnode\np5678\ncclaude-code-proxy\n"),
+            vec![
+                PortHolder { pid: 1234, process_name: Some("node".to_string()) },
+                PortHolder { pid: 5678, process_name: Some("claude-code-proxy".to_string()) },
+            ]
+        );
+        assert_eq!(
+            parse_ss_output(
+                "LISTEN 0 128 127.0.0.1:25341 0.0.0.0:* users:((\"node\",pid=1234,fd=20),(\"node\",pid=1234,fd=21))\n"
+            ),
+            vec![PortHolder { pid: 1234, process_name: Some("node".to_string()) }]
+        );
+
+        let netstat = "\
+  Proto  Local Address          Foreign Address        State           PID
+  TCP    127.0.0.1:25341        0.0.0.0:0              LISTENING       4321
+  TCP    127.0.0.1:253410       0.0.0.0:0              LISTENING       1
+  TCP    127.0.0.1:25341        127.0.0.1:50000        ESTABLISHED     4321
+";
+        assert_eq!(parse_netstat_output(netstat, 25341), vec![4321]);
+        assert_eq!(
+            parse_tasklist_output("\"node.exe\",\"4321\",\"Console\",\"1\",\"50,000 K\"\r\n"),
+            Some("node.exe".to_string())
+        );
+        assert_eq!(parse_tasklist_output("INFO: No tasks are running"), None);
+    }
+
+    #[test]
+    fn test_diagnose_port() {
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // 系统分配的端口可能是 65535，备用端口不能直接 +1
+        let other_port = port.checked_add(1).unwrap_or(port - 1);
+
+        let diagnosis = PortDiagnosisService::diagnose("127.0.0.1", port, Some(other_port));
+        assert!(diagnosis.in_use);
+        assert!(!diagnosis.held_by_self);
+        assert!(diagnosis.message.contains(&format!("自动改用端口 {}", other_port)));
+        // 能识别占用进程时应包含测试进程自身
+        if diagnosis.holder_lookup_supported && !diagnosis.holders.is_empty() {
+            assert!(diagnosis.holders.iter().any(|h| h.pid == std::process::id()));
+        }
+
+        let diagnosis = PortDiagnosisService::diagnose("127.0.0.1", port, Some(port));
+        assert!(diagnosis.held_by_self);
+        assert!(diagnosis.holders.is_empty());
+
+        drop(listener);
+        let diagnosis = PortDiagnosisService::diagnose("127.0.0.1", port, None);
+        assert!(!diagnosis.in_use);
+    }
+}
@@ -1,5 +1,8 @@
 use crate::models::error::{AppError, AppResult};
-use crate::models::provider_preset::{ProviderCategory, ProviderConfig, ProviderPreset};
+use crate::models::provider_preset::{
+    EmbeddedProvidersReport, ProviderCategory, ProviderConfig, ProviderPreset,
+};
+use std::collections::HashSet;
 
 /// 内嵌的 providers.json 配置文件
 /// 在编译时将配置文件内容嵌入到二进制文件中，避免打包后找不到文件
@@ -59,6 +62,14 @@ impl ProviderPresetService {
             .collect())
     }
 
+    /// 校验内嵌的 providers.json
+    ///
+    /// 远程获取失败时推荐服务回退到内嵌配置，内嵌配置损坏时回退也会失败，
+    /// 因此在启动时检查，避免在网络故障时才发现
+    pub fn validate_embedded_providers() -> EmbeddedProvidersReport {
+        validate_providers_json(EMBEDDED_PROVIDERS_JSON)
+    }
+
     /// 获取所有分类
     pub fn get_all_categories() -> Vec<ProviderCategory> {
         vec![
@@ -71,6 +82,93 @@ impl ProviderPresetService {
     }
 }
 
+/// 解析并校验 providers.json 内容
+fn validate_providers_json(content: &str) -> EmbeddedProvidersReport {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    let config: ProviderConfig = match serde_json::from_str(content) {
+        Ok(config) => config,
+        Err(e) => {
+            return EmbeddedProvidersReport {
+                usable: false,
+                version: None,
+                provider_count: 0,
+                recommended_count: 0,
+                errors: vec![format!("解析配置文件失败: {}", e)],
+                warnings,
+            };
+        }
+    };
+
+    if config.version.trim().is_empty() {
+        warnings.push("缺少配置版本 version".to_string());
+    }
+    if config.providers.is_empty() {
+        errors.push("供应商列表为空".to_string());
+    }
+
+    let mut seen_ids = HashSet::new();
+    for (index, provider) in config.providers.iter().enumerate() {
+        let label = if provider.id.trim().is_empty() {
+            format!("第 {} 个供应商", index + 1)
+        } else {
+            format!("供应商 {}", provider.id)
+        };
+
+        if provider.id.trim().is_empty() {
+            errors.push(format!("{}: 缺少 id", label));
+        } else if !seen_ids.insert(provider.id.as_str()) {
+            errors.push(format!("{}: id 重复", label));
+        }
+        if provider.name.trim().is_empty() {
+            errors.push(format!("{}: 缺少名称", label));
+        }
+        if !is_http_url(&provider.server_url) {
+            errors.push(format!("{}: 服务器地址无效: {}", label, provider.server_url));
+        }
+        if !is_http_url(&provider.website_url) {
+            warnings.push(format!("{}: 网站地址无效: {}", label, provider.website_url));
+        }
+        for candidate in provider.endpoint_candidates.iter().filter(|c| !is_http_url(c)) {
+            warnings.push(format!("{}: 备选服务器地址无效: {}", label, candidate));
+        }
+        if !(0..=100).contains(&provider.hotness_score) {
+            warnings.push(format!(
+                "{}: 热度分数 {} 超出 0-100 范围，将被截断",
+                label, provider.hotness_score
+            ));
+        }
+        if !matches!(provider.region.as_str(), "domestic" | "international") {
+            warnings.push(format!("{}: 未知的服务区域: {}", label, provider.region));
+        }
+    }
+
+    let recommended_count = config
+        .providers
+        .iter()
+        .filter(|provider| provider.show_in_recommendations)
+        .count();
+    if !config.providers.is_empty() && recommended_count == 0 {
+        errors.push("没有在推荐服务页面显示的供应商，回退时推荐列表为空".to_string());
+    }
+
+    EmbeddedProvidersReport {
+        usable: errors.is_empty(),
+        version: Some(config.version),
+        provider_count: config.providers.len(),
+        recommended_count,
+        errors,
+        warnings,
+    }
+}
+
+/// 是否为有效的 http(s) 地址
+fn is_http_url(url: &str) -> bool {
+    reqwest::Url::parse(url.trim())
+        .is_ok_and(|parsed| matches!(parsed.scheme(), "http" | "https") && parsed.host_str().is_some())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,6 +181,35 @@ mod tests {
         assert!(categories.contains(&ProviderCategory::CnOfficial));
     }
 
+    #[test]
+    fn test_validate_embedded_providers() {
+        let report = ProviderPresetService::validate_embedded_providers();
+        assert!(report.usable, "内嵌 providers.json 不可用: {:?}", report.errors);
+        assert!(report.recommended_count > 0);
+    }
+
+    #[test]
+    fn test_validate_providers_json_reports_issues() {
+        let report = validate_providers_json("{ not json");
+        assert!(!report.usable);
+        assert!(report.version.is_none());
+
+        let report = validate_providers_json(
+            r#"{"version":"1","providers":[
+                {"id":"a","name":"A","category":"official","websiteUrl":"https://a.example.com",
+                 "serverUrl":"https://api.a.example.com","hotnessScore":150},
+                {"id":"a","name":"","category":"custom","websiteUrl":"",
+                 "serverUrl":"api.example.com","showInRecommendations":false}
+            ]}"#,
+        );
+        assert!(!report.usable);
+        assert_eq!(report.provider_count, 2);
+        assert_eq!(report.recommended_count, 1);
+        assert_eq!(report.errors.len(), 3, "{:?}", report.errors);
+        assert!(report.errors.iter().any(|e| e.contains("id 重复")));
+        assert!(report.warnings.iter().any(|w| w.contains("热度分数")));
+    }
+
     // 注意：以下测试需要 config/providers.json 文件存在
     #[test]
     #[ignore] // 默认忽略，需要时手动运行
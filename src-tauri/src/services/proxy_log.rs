@@ -6,9 +6,30 @@
 use crate::db::DbPool;
 use crate::models::error::{AppError, AppResult};
 use crate::proxy::logger::RequestLogEntry;
-use rusqlite::params;
+use crate::proxy::stream_converter::StreamIntegrityReport;
+use crate::proxy::token_usage::TokenUsage;
+use crate::utils::time::{now_rfc3339, rfc3339_ago, to_rfc3339_utc};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 
+/// 日志表中直接保存的请求体/响应体最大字节数
+pub const INLINE_BODY_LIMIT: usize = 8192;
+
+/// ProxyRequestBody 侧表中保存的完整请求体/响应体最大字节数
+pub const FULL_BODY_LIMIT: usize = 1024 * 1024;
+
+/// 按字节数截断内容（不会截断在 UTF-8 字符中间），截断时追加标记
+pub fn truncate_body(body: &str, limit: usize) -> String {
+    if body.len() <= limit {
+        return body.to_string();
+    }
+    let mut end = limit;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...(truncated)", &body[..end])
+}
+
 /// 代理请求日志记录（简要版本，用于列表展示）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyRequestLog {
@@ -29,6 +50,10 @@ pub struct ProxyRequestLog {
     pub model: Option<String>,
     pub request_body_size: i64,
     pub response_body_size: i64,
+    pub request_id: Option<String>,
+    /// token 用量（区分后端报告值与本地估算值）
+    #[serde(flatten)]
+    pub token_usage: TokenUsage,
 }
 
 /// 代理请求日志详情（完整版本，用于详情展示）
@@ -61,6 +86,34 @@ pub struct ProxyRequestLogDetail {
     pub content_type: Option<String>,
     pub user_agent: Option<String>,
     pub model: Option<String>,
+    pub request_id: Option<String>,
+    pub stream_integrity: Option<StreamIntegrityReport>,
+    /// token 用量（区分后端报告值与本地估算值）
+    #[serde(flatten)]
+    pub token_usage: TokenUsage,
+}
+
+/// 流完整性异常的请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamIntegrityIssue {
+    pub log_id: i64,
+    pub request_at: String,
+    pub request_id: Option<String>,
+    pub model: Option<String>,
+    pub report: StreamIntegrityReport,
+}
+
+/// 按配置汇总的流完整性异常
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigStreamIntegrity {
+    pub config_id: Option<i64>,
+    pub config_name: Option<String>,
+    /// 已检查的流式请求数
+    pub total_streams: i64,
+    /// 存在完整性问题的流式请求数
+    pub problem_count: i64,
+    /// 最近的异常请求（按时间倒序）
+    pub issues: Vec<StreamIntegrityIssue>,
 }
 
 /// 代理请求日志服务
@@ -68,11 +121,30 @@ pub struct ProxyRequestLogService;
 
 impl ProxyRequestLogService {
     /// 保存请求日志到数据库（包含所有详细信息）
+    /// 开启完整请求体日志时，日志表只保留前 8KB，超出部分的完整内容存入 ProxyRequestBody
     /// 自动清理：每个服务商(config_id)只保留最近100条记录
+    /// 写入使用日志专用连接，不占用转发时读取配置的主连接
     pub fn save_log(pool: &DbPool, entry: &RequestLogEntry) -> AppResult<i64> {
         let config_id = entry.config_id;
 
-        let id = pool.with_connection(|conn| {
+        let id = pool.with_log_connection(|conn| {
+            let log_bodies = Self::body_logging_enabled(conn);
+            let log_full_bodies = log_bodies && Self::full_body_logging_enabled(conn);
+
+            // 未开启完整请求体日志时请求体保持原样保存
+            let request_body = entry.request_body.as_deref().filter(|_| log_bodies).map(|body| {
+                if log_full_bodies {
+                    truncate_body(body, INLINE_BODY_LIMIT)
+                } else {
+                    body.to_string()
+                }
+            });
+            let response_body = entry
+                .response_body
+                .as_deref()
+                .filter(|_| log_bodies)
+                .map(|body| truncate_body(body, INLINE_BODY_LIMIT));
+
             conn.execute(
                 r#"
                 INSERT INTO ProxyRequestLog (
@@ -81,11 +153,12 @@ impl ProxyRequestLogService {
                     request_headers, request_body, response_headers, response_body,
                     response_start_at, response_end_at, request_body_size, response_body_size,
                     is_streaming, stream_chunk_count, time_to_first_byte_ms,
-                    content_type, user_agent, model
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    content_type, user_agent, model, request_id,
+                    input_tokens, output_tokens, input_tokens_estimated, output_tokens_estimated
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 "#,
                 params![
-                    entry.timestamp.to_rfc3339(),
+                    to_rfc3339_utc(&entry.timestamp),
                     entry.method.to_string(),
                     entry.uri.to_string(),
                     entry.target_url,
@@ -97,11 +170,11 @@ impl ProxyRequestLogService {
                     entry.error,
                     entry.remote_addr,
                     entry.request_headers,
-                    entry.request_body,
+                    request_body,
                     entry.response_headers,
-                    entry.response_body,
-                    entry.response_start_at.map(|t| t.to_rfc3339()),
-                    entry.response_end_at.map(|t| t.to_rfc3339()),
+                    response_body,
+                    entry.response_start_at.as_ref().map(to_rfc3339_utc),
+                    entry.response_end_at.as_ref().map(to_rfc3339_utc),
                     entry.request_body_size as i64,
                     entry.response_body_size as i64,
                     entry.is_streaming,
@@ -110,6 +183,11 @@ impl ProxyRequestLogService {
                     entry.content_type,
                     entry.user_agent,
                     entry.model,
+                    entry.request_id,
+                    entry.token_usage.input_tokens,
+                    entry.token_usage.output_tokens,
+                    entry.token_usage.input_tokens_estimated,
+                    entry.token_usage.output_tokens_estimated,
                 ],
             )
             .map_err(|e| AppError::DatabaseError {
@@ -117,12 +195,22 @@ impl ProxyRequestLogService {
             })?;
 
             let id = conn.last_insert_rowid();
+
+            if log_full_bodies {
+                if let Some(body) = &entry.request_body {
+                    Self::save_full_body(conn, id, "request", body)?;
+                }
+                if let Some(body) = &entry.response_body {
+                    Self::save_full_body(conn, id, "response", body)?;
+                }
+            }
+
             Ok(id)
         })?;
 
         // 自动清理：每个服务商(config_id)只保留最近100条记录
         if let Some(cid) = config_id {
-            pool.with_connection(|conn| {
+            pool.with_log_connection(|conn| {
                 let count: i64 = conn
                     .query_row(
                         "SELECT COUNT(*) FROM ProxyRequestLog WHERE config_id = ?",
@@ -146,6 +234,7 @@ impl ProxyRequestLogService {
                     ).unwrap_or(0);
 
                     if deleted > 0 {
+                        Self::delete_orphan_bodies(conn);
                         log::info!(
                             "自动清理代理请求日志: config_id={}, 保留最近100条，删除了 {} 条旧记录",
                             cid, deleted
@@ -171,7 +260,8 @@ impl ProxyRequestLogService {
                     r#"
                     SELECT id, request_at, method, uri, target_url, config_id, config_name,
                            latency_ms, status_code, is_success, error_message, remote_addr,
-                           is_streaming, model, request_body_size, response_body_size
+                           is_streaming, model, request_body_size, response_body_size, request_id,
+                           input_tokens, output_tokens, input_tokens_estimated, output_tokens_estimated
                     FROM ProxyRequestLog
                     WHERE config_id = ?
                     ORDER BY request_at DESC
@@ -201,6 +291,8 @@ impl ProxyRequestLogService {
                         model: row.get(13)?,
                         request_body_size: row.get::<_, Option<i64>>(14)?.unwrap_or(0),
                         response_body_size: row.get::<_, Option<i64>>(15)?.unwrap_or(0),
+                        request_id: row.get(16)?,
+                        token_usage: Self::map_token_usage(row, 17)?,
                     })
                 })
                 .map_err(|e| AppError::DatabaseError {
@@ -225,7 +317,8 @@ impl ProxyRequestLogService {
                     r#"
                     SELECT id, request_at, method, uri, target_url, config_id, config_name,
                            latency_ms, status_code, is_success, error_message, remote_addr,
-                           is_streaming, model, request_body_size, response_body_size
+                           is_streaming, model, request_body_size, response_body_size, request_id,
+                           input_tokens, output_tokens, input_tokens_estimated, output_tokens_estimated
                     FROM ProxyRequestLog
                     ORDER BY request_at DESC
                     LIMIT ? OFFSET ?
@@ -254,6 +347,8 @@ impl ProxyRequestLogService {
                         model: row.get(13)?,
                         request_body_size: row.get::<_, Option<i64>>(14)?.unwrap_or(0),
                         response_body_size: row.get::<_, Option<i64>>(15)?.unwrap_or(0),
+                        request_id: row.get(16)?,
+                        token_usage: Self::map_token_usage(row, 17)?,
                     })
                 })
                 .map_err(|e| AppError::DatabaseError {
@@ -277,7 +372,8 @@ impl ProxyRequestLogService {
                            request_headers, request_body, response_headers, response_body,
                            response_start_at, response_end_at, request_body_size, response_body_size,
                            is_streaming, stream_chunk_count, time_to_first_byte_ms,
-                           content_type, user_agent, model
+                           content_type, user_agent, model, request_id, stream_integrity,
+                           input_tokens, output_tokens, input_tokens_estimated, output_tokens_estimated
                     FROM ProxyRequestLog
                     WHERE id = ?
                     "#,
@@ -315,14 +411,160 @@ impl ProxyRequestLogService {
                         content_type: row.get(23)?,
                         user_agent: row.get(24)?,
                         model: row.get(25)?,
+                        request_id: row.get(26)?,
+                        stream_integrity: row
+                            .get::<_, Option<String>>(27)?
+                            .and_then(|json| serde_json::from_str(&json).ok()),
+                        token_usage: Self::map_token_usage(row, 28)?,
                     })
                 })
                 .ok();
 
+            // 侧表中有完整内容时替换日志表中截断的内容
+            let log = log.map(|mut log| {
+                if let Some(body) = Self::get_full_body(conn, log_id, "request") {
+                    log.request_body = Some(body);
+                }
+                if let Some(body) = Self::get_full_body(conn, log_id, "response") {
+                    log.response_body = Some(body);
+                }
+                log
+            });
+
             Ok(log)
         })
     }
 
+    /// 从查询结果中读取 token 用量（从 `start` 列开始的 4 列）
+    fn map_token_usage(row: &rusqlite::Row, start: usize) -> rusqlite::Result<TokenUsage> {
+        Ok(TokenUsage {
+            input_tokens: row.get(start)?,
+            output_tokens: row.get(start + 1)?,
+            input_tokens_estimated: row.get::<_, Option<bool>>(start + 2)?.unwrap_or(false),
+            output_tokens_estimated: row.get::<_, Option<bool>>(start + 3)?.unwrap_or(false),
+        })
+    }
+
+    /// 是否记录请求体/响应体内容（应用设置，默认开启）
+    ///
+    /// 关闭后日志只保存大小、状态、模型、耗时等元数据
+    pub fn body_logging_enabled(conn: &Connection) -> bool {
+        match conn
+            .query_row("SELECT log_bodies FROM AppSettings WHERE id = 1", [], |row| row.get::<_, bool>(0))
+            .optional()
+        {
+            Ok(enabled) => enabled.unwrap_or(true),
+            Err(e) => {
+                log::warn!("读取请求体日志设置失败，按默认开启处理: {}", e);
+                true
+            }
+        }
+    }
+
+    /// 设置是否记录请求体/响应体内容
+    pub fn set_body_logging_enabled(pool: &DbPool, enabled: bool) -> AppResult<()> {
+        pool.with_connection(|conn| {
+            let updated = conn
+                .execute(
+                    "UPDATE AppSettings SET log_bodies = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+                    params![enabled],
+                )
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("更新请求体日志设置失败: {}", e),
+                })?;
+
+            if updated == 0 {
+                return Err(AppError::NotFound {
+                    resource: "AppSettings".to_string(),
+                    id: "1".to_string(),
+                });
+            }
+
+            log::info!("请求体日志已{}", if enabled { "开启" } else { "关闭" });
+            Ok(())
+        })
+    }
+
+    /// 是否在 ProxyRequestBody 侧表中保存完整请求体/响应体（应用设置，默认关闭）
+    ///
+    /// 仅在同时开启请求体日志时生效，完整内容上限为 FULL_BODY_LIMIT
+    pub fn full_body_logging_enabled(conn: &Connection) -> bool {
+        match conn
+            .query_row("SELECT log_full_bodies FROM AppSettings WHERE id = 1", [], |row| row.get::<_, bool>(0))
+            .optional()
+        {
+            Ok(enabled) => enabled.unwrap_or(false),
+            Err(e) => {
+                log::warn!("读取完整请求体日志设置失败，按默认关闭处理: {}", e);
+                false
+            }
+        }
+    }
+
+    /// 设置是否保存完整请求体/响应体
+    pub fn set_full_body_logging_enabled(pool: &DbPool, enabled: bool) -> AppResult<()> {
+        pool.with_connection(|conn| {
+            let updated = conn
+                .execute(
+                    "UPDATE AppSettings SET log_full_bodies = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+                    params![enabled],
+                )
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("更新完整请求体日志设置失败: {}", e),
+                })?;
+
+            if updated == 0 {
+                return Err(AppError::NotFound {
+                    resource: "AppSettings".to_string(),
+                    id: "1".to_string(),
+                });
+            }
+
+            log::info!("完整请求体日志已{}", if enabled { "开启" } else { "关闭" });
+            Ok(())
+        })
+    }
+
+    /// 保存完整内容到侧表（未超过日志表截断长度时无需保存）
+    fn save_full_body(conn: &Connection, log_id: i64, kind: &str, body: &str) -> AppResult<()> {
+        if body.len() <= INLINE_BODY_LIMIT {
+            return Ok(());
+        }
+
+        conn.execute(
+            "INSERT OR REPLACE INTO ProxyRequestBody (log_id, kind, content, size) VALUES (?1, ?2, ?3, ?4)",
+            params![log_id, kind, truncate_body(body, FULL_BODY_LIMIT), body.len() as i64],
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("保存完整{}失败: {}", if kind == "request" { "请求体" } else { "响应体" }, e),
+        })?;
+        Ok(())
+    }
+
+    /// 读取侧表中的完整内容
+    fn get_full_body(conn: &Connection, log_id: i64, kind: &str) -> Option<String> {
+        conn.query_row(
+            "SELECT content FROM ProxyRequestBody WHERE log_id = ?1 AND kind = ?2",
+            params![log_id, kind],
+            |row| row.get(0),
+        )
+        .optional()
+        .unwrap_or_else(|e| {
+            log::warn!("读取完整请求体失败: log_id={}, {}", log_id, e);
+            None
+        })
+    }
+
+    /// 删除所属日志已不存在的完整请求体/响应体
+    fn delete_orphan_bodies(conn: &Connection) {
+        if let Err(e) = conn.execute(
+            "DELETE FROM ProxyRequestBody WHERE log_id NOT IN (SELECT id FROM ProxyRequestLog)",
+            [],
+        ) {
+            log::warn!("清理完整请求体失败: {}", e);
+        }
+    }
+
     /// 清理旧日志（保留最近N条）
     pub fn cleanup_old_logs(pool: &DbPool, keep_count: i64) -> AppResult<i64> {
         pool.with_connection(|conn| {
@@ -342,6 +584,7 @@ impl ProxyRequestLogService {
                     message: format!("清理旧日志失败: {}", e),
                 })?;
 
+            Self::delete_orphan_bodies(conn);
             Ok(deleted as i64)
         })
     }
@@ -378,9 +621,9 @@ impl ProxyRequestLogService {
                         SUM(request_body_size) as total_request_size,
                         SUM(response_body_size) as total_response_size
                     FROM ProxyRequestLog
-                    WHERE request_at >= datetime('now', 'localtime', ? || ' hours')
+                    WHERE request_at >= ?
                     "#,
-                    params![format!("-{}", hours)],
+                    params![rfc3339_ago(chrono::Duration::hours(hours))],
                     |row| {
                         Ok(LogStats {
                             total_count: row.get(0)?,
@@ -403,6 +646,7 @@ impl ProxyRequestLogService {
     }
 
     /// 更新流式响应日志（在流结束后调用）
+    #[allow(clippy::too_many_arguments)]
     pub fn update_streaming_log(
         pool: &DbPool,
         log_id: i64,
@@ -410,16 +654,17 @@ impl ProxyRequestLogService {
         response_body: Option<String>,
         response_body_size: i64,
         stream_chunk_count: i32,
+        integrity: Option<&StreamIntegrityReport>,
+        token_usage: &TokenUsage,
     ) -> AppResult<()> {
-        pool.with_connection(|conn| {
+        pool.with_log_connection(|conn| {
+            let response_body = response_body.filter(|_| Self::body_logging_enabled(conn));
+            if let Some(body) = response_body.as_deref().filter(|_| Self::full_body_logging_enabled(conn)) {
+                Self::save_full_body(conn, log_id, "response", body)?;
+            }
+
             // 截断响应体（如果太大）
-            let truncated_body = response_body.map(|body| {
-                if body.len() > 8192 {
-                    format!("{}...(truncated)", &body[..8192])
-                } else {
-                    body
-                }
-            });
+            let truncated_body = response_body.map(|body| truncate_body(&body, INLINE_BODY_LIMIT));
 
             conn.execute(
                 r#"
@@ -428,7 +673,13 @@ impl ProxyRequestLogService {
                     response_body = ?,
                     response_body_size = ?,
                     stream_chunk_count = ?,
-                    response_end_at = datetime('now', 'localtime')
+                    stream_integrity = ?,
+                    stream_integrity_ok = ?,
+                    input_tokens = ?,
+                    output_tokens = ?,
+                    input_tokens_estimated = ?,
+                    output_tokens_estimated = ?,
+                    response_end_at = ?
                 WHERE id = ?
                 "#,
                 params![
@@ -436,6 +687,13 @@ impl ProxyRequestLogService {
                     truncated_body,
                     response_body_size,
                     stream_chunk_count,
+                    integrity.and_then(|r| serde_json::to_string(r).ok()),
+                    integrity.map(|r| r.is_complete),
+                    token_usage.input_tokens,
+                    token_usage.output_tokens,
+                    token_usage.input_tokens_estimated,
+                    token_usage.output_tokens_estimated,
+                    now_rfc3339(),
                     log_id,
                 ],
             )
@@ -447,6 +705,105 @@ impl ProxyRequestLogService {
             Ok(())
         })
     }
+
+    /// 获取存在流完整性问题的请求，按配置分组
+    ///
+    /// # 参数
+    /// - `limit`: 最多返回的异常请求数（所有配置合计）
+    pub fn get_stream_integrity_issues(
+        pool: &DbPool,
+        limit: i64,
+    ) -> AppResult<Vec<ConfigStreamIntegrity>> {
+        pool.with_connection(|conn| {
+            // 按配置汇总已检查的流与异常流数量
+            let mut stmt = conn
+                .prepare(
+                    r#"
+                    SELECT config_id, MAX(config_name), COUNT(*),
+                           SUM(CASE WHEN stream_integrity_ok = 0 THEN 1 ELSE 0 END) as problem_count
+                    FROM ProxyRequestLog
+                    WHERE stream_integrity_ok IS NOT NULL
+                    GROUP BY config_id
+                    HAVING problem_count > 0
+                    ORDER BY problem_count DESC
+                    "#,
+                )
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("准备查询语句失败: {}", e),
+                })?;
+
+            let mut groups = stmt
+                .query_map([], |row| {
+                    Ok(ConfigStreamIntegrity {
+                        config_id: row.get(0)?,
+                        config_name: row.get(1)?,
+                        total_streams: row.get(2)?,
+                        problem_count: row.get(3)?,
+                        issues: Vec::new(),
+                    })
+                })
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("查询流完整性统计失败: {}", e),
+                })?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("解析流完整性统计失败: {}", e),
+                })?;
+
+            // 最近的异常请求
+            let mut stmt = conn
+                .prepare(
+                    r#"
+                    SELECT id, request_at, request_id, model, config_id, stream_integrity
+                    FROM ProxyRequestLog
+                    WHERE stream_integrity_ok = 0
+                    ORDER BY id DESC
+                    LIMIT ?
+                    "#,
+                )
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("准备查询语句失败: {}", e),
+                })?;
+
+            let rows = stmt
+                .query_map(params![limit], |row| {
+                    Ok((
+                        row.get::<_, Option<i64>>(4)?,
+                        row.get::<_, Option<String>>(5)?,
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                    ))
+                })
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("查询流完整性异常失败: {}", e),
+                })?;
+
+            for row in rows {
+                let (config_id, report_json, log_id, request_at, request_id, model) =
+                    row.map_err(|e| AppError::DatabaseError {
+                        message: format!("解析流完整性异常失败: {}", e),
+                    })?;
+
+                let Some(report) = report_json.and_then(|json| serde_json::from_str(&json).ok()) else {
+                    continue;
+                };
+
+                if let Some(group) = groups.iter_mut().find(|g| g.config_id == config_id) {
+                    group.issues.push(StreamIntegrityIssue {
+                        log_id,
+                        request_at,
+                        request_id,
+                        model,
+                        report,
+                    });
+                }
+            }
+
+            Ok(groups)
+        })
+    }
 }
 
 /// 日志统计信息
@@ -461,3 +818,199 @@ pub struct LogStats {
     pub total_request_size: i64,
     pub total_response_size: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::stream_converter::StreamEventTracker;
+
+    fn setup_pool() -> DbPool {
+        DbPool::new(crate::db::test_db())
+    }
+
+    fn insert_stream_log(pool: &DbPool, config_id: i64, stream: &str) {
+        let mut tracker = StreamEventTracker::new();
+        tracker.push(stream.as_bytes());
+        let report = tracker.finish("req");
+
+        pool.with_connection(|conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO ApiConfig (id, name, api_key, server_url) VALUES (?1, ?2, 'k', 'https://example.com')",
+                params![config_id, format!("config-{}", config_id)],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO ProxyRequestLog (request_at, method, uri, target_url, config_id, config_name,
+                                              latency_ms, status_code, is_success, is_streaming)
+                 VALUES (datetime('now'), 'POST', '/v1/messages', 'https://example.com', ?1, ?2, 10, 200, 1, 1)",
+                params![config_id, format!("config-{}", config_id)],
+            )
+            .unwrap();
+            Ok(conn.last_insert_rowid())
+        })
+        .and_then(|id| {
+            ProxyRequestLogService::update_streaming_log(
+                pool,
+                id,
+                None,
+                None,
+                0,
+                1,
+                report.as_ref(),
+                &TokenUsage::default(),
+            )
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_stream_integrity_issues_grouped_by_config() {
+        let pool = setup_pool();
+        let complete = "event: message_start\ndata: {}\n\nevent: message_stop\ndata: {}\n\n";
+        let truncated = "event: message_start\ndata: {}\n\nevent: content_block_start\ndata: {\"index\":0}\n\n";
+
+        insert_stream_log(&pool, 1, complete);
+        insert_stream_log(&pool, 1, complete);
+        insert_stream_log(&pool, 2, complete);
+        insert_stream_log(&pool, 2, truncated);
+        insert_stream_log(&pool, 2, truncated);
+
+        let groups = ProxyRequestLogService::get_stream_integrity_issues(&pool, 50).unwrap();
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].config_id, Some(2));
+        assert_eq!(groups[0].total_streams, 3);
+        assert_eq!(groups[0].problem_count, 2);
+        assert_eq!(groups[0].issues.len(), 2);
+        assert!(!groups[0].issues[0].report.ended_with_message_stop);
+
+        let detail = ProxyRequestLogService::get_log_detail(&pool, groups[0].issues[0].log_id)
+            .unwrap()
+            .unwrap();
+        assert!(detail.stream_integrity.is_some());
+    }
+
+    #[test]
+    fn test_body_logging_disabled_keeps_metadata_only() {
+        let pool = setup_pool();
+        pool.with_connection(|conn| {
+            assert!(ProxyRequestLogService::body_logging_enabled(conn));
+            conn.execute("INSERT INTO AppSettings (id) VALUES (1)", []).unwrap();
+            Ok(())
+        })
+        .unwrap();
+
+        ProxyRequestLogService::set_body_logging_enabled(&pool, false).unwrap();
+        insert_stream_log(&pool, 1, "event: message_start\ndata: {}\n\n");
+        let log_id = pool
+            .with_connection(|conn| Ok(conn.last_insert_rowid()))
+            .unwrap();
+        ProxyRequestLogService::update_streaming_log(
+            &pool,
+            log_id,
+            None,
+            Some("secret response".to_string()),
+            15,
+            1,
+            None,
+            &TokenUsage::default(),
+        )
+        .unwrap();
+
+        let (body, size): (Option<String>, i64) = pool
+            .with_connection(|conn| {
+                Ok(conn
+                    .query_row(
+                        "SELECT response_body, response_body_size FROM ProxyRequestLog WHERE id = ?1",
+                        [log_id],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .unwrap())
+            })
+            .unwrap();
+        assert_eq!(body, None);
+        assert_eq!(size, 15);
+    }
+
+    #[test]
+    fn test_truncate_body_respects_char_boundary() {
+        assert_eq!(truncate_body("short", 8), "short");
+        assert_eq!(truncate_body("日志内容", 4), "日...(truncated)");
+    }
+
+    #[test]
+    fn test_full_bodies_stored_in_side_table() {
+        let pool = setup_pool();
+        pool.with_connection(|conn| {
+            assert!(!ProxyRequestLogService::full_body_logging_enabled(conn));
+            conn.execute("INSERT INTO AppSettings (id) VALUES (1)", []).unwrap();
+            Ok(())
+        })
+        .unwrap();
+        ProxyRequestLogService::set_full_body_logging_enabled(&pool, true).unwrap();
+
+        insert_stream_log(&pool, 1, "event: message_start\ndata: {}\n\n");
+        let log_id = pool
+            .with_connection(|conn| Ok(conn.last_insert_rowid()))
+            .unwrap();
+        let large = "x".repeat(INLINE_BODY_LIMIT * 3);
+        ProxyRequestLogService::update_streaming_log(
+            &pool,
+            log_id,
+            None,
+            Some(large.clone()),
+            large.len() as i64,
+            3,
+            None,
+            &TokenUsage::default(),
+        )
+        .unwrap();
+
+        let inline: String = pool
+            .with_connection(|conn| {
+                Ok(conn
+                    .query_row("SELECT response_body FROM ProxyRequestLog WHERE id = ?1", [log_id], |row| row.get(0))
+                    .unwrap())
+            })
+            .unwrap();
+        assert!(inline.ends_with("...(truncated)"));
+        assert!(inline.len() < large.len());
+
+        let detail = ProxyRequestLogService::get_log_detail(&pool, log_id).unwrap().unwrap();
+        assert_eq!(detail.response_body, Some(large));
+
+        // 日志被清理后侧表中的内容一并删除
+        ProxyRequestLogService::cleanup_old_logs(&pool, 0).unwrap();
+        let remaining: i64 = pool
+            .with_connection(|conn| {
+                Ok(conn
+                    .query_row("SELECT COUNT(*) FROM ProxyRequestBody", [], |row| row.get(0))
+                    .unwrap())
+            })
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn test_streaming_log_records_token_usage() {
+        let pool = setup_pool();
+        insert_stream_log(&pool, 1, "event: message_start\ndata: {}\n\n");
+        let log_id = pool
+            .with_connection(|conn| Ok(conn.last_insert_rowid()))
+            .unwrap();
+
+        let usage = TokenUsage {
+            input_tokens: Some(120),
+            output_tokens: Some(42),
+            input_tokens_estimated: false,
+            output_tokens_estimated: true,
+        };
+        ProxyRequestLogService::update_streaming_log(&pool, log_id, None, None, 10, 2, None, &usage)
+            .unwrap();
+
+        let detail = ProxyRequestLogService::get_log_detail(&pool, log_id).unwrap().unwrap();
+        assert_eq!(detail.token_usage, usage);
+        let logs = ProxyRequestLogService::get_logs_by_config(&pool, 1, 10).unwrap();
+        assert_eq!(logs[0].token_usage, usage);
+    }
+}
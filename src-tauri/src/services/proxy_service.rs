@@ -8,13 +8,20 @@
  * - Switch active configuration/group
  * - Auto port fallback (handled by ProxyServer)
  * - Status reporting
+ * - Routing state snapshots (save/restore)
+ * - Additional named listeners on other ports (in-memory only, not restored after restart)
  */
 
 use crate::db::DbPool;
 use crate::models::error::{AppError, AppResult};
-use crate::models::proxy_status::{ProxyService as ProxyServiceModel, ProxyStatus};
+use crate::models::proxy_status::{ProxyListenerInfo, ProxyService as ProxyServiceModel, ProxyStatus};
+use crate::models::routing_snapshot::{RestoreRoutingSnapshotResult, RoutingSnapshot};
+use crate::proxy::active_requests::ActiveRequestInfo;
 use crate::proxy::server::{ProxyConfig, ProxyServer, ProxyServerStatus};
+use crate::services::auto_switch::AutoSwitchService;
+use crate::services::routing_snapshot::RoutingSnapshotService;
 use crate::services::status_notifier::StatusNotifier;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::AppHandle;
 use tokio::sync::RwLock;
@@ -27,18 +34,24 @@ pub struct ProxyService {
     db_pool: Arc<DbPool>,
     /// Tauri app handle (optional, for event emission)
     app_handle: Arc<RwLock<Option<AppHandle>>>,
+    /// Additional named listeners (keyed by name)
+    listeners: RwLock<HashMap<String, Arc<ProxyServer>>>,
 }
 
 impl ProxyService {
     /// Create new proxy service manager
     pub fn new(db_pool: Arc<DbPool>) -> Self {
-        let config = ProxyConfig::default();
+        let mut config = ProxyConfig::default();
+        if let Err(e) = db_pool.with_connection(|conn| Self::load_persisted_settings(conn, &mut config)) {
+            log::warn!("Failed to load persisted proxy settings, using defaults: {}", e);
+        }
         let server = Arc::new(ProxyServer::new(config, db_pool.clone()));
 
         Self {
             server,
             db_pool,
             app_handle: Arc::new(RwLock::new(None)),
+            listeners: RwLock::new(HashMap::new()),
         }
     }
 
@@ -504,6 +517,455 @@ impl ProxyService {
         Ok(status)
     }
 
+    /// Apply proxy-wide settings persisted in AppSettings on top of `config`
+    ///
+    /// NULL columns (never set) keep the defaults already in `config`.
+    fn load_persisted_settings(conn: &rusqlite::Connection, config: &mut ProxyConfig) -> AppResult<()> {
+        use rusqlite::OptionalExtension;
+
+        let row = conn
+            .query_row(
+                "SELECT proxy_connect_timeout_secs, proxy_request_timeout_secs,
+                        proxy_max_stream_duration_secs, proxy_max_stream_chunks
+                 FROM AppSettings WHERE id = 1",
+                [],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<i64>>(0)?,
+                        row.get::<_, Option<i64>>(1)?,
+                        row.get::<_, Option<i64>>(2)?,
+                        row.get::<_, Option<i64>>(3)?,
+                    ))
+                },
+            )
+            .optional()
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("Failed to read proxy settings: {}", e),
+            })?;
+
+        if let Some((connect_timeout_secs, request_timeout_secs, max_stream_duration_secs, max_stream_chunks)) = row {
+            if let Some(secs) = connect_timeout_secs.and_then(|v| u64::try_from(v).ok()) {
+                config.connect_timeout_secs = secs;
+            }
+            if let Some(secs) = request_timeout_secs.and_then(|v| u64::try_from(v).ok()) {
+                config.request_timeout_secs = secs;
+            }
+            if let Some(secs) = max_stream_duration_secs.and_then(|v| u64::try_from(v).ok()) {
+                config.max_stream_duration_secs = secs;
+            }
+            if let Some(chunks) = max_stream_chunks.and_then(|v| u32::try_from(v).ok()) {
+                config.max_stream_chunks = chunks;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Update proxy-wide connect / request timeouts
+    ///
+    /// Per-config overrides (ApiConfig.connect_timeout_secs / request_timeout_secs)
+    /// still take precedence over these values.
+    ///
+    /// # Arguments
+    /// - `connect_timeout_secs`: TCP connect + TLS handshake timeout (None keeps current)
+    /// - `request_timeout_secs`: Send request + wait for response timeout (None keeps current)
+    ///
+    /// The effective values are persisted in AppSettings and restored on startup.
+    ///
+    /// # Returns
+    /// - Effective (connect_timeout_secs, request_timeout_secs)
+    pub async fn set_timeouts(
+        &self,
+        connect_timeout_secs: Option<u64>,
+        request_timeout_secs: Option<u64>,
+    ) -> AppResult<(u64, u64)> {
+        use crate::models::api_config::{MAX_CONNECT_TIMEOUT_SECS, MAX_REQUEST_TIMEOUT_SECS};
+
+        for (field, value, max) in [
+            ("connect_timeout_secs", connect_timeout_secs, MAX_CONNECT_TIMEOUT_SECS as u64),
+            ("request_timeout_secs", request_timeout_secs, MAX_REQUEST_TIMEOUT_SECS as u64),
+        ] {
+            if let Some(secs) = value {
+                if secs == 0 || secs > max {
+                    return Err(AppError::ValidationError {
+                        field: field.to_string(),
+                        message: format!("{} 必须在 1-{} 秒之间", field, max),
+                    });
+                }
+            }
+        }
+
+        let mut config = self.server.config().await;
+        if let Some(secs) = connect_timeout_secs {
+            config.connect_timeout_secs = secs;
+        }
+        if let Some(secs) = request_timeout_secs {
+            config.request_timeout_secs = secs;
+        }
+        let effective = (config.connect_timeout_secs, config.request_timeout_secs);
+
+        self.db_pool.with_connection(|conn| {
+            conn.execute(
+                "UPDATE AppSettings SET
+                    proxy_connect_timeout_secs = ?1,
+                    proxy_request_timeout_secs = ?2,
+                    updated_at = CURRENT_TIMESTAMP
+                 WHERE id = 1",
+                rusqlite::params![effective.0 as i64, effective.1 as i64],
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("Failed to save proxy timeouts: {}", e),
+            })
+        })?;
+        self.server.update_config(config).await;
+
+        log::info!(
+            "Proxy timeouts updated: connect={}s, request={}s",
+            effective.0,
+            effective.1
+        );
+
+        Ok(effective)
+    }
+
+    /// Update proxy-wide streaming response limits
+    ///
+    /// Streams exceeding either limit are cut off with a closing `message_stop`
+    /// and counted as failures for auto-switch. 0 disables a limit.
+    ///
+    /// # Arguments
+    /// - `max_duration_secs`: Maximum stream duration (None keeps current)
+    /// - `max_chunks`: Maximum chunk count (None keeps current)
+    ///
+    /// Applies to pass-through and converted (OpenAI / Gemini) streams alike. The
+    /// effective values are persisted in AppSettings and restored on startup.
+    ///
+    /// # Returns
+    /// - Effective (max_stream_duration_secs, max_stream_chunks)
+    pub async fn set_stream_limits(
+        &self,
+        max_duration_secs: Option<u64>,
+        max_chunks: Option<u32>,
+    ) -> AppResult<(u64, u32)> {
+        const MAX_STREAM_DURATION_SECS: u64 = 86400;
+
+        if matches!(max_duration_secs, Some(secs) if secs > MAX_STREAM_DURATION_SECS) {
+            return Err(AppError::ValidationError {
+                field: "max_duration_secs".to_string(),
+                message: format!("流式响应最长持续时间不能超过 {} 秒", MAX_STREAM_DURATION_SECS),
+            });
+        }
+
+        let mut config = self.server.config().await;
+        if let Some(secs) = max_duration_secs {
+            config.max_stream_duration_secs = secs;
+        }
+        if let Some(chunks) = max_chunks {
+            config.max_stream_chunks = chunks;
+        }
+        let effective = (config.max_stream_duration_secs, config.max_stream_chunks);
+
+        self.db_pool.with_connection(|conn| {
+            conn.execute(
+                "UPDATE AppSettings SET
+                    proxy_max_stream_duration_secs = ?1,
+                    proxy_max_stream_chunks = ?2,
+                    updated_at = CURRENT_TIMESTAMP
+                 WHERE id = 1",
+                rusqlite::params![effective.0 as i64, effective.1],
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("Failed to save proxy stream limits: {}", e),
+            })
+        })?;
+        self.server.update_config(config).await;
+
+        log::info!(
+            "Proxy stream limits updated: max_duration={}s, max_chunks={}",
+            effective.0,
+            effective.1
+        );
+
+        Ok(effective)
+    }
+
+    /// List requests currently being processed by the proxy (longest running first)
+    pub fn list_active_requests(&self) -> Vec<ActiveRequestInfo> {
+        self.server.active_requests()
+    }
+
+    /// Number of requests currently being processed by the proxy and its named listeners
+    ///
+    /// Streaming requests are counted until the stream ends.
+    pub async fn active_request_count(&self) -> usize {
+        let listeners = self.listeners.read().await;
+        self.server.active_request_count()
+            + listeners.values().map(|server| server.active_request_count()).sum::<usize>()
+    }
+
+    /// Save current routing state as a named snapshot
+    ///
+    /// Captures active group/config and per-config / per-group enabled flags.
+    /// An existing snapshot with the same name is overwritten.
+    ///
+    /// # Arguments
+    /// - `name`: Snapshot name
+    pub async fn save_routing_snapshot(&self, name: &str) -> AppResult<RoutingSnapshot> {
+        let config = self.server.config().await;
+
+        self.db_pool.with_connection(|conn| {
+            RoutingSnapshotService::save(conn, name, config.active_group_id, config.active_config_id)
+        })
+    }
+
+    /// Restore a named routing snapshot
+    ///
+    /// - Reapplies enabled flags (deleted configs/groups are skipped)
+    /// - Switches back to the saved active config, falling back to the saved
+    ///   group when that config is gone or unavailable
+    ///
+    /// # Arguments
+    /// - `name`: Snapshot name
+    pub async fn restore_routing_snapshot(&self, name: &str) -> AppResult<RestoreRoutingSnapshotResult> {
+        let snapshot = self
+            .db_pool
+            .with_connection(|conn| RoutingSnapshotService::get_by_name(conn, name))?;
+
+        let (restored_configs, restored_groups, mut skipped) = self
+            .db_pool
+            .with_connection(|conn| RoutingSnapshotService::apply_flags(conn, &snapshot.flags))?;
+
+        let mut switched = false;
+        if let Some(config_id) = snapshot.active_config_id {
+            match self.switch_config(config_id).await {
+                Ok(_) => switched = true,
+                Err(e) => {
+                    log::warn!("Failed to restore snapshot config {}: {}", config_id, e);
+                    skipped.push(format!("active_config:{}", config_id));
+                }
+            }
+        }
+
+        if !switched {
+            if let Some(group_id) = snapshot.active_group_id {
+                if let Err(e) = self.switch_group(group_id).await {
+                    log::warn!("Failed to restore snapshot group {}: {}", group_id, e);
+                    skipped.push(format!("active_group:{}", group_id));
+                }
+            }
+        }
+
+        log::info!(
+            "Routing snapshot '{}' restored: {} configs, {} groups, skipped: {:?}",
+            snapshot.name,
+            restored_configs,
+            restored_groups,
+            skipped
+        );
+
+        Ok(RestoreRoutingSnapshotResult {
+            snapshot,
+            restored_configs,
+            restored_groups,
+            skipped,
+        })
+    }
+
+    /// Create a named listener on another port
+    ///
+    /// The listener shares the database pool with the main proxy but routes to its
+    /// own active group/config. When only a group is given, the first available
+    /// configuration in that group is used. It has its own auto-switch service:
+    /// failovers update only the listener's active config, never the main proxy's
+    /// ProxyService record.
+    ///
+    /// Listeners are kept in memory only; they are not persisted and must be
+    /// recreated after the application restarts.
+    ///
+    /// # Arguments
+    /// - `name`: Unique listener name
+    /// - `port`: Listen port (must differ from the main proxy and other listeners)
+    /// - `group_id`: Active group
+    /// - `config_id`: Active configuration (takes precedence over `group_id`)
+    pub async fn create_listener(
+        &self,
+        name: &str,
+        port: u16,
+        group_id: Option<i64>,
+        config_id: Option<i64>,
+    ) -> AppResult<ProxyListenerInfo> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::ValidationError {
+                field: "name".to_string(),
+                message: "监听实例名称不能为空".to_string(),
+            });
+        }
+        if port == 0 {
+            return Err(AppError::ValidationError {
+                field: "port".to_string(),
+                message: "端口必须在 1-65535 之间".to_string(),
+            });
+        }
+
+        let (active_group_id, active_config_id) = self.resolve_listener_target(group_id, config_id)?;
+
+        let mut listeners = self.listeners.write().await;
+        if listeners.contains_key(name) {
+            return Err(AppError::DuplicateEntry {
+                field: "name".to_string(),
+                value: name.to_string(),
+            });
+        }
+
+        let mut used_ports = vec![self.server.config().await.port];
+        for listener in listeners.values() {
+            used_ports.push(listener.config().await.port);
+        }
+        if used_ports.contains(&port) {
+            return Err(AppError::DuplicateEntry {
+                field: "port".to_string(),
+                value: port.to_string(),
+            });
+        }
+
+        let base = self.server.config().await;
+        let config = ProxyConfig {
+            port,
+            active_group_id,
+            active_config_id,
+            ..base
+        };
+        let server = Arc::new(ProxyServer::with_auto_switch(
+            config,
+            self.db_pool.clone(),
+            Arc::new(AutoSwitchService::for_listener(self.db_pool.clone())),
+        ));
+        let info = Self::listener_info(name, &server).await;
+        listeners.insert(name.to_string(), server);
+
+        log::info!(
+            "Proxy listener '{}' created on port {} (group: {:?}, config: {:?})",
+            name,
+            port,
+            active_group_id,
+            active_config_id
+        );
+        Ok(info)
+    }
+
+    /// Start a named listener
+    pub async fn start_listener(&self, name: &str) -> AppResult<ProxyListenerInfo> {
+        let server = self.get_listener(name).await?;
+        if server.status().await == ProxyServerStatus::Running {
+            return Err(AppError::AlreadyRunning);
+        }
+
+        server.start().await?;
+        log::info!("Proxy listener '{}' started on port {}", name, server.config().await.port);
+        Ok(Self::listener_info(name, &server).await)
+    }
+
+    /// Stop a named listener
+    pub async fn stop_listener(&self, name: &str) -> AppResult<ProxyListenerInfo> {
+        let server = self.get_listener(name).await?;
+        server.stop().await?;
+        log::info!("Proxy listener '{}' stopped", name);
+        Ok(Self::listener_info(name, &server).await)
+    }
+
+    /// Remove a named listener, stopping it first if running
+    pub async fn remove_listener(&self, name: &str) -> AppResult<()> {
+        let server = self.listeners.write().await.remove(name).ok_or_else(|| AppError::NotFound {
+            resource: "ProxyListener".to_string(),
+            id: name.to_string(),
+        })?;
+
+        if server.status().await == ProxyServerStatus::Running {
+            server.stop().await?;
+        }
+        log::info!("Proxy listener '{}' removed", name);
+        Ok(())
+    }
+
+    /// List named listeners (sorted by name)
+    pub async fn list_listeners(&self) -> Vec<ProxyListenerInfo> {
+        let listeners = self.listeners.read().await;
+        let mut infos = Vec::with_capacity(listeners.len());
+        for (name, server) in listeners.iter() {
+            infos.push(Self::listener_info(name, server).await);
+        }
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos
+    }
+
+    async fn get_listener(&self, name: &str) -> AppResult<Arc<ProxyServer>> {
+        self.listeners
+            .read()
+            .await
+            .get(name)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound {
+                resource: "ProxyListener".to_string(),
+                id: name.to_string(),
+            })
+    }
+
+    /// Resolve (group_id, config_id) for a listener
+    fn resolve_listener_target(
+        &self,
+        group_id: Option<i64>,
+        config_id: Option<i64>,
+    ) -> AppResult<(Option<i64>, Option<i64>)> {
+        use crate::services::api_config::ApiConfigService;
+
+        if let Some(config_id) = config_id {
+            let config = self
+                .db_pool
+                .with_connection(|conn| ApiConfigService::get_config_by_id(conn, config_id))?;
+            return Ok((config.group_id.or(group_id), Some(config.id)));
+        }
+
+        if let Some(group_id) = group_id {
+            let configs = self
+                .db_pool
+                .with_connection(|conn| ApiConfigService::list_configs(conn, Some(group_id)))?;
+            if configs.is_empty() {
+                return Err(AppError::EmptyGroup { group_id });
+            }
+            let first = configs
+                .into_iter()
+                .find(|c| c.is_available)
+                .ok_or(AppError::NoConfigAvailable)?;
+            return Ok((Some(group_id), Some(first.id)));
+        }
+
+        Err(AppError::ValidationError {
+            field: "group_id".to_string(),
+            message: "必须指定分组或配置".to_string(),
+        })
+    }
+
+    async fn listener_info(name: &str, server: &ProxyServer) -> ProxyListenerInfo {
+        let config = server.config().await;
+        let status = match server.status().await {
+            ProxyServerStatus::Stopped => ProxyStatus::Stopped,
+            ProxyServerStatus::Starting => ProxyStatus::Starting,
+            ProxyServerStatus::Running => ProxyStatus::Running,
+            ProxyServerStatus::Stopping => ProxyStatus::Stopping,
+            ProxyServerStatus::Error => ProxyStatus::Error,
+        };
+
+        ProxyListenerInfo {
+            name: name.to_string(),
+            status,
+            listen_host: config.host,
+            listen_port: config.port as i32,
+            active_group_id: config.active_group_id,
+            active_config_id: config.active_config_id,
+        }
+    }
+
     /// Get the underlying proxy server (for advanced operations)
     #[allow(dead_code)]
     pub fn server(&self) -> &Arc<ProxyServer> {
@@ -593,3 +1055,83 @@ mod tests {
         assert!(result.is_err());
     }
 }
+
+#[cfg(test)]
+mod listener_tests {
+    use super::*;
+
+    fn setup_service() -> ProxyService {
+        let conn = crate::db::test_db();
+        conn.execute_batch(
+            "INSERT INTO ConfigGroup (id, name) VALUES (1, 'g');
+             INSERT INTO ApiConfig (id, name, api_key, server_url, server_port, group_id)
+                 VALUES (1, 'c', 'k', 'https://example.com', 443, 1);",
+        )
+        .unwrap();
+        ProxyService::new(Arc::new(DbPool::new(conn)))
+    }
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[tokio::test]
+    async fn test_listener_lifecycle() {
+        let service = setup_service();
+        let port = free_port();
+
+        let info = service.create_listener("team-b", port, Some(1), None).await.unwrap();
+        assert_eq!(info.status, ProxyStatus::Stopped);
+        assert_eq!(info.active_group_id, Some(1));
+        assert_eq!(info.active_config_id, Some(1));
+        // 监听实例的故障切换不写入主代理的 ProxyService 记录
+        assert!(service.server().auto_switch_service().updates_proxy_service());
+        assert!(!service.get_listener("team-b").await.unwrap().auto_switch_service().updates_proxy_service());
+
+        assert!(service.create_listener("team-b", port + 1, None, Some(1)).await.is_err());
+        assert!(service.create_listener("team-c", port, None, Some(1)).await.is_err());
+        assert!(service.create_listener("team-d", port + 1, None, None).await.is_err());
+
+        let info = service.start_listener("team-b").await.unwrap();
+        assert_eq!(info.status, ProxyStatus::Running);
+        assert!(tokio::net::TcpStream::connect(("127.0.0.1", info.listen_port as u16)).await.is_ok());
+
+        let listeners = service.list_listeners().await;
+        assert_eq!(listeners.len(), 1);
+        assert_eq!(listeners[0].name, "team-b");
+
+        let info = service.stop_listener("team-b").await.unwrap();
+        assert_eq!(info.status, ProxyStatus::Stopped);
+
+        service.remove_listener("team-b").await.unwrap();
+        assert!(service.list_listeners().await.is_empty());
+        assert!(service.start_listener("team-b").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_and_stream_limits_persist_across_restart() {
+        let conn = crate::db::test_db();
+        conn.execute("INSERT INTO AppSettings (id) VALUES (1)", []).unwrap();
+        let db_pool = Arc::new(DbPool::new(conn));
+
+        // 未设置时使用默认值
+        let defaults = ProxyConfig::default();
+        let service = ProxyService::new(db_pool.clone());
+        assert_eq!(service.server().config().await.request_timeout_secs, defaults.request_timeout_secs);
+
+        assert_eq!(service.set_timeouts(Some(7), None).await.unwrap(), (7, defaults.request_timeout_secs));
+        assert_eq!(service.set_timeouts(None, Some(300)).await.unwrap(), (7, 300));
+        assert!(service.set_timeouts(Some(0), None).await.is_err());
+        assert_eq!(service.set_stream_limits(Some(600), Some(0)).await.unwrap(), (600, 0));
+
+        // 重新创建服务（模拟应用重启）后恢复已保存的值
+        let restarted = ProxyService::new(db_pool);
+        let config = restarted.server().config().await;
+        assert_eq!((config.connect_timeout_secs, config.request_timeout_secs), (7, 300));
+        assert_eq!((config.max_stream_duration_secs, config.max_stream_chunks), (600, 0));
+    }
+}
@@ -8,7 +8,8 @@
  * tokio::sync::Mutex for metadata to support async operations.
  */
 
-use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
+use chrono::{DateTime, Utc};
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
 use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::sync::{Arc, Mutex as StdMutex};
@@ -20,6 +21,7 @@ use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use crate::db::DbPool;
 use crate::models::terminal_session::NewTerminalSession;
 use crate::services::session_config::SESSION_CONFIG_MAP;
+use crate::utils::time::to_rfc3339_utc;
 use crate::services::terminal_session_service::TerminalSessionService;
 
 /// Claude Code startup options
@@ -68,12 +70,20 @@ pub struct PtySessionMeta {
     pub is_claude_code: bool,
     /// Claude Code options (if applicable)
     pub claude_options: Option<ClaudeCodeOptions>,
+    /// PID of the spawned shell process (if reported by the platform)
+    pub pid: Option<u32>,
+    /// When the session was created
+    pub created_at: DateTime<Utc>,
+    /// Last user activity (creation, input or resize)
+    pub last_activity_at: DateTime<Utc>,
 }
 
 /// PTY handle wrapper for resize operations
 struct PtyHandle {
     master: Box<dyn MasterPty + Send>,
     writer: Box<dyn Write + Send>,
+    /// Spawned process, kept so it can be terminated explicitly
+    child: Box<dyn Child + Send + Sync>,
 }
 
 /// PTY Manager for handling multiple terminal sessions
@@ -113,6 +123,11 @@ impl PtyManager {
         }
     }
 
+    /// Proxy port that session URLs point at
+    pub fn proxy_port(&self) -> u16 {
+        self.proxy_port
+    }
+
     /// 从数据库恢复持久化会话
     ///
     /// 注意: 此方法仅恢复会话元数据到 SessionConfigMap，不会重新创建 PTY 实例
@@ -245,10 +260,11 @@ impl PtyManager {
         };
 
         // Spawn the process
-        let _child = pair
+        let child = pair
             .slave
             .spawn_command(cmd)
             .map_err(|e| format!("Failed to spawn process: {}", e))?;
+        let pid = child.process_id();
 
         // Get master for I/O
         let master = pair.master;
@@ -291,6 +307,7 @@ impl PtyManager {
         });
 
         // Store session metadata
+        let now = Utc::now();
         let meta = PtySessionMeta {
             session_id: session_id.clone(),
             config_id,
@@ -301,6 +318,9 @@ impl PtyManager {
             cols,
             is_claude_code,
             claude_options: claude_options.clone(),
+            pid,
+            created_at: now,
+            last_activity_at: now,
         };
 
         {
@@ -311,7 +331,7 @@ impl PtyManager {
         // Store PTY handle
         {
             let mut handles = self.handles.lock().unwrap();
-            handles.insert(session_id.clone(), PtyHandle { master, writer });
+            handles.insert(session_id.clone(), PtyHandle { master, writer, child });
         }
 
         // 持久化到数据库（如果启用）
@@ -346,6 +366,7 @@ impl PtyManager {
             if let Some(session) = sessions.get_mut(session_id) {
                 session.rows = rows;
                 session.cols = cols;
+                session.last_activity_at = Utc::now();
             } else {
                 return Err(format!("Session not found: {}", session_id));
             }
@@ -382,6 +403,10 @@ impl PtyManager {
 
     /// Write input to a terminal session
     pub async fn write_input(&self, session_id: &str, data: &[u8]) -> Result<(), String> {
+        if let Some(session) = self.sessions.lock().await.get_mut(session_id) {
+            session.last_activity_at = Utc::now();
+        }
+
         let mut handles = self.handles.lock().unwrap();
 
         if let Some(handle) = handles.get_mut(session_id) {
@@ -429,23 +454,101 @@ impl PtyManager {
         Ok(())
     }
 
+    /// Terminate a session's process and remove it
+    ///
+    /// Sends SIGHUP (falling back to a hard kill after a short grace period),
+    /// then drops the PTY, the proxy routing entry and the persisted record.
+    /// Sessions that only exist in the routing map (no PTY) are unpinned as well.
+    ///
+    /// Returns `false` if nothing was known about the session.
+    pub async fn kill_session(&self, session_id: &str) -> Result<bool, String> {
+        let had_meta = self.sessions.lock().await.remove(session_id).is_some();
+        let handle = self.handles.lock().unwrap().remove(session_id);
+        let had_pty = handle.is_some();
+
+        if let Some(mut handle) = handle {
+            let pid = handle.child.process_id();
+            let exited = tokio::task::spawn_blocking(move || {
+                if !matches!(handle.child.try_wait(), Ok(Some(_))) {
+                    handle.child.kill()?;
+                }
+                // Reap the process so it does not linger as a zombie
+                handle.child.try_wait().map(|status| status.is_some())
+            })
+            .await
+            .map_err(|e| format!("Failed to kill process: {}", e))?
+            .map_err(|e| format!("Failed to kill process: {}", e))?;
+
+            log::info!(
+                "Killed terminal session {} (pid: {:?}, exited: {})",
+                session_id,
+                pid,
+                exited
+            );
+        }
+
+        let was_pinned = SESSION_CONFIG_MAP.remove(session_id).is_some();
+
+        if had_meta || had_pty {
+            if let Some(service) = &self.session_service {
+                if let Err(e) = service.close_session(session_id, None).await {
+                    log::error!("关闭数据库会话记录失败: {}", e);
+                }
+            }
+        }
+
+        Ok(had_meta || had_pty || was_pinned)
+    }
+
     /// Get list of active sessions
     pub async fn list_sessions(&self) -> Vec<PtySessionInfo> {
+        let now = Utc::now();
         let sessions = self.sessions.lock().await;
+        let mut handles = self.handles.lock().unwrap();
         sessions
             .values()
-            .map(|s| PtySessionInfo {
-                session_id: s.session_id.clone(),
-                config_id: s.config_id,
-                name: s.name.clone(),
-                work_dir: s.work_dir.clone(),
-                running: s.running,
-                is_claude_code: s.is_claude_code,
-                claude_options: s.claude_options.clone(),
-            })
+            .map(|s| Self::session_info_of(s, &mut handles, now))
             .collect()
     }
 
+    /// Get info for a single session
+    pub async fn session_info(&self, session_id: &str) -> Option<PtySessionInfo> {
+        let sessions = self.sessions.lock().await;
+        let mut handles = self.handles.lock().unwrap();
+        sessions
+            .get(session_id)
+            .map(|s| Self::session_info_of(s, &mut handles, Utc::now()))
+    }
+
+    fn session_info_of(
+        meta: &PtySessionMeta,
+        handles: &mut HashMap<String, PtyHandle>,
+        now: DateTime<Utc>,
+    ) -> PtySessionInfo {
+        // Report whether the process is still alive, not just whether we started it
+        let process_alive = handles
+            .get_mut(&meta.session_id)
+            .is_some_and(|h| matches!(h.child.try_wait(), Ok(None)));
+        PtySessionInfo {
+            session_id: meta.session_id.clone(),
+            config_id: meta.config_id,
+            name: meta.name.clone(),
+            work_dir: meta.work_dir.clone(),
+            running: meta.running && process_alive,
+            is_claude_code: meta.is_claude_code,
+            claude_options: meta.claude_options.clone(),
+            pid: meta.pid,
+            created_at: to_rfc3339_utc(&meta.created_at),
+            last_activity_at: to_rfc3339_utc(&meta.last_activity_at),
+            idle_secs: now.signed_duration_since(meta.last_activity_at).num_seconds(),
+        }
+    }
+
+    /// Get the process ID of a session's shell, if it has a PTY
+    pub async fn session_pid(&self, session_id: &str) -> Option<u32> {
+        self.sessions.lock().await.get(session_id).and_then(|s| s.pid)
+    }
+
     /// Get session count
     pub async fn session_count(&self) -> usize {
         self.sessions.lock().await.len()
@@ -794,6 +897,18 @@ pub struct PtySessionInfo {
     pub is_claude_code: bool,
     #[serde(default)]
     pub claude_options: Option<ClaudeCodeOptions>,
+    /// PID of the shell process
+    #[serde(default)]
+    pub pid: Option<u32>,
+    /// Creation time (RFC3339)
+    #[serde(default)]
+    pub created_at: String,
+    /// Last input / resize time (RFC3339)
+    #[serde(default)]
+    pub last_activity_at: String,
+    /// Seconds since last activity
+    #[serde(default)]
+    pub idle_secs: i64,
 }
 
 /// Thread-safe PTY manager wrapper for Tauri state
@@ -861,4 +976,14 @@ mod tests {
         assert!(script.contains("'hello world'"));
         assert!(script.contains("& 'claude'"));
     }
+
+    #[tokio::test]
+    async fn test_kill_session_unpins_routing_only_session() {
+        let manager = PtyManager::new(25341);
+        SESSION_CONFIG_MAP.register("kill-test-session".to_string(), 7, None);
+
+        assert!(manager.kill_session("kill-test-session").await.unwrap());
+        assert!(!SESSION_CONFIG_MAP.has_session("kill-test-session"));
+        assert!(!manager.kill_session("kill-test-session").await.unwrap());
+    }
 }
@@ -5,12 +5,17 @@
 
 use crate::models::error::{AppError, AppResult};
 use crate::models::provider_preset::{ProviderConfig, ProviderPreset};
-use crate::models::recommended_service::{RecommendedService, ServiceSource};
+use crate::models::recommended_service::{
+    RecommendationRefreshResult, RecommendedService, RefreshSource, ServiceSource,
+};
 use crate::utils::time::now_rfc3339;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 远程获取的默认超时时间
+pub const DEFAULT_REMOTE_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// 推荐服务列表容器（用于 JSON 反序列化）
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -120,7 +125,7 @@ impl RecommendationService {
         // 优先尝试从远程加载
         let result = if let Some(url) = &self.remote_url {
             log::info!("尝试从远程加载推荐服务: {}", url);
-            match self.load_remote(url).await {
+            match self.load_remote(url, DEFAULT_REMOTE_TIMEOUT).await {
                 Ok(services) => {
                     log::info!("成功从远程加载 {} 个推荐服务", services.len());
                     self.update_cache(services.clone());
@@ -129,22 +134,91 @@ impl RecommendationService {
                 Err(e) => {
                     log::warn!("从远程加载失败: {}, 回退到本地配置", e);
                     // 回退到本地
-                    self.load_local()
+                    self.load_local().map(|(services, _)| services)
                 }
             }
         } else {
             log::info!("未配置远程 URL,直接从本地加载");
-            self.load_local()
+            self.load_local().map(|(services, _)| services)
         };
 
         result
     }
 
+    /// 强制刷新推荐服务列表，并返回实际使用的数据源
+    ///
+    /// 远程获取失败时不再静默回退：错误信息会写入结果的 `error` 字段，
+    /// 并优先沿用上次的缓存 (source = cache)，没有缓存时才回退到本地/内嵌配置
+    pub async fn refresh(&self, timeout: Duration) -> AppResult<RecommendationRefreshResult> {
+        let Some(url) = self.remote_url.clone() else {
+            log::info!("未配置远程 URL,直接从本地加载");
+            let (services, source) = self.load_local()?;
+            return Ok(Self::refresh_result(source, services, None, None));
+        };
+
+        log::info!("刷新推荐服务: {} (超时 {:?})", url, timeout);
+        let started = Instant::now();
+        let fetched = self.load_remote(&url, timeout).await;
+        let latency_ms = Some(started.elapsed().as_millis() as u64);
+
+        match fetched {
+            Ok(services) => {
+                log::info!("成功从远程刷新 {} 个推荐服务", services.len());
+                self.update_cache(services.clone());
+                Ok(Self::refresh_result(RefreshSource::Remote, services, latency_ms, None))
+            }
+            Err(e) => {
+                let error = e.to_string();
+                log::warn!("从远程刷新推荐服务失败: {}", error);
+
+                let cached = self.cache.lock().unwrap().as_ref().map(|c| c.services.clone());
+                if let Some(services) = cached.filter(|s| !s.is_empty()) {
+                    return Ok(Self::refresh_result(
+                        RefreshSource::Cache,
+                        services,
+                        latency_ms,
+                        Some(error),
+                    ));
+                }
+
+                let (services, source) = self.load_local()?;
+                Ok(Self::refresh_result(source, services, latency_ms, Some(error)))
+            }
+        }
+    }
+
+    fn refresh_result(
+        source: RefreshSource,
+        services: Vec<RecommendedService>,
+        latency_ms: Option<u64>,
+        error: Option<String>,
+    ) -> RecommendationRefreshResult {
+        RecommendationRefreshResult {
+            source,
+            count: services.len(),
+            latency_ms,
+            error,
+            services,
+            refreshed_at: now_rfc3339(),
+        }
+    }
+
     /// 从远程 URL 加载推荐服务
-    async fn load_remote(&self, url: &str) -> AppResult<Vec<RecommendedService>> {
-        // 使用 reqwest 进行 HTTP 请求
-        let response = reqwest::get(url).await.map_err(|e| AppError::ServiceError {
-            message: format!("HTTP 请求失败: {}", e),
+    async fn load_remote(&self, url: &str, timeout: Duration) -> AppResult<Vec<RecommendedService>> {
+        // 使用 reqwest 进行 HTTP 请求 (带超时，避免远程端点挂起时阻塞刷新)
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| AppError::ServiceError {
+                message: format!("创建 HTTP 客户端失败: {}", e),
+            })?;
+
+        let response = client.get(url).send().await.map_err(|e| AppError::ServiceError {
+            message: if e.is_timeout() {
+                format!("HTTP 请求超时 ({} 秒)", timeout.as_secs())
+            } else {
+                format!("HTTP 请求失败: {}", e)
+            },
         })?;
 
         if !response.status().is_success() {
@@ -200,8 +274,8 @@ impl RecommendationService {
         Ok(services)
     }
 
-    /// 从本地文件或内嵌配置加载推荐服务
-    fn load_local(&self) -> AppResult<Vec<RecommendedService>> {
+    /// 从本地文件或内嵌配置加载推荐服务，同时返回实际使用的数据源
+    fn load_local(&self) -> AppResult<(Vec<RecommendedService>, RefreshSource)> {
         let loaded_at = now_rfc3339();
 
         // 如果配置了本地路径，优先从本地文件加载
@@ -222,7 +296,7 @@ impl RecommendationService {
 
                     log::info!("成功从本地文件加载 {} 个推荐服务", services.len());
                     self.update_cache(services.clone());
-                    return Ok(services);
+                    return Ok((services, RefreshSource::Local));
                 }
 
                 // 回退到旧格式 (recommendations.json)
@@ -250,7 +324,7 @@ impl RecommendationService {
 
                 log::info!("成功从本地文件加载 {} 个推荐服务（旧格式）", services.len());
                 self.update_cache(services.clone());
-                return Ok(services);
+                return Ok((services, RefreshSource::Local));
             }
         }
 
@@ -267,7 +341,7 @@ impl RecommendationService {
 
         log::info!("成功从内嵌配置加载 {} 个推荐服务", services.len());
         self.update_cache(services.clone());
-        Ok(services)
+        Ok((services, RefreshSource::Embedded))
     }
 
     /// 更新缓存
@@ -286,13 +360,6 @@ impl RecommendationService {
         let mut cache = self.cache.lock().unwrap();
         *cache = Some(cache_data);
     }
-
-    /// 清空缓存
-    pub fn clear_cache(&self) {
-        let mut cache = self.cache.lock().unwrap();
-        *cache = None;
-        log::info!("已清空推荐服务缓存");
-    }
 }
 
 #[cfg(test)]
@@ -324,4 +391,39 @@ mod tests {
 
         assert!(!cache2.is_expired());
     }
+
+    #[tokio::test]
+    async fn test_refresh_without_remote_uses_embedded() {
+        let service = RecommendationService::new(None, None, 3600);
+        let result = service.refresh(DEFAULT_REMOTE_TIMEOUT).await.unwrap();
+
+        assert_eq!(result.source, RefreshSource::Embedded);
+        assert_eq!(result.count, result.services.len());
+        assert!(result.latency_ms.is_none());
+        assert!(result.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_surfaces_remote_error_and_uses_cache() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/providers.json", listener.local_addr().unwrap());
+        // 接受连接但从不响应，模拟挂起的远程端点
+        let _server = tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                held.push(stream);
+            }
+        });
+
+        let service = RecommendationService::new(Some(url), None, 3600);
+        let first = service.refresh(Duration::from_millis(200)).await.unwrap();
+        assert_eq!(first.source, RefreshSource::Embedded);
+        assert!(first.error.as_deref().unwrap().contains("超时"));
+        assert!(first.latency_ms.is_some());
+
+        let second = service.refresh(Duration::from_millis(200)).await.unwrap();
+        assert_eq!(second.source, RefreshSource::Cache);
+        assert_eq!(second.count, first.count);
+        assert!(second.error.is_some());
+    }
 }
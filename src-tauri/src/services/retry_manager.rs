@@ -3,6 +3,7 @@
 use crate::models::error_classifier::ErrorRecoverability;
 use crate::models::failure_counter::FailureCounter;
 use crate::models::retry_strategy::RetryStrategy;
+use chrono::{DateTime, Local};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
@@ -13,6 +14,9 @@ pub struct RetryManager {
 
     /// 失败计数器 Map (config_id => FailureCounter)
     counters: Arc<RwLock<HashMap<i64, FailureCounter>>>,
+
+    /// 下一次允许重试的时间 Map (config_id => 时间)
+    next_retry_at: Arc<RwLock<HashMap<i64, DateTime<Local>>>>,
 }
 
 impl RetryManager {
@@ -21,6 +25,7 @@ impl RetryManager {
         Self {
             strategy,
             counters: Arc::new(RwLock::new(HashMap::new())),
+            next_retry_at: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -38,6 +43,16 @@ impl RetryManager {
         &self,
         config_id: i64,
         recoverability: &ErrorRecoverability,
+    ) -> (bool, u32) {
+        self.should_retry_with_strategy(config_id, recoverability, &self.strategy)
+    }
+
+    /// 按指定策略判断是否应该重试 (用于分组级别的重试策略)
+    pub fn should_retry_with_strategy(
+        &self,
+        config_id: i64,
+        recoverability: &ErrorRecoverability,
+        strategy: &RetryStrategy,
     ) -> (bool, u32) {
         // 不可恢复错误直接返回 false
         if !recoverability.should_retry() {
@@ -51,7 +66,7 @@ impl RetryManager {
             .unwrap_or(0);
 
         // 检查是否超过最大重试次数
-        let should_retry = current_count < self.strategy.max_retries;
+        let should_retry = current_count < strategy.max_retries;
 
         (should_retry, current_count)
     }
@@ -62,9 +77,19 @@ impl RetryManager {
     ///   - recoverability: 错误可恢复性
     /// 返回: 延迟时间 (毫秒)
     pub fn calculate_delay(&self, config_id: i64, recoverability: &ErrorRecoverability) -> u32 {
+        self.calculate_delay_with_strategy(config_id, recoverability, &self.strategy)
+    }
+
+    /// 按指定策略计算重试延迟 (毫秒)
+    pub fn calculate_delay_with_strategy(
+        &self,
+        config_id: i64,
+        recoverability: &ErrorRecoverability,
+        strategy: &RetryStrategy,
+    ) -> u32 {
         // 限流错误使用特殊延迟
         if recoverability.needs_rate_limit_delay() {
-            return self.strategy.rate_limit_delay_ms;
+            return strategy.rate_limit_delay_ms;
         }
 
         let counters = self.counters.read().unwrap();
@@ -74,7 +99,7 @@ impl RetryManager {
             .unwrap_or(0);
 
         // 使用指数退避算法计算延迟
-        self.strategy.calculate_delay_with_jitter(current_count)
+        strategy.calculate_delay_with_jitter(current_count)
     }
 
     /// 增加失败计数
@@ -87,12 +112,24 @@ impl RetryManager {
         counter.increment()
     }
 
+    /// 记录重试退避：从现在起 delay_ms 毫秒后允许下一次重试
+    pub fn schedule_retry(&self, config_id: i64, delay_ms: u32) {
+        let next = Local::now() + chrono::Duration::milliseconds(delay_ms as i64);
+        self.next_retry_at.write().unwrap().insert(config_id, next);
+    }
+
+    /// 获取下一次允许重试的时间
+    pub fn get_next_retry_at(&self, config_id: i64) -> Option<DateTime<Local>> {
+        self.next_retry_at.read().unwrap().get(&config_id).copied()
+    }
+
     /// 重置失败计数 (成功响应后调用)
     pub fn reset_counter(&self, config_id: i64) {
         let counters = self.counters.read().unwrap();
         if let Some(counter) = counters.get(&config_id) {
             counter.reset();
         }
+        self.next_retry_at.write().unwrap().remove(&config_id);
     }
 
     /// 获取当前失败计数
@@ -119,6 +156,7 @@ impl RetryManager {
     pub fn clear_all_counters(&self) {
         let mut counters = self.counters.write().unwrap();
         counters.clear();
+        self.next_retry_at.write().unwrap().clear();
     }
 
     /// 更新重试策略
@@ -137,6 +175,7 @@ impl Clone for RetryManager {
         Self {
             strategy: self.strategy.clone(),
             counters: Arc::clone(&self.counters),
+            next_retry_at: Arc::clone(&self.next_retry_at),
         }
     }
 }
@@ -215,6 +254,25 @@ mod tests {
         assert_eq!(delay, 30000);
     }
 
+    #[test]
+    fn test_group_strategy_override() {
+        let manager = RetryManager::with_default_strategy();
+        let local_strategy = RetryStrategy::new(1, 200, 1000, 1000);
+
+        manager.increment_failure(1);
+
+        // 默认策略允许继续重试,分组策略只允许 1 次
+        assert!(manager.should_retry(1, &ErrorRecoverability::Recoverable).0);
+        assert!(!manager
+            .should_retry_with_strategy(1, &ErrorRecoverability::Recoverable, &local_strategy)
+            .0);
+
+        assert_eq!(
+            manager.calculate_delay_with_strategy(1, &ErrorRecoverability::RateLimit, &local_strategy),
+            1000
+        );
+    }
+
     #[test]
     fn test_increment_failure() {
         let manager = RetryManager::with_default_strategy();
@@ -281,6 +339,21 @@ mod tests {
         assert_eq!(manager.get_all_failure_counts().len(), 0);
     }
 
+    #[test]
+    fn test_schedule_retry_cleared_on_reset() {
+        let manager = RetryManager::with_default_strategy();
+        assert!(manager.get_next_retry_at(1).is_none());
+
+        manager.increment_failure(1);
+        manager.schedule_retry(1, 5000);
+        let next = manager.get_next_retry_at(1).unwrap();
+        assert!(next > Local::now() + chrono::Duration::milliseconds(4000));
+
+        manager.reset_counter(1);
+        assert_eq!(manager.get_failure_count(1), 0);
+        assert!(manager.get_next_retry_at(1).is_none());
+    }
+
     #[test]
     fn test_clone_shares_counters() {
         let manager1 = RetryManager::with_default_strategy();
@@ -0,0 +1,313 @@
+/**
+ * Routing Snapshot Service
+ * 路由状态快照：保存/恢复激活分组、激活配置以及启用状态
+ *
+ * 快照只负责数据库层面的读写与启用状态恢复，
+ * 激活分组/配置的切换由 ProxyService 完成。
+ */
+
+use crate::models::error::{AppError, AppResult};
+use crate::models::routing_snapshot::{
+    ConfigEnabledFlag, GroupEnabledFlag, RoutingSnapshot, RoutingSnapshotFlags,
+};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+/// 路由状态快照服务
+pub struct RoutingSnapshotService;
+
+/// 解析 RoutingSnapshot 行
+/// 列顺序: id, name, active_group_id, active_config_id, enabled_flags, created_at, updated_at
+fn map_row_to_snapshot(row: &Row) -> rusqlite::Result<RoutingSnapshot> {
+    let flags_json: String = row.get(4)?;
+    let flags: RoutingSnapshotFlags = serde_json::from_str(&flags_json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+
+    Ok(RoutingSnapshot {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        active_group_id: row.get(2)?,
+        active_config_id: row.get(3)?,
+        flags,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+impl RoutingSnapshotService {
+    /// 读取当前所有配置与分组的启用状态
+    pub fn capture_flags(conn: &Connection) -> AppResult<RoutingSnapshotFlags> {
+        let mut stmt = conn
+            .prepare("SELECT id, group_id, is_enabled FROM ApiConfig ORDER BY id ASC")
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("准备查询失败: {}", e),
+            })?;
+        let configs = stmt
+            .query_map([], |row| {
+                Ok(ConfigEnabledFlag {
+                    config_id: row.get(0)?,
+                    group_id: row.get(1)?,
+                    is_enabled: row.get(2)?,
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("读取配置启用状态失败: {}", e),
+            })?;
+
+        let mut stmt = conn
+            .prepare("SELECT id, auto_switch_enabled FROM ConfigGroup ORDER BY id ASC")
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("准备查询失败: {}", e),
+            })?;
+        let groups = stmt
+            .query_map([], |row| {
+                Ok(GroupEnabledFlag {
+                    group_id: row.get(0)?,
+                    auto_switch_enabled: row.get(1)?,
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("读取分组自动切换状态失败: {}", e),
+            })?;
+
+        Ok(RoutingSnapshotFlags { configs, groups })
+    }
+
+    /// 保存快照 (同名快照会被覆盖)
+    pub fn save(
+        conn: &Connection,
+        name: &str,
+        active_group_id: Option<i64>,
+        active_config_id: Option<i64>,
+    ) -> AppResult<RoutingSnapshot> {
+        RoutingSnapshot::validate_name(name).map_err(|e| AppError::ValidationError {
+            field: "name".to_string(),
+            message: e,
+        })?;
+        let name = name.trim();
+
+        let flags = Self::capture_flags(conn)?;
+        let flags_json = serde_json::to_string(&flags).map_err(|e| AppError::ParseError {
+            message: format!("序列化快照启用状态失败: {}", e),
+        })?;
+
+        conn.execute(
+            "INSERT INTO RoutingSnapshot (name, active_group_id, active_config_id, enabled_flags)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(name) DO UPDATE SET
+                active_group_id = excluded.active_group_id,
+                active_config_id = excluded.active_config_id,
+                enabled_flags = excluded.enabled_flags,
+                updated_at = CURRENT_TIMESTAMP",
+            params![name, active_group_id, active_config_id, flags_json],
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("保存路由快照失败: {}", e),
+        })?;
+
+        log::info!(
+            "路由快照已保存: {} (group: {:?}, config: {:?}, {} 个配置, {} 个分组)",
+            name,
+            active_group_id,
+            active_config_id,
+            flags.configs.len(),
+            flags.groups.len()
+        );
+
+        Self::get_by_name(conn, name)
+    }
+
+    /// 按名称获取快照
+    pub fn get_by_name(conn: &Connection, name: &str) -> AppResult<RoutingSnapshot> {
+        conn.query_row(
+            "SELECT id, name, active_group_id, active_config_id, enabled_flags, created_at, updated_at
+             FROM RoutingSnapshot WHERE name = ?1",
+            [name.trim()],
+            map_row_to_snapshot,
+        )
+        .optional()
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("获取路由快照失败: {}", e),
+        })?
+        .ok_or_else(|| AppError::NotFound {
+            resource: "RoutingSnapshot".to_string(),
+            id: name.to_string(),
+        })
+    }
+
+    /// 列出所有快照 (最近更新的在前)
+    pub fn list(conn: &Connection) -> AppResult<Vec<RoutingSnapshot>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name, active_group_id, active_config_id, enabled_flags, created_at, updated_at
+                 FROM RoutingSnapshot ORDER BY updated_at DESC, id DESC",
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("准备查询失败: {}", e),
+            })?;
+
+        stmt.query_map([], map_row_to_snapshot)
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("列出路由快照失败: {}", e),
+            })
+    }
+
+    /// 删除快照
+    pub fn delete(conn: &Connection, name: &str) -> AppResult<()> {
+        let affected = conn
+            .execute("DELETE FROM RoutingSnapshot WHERE name = ?1", [name.trim()])
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("删除路由快照失败: {}", e),
+            })?;
+
+        if affected == 0 {
+            return Err(AppError::NotFound {
+                resource: "RoutingSnapshot".to_string(),
+                id: name.to_string(),
+            });
+        }
+
+        log::info!("路由快照已删除: {}", name);
+        Ok(())
+    }
+
+    /// 恢复快照中的启用状态
+    ///
+    /// 已删除的配置/分组会被跳过，快照之后新建的配置保持不变。
+    ///
+    /// # 返回
+    /// - (恢复的配置数, 恢复的分组数, 跳过的条目描述)
+    pub fn apply_flags(
+        conn: &Connection,
+        flags: &RoutingSnapshotFlags,
+    ) -> AppResult<(usize, usize, Vec<String>)> {
+        let tx = conn.unchecked_transaction().map_err(|e| AppError::DatabaseError {
+            message: format!("开启事务失败: {}", e),
+        })?;
+
+        let mut restored_configs = 0;
+        let mut restored_groups = 0;
+        let mut skipped = Vec::new();
+
+        for flag in &flags.configs {
+            let affected = tx
+                .execute(
+                    "UPDATE ApiConfig SET is_enabled = ?1, updated_at = CURRENT_TIMESTAMP
+                     WHERE id = ?2 AND is_enabled != ?1",
+                    params![flag.is_enabled, flag.config_id],
+                )
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("恢复配置启用状态失败: {}", e),
+                })?;
+
+            if affected > 0 {
+                restored_configs += 1;
+            } else if !Self::row_exists(&tx, "ApiConfig", flag.config_id)? {
+                skipped.push(format!("config:{}", flag.config_id));
+            }
+        }
+
+        for flag in &flags.groups {
+            let affected = tx
+                .execute(
+                    "UPDATE ConfigGroup SET auto_switch_enabled = ?1, updated_at = CURRENT_TIMESTAMP
+                     WHERE id = ?2 AND auto_switch_enabled != ?1",
+                    params![flag.auto_switch_enabled, flag.group_id],
+                )
+                .map_err(|e| AppError::DatabaseError {
+                    message: format!("恢复分组自动切换状态失败: {}", e),
+                })?;
+
+            if affected > 0 {
+                restored_groups += 1;
+            } else if !Self::row_exists(&tx, "ConfigGroup", flag.group_id)? {
+                skipped.push(format!("group:{}", flag.group_id));
+            }
+        }
+
+        tx.commit().map_err(|e| AppError::DatabaseError {
+            message: format!("提交事务失败: {}", e),
+        })?;
+
+        Ok((restored_configs, restored_groups, skipped))
+    }
+
+    fn row_exists(conn: &Connection, table: &str, id: i64) -> AppResult<bool> {
+        conn.query_row(
+            &format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?1)", table),
+            [id],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查 {} 是否存在失败: {}", table, e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_conn() -> Connection {
+        let conn = crate::db::test_db();
+        conn.execute_batch(
+            "INSERT INTO ConfigGroup (id, name, auto_switch_enabled) VALUES (100, 'g', 1);
+             INSERT INTO ApiConfig (id, name, api_key, server_url, server_port, group_id, is_enabled)
+             VALUES (200, 'a', 'k', 'https://a.example.com', 443, 100, 1),
+                    (201, 'b', 'k', 'https://b.example.com', 443, 100, 0);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_save_and_restore_flags() {
+        let conn = setup_conn();
+
+        let snapshot = RoutingSnapshotService::save(&conn, " known-good ", Some(100), Some(200)).unwrap();
+        assert_eq!(snapshot.name, "known-good");
+        assert_eq!(snapshot.active_config_id, Some(200));
+        assert!(snapshot.flags.configs.iter().any(|f| f.config_id == 201 && !f.is_enabled));
+
+        conn.execute_batch(
+            "UPDATE ApiConfig SET is_enabled = 0 WHERE id = 200;
+             UPDATE ApiConfig SET is_enabled = 1 WHERE id = 201;
+             UPDATE ConfigGroup SET auto_switch_enabled = 0 WHERE id = 100;
+             DELETE FROM ApiConfig WHERE id = 201;",
+        )
+        .unwrap();
+
+        let loaded = RoutingSnapshotService::get_by_name(&conn, "known-good").unwrap();
+        let (configs, groups, skipped) = RoutingSnapshotService::apply_flags(&conn, &loaded.flags).unwrap();
+        assert_eq!(configs, 1);
+        assert_eq!(groups, 1);
+        assert_eq!(skipped, vec!["config:201".to_string()]);
+
+        let enabled: bool = conn
+            .query_row("SELECT is_enabled FROM ApiConfig WHERE id = 200", [], |row| row.get(0))
+            .unwrap();
+        assert!(enabled);
+    }
+
+    #[test]
+    fn test_save_overwrites_and_delete() {
+        let conn = setup_conn();
+
+        RoutingSnapshotService::save(&conn, "s", Some(100), Some(200)).unwrap();
+        RoutingSnapshotService::save(&conn, "s", Some(100), None).unwrap();
+
+        let snapshots = RoutingSnapshotService::list(&conn).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].active_config_id, None);
+
+        RoutingSnapshotService::delete(&conn, "s").unwrap();
+        assert!(matches!(
+            RoutingSnapshotService::get_by_name(&conn, "s"),
+            Err(AppError::NotFound { .. })
+        ));
+        assert!(RoutingSnapshotService::save(&conn, "", None, None).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+/**
+ * Deprecated server_port Migration
+ * 检测并迁移已弃用的 ApiConfig.server_port
+ *
+ * 转发目标只由 server_url 决定 (见 parse_server_url)，server_port 不参与转发，
+ * 但旧版本允许用户填写端口，容易误以为端口生效。
+ *
+ * - server_url 未显式指定端口、server_port 为有意义的值 (非 443 且非协议默认端口) 时，
+ *   迁移将端口并入 server_url，并将 server_port 重置为 443
+ * - server_url 已显式指定其它端口时以 URL 为准，server_port 保持原值以便报告
+ */
+
+use crate::models::error::{AppError, AppResult};
+use crate::utils::server_url::parse_server_url;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// server_port 的占位值 (数据库约束要求 1-65535，新建配置统一写入 443)
+const PLACEHOLDER_PORT: i32 = 443;
+
+/// 已弃用端口的处理状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeprecatedPortStatus {
+    /// server_url 未指定端口，可以将 server_port 并入 server_url
+    Foldable,
+    /// server_url 已指定其它端口，server_port 被忽略
+    Ignored,
+}
+
+/// 已弃用端口会影响行为的配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeprecatedServerPortUsage {
+    pub config_id: i64,
+    pub config_name: String,
+    pub server_url: String,
+    /// 已弃用的 server_port
+    pub server_port: i32,
+    /// 按 server_url 实际连接的端口
+    pub effective_port: u16,
+    pub status: DeprecatedPortStatus,
+    /// 并入端口后的 server_url (仅 Foldable)
+    pub suggested_url: Option<String>,
+}
+
+/// 已弃用端口迁移服务
+pub struct ServerPortMigrationService;
+
+impl ServerPortMigrationService {
+    /// 列出 server_port 与实际连接端口不一致的配置
+    pub fn detect(conn: &Connection) -> AppResult<Vec<DeprecatedServerPortUsage>> {
+        let mut stmt = conn
+            .prepare("SELECT id, name, server_url, server_port FROM ApiConfig ORDER BY id")
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("查询配置端口失败: {}", e),
+            })?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i32>(3)?,
+                ))
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("查询配置端口失败: {}", e),
+            })?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(config_id, config_name, server_url, server_port)| {
+                classify(&server_url, server_port).map(|(effective_port, status, suggested_url)| {
+                    DeprecatedServerPortUsage {
+                        config_id,
+                        config_name,
+                        server_url,
+                        server_port,
+                        effective_port,
+                        status,
+                        suggested_url,
+                    }
+                })
+            })
+            .collect())
+    }
+
+    /// 将可并入的 server_port 写入 server_url，返回迁移的配置数
+    pub fn fold_ports(conn: &Connection) -> AppResult<usize> {
+        let foldable: Vec<DeprecatedServerPortUsage> = Self::detect(conn)?
+            .into_iter()
+            .filter(|usage| usage.status == DeprecatedPortStatus::Foldable)
+            .collect();
+
+        for usage in &foldable {
+            let Some(url) = usage.suggested_url.as_deref() else {
+                continue;
+            };
+            conn.execute(
+                "UPDATE ApiConfig SET server_url = ?1, server_port = ?2, updated_at = CURRENT_TIMESTAMP WHERE id = ?3",
+                params![url, PLACEHOLDER_PORT, usage.config_id],
+            )
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("迁移配置 {} 的端口失败: {}", usage.config_id, e),
+            })?;
+            log::info!(
+                "配置 {} ({}) 的端口 {} 已并入服务器地址: {} -> {}",
+                usage.config_id,
+                usage.config_name,
+                usage.server_port,
+                usage.server_url,
+                url
+            );
+        }
+
+        Ok(foldable.len())
+    }
+}
+
+/// 判断已弃用端口是否会影响行为，返回 (实际连接端口, 状态, 建议地址)
+fn classify(server_url: &str, server_port: i32) -> Option<(u16, DeprecatedPortStatus, Option<String>)> {
+    let parsed = parse_server_url(server_url);
+    let default_port: u16 = if parsed.is_https { 443 } else { 80 };
+    let effective_port = parsed
+        .target_addr
+        .rsplit_once(':')
+        .and_then(|(_, port)| port.parse().ok())
+        .unwrap_or(default_port);
+
+    let port = u16::try_from(server_port).ok()?;
+    if server_port == PLACEHOLDER_PORT || port == effective_port {
+        return None;
+    }
+
+    if parsed.host_and_port.contains(':') {
+        return Some((effective_port, DeprecatedPortStatus::Ignored, None));
+    }
+    if port == default_port {
+        return None;
+    }
+
+    let scheme = if parsed.is_https { "https" } else { "http" };
+    let suggested_url = format!("{}://{}:{}{}", scheme, parsed.host, port, parsed.path_prefix);
+    Some((effective_port, DeprecatedPortStatus::Foldable, Some(suggested_url)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert!(classify("https://api.example.com", 443).is_none());
+        assert!(classify("http://localhost", 80).is_none());
+        assert!(classify("http://localhost:8080", 8080).is_none());
+
+        let (effective, status, url) = classify("http://localhost/api", 8080).unwrap();
+        assert_eq!(effective, 80);
+        assert_eq!(status, DeprecatedPortStatus::Foldable);
+        assert_eq!(url.as_deref(), Some("http://localhost:8080/api"));
+
+        let (effective, status, url) = classify("https://api.example.com:8443", 9000).unwrap();
+        assert_eq!(effective, 8443);
+        assert_eq!(status, DeprecatedPortStatus::Ignored);
+        assert!(url.is_none());
+    }
+
+    #[test]
+    fn test_fold_ports() {
+        let conn = crate::db::test_db();
+        conn.execute_batch(
+            "INSERT INTO ApiConfig (id, name, api_key, server_url, server_port) VALUES
+                 (1, 'plain', 'k', 'https://a.example.com', 443),
+                 (2, 'foldable', 'k', 'http://192.168.1.5/v1', 3000),
+                 (3, 'ignored', 'k', 'https://b.example.com:8443', 9000);",
+        )
+        .unwrap();
+
+        let usages = ServerPortMigrationService::detect(&conn).unwrap();
+        assert_eq!(usages.len(), 2);
+        assert_eq!(usages[0].config_id, 2);
+        assert_eq!(usages[1].status, DeprecatedPortStatus::Ignored);
+
+        assert_eq!(ServerPortMigrationService::fold_ports(&conn).unwrap(), 1);
+        let (url, port): (String, i32) = conn
+            .query_row("SELECT server_url, server_port FROM ApiConfig WHERE id = 2", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(url, "http://192.168.1.5:3000/v1");
+        assert_eq!(port, 443);
+
+        // 迁移后只剩被忽略的端口
+        let usages = ServerPortMigrationService::detect(&conn).unwrap();
+        assert_eq!(usages.len(), 1);
+        assert_eq!(usages[0].config_id, 3);
+    }
+}
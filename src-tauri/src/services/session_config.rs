@@ -198,6 +198,22 @@ lazy_static::lazy_static! {
     pub static ref SESSION_CONFIG_MAP: SessionConfigMap = SessionConfigMap::new();
 }
 
+/// Prefix of the per-config sessions embedded in exported environment variables
+const ENV_SESSION_PREFIX: &str = "env-";
+
+/// Session id for exported environment variables of a config (`env-<config_id>`)
+pub fn env_session_id(config_id: i64) -> String {
+    format!("{}{}", ENV_SESSION_PREFIX, config_id)
+}
+
+/// Config id encoded in an `env-<config_id>` session id
+///
+/// These sessions stay valid after an app restart or stale-session cleanup:
+/// the proxy resolves them directly to the config when they are not registered.
+pub fn env_session_config_id(session_id: &str) -> Option<i64> {
+    session_id.strip_prefix(ENV_SESSION_PREFIX)?.parse().ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -241,4 +257,12 @@ mod tests {
         let sessions = map.list_sessions();
         assert_eq!(sessions.len(), 2);
     }
+
+    #[test]
+    fn test_env_session_id() {
+        assert_eq!(env_session_id(42), "env-42");
+        assert_eq!(env_session_config_id("env-42"), Some(42));
+        assert_eq!(env_session_config_id("env-abc"), None);
+        assert_eq!(env_session_config_id("session_42"), None);
+    }
 }
@@ -5,12 +5,13 @@
 //! - 项目级命令: .claude/commands/
 
 use crate::models::claude_advanced::{
-    CommandScope, SlashCommand, SlashCommandInfo, SlashCommandInput, SlashCommandMeta,
+    BrokenSlashCommand, CommandScope, SlashCommand, SlashCommandInfo, SlashCommandInput,
+    SlashCommandMeta,
 };
 use crate::models::error::{AppError, AppResult};
 use regex::Regex;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// 斜杠命令配置管理服务
 pub struct SlashCommandService;
@@ -438,6 +439,104 @@ impl SlashCommandService {
         Ok(command.body.unwrap_or_default())
     }
 
+    // ============================================================
+    // 一致性检查
+    // ============================================================
+
+    /// 检查无法读取的命令文件
+    /// 列表会跳过这些文件 (如指向已删除文件的符号链接、无读取权限或非 UTF-8 的文件)，
+    /// 但在外部手动编辑后它们仍留在命令目录中
+    pub fn validate_commands(project_root: Option<PathBuf>) -> AppResult<Vec<BrokenSlashCommand>> {
+        let mut broken = Vec::new();
+
+        if let Ok(user_dir) = Self::get_user_commands_dir() {
+            broken.extend(Self::find_broken_in_dir(&user_dir, CommandScope::User)?);
+        }
+
+        if let Some(ref root) = project_root {
+            let project_dir = Self::get_project_commands_dir(root);
+            broken.extend(Self::find_broken_in_dir(&project_dir, CommandScope::Project)?);
+        }
+
+        broken.sort_by(|a, b| a.name.cmp(&b.name));
+
+        if !broken.is_empty() {
+            log::warn!("发现 {} 个无法读取的斜杠命令文件", broken.len());
+        }
+        Ok(broken)
+    }
+
+    /// 删除无法读取的命令文件 (符号链接只删除链接本身)，返回已删除的命令
+    pub fn prune_broken_commands(project_root: Option<PathBuf>) -> AppResult<Vec<BrokenSlashCommand>> {
+        let broken = Self::validate_commands(project_root)?;
+        Self::remove_broken(broken)
+    }
+
+    fn remove_broken(broken: Vec<BrokenSlashCommand>) -> AppResult<Vec<BrokenSlashCommand>> {
+        for command in &broken {
+            fs::remove_file(&command.file_path).map_err(|e| AppError::IoError {
+                message: format!("删除命令文件 {} 失败: {}", command.file_path, e),
+            })?;
+            log::info!("已删除无法读取的斜杠命令: /{} ({})", command.name, command.file_path);
+        }
+        Ok(broken)
+    }
+
+    /// 扫描目录中无法读取的 .md 命令文件
+    fn find_broken_in_dir(dir: &Path, scope: CommandScope) -> AppResult<Vec<BrokenSlashCommand>> {
+        let mut broken = Vec::new();
+
+        if !dir.exists() {
+            return Ok(broken);
+        }
+
+        let entries = fs::read_dir(dir).map_err(|e| AppError::IoError {
+            message: format!("读取命令目录失败: {}", e),
+        })?;
+
+        for entry in entries {
+            let entry = entry.map_err(|e| AppError::IoError {
+                message: format!("读取目录项失败: {}", e),
+            })?;
+
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "md") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+
+            let is_symlink = fs::symlink_metadata(&path).is_ok_and(|meta| meta.file_type().is_symlink());
+            let (missing_path, reason) = match fs::metadata(&path) {
+                Ok(meta) if meta.is_dir() => continue,
+                Ok(_) => match fs::read_to_string(&path) {
+                    Ok(_) => continue,
+                    Err(e) => (None, format!("读取命令文件失败: {}", e)),
+                },
+                Err(e) if is_symlink => {
+                    // 相对链接目标按命令目录解析
+                    let target = fs::read_link(&path).map(|target| dir.join(target)).ok();
+                    (
+                        target.map(|t| t.to_string_lossy().to_string()),
+                        format!("链接的命令文件不存在: {}", e),
+                    )
+                }
+                Err(e) => (None, format!("读取命令文件失败: {}", e)),
+            };
+
+            broken.push(BrokenSlashCommand {
+                name: name.to_string(),
+                scope,
+                file_path: path.to_string_lossy().to_string(),
+                missing_path,
+                reason,
+            });
+        }
+
+        Ok(broken)
+    }
+
     // ============================================================
     // 迁移工具
     // ============================================================
@@ -538,6 +637,32 @@ This is the command body."#;
         assert!(body.contains("# Command Body"));
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_find_and_remove_broken_commands() {
+        let dir = std::env::temp_dir().join(format!("ccp-commands-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("ok.md"), "---\ndescription: ok\n---\nbody").unwrap();
+        fs::write(dir.join("binary.md"), [0xffu8, 0xfe, 0x00]).unwrap();
+        std::os::unix::fs::symlink("deleted-prompt.md", dir.join("dangling.md")).unwrap();
+
+        let broken = SlashCommandService::find_broken_in_dir(&dir, CommandScope::Project).unwrap();
+        let mut names: Vec<_> = broken.iter().map(|b| b.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["binary", "dangling"]);
+
+        let dangling = broken.iter().find(|b| b.name == "dangling").unwrap();
+        assert_eq!(
+            dangling.missing_path.as_deref(),
+            Some(dir.join("deleted-prompt.md").to_string_lossy().as_ref())
+        );
+
+        SlashCommandService::remove_broken(broken).unwrap();
+        assert!(SlashCommandService::find_broken_in_dir(&dir, CommandScope::Project).unwrap().is_empty());
+        assert!(dir.join("ok.md").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_generate_frontmatter() {
         let meta = SlashCommandMeta {
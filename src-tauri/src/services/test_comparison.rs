@@ -0,0 +1,471 @@
+/**
+ * Test Run Comparison Service
+ * 对比同一配置在两个时间窗口内的成功率与延迟，识别性能回退
+ *
+ * 数据来源:
+ * - TestResult: 手动/定时连通性测试
+ * - ProxyRequestLog: 实际代理请求
+ *
+ * 回退判定同时要求样本量足够、变化幅度超过阈值且统计显著 (95% 置信度)，
+ * 避免少量样本的随机波动被误报。
+ */
+
+use crate::db::DbPool;
+use crate::models::error::{AppError, AppResult};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// 每个窗口参与显著性判定的最少样本数
+const MIN_SAMPLES: usize = 5;
+
+/// 95% 置信度对应的 z 值
+const Z_CRITICAL: f64 = 1.96;
+
+/// 延迟上升超过该百分比才视为回退
+const LATENCY_REGRESSION_PCT: f64 = 20.0;
+
+/// 时间窗口 (RFC3339)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeWindow {
+    pub start: String,
+    pub end: String,
+}
+
+impl TimeWindow {
+    fn validate(&self, field: &str) -> AppResult<()> {
+        let parse = |value: &str| {
+            chrono::DateTime::parse_from_rfc3339(value).map_err(|e| AppError::ValidationError {
+                field: field.to_string(),
+                message: format!("时间格式无效 (需要 RFC3339): {}", e),
+            })
+        };
+
+        if parse(&self.start)? >= parse(&self.end)? {
+            return Err(AppError::ValidationError {
+                field: field.to_string(),
+                message: "窗口开始时间必须早于结束时间".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// 单个窗口的统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunStats {
+    pub sample_count: usize,
+    pub success_count: usize,
+    /// 成功率 (0.0 - 1.0)，无样本时为 0
+    pub success_rate: f64,
+    /// 成功请求的平均延迟
+    pub avg_latency_ms: Option<f64>,
+    pub p50_latency_ms: Option<i64>,
+    pub p95_latency_ms: Option<i64>,
+    /// 用于显著性检验的延迟样本
+    #[serde(skip)]
+    latencies: Vec<i64>,
+}
+
+/// 回退指标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegressionMetric {
+    SuccessRate,
+    AvgLatency,
+    P95Latency,
+}
+
+/// 检测到的回退
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Regression {
+    pub metric: RegressionMetric,
+    pub baseline: f64,
+    pub current: f64,
+    /// 相对基线的变化百分比（成功率为百分点）
+    pub change_pct: f64,
+    pub message: String,
+}
+
+/// 单一数据来源的对比结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceComparison {
+    pub baseline: RunStats,
+    pub current: RunStats,
+    /// 成功率变化（百分点，负数表示下降）
+    pub success_rate_delta: f64,
+    /// 平均延迟变化百分比
+    pub avg_latency_change_pct: Option<f64>,
+    /// p95 延迟变化百分比
+    pub p95_latency_change_pct: Option<f64>,
+    pub regressions: Vec<Regression>,
+}
+
+/// 两次测试窗口的对比结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestRunComparison {
+    pub config_id: i64,
+    pub baseline_window: TimeWindow,
+    pub current_window: TimeWindow,
+    /// 基于 TestResult 的对比
+    pub test_results: SourceComparison,
+    /// 基于 ProxyRequestLog 的对比
+    pub proxy_requests: SourceComparison,
+    pub has_regression: bool,
+}
+
+/// 测试结果对比服务
+pub struct TestComparisonService;
+
+impl TestComparisonService {
+    /// 对比配置在基线窗口与当前窗口内的表现
+    pub fn compare(
+        pool: &DbPool,
+        config_id: i64,
+        baseline_window: TimeWindow,
+        current_window: TimeWindow,
+    ) -> AppResult<TestRunComparison> {
+        baseline_window.validate("baseline_window")?;
+        current_window.validate("current_window")?;
+
+        pool.with_connection(|conn| {
+            let test_results = compare_stats(
+                RunStats::from_samples(Self::test_samples(conn, config_id, &baseline_window)?),
+                RunStats::from_samples(Self::test_samples(conn, config_id, &current_window)?),
+            );
+            let proxy_requests = compare_stats(
+                RunStats::from_samples(Self::request_samples(conn, config_id, &baseline_window)?),
+                RunStats::from_samples(Self::request_samples(conn, config_id, &current_window)?),
+            );
+
+            let has_regression =
+                !test_results.regressions.is_empty() || !proxy_requests.regressions.is_empty();
+
+            Ok(TestRunComparison {
+                config_id,
+                baseline_window,
+                current_window,
+                test_results,
+                proxy_requests,
+                has_regression,
+            })
+        })
+    }
+
+    /// 读取窗口内的测试结果样本 (是否成功, 延迟)
+    fn test_samples(
+        conn: &Connection,
+        config_id: i64,
+        window: &TimeWindow,
+    ) -> AppResult<Vec<(bool, Option<i64>)>> {
+        Self::query_samples(
+            conn,
+            "SELECT status = 'success', latency_ms FROM TestResult
+             WHERE config_id = ?1
+               AND julianday(test_at) >= julianday(?2) AND julianday(test_at) < julianday(?3)",
+            config_id,
+            window,
+        )
+    }
+
+    /// 读取窗口内的代理请求样本 (是否成功, 延迟)
+    fn request_samples(
+        conn: &Connection,
+        config_id: i64,
+        window: &TimeWindow,
+    ) -> AppResult<Vec<(bool, Option<i64>)>> {
+        Self::query_samples(
+            conn,
+            "SELECT is_success, latency_ms FROM ProxyRequestLog
+             WHERE config_id = ?1
+               AND julianday(request_at) >= julianday(?2) AND julianday(request_at) < julianday(?3)",
+            config_id,
+            window,
+        )
+    }
+
+    fn query_samples(
+        conn: &Connection,
+        sql: &str,
+        config_id: i64,
+        window: &TimeWindow,
+    ) -> AppResult<Vec<(bool, Option<i64>)>> {
+        let mut stmt = conn.prepare(sql).map_err(|e| AppError::DatabaseError {
+            message: format!("准备查询失败: {}", e),
+        })?;
+
+        let samples = stmt
+            .query_map(params![config_id, window.start, window.end], |row| {
+                Ok((row.get::<_, bool>(0)?, row.get::<_, Option<i64>>(1)?))
+            })
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("查询样本失败: {}", e),
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::DatabaseError {
+                message: format!("解析样本失败: {}", e),
+            })?;
+
+        Ok(samples)
+    }
+}
+
+impl RunStats {
+    /// 由 (是否成功, 延迟) 样本计算统计，延迟只统计成功的请求
+    fn from_samples(samples: Vec<(bool, Option<i64>)>) -> Self {
+        let sample_count = samples.len();
+        let success_count = samples.iter().filter(|(ok, _)| *ok).count();
+
+        let mut latencies: Vec<i64> = samples
+            .iter()
+            .filter(|(ok, _)| *ok)
+            .filter_map(|(_, latency)| *latency)
+            .collect();
+        latencies.sort_unstable();
+
+        let avg_latency_ms = (!latencies.is_empty())
+            .then(|| latencies.iter().sum::<i64>() as f64 / latencies.len() as f64);
+
+        Self {
+            sample_count,
+            success_count,
+            success_rate: if sample_count > 0 {
+                success_count as f64 / sample_count as f64
+            } else {
+                0.0
+            },
+            avg_latency_ms,
+            p50_latency_ms: percentile(&latencies, 0.50),
+            p95_latency_ms: percentile(&latencies, 0.95),
+            latencies,
+        }
+    }
+
+    /// 延迟样本方差 (无偏估计)
+    fn latency_variance(&self) -> Option<f64> {
+        let mean = self.avg_latency_ms?;
+        let n = self.latencies.len();
+        if n < 2 {
+            return None;
+        }
+        let sum_sq: f64 = self.latencies.iter().map(|&l| (l as f64 - mean).powi(2)).sum();
+        Some(sum_sq / (n - 1) as f64)
+    }
+}
+
+/// 最近秩法计算百分位（输入需已排序）
+fn percentile(sorted: &[i64], p: f64) -> Option<i64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// 相对变化百分比
+fn change_pct(baseline: f64, current: f64) -> Option<f64> {
+    (baseline > 0.0).then(|| (current - baseline) / baseline * 100.0)
+}
+
+/// 成功率下降是否显著（双比例 z 检验）
+fn success_rate_dropped(baseline: &RunStats, current: &RunStats) -> bool {
+    if baseline.sample_count < MIN_SAMPLES || current.sample_count < MIN_SAMPLES {
+        return false;
+    }
+    if current.success_rate >= baseline.success_rate {
+        return false;
+    }
+
+    let n1 = baseline.sample_count as f64;
+    let n2 = current.sample_count as f64;
+    let pooled = (baseline.success_count + current.success_count) as f64 / (n1 + n2);
+    let se = (pooled * (1.0 - pooled) * (1.0 / n1 + 1.0 / n2)).sqrt();
+    if se == 0.0 {
+        return false;
+    }
+
+    (baseline.success_rate - current.success_rate) / se > Z_CRITICAL
+}
+
+/// 平均延迟上升是否显著（Welch t 检验，样本量较大时按正态近似）
+fn latency_increased(baseline: &RunStats, current: &RunStats) -> bool {
+    if baseline.latencies.len() < MIN_SAMPLES || current.latencies.len() < MIN_SAMPLES {
+        return false;
+    }
+    let (Some(mean1), Some(mean2)) = (baseline.avg_latency_ms, current.avg_latency_ms) else {
+        return false;
+    };
+    let (Some(var1), Some(var2)) = (baseline.latency_variance(), current.latency_variance()) else {
+        return false;
+    };
+
+    let se = (var1 / baseline.latencies.len() as f64 + var2 / current.latencies.len() as f64).sqrt();
+    if se == 0.0 {
+        return mean2 > mean1;
+    }
+
+    (mean2 - mean1) / se > Z_CRITICAL
+}
+
+/// 对比两个窗口的统计并识别回退
+fn compare_stats(baseline: RunStats, current: RunStats) -> SourceComparison {
+    let mut regressions = Vec::new();
+
+    let success_rate_delta = (current.success_rate - baseline.success_rate) * 100.0;
+    if success_rate_dropped(&baseline, &current) {
+        regressions.push(Regression {
+            metric: RegressionMetric::SuccessRate,
+            baseline: baseline.success_rate,
+            current: current.success_rate,
+            change_pct: success_rate_delta,
+            message: format!(
+                "成功率下降 {:.1} 个百分点 ({:.1}% → {:.1}%)",
+                -success_rate_delta,
+                baseline.success_rate * 100.0,
+                current.success_rate * 100.0
+            ),
+        });
+    }
+
+    let avg_latency_change_pct = baseline
+        .avg_latency_ms
+        .zip(current.avg_latency_ms)
+        .and_then(|(b, c)| change_pct(b, c));
+    let p95_latency_change_pct = baseline
+        .p95_latency_ms
+        .zip(current.p95_latency_ms)
+        .and_then(|(b, c)| change_pct(b as f64, c as f64));
+
+    if latency_increased(&baseline, &current) {
+        if let Some(pct) = avg_latency_change_pct.filter(|pct| *pct >= LATENCY_REGRESSION_PCT) {
+            regressions.push(Regression {
+                metric: RegressionMetric::AvgLatency,
+                baseline: baseline.avg_latency_ms.unwrap_or_default(),
+                current: current.avg_latency_ms.unwrap_or_default(),
+                change_pct: pct,
+                message: format!("平均延迟上升 {:.0}%", pct),
+            });
+        }
+        if let Some(pct) = p95_latency_change_pct.filter(|pct| *pct >= LATENCY_REGRESSION_PCT) {
+            regressions.push(Regression {
+                metric: RegressionMetric::P95Latency,
+                baseline: baseline.p95_latency_ms.unwrap_or_default() as f64,
+                current: current.p95_latency_ms.unwrap_or_default() as f64,
+                change_pct: pct,
+                message: format!("p95 延迟上升 {:.0}%", pct),
+            });
+        }
+    }
+
+    SourceComparison {
+        baseline,
+        current,
+        success_rate_delta,
+        avg_latency_change_pct,
+        p95_latency_change_pct,
+        regressions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(successes: usize, failures: usize, latency: i64) -> Vec<(bool, Option<i64>)> {
+        let mut samples: Vec<_> = (0..successes)
+            .map(|i| (true, Some(latency + (i as i64 % 5) * 10)))
+            .collect();
+        samples.extend((0..failures).map(|_| (false, None)));
+        samples
+    }
+
+    #[test]
+    fn test_run_stats_percentiles() {
+        let stats = RunStats::from_samples((1..=20).map(|i| (true, Some(i * 100))).collect());
+        assert_eq!(stats.p50_latency_ms, Some(1000));
+        assert_eq!(stats.p95_latency_ms, Some(1900));
+        assert_eq!(stats.success_rate, 1.0);
+
+        let empty = RunStats::from_samples(Vec::new());
+        assert_eq!(empty.p95_latency_ms, None);
+        assert_eq!(empty.success_rate, 0.0);
+    }
+
+    #[test]
+    fn test_detects_latency_and_success_rate_regressions() {
+        let comparison = compare_stats(
+            RunStats::from_samples(samples(30, 0, 1000)),
+            RunStats::from_samples(samples(20, 10, 1400)),
+        );
+
+        let metrics: Vec<_> = comparison.regressions.iter().map(|r| r.metric).collect();
+        assert!(metrics.contains(&RegressionMetric::SuccessRate));
+        assert!(metrics.contains(&RegressionMetric::P95Latency));
+        assert!(comparison.p95_latency_change_pct.unwrap() > 30.0);
+        assert!(comparison.success_rate_delta < -30.0);
+    }
+
+    #[test]
+    fn test_small_or_stable_samples_are_not_regressions() {
+        // 样本不足时不判定
+        let comparison = compare_stats(
+            RunStats::from_samples(samples(3, 0, 1000)),
+            RunStats::from_samples(samples(1, 2, 3000)),
+        );
+        assert!(comparison.regressions.is_empty());
+
+        // 变化很小时不判定
+        let comparison = compare_stats(
+            RunStats::from_samples(samples(30, 1, 1000)),
+            RunStats::from_samples(samples(29, 2, 1050)),
+        );
+        assert!(comparison.regressions.is_empty());
+    }
+
+    #[test]
+    fn test_window_validation() {
+        let window = TimeWindow {
+            start: "2026-01-02T00:00:00Z".to_string(),
+            end: "2026-01-01T00:00:00Z".to_string(),
+        };
+        assert!(window.validate("baseline_window").is_err());
+    }
+
+    #[test]
+    fn test_compare_reads_both_sources() {
+        let conn = crate::db::test_db();
+        conn.execute_batch(
+            "INSERT INTO ConfigGroup (id, name) VALUES (1, 'g');
+             INSERT INTO ApiConfig (id, name, api_key, server_url, server_port, group_id)
+                 VALUES (1, 'c', 'k', 'https://example.com', 443, 1);
+             INSERT INTO TestResult (config_id, test_at, status, latency_ms) VALUES
+                 (1, '2026-01-01T10:00:00+08:00', 'success', 100),
+                 (1, '2026-01-02T10:00:00+08:00', 'failed', NULL);
+             INSERT INTO ProxyRequestLog (config_id, request_at, method, uri, target_url, latency_ms, status_code, is_success)
+                 VALUES (1, '2026-01-02T02:30:00Z', 'POST', '/v1/messages', 'https://example.com', 200, 200, 1);",
+        )
+        .unwrap();
+        let pool = DbPool::new(conn);
+
+        let comparison = TestComparisonService::compare(
+            &pool,
+            1,
+            TimeWindow {
+                start: "2026-01-01T00:00:00+08:00".to_string(),
+                end: "2026-01-02T00:00:00+08:00".to_string(),
+            },
+            TimeWindow {
+                start: "2026-01-02T00:00:00+08:00".to_string(),
+                end: "2026-01-03T00:00:00+08:00".to_string(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(comparison.test_results.baseline.success_count, 1);
+        assert_eq!(comparison.test_results.current.sample_count, 1);
+        assert_eq!(comparison.test_results.current.success_count, 0);
+        assert_eq!(comparison.proxy_requests.baseline.sample_count, 0);
+        assert_eq!(comparison.proxy_requests.current.p95_latency_ms, Some(200));
+        assert!(!comparison.has_regression);
+    }
+}
@@ -0,0 +1,230 @@
+/**
+ * Timestamp Audit Service
+ * 检查日志类表中时间戳的格式与时钟偏差
+ *
+ * 应用统一以 UTC RFC3339 (毫秒精度、`Z` 结尾) 写入时间戳，见 `utils::time`。
+ * 旧版本写入的本地时区偏移值和 SQLite `CURRENT_TIMESTAMP` 格式的值按字符串排序/比较时顺序错误；
+ * 系统时钟回拨或漂移会产生晚于当前时间的时间戳。两者都会导致日志顺序错乱、按时间清理失效。
+ */
+
+use crate::models::error::{AppError, AppResult};
+use crate::utils::time::{normalize_timestamp, now_rfc3339, NaiveZone};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// 晚于当前时间超过该秒数的时间戳视为时钟偏差
+const FUTURE_TOLERANCE_SECS: i64 = 60;
+
+/// 规范化格式对应的 GLOB 模式 (`2025-12-11T10:30:00.123Z`)
+const NORMALIZED_GLOB: &str =
+    "[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9]T[0-9][0-9]:[0-9][0-9]:[0-9][0-9].[0-9][0-9][0-9]Z";
+
+/// 需要按时间排序/比较的时间戳列
+pub struct TimestampColumn {
+    pub table: &'static str,
+    pub column: &'static str,
+    /// 该列中无时区信息的旧值的写入时区
+    pub naive_zone: NaiveZone,
+}
+
+/// 检查与规范化的时间戳列
+pub const TIMESTAMP_COLUMNS: &[TimestampColumn] = &[
+    TimestampColumn { table: "ProxyRequestLog", column: "request_at", naive_zone: NaiveZone::Utc },
+    TimestampColumn { table: "ProxyRequestLog", column: "response_start_at", naive_zone: NaiveZone::Utc },
+    // 旧版本以 datetime('now', 'localtime') 写入
+    TimestampColumn { table: "ProxyRequestLog", column: "response_end_at", naive_zone: NaiveZone::Local },
+    TimestampColumn { table: "TestResult", column: "test_at", naive_zone: NaiveZone::Utc },
+    TimestampColumn { table: "HealthCheckRecord", column: "check_at", naive_zone: NaiveZone::Utc },
+    TimestampColumn { table: "SwitchLog", column: "switch_at", naive_zone: NaiveZone::Utc },
+    TimestampColumn { table: "MetricsSnapshot", column: "captured_at", naive_zone: NaiveZone::Utc },
+];
+
+/// 单列检查结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimestampColumnReport {
+    pub table: String,
+    pub column: String,
+    /// 非空时间戳数
+    pub total_rows: i64,
+    /// 不是 UTC RFC3339 规范格式的行数
+    pub non_normalized_rows: i64,
+    /// 晚于当前时间 (超过容差) 的行数
+    pub future_rows: i64,
+    /// 最大超前秒数，没有未来时间戳时为空
+    pub max_future_skew_secs: Option<i64>,
+}
+
+/// 时钟偏差检查报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockSkewReport {
+    pub checked_at: String,
+    pub columns: Vec<TimestampColumnReport>,
+    /// 是否存在格式不一致或未来时间戳
+    pub has_issues: bool,
+}
+
+/// 时间戳检查服务
+pub struct TimestampAuditService;
+
+impl TimestampAuditService {
+    /// 检查所有时间戳列的格式与时钟偏差
+    pub fn check(conn: &Connection) -> AppResult<ClockSkewReport> {
+        let columns = TIMESTAMP_COLUMNS
+            .iter()
+            .map(|column| Self::check_column(conn, column))
+            .collect::<AppResult<Vec<_>>>()?;
+        let has_issues = columns.iter().any(|c| c.non_normalized_rows > 0 || c.future_rows > 0);
+
+        if has_issues {
+            log::warn!("Timestamp audit found issues: {:?}", columns);
+        }
+        Ok(ClockSkewReport {
+            checked_at: now_rfc3339(),
+            columns,
+            has_issues,
+        })
+    }
+
+    fn check_column(conn: &Connection, column: &TimestampColumn) -> AppResult<TimestampColumnReport> {
+        // julianday 能解析所有已知格式 (无时区的值按 UTC 计算)
+        let sql = format!(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(CASE WHEN {col} GLOB ?1 THEN 0 ELSE 1 END), 0),
+                    COALESCE(SUM(CASE WHEN (julianday({col}) - julianday('now')) * 86400.0 > ?2 THEN 1 ELSE 0 END), 0),
+                    MAX((julianday({col}) - julianday('now')) * 86400.0)
+             FROM {table} WHERE {col} IS NOT NULL",
+            table = column.table,
+            col = column.column,
+        );
+        conn.query_row(&sql, params![NORMALIZED_GLOB, FUTURE_TOLERANCE_SECS], |row| {
+            let max_skew: Option<f64> = row.get(3)?;
+            Ok(TimestampColumnReport {
+                table: column.table.to_string(),
+                column: column.column.to_string(),
+                total_rows: row.get(0)?,
+                non_normalized_rows: row.get(1)?,
+                future_rows: row.get(2)?,
+                max_future_skew_secs: max_skew
+                    .map(|secs| secs.round() as i64)
+                    .filter(|&secs| secs > FUTURE_TOLERANCE_SECS),
+            })
+        })
+        .map_err(|e| AppError::DatabaseError {
+            message: format!("检查 {}.{} 时间戳失败: {}", column.table, column.column, e),
+        })
+    }
+
+    /// 将所有时间戳列中可识别的旧格式值改写为 UTC RFC3339，返回改写的行数
+    pub fn normalize_stored(conn: &Connection) -> AppResult<usize> {
+        let db_err = |e: rusqlite::Error| AppError::DatabaseError {
+            message: format!("规范化时间戳失败: {}", e),
+        };
+
+        let mut updated = 0;
+        for column in TIMESTAMP_COLUMNS {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT rowid, {col} FROM {table} WHERE {col} IS NOT NULL AND {col} NOT GLOB ?1",
+                    table = column.table,
+                    col = column.column,
+                ))
+                .map_err(db_err)?;
+            let rows = stmt
+                .query_map([NORMALIZED_GLOB], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+                .map_err(db_err)?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(db_err)?;
+
+            let update_sql = format!(
+                "UPDATE {table} SET {col} = ?1 WHERE rowid = ?2",
+                table = column.table,
+                col = column.column,
+            );
+            for (rowid, value) in rows {
+                match normalize_timestamp(&value, column.naive_zone) {
+                    Some(normalized) => {
+                        updated += conn.execute(&update_sql, params![normalized, rowid]).map_err(db_err)?;
+                    }
+                    None => log::warn!(
+                        "Unrecognized timestamp in {}.{} (rowid {}): {}",
+                        column.table,
+                        column.column,
+                        rowid,
+                        value
+                    ),
+                }
+            }
+        }
+
+        if updated > 0 {
+            log::info!("已规范化 {} 个时间戳为 UTC RFC3339", updated);
+        }
+        Ok(updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_conn() -> Connection {
+        let conn = crate::db::test_db();
+        conn
+    }
+
+    fn report_for<'a>(report: &'a ClockSkewReport, table: &str, column: &str) -> &'a TimestampColumnReport {
+        report
+            .columns
+            .iter()
+            .find(|c| c.table == table && c.column == column)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_normalize_restores_log_order() {
+        let conn = setup_conn();
+        // 不同时区写入：字符串顺序与时间顺序不一致
+        conn.execute_batch(
+            "INSERT INTO ProxyRequestLog (id, request_at, method, uri, target_url, latency_ms, status_code)
+                 VALUES (1, '2026-01-02T09:30:00+08:00', 'POST', '/', 't', 1, 200),
+                        (2, '2026-01-02T02:00:00Z', 'POST', '/', 't', 1, 200),
+                        (3, '2026-01-02 03:00:00', 'POST', '/', 't', 1, 200);",
+        )
+        .unwrap();
+
+        let order = |conn: &Connection| -> Vec<i64> {
+            let mut stmt = conn.prepare("SELECT id FROM ProxyRequestLog ORDER BY request_at DESC").unwrap();
+            stmt.query_map([], |row| row.get(0)).unwrap().map(Result::unwrap).collect()
+        };
+        assert_eq!(order(&conn), vec![1, 2, 3]);
+
+        let report = TimestampAuditService::check(&conn).unwrap();
+        assert!(report.has_issues);
+        assert_eq!(report_for(&report, "ProxyRequestLog", "request_at").non_normalized_rows, 3);
+
+        assert_eq!(TimestampAuditService::normalize_stored(&conn).unwrap(), 3);
+        assert_eq!(order(&conn), vec![3, 2, 1]);
+        assert!(!TimestampAuditService::check(&conn).unwrap().has_issues);
+        // 已规范化的值不再改写
+        assert_eq!(TimestampAuditService::normalize_stored(&conn).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_detects_future_timestamps() {
+        let conn = setup_conn();
+        let future = crate::utils::time::to_rfc3339_utc(&(chrono::Utc::now() + chrono::Duration::hours(2)));
+        conn.execute(
+            "INSERT INTO MetricsSnapshot (captured_at) VALUES (?1), (?2)",
+            params![future, now_rfc3339()],
+        )
+        .unwrap();
+
+        let report = TimestampAuditService::check(&conn).unwrap();
+        let captured_at = report_for(&report, "MetricsSnapshot", "captured_at");
+        assert!(report.has_issues);
+        assert_eq!(captured_at.total_rows, 2);
+        assert_eq!(captured_at.non_normalized_rows, 0);
+        assert_eq!(captured_at.future_rows, 1);
+        assert!(captured_at.max_future_skew_secs.is_some_and(|secs| (7100..=7200).contains(&secs)));
+    }
+}
@@ -16,7 +16,16 @@
 //! - **成功率分**：基于连续失败次数，0次得100分，≥5次得0分
 //! - **余额分**：基于剩余余额，≥$50得100分，≤$1得0分，对数插值
 //! - **优先级分**：基于sort_order，第1位得100分，递减
+//!
+//! ## 时间衰减
+//!
+//! 距最后健康时间（请求成功或健康检查成功）越久，高于中性值 (0.5) 的权重越向中性值靠拢，
+//! 每经过一个半衰期差值减半：
+//! W' = min(W, 0.5 + (W - 0.5) × 0.5^(距最后健康小时数 / 半衰期))
+//! 衰减只会降低权重，避免昨天大量成功、今天已不可用的配置仍排在最前，
+//! 同时不会把失败配置的低权重拉高；没有健康时间的配置不衰减。
 
+use chrono::{DateTime, Utc};
 use rusqlite::Connection;
 use crate::models::api_config::ApiConfig;
 use crate::models::error::AppResult;
@@ -50,6 +59,11 @@ pub struct WeightConfig {
 
     /// 连续失败次数阈值
     pub max_consecutive_failures: i32,
+
+    /// 权重衰减半衰期（小时），0 表示不衰减
+    pub decay_half_life_hours: f64,
+    /// 衰减趋近的中性权重
+    pub neutral_weight: f64,
 }
 
 impl Default for WeightConfig {
@@ -76,6 +90,10 @@ impl Default for WeightConfig {
 
             // 最大连续失败次数
             max_consecutive_failures: 5,
+
+            // 时间衰减
+            decay_half_life_hours: 6.0,
+            neutral_weight: 0.5,
         }
     }
 }
@@ -189,6 +207,34 @@ impl WeightCalculator {
         }
     }
 
+    /// 按距最后健康（请求成功或健康检查成功）的时间衰减权重
+    ///
+    /// - 刚确认可用: 权重不变
+    /// - 每经过一个半衰期: 高于中性值的部分减半
+    /// - 衰减只会降低权重，低于中性值的权重保持不变，避免失败的配置被拉高
+    /// - 从未确认可用或时间无法解析: 缺少时间信息，权重不变
+    pub fn apply_recency_decay(
+        &self,
+        weight: f64,
+        last_healthy_at: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> f64 {
+        if self.config.decay_half_life_hours <= 0.0 {
+            return weight;
+        }
+
+        let Some(last_healthy) = last_healthy_at
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        else {
+            return weight;
+        };
+
+        let neutral = self.config.neutral_weight;
+        let hours = (now - last_healthy.with_timezone(&Utc)).num_seconds().max(0) as f64 / 3600.0;
+        let factor = 0.5f64.powf(hours / self.config.decay_half_life_hours);
+        weight.min(neutral + (weight - neutral) * factor).clamp(0.0, 1.0)
+    }
+
     /// 计算配置的综合权重分数
     ///
     /// # Arguments
@@ -196,8 +242,14 @@ impl WeightCalculator {
     /// - `total_configs`: 同组配置总数（用于优先级计算）
     ///
     /// # Returns
-    /// 权重分数 (0.0 - 1.0)
+    /// 权重分数 (0.0 - 1.0)，已按距最后健康的时间衰减
     pub fn calculate_weight(&self, config: &ApiConfig, total_configs: i32) -> f64 {
+        let weight = self.calculate_base_weight(config, total_configs);
+        self.apply_recency_decay(weight, config.last_healthy_at.as_deref(), Utc::now())
+    }
+
+    /// 计算未衰减的综合权重分数
+    fn calculate_base_weight(&self, config: &ApiConfig, total_configs: i32) -> f64 {
         let latency_score = self.calculate_latency_score(config.last_latency_ms);
         let success_rate_score = self.calculate_success_rate_score(config.consecutive_failures);
         let priority_score = self.calculate_priority_score(config.sort_order, total_configs);
@@ -367,4 +419,95 @@ mod tests {
         let mid_score = calculator.calculate_priority_score(2, 5);
         assert!(mid_score > 0.0 && mid_score < 100.0);
     }
+
+    #[test]
+    fn test_recency_decay_curve() {
+        let calculator = WeightCalculator::new();
+        let now = Utc::now();
+        let hours_ago = |h: i64| Some((now - chrono::Duration::hours(h)).to_rfc3339());
+        let decay = |weight: f64, at: Option<String>| calculator.apply_recency_decay(weight, at.as_deref(), now);
+
+        // 刚成功：不衰减
+        assert!((decay(0.9, hours_ago(0)) - 0.9).abs() < 1e-9);
+
+        // 一个半衰期：差值减半；两个半衰期：剩四分之一
+        assert!((decay(0.9, hours_ago(6)) - 0.7).abs() < 1e-9);
+        assert!((decay(0.9, hours_ago(12)) - 0.6).abs() < 1e-9);
+        // 低于中性值的权重不会被拉高
+        assert_eq!(decay(0.1, hours_ago(6)), 0.1);
+
+        // 很久以前：接近中性值；缺少时间信息：不衰减
+        assert!((decay(0.9, hours_ago(24 * 7)) - 0.5).abs() < 1e-3);
+        assert_eq!(decay(0.9, None), 0.9);
+        assert_eq!(decay(0.9, Some("not a time".to_string())), 0.9);
+
+        // 单调递减
+        let mut previous = 1.0;
+        for h in 0..48 {
+            let value = decay(1.0, hours_ago(h));
+            assert!(value <= previous);
+            previous = value;
+        }
+    }
+
+    #[test]
+    fn test_stale_success_ranks_below_recent_success() {
+        let calculator = WeightCalculator::new();
+        let now = Utc::now();
+        let yesterday = (now - chrono::Duration::hours(24)).to_rfc3339();
+        let just_now = (now - chrono::Duration::minutes(5)).to_rfc3339();
+
+        let stale_strong = calculator.apply_recency_decay(1.0, Some(&yesterday), now);
+        let recent_weaker = calculator.apply_recency_decay(0.7, Some(&just_now), now);
+        assert!(recent_weaker > stale_strong);
+
+        let no_decay = WeightCalculator::with_config(WeightConfig {
+            decay_half_life_hours: 0.0,
+            ..WeightConfig::default()
+        });
+        assert_eq!(no_decay.apply_recency_decay(1.0, Some(&yesterday), now), 1.0);
+    }
+
+    fn weighted_config(
+        sort_order: i32,
+        consecutive_failures: i32,
+        last_latency_ms: i32,
+        last_healthy_at: Option<String>,
+    ) -> ApiConfig {
+        serde_json::from_value(serde_json::json!({
+            "id": sort_order + 1,
+            "name": "c",
+            "api_key": "k",
+            "server_url": "https://api.example.com",
+            "server_port": 443,
+            "sort_order": sort_order,
+            "is_available": true,
+            "consecutive_failures": consecutive_failures,
+            "last_latency_ms": last_latency_ms,
+            "last_healthy_at": last_healthy_at,
+            "auto_balance_check": false,
+            "created_at": "",
+            "updated_at": ""
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_failing_config_does_not_outrank_healthy_backup() {
+        let calculator = WeightCalculator::new();
+        let now = Utc::now();
+
+        // 主配置：一天前最后成功，之后连续失败
+        let failing = weighted_config(0, 5, 200, Some((now - chrono::Duration::hours(24)).to_rfc3339()));
+        // 备用配置：从未承载流量，但刚通过健康检查
+        let backup = weighted_config(1, 0, 1000, Some(now.to_rfc3339()));
+
+        let failing_weight = calculator.calculate_weight(&failing, 2);
+        let backup_weight = calculator.calculate_weight(&backup, 2);
+        assert!(backup_weight > failing_weight, "{} <= {}", backup_weight, failing_weight);
+
+        // 失败配置的低权重不会被衰减拉高
+        let dead = weighted_config(1, 5, 2000, Some((now - chrono::Duration::hours(24)).to_rfc3339()));
+        assert!(calculator.calculate_weight(&dead, 2) < 0.1);
+    }
 }
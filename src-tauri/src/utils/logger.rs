@@ -4,6 +4,13 @@ use env_logger::{Builder, Target};
 use log::LevelFilter;
 use std::io::Write;
 
+/// 代理请求处理期间的日志行前缀（包含请求 ID）
+fn request_id_prefix() -> String {
+    crate::proxy::structured_logger::current_request_id()
+        .map(|id| format!("[{}] ", id))
+        .unwrap_or_default()
+}
+
 /// 初始化日志系统
 /// 使用 env_logger,支持文件输出和控制台输出
 pub fn init_logger() {
@@ -14,11 +21,12 @@ pub fn init_logger() {
         .format(|buf, record| {
             writeln!(
                 buf,
-                "[{} {} {}:{}] {}",
+                "[{} {} {}:{}] {}{}",
                 chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
                 record.level(),
                 record.file().unwrap_or("unknown"),
                 record.line().unwrap_or(0),
+                request_id_prefix(),
                 record.args()
             )
         })
@@ -37,11 +45,12 @@ pub fn init_logger_with_level(level: LevelFilter) {
         .format(|buf, record| {
             writeln!(
                 buf,
-                "[{} {} {}:{}] {}",
+                "[{} {} {}:{}] {}{}",
                 chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
                 record.level(),
                 record.file().unwrap_or("unknown"),
                 record.line().unwrap_or(0),
+                request_id_prefix(),
                 record.args()
             )
         })
@@ -69,11 +78,12 @@ pub fn init_logger_with_file(log_file_path: &str) -> Result<(), String> {
         .format(|buf, record| {
             writeln!(
                 buf,
-                "[{} {} {}:{}] {}",
+                "[{} {} {}:{}] {}{}",
                 chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
                 record.level(),
                 record.file().unwrap_or("unknown"),
                 record.line().unwrap_or(0),
+                request_id_prefix(),
                 record.args()
             )
         })
@@ -1,4 +1,5 @@
 pub mod constants;
 pub mod logger;
 pub mod paths;
+pub mod server_url;
 pub mod time;
@@ -1,30 +1,135 @@
 #![allow(dead_code)]
 
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use dirs;
 
 /// 跨平台路径检测工具
 /// 用于检测 Claude Code 配置文件路径和应用数据目录
 
-/// 获取 Claude Code 配置文件路径
+/// 指定 Claude Code 配置目录的环境变量
+pub const ENV_CLAUDE_CONFIG_DIR: &str = "CLAUDE_CONFIG_DIR";
+
+/// XDG 配置目录环境变量
+const ENV_XDG_CONFIG_HOME: &str = "XDG_CONFIG_HOME";
+
+/// Claude Code 配置文件候选位置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaudeSettingsCandidate {
+    /// 配置文件路径
+    pub path: String,
+    /// 来源: env (CLAUDE_CONFIG_DIR) / home (~/.claude) / xdg (~/.config/claude)
+    pub source: String,
+    /// 文件是否存在
+    pub exists: bool,
+    /// 是否为当前生效的配置文件
+    pub active: bool,
+}
+
+/// Claude Code 配置文件位置检测结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaudeSettingsPaths {
+    /// 所有候选位置（按优先级排列）
+    pub candidates: Vec<ClaudeSettingsCandidate>,
+    /// 当前生效的配置文件路径（启用/禁用代理时写入此文件）
+    pub active_path: String,
+    /// 选择该路径的原因
+    pub reason: String,
+}
+
+/// 检测 Claude Code 配置文件的所有候选位置及当前生效的位置
+pub fn detect_claude_code_settings_paths() -> Result<ClaudeSettingsPaths, String> {
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| "无法获取用户主目录".to_string())?;
+
+    Ok(resolve_claude_settings_paths(
+        &home_dir,
+        |key| std::env::var(key).ok(),
+        |path| path.exists(),
+    ))
+}
+
+/// 按优先级确定生效的配置文件
+///
+/// 1. 设置了 CLAUDE_CONFIG_DIR 时 Claude Code 只读取该目录，无论文件是否存在
+/// 2. 否则使用第一个已存在的候选位置 (~/.claude 优先于 XDG 目录)
+/// 3. 都不存在时使用默认位置 ~/.claude/settings.json
+///
+/// npm 全局安装、本地安装 (~/.claude/local) 与原生安装读取的是同一个用户配置文件，
+/// 因此候选位置只取决于环境变量而不取决于安装方式
+fn resolve_claude_settings_paths(
+    home_dir: &Path,
+    lookup: impl Fn(&str) -> Option<String>,
+    exists: impl Fn(&Path) -> bool,
+) -> ClaudeSettingsPaths {
+    let env_dir = lookup(ENV_CLAUDE_CONFIG_DIR)
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+
+    let mut candidates: Vec<(PathBuf, &str)> = Vec::new();
+    if let Some(dir) = &env_dir {
+        candidates.push((PathBuf::from(dir).join("settings.json"), "env"));
+    }
+    candidates.push((home_dir.join(".claude").join("settings.json"), "home"));
+    if !cfg!(target_os = "windows") {
+        let xdg_dir = lookup(ENV_XDG_CONFIG_HOME)
+            .filter(|v| !v.trim().is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home_dir.join(".config"));
+        candidates.push((xdg_dir.join("claude").join("settings.json"), "xdg"));
+    }
+
+    let existing: Vec<bool> = candidates.iter().map(|(path, _)| exists(path)).collect();
+    let (active_index, reason) = if env_dir.is_some() {
+        (0, format!("已设置 {}，Claude Code 只读取该目录下的配置文件", ENV_CLAUDE_CONFIG_DIR))
+    } else if let Some(index) = existing.iter().position(|&e| e) {
+        let reason = if index == 0 {
+            "使用默认位置 ~/.claude/settings.json（文件已存在）".to_string()
+        } else {
+            "~/.claude/settings.json 不存在，使用已存在的 XDG 配置目录中的配置文件".to_string()
+        };
+        (index, reason)
+    } else {
+        (0, "未找到已存在的配置文件，使用默认位置 ~/.claude/settings.json（启用代理时创建）".to_string())
+    };
+
+    let active_path = candidates[active_index].0.to_string_lossy().to_string();
+    let candidates = candidates
+        .into_iter()
+        .zip(existing)
+        .enumerate()
+        .map(|(index, ((path, source), exists))| ClaudeSettingsCandidate {
+            path: path.to_string_lossy().to_string(),
+            source: source.to_string(),
+            exists,
+            active: index == active_index,
+        })
+        .collect();
+
+    ClaudeSettingsPaths {
+        candidates,
+        active_path,
+        reason,
+    }
+}
+
+/// 获取 Claude Code 配置文件路径（当前生效的位置）
 /// Windows: %USERPROFILE%\.claude\settings.json
 /// macOS: ~/.claude/settings.json
 /// Linux: ~/.claude/settings.json
+/// 设置了 CLAUDE_CONFIG_DIR 或只存在 XDG 配置文件时使用对应位置
 pub fn get_claude_code_settings_path() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir()
-        .ok_or_else(|| "无法获取用户主目录".to_string())?;
-
-    let settings_path = home_dir.join(".claude").join("settings.json");
-    Ok(settings_path)
+    let paths = detect_claude_code_settings_paths()?;
+    Ok(PathBuf::from(paths.active_path))
 }
 
-/// 获取 Claude Code 配置目录
+/// 获取 Claude Code 配置目录（当前生效的配置文件所在目录）
 pub fn get_claude_code_config_dir() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir()
-        .ok_or_else(|| "无法获取用户主目录".to_string())?;
-
-    let config_dir = home_dir.join(".claude");
-    Ok(config_dir)
+    let settings_path = get_claude_code_settings_path()?;
+    settings_path
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "无法获取 Claude Code 配置目录".to_string())
 }
 
 /// 获取应用数据目录
@@ -127,6 +232,34 @@ mod tests {
         assert!(path.to_string_lossy().contains("settings.json"));
     }
 
+    #[test]
+    fn test_resolve_claude_settings_paths() {
+        let home = PathBuf::from("/home/u");
+        let home_settings = home.join(".claude").join("settings.json");
+
+        // 都不存在时使用默认位置
+        let paths = resolve_claude_settings_paths(&home, |_| None, |_| false);
+        assert_eq!(paths.active_path, home_settings.to_string_lossy());
+        assert!(paths.candidates[0].active);
+        assert!(paths.candidates.iter().all(|c| !c.exists));
+
+        // CLAUDE_CONFIG_DIR 优先，即使文件不存在
+        let lookup = |key: &str| (key == ENV_CLAUDE_CONFIG_DIR).then(|| "/opt/claude".to_string());
+        let paths = resolve_claude_settings_paths(&home, lookup, |p| p == home_settings);
+        assert_eq!(paths.active_path, PathBuf::from("/opt/claude/settings.json").to_string_lossy());
+        assert_eq!(paths.candidates[0].source, "env");
+        assert!(paths.candidates[1].exists && !paths.candidates[1].active);
+        assert!(paths.reason.contains(ENV_CLAUDE_CONFIG_DIR));
+
+        // 只存在 XDG 配置文件时使用 XDG 位置
+        if !cfg!(target_os = "windows") {
+            let xdg = home.join(".config").join("claude").join("settings.json");
+            let paths = resolve_claude_settings_paths(&home, |_| None, |p| p == xdg);
+            assert_eq!(paths.active_path, xdg.to_string_lossy());
+            assert_eq!(paths.candidates.iter().filter(|c| c.active).count(), 1);
+        }
+    }
+
     #[test]
     fn test_get_app_data_dir() {
         let dir = get_app_data_dir();
@@ -0,0 +1,422 @@
+/**
+ * 服务器地址解析与规范化
+ *
+ * - `parse_server_url`: 代理转发时拆分 server_url（主机、端口、路径前缀）
+ * - `normalize_server_url`: 用户输入时校验并生成规范形式,同时提示可能导致意外转发路径的写法
+ *
+ * 两者共用同一套拆分规则,保证保存下来的地址与实际转发目标一致。
+ *
+ * 另提供配置级别固定查询参数 (extra_query) 的校验与合并。
+ */
+
+use serde::{Deserialize, Serialize};
+
+/// 代理转发使用的 server_url 拆分结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedServerUrl {
+    /// 是否使用 HTTPS
+    pub is_https: bool,
+    /// 主机与端口（端口仅在 URL 中显式指定时存在）
+    pub host_and_port: String,
+    /// 主机名（不含端口）
+    pub host: String,
+    /// 连接地址（host:port,未指定端口时按协议使用 443/80）
+    pub target_addr: String,
+    /// 后端路径前缀（如 "/api"）,没有路径时为空字符串
+    pub path_prefix: String,
+}
+
+impl ParsedServerUrl {
+    /// 将后端路径前缀与客户端请求路径拼接为最终转发路径
+    pub fn target_path(&self, client_path_and_query: &str) -> String {
+        if !self.path_prefix.is_empty() {
+            format!("{}{}", self.path_prefix, client_path_and_query)
+        } else {
+            client_path_and_query.to_string()
+        }
+    }
+}
+
+/// 拆分 server_url（代理转发使用的规则）
+///
+/// 不做任何校验或修正,仅按原样拆分,例如 `https://api.example.com/` 的路径前缀为 `/`。
+pub fn parse_server_url(server_url: &str) -> ParsedServerUrl {
+    let is_https = server_url.starts_with("https://");
+    let url_without_protocol = server_url
+        .strip_prefix("https://")
+        .or_else(|| server_url.strip_prefix("http://"))
+        .unwrap_or(server_url);
+
+    let parts: Vec<&str> = url_without_protocol.splitn(2, '/').collect();
+    let host_and_port = parts[0];
+    let path_prefix = if parts.len() > 1 {
+        format!("/{}", parts[1])
+    } else {
+        String::new()
+    };
+
+    let target_addr = if host_and_port.contains(':') {
+        host_and_port.to_string()
+    } else {
+        let default_port = if is_https { 443 } else { 80 };
+        format!("{}:{}", host_and_port, default_port)
+    };
+
+    let host = host_and_port
+        .split(':')
+        .next()
+        .unwrap_or(host_and_port)
+        .to_string();
+
+    ParsedServerUrl {
+        is_https,
+        host_and_port: host_and_port.to_string(),
+        host,
+        target_addr,
+        path_prefix,
+    }
+}
+
+/// 服务器地址规范化结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizedServerUrl {
+    /// 用户输入的原始地址
+    pub original: String,
+    /// 规范化后的地址（应保存到配置中的值）
+    pub normalized: String,
+    /// 是否与原始输入不同
+    pub changed: bool,
+    /// 是否使用 HTTPS
+    pub is_https: bool,
+    /// 主机名
+    pub host: String,
+    /// 实际连接端口
+    pub port: u16,
+    /// 检测到的后端路径前缀
+    pub path_prefix: Option<String>,
+    /// 需要用户确认的可疑写法
+    pub warnings: Vec<String>,
+}
+
+/// 校验并规范化服务器地址
+///
+/// - 缺少协议时默认补全 `https://`
+/// - 主机名转为小写
+/// - 去除路径末尾的 `/`（避免转发为 `//v1/messages`）
+/// - 路径前缀以 `/v1` 结尾、包含具体端点路径或使用明文 HTTP 访问远程主机时给出提示
+pub fn normalize_server_url(input: &str) -> Result<NormalizedServerUrl, String> {
+    let original = input.to_string();
+    let trimmed = input.trim();
+    let mut warnings = Vec::new();
+
+    if trimmed.is_empty() {
+        return Err("服务器地址不能为空".to_string());
+    }
+
+    if trimmed.chars().any(char::is_whitespace) {
+        return Err("服务器地址不能包含空白字符".to_string());
+    }
+
+    let (scheme, rest) = match trimmed.split_once("://") {
+        Some((scheme, rest)) => {
+            let scheme = scheme.to_ascii_lowercase();
+            if scheme != "http" && scheme != "https" {
+                return Err(format!("不支持的协议: {}://,仅支持 http:// 或 https://", scheme));
+            }
+            (scheme, rest)
+        }
+        None => {
+            warnings.push("未指定协议,已默认使用 https://".to_string());
+            ("https".to_string(), trimmed)
+        }
+    };
+
+    if rest.contains('?') || rest.contains('#') {
+        return Err("服务器地址不能包含查询参数或片段".to_string());
+    }
+
+    let (authority, path) = match rest.split_once('/') {
+        Some((authority, path)) => (authority, path),
+        None => (rest, ""),
+    };
+
+    if authority.is_empty() {
+        return Err("服务器地址缺少主机名".to_string());
+    }
+
+    if authority.contains('@') {
+        return Err("服务器地址不能包含用户信息".to_string());
+    }
+
+    // 转发时按 ':' 拆分主机与端口,暂不支持 IPv6 字面量地址
+    if authority.starts_with('[') {
+        return Err("暂不支持 IPv6 地址,请使用域名".to_string());
+    }
+
+    let (host, explicit_port) = match authority.rsplit_once(':') {
+        Some((host, port)) => {
+            let port: u16 = port
+                .parse()
+                .ok()
+                .filter(|p| *p > 0)
+                .ok_or_else(|| format!("无效的端口: {}", port))?;
+            (host, Some(port))
+        }
+        None => (authority, None),
+    };
+
+    if host.is_empty() {
+        return Err("服务器地址缺少主机名".to_string());
+    }
+
+    let host = host.to_ascii_lowercase();
+    let is_https = scheme == "https";
+    let port = explicit_port.unwrap_or(if is_https { 443 } else { 80 });
+
+    // 合并重复斜杠并去除末尾斜杠
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let path_prefix = if segments.is_empty() {
+        None
+    } else {
+        Some(format!("/{}", segments.join("/")))
+    };
+
+    if let Some(ref prefix) = path_prefix {
+        let lower = prefix.to_ascii_lowercase();
+        if lower.ends_with("/v1") {
+            warnings.push(format!(
+                "路径前缀 {} 以 /v1 结尾,客户端请求路径通常已包含 /v1,转发时可能变成 {}/v1/...",
+                prefix, prefix
+            ));
+        }
+        if lower.ends_with("/messages") || lower.ends_with("/chat/completions") {
+            warnings.push(format!(
+                "路径前缀 {} 包含具体的接口路径,服务器地址通常只需填写到 API 根路径",
+                prefix
+            ));
+        }
+    }
+
+    if !is_https && host != "localhost" && host != "127.0.0.1" {
+        warnings.push("使用明文 HTTP 连接远程主机,API 密钥将以明文传输".to_string());
+    }
+
+    let authority = match explicit_port {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.clone(),
+    };
+    let normalized = format!(
+        "{}://{}{}",
+        scheme,
+        authority,
+        path_prefix.as_deref().unwrap_or("")
+    );
+
+    Ok(NormalizedServerUrl {
+        changed: normalized != original,
+        original,
+        normalized,
+        is_https,
+        host,
+        port,
+        path_prefix,
+        warnings,
+    })
+}
+
+/// 查询参数的键（`key=value` 中 `=` 之前的部分）
+fn query_key(pair: &str) -> &str {
+    pair.split_once('=').map_or(pair, |(key, _)| key)
+}
+
+/// 校验并规范化配置的固定查询参数
+///
+/// 接受 `key=value&alt=sse` 形式（可带前导 `?`），空字符串返回 `None` 表示清除。
+/// 同一个键只保留最后一次出现的值。
+pub fn normalize_extra_query(raw: &str) -> Result<Option<String>, String> {
+    let trimmed = raw.trim();
+    let trimmed = trimmed.strip_prefix('?').unwrap_or(trimmed);
+
+    let mut pairs: Vec<&str> = Vec::new();
+    for pair in trimmed.split('&').filter(|p| !p.is_empty()) {
+        let key = query_key(pair);
+        if key.is_empty() {
+            return Err(format!("查询参数 {} 缺少参数名", pair));
+        }
+        if pair.contains(|c: char| c.is_whitespace() || c == '#' || c == '?') {
+            return Err(format!("查询参数 {} 包含非法字符,请使用 URL 编码", pair));
+        }
+        pairs.retain(|p| query_key(p) != key);
+        pairs.push(pair);
+    }
+
+    Ok(if pairs.is_empty() { None } else { Some(pairs.join("&")) })
+}
+
+/// 将配置的固定查询参数合并到转发路径
+///
+/// 与客户端同名的参数以配置为准,不会重复出现。
+pub fn merge_query(path_and_query: &str, extra_query: &str) -> String {
+    if extra_query.is_empty() {
+        return path_and_query.to_string();
+    }
+
+    let (path, client_query) = path_and_query
+        .split_once('?')
+        .unwrap_or((path_and_query, ""));
+
+    let extra_keys: Vec<&str> = extra_query.split('&').map(query_key).collect();
+    let mut pairs: Vec<&str> = client_query
+        .split('&')
+        .filter(|p| !p.is_empty() && !extra_keys.contains(&query_key(p)))
+        .collect();
+    pairs.extend(extra_query.split('&'));
+
+    format!("{}?{}", path, pairs.join("&"))
+}
+
+/// 隐藏查询参数的值（用于日志与预览，避免泄露 `key=` 等密钥）
+pub fn redact_query_values(query: &str) -> String {
+    query
+        .split('&')
+        .map(|pair| format!("{}=***", query_key(pair)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_server_url() {
+        let parsed = parse_server_url("https://api.example.com");
+        assert!(parsed.is_https);
+        assert_eq!(parsed.host, "api.example.com");
+        assert_eq!(parsed.target_addr, "api.example.com:443");
+        assert_eq!(parsed.path_prefix, "");
+        assert_eq!(parsed.target_path("/v1/messages"), "/v1/messages");
+
+        let parsed = parse_server_url("http://localhost:8080/api");
+        assert!(!parsed.is_https);
+        assert_eq!(parsed.host, "localhost");
+        assert_eq!(parsed.host_and_port, "localhost:8080");
+        assert_eq!(parsed.target_addr, "localhost:8080");
+        assert_eq!(parsed.target_path("/v1/messages"), "/api/v1/messages");
+
+        // 未规范化的写法会产生意外的转发路径
+        assert_eq!(
+            parse_server_url("https://api.example.com/").target_path("/v1/messages"),
+            "//v1/messages"
+        );
+        assert_eq!(
+            parse_server_url("api.example.com").target_addr,
+            "api.example.com:80"
+        );
+    }
+
+    #[test]
+    fn test_normalize_adds_scheme_and_strips_trailing_slash() {
+        let result = normalize_server_url("api.example.com").unwrap();
+        assert_eq!(result.normalized, "https://api.example.com");
+        assert!(result.changed);
+        assert_eq!(result.port, 443);
+        assert_eq!(result.warnings.len(), 1);
+
+        let result = normalize_server_url("https://API.example.com/").unwrap();
+        assert_eq!(result.normalized, "https://api.example.com");
+        assert!(result.path_prefix.is_none());
+
+        let result = normalize_server_url("https://api.example.com").unwrap();
+        assert!(!result.changed);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_normalize_path_prefix() {
+        let result = normalize_server_url("https://api.example.com//proxy/claude/").unwrap();
+        assert_eq!(result.normalized, "https://api.example.com/proxy/claude");
+        assert_eq!(result.path_prefix.as_deref(), Some("/proxy/claude"));
+        assert!(result.warnings.is_empty());
+
+        let result = normalize_server_url("https://api.example.com/v1/").unwrap();
+        assert_eq!(result.normalized, "https://api.example.com/v1");
+        assert!(result.warnings.iter().any(|w| w.contains("/v1/v1")));
+
+        let result = normalize_server_url("https://api.example.com/v1/messages").unwrap();
+        assert!(result.warnings.iter().any(|w| w.contains("接口路径")));
+    }
+
+    #[test]
+    fn test_normalize_ports_and_http() {
+        let result = normalize_server_url("http://localhost:8080").unwrap();
+        assert_eq!(result.normalized, "http://localhost:8080");
+        assert_eq!(result.port, 8080);
+        assert!(result.warnings.is_empty());
+
+        let result = normalize_server_url("http://api.example.com").unwrap();
+        assert_eq!(result.port, 80);
+        assert!(result.warnings.iter().any(|w| w.contains("明文")));
+    }
+
+    #[test]
+    fn test_normalize_rejects_invalid() {
+        assert!(normalize_server_url("").is_err());
+        assert!(normalize_server_url("   ").is_err());
+        assert!(normalize_server_url("ftp://example.com").is_err());
+        assert!(normalize_server_url("https://").is_err());
+        assert!(normalize_server_url("https://example.com:abc").is_err());
+        assert!(normalize_server_url("https://example.com:0").is_err());
+        assert!(normalize_server_url("https://example.com/?key=1").is_err());
+        assert!(normalize_server_url("https://user@example.com").is_err());
+        assert!(normalize_server_url("https://exa mple.com").is_err());
+        assert!(normalize_server_url("http://[::1]:8080").is_err());
+    }
+
+    #[test]
+    fn test_normalized_url_routes_as_expected() {
+        // 规范化后的地址经过转发拆分规则应得到一致的主机、端口与路径
+        let cases = [
+            ("api.example.com", "api.example.com:443", "/v1/messages"),
+            ("https://api.example.com/", "api.example.com:443", "/v1/messages"),
+            ("https://relay.example.com/api/", "relay.example.com:443", "/api/v1/messages"),
+            ("http://127.0.0.1:3000//", "127.0.0.1:3000", "/v1/messages"),
+        ];
+
+        for (input, addr, path) in cases {
+            let normalized = normalize_server_url(input).unwrap();
+            let parsed = parse_server_url(&normalized.normalized);
+            assert_eq!(parsed.target_addr, addr, "input: {}", input);
+            assert_eq!(parsed.target_path("/v1/messages"), path, "input: {}", input);
+            assert_eq!(parsed.is_https, normalized.is_https);
+            assert_eq!(parsed.host, normalized.host);
+            assert_eq!(
+                parsed.path_prefix,
+                normalized.path_prefix.clone().unwrap_or_default()
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize_extra_query() {
+        assert_eq!(normalize_extra_query("").unwrap(), None);
+        assert_eq!(normalize_extra_query("  ?  ").unwrap(), None);
+        assert_eq!(
+            normalize_extra_query("?key=abc&alt=sse&key=def").unwrap().as_deref(),
+            Some("alt=sse&key=def")
+        );
+        assert!(normalize_extra_query("=abc").is_err());
+        assert!(normalize_extra_query("key=a b").is_err());
+    }
+
+    #[test]
+    fn test_merge_query() {
+        assert_eq!(merge_query("/v1/messages", ""), "/v1/messages");
+        assert_eq!(merge_query("/v1/messages", "key=abc"), "/v1/messages?key=abc");
+        assert_eq!(
+            merge_query("/v1beta/models/m:streamGenerateContent?alt=sse&key=client", "key=abc&beta"),
+            "/v1beta/models/m:streamGenerateContent?alt=sse&key=abc&beta"
+        );
+        assert_eq!(redact_query_values("key=abc&beta"), "key=***&beta=***");
+    }
+}
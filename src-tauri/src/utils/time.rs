@@ -1,17 +1,28 @@
 //! 时间工具模块
 //!
-//! 提供统一的本地时间获取方法，确保应用中所有时间戳使用系统本地时区（北京时间 UTC+8）
+//! 应用写入的所有时间戳统一为 UTC RFC3339 格式 (毫秒精度、`Z` 结尾，如 `2025-12-11T10:30:00.123Z`)。
+//! 格式固定后字符串顺序即时间顺序，SQL 中可以直接按字符串排序和比较；显示时由前端转换为本地时区。
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDateTime, SecondsFormat, TimeZone, Utc};
 
-/// 获取当前本地时间的 RFC3339 格式字符串
-///
-/// 返回格式示例：`2025-12-11T18:30:00+08:00`
+/// 与 [`now_rfc3339`] 格式一致的 SQLite `strftime` 格式 (`%f` 为带毫秒的秒数)
+pub const SQLITE_RFC3339_UTC_FORMAT: &str = "%Y-%m-%dT%H:%M:%fZ";
+
+/// 获取当前 UTC 时间的 RFC3339 格式字符串
 ///
-/// # Returns
-/// 包含时区信息的 RFC3339 格式时间字符串
+/// 返回格式示例：`2025-12-11T10:30:00.123Z`
 pub fn now_rfc3339() -> String {
-    Local::now().to_rfc3339()
+    to_rfc3339_utc(&Utc::now())
+}
+
+/// 将任意时区的时间格式化为 UTC RFC3339 字符串
+pub fn to_rfc3339_utc<Tz: TimeZone>(at: &DateTime<Tz>) -> String {
+    at.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// 当前时间之前 `duration` 的 UTC RFC3339 字符串，用作 SQL 中的时间下限
+pub fn rfc3339_ago(duration: chrono::Duration) -> String {
+    to_rfc3339_utc(&(Utc::now() - duration))
 }
 
 /// 获取当前本地时间的 DateTime 对象
@@ -23,6 +34,34 @@ pub fn now() -> DateTime<Local> {
     Local::now()
 }
 
+/// 无时区信息的时间戳的解释方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NaiveZone {
+    /// SQLite `CURRENT_TIMESTAMP` / `datetime('now')` 写入的 UTC 时间
+    Utc,
+    /// `datetime('now', 'localtime')` 写入的本地时间
+    Local,
+}
+
+/// 将已存储的时间戳规范化为 UTC RFC3339 格式
+///
+/// 支持带时区偏移的 RFC3339 与 SQLite `YYYY-MM-DD HH:MM:SS[.SSS]` 格式，
+/// 后者按 `naive_zone` 解释。无法识别时返回 None
+pub fn normalize_timestamp(value: &str, naive_zone: NaiveZone) -> Option<String> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Some(to_rfc3339_utc(&at));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()?;
+    match naive_zone {
+        NaiveZone::Utc => Some(to_rfc3339_utc(&naive.and_utc())),
+        // 夏令时切换导致的重复时刻取较早者
+        NaiveZone::Local => naive.and_local_timezone(Local).earliest().map(|at| to_rfc3339_utc(&at)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -30,8 +69,9 @@ mod tests {
     #[test]
     fn test_now_rfc3339_format() {
         let time_str = now_rfc3339();
-        // 应该包含时区偏移（如 +08:00）
-        assert!(time_str.contains('+') || time_str.contains('-'));
+        // 统一为 UTC，毫秒精度
+        assert!(time_str.ends_with('Z'));
+        assert_eq!(time_str.len(), "2025-12-11T10:30:00.123Z".len());
         // 应该是有效的 RFC3339 格式
         assert!(chrono::DateTime::parse_from_rfc3339(&time_str).is_ok());
     }
@@ -45,4 +85,39 @@ mod tests {
         println!("Local timezone offset: {} seconds", utc_offset);
         assert!(utc_offset != 0 || cfg!(test)); // 大多数情况下不会是 UTC
     }
+
+    #[test]
+    fn test_normalize_timestamp() {
+        assert_eq!(
+            normalize_timestamp("2026-01-02T10:00:00+08:00", NaiveZone::Local).as_deref(),
+            Some("2026-01-02T02:00:00.000Z")
+        );
+        assert_eq!(
+            normalize_timestamp("2026-01-02T02:00:00.123456Z", NaiveZone::Utc).as_deref(),
+            Some("2026-01-02T02:00:00.123Z")
+        );
+        assert_eq!(
+            normalize_timestamp("2026-01-02 02:00:00", NaiveZone::Utc).as_deref(),
+            Some("2026-01-02T02:00:00.000Z")
+        );
+
+        let local = Local.with_ymd_and_hms(2026, 1, 2, 10, 0, 0).unwrap();
+        assert_eq!(
+            normalize_timestamp("2026-01-02 10:00:00", NaiveZone::Local),
+            Some(to_rfc3339_utc(&local))
+        );
+        assert_eq!(normalize_timestamp("yesterday", NaiveZone::Utc), None);
+    }
+
+    #[test]
+    fn test_string_order_matches_time_order() {
+        // 不同时区写入的时间规范化后，字符串顺序与时间顺序一致
+        let earlier = normalize_timestamp("2026-01-02T09:30:00+08:00", NaiveZone::Utc).unwrap();
+        let later = normalize_timestamp("2026-01-02T02:00:00Z", NaiveZone::Utc).unwrap();
+        assert!(earlier < later);
+        assert!("2026-01-02T09:30:00+08:00" > "2026-01-02T02:00:00Z");
+
+        let now = now_rfc3339();
+        assert!(rfc3339_ago(chrono::Duration::seconds(1)) < now);
+    }
 }
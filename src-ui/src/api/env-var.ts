@@ -4,7 +4,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import type { EnvironmentVariable } from '../types/tauri';
+import type { AnthropicEnvCheck, EnvironmentVariable } from '../types/tauri';
 
 /**
  * 列出所有环境变量
@@ -57,11 +57,11 @@ export async function applyConfigToEnv(configId: number): Promise<void> {
 }
 
 /**
- * 检查 Anthropic 环境变量是否已设置
- * @returns 是否已设置所有必需的 Anthropic 环境变量
+ * 检查 Anthropic 环境变量
+ * @returns 各变量的取值，以及代理运行时未指向代理的冲突项
  */
-export async function checkAnthropicEnv(): Promise<boolean> {
-  return invoke<boolean>('check_anthropic_env');
+export async function checkAnthropicEnv(): Promise<AnthropicEnvCheck> {
+  return invoke<AnthropicEnvCheck>('check_anthropic_env');
 }
 
 /**
@@ -3,7 +3,13 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import type { McpServerInfo, McpServerConfig, McpServerTemplate } from '../types/tauri';
+import type {
+  McpImportResult,
+  McpImportStrategy,
+  McpServerInfo,
+  McpServerConfig,
+  McpServerTemplate,
+} from '../types/tauri';
 
 /**
  * 列出所有 MCP 服务器
@@ -75,9 +81,14 @@ export async function testMcpServer(name: string): Promise<string> {
 /**
  * 批量导入 MCP 服务器
  * @param servers 服务器配置映射
+ * @param strategy 同名冲突处理策略(默认 skip)
+ * @returns 导入结果汇总
  */
-export async function importMcpServers(servers: Record<string, McpServerConfig>): Promise<void> {
-  return await invoke<void>('import_mcp_servers', { servers });
+export async function importMcpServers(
+  servers: Record<string, McpServerConfig>,
+  strategy?: McpImportStrategy
+): Promise<McpImportResult> {
+  return await invoke<McpImportResult>('import_mcp_servers', { servers, strategy: strategy ?? null });
 }
 
 /**
@@ -4,7 +4,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import type { RecommendedService } from '../types/tauri';
+import type { RecommendationRefreshResult, RecommendedService } from '../types/tauri';
 
 /**
  * 加载推荐服务列表
@@ -21,8 +21,13 @@ export async function loadRecommendedServices(
 
 /**
  * 强制刷新推荐服务列表
- * @returns 刷新后的推荐服务列表
+ * @param timeoutSecs 远程获取超时时间（秒，默认 10）
+ * @returns 刷新结果（数据源、数量、耗时与刷新后的推荐服务列表）
  */
-export async function refreshRecommendedServices(): Promise<RecommendedService[]> {
-  return invoke<RecommendedService[]>('refresh_recommended_services');
+export async function refreshRecommendedServices(
+  timeoutSecs?: number
+): Promise<RecommendationRefreshResult> {
+  return invoke<RecommendationRefreshResult>('refresh_recommended_services', {
+    timeoutSecs: timeoutSecs ?? null,
+  });
 }
@@ -553,8 +553,17 @@ export const McpServerManager: React.FC = () => {
     try {
       const text = await file.text();
       const configs = JSON.parse(text);
-      await mcpApi.importMcpServers(configs);
-      showSuccess('配置已导入');
+      const result = await mcpApi.importMcpServers(configs);
+      const summary = `新增 ${result.added}，覆盖 ${result.overwritten}，跳过 ${result.skipped}`;
+      if (result.errors > 0) {
+        const failed = result.entries
+          .filter((entry) => entry.status === 'error')
+          .map((entry) => `${entry.name}: ${entry.message ?? '未知错误'}`)
+          .join('; ');
+        setError(`配置导入完成(${summary})，${result.errors} 个失败: ${failed}`);
+      } else {
+        showSuccess(`配置已导入(${summary})`);
+      }
       await loadServers();
     } catch (err) {
       setError(err instanceof Error ? err.message : '导入配置失败');
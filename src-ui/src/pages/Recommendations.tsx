@@ -45,8 +45,11 @@ export const Recommendations: React.FC = () => {
     try {
       setLoading(true);
       setError(null);
-      const data = await recommendationApi.refreshRecommendedServices();
-      setServices(data);
+      const result = await recommendationApi.refreshRecommendedServices();
+      setServices(result.services);
+      if (result.error) {
+        console.warn(`Remote refresh failed, using ${result.source} data:`, result.error);
+      }
     } catch (err) {
       setError(err instanceof Error ? err.message : t('errors.refreshRecommendedServicesFailed'));
       console.error('Failed to refresh recommended services:', err);
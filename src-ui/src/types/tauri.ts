@@ -128,6 +128,8 @@ export interface ApiConfig {
   weight_score: number;
   /** 最后成功请求时间 */
   last_success_time: string | null;
+  /** 最后确认可用的时间（请求成功或健康检查成功） */
+  last_healthy_at: string | null;
   /** 连续失败次数 */
   consecutive_failures: number;
   /** 最后测试时间 */
@@ -426,6 +428,27 @@ export interface RecommendedService {
   loaded_at: string;
 }
 
+/**
+ * 刷新推荐服务时实际使用的数据源
+ */
+export type RefreshSource = 'remote' | 'local' | 'embedded' | 'cache';
+
+/**
+ * 刷新推荐服务的结果
+ */
+export interface RecommendationRefreshResult {
+  /** 实际使用的数据源 */
+  source: RefreshSource;
+  /** 加载的推荐服务数量 */
+  count: number;
+  /** 远程获取耗时(毫秒)，未配置远程 URL 时为 null */
+  latency_ms: number | null;
+  /** 远程获取失败的错误信息(成功时为 null) */
+  error: string | null;
+  /** 刷新后的推荐服务列表 */
+  services: RecommendedService[];
+}
+
 /**
  * 环境变量信息
  */
@@ -438,6 +461,54 @@ export interface EnvironmentVariable {
   is_anthropic: boolean;
 }
 
+/**
+ * Anthropic 环境变量的当前取值
+ */
+export interface AnthropicEnvValue {
+  /** 变量名 */
+  key: string;
+  /** 是否已设置(空字符串视为未设置) */
+  is_set: boolean;
+  /** 变量值(密钥类变量已脱敏) */
+  value: string | null;
+}
+
+/**
+ * 与代理配置冲突的 Anthropic 环境变量
+ */
+export interface AnthropicEnvConflict {
+  /** 变量名 */
+  key: string;
+  /** 期望值 */
+  expected: string | null;
+  /** 实际值(密钥类变量已脱敏) */
+  actual: string | null;
+  /** 说明 */
+  message: string;
+  /** 清除该变量的建议操作 */
+  clear_hint: string;
+}
+
+/**
+ * Anthropic 环境变量检查结果
+ */
+export interface AnthropicEnvCheck {
+  /** 是否已设置 Base URL 及任一凭据 */
+  is_configured: boolean;
+  /** 环境中的 ANTHROPIC_BASE_URL */
+  base_url: string | null;
+  /** 凭据来源变量名(ANTHROPIC_AUTH_TOKEN 优先于 ANTHROPIC_API_KEY) */
+  auth_token_source: string | null;
+  /** 代理是否正在运行 */
+  proxy_running: boolean;
+  /** 代理运行时期望的 ANTHROPIC_BASE_URL */
+  expected_base_url: string | null;
+  /** 各变量的当前取值 */
+  variables: AnthropicEnvValue[];
+  /** 冲突项 */
+  conflicts: AnthropicEnvConflict[];
+}
+
 /**
  * 余额查询状态
  */
@@ -781,6 +852,49 @@ export interface McpServerInfo {
   enabled: boolean;
 }
 
+/**
+ * 导入 MCP 服务器时的同名冲突处理策略
+ * - skip: 跳过已存在的同名服务器(默认)
+ * - rename: 以 name-2、name-3 ... 的形式重命名后导入
+ * - overwrite: 覆盖已存在的同名服务器
+ */
+export type McpImportStrategy = 'skip' | 'rename' | 'overwrite';
+
+/**
+ * 单个导入条目的处理状态
+ */
+export type McpImportStatus = 'added' | 'renamed' | 'overwritten' | 'skipped' | 'error';
+
+/**
+ * 单个导入条目
+ */
+export interface McpImportEntry {
+  /** 导入文件中的服务器名称 */
+  name: string;
+  /** 实际写入的名称(重命名时与 name 不同，跳过/出错时为 null) */
+  imported_as: string | null;
+  /** 处理状态 */
+  status: McpImportStatus;
+  /** 跳过或出错的原因 */
+  message: string | null;
+}
+
+/**
+ * 批量导入 MCP 服务器的结果汇总
+ */
+export interface McpImportResult {
+  /** 新增数量(含重命名) */
+  added: number;
+  /** 覆盖数量 */
+  overwritten: number;
+  /** 跳过数量 */
+  skipped: number;
+  /** 出错数量 */
+  errors: number;
+  /** 各条目的处理结果(按名称排序) */
+  entries: McpImportEntry[];
+}
+
 /**
  * MCP 服务器模板
  */